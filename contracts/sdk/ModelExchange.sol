@@ -0,0 +1,91 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity ^0.8.23;
+
+import {IpcEnvelope, ResultMsg, CallMsg, OutcomeType} from "../src/structs/CrossNet.sol";
+import {IPCAddress} from "../src/structs/Subnet.sol";
+import {IpcExchange} from "./IpcContract.sol";
+
+/// @notice Share trained machine learning models between subnets.
+///
+/// An instance has to be deployed in every subnet taking part in the exchange. A model exported
+/// from one instance is carried to the instance in the destination subnet by a `Call` cross-net
+/// message, together with its SHA-256 digest, which serves as the content identifier of the model.
+/// The receiving instance only stores the model if the bytes match the digest, so the message
+/// proves the integrity of the model it carries, and the model can then be passed to the
+/// machine learning actor of the destination subnet for predictions.
+contract ModelExchange is IpcExchange {
+    /// @dev Selector of the method invoked on the destination instance.
+    bytes4 public constant IMPORT_MODEL_SELECTOR = bytes4(keccak256("importModel(bytes32,bytes)"));
+
+    struct ImportedModel {
+        IPCAddress source;
+        bytes model;
+    }
+
+    /// @dev Imported models by their digest.
+    mapping(bytes32 => ImportedModel) private models;
+
+    event ModelExported(bytes32 indexed id, bytes32 indexed modelHash, IPCAddress to);
+    event ModelImported(bytes32 indexed modelHash, IPCAddress source);
+    event ModelTransferResult(bytes32 indexed id, bytes32 indexed modelHash, OutcomeType outcome);
+
+    error ModelHashMismatch();
+    error UnknownMethod();
+
+    constructor(address gatewayAddr_) IpcExchange(gatewayAddr_) {}
+
+    /// @notice Send a model to the instance of this contract at `to`.
+    /// Any value is forwarded with the cross-net message.
+    function exportModel(IPCAddress calldata to, bytes32 modelHash, bytes calldata model) external payable {
+        if (sha256(model) != modelHash) {
+            revert ModelHashMismatch();
+        }
+        CallMsg memory callMsg = CallMsg({
+            method: abi.encodePacked(IMPORT_MODEL_SELECTOR),
+            params: abi.encode(modelHash, model)
+        });
+        IpcEnvelope memory envelope = performIpcCall(to, callMsg, msg.value);
+
+        emit ModelExported(keccak256(abi.encode(envelope)), modelHash, to);
+    }
+
+    /// @notice Check whether a model with the given digest has been imported.
+    function hasModel(bytes32 modelHash) external view returns (bool) {
+        return models[modelHash].model.length > 0;
+    }
+
+    /// @notice Get an imported model and the address it was exported from.
+    function getModel(bytes32 modelHash) external view returns (IPCAddress memory source, bytes memory model) {
+        ImportedModel storage m = models[modelHash];
+        return (m.source, m.model);
+    }
+
+    function _handleIpcCall(
+        IpcEnvelope memory envelope,
+        CallMsg memory callMsg
+    ) internal override returns (bytes memory) {
+        if (bytes4(callMsg.method) != IMPORT_MODEL_SELECTOR) {
+            revert UnknownMethod();
+        }
+        (bytes32 modelHash, bytes memory model) = abi.decode(callMsg.params, (bytes32, bytes));
+        if (sha256(model) != modelHash) {
+            revert ModelHashMismatch();
+        }
+        models[modelHash] = ImportedModel({source: envelope.from, model: model});
+
+        emit ModelImported(modelHash, envelope.from);
+
+        return abi.encode(modelHash);
+    }
+
+    function _handleIpcResult(
+        IpcEnvelope storage original,
+        IpcEnvelope memory,
+        ResultMsg memory resultMsg
+    ) internal override {
+        CallMsg memory callMsg = abi.decode(original.message, (CallMsg));
+        (bytes32 modelHash, ) = abi.decode(callMsg.params, (bytes32, bytes));
+
+        emit ModelTransferResult(resultMsg.id, modelHash, resultMsg.outcome);
+    }
+}
@@ -8,3 +8,7 @@ bytes4 constant METHOD_SEND = bytes4(0);
 
 // The length of the public key that is associated with a validator.
 uint256 constant VALIDATOR_SECP256K1_PUBLIC_KEY_LENGTH = 65;
+
+// The number of blocks during which the validators of a subnet can vote on a parameter change proposal,
+// about a week of Filecoin epochs.
+uint256 constant PARAM_CHANGE_VOTING_PERIOD = 20160;
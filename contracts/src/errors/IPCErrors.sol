@@ -15,6 +15,7 @@ error CheckpointNotCreated();
 error BottomUpCheckpointAlreadySubmitted();
 error BatchNotCreated();
 error CollateralIsZero();
+error DoubleSignAlreadyReported();
 error EmptyAddress();
 error FailedAddIncompleteQuorum();
 error FailedAddSignatory();
@@ -28,17 +29,21 @@ error InvalidCheckpointSource();
 error InvalidBatchSource();
 error InvalidSubnetActor();
 error InvalidCollateral();
+error InvalidDoubleSignEvidence(InvalidDoubleSignEvidenceReason reason);
 error InvalidConfigurationNumber();
 error InvalidXnetMessage(InvalidXnetMessageReason reason);
 error InvalidMajorityPercentage();
+error InvalidParamChangeValue();
 error InvalidPowerScale();
 error InvalidRetentionHeight();
 error InvalidSignature();
 error InvalidSignatureErr(uint8);
 error InvalidSignatureLength();
+error InvalidSignatureBatch();
 error InvalidPublicKeyLength();
 error InvalidSubmissionPeriod();
 error InvalidSubnet();
+error InvalidVoteEncoding();
 error NoCollateralToWithdraw();
 error NoValidatorsInSubnet();
 error NotAllValidatorsHaveLeft();
@@ -59,6 +64,9 @@ error NotStakedBefore();
 error NotSystemActor();
 error NotValidator(address);
 error OldConfigurationNumber();
+error ParamChangeAlreadyVoted();
+error ParamChangeProposalClosed();
+error ParamChangeProposalNotFound();
 error PQDoesNotContainAddress();
 error PQEmpty();
 error ParentFinalityAlreadyCommitted();
@@ -88,6 +96,13 @@ enum InvalidXnetMessageReason {
     Kind
 }
 
+enum InvalidDoubleSignEvidenceReason {
+    Signature,
+    DifferentVotes,
+    SameBlock,
+    WrongChain
+}
+
 string constant ERR_PERMISSIONED_AND_BOOTSTRAPPED = "Method not allowed if permissioned is enabled and subnet bootstrapped";
 string constant ERR_VALIDATOR_JOINED = "Method not allowed if validator has already joined";
 string constant ERR_VALIDATOR_NOT_JOINED = "Method not allowed if validator has not joined";
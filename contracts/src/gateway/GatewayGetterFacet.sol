@@ -139,6 +139,22 @@ contract GatewayGetterFacet {
         return (s.postbox[id]);
     }
 
+    /// @notice Returns the messages waiting in the postbox to be propagated, and their identifiers.
+    /// @return ids The identifiers of the messages, to propagate them with.
+    /// @return msgs The messages, in the same order as their identifiers.
+    function listPostbox() external view returns (bytes32[] memory ids, IpcEnvelope[] memory msgs) {
+        ids = s.postboxKeys.values();
+        uint256 size = ids.length;
+        msgs = new IpcEnvelope[](size);
+        for (uint256 i; i < size; ) {
+            msgs[i] = s.postbox[ids[i]];
+            unchecked {
+                ++i;
+            }
+        }
+        return (ids, msgs);
+    }
+
     /// @notice Returns the majority percentage required for certain consensus or decision-making processes.
     function majorityPercentage() external view returns (uint64) {
         return s.majorityPercentage;
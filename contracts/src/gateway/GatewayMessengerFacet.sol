@@ -12,6 +12,7 @@ import {FilAddress} from "fevmate/utils/FilAddress.sol";
 import {SupplySourceHelper} from "../lib/SupplySourceHelper.sol";
 import {CrossMsgHelper} from "../lib/CrossMsgHelper.sol";
 import {FvmAddressHelper} from "../lib/FvmAddressHelper.sol";
+import {EnumerableSet} from "openzeppelin-contracts/utils/structs/EnumerableSet.sol";
 
 string constant ERR_GENERAL_CROSS_MSG_DISABLED = "Support for general-purpose cross-net messages is disabled";
 string constant ERR_MULTILEVEL_CROSS_MSG_DISABLED = "Support for multi-level cross-net messages is disabled";
@@ -19,6 +20,7 @@ string constant ERR_MULTILEVEL_CROSS_MSG_DISABLED = "Support for multi-level cro
 contract GatewayMessengerFacet is GatewayActorModifiers {
     using FilAddress for address payable;
     using SubnetIDHelper for SubnetID;
+    using EnumerableSet for EnumerableSet.Bytes32Set;
 
     /**
      * @dev Sends a general-purpose cross-message from the local subnet to the destination subnet.
@@ -91,6 +93,7 @@ contract GatewayMessengerFacet is GatewayActorModifiers {
         // anymore, we need to pull the data from the message to trigger the side-effects.
         uint256 v = crossMsg.value;
         delete s.postbox[msgCid];
+        s.postboxKeys.remove(msgCid);
 
         LibGateway.crossMsgSideEffects({v: v, shouldBurn: shouldBurn});
     }
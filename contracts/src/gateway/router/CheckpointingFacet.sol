@@ -12,6 +12,7 @@ import {Address} from "openzeppelin-contracts/utils/Address.sol";
 import {InvalidBatchSource, NotEnoughBalance, MaxMsgsPerBatchExceeded, InvalidCheckpointSource, CheckpointAlreadyExists} from "../../errors/IPCErrors.sol";
 import {NotRegisteredSubnet, SubnetNotActive, SubnetNotFound, InvalidSubnet, CheckpointNotCreated} from "../../errors/IPCErrors.sol";
 import {BatchNotCreated, InvalidBatchEpoch, BatchAlreadyExists, NotEnoughSubnetCircSupply, InvalidCheckpointEpoch} from "../../errors/IPCErrors.sol";
+import {InvalidSignatureBatch} from "../../errors/IPCErrors.sol";
 
 import {CrossMsgHelper} from "../../lib/CrossMsgHelper.sol";
 import {IpcEnvelope, SubnetID} from "../../structs/CrossNet.sol";
@@ -115,6 +116,45 @@ contract CheckpointingFacet is GatewayActorModifiers {
         });
     }
 
+    /// @notice adds the signatures of a checkpoint collected off-chain by one of the validators, in a single transaction
+    /// @dev Each signature is checked and accumulated like in `addCheckpointSignature`; the whole batch reverts if any of them is invalid.
+    /// @param height - the height of the block in the checkpoint
+    /// @param membershipProofs - the Merkle proofs that the signatories were in the membership at height `height`
+    /// @param weights - the weights of the signatories
+    /// @param signatures - the signatures of the checkpoint
+    function addCheckpointSignatures(
+        uint256 height,
+        bytes32[][] memory membershipProofs,
+        uint256[] memory weights,
+        bytes[] memory signatures
+    ) external {
+        uint256 n = signatures.length;
+        if (n == 0 || membershipProofs.length != n || weights.length != n) {
+            revert InvalidSignatureBatch();
+        }
+
+        LibQuorum.isHeightAlreadyProcessed(s.checkpointQuorumMap, height);
+
+        // slither-disable-next-line unused-return
+        (bool exists, ) = LibGateway.getBottomUpCheckpoint(height);
+        if (!exists) {
+            revert CheckpointNotCreated();
+        }
+
+        for (uint256 i; i < n; ) {
+            LibQuorum.addQuorumSignature({
+                self: s.checkpointQuorumMap,
+                height: height,
+                membershipProof: membershipProofs[i],
+                weight: weights[i],
+                signature: signatures[i]
+            });
+            unchecked {
+                ++i;
+            }
+        }
+    }
+
     /// @notice submit a batch of cross-net messages for execution.
     /// @param msgs The batch of bottom-up cross-network messages to be executed.
     function execBottomUpMsgs(IpcEnvelope[] calldata msgs, Subnet storage subnet) internal {
@@ -0,0 +1,124 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity ^0.8.23;
+
+import {InvalidVoteEncoding} from "../errors/IPCErrors.sol";
+
+/// @notice The fields of a CometBFT vote which tell whether two votes conflict.
+struct CanonicalVote {
+    /// @notice The signed message type: 1 for prevotes, 2 for precommits.
+    uint64 voteType;
+    int64 height;
+    int64 round;
+    /// @notice Hash of the encoded block ID the vote is for; nil votes have the hash of empty bytes.
+    bytes32 blockIdHash;
+    bytes32 chainIdHash;
+}
+
+/// @title Helper library for the votes CometBFT validators sign.
+/// @dev CometBFT signs the length delimited protobuf encoding of a `CanonicalVote`:
+///      1: type (varint), 2: height (sfixed64), 3: round (sfixed64), 4: block_id (message),
+///      5: timestamp (message), 6: chain_id (string). Fields with default values are omitted.
+library CometVoteHelper {
+    uint256 private constant WIRE_VARINT = 0;
+    uint256 private constant WIRE_FIXED64 = 1;
+    uint256 private constant WIRE_LEN = 2;
+
+    /// @notice Decodes the sign bytes of a vote.
+    function decode(bytes memory signBytes) internal pure returns (CanonicalVote memory vote) {
+        (uint256 length, uint256 pos) = readVarint(signBytes, 0);
+        if (pos + length != signBytes.length) {
+            revert InvalidVoteEncoding();
+        }
+
+        vote.blockIdHash = keccak256("");
+        vote.chainIdHash = keccak256("");
+
+        while (pos < signBytes.length) {
+            uint256 key;
+            (key, pos) = readVarint(signBytes, pos);
+            uint256 field = key >> 3;
+            uint256 wireType = key & 7;
+
+            if (wireType == WIRE_VARINT) {
+                uint256 value;
+                (value, pos) = readVarint(signBytes, pos);
+                if (field == 1) {
+                    vote.voteType = uint64(value);
+                }
+            } else if (wireType == WIRE_FIXED64) {
+                if (pos + 8 > signBytes.length) {
+                    revert InvalidVoteEncoding();
+                }
+                uint64 value = readFixed64(signBytes, pos);
+                pos += 8;
+                if (field == 2) {
+                    vote.height = int64(value);
+                } else if (field == 3) {
+                    vote.round = int64(value);
+                }
+            } else if (wireType == WIRE_LEN) {
+                uint256 size;
+                (size, pos) = readVarint(signBytes, pos);
+                if (pos + size > signBytes.length) {
+                    revert InvalidVoteEncoding();
+                }
+                if (field == 4) {
+                    vote.blockIdHash = hashSlice(signBytes, pos, size);
+                } else if (field == 6) {
+                    vote.chainIdHash = hashSlice(signBytes, pos, size);
+                }
+                pos += size;
+            } else {
+                revert InvalidVoteEncoding();
+            }
+        }
+    }
+
+    /// @notice Checks that a CometBFT secp256k1 signature of the sign bytes was made by the signer.
+    /// @dev CometBFT signs the SHA256 hash of the message, and leaves the recovery ID out of the
+    ///      64 byte signature, so both are tried.
+    function isSignedBy(bytes memory signBytes, bytes memory signature, address signer) internal pure returns (bool) {
+        if (signature.length != 64 || signer == address(0)) {
+            return false;
+        }
+
+        bytes32 digest = sha256(signBytes);
+        bytes32 r;
+        bytes32 s;
+        // slither-disable-next-line assembly
+        assembly {
+            r := mload(add(signature, 32))
+            s := mload(add(signature, 64))
+        }
+
+        return ecrecover(digest, 27, r, s) == signer || ecrecover(digest, 28, r, s) == signer;
+    }
+
+    function readVarint(bytes memory data, uint256 pos) private pure returns (uint256 value, uint256) {
+        for (uint256 shift = 0; shift < 64; shift += 7) {
+            if (pos >= data.length) {
+                revert InvalidVoteEncoding();
+            }
+            uint8 b = uint8(data[pos]);
+            pos++;
+            value |= uint256(b & 0x7f) << shift;
+            if ((b & 0x80) == 0) {
+                return (value, pos);
+            }
+        }
+        revert InvalidVoteEncoding();
+    }
+
+    function readFixed64(bytes memory data, uint256 pos) private pure returns (uint64 value) {
+        for (uint256 i = 0; i < 8; i++) {
+            value |= uint64(uint8(data[pos + i])) << uint64(8 * i);
+        }
+    }
+
+    function hashSlice(bytes memory data, uint256 pos, uint256 size) private pure returns (bytes32 hash) {
+        // slither-disable-next-line assembly
+        assembly {
+            hash := keccak256(add(add(data, 32), pos), size)
+        }
+    }
+}
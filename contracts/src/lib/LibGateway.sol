@@ -13,6 +13,7 @@ import {CrossMsgHelper} from "../lib/CrossMsgHelper.sol";
 import {FilAddress} from "fevmate/utils/FilAddress.sol";
 import {SubnetIDHelper} from "../lib/SubnetIDHelper.sol";
 import {SupplySourceHelper} from "../lib/SupplySourceHelper.sol";
+import {EnumerableSet} from "openzeppelin-contracts/utils/structs/EnumerableSet.sol";
 
 library LibGateway {
     using SubnetIDHelper for SubnetID;
@@ -21,6 +22,7 @@ library LibGateway {
     using SubnetIDHelper for SubnetID;
     using FilAddress for address payable;
     using SupplySourceHelper for SupplySource;
+    using EnumerableSet for EnumerableSet.Bytes32Set;
 
     event MembershipUpdated(Membership);
     /// @dev subnet refers to the next "down" subnet that the `envelope.message.to` should be forwarded to.
@@ -406,6 +408,7 @@ library LibGateway {
         if (!crossMsg.to.subnetId.equals(s.networkName)) {
             bytes32 cid = crossMsg.toHash();
             s.postbox[cid] = crossMsg;
+            s.postboxKeys.add(cid);
             return;
         }
 
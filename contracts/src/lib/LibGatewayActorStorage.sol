@@ -73,6 +73,8 @@ struct GatewayActorStorage {
     mapping(uint256 => BottomUpMsgBatch) bottomUpMsgBatches;
     /// @notice Keys of the registered subnets. Useful to iterate through them
     EnumerableSet.Bytes32Set subnetKeys;
+    /// @notice Keys of the messages in the postbox, to list the messages waiting to be propagated
+    EnumerableSet.Bytes32Set postboxKeys;
 }
 
 library LibGatewayActorStorage {
@@ -4,7 +4,7 @@ pragma solidity ^0.8.23;
 import {ConsensusType} from "../enums/ConsensusType.sol";
 import {NotGateway, SubnetAlreadyKilled} from "../errors/IPCErrors.sol";
 import {BottomUpCheckpoint, BottomUpMsgBatchInfo} from "../structs/CrossNet.sol";
-import {SubnetID, ValidatorSet, StakingChangeLog, StakingReleaseQueue, SupplySource, Validator, PermissionMode} from "../structs/Subnet.sol";
+import {SubnetID, ValidatorSet, StakingChangeLog, StakingReleaseQueue, SupplySource, Validator, PermissionMode, ParamChangeProposal} from "../structs/Subnet.sol";
 import {EnumerableSet} from "openzeppelin-contracts/utils/structs/EnumerableSet.sol";
 
     struct SubnetActorStorage {
@@ -61,6 +61,16 @@ import {EnumerableSet} from "openzeppelin-contracts/utils/structs/EnumerableSet.
         mapping(address => uint256) genesisBalance;
         /// @notice genesis balance addresses
         address[] genesisBalanceKeys;
+        /// @notice the double signs reported so far, keyed by the hash of the validator, vote type, height and round
+        mapping(bytes32 => bool) reportedDoubleSigns;
+        /// @notice the proposals to change the parameters of the subnet, indexed by their ID
+        ParamChangeProposal[] paramChangeProposals;
+        /// @notice the validators who voted for each parameter change proposal
+        mapping(uint256 => mapping(address => bool)) paramChangeVotes;
+        /// @notice the validators who voted for each parameter change proposal, in the order of their votes
+        mapping(uint256 => address[]) paramChangeVoters;
+        /// @notice the EVM chain ID of the subnet, set by a parameter change proposal; zero until then
+        uint64 chainId;
     }
 
 library LibSubnetActorStorage {
@@ -177,3 +177,39 @@ enum SupplyKind {
     Native,
     ERC20
 }
+
+/// @notice Two conflicting CometBFT votes signed by a validator of the subnet for the same height and round.
+struct DoubleSignEvidence {
+    /// @notice The address of the validator who signed both votes.
+    address validator;
+    /// @notice The length delimited protobuf encoding of the canonical vote, as signed by CometBFT.
+    bytes voteA;
+    /// @notice The 64 byte secp256k1 signature of the SHA256 hash of `voteA`.
+    bytes signatureA;
+    bytes voteB;
+    bytes signatureB;
+}
+
+/// @notice The parameters of a subnet which its validators can change with a governance proposal.
+/// @dev The checkpoint period and the batch size are not included, because the gateway of the
+///      child subnet keeps the values it was created with, so changing them here would get the
+///      checkpoints of the child rejected.
+enum SubnetParam {
+    MinValidators,
+    MajorityPercentage,
+    /// @notice The EVM chain ID of the subnet, whose decimal form is the chain ID its CometBFT
+    ///         validators sign votes for.
+    ChainId
+}
+
+/// @notice A proposal to change a parameter of the subnet, voted on by the active validators.
+struct ParamChangeProposal {
+    SubnetParam param;
+    uint256 value;
+    address proposer;
+    /// @notice The last block at which the proposal accepts votes.
+    uint256 deadline;
+    /// @notice The current power of the voters who are still active validators, as of the last vote.
+    uint256 votes;
+    bool executed;
+}
@@ -4,7 +4,7 @@ pragma solidity ^0.8.23;
 import {ConsensusType} from "../enums/ConsensusType.sol";
 import {BottomUpCheckpoint, IpcEnvelope} from "../structs/CrossNet.sol";
 import {SubnetID, SupplySource} from "../structs/Subnet.sol";
-import {SubnetID, ValidatorInfo, Validator, PermissionMode} from "../structs/Subnet.sol";
+import {SubnetID, ValidatorInfo, Validator, PermissionMode, ParamChangeProposal} from "../structs/Subnet.sol";
 import {SubnetActorStorage} from "../lib/LibSubnetActorStorage.sol";
 import {SubnetIDHelper} from "../lib/SubnetIDHelper.sol";
 import {Address} from "openzeppelin-contracts/utils/Address.sol";
@@ -44,6 +44,11 @@ contract SubnetActorGetterFacet {
         return s.majorityPercentage;
     }
 
+    /// @notice Returns the EVM chain ID of the subnet, or zero if the validators haven't set it yet.
+    function chainId() external view returns (uint64) {
+        return s.chainId;
+    }
+
     /// @notice Fetches the limit on the number of active validators.
     function activeValidatorsLimit() external view returns (uint16) {
         return s.validatorSet.activeLimit;
@@ -216,4 +221,16 @@ contract SubnetActorGetterFacet {
     function supplySource() external view returns (SupplySource memory supply) {
         return s.supplySource;
     }
+
+    /// @notice Returns the parameter change proposals, indexed by their ID.
+    function paramChangeProposals() external view returns (ParamChangeProposal[] memory) {
+        return s.paramChangeProposals;
+    }
+
+    /// @notice Checks whether a validator voted for a parameter change proposal.
+    /// @param id The ID of the proposal.
+    /// @param validator The address of the validator.
+    function hasVotedParamChange(uint256 id, address validator) external view returns (bool) {
+        return s.paramChangeVotes[id][validator];
+    }
 }
@@ -1,17 +1,19 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 pragma solidity ^0.8.23;
 
-import {VALIDATOR_SECP256K1_PUBLIC_KEY_LENGTH} from "../constants/Constants.sol";
+import {VALIDATOR_SECP256K1_PUBLIC_KEY_LENGTH, PARAM_CHANGE_VOTING_PERIOD} from "../constants/Constants.sol";
 import {ERR_VALIDATOR_JOINED, ERR_VALIDATOR_NOT_JOINED} from "../errors/IPCErrors.sol";
-import {InvalidFederationPayload, SubnetAlreadyBootstrapped, NotEnoughFunds, CollateralIsZero, CannotReleaseZero, NotOwnerOfPublicKey, EmptyAddress, NotEnoughBalance, NotEnoughCollateral, NotValidator, NotAllValidatorsHaveLeft, InvalidPublicKeyLength, MethodNotAllowed, SubnetNotBootstrapped} from "../errors/IPCErrors.sol";
+import {InvalidFederationPayload, SubnetAlreadyBootstrapped, NotEnoughFunds, CollateralIsZero, CannotReleaseZero, NotOwnerOfPublicKey, EmptyAddress, NotEnoughBalance, NotEnoughCollateral, NotValidator, NotAllValidatorsHaveLeft, InvalidPublicKeyLength, MethodNotAllowed, SubnetNotBootstrapped, InvalidDoubleSignEvidence, InvalidDoubleSignEvidenceReason, DoubleSignAlreadyReported, InvalidParamChangeValue, InvalidMajorityPercentage, ParamChangeAlreadyVoted, ParamChangeProposalClosed, ParamChangeProposalNotFound} from "../errors/IPCErrors.sol";
 import {IGateway} from "../interfaces/IGateway.sol";
-import {Validator, ValidatorSet} from "../structs/Subnet.sol";
+import {Validator, ValidatorSet, DoubleSignEvidence, ParamChangeProposal, SubnetParam} from "../structs/Subnet.sol";
+import {CanonicalVote, CometVoteHelper} from "../lib/CometVoteHelper.sol";
 import {LibDiamond} from "../lib/LibDiamond.sol";
 import {ReentrancyGuard} from "../lib/LibReentrancyGuard.sol";
 import {SubnetActorModifiers} from "../lib/LibSubnetActorStorage.sol";
 import {LibValidatorSet, LibStaking} from "../lib/LibStaking.sol";
 import {EnumerableSet} from "openzeppelin-contracts/utils/structs/EnumerableSet.sol";
 import {Address} from "openzeppelin-contracts/utils/Address.sol";
+import {Strings} from "openzeppelin-contracts/utils/Strings.sol";
 import {LibSubnetActor} from "../lib/LibSubnetActor.sol";
 import {Pausable} from "../lib/LibPausable.sol";
 
@@ -20,6 +22,11 @@ contract SubnetActorManagerFacet is SubnetActorModifiers, ReentrancyGuard, Pausa
     using LibValidatorSet for ValidatorSet;
     using Address for address payable;
 
+    event DoubleSignReported(address indexed validator, int64 height, int64 round, address reporter);
+    event ParamChangeProposed(uint256 indexed id, SubnetParam param, uint256 value, address proposer);
+    event ParamChangeVoted(uint256 indexed id, address validator, uint256 power);
+    event ParamChangeExecuted(uint256 indexed id, SubnetParam param, uint256 value);
+
     /// @notice method to add some initial balance into a subnet that hasn't yet bootstrapped.
     /// @dev This balance is added to user addresses in genesis, and becomes part of the genesis
     /// circulating supply.
@@ -268,4 +275,152 @@ contract SubnetActorManagerFacet is SubnetActorModifiers, ReentrancyGuard, Pausa
         // slither-disable-next-line unused-return
         s.bootstrapOwners.add(msg.sender);
     }
+
+    /// @notice Report a validator who signed two conflicting votes for the same height and round,
+    ///         as packaged from the duplicate vote evidence of CometBFT.
+    /// @dev The offence is recorded so that it can't be reported twice, and announced with
+    ///      `DoubleSignReported` for the penalties to be decided on. The votes have to be for the
+    ///      chain ID of this subnet, so evidence can't be replayed from another chain the validator
+    ///      signs for with the same key; until the validators set it, no evidence is accepted.
+    /// @param evidence The conflicting votes and their signatures.
+    function reportDoubleSign(DoubleSignEvidence calldata evidence) external whenNotPaused notKilled {
+        if (!LibStaking.isValidator(evidence.validator)) {
+            revert NotValidator(evidence.validator);
+        }
+
+        CanonicalVote memory a = CometVoteHelper.decode(evidence.voteA);
+        CanonicalVote memory b = CometVoteHelper.decode(evidence.voteB);
+
+        if (a.voteType != b.voteType || a.height != b.height || a.round != b.round || a.chainIdHash != b.chainIdHash) {
+            revert InvalidDoubleSignEvidence(InvalidDoubleSignEvidenceReason.DifferentVotes);
+        }
+        if (a.blockIdHash == b.blockIdHash) {
+            revert InvalidDoubleSignEvidence(InvalidDoubleSignEvidenceReason.SameBlock);
+        }
+        if (s.chainId == 0 || a.chainIdHash != keccak256(bytes(Strings.toString(s.chainId)))) {
+            revert InvalidDoubleSignEvidence(InvalidDoubleSignEvidenceReason.WrongChain);
+        }
+        if (
+            !CometVoteHelper.isSignedBy(evidence.voteA, evidence.signatureA, evidence.validator) ||
+            !CometVoteHelper.isSignedBy(evidence.voteB, evidence.signatureB, evidence.validator)
+        ) {
+            revert InvalidDoubleSignEvidence(InvalidDoubleSignEvidenceReason.Signature);
+        }
+
+        bytes32 offence = keccak256(abi.encode(evidence.validator, a.voteType, a.height, a.round));
+        if (s.reportedDoubleSigns[offence]) {
+            revert DoubleSignAlreadyReported();
+        }
+        s.reportedDoubleSigns[offence] = true;
+
+        emit DoubleSignReported(evidence.validator, a.height, a.round, msg.sender);
+    }
+
+    /// @notice Propose to change a parameter of the subnet, which is applied once active validators
+    ///         holding the majority percentage of the active power have voted for it.
+    /// @dev The proposer must be an active validator, and its vote is counted right away.
+    /// @param param The parameter to change.
+    /// @param value The new value of the parameter.
+    /// @return id The ID of the proposal, to vote on it.
+    function proposeParamChange(
+        SubnetParam param,
+        uint256 value
+    ) external whenNotPaused notKilled returns (uint256 id) {
+        if (!LibStaking.isActiveValidator(msg.sender)) {
+            revert NotValidator(msg.sender);
+        }
+        validateParamChange(param, value);
+
+        id = s.paramChangeProposals.length;
+        s.paramChangeProposals.push(
+            ParamChangeProposal({
+                param: param,
+                value: value,
+                proposer: msg.sender,
+                deadline: block.number + PARAM_CHANGE_VOTING_PERIOD,
+                votes: 0,
+                executed: false
+            })
+        );
+
+        emit ParamChangeProposed(id, param, value, msg.sender);
+
+        castParamChangeVote(id);
+    }
+
+    /// @notice Vote for a parameter change proposal as an active validator, with the current power
+    ///         of the validator.
+    /// @dev The change is applied by the vote which brings the proposal to the majority.
+    /// @param id The ID of the proposal.
+    function voteParamChange(uint256 id) external whenNotPaused notKilled {
+        if (id >= s.paramChangeProposals.length) {
+            revert ParamChangeProposalNotFound();
+        }
+        if (!LibStaking.isActiveValidator(msg.sender)) {
+            revert NotValidator(msg.sender);
+        }
+
+        ParamChangeProposal storage proposal = s.paramChangeProposals[id];
+        if (proposal.executed || block.number > proposal.deadline) {
+            revert ParamChangeProposalClosed();
+        }
+        if (s.paramChangeVotes[id][msg.sender]) {
+            revert ParamChangeAlreadyVoted();
+        }
+
+        castParamChangeVote(id);
+    }
+
+    function castParamChangeVote(uint256 id) internal {
+        ParamChangeProposal storage proposal = s.paramChangeProposals[id];
+
+        s.paramChangeVotes[id][msg.sender] = true;
+        s.paramChangeVoters[id].push(msg.sender);
+
+        emit ParamChangeVoted(id, msg.sender, LibStaking.getPower(msg.sender));
+
+        // Recount the votes with the current power of the voters, so that validators who left or
+        // lost power since they voted don't count towards the majority.
+        address[] storage voters = s.paramChangeVoters[id];
+        uint256 length = voters.length;
+        uint256 votes;
+        for (uint256 i; i < length; ) {
+            if (LibStaking.isActiveValidator(voters[i])) {
+                votes += LibStaking.getPower(voters[i]);
+            }
+            unchecked {
+                ++i;
+            }
+        }
+        proposal.votes = votes;
+
+        uint256 threshold = (s.validatorSet.getTotalActivePower() * s.majorityPercentage) / 100;
+        if (votes < threshold) {
+            return;
+        }
+
+        proposal.executed = true;
+
+        if (proposal.param == SubnetParam.MinValidators) {
+            s.minValidators = uint64(proposal.value);
+        } else if (proposal.param == SubnetParam.MajorityPercentage) {
+            s.majorityPercentage = uint8(proposal.value);
+        } else {
+            s.chainId = uint64(proposal.value);
+        }
+
+        emit ParamChangeExecuted(id, proposal.param, proposal.value);
+    }
+
+    function validateParamChange(SubnetParam param, uint256 value) internal pure {
+        if (param == SubnetParam.MajorityPercentage) {
+            if (value < 51 || value > 100) {
+                revert InvalidMajorityPercentage();
+            }
+            return;
+        }
+        if (value == 0 || value > type(uint64).max) {
+            revert InvalidParamChangeValue();
+        }
+    }
 }
@@ -1427,6 +1427,78 @@ contract GatewayActorDiamondTest is Test, IntegrationTestBase {
         vm.stopPrank();
     }
 
+    function testGatewayDiamond_addCheckpointSignatures_batch() public {
+        (uint256[] memory privKeys, address[] memory addrs, uint256[] memory weights) = TestUtils.getFourValidators(vm);
+
+        (bytes32 membershipRoot, bytes32[][] memory membershipProofs) = MerkleTreeHelper
+            .createMerkleProofsForValidators(addrs, weights);
+
+        BottomUpCheckpoint memory checkpoint = BottomUpCheckpoint({
+            subnetID: gatewayDiamond.getter().getNetworkName(),
+            blockHeight: gatewayDiamond.getter().bottomUpCheckPeriod(),
+            blockHash: keccak256("block"),
+            nextConfigurationNumber: 1,
+            msgs: new IpcEnvelope[](0)
+        });
+
+        // create a checkpoint
+        vm.startPrank(FilAddress.SYSTEM_ACTOR);
+        gatewayDiamond.checkpointer().createBottomUpCheckpoint(
+            checkpoint,
+            membershipRoot,
+            weights[0] + weights[1] + weights[2]
+        );
+        vm.stopPrank();
+
+        // the signatures of three validators, collected off-chain
+        bytes32[][] memory proofs = new bytes32[][](3);
+        uint256[] memory batchWeights = new uint256[](3);
+        bytes[] memory signatures = new bytes[](3);
+
+        for (uint64 i = 0; i < 3; i++) {
+            (uint8 v, bytes32 r, bytes32 s) = vm.sign(privKeys[i], keccak256(abi.encode(checkpoint)));
+            proofs[i] = membershipProofs[i];
+            batchWeights[i] = weights[i];
+            signatures[i] = abi.encodePacked(r, s, v);
+        }
+
+        // mismatched batches are rejected
+        vm.expectRevert(InvalidSignatureBatch.selector);
+        gatewayDiamond.checkpointer().addCheckpointSignatures(
+            checkpoint.blockHeight,
+            proofs,
+            weights,
+            signatures
+        );
+
+        // anyone can submit them, in a single transaction
+        vm.prank(vm.addr(privKeys[3]));
+        gatewayDiamond.checkpointer().addCheckpointSignatures(
+            checkpoint.blockHeight,
+            proofs,
+            batchWeights,
+            signatures
+        );
+
+        QuorumInfo memory info = gatewayDiamond.getter().getCheckpointInfo(checkpoint.blockHeight);
+        require(info.reached, "not reached");
+        require(gatewayDiamond.getter().getIncompleteCheckpointHeights().length == 0, "unexpected size");
+
+        (, , address[] memory signatories, ) = gatewayDiamond.getter().getCheckpointSignatureBundle(
+            checkpoint.blockHeight
+        );
+        require(signatories.length == 3, "unexpected signatories length");
+
+        // the same signatures can't be added twice
+        vm.expectRevert(SignatureReplay.selector);
+        gatewayDiamond.checkpointer().addCheckpointSignatures(
+            checkpoint.blockHeight,
+            proofs,
+            batchWeights,
+            signatures
+        );
+    }
+
     function testGatewayDiamond_addCheckpointSignature_notAuthorized() public {
         (uint256[] memory privKeys, address[] memory addrs, uint256[] memory weights) = TestUtils.getFourValidators(vm);
 
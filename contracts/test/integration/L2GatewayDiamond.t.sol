@@ -94,10 +94,17 @@ contract L2GatewayActorDiamondTest is Test, L2GatewayActorDiamond {
 
         bytes32 postboxId = setupWhiteListMethod(caller);
 
+        (bytes32[] memory ids, IpcEnvelope[] memory msgs) = gatewayDiamond.getter().listPostbox();
+        require(ids.length == 1 && ids[0] == postboxId, "unexpected postbox ids");
+        require(msgs.length == 1 && msgs[0].toHash() == postboxId, "unexpected postbox msgs");
+
         vm.prank(caller);
         vm.expectCall(caller, 0, EMPTY_BYTES, 0);
         gatewayDiamond.messenger().propagate{value: 0}(postboxId);
         require(caller.balance == 0, "unexpected balance");
+
+        (ids, ) = gatewayDiamond.getter().listPostbox();
+        require(ids.length == 0, "postbox not emptied");
     }
 
     function setupWhiteListMethod(address caller) internal returns (bytes32) {
@@ -8,11 +8,11 @@ import {TestUtils} from "../helpers/TestUtils.sol";
 import {SelectorLibrary} from "../helpers/SelectorLibrary.sol";
 import {NumberContractFacetSeven} from "../helpers/NumberContractFacetSeven.sol";
 import {NumberContractFacetEight} from "../helpers/NumberContractFacetEight.sol";
-import {METHOD_SEND} from "../../src/constants/Constants.sol";
+import {METHOD_SEND, PARAM_CHANGE_VOTING_PERIOD} from "../../src/constants/Constants.sol";
 import {ConsensusType} from "../../src/enums/ConsensusType.sol";
 import {BottomUpMsgBatch, IpcEnvelope, BottomUpCheckpoint, MAX_MSGS_PER_BATCH} from "../../src/structs/CrossNet.sol";
 import {FvmAddress} from "../../src/structs/FvmAddress.sol";
-import {SubnetID, PermissionMode, IPCAddress, Subnet, SupplySource, ValidatorInfo} from "../../src/structs/Subnet.sol";
+import {SubnetID, PermissionMode, IPCAddress, Subnet, SupplySource, ValidatorInfo, DoubleSignEvidence, ParamChangeProposal, SubnetParam} from "../../src/structs/Subnet.sol";
 import {IERC165} from "../../src/interfaces/IERC165.sol";
 import {IGateway} from "../../src/interfaces/IGateway.sol";
 import {IDiamond} from "../../src/interfaces/IDiamond.sol";
@@ -35,6 +35,7 @@ import {LibStaking} from "../../src/lib/LibStaking.sol";
 import {LibDiamond} from "../../src/lib/LibDiamond.sol";
 import {Pausable} from "../../src/lib/LibPausable.sol";
 import {SupplySourceHelper} from "../../src/lib/SupplySourceHelper.sol";
+import {Strings} from "openzeppelin-contracts/utils/Strings.sol";
 
 import {IntegrationTestBase} from "../IntegrationTestBase.sol";
 
@@ -50,6 +51,9 @@ contract SubnetActorDiamondTest is Test, IntegrationTestBase {
 
     address gatewayAddress;
 
+    /// The EVM chain ID of the subnet the CometBFT votes in the tests are signed for.
+    uint64 constant COMET_CHAIN_ID = 1234;
+
     function setUp() public override {
         super.setUp();
 
@@ -491,6 +495,226 @@ contract SubnetActorDiamondTest is Test, IntegrationTestBase {
         require(info.totalCollateral == 3);
     }
 
+    function testSubnetActorDiamond_ReportDoubleSign() public {
+        (address validator, uint256 privKey, bytes memory publicKey) = TestUtils.newValidator(100);
+        (, uint256 otherKey, ) = TestUtils.newValidator(101);
+
+        vm.deal(validator, DEFAULT_MIN_VALIDATOR_STAKE);
+        vm.prank(validator);
+        saDiamond.manager().join{value: DEFAULT_MIN_VALIDATOR_STAKE}(publicKey);
+
+        bytes memory voteA = cometVote(2, 100, 0, bytes32(uint256(1)));
+        bytes memory voteB = cometVote(2, 100, 0, bytes32(uint256(2)));
+
+        // No evidence is accepted until the validators set the chain ID of the subnet.
+        vm.expectRevert(
+            abi.encodeWithSelector(InvalidDoubleSignEvidence.selector, InvalidDoubleSignEvidenceReason.WrongChain)
+        );
+        saDiamond.manager().reportDoubleSign(doubleSign(validator, privKey, voteA, privKey, voteB));
+
+        vm.prank(validator);
+        saDiamond.manager().proposeParamChange(SubnetParam.ChainId, COMET_CHAIN_ID);
+        require(saDiamond.getter().chainId() == COMET_CHAIN_ID, "chain ID not set");
+
+        // Votes signed for another chain are not an offence on this one.
+        vm.expectRevert(
+            abi.encodeWithSelector(InvalidDoubleSignEvidence.selector, InvalidDoubleSignEvidenceReason.WrongChain)
+        );
+        saDiamond.manager().reportDoubleSign(
+            doubleSign(
+                validator,
+                privKey,
+                cometVote("other-chain", 2, 100, 0, bytes32(uint256(1))),
+                privKey,
+                cometVote("other-chain", 2, 100, 0, bytes32(uint256(2)))
+            )
+        );
+
+        // The same vote twice is not an offence.
+        vm.expectRevert(
+            abi.encodeWithSelector(InvalidDoubleSignEvidence.selector, InvalidDoubleSignEvidenceReason.SameBlock)
+        );
+        saDiamond.manager().reportDoubleSign(doubleSign(validator, privKey, voteA, privKey, voteA));
+
+        // Neither is voting at different heights.
+        vm.expectRevert(
+            abi.encodeWithSelector(InvalidDoubleSignEvidence.selector, InvalidDoubleSignEvidenceReason.DifferentVotes)
+        );
+        saDiamond.manager().reportDoubleSign(
+            doubleSign(validator, privKey, voteA, privKey, cometVote(2, 101, 0, bytes32(uint256(2))))
+        );
+
+        // Both votes have to be signed by the validator.
+        vm.expectRevert(
+            abi.encodeWithSelector(InvalidDoubleSignEvidence.selector, InvalidDoubleSignEvidenceReason.Signature)
+        );
+        saDiamond.manager().reportDoubleSign(doubleSign(validator, privKey, voteA, otherKey, voteB));
+
+        vm.expectRevert(abi.encodeWithSelector(NotValidator.selector, vm.addr(otherKey)));
+        saDiamond.manager().reportDoubleSign(doubleSign(vm.addr(otherKey), otherKey, voteA, otherKey, voteB));
+
+        saDiamond.manager().reportDoubleSign(doubleSign(validator, privKey, voteA, privKey, voteB));
+
+        vm.expectRevert(DoubleSignAlreadyReported.selector);
+        saDiamond.manager().reportDoubleSign(doubleSign(validator, privKey, voteB, privKey, voteA));
+
+        // Truncated votes are rejected.
+        vm.expectRevert(InvalidVoteEncoding.selector);
+        saDiamond.manager().reportDoubleSign(
+            doubleSign(validator, privKey, voteA, privKey, abi.encodePacked(uint8(10), bytes1(0x08)))
+        );
+    }
+
+    function testSubnetActorDiamond_ReportDoubleSign_ForeignChain() public {
+        (address validator, uint256 privKey, bytes memory publicKey) = TestUtils.newValidator(100);
+
+        vm.deal(validator, DEFAULT_MIN_VALIDATOR_STAKE);
+        vm.prank(validator);
+        saDiamond.manager().join{value: DEFAULT_MIN_VALIDATOR_STAKE}(publicKey);
+
+        vm.prank(validator);
+        saDiamond.manager().proposeParamChange(SubnetParam.ChainId, COMET_CHAIN_ID);
+
+        // Valid evidence of double signing on another subnet, e.g. its parent or a sibling.
+        string memory foreignChainId = Strings.toString(COMET_CHAIN_ID + 1);
+        DoubleSignEvidence memory evidence = doubleSign(
+            validator,
+            privKey,
+            cometVote(foreignChainId, 2, 100, 0, bytes32(uint256(1))),
+            privKey,
+            cometVote(foreignChainId, 2, 100, 0, bytes32(uint256(2)))
+        );
+
+        vm.expectRevert(
+            abi.encodeWithSelector(InvalidDoubleSignEvidence.selector, InvalidDoubleSignEvidenceReason.WrongChain)
+        );
+        saDiamond.manager().reportDoubleSign(evidence);
+
+        // Once the subnet is on that chain ID, the same evidence is an offence.
+        vm.prank(validator);
+        saDiamond.manager().proposeParamChange(SubnetParam.ChainId, COMET_CHAIN_ID + 1);
+        saDiamond.manager().reportDoubleSign(evidence);
+    }
+
+    function testSubnetActorDiamond_ReportDoubleSign_ValidEvidence() public {
+        (address validator, uint256 privKey, bytes memory publicKey) = TestUtils.newValidator(100);
+        address reporter = vm.addr(12345);
+
+        vm.deal(validator, DEFAULT_MIN_VALIDATOR_STAKE);
+        vm.prank(validator);
+        saDiamond.manager().join{value: DEFAULT_MIN_VALIDATOR_STAKE}(publicKey);
+
+        vm.prank(validator);
+        saDiamond.manager().proposeParamChange(SubnetParam.ChainId, COMET_CHAIN_ID);
+
+        DoubleSignEvidence memory evidence = doubleSign(
+            validator,
+            privKey,
+            cometVote(1, 100, 2, bytes32(uint256(1))),
+            privKey,
+            cometVote(1, 100, 2, bytes32(uint256(2)))
+        );
+
+        // Anyone can report it, not just the validators.
+        vm.expectEmit(true, false, false, true, address(saDiamond));
+        emit SubnetActorManagerFacet.DoubleSignReported(validator, 100, 2, reporter);
+        vm.prank(reporter);
+        saDiamond.manager().reportDoubleSign(evidence);
+
+        vm.expectRevert(DoubleSignAlreadyReported.selector);
+        saDiamond.manager().reportDoubleSign(evidence);
+    }
+
+    function testSubnetActorDiamond_ParamChangeProposals() public {
+        (uint256[] memory keys, address[] memory validators, ) = TestUtils.getThreeValidators(vm);
+
+        for (uint256 i = 0; i < 3; i++) {
+            vm.deal(validators[i], 10 gwei);
+            vm.prank(validators[i]);
+            saDiamond.manager().join{value: 10}(TestUtils.deriveValidatorPubKeyBytes(keys[i]));
+        }
+
+        vm.expectRevert(abi.encodeWithSelector(NotValidator.selector, address(this)));
+        saDiamond.manager().proposeParamChange(SubnetParam.MinValidators, 2);
+
+        vm.startPrank(validators[0]);
+        vm.expectRevert(InvalidMajorityPercentage.selector);
+        saDiamond.manager().proposeParamChange(SubnetParam.MajorityPercentage, 50);
+        vm.expectRevert(InvalidParamChangeValue.selector);
+        saDiamond.manager().proposeParamChange(SubnetParam.MinValidators, 0);
+
+        uint256 id = saDiamond.manager().proposeParamChange(SubnetParam.MinValidators, 2);
+        require(id == 0, "unexpected proposal id");
+
+        vm.expectRevert(ParamChangeAlreadyVoted.selector);
+        saDiamond.manager().voteParamChange(id);
+        vm.expectRevert(ParamChangeProposalNotFound.selector);
+        saDiamond.manager().voteParamChange(id + 1);
+        vm.stopPrank();
+
+        // 20 out of 30 is below the majority of 70%.
+        vm.prank(validators[1]);
+        saDiamond.manager().voteParamChange(id);
+        require(saDiamond.getter().minValidators() == DEFAULT_MIN_VALIDATORS, "changed before the majority");
+
+        vm.prank(validators[2]);
+        saDiamond.manager().voteParamChange(id);
+        require(saDiamond.getter().minValidators() == 2, "not changed at the majority");
+
+        ParamChangeProposal[] memory proposals = saDiamond.getter().paramChangeProposals();
+        require(proposals.length == 1, "unexpected number of proposals");
+        require(proposals[0].executed, "proposal not executed");
+        require(proposals[0].votes == 30, "unexpected votes");
+        require(saDiamond.getter().hasVotedParamChange(id, validators[2]), "vote not recorded");
+
+        // Proposals expire at their deadline.
+        vm.prank(validators[0]);
+        id = saDiamond.manager().proposeParamChange(SubnetParam.MajorityPercentage, 60);
+
+        vm.roll(block.number + PARAM_CHANGE_VOTING_PERIOD + 1);
+        vm.prank(validators[1]);
+        vm.expectRevert(ParamChangeProposalClosed.selector);
+        saDiamond.manager().voteParamChange(id);
+        require(
+            saDiamond.getter().majorityPercentage() == DEFAULT_MAJORITY_PERCENTAGE,
+            "expired proposal executed"
+        );
+    }
+
+    function testSubnetActorDiamond_ParamChangeProposals_RecountVotes() public {
+        (uint256[] memory keys, address[] memory validators, ) = TestUtils.getFourValidators(vm);
+
+        for (uint256 i = 0; i < 4; i++) {
+            vm.deal(validators[i], 10 gwei);
+            vm.prank(validators[i]);
+            saDiamond.manager().join{value: 10}(TestUtils.deriveValidatorPubKeyBytes(keys[i]));
+        }
+
+        // 20 out of 40 is below the majority of 70%.
+        vm.prank(validators[0]);
+        uint256 id = saDiamond.manager().proposeParamChange(SubnetParam.MinValidators, 2);
+        vm.prank(validators[1]);
+        saDiamond.manager().voteParamChange(id);
+
+        // The first voter leaves, so the majority of the remaining 30 is 21.
+        vm.prank(validators[0]);
+        saDiamond.manager().leave();
+        require(!saDiamond.getter().isActiveValidator(validators[0]), "validator 0 is still active");
+
+        // Counting the vote of the validator who left would reach 30; only 20 remain.
+        vm.prank(validators[2]);
+        saDiamond.manager().voteParamChange(id);
+        require(saDiamond.getter().minValidators() == DEFAULT_MIN_VALIDATORS, "changed with the votes of a former validator");
+
+        ParamChangeProposal[] memory proposals = saDiamond.getter().paramChangeProposals();
+        require(proposals[0].votes == 20, "unexpected votes");
+        require(!proposals[0].executed, "proposal executed");
+
+        vm.prank(validators[3]);
+        saDiamond.manager().voteParamChange(id);
+        require(saDiamond.getter().minValidators() == 2, "not changed at the majority");
+    }
+
     function testSubnetActorDiamond_crossMsgGetter() public view {
         IpcEnvelope[] memory msgs = new IpcEnvelope[](1);
         msgs[0] = TestUtils.newXnetCallMsg(
@@ -1798,4 +2022,68 @@ contract SubnetActorDiamondTest is Test, IntegrationTestBase {
 
         saDiamond.checkpointer().submitCheckpoint(checkpoint, validators, signatures);
     }
+
+    /// Sign bytes of a CometBFT vote on the test chain.
+    function cometVote(
+        uint8 voteType,
+        int64 height,
+        int64 round,
+        bytes32 blockHash
+    ) internal pure returns (bytes memory) {
+        return cometVote(Strings.toString(COMET_CHAIN_ID), voteType, height, round, blockHash);
+    }
+
+    /// Sign bytes of a CometBFT vote, i.e. a length delimited protobuf `CanonicalVote`.
+    function cometVote(
+        string memory chainIdString,
+        uint8 voteType,
+        int64 height,
+        int64 round,
+        bytes32 blockHash
+    ) internal pure returns (bytes memory) {
+        bytes memory chainId = bytes(chainIdString);
+        bytes memory body = abi.encodePacked(
+            bytes1(0x08),
+            voteType,
+            bytes1(0x11),
+            littleEndian(uint64(height)),
+            bytes1(0x19),
+            littleEndian(uint64(round)),
+            // block_id with just the hash
+            bytes1(0x22),
+            uint8(34),
+            bytes1(0x0a),
+            uint8(32),
+            blockHash,
+            bytes1(0x32),
+            uint8(chainId.length),
+            chainId
+        );
+        return abi.encodePacked(uint8(body.length), body);
+    }
+
+    function littleEndian(uint64 value) internal pure returns (bytes8 result) {
+        for (uint256 i = 0; i < 8; i++) {
+            result |= bytes8(uint64(uint8(value >> (8 * i))) << (56 - 8 * i));
+        }
+    }
+
+    function doubleSign(
+        address validator,
+        uint256 keyA,
+        bytes memory voteA,
+        uint256 keyB,
+        bytes memory voteB
+    ) internal pure returns (DoubleSignEvidence memory) {
+        (, bytes32 ra, bytes32 sa) = vm.sign(keyA, sha256(voteA));
+        (, bytes32 rb, bytes32 sb) = vm.sign(keyB, sha256(voteB));
+        return
+            DoubleSignEvidence({
+                validator: validator,
+                voteA: voteA,
+                signatureA: abi.encodePacked(ra, sa),
+                voteB: voteB,
+                signatureB: abi.encodePacked(rb, sb)
+            });
+    }
 }
@@ -0,0 +1,162 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity ^0.8.23;
+
+import "forge-std/Test.sol";
+
+import {IpcEnvelope, CallMsg, ResultMsg, IpcMsgKind, OutcomeType} from "../../src/structs/CrossNet.sol";
+import {SubnetID, IPCAddress} from "../../src/structs/Subnet.sol";
+import {FvmAddressHelper} from "../../src/lib/FvmAddressHelper.sol";
+import {CrossMsgHelper} from "../../src/lib/CrossMsgHelper.sol";
+import {IGateway} from "../../src/interfaces/IGateway.sol";
+import {IIpcHandler} from "../../sdk/interfaces/IIpcHandler.sol";
+import {ModelExchange} from "../../sdk/ModelExchange.sol";
+
+contract ModelExchangeTest is Test {
+    using CrossMsgHelper for IpcEnvelope;
+
+    bytes4 constant IMPORT_MODEL_SELECTOR = bytes4(keccak256("importModel(bytes32,bytes)"));
+
+    address gateway = vm.addr(1);
+    ModelExchange exchange;
+
+    IPCAddress ipcAddressA;
+    IPCAddress ipcAddressB;
+
+    bytes model = bytes("trained model");
+    bytes32 modelHash;
+
+    function setUp() public {
+        address[] memory pathA = new address[](1);
+        pathA[0] = vm.addr(2000);
+        address[] memory pathB = new address[](1);
+        pathB[0] = vm.addr(3000);
+
+        // The exchange under test lives in subnet A, and its peer in the sibling subnet B.
+        exchange = new ModelExchange(gateway);
+        ipcAddressA = IPCAddress({
+            subnetId: SubnetID({root: 123, route: pathA}),
+            rawAddress: FvmAddressHelper.from(address(exchange))
+        });
+        ipcAddressB = IPCAddress({
+            subnetId: SubnetID({root: 123, route: pathB}),
+            rawAddress: FvmAddressHelper.from(address(200))
+        });
+
+        modelHash = sha256(model);
+    }
+
+    function importEnvelope(bytes4 method, bytes32 hash, bytes memory data) internal view returns (IpcEnvelope memory) {
+        return
+            IpcEnvelope({
+                kind: IpcMsgKind.Call,
+                from: ipcAddressB,
+                to: ipcAddressA,
+                value: 0,
+                message: abi.encode(CallMsg({method: abi.encodePacked(method), params: abi.encode(hash, data)})),
+                nonce: 0
+            });
+    }
+
+    function exportEnvelope() internal view returns (IpcEnvelope memory) {
+        return
+            IpcEnvelope({
+                kind: IpcMsgKind.Call,
+                from: ipcAddressA,
+                to: ipcAddressB,
+                value: 10,
+                message: abi.encode(
+                    CallMsg({
+                        method: abi.encodePacked(IMPORT_MODEL_SELECTOR),
+                        params: abi.encode(modelHash, model)
+                    })
+                ),
+                nonce: 0
+            });
+    }
+
+    function test_ModelExchange_selector() public view {
+        require(exchange.IMPORT_MODEL_SELECTOR() == IMPORT_MODEL_SELECTOR, "unexpected selector");
+    }
+
+    function test_ModelExchange_exportRejectsWrongHash() public {
+        vm.expectRevert(ModelExchange.ModelHashMismatch.selector);
+        exchange.exportModel(ipcAddressB, keccak256(model), model);
+    }
+
+    function test_ModelExchange_export() public {
+        IpcEnvelope memory envelope = exportEnvelope();
+        vm.mockCall(gateway, abi.encodeWithSelector(IGateway.sendContractXnetMessage.selector), abi.encode(envelope));
+
+        vm.expectEmit(true, true, true, true, address(exchange));
+        emit ModelExchange.ModelExported(keccak256(abi.encode(envelope)), modelHash, ipcAddressB);
+
+        vm.deal(address(this), 10);
+        exchange.exportModel{value: 10}(ipcAddressB, modelHash, model);
+
+        (, , , , uint256 value, ) = exchange.inflightMsgs(envelope.toHash());
+        require(value == 10, "export not in flight");
+    }
+
+    function test_ModelExchange_import() public {
+        require(!exchange.hasModel(modelHash), "model imported before the message");
+
+        IpcEnvelope memory envelope = importEnvelope(IMPORT_MODEL_SELECTOR, modelHash, model);
+
+        vm.expectEmit(true, true, true, true, address(exchange));
+        emit ModelExchange.ModelImported(modelHash, ipcAddressB);
+
+        vm.prank(gateway);
+        bytes memory ret = exchange.handleIpcMessage(envelope);
+        require(abi.decode(ret, (bytes32)) == modelHash, "unexpected return");
+
+        require(exchange.hasModel(modelHash), "model not imported");
+        (IPCAddress memory source, bytes memory imported) = exchange.getModel(modelHash);
+        require(keccak256(imported) == keccak256(model), "unexpected model");
+        require(keccak256(abi.encode(source)) == keccak256(abi.encode(ipcAddressB)), "unexpected source");
+    }
+
+    function test_ModelExchange_importRejectsTamperedModel() public {
+        vm.prank(gateway);
+        vm.expectRevert(ModelExchange.ModelHashMismatch.selector);
+        exchange.handleIpcMessage(importEnvelope(IMPORT_MODEL_SELECTOR, modelHash, bytes("other model")));
+
+        require(!exchange.hasModel(modelHash), "tampered model imported");
+    }
+
+    function test_ModelExchange_importRejectsUnknownMethod() public {
+        vm.prank(gateway);
+        vm.expectRevert(ModelExchange.UnknownMethod.selector);
+        exchange.handleIpcMessage(importEnvelope(bytes4(keccak256("foo()")), modelHash, model));
+    }
+
+    function test_ModelExchange_importOnlyFromGateway() public {
+        vm.expectRevert(IIpcHandler.CallerIsNotGateway.selector);
+        exchange.handleIpcMessage(importEnvelope(IMPORT_MODEL_SELECTOR, modelHash, model));
+    }
+
+    function test_ModelExchange_transferResult() public {
+        IpcEnvelope memory envelope = exportEnvelope();
+        vm.mockCall(gateway, abi.encodeWithSelector(IGateway.sendContractXnetMessage.selector), abi.encode(envelope));
+        vm.deal(address(this), 10);
+        exchange.exportModel{value: 10}(ipcAddressB, modelHash, model);
+
+        bytes32 id = envelope.toHash();
+        IpcEnvelope memory result = IpcEnvelope({
+            kind: IpcMsgKind.Result,
+            from: ipcAddressB,
+            to: ipcAddressA,
+            value: 0,
+            message: abi.encode(ResultMsg({outcome: OutcomeType.Ok, id: id, ret: abi.encode(modelHash)})),
+            nonce: 0
+        });
+
+        vm.expectEmit(true, true, true, true, address(exchange));
+        emit ModelExchange.ModelTransferResult(id, modelHash, OutcomeType.Ok);
+
+        vm.prank(gateway);
+        exchange.handleIpcMessage(result);
+
+        (, , , , uint256 value, ) = exchange.inflightMsgs(id);
+        require(value == 0, "result not correlated");
+    }
+}
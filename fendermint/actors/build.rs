@@ -8,7 +8,13 @@ use std::path::Path;
 use std::process::{Command, Stdio};
 use std::thread;
 
-const ACTORS: &[&str] = &["chainmetadata", "eam", "machinelearning"];
+const ACTORS: &[&str] = &[
+    "chainmetadata",
+    "eam",
+    "machinelearning",
+    "mljobs",
+    "faucet",
+];
 
 const FILES_TO_WATCH: &[&str] = &["Cargo.toml", "src"];
 
@@ -12,7 +12,8 @@ use fvm_shared::clock::ChainEpoch;
 use fvm_shared::error::ExitCode;
 
 use crate::{
-    BlockHash, ConstructorParams, Method, PushBlockParams, State, CHAINMETADATA_ACTOR_NAME,
+    BlockHash, BlockTimestamp, ConstructorParams, Method, PushBlockParams, State,
+    CHAINMETADATA_ACTOR_NAME,
 };
 
 fil_actors_runtime::wasm_trampoline!(Actor);
@@ -44,8 +45,19 @@ impl Actor {
                 )
             })?;
 
-            // push the block to the AMT
+            // load the timestamps AMT
+            let mut timestamps = Array::load(&st.timestamps, rt.store()).map_err(|e| {
+                e.downcast_default(
+                    ExitCode::USR_ILLEGAL_STATE,
+                    "failed to load timestamps states",
+                )
+            })?;
+
+            // push the block and its timestamp to the AMTs
             blockhashes.set(params.epoch as u64, params.block).unwrap();
+            timestamps
+                .set(params.epoch as u64, params.timestamp)
+                .unwrap();
 
             // remove the oldest block if the AMT is full (note that this assume the
             // for_each_while iterates in order, which it seems to do)
@@ -58,6 +70,7 @@ impl Actor {
                     })
                     .unwrap();
                 blockhashes.delete(first_idx).unwrap();
+                timestamps.delete(first_idx).unwrap();
             }
 
             // save the new blockhashes AMT cid root
@@ -65,6 +78,11 @@ impl Actor {
                 e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to save blockhashes")
             })?;
 
+            // save the new timestamps AMT cid root
+            st.timestamps = timestamps.flush().map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to save timestamps")
+            })?;
+
             Ok(())
         })?;
 
@@ -85,6 +103,16 @@ impl Actor {
         st.get_block_hash(rt.store(), epoch)
             .map_err(|e| e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to get blockhash"))
     }
+
+    fn get_block_timestamp(
+        rt: &impl Runtime,
+        epoch: ChainEpoch,
+    ) -> Result<Option<BlockTimestamp>, ActorError> {
+        let st: State = rt.state()?;
+
+        st.get_block_timestamp(rt.store(), epoch)
+            .map_err(|e| e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to get timestamp"))
+    }
 }
 
 impl ActorCode for Actor {
@@ -99,5 +127,6 @@ impl ActorCode for Actor {
         PushBlockHash => push_block_hash,
         LookbackLen => lookback_len,
         GetBlockHash => get_block_hash,
+        GetBlockTimestamp => get_block_timestamp,
     }
 }
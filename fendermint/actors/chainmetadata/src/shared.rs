@@ -8,7 +8,7 @@ use fvm_ipld_encoding::tuple::{Deserialize_tuple, Serialize_tuple};
 use fvm_shared::{clock::ChainEpoch, METHOD_CONSTRUCTOR};
 use num_derive::FromPrimitive;
 
-// The state stores the blockhashes of the last `lookback_len` epochs
+// The state stores the blockhashes and timestamps of the last `lookback_len` epochs
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct State {
     // the AMT root cid of blockhashes
@@ -20,6 +20,12 @@ pub struct State {
 
     // the maximum size of blockhashes before removing the oldest epoch
     pub lookback_len: u64,
+
+    // the AMT root cid of block timestamps, pruned along with the blockhashes
+    //
+    // appended last, chains created without it add it with the upgrade
+    // migration in `fendermint_vm_interpreter::fvm::migrations`
+    pub timestamps: Cid,
 }
 
 impl State {
@@ -35,9 +41,21 @@ impl State {
                 }
             };
 
+        let empty_timestamps_cid =
+            match Amt::<(), _>::new_with_bit_width(store, BLOCKHASHES_AMT_BITWIDTH).flush() {
+                Ok(cid) => cid,
+                Err(e) => {
+                    return Err(anyhow::anyhow!(
+                        "chainmetadata actor failed to create empty Amt: {}",
+                        e
+                    ))
+                }
+            };
+
         Ok(Self {
             blockhashes: empty_blockhashes_cid,
             lookback_len,
+            timestamps: empty_timestamps_cid,
         })
     }
 
@@ -71,6 +89,35 @@ impl State {
             )),
         }
     }
+
+    // loads the timestamps array from the AMT root cid and returns the timestamp
+    // of the block at the given epoch
+    pub fn get_block_timestamp<BS: Blockstore>(
+        &self,
+        store: &BS,
+        epoch: ChainEpoch,
+    ) -> anyhow::Result<Option<BlockTimestamp>> {
+        let timestamps = match Amt::load(&self.timestamps, &store) {
+            Ok(v) => v,
+            Err(e) => {
+                return Err(anyhow::anyhow!(
+                    "failed to load timestamps from AMT cid {}, error: {}",
+                    self.timestamps,
+                    e
+                ));
+            }
+        };
+
+        match timestamps.get(epoch as u64) {
+            Ok(Some(v)) => Ok(Some(*v)),
+            Ok(None) => Ok(None),
+            Err(err) => Err(anyhow::anyhow!(
+                "failed to get timestamp at epoch {}, error: {}",
+                epoch,
+                err
+            )),
+        }
+    }
 }
 
 pub const CHAINMETADATA_ACTOR_NAME: &str = "chainmetadata";
@@ -88,10 +135,14 @@ pub struct ConstructorParams {
 
 pub type BlockHash = [u8; 32];
 
+// seconds since the Unix epoch, as in the block header
+pub type BlockTimestamp = u64;
+
 #[derive(Default, Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct PushBlockParams {
     pub epoch: ChainEpoch,
     pub block: BlockHash,
+    pub timestamp: BlockTimestamp,
 }
 
 #[derive(FromPrimitive)]
@@ -101,4 +152,5 @@ pub enum Method {
     PushBlockHash = frc42_dispatch::method_hash!("PushBlockHash"),
     LookbackLen = frc42_dispatch::method_hash!("LookbackLen"),
     GetBlockHash = frc42_dispatch::method_hash!("GetBlockHash"),
+    GetBlockTimestamp = frc42_dispatch::method_hash!("GetBlockTimestamp"),
 }
@@ -0,0 +1,121 @@
+// Copyright 2021-2023 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use fil_actors_runtime::actor_dispatch;
+use fil_actors_runtime::actor_error;
+use fil_actors_runtime::builtin::singletons::SYSTEM_ACTOR_ADDR;
+use fil_actors_runtime::extract_send_result;
+use fil_actors_runtime::runtime::{ActorCode, Runtime};
+use fil_actors_runtime::ActorDowncast;
+use fil_actors_runtime::ActorError;
+use fvm_ipld_encoding::BytesDe;
+use fvm_shared::address::Address;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::error::ExitCode;
+use fvm_shared::METHOD_SEND;
+
+use crate::{
+    decode_drip_calldata, ConstructorParams, DripParams, Method, NextDripParams, State,
+    FAUCET_ACTOR_NAME,
+};
+
+fil_actors_runtime::wasm_trampoline!(Actor);
+
+pub struct Actor;
+
+impl Actor {
+    fn constructor(rt: &impl Runtime, params: ConstructorParams) -> Result<(), ActorError> {
+        rt.validate_immediate_caller_is(std::iter::once(&SYSTEM_ACTOR_ADDR))?;
+
+        let state = State::new(rt.store(), params).map_err(|e| {
+            e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to create empty HAMT")
+        })?;
+
+        rt.create(&state)?;
+
+        Ok(())
+    }
+
+    /// Send the drip amount to an address, unless it was already dripped within the cooldown.
+    fn drip(rt: &impl Runtime, params: DripParams) -> Result<TokenAmount, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+
+        Self::drip_to(rt, params.to)
+    }
+
+    /// Handle the `drip(address)` calls of EVM transactions.
+    fn invoke_contract(rt: &impl Runtime, calldata: BytesDe) -> Result<(), ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+
+        let to = decode_drip_calldata(&calldata.0)
+            .map_err(|e| actor_error!(illegal_argument; "{}", e))?;
+
+        Self::drip_to(rt, to)?;
+
+        Ok(())
+    }
+
+    /// The first epoch at which an address can be dripped again.
+    fn next_drip(rt: &impl Runtime, params: NextDripParams) -> Result<ChainEpoch, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+
+        let st: State = rt.state()?;
+        let address = Self::normalize(rt, params.address);
+
+        st.next_drip(rt.store(), &address)
+            .map_err(|e| e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to get drip"))
+    }
+
+    fn drip_to(rt: &impl Runtime, to: Address) -> Result<TokenAmount, ActorError> {
+        let address = Self::normalize(rt, to);
+        let curr_epoch = rt.curr_epoch();
+
+        let amount = rt.transaction(|st: &mut State, rt| {
+            let next = st.next_drip(rt.store(), &address).map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to get drip")
+            })?;
+            if curr_epoch < next {
+                return Err(actor_error!(forbidden;
+                    "{} was already dripped; try again at epoch {}", to, next));
+            }
+            if rt.current_balance() < st.drip_amount {
+                return Err(actor_error!(insufficient_funds;
+                    "the faucet has run dry: {} left, drips are {}", rt.current_balance(), st.drip_amount));
+            }
+
+            st.record_drip(rt.store(), &address, curr_epoch)
+                .map_err(|e| {
+                    e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to record drip")
+                })?;
+
+            Ok(st.drip_amount.clone())
+        })?;
+
+        extract_send_result(rt.send_simple(&to, METHOD_SEND, None, amount.clone()))?;
+
+        Ok(amount)
+    }
+
+    // the ID address of the recipient if it exists, so that its other addresses share the limit
+    fn normalize(rt: &impl Runtime, address: Address) -> Address {
+        rt.resolve_address(&address)
+            .map(Address::new_id)
+            .unwrap_or(address)
+    }
+}
+
+impl ActorCode for Actor {
+    type Methods = Method;
+
+    fn name() -> &'static str {
+        FAUCET_ACTOR_NAME
+    }
+
+    actor_dispatch! {
+        Constructor => constructor,
+        Drip => drip,
+        NextDrip => next_drip,
+        InvokeContract => invoke_contract,
+    }
+}
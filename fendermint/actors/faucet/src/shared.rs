@@ -0,0 +1,194 @@
+// Copyright 2021-2023 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use anyhow::anyhow;
+use cid::Cid;
+use fvm_ipld_blockstore::Blockstore;
+use fvm_ipld_encoding::tuple::{Deserialize_tuple, Serialize_tuple};
+use fvm_ipld_hamt::{BytesKey, Hamt};
+use fvm_shared::{address::Address, clock::ChainEpoch, econ::TokenAmount, METHOD_CONSTRUCTOR};
+use num_derive::FromPrimitive;
+
+pub const FAUCET_ACTOR_NAME: &str = "faucet";
+
+// the default number of epochs an address has to wait between two drips
+pub const DEFAULT_COOLDOWN: ChainEpoch = 1000;
+
+// the default bitwidth of the last drips HAMT
+pub const DRIPS_HAMT_BITWIDTH: u32 = 5;
+
+/// The selector of `drip(address)`, which EVM transactions call the faucet with.
+pub const DRIP_SELECTOR: [u8; 4] = [0x67, 0xa5, 0xcd, 0x06];
+
+// the namespace of the delegated addresses of Ethereum accounts
+const EAM_ACTOR_ID: u64 = 10;
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct State {
+    // the amount sent with every drip
+    pub drip_amount: TokenAmount,
+
+    // the number of epochs an address has to wait between two drips
+    pub cooldown: ChainEpoch,
+
+    // the HAMT root cid of the epoch of the last drip of every address
+    pub last_drips: Cid,
+}
+
+impl State {
+    pub fn new<BS: Blockstore>(store: &BS, params: ConstructorParams) -> anyhow::Result<Self> {
+        let last_drips = Hamt::<_, ChainEpoch>::new_with_bit_width(store, DRIPS_HAMT_BITWIDTH)
+            .flush()
+            .map_err(|e| anyhow!("faucet actor failed to create empty Hamt: {}", e))?;
+
+        Ok(Self {
+            drip_amount: params.drip_amount,
+            cooldown: params.cooldown,
+            last_drips,
+        })
+    }
+
+    // returns the first epoch at which the address can be dripped again
+    pub fn next_drip<BS: Blockstore>(
+        &self,
+        store: &BS,
+        address: &Address,
+    ) -> anyhow::Result<ChainEpoch> {
+        let drips = Hamt::<_, ChainEpoch>::load_with_bit_width(
+            &self.last_drips,
+            store,
+            DRIPS_HAMT_BITWIDTH,
+        )
+        .map_err(|e| anyhow!("failed to load last drips: {}", e))?;
+
+        match drips.get(&BytesKey::from(address.to_bytes())) {
+            Ok(Some(last)) => Ok(last + self.cooldown),
+            Ok(None) => Ok(0),
+            Err(e) => Err(anyhow!("failed to get last drip of {}: {}", address, e)),
+        }
+    }
+
+    pub fn record_drip<BS: Blockstore>(
+        &mut self,
+        store: &BS,
+        address: &Address,
+        epoch: ChainEpoch,
+    ) -> anyhow::Result<()> {
+        let mut drips = Hamt::<_, ChainEpoch>::load_with_bit_width(
+            &self.last_drips,
+            store,
+            DRIPS_HAMT_BITWIDTH,
+        )
+        .map_err(|e| anyhow!("failed to load last drips: {}", e))?;
+
+        drips
+            .set(BytesKey::from(address.to_bytes()), epoch)
+            .map_err(|e| anyhow!("failed to set last drip of {}: {}", address, e))?;
+
+        self.last_drips = drips
+            .flush()
+            .map_err(|e| anyhow!("failed to save last drips: {}", e))?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct ConstructorParams {
+    pub drip_amount: TokenAmount,
+    pub cooldown: ChainEpoch,
+}
+
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct DripParams {
+    pub to: Address,
+}
+
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct NextDripParams {
+    pub address: Address,
+}
+
+/// Decode the ABI encoded calldata of `drip(address)` into the address to drip to.
+///
+/// Masked ID addresses (`0xff00..00<id>`) are turned into ID addresses, and any other into the
+/// delegated address of the Ethereum account.
+pub fn decode_drip_calldata(calldata: &[u8]) -> anyhow::Result<Address> {
+    if calldata.len() != 36 || calldata[..4] != DRIP_SELECTOR {
+        return Err(anyhow!("expected a call of drip(address)"));
+    }
+    let (padding, eth_addr) = calldata[4..].split_at(12);
+    if padding.iter().any(|b| *b != 0) {
+        return Err(anyhow!("invalid address argument"));
+    }
+
+    if eth_addr[0] == 0xff && eth_addr[1..12].iter().all(|b| *b == 0) {
+        let mut id = [0u8; 8];
+        id.copy_from_slice(&eth_addr[12..]);
+        return Ok(Address::new_id(u64::from_be_bytes(id)));
+    }
+    Address::new_delegated(EAM_ACTOR_ID, eth_addr).map_err(|e| anyhow!("invalid address: {}", e))
+}
+
+#[derive(FromPrimitive)]
+#[repr(u64)]
+pub enum Method {
+    Constructor = METHOD_CONSTRUCTOR,
+    Drip = frc42_dispatch::method_hash!("Drip"),
+    NextDrip = frc42_dispatch::method_hash!("NextDrip"),
+    // the method EVM transactions are delivered with
+    InvokeContract = frc42_dispatch::method_hash!("InvokeEVM"),
+}
+
+#[cfg(test)]
+mod tests {
+    use fvm_ipld_blockstore::MemoryBlockstore;
+    use fvm_shared::address::Address;
+    use fvm_shared::econ::TokenAmount;
+
+    use super::{decode_drip_calldata, ConstructorParams, State, DRIP_SELECTOR};
+
+    #[test]
+    fn drips_are_rate_limited() {
+        let store = MemoryBlockstore::default();
+        let mut state = State::new(
+            &store,
+            ConstructorParams {
+                drip_amount: TokenAmount::from_whole(10),
+                cooldown: 100,
+            },
+        )
+        .unwrap();
+
+        let address = Address::new_id(1001);
+        assert_eq!(state.next_drip(&store, &address).unwrap(), 0);
+
+        state.record_drip(&store, &address, 50).unwrap();
+        assert_eq!(state.next_drip(&store, &address).unwrap(), 150);
+        assert_eq!(state.next_drip(&store, &Address::new_id(1002)).unwrap(), 0);
+    }
+
+    #[test]
+    fn decode_drip_calls() {
+        let call = |addr: [u8; 20]| [&DRIP_SELECTOR[..], &[0u8; 12], &addr].concat();
+
+        let mut masked = [0u8; 20];
+        masked[0] = 0xff;
+        masked[19] = 100;
+        assert_eq!(
+            decode_drip_calldata(&call(masked)).unwrap(),
+            Address::new_id(100)
+        );
+
+        let eth_addr = [0x11u8; 20];
+        assert_eq!(
+            decode_drip_calldata(&call(eth_addr)).unwrap(),
+            Address::new_delegated(10, &eth_addr).unwrap()
+        );
+
+        let mut other = call(eth_addr);
+        other[0] = 0;
+        assert!(decode_drip_calldata(&other).is_err());
+        assert!(decode_drip_calldata(&DRIP_SELECTOR).is_err());
+    }
+}
@@ -6,20 +6,43 @@ use std::io::Read;
 use fil_actors_runtime::actor_dispatch;
 use fil_actors_runtime::actor_error;
 use fil_actors_runtime::builtin::singletons::SYSTEM_ACTOR_ADDR;
+use fil_actors_runtime::extract_send_result;
 use fil_actors_runtime::runtime::{ActorCode, Runtime};
+use fil_actors_runtime::ActorDowncast;
 use fil_actors_runtime::ActorError;
-use fvm_ipld_encoding::RawBytes;
+use fil_actors_runtime::Array;
+use fvm_ipld_encoding::{RawBytes, IPLD_RAW};
+use fvm_shared::address::Address;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::error::{ErrorNumber, ExitCode};
+use fvm_shared::event::{ActorEvent, Entry, Flags};
 use fvm_shared::sys::out;
+use fvm_shared::METHOD_SEND;
+use num_traits::Zero;
 use std::cmp;
 
 use crate::{
-    Method, PredictKNNRegressionParams, PredictLinearRegressionParams,
-    PredictLogisticRegressionParams, TrainKNNRegressionParams, TrainLinearRegressionParams,
-    TrainLogisticRegressionParams, MACHINELEARNING_ACTOR_NAME,
+    kfold_test_indices, transpose, Algorithm, AppendColumnsParams, AppendRowsParams, ArModel,
+    ConstructorParams, CreateDatasetParams, CrossValidateParams, Dataset, DatasetId, FoldMetrics,
+    InputMatrix, LinearModel, ListModelParams, ListedModel, Method, ModelId, ModelInfo,
+    PredictARParams, PredictKNNRegressionParams, PredictLinearRegressionGDParams,
+    PredictLinearRegressionParams, PredictLogisticRegressionParams,
+    PredictMultiOutputLinearRegressionParams, PredictWithModelParams, ScaleParams, Scaler,
+    ScalerId, SetPredictionFeeParams, State, StorageUsage, Stored, TrainARParams,
+    TrainKNNRegressionParams, TrainLinearRegressionGDParams, TrainLinearRegressionGDReturn,
+    TrainLinearRegressionParams, TrainLogisticRegressionParams,
+    TrainMultiOutputLinearRegressionParams, TrainingInput, TrainingPermissionMode,
+    ALGORITHM_VERSION, EVENT_DATASET_DELETED, EVENT_MODEL_DELETED, EVENT_PREDICTION_FEE_PAID,
+    EVENT_PREDICTION_FEE_SET, MACHINELEARNING_ACTOR_NAME, MAX_SPARSE_COLS, MAX_SPARSE_ROWS,
 };
 
+#[cfg(feature = "fil-actor")]
 fil_actors_runtime::wasm_trampoline!(Actor);
 
+/// All the ML syscalls take an input buffer, an output buffer and a second input buffer.
+type Syscall = unsafe fn(u32, u32, u32, u32, u32, u32) -> Result<u32, ErrorNumber>;
+
+#[cfg(target_arch = "wasm32")]
 fvm_sdk::sys::fvm_syscalls! {
     module = "mlsyscall_kernel";
     pub fn train_linear_regression_syscall(
@@ -70,42 +93,230 @@ fvm_sdk::sys::fvm_syscalls! {
       model_offset: u32,
       model_length: u32,
     ) -> Result<u32>;
+    pub fn ml_randomness_syscall(
+      epoch: i64,
+      entropy_offset: u32,
+      entropy_length: u32,
+    ) -> Result<[u8; 32]>;
+}
+
+/// Outside of Wasm, e.g. in the unit tests, there is no kernel to provide the syscalls,
+/// so they fail as if the kernel didn't have them.
+#[cfg(not(target_arch = "wasm32"))]
+macro_rules! unsupported_syscalls {
+    ($($name:ident),*) => {
+        $(
+            unsafe fn $name(
+                _: u32, _: u32, _: u32, _: u32, _: u32, _: u32,
+            ) -> Result<u32, ErrorNumber> {
+                Err(ErrorNumber::IllegalOperation)
+            }
+        )*
+    };
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+unsupported_syscalls!(
+    train_linear_regression_syscall,
+    predict_linear_regression_syscall,
+    train_logistic_regression_syscall,
+    predict_logistic_regression_syscall,
+    train_knn_regression_syscall,
+    predict_knn_regression_syscall
+);
+
+#[cfg(not(target_arch = "wasm32"))]
+unsafe fn ml_randomness_syscall(_: i64, _: u32, _: u32) -> Result<[u8; 32], ErrorNumber> {
+    Err(ErrorNumber::IllegalOperation)
 }
 
 pub struct Actor;
 impl Actor {
-    fn train_linear_regression(
-        rt: &impl Runtime,
-        params: TrainLinearRegressionParams,
-    ) -> Result<Vec<u8>, ActorError> {
+    fn constructor(rt: &impl Runtime, params: ConstructorParams) -> Result<(), ActorError> {
+        rt.validate_immediate_caller_is(std::iter::once(&SYSTEM_ACTOR_ADDR))?;
+
+        let state = State::new(rt.store(), params).map_err(|e| {
+            e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to create empty AMT")
+        })?;
+
+        rt.create(&state)?;
+
+        Ok(())
+    }
+
+    /// Check that the caller is allowed to train models and has paid the fee.
+    fn validate_can_train(rt: &impl Runtime) -> Result<(), ActorError> {
         rt.validate_immediate_caller_accept_any()?;
 
-        unsafe {
-            let input_matrix: Vec<Vec<i64>> = params.input_matrix;
+        let caller = rt.message().caller();
+        if caller == SYSTEM_ACTOR_ADDR {
+            return Ok(());
+        }
 
-            let output_length = 95 + 9 * input_matrix[0].len();
+        let state: State = rt.state()?;
 
-            let array = fvm_ipld_encoding::RawBytes::serialize(input_matrix).unwrap();
-            let conv_array = fvm_ipld_encoding::RawBytes::serialize(params.labels).unwrap();
+        if state.trusted_callers.contains(&caller) {
+            return Ok(());
+        }
 
-            let data_offset = array.bytes().as_ptr() as u32;
-            let data_length = array.bytes().len() as u32;
-            let conv_offset = conv_array.bytes().as_ptr() as u32;
-            let conv_length = conv_array.bytes().len() as u32;
+        if let TrainingPermissionMode::AllowList(addresses) = &state.permission_mode {
+            // Normalize the allowed addresses to IDs, so we can compare any kind of address.
+            let caller_id = caller.id().ok();
+            let allowed = addresses.iter().any(|a| rt.resolve_address(a) == caller_id);
 
-            let mut result: Vec<u8> = vec![0; output_length];
-            let value: u32 = train_linear_regression_syscall(
-                data_offset,
-                data_length,
-                result.as_ptr() as u32,
-                output_length as u32,
-                conv_offset,
-                conv_length,
-            )
-            .unwrap();
+            if !allowed {
+                return Err(actor_error!(forbidden;
+                    "caller {} is not allowed to train models", caller));
+            }
+        }
 
-            Ok(result.to_vec())
+        let value = rt.message().value_received();
+        if value < state.training_fee {
+            return Err(actor_error!(insufficient_funds;
+                "training requires a fee of {}; received {}", state.training_fee, value));
         }
+
+        Ok(())
+    }
+
+    /// Compute the mean and standard deviation of each feature and store them as a new scaler.
+    fn standardize(rt: &impl Runtime, params: ScaleParams) -> Result<ScalerId, ActorError> {
+        Self::validate_can_train(rt)?;
+
+        let input_matrix = Self::dense(rt, params.input_matrix)?;
+        let scaler = Scaler::standard(&input_matrix)
+            .map_err(|e| actor_error!(illegal_argument; "cannot standardize: {}", e))?;
+
+        Self::save_scaler(rt, scaler)
+    }
+
+    /// Compute the range of each feature and store it as a new scaler.
+    fn min_max_scale(rt: &impl Runtime, params: ScaleParams) -> Result<ScalerId, ActorError> {
+        Self::validate_can_train(rt)?;
+
+        let input_matrix = Self::dense(rt, params.input_matrix)?;
+        let scaler = Scaler::min_max(&input_matrix)
+            .map_err(|e| actor_error!(illegal_argument; "cannot min-max scale: {}", e))?;
+
+        Self::save_scaler(rt, scaler)
+    }
+
+    fn get_scaler(rt: &impl Runtime, scaler_id: ScalerId) -> Result<Option<Scaler>, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+
+        let st: State = rt.state()?;
+
+        st.get_scaler(rt.store(), scaler_id)
+            .map_err(|e| e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to get scaler"))
+    }
+
+    fn save_scaler(rt: &impl Runtime, scaler: Scaler) -> Result<ScalerId, ActorError> {
+        rt.transaction(|st: &mut State, rt| {
+            let mut scalers = Array::load(&st.scalers, rt.store()).map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load scalers")
+            })?;
+
+            let scaler_id = st.next_scaler_id;
+
+            scalers.set(scaler_id, scaler).map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to set scaler")
+            })?;
+
+            st.scalers = scalers.flush().map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to save scalers")
+            })?;
+            st.next_scaler_id += 1;
+
+            Ok(scaler_id)
+        })
+    }
+
+    /// Decode a sparse input matrix into the dense form expected by the syscalls, refusing
+    /// dimensions which would take more memory than any accepted dense input, or which are
+    /// over the limits in the state.
+    fn dense(rt: &impl Runtime, input_matrix: InputMatrix) -> Result<Vec<Vec<i64>>, ActorError> {
+        let (rows, cols) = match &input_matrix {
+            InputMatrix::Sparse(m) => {
+                if m.rows > MAX_SPARSE_ROWS || m.cols > MAX_SPARSE_COLS {
+                    return Err(actor_error!(
+                        illegal_argument;
+                        "the sparse matrix is {}x{}; the maximum is {}x{}",
+                        m.rows,
+                        m.cols,
+                        MAX_SPARSE_ROWS,
+                        MAX_SPARSE_COLS
+                    ));
+                }
+                (m.rows, m.cols)
+            }
+            InputMatrix::Dense(m) => (m.len() as u64, max_row_len(m)),
+        };
+        Self::check_limits(rt, rows, cols)?;
+
+        input_matrix
+            .into_dense()
+            .map_err(|e| actor_error!(illegal_argument; "invalid sparse matrix: {}", e))
+    }
+
+    /// Check the dimensions of an input against the limits in the state, if there are any.
+    fn check_limits(rt: &impl Runtime, rows: u64, cols: u64) -> Result<(), ActorError> {
+        let st: State = rt.state()?;
+        match st.limits {
+            Some(limits) => limits
+                .check(rows, cols)
+                .map_err(|e| actor_error!(illegal_argument; "{}", e)),
+            None => Ok(()),
+        }
+    }
+
+    /// Resolve the input of a training method to the matrix sent with the message, or to the
+    /// one of a stored dataset, which anyone can train on.
+    fn training_matrix(rt: &impl Runtime, input: TrainingInput) -> Result<InputMatrix, ActorError> {
+        match input {
+            TrainingInput::Matrix(input_matrix) => Ok(input_matrix),
+            TrainingInput::Dataset(dataset_id) => {
+                let st: State = rt.state()?;
+                let dataset = Self::load_dataset(&st, rt, dataset_id)?;
+                Ok(InputMatrix::Dense(dataset.input_matrix))
+            }
+        }
+    }
+
+    /// Apply the referenced scaler, if any, to the input matrix, so that the same scaling
+    /// is used for training and prediction.
+    fn scale(
+        rt: &impl Runtime,
+        scaler_id: Option<ScalerId>,
+        input_matrix: InputMatrix,
+    ) -> Result<Vec<Vec<i64>>, ActorError> {
+        let input_matrix = Self::dense(rt, input_matrix)?;
+
+        let Some(scaler_id) = scaler_id else {
+            return Ok(input_matrix);
+        };
+
+        let st: State = rt.state()?;
+
+        let scaler = st
+            .get_scaler(rt.store(), scaler_id)
+            .map_err(|e| e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to get scaler"))?
+            .ok_or_else(|| actor_error!(not_found; "scaler {} not found", scaler_id))?;
+
+        scaler
+            .transform(input_matrix)
+            .map_err(|e| actor_error!(illegal_argument; "cannot scale: {}", e))
+    }
+
+    fn train_linear_regression(
+        rt: &impl Runtime,
+        params: TrainLinearRegressionParams,
+    ) -> Result<Vec<u8>, ActorError> {
+        Self::validate_can_train(rt)?;
+
+        let input_matrix = Self::training_matrix(rt, params.input_matrix)?;
+        let input_matrix = Self::scale(rt, params.scaler, input_matrix)?;
+
+        Self::train(Algorithm::LinearRegression, input_matrix, params.labels)
     }
 
     fn predict_linear_regression(
@@ -114,183 +325,822 @@ impl Actor {
     ) -> Result<Vec<i64>, ActorError> {
         rt.validate_immediate_caller_accept_any()?;
 
-        unsafe {
-            let input_matrix: Vec<Vec<i64>> = params.input_matrix;
+        let input_matrix = Self::scale(rt, params.scaler, params.input_matrix)?;
 
-            let output_length = 1 + 2 * input_matrix.len();
+        Self::predict(Algorithm::LinearRegression, input_matrix, params.model)
+    }
 
-            let array = fvm_ipld_encoding::RawBytes::serialize(input_matrix).unwrap();
-            let model_array = fvm_ipld_encoding::RawBytes::serialize(params.model).unwrap();
+    /// Train a linear regression for each column of the labels, returning one model per column,
+    /// so that vectors of targets can be learned with a single message.
+    fn train_multi_output_linear_regression(
+        rt: &impl Runtime,
+        params: TrainMultiOutputLinearRegressionParams,
+    ) -> Result<Vec<Vec<u8>>, ActorError> {
+        Self::validate_can_train(rt)?;
 
-            let data_offset = array.bytes().as_ptr() as u32;
-            let data_length = array.bytes().len() as u32;
-            let model_offset = model_array.bytes().as_ptr() as u32;
-            let model_length = model_array.bytes().len() as u32;
+        let input_matrix = Self::training_matrix(rt, params.input_matrix)?;
+        let rows = input_matrix.num_rows();
+        if params.labels.len() != rows {
+            return Err(actor_error!(illegal_argument;
+                "there are {} rows of labels for {} rows", params.labels.len(), rows));
+        }
 
-            let mut result_raw: Vec<u8> = vec![0; output_length];
-            let value: u32 = predict_linear_regression_syscall(
-                data_offset,
-                data_length,
-                result_raw.as_ptr() as u32,
-                output_length as u32,
-                model_offset,
-                model_length,
-            )
-            .unwrap();
+        // Every target column is a separate training run.
+        Self::check_limits(rt, rows as u64, max_row_len(&params.labels))?;
+        let columns = transpose(&params.labels)
+            .map_err(|e| actor_error!(illegal_argument; "invalid labels: {}", e))?;
 
-            let result: Vec<i64> = fvm_ipld_encoding::RawBytes::deserialize(
-                &fvm_ipld_encoding::RawBytes::new(result_raw),
-            )
-            .unwrap();
+        let input_matrix = Self::scale(rt, params.scaler, input_matrix)?;
+
+        columns
+            .into_iter()
+            .map(|labels| Self::train(Algorithm::LinearRegression, input_matrix.clone(), labels))
+            .collect()
+    }
+
+    /// Predict every target column with its model, returning one row of targets per input row.
+    fn predict_multi_output_linear_regression(
+        rt: &impl Runtime,
+        params: PredictMultiOutputLinearRegressionParams,
+    ) -> Result<Vec<Vec<i64>>, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
 
-            Ok(result)
+        if params.models.is_empty() {
+            return Err(actor_error!(illegal_argument; "there are no models to predict with"));
         }
+        Self::check_limits(rt, 1, params.models.len() as u64)?;
+
+        let input_matrix = Self::scale(rt, params.scaler, params.input_matrix)?;
+
+        let columns = params
+            .models
+            .into_iter()
+            .map(|model| Self::predict(Algorithm::LinearRegression, input_matrix.clone(), model))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        transpose(&columns)
+            .map_err(|e| actor_error!(illegal_state; "inconsistent predictions: {}", e))
+    }
+
+    /// Train a linear regression with gradient descent in the actor, so the caller controls how
+    /// much gas it takes and learns whether the model converged.
+    fn train_linear_regression_gd(
+        rt: &impl Runtime,
+        params: TrainLinearRegressionGDParams,
+    ) -> Result<TrainLinearRegressionGDReturn, ActorError> {
+        Self::validate_can_train(rt)?;
+
+        let input_matrix = Self::training_matrix(rt, params.input_matrix)?;
+        let input_matrix = Self::scale(rt, params.scaler, input_matrix)?;
+
+        let (model, convergence) = LinearModel::fit_gradient_descent(
+            &input_matrix,
+            &params.labels,
+            params.learning_rate,
+            params.max_iterations,
+            params.tolerance,
+        )
+        .map_err(|e| actor_error!(illegal_argument; "cannot train: {}", e))?;
+
+        Ok(TrainLinearRegressionGDReturn { model, convergence })
+    }
+
+    fn predict_linear_regression_gd(
+        rt: &impl Runtime,
+        params: PredictLinearRegressionGDParams,
+    ) -> Result<Vec<i64>, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+
+        let input_matrix = Self::scale(rt, params.scaler, params.input_matrix)?;
+
+        params
+            .model
+            .predict(&input_matrix)
+            .map_err(|e| actor_error!(illegal_argument; "cannot predict: {}", e))
     }
 
     fn train_logistic_regression(
         rt: &impl Runtime,
         params: TrainLogisticRegressionParams,
     ) -> Result<Vec<u8>, ActorError> {
-        rt.validate_immediate_caller_accept_any()?;
+        Self::validate_can_train(rt)?;
 
-        unsafe {
-            let input_matrix: Vec<Vec<i64>> = params.input_matrix;
+        let input_matrix = Self::training_matrix(rt, params.input_matrix)?;
+        let input_matrix = Self::scale(rt, params.scaler, input_matrix)?;
 
-            let output_length = 172 + 9 * input_matrix[0].len();
+        Self::train(Algorithm::LogisticRegression, input_matrix, params.labels)
+    }
 
-            let array = fvm_ipld_encoding::RawBytes::serialize(input_matrix).unwrap();
-            let conv_array = fvm_ipld_encoding::RawBytes::serialize(params.labels).unwrap();
+    fn predict_logistic_regression(
+        rt: &impl Runtime,
+        params: PredictLogisticRegressionParams,
+    ) -> Result<Vec<i64>, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
 
-            let data_offset = array.bytes().as_ptr() as u32;
-            let data_length = array.bytes().len() as u32;
-            let conv_offset = conv_array.bytes().as_ptr() as u32;
-            let conv_length = conv_array.bytes().len() as u32;
+        let input_matrix = Self::scale(rt, params.scaler, params.input_matrix)?;
 
-            let mut result: Vec<u8> = vec![0; output_length];
-            let value: u32 = train_logistic_regression_syscall(
-                data_offset,
-                data_length,
-                result.as_ptr() as u32,
-                output_length as u32,
-                conv_offset,
-                conv_length,
-            )
-            .unwrap();
+        Self::predict(Algorithm::LogisticRegression, input_matrix, params.model)
+    }
 
-            Ok(result.to_vec())
-        }
+    fn train_knn_regression(
+        rt: &impl Runtime,
+        params: TrainKNNRegressionParams,
+    ) -> Result<Vec<u8>, ActorError> {
+        Self::validate_can_train(rt)?;
+
+        let input_matrix = Self::training_matrix(rt, params.input_matrix)?;
+        let input_matrix = Self::scale(rt, params.scaler, input_matrix)?;
+
+        Self::train(Algorithm::KNNRegression, input_matrix, params.labels)
     }
 
-    fn predict_logistic_regression(
+    fn predict_knn_regression(
         rt: &impl Runtime,
-        params: PredictLogisticRegressionParams,
+        params: PredictKNNRegressionParams,
     ) -> Result<Vec<i64>, ActorError> {
         rt.validate_immediate_caller_accept_any()?;
 
-        unsafe {
-            let input_matrix: Vec<Vec<i64>> = params.input_matrix;
+        let input_matrix = Self::scale(rt, params.scaler, params.input_matrix)?;
 
-            let output_length = 1 + 1 * input_matrix.len();
+        Self::predict(Algorithm::KNNRegression, input_matrix, params.model)
+    }
 
-            let array = fvm_ipld_encoding::RawBytes::serialize(input_matrix).unwrap();
-            let model_array = fvm_ipld_encoding::RawBytes::serialize(params.model).unwrap();
+    /// Fit an autoregressive model to a time series, e.g. to smooth or forecast an oracle feed.
+    fn train_ar(rt: &impl Runtime, params: TrainARParams) -> Result<ArModel, ActorError> {
+        Self::validate_can_train(rt)?;
+        Self::check_limits(rt, params.series.len() as u64, 1)?;
 
-            let data_offset = array.bytes().as_ptr() as u32;
-            let data_length = array.bytes().len() as u32;
-            let model_offset = model_array.bytes().as_ptr() as u32;
-            let model_length = model_array.bytes().len() as u32;
+        ArModel::fit(&params.series, params.lag_order)
+            .map_err(|e| actor_error!(illegal_argument; "cannot fit AR model: {}", e))
+    }
 
-            let mut result_raw: Vec<u8> = vec![0; output_length];
-            let value: u32 = predict_logistic_regression_syscall(
-                data_offset,
-                data_length,
-                result_raw.as_ptr() as u32,
-                output_length as u32,
-                model_offset,
-                model_length,
-            )
-            .unwrap();
+    /// Forecast the next values of a time series with an autoregressive model.
+    fn predict_ar(rt: &impl Runtime, params: PredictARParams) -> Result<Vec<i64>, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        Self::check_limits(rt, params.history.len() as u64, 1)?;
 
-            let result: Vec<i64> = fvm_ipld_encoding::RawBytes::deserialize(
-                &fvm_ipld_encoding::RawBytes::new(result_raw),
-            )
-            .unwrap();
+        params
+            .model
+            .forecast(&params.history, params.steps)
+            .map_err(|e| actor_error!(illegal_argument; "cannot forecast: {}", e))
+    }
+
+    /// Run a k-fold cross-validation: train a model on all folds but one and evaluate it on the
+    /// remaining one, for each fold, so the quality of an algorithm can be established on-chain.
+    fn cross_validate(
+        rt: &impl Runtime,
+        params: CrossValidateParams,
+    ) -> Result<Vec<FoldMetrics>, ActorError> {
+        Self::validate_can_train(rt)?;
 
-            Ok(result)
+        let input_matrix = Self::training_matrix(rt, params.input_matrix)?;
+        let rows = input_matrix.num_rows();
+        if params.labels.len() != rows {
+            return Err(actor_error!(illegal_argument;
+                "there are {} labels for {} rows", params.labels.len(), rows));
         }
+
+        let seed = if params.random_seed {
+            let entropy = rt.message().caller().to_bytes();
+            let randomness = Self::randomness(rt, &entropy)?;
+            let mut seed = [0u8; 8];
+            seed.copy_from_slice(&randomness[..8]);
+            Some(u64::from_be_bytes(seed))
+        } else {
+            params.seed
+        };
+
+        let folds = kfold_test_indices(rows, params.folds, seed)
+            .map_err(|e| actor_error!(illegal_argument; "cannot split folds: {}", e))?;
+
+        let input_matrix = Self::scale(rt, params.scaler, input_matrix)?;
+
+        let mut metrics = Vec::with_capacity(folds.len());
+
+        for (fold, test_indices) in folds.iter().enumerate() {
+            let mut train_x = Vec::new();
+            let mut train_y = Vec::new();
+            let mut test_x = Vec::new();
+            let mut test_y = Vec::new();
+
+            for (i, (row, label)) in input_matrix.iter().zip(params.labels.iter()).enumerate() {
+                if test_indices.binary_search(&i).is_ok() {
+                    test_x.push(row.clone());
+                    test_y.push(*label);
+                } else {
+                    train_x.push(row.clone());
+                    train_y.push(*label);
+                }
+            }
+
+            let train_rows = train_x.len() as u64;
+            let model = Self::train(params.algorithm, train_x, train_y)?;
+            let predictions = Self::predict(params.algorithm, test_x, model)?;
+
+            metrics.push(FoldMetrics::new(
+                fold as u64,
+                train_rows,
+                &test_y,
+                &predictions,
+                params.algorithm.is_classifier(),
+            ));
+        }
+
+        Ok(metrics)
     }
 
-    fn train_knn_regression(
+    /// List a trained model, so that anyone can predict with it by paying a fee to the caller.
+    ///
+    /// Listing stores the model in the state, so it's subject to the same rules as training.
+    fn list_model(rt: &impl Runtime, params: ListModelParams) -> Result<ModelId, ActorError> {
+        Self::validate_can_train(rt)?;
+
+        let owner = rt.message().caller();
+        let prediction_fee = params.prediction_fee.clone();
+        let bytes = params.model.len() as u64;
+
+        let model_id = rt.transaction(|st: &mut State, rt| {
+            if let Some(scaler_id) = params.scaler {
+                let scaler = st.get_scaler(rt.store(), scaler_id).map_err(|e| {
+                    e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to get scaler")
+                })?;
+                if scaler.is_none() {
+                    return Err(actor_error!(not_found; "scaler {} not found", scaler_id));
+                }
+            }
+
+            let model_id = st.next_model_id;
+
+            let model = ListedModel {
+                owner,
+                algorithm: params.algorithm,
+                model: params.model,
+                scaler: params.scaler,
+                prediction_fee: params.prediction_fee,
+                predictions: 0,
+                revenue: TokenAmount::zero(),
+                algorithm_version: ALGORITHM_VERSION,
+                dataset_hash: params.dataset_hash,
+                hyperparameters: params.hyperparameters,
+                listed_at: rt.curr_epoch(),
+            };
+
+            Self::save_model(st, rt, model_id, model)?;
+            st.next_model_id += 1;
+            st.add_storage(rt.store(), &owner, Stored::Model, bytes)
+                .map_err(|e| {
+                    e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to account for storage")
+                })?;
+
+            Ok(model_id)
+        })?;
+
+        Self::emit_model_event(
+            rt,
+            EVENT_PREDICTION_FEE_SET,
+            model_id,
+            &owner,
+            &prediction_fee,
+        )?;
+
+        Ok(model_id)
+    }
+
+    /// Change the fee of a listed model; only the owner can do this.
+    fn set_prediction_fee(
         rt: &impl Runtime,
-        params: TrainKNNRegressionParams,
-    ) -> Result<Vec<u8>, ActorError> {
+        params: SetPredictionFeeParams,
+    ) -> Result<(), ActorError> {
         rt.validate_immediate_caller_accept_any()?;
 
-        unsafe {
-            let input_matrix: Vec<Vec<i64>> = params.input_matrix;
+        let caller = rt.message().caller();
 
-            let output_length = 861 + 9 * input_matrix[0].len();
+        rt.transaction(|st: &mut State, rt| {
+            let mut model = Self::load_model(st, rt, params.model_id)?;
 
-            let array = fvm_ipld_encoding::RawBytes::serialize(input_matrix).unwrap();
-            let conv_array = fvm_ipld_encoding::RawBytes::serialize(params.labels).unwrap();
+            if model.owner != caller {
+                return Err(actor_error!(forbidden;
+                    "only the owner can set the fee of model {}", params.model_id));
+            }
 
-            let data_offset = array.bytes().as_ptr() as u32;
-            let data_length = array.bytes().len() as u32;
-            let conv_offset = conv_array.bytes().as_ptr() as u32;
-            let conv_length = conv_array.bytes().len() as u32;
+            model.prediction_fee = params.prediction_fee.clone();
 
-            let mut result: Vec<u8> = vec![0; output_length];
-            let value: u32 = train_knn_regression_syscall(
-                data_offset,
-                data_length,
-                result.as_ptr() as u32,
-                output_length as u32,
-                conv_offset,
-                conv_length,
-            )
-            .unwrap();
+            Self::save_model(st, rt, params.model_id, model)
+        })?;
+
+        Self::emit_model_event(
+            rt,
+            EVENT_PREDICTION_FEE_SET,
+            params.model_id,
+            &caller,
+            &params.prediction_fee,
+        )
+    }
+
+    /// Predict with a listed model, paying its fee to the owner. Any value sent on top of the
+    /// fee is returned to the caller.
+    fn predict_with_model(
+        rt: &impl Runtime,
+        params: PredictWithModelParams,
+    ) -> Result<Vec<i64>, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+
+        let st: State = rt.state()?;
+        let model = Self::load_model(&st, rt, params.model_id)?;
+
+        let value = rt.message().value_received();
+        let Some((fee, change)) = model.split_payment(&value) else {
+            return Err(actor_error!(insufficient_funds;
+                "predicting with model {} requires a fee of {}; received {}",
+                params.model_id, model.prediction_fee, value));
+        };
 
-            Ok(result.to_vec())
+        let input_matrix = Self::scale(rt, model.scaler, params.input_matrix)?;
+        let predictions = Self::predict(model.algorithm, input_matrix, model.model)?;
+
+        rt.transaction(|st: &mut State, rt| {
+            let mut model = Self::load_model(st, rt, params.model_id)?;
+            model.record_prediction(&fee);
+            Self::save_model(st, rt, params.model_id, model)
+        })?;
+
+        if !fee.is_zero() {
+            extract_send_result(rt.send_simple(&model.owner, METHOD_SEND, None, fee.clone()))?;
+        }
+
+        if !change.is_zero() {
+            extract_send_result(rt.send_simple(&rt.message().caller(), METHOD_SEND, None, change))?;
         }
+
+        Self::emit_model_event(
+            rt,
+            EVENT_PREDICTION_FEE_PAID,
+            params.model_id,
+            &model.owner,
+            &fee,
+        )?;
+
+        Ok(predictions)
     }
 
-    fn predict_knn_regression(
+    /// Predict with a listed model without charging its fee or counting the prediction. Only the
+    /// system actor can call it, which is what the node does to answer read-only queries, so
+    /// the predictions of a model can be looked up at any height still in the state.
+    fn query_prediction(
         rt: &impl Runtime,
-        params: PredictKNNRegressionParams,
+        params: PredictWithModelParams,
     ) -> Result<Vec<i64>, ActorError> {
+        rt.validate_immediate_caller_is(std::iter::once(&SYSTEM_ACTOR_ADDR))?;
+
+        let st: State = rt.state()?;
+        let model = Self::load_model(&st, rt, params.model_id)?;
+
+        let input_matrix = Self::scale(rt, model.scaler, params.input_matrix)?;
+        Self::predict(model.algorithm, input_matrix, model.model)
+    }
+
+    /// Delete a listed model to release its storage; only the owner can do this. Predictions
+    /// with the model fail from then on.
+    fn delete_model(rt: &impl Runtime, model_id: ModelId) -> Result<(), ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+
+        let caller = rt.message().caller();
+
+        let bytes = rt.transaction(|st: &mut State, rt| {
+            let model = Self::load_model(st, rt, model_id)?;
+
+            if model.owner != caller {
+                return Err(actor_error!(forbidden;
+                    "only the owner can delete model {}", model_id));
+            }
+
+            let mut models: Array<ListedModel, _> =
+                Array::load(&st.models, rt.store()).map_err(|e| {
+                    e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load models")
+                })?;
+
+            models.delete(model_id).map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to delete model")
+            })?;
+
+            st.models = models.flush().map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to save models")
+            })?;
+
+            let bytes = model.model.len() as u64;
+            st.remove_storage(rt.store(), &model.owner, Stored::Model, bytes)
+                .map_err(|e| {
+                    e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to release storage")
+                })?;
+
+            Ok(bytes)
+        })?;
+
+        let entries = vec![
+            event_entry(
+                "$type",
+                Flags::FLAG_INDEXED_ALL,
+                EVENT_MODEL_DELETED.as_bytes().to_vec(),
+            ),
+            event_entry(
+                "model_id",
+                Flags::FLAG_INDEXED_ALL,
+                model_id.to_be_bytes().to_vec(),
+            ),
+            event_entry("owner", Flags::FLAG_INDEXED_ALL, caller.to_bytes()),
+            event_entry("bytes", Flags::empty(), bytes.to_be_bytes().to_vec()),
+        ];
+
+        rt.emit_event(&ActorEvent { entries })
+    }
+
+    /// The models listed and the datasets created by an address, and the storage they use.
+    fn get_storage_usage(rt: &impl Runtime, owner: Address) -> Result<StorageUsage, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+
+        let st: State = rt.state()?;
+
+        // The owners are recorded by their ID addresses.
+        match rt.resolve_address(&owner) {
+            Some(id) => st
+                .storage_usage(rt.store(), &Address::new_id(id))
+                .map_err(|e| {
+                    e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to get storage usage")
+                }),
+            None => Ok(StorageUsage {
+                owner,
+                ..Default::default()
+            }),
+        }
+    }
+
+    fn get_model(rt: &impl Runtime, model_id: ModelId) -> Result<Option<ListedModel>, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+
+        let st: State = rt.state()?;
+
+        st.get_model(rt.store(), model_id)
+            .map_err(|e| e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to get model"))
+    }
+
+    /// The provenance of a listed model, without the model itself.
+    fn get_model_info(
+        rt: &impl Runtime,
+        model_id: ModelId,
+    ) -> Result<Option<ModelInfo>, ActorError> {
+        Ok(Self::get_model(rt, model_id)?.map(|model| model.info()))
+    }
+
+    /// Store an input matrix, so that rows or columns can be appended to it later on, and models
+    /// can be trained on it by its id.
+    ///
+    /// The dataset is kept in the state, so it's subject to the same rules as training, and its
+    /// storage is accounted to the caller until it's deleted.
+    fn create_dataset(
+        rt: &impl Runtime,
+        params: CreateDatasetParams,
+    ) -> Result<DatasetId, ActorError> {
+        Self::validate_can_train(rt)?;
+
+        let owner = rt.message().caller();
+        let input_matrix = Self::dense(rt, params.input_matrix)?;
+        let dataset = Dataset::new(owner, input_matrix)
+            .map_err(|e| actor_error!(illegal_argument; "invalid dataset: {}", e))?;
+        let bytes = dataset.size();
+
+        rt.transaction(|st: &mut State, rt| {
+            Self::check_dataset(st, &dataset)?;
+
+            let dataset_id = st.next_dataset_id;
+            Self::save_dataset(st, rt, dataset_id, dataset)?;
+            st.next_dataset_id += 1;
+            st.add_storage(rt.store(), &owner, Stored::Dataset, bytes)
+                .map_err(|e| {
+                    e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to account for storage")
+                })?;
+
+            Ok(dataset_id)
+        })
+    }
+
+    /// Append observations to a dataset; only the owner can do this.
+    fn append_rows(rt: &impl Runtime, params: AppendRowsParams) -> Result<(), ActorError> {
+        Self::validate_can_train(rt)?;
+
+        let rows = Self::dense(rt, params.rows)?;
+
+        Self::update_dataset(rt, params.dataset_id, |dataset| dataset.append_rows(rows))
+    }
+
+    /// Append features to a dataset; only the owner can do this.
+    fn append_columns(rt: &impl Runtime, params: AppendColumnsParams) -> Result<(), ActorError> {
+        Self::validate_can_train(rt)?;
+
+        let columns = Self::dense(rt, params.columns)?;
+
+        Self::update_dataset(rt, params.dataset_id, |dataset| {
+            dataset.append_columns(columns)
+        })
+    }
+
+    fn get_dataset(
+        rt: &impl Runtime,
+        dataset_id: DatasetId,
+    ) -> Result<Option<Dataset>, ActorError> {
         rt.validate_immediate_caller_accept_any()?;
 
+        let st: State = rt.state()?;
+
+        st.get_dataset(rt.store(), dataset_id)
+            .map_err(|e| e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to get dataset"))
+    }
+
+    /// Delete a dataset to release its storage; only the owner can do this. Training on the
+    /// dataset fails from then on.
+    fn delete_dataset(rt: &impl Runtime, dataset_id: DatasetId) -> Result<(), ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+
+        let caller = rt.message().caller();
+
+        let bytes = rt.transaction(|st: &mut State, rt| {
+            let dataset = Self::load_dataset(st, rt, dataset_id)?;
+
+            if dataset.owner != caller {
+                return Err(actor_error!(forbidden;
+                    "only the owner can delete dataset {}", dataset_id));
+            }
+
+            let mut datasets: Array<Dataset, _> =
+                Array::load(&st.datasets, rt.store()).map_err(|e| {
+                    e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load datasets")
+                })?;
+
+            datasets.delete(dataset_id).map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to delete dataset")
+            })?;
+
+            st.datasets = datasets.flush().map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to save datasets")
+            })?;
+
+            let bytes = dataset.size();
+            st.remove_storage(rt.store(), &dataset.owner, Stored::Dataset, bytes)
+                .map_err(|e| {
+                    e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to release storage")
+                })?;
+
+            Ok(bytes)
+        })?;
+
+        let entries = vec![
+            event_entry(
+                "$type",
+                Flags::FLAG_INDEXED_ALL,
+                EVENT_DATASET_DELETED.as_bytes().to_vec(),
+            ),
+            event_entry(
+                "dataset_id",
+                Flags::FLAG_INDEXED_ALL,
+                dataset_id.to_be_bytes().to_vec(),
+            ),
+            event_entry("owner", Flags::FLAG_INDEXED_ALL, caller.to_bytes()),
+            event_entry("bytes", Flags::empty(), bytes.to_be_bytes().to_vec()),
+        ];
+
+        rt.emit_event(&ActorEvent { entries })
+    }
+
+    /// Change a dataset of the caller, failing if the dimensions don't match or the result is
+    /// over the limits, and account for the storage it grows by.
+    fn update_dataset(
+        rt: &impl Runtime,
+        dataset_id: DatasetId,
+        f: impl FnOnce(&mut Dataset) -> Result<(), String>,
+    ) -> Result<(), ActorError> {
+        let caller = rt.message().caller();
+
+        rt.transaction(|st: &mut State, rt| {
+            let mut dataset = Self::load_dataset(st, rt, dataset_id)?;
+
+            if dataset.owner != caller {
+                return Err(actor_error!(forbidden;
+                    "only the owner can append to dataset {}", dataset_id));
+            }
+
+            let old_bytes = dataset.size();
+            f(&mut dataset).map_err(|e| {
+                actor_error!(illegal_argument; "cannot append to dataset {}: {}", dataset_id, e)
+            })?;
+            Self::check_dataset(st, &dataset)?;
+
+            let bytes = dataset.size().saturating_sub(old_bytes);
+            Self::save_dataset(st, rt, dataset_id, dataset)?;
+            st.grow_storage(rt.store(), &caller, bytes).map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to account for storage")
+            })
+        })
+    }
+
+    /// Check a dataset about to be stored against the limits in the state, if there are any.
+    fn check_dataset(st: &State, dataset: &Dataset) -> Result<(), ActorError> {
+        match st.limits {
+            Some(limits) => limits
+                .check_dataset(dataset)
+                .map_err(|e| actor_error!(illegal_argument; "{}", e)),
+            None => Ok(()),
+        }
+    }
+
+    fn load_dataset(
+        st: &State,
+        rt: &impl Runtime,
+        dataset_id: DatasetId,
+    ) -> Result<Dataset, ActorError> {
+        st.get_dataset(rt.store(), dataset_id)
+            .map_err(|e| e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to get dataset"))?
+            .ok_or_else(|| actor_error!(not_found; "dataset {} not found", dataset_id))
+    }
+
+    fn save_dataset(
+        st: &mut State,
+        rt: &impl Runtime,
+        dataset_id: DatasetId,
+        dataset: Dataset,
+    ) -> Result<(), ActorError> {
+        let mut datasets = Array::load(&st.datasets, rt.store()).map_err(|e| {
+            e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load datasets")
+        })?;
+
+        datasets.set(dataset_id, dataset).map_err(|e| {
+            e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to set dataset")
+        })?;
+
+        st.datasets = datasets.flush().map_err(|e| {
+            e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to save datasets")
+        })?;
+
+        Ok(())
+    }
+
+    fn load_model(
+        st: &State,
+        rt: &impl Runtime,
+        model_id: ModelId,
+    ) -> Result<ListedModel, ActorError> {
+        st.get_model(rt.store(), model_id)
+            .map_err(|e| e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to get model"))?
+            .ok_or_else(|| actor_error!(not_found; "model {} not found", model_id))
+    }
+
+    fn save_model(
+        st: &mut State,
+        rt: &impl Runtime,
+        model_id: ModelId,
+        model: ListedModel,
+    ) -> Result<(), ActorError> {
+        let mut models = Array::load(&st.models, rt.store()).map_err(|e| {
+            e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load models")
+        })?;
+
+        models
+            .set(model_id, model)
+            .map_err(|e| e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to set model"))?;
+
+        st.models = models.flush().map_err(|e| {
+            e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to save models")
+        })?;
+
+        Ok(())
+    }
+
+    /// Emit an accounting event about the fee of a listed model.
+    ///
+    /// The values are raw bytes, as required by the FVM: big-endian integers, the address bytes
+    /// and the signed big-endian atto amount. The caller of the message is the payer.
+    fn emit_model_event(
+        rt: &impl Runtime,
+        typ: &str,
+        model_id: ModelId,
+        owner: &Address,
+        fee: &TokenAmount,
+    ) -> Result<(), ActorError> {
+        let entries = vec![
+            event_entry("$type", Flags::FLAG_INDEXED_ALL, typ.as_bytes().to_vec()),
+            event_entry(
+                "model_id",
+                Flags::FLAG_INDEXED_ALL,
+                model_id.to_be_bytes().to_vec(),
+            ),
+            event_entry("owner", Flags::FLAG_INDEXED_ALL, owner.to_bytes()),
+            event_entry(
+                "caller",
+                Flags::FLAG_INDEXED_ALL,
+                rt.message().caller().to_bytes(),
+            ),
+            event_entry("fee", Flags::empty(), fee.atto().to_signed_bytes_be()),
+        ];
+
+        rt.emit_event(&ActorEvent { entries })
+    }
+
+    /// Invoke the training syscall of an algorithm and return the serialized model.
+    fn train(
+        algorithm: Algorithm,
+        input_matrix: Vec<Vec<i64>>,
+        labels: Vec<i64>,
+    ) -> Result<Vec<u8>, ActorError> {
+        let cols = input_matrix
+            .first()
+            .map(|row| row.len())
+            .unwrap_or_default();
+
+        let (syscall, output_length): (Syscall, usize) = match algorithm {
+            Algorithm::LinearRegression => (train_linear_regression_syscall, 95 + 9 * cols),
+            Algorithm::LogisticRegression => (train_logistic_regression_syscall, 172 + 9 * cols),
+            Algorithm::KNNRegression => (train_knn_regression_syscall, 861 + 9 * cols),
+        };
+
+        let array = fvm_ipld_encoding::RawBytes::serialize(input_matrix).unwrap();
+        let conv_array = fvm_ipld_encoding::RawBytes::serialize(labels).unwrap();
+
+        let result: Vec<u8> = vec![0; output_length];
+
         unsafe {
-            let input_matrix: Vec<Vec<i64>> = params.input_matrix;
+            syscall(
+                array.bytes().as_ptr() as u32,
+                array.bytes().len() as u32,
+                result.as_ptr() as u32,
+                output_length as u32,
+                conv_array.bytes().as_ptr() as u32,
+                conv_array.bytes().len() as u32,
+            )
+        }
+        .map_err(|e| actor_error!(unspecified; "failed to train {:?} model: {}", algorithm, e))?;
 
-            let output_length = 4 + 2 * input_matrix.len();
+        Ok(result)
+    }
 
-            let array = fvm_ipld_encoding::RawBytes::serialize(input_matrix).unwrap();
-            let model_array = fvm_ipld_encoding::RawBytes::serialize(params.model).unwrap();
+    /// Invoke the prediction syscall of an algorithm and return the predicted labels.
+    fn predict(
+        algorithm: Algorithm,
+        input_matrix: Vec<Vec<i64>>,
+        model: Vec<u8>,
+    ) -> Result<Vec<i64>, ActorError> {
+        let rows = input_matrix.len();
 
-            let data_offset = array.bytes().as_ptr() as u32;
-            let data_length = array.bytes().len() as u32;
-            let model_offset = model_array.bytes().as_ptr() as u32;
-            let model_length = model_array.bytes().len() as u32;
+        let (syscall, output_length): (Syscall, usize) = match algorithm {
+            Algorithm::LinearRegression => (predict_linear_regression_syscall, 1 + 2 * rows),
+            Algorithm::LogisticRegression => (predict_logistic_regression_syscall, 1 + rows),
+            Algorithm::KNNRegression => (predict_knn_regression_syscall, 4 + 2 * rows),
+        };
 
-            let mut result_raw: Vec<u8> = vec![0; output_length];
-            let value: u32 = predict_knn_regression_syscall(
-                data_offset,
-                data_length,
+        let array = fvm_ipld_encoding::RawBytes::serialize(input_matrix).unwrap();
+        let model_array = fvm_ipld_encoding::RawBytes::serialize(model).unwrap();
+
+        let result_raw: Vec<u8> = vec![0; output_length];
+
+        unsafe {
+            syscall(
+                array.bytes().as_ptr() as u32,
+                array.bytes().len() as u32,
                 result_raw.as_ptr() as u32,
                 output_length as u32,
-                model_offset,
-                model_length,
+                model_array.bytes().as_ptr() as u32,
+                model_array.bytes().len() as u32,
             )
-            .unwrap();
+        }
+        .map_err(
+            |e| actor_error!(unspecified; "failed to predict with {:?} model: {}", algorithm, e),
+        )?;
 
-            let result: Vec<i64> = fvm_ipld_encoding::RawBytes::deserialize(
-                &fvm_ipld_encoding::RawBytes::new(result_raw),
-            )
-            .unwrap();
+        fvm_ipld_encoding::RawBytes::deserialize(&fvm_ipld_encoding::RawBytes::new(result_raw))
+            .map_err(|e| actor_error!(serialization; "failed to decode predictions: {}", e))
+    }
 
-            Ok(result)
-        }
+    /// Draw randomness from the hash of the previous block, mixed with `entropy`, which is the
+    /// same for every validator. It is not secret: anyone can compute it once that block is
+    /// committed, before the message executes, and its proposer can grind the hash to bias it.
+    /// Only use it where a predictable value the caller doesn't choose is enough, and never for
+    /// anything that affects rewards.
+    fn randomness(rt: &impl Runtime, entropy: &[u8]) -> Result<[u8; 32], ActorError> {
+        let epoch = rt.curr_epoch() - 1;
+        unsafe { ml_randomness_syscall(epoch, entropy.as_ptr() as u32, entropy.len() as u32) }
+            .map_err(|e| actor_error!(unspecified; "failed to draw randomness at epoch {}: {}", epoch, e))
+    }
+}
+
+/// Number of columns of the longest row of a matrix.
+fn max_row_len(matrix: &[Vec<i64>]) -> u64 {
+    matrix.iter().map(|row| row.len()).max().unwrap_or_default() as u64
+}
+
+/// An event entry with a raw value, as required by the FVM.
+fn event_entry(key: &str, flags: Flags, value: Vec<u8>) -> Entry {
+    Entry {
+        flags,
+        key: key.to_owned(),
+        codec: IPLD_RAW,
+        value,
     }
 }
 
@@ -302,11 +1152,533 @@ impl ActorCode for Actor {
     }
 
     actor_dispatch! {
+      Constructor => constructor,
       TrainLinearRegression => train_linear_regression,
       PredictLinearRegression => predict_linear_regression,
       TrainLogisticRegression => train_logistic_regression,
       PredictLogisticRegression => predict_logistic_regression,
       TrainKNNRegression => train_knn_regression,
       PredictKNNRegression => predict_knn_regression,
+      Standardize => standardize,
+      MinMaxScale => min_max_scale,
+      GetScaler => get_scaler,
+      CrossValidate => cross_validate,
+      ListModel => list_model,
+      SetPredictionFee => set_prediction_fee,
+      PredictWithModel => predict_with_model,
+      GetModel => get_model,
+      GetModelInfo => get_model_info,
+      TrainAR => train_ar,
+      PredictAR => predict_ar,
+      TrainLinearRegressionGD => train_linear_regression_gd,
+      PredictLinearRegressionGD => predict_linear_regression_gd,
+      DeleteModel => delete_model,
+      GetStorageUsage => get_storage_usage,
+      QueryPrediction => query_prediction,
+      TrainMultiOutputLinearRegression => train_multi_output_linear_regression,
+      PredictMultiOutputLinearRegression => predict_multi_output_linear_regression,
+      CreateDataset => create_dataset,
+      AppendRows => append_rows,
+      AppendColumns => append_columns,
+      GetDataset => get_dataset,
+      DeleteDataset => delete_dataset,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fil_actors_runtime::test_utils::{
+        MockRuntime, ACCOUNT_ACTOR_CODE_ID, EVM_ACTOR_CODE_ID, SYSTEM_ACTOR_CODE_ID,
+    };
+    use fil_actors_runtime::SYSTEM_ACTOR_ADDR;
+    use fvm_ipld_encoding::ipld_block::IpldBlock;
+    use fvm_shared::address::Address;
+    use fvm_shared::econ::TokenAmount;
+    use fvm_shared::error::ExitCode;
+    use fvm_shared::event::{ActorEvent, Flags};
+    use num_traits::Zero;
+    use serde::Serialize;
+
+    use super::{event_entry, Actor};
+    use crate::{
+        Algorithm, AppendRowsParams, ConstructorParams, CreateDatasetParams, Dataset, DatasetId,
+        InputLimits, InputMatrix, ListModelParams, Method, ModelId, PredictWithModelParams,
+        ScaleParams, ScalerId, SparseMatrix, State, StorageUsage, TrainLinearRegressionGDParams,
+        TrainingInput, TrainingPermissionMode, EVENT_DATASET_DELETED, EVENT_MODEL_DELETED,
+        EVENT_PREDICTION_FEE_SET, MAX_SPARSE_COLS, MAX_SPARSE_ROWS,
+    };
+
+    const ALLOWED: Address = Address::new_id(100);
+    const OTHER: Address = Address::new_id(101);
+    const TRUSTED: Address = Address::new_id(102);
+
+    fn construct(params: ConstructorParams) -> MockRuntime {
+        let rt = MockRuntime {
+            receiver: Address::new_id(10),
+            ..Default::default()
+        };
+
+        rt.set_caller(*SYSTEM_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR);
+        rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
+
+        rt.call::<Actor>(
+            Method::Constructor as u64,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        )
+        .unwrap();
+        rt.verify();
+        rt.reset();
+
+        rt
+    }
+
+    /// Call a method from an account, which every method accepts.
+    fn call(
+        rt: &MockRuntime,
+        caller: Address,
+        method: Method,
+        params: &impl Serialize,
+    ) -> Result<Option<IpldBlock>, ExitCode> {
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, caller);
+        rt.expect_validate_caller_any();
+
+        let res = rt.call::<Actor>(method as u64, IpldBlock::serialize_cbor(params).unwrap());
+        rt.reset();
+
+        res.map_err(|e| e.exit_code())
+    }
+
+    /// Compute a scaler, which goes through the same checks as training but needs no syscall.
+    fn standardize(
+        rt: &MockRuntime,
+        caller: Address,
+        value: TokenAmount,
+    ) -> Result<ScalerId, ExitCode> {
+        if caller == SYSTEM_ACTOR_ADDR {
+            rt.set_caller(*SYSTEM_ACTOR_CODE_ID, caller);
+        } else {
+            rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, caller);
+        }
+        rt.set_received(value);
+        rt.expect_validate_caller_any();
+
+        let res = rt.call::<Actor>(
+            Method::Standardize as u64,
+            IpldBlock::serialize_cbor(&ScaleParams {
+                input_matrix: vec![vec![1, 2], vec![3, 4]].into(),
+            })
+            .unwrap(),
+        );
+        rt.reset();
+
+        res.map(|ret| ret.unwrap().deserialize().unwrap())
+            .map_err(|e| e.exit_code())
+    }
+
+    #[test]
+    fn allow_list_restricts_training() {
+        let rt = construct(ConstructorParams {
+            permission_mode: TrainingPermissionMode::AllowList(vec![ALLOWED]),
+            trusted_callers: vec![TRUSTED],
+            ..Default::default()
+        });
+
+        assert_eq!(standardize(&rt, ALLOWED, TokenAmount::zero()), Ok(0));
+        assert_eq!(
+            standardize(&rt, OTHER, TokenAmount::zero()),
+            Err(ExitCode::USR_FORBIDDEN)
+        );
+        assert_eq!(standardize(&rt, TRUSTED, TokenAmount::zero()), Ok(1));
+        assert_eq!(
+            standardize(&rt, SYSTEM_ACTOR_ADDR, TokenAmount::zero()),
+            Ok(2)
+        );
+
+        let st: State = rt.get_state();
+        assert_eq!(st.next_scaler_id, 3);
+    }
+
+    #[test]
+    fn training_requires_fee() {
+        let fee = TokenAmount::from_whole(1);
+        let rt = construct(ConstructorParams {
+            training_fee: fee.clone(),
+            trusted_callers: vec![TRUSTED],
+            ..Default::default()
+        });
+
+        assert_eq!(
+            standardize(&rt, OTHER, TokenAmount::from_atto(1)),
+            Err(ExitCode::USR_INSUFFICIENT_FUNDS)
+        );
+        assert_eq!(standardize(&rt, OTHER, fee), Ok(0));
+
+        // The system actor and the trusted callers train for free.
+        assert_eq!(standardize(&rt, TRUSTED, TokenAmount::zero()), Ok(1));
+        assert_eq!(
+            standardize(&rt, SYSTEM_ACTOR_ADDR, TokenAmount::zero()),
+            Ok(2)
+        );
+    }
+
+    #[test]
+    fn sparse_dimensions_are_capped() {
+        let sparse = |rows: u64, cols: u64| {
+            InputMatrix::Sparse(SparseMatrix {
+                rows,
+                cols,
+                row_offsets: vec![0; rows as usize + 1],
+                ..Default::default()
+            })
+        };
+
+        let rt = construct(Default::default());
+
+        let dense = Actor::dense(&rt, sparse(2, MAX_SPARSE_COLS)).unwrap();
+        assert_eq!(dense.len(), 2);
+
+        let err = Actor::dense(&rt, sparse(2, MAX_SPARSE_COLS + 1)).unwrap_err();
+        assert_eq!(err.exit_code(), ExitCode::USR_ILLEGAL_ARGUMENT);
+        let err = Actor::dense(&rt, sparse(MAX_SPARSE_ROWS + 1, 1)).unwrap_err();
+        assert_eq!(err.exit_code(), ExitCode::USR_ILLEGAL_ARGUMENT);
+    }
+
+    #[test]
+    fn limits_apply_to_every_caller() {
+        let rt = construct(ConstructorParams {
+            limits: Some(InputLimits {
+                max_rows: 2,
+                max_cols: 2,
+                max_bytes: 1_000,
+            }),
+            ..Default::default()
+        });
+
+        // A contract calling the actor isn't checked by the node before execution.
+        let scale = |input_matrix: Vec<Vec<i64>>| {
+            rt.set_caller(*EVM_ACTOR_CODE_ID, OTHER);
+            rt.expect_validate_caller_any();
+            let res = rt.call::<Actor>(
+                Method::Standardize as u64,
+                IpldBlock::serialize_cbor(&ScaleParams {
+                    input_matrix: input_matrix.into(),
+                })
+                .unwrap(),
+            );
+            rt.reset();
+            res.map(|_| ()).map_err(|e| e.exit_code())
+        };
+
+        assert_eq!(scale(vec![vec![1, 2], vec![3, 4]]), Ok(()));
+        assert_eq!(
+            scale(vec![vec![1, 2], vec![3, 4], vec![5, 6]]),
+            Err(ExitCode::USR_ILLEGAL_ARGUMENT)
+        );
+        assert_eq!(
+            scale(vec![vec![1, 2, 3], vec![4, 5, 6]]),
+            Err(ExitCode::USR_ILLEGAL_ARGUMENT)
+        );
+    }
+
+    #[test]
+    fn datasets_are_limited_and_trained_on() {
+        let rt = construct(ConstructorParams {
+            limits: Some(InputLimits {
+                max_rows: 3,
+                max_cols: 2,
+                max_bytes: 1_000,
+            }),
+            ..Default::default()
+        });
+
+        let dataset_id: DatasetId = call(
+            &rt,
+            OTHER,
+            Method::CreateDataset,
+            &CreateDatasetParams {
+                input_matrix: vec![vec![1, 2], vec![3, 4]].into(),
+            },
+        )
+        .unwrap()
+        .unwrap()
+        .deserialize()
+        .unwrap();
+
+        // The rows are within the limits, but the dataset would go over them.
+        let append = |rows: Vec<Vec<i64>>| {
+            call(
+                &rt,
+                OTHER,
+                Method::AppendRows,
+                &AppendRowsParams {
+                    dataset_id,
+                    rows: rows.into(),
+                },
+            )
+            .map(|_| ())
+        };
+        assert_eq!(
+            append(vec![vec![5, 6], vec![7, 8]]),
+            Err(ExitCode::USR_ILLEGAL_ARGUMENT)
+        );
+        assert_eq!(append(vec![vec![5, 6]]), Ok(()));
+
+        let dataset: Option<Dataset> = call(&rt, OTHER, Method::GetDataset, &dataset_id)
+            .unwrap()
+            .unwrap()
+            .deserialize()
+            .unwrap();
+        let bytes = dataset.unwrap().size();
+        let usage: StorageUsage = call(&rt, OTHER, Method::GetStorageUsage, &OTHER)
+            .unwrap()
+            .unwrap()
+            .deserialize()
+            .unwrap();
+        assert_eq!((usage.datasets, usage.bytes), (1, bytes));
+
+        let train = |input_matrix: TrainingInput| {
+            call(
+                &rt,
+                ALLOWED,
+                Method::TrainLinearRegressionGD,
+                &TrainLinearRegressionGDParams {
+                    input_matrix,
+                    labels: vec![1, 2, 3],
+                    learning_rate: 50_000,
+                    max_iterations: 10,
+                    ..Default::default()
+                },
+            )
+            .map(|_| ())
+        };
+        assert_eq!(train(TrainingInput::Dataset(dataset_id)), Ok(()));
+        assert_eq!(
+            train(TrainingInput::Dataset(dataset_id + 1)),
+            Err(ExitCode::USR_NOT_FOUND)
+        );
+
+        assert_eq!(
+            call(&rt, ALLOWED, Method::DeleteDataset, &dataset_id).map(|_| ()),
+            Err(ExitCode::USR_FORBIDDEN)
+        );
+        rt.expect_emitted_event(ActorEvent {
+            entries: vec![
+                event_entry(
+                    "$type",
+                    Flags::FLAG_INDEXED_ALL,
+                    EVENT_DATASET_DELETED.as_bytes().to_vec(),
+                ),
+                event_entry(
+                    "dataset_id",
+                    Flags::FLAG_INDEXED_ALL,
+                    dataset_id.to_be_bytes().to_vec(),
+                ),
+                event_entry("owner", Flags::FLAG_INDEXED_ALL, OTHER.to_bytes()),
+                event_entry("bytes", Flags::empty(), bytes.to_be_bytes().to_vec()),
+            ],
+        });
+        assert_eq!(
+            call(&rt, OTHER, Method::DeleteDataset, &dataset_id).map(|_| ()),
+            Ok(())
+        );
+        assert_eq!(
+            train(TrainingInput::Dataset(dataset_id)),
+            Err(ExitCode::USR_NOT_FOUND)
+        );
+
+        let st: State = rt.get_state();
+        assert_eq!(
+            st.storage,
+            State::new(&*rt.store, Default::default()).unwrap().storage
+        );
+    }
+
+    #[test]
+    fn prediction_requires_fee() {
+        let rt = construct(Default::default());
+        let fee = TokenAmount::from_atto(10);
+
+        rt.expect_emitted_event(ActorEvent {
+            entries: vec![
+                event_entry(
+                    "$type",
+                    Flags::FLAG_INDEXED_ALL,
+                    EVENT_PREDICTION_FEE_SET.as_bytes().to_vec(),
+                ),
+                event_entry(
+                    "model_id",
+                    Flags::FLAG_INDEXED_ALL,
+                    0u64.to_be_bytes().to_vec(),
+                ),
+                event_entry("owner", Flags::FLAG_INDEXED_ALL, OTHER.to_bytes()),
+                event_entry("caller", Flags::FLAG_INDEXED_ALL, OTHER.to_bytes()),
+                event_entry("fee", Flags::empty(), fee.atto().to_signed_bytes_be()),
+            ],
+        });
+        let model_id: ModelId = call(
+            &rt,
+            OTHER,
+            Method::ListModel,
+            &ListModelParams {
+                algorithm: Algorithm::LinearRegression,
+                model: vec![0; 10],
+                scaler: None,
+                prediction_fee: fee.clone(),
+                dataset_hash: Default::default(),
+                hyperparameters: Default::default(),
+            },
+        )
+        .unwrap()
+        .unwrap()
+        .deserialize()
+        .unwrap();
+
+        // Paying less than the fee is rejected before predicting, and nothing is recorded.
+        rt.set_received(TokenAmount::from_atto(9));
+        assert_eq!(
+            call(
+                &rt,
+                ALLOWED,
+                Method::PredictWithModel,
+                &PredictWithModelParams {
+                    model_id,
+                    input_matrix: vec![vec![1, 2]].into(),
+                },
+            ),
+            Err(ExitCode::USR_INSUFFICIENT_FUNDS)
+        );
+
+        let st: State = rt.get_state();
+        let model = st.get_model(rt.store(), model_id).unwrap().unwrap();
+        assert_eq!(model.predictions, 0);
+        assert!(model.revenue.is_zero());
+    }
+
+    #[test]
+    fn query_prediction_is_only_for_the_system() {
+        let rt = construct(Default::default());
+        let query = |caller: Address, model_id: ModelId| {
+            if caller == SYSTEM_ACTOR_ADDR {
+                rt.set_caller(*SYSTEM_ACTOR_CODE_ID, caller);
+            } else {
+                rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, caller);
+            }
+            rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
+
+            let res = rt.call::<Actor>(
+                Method::QueryPrediction as u64,
+                IpldBlock::serialize_cbor(&PredictWithModelParams {
+                    model_id,
+                    input_matrix: vec![vec![1, 2]].into(),
+                })
+                .unwrap(),
+            );
+            rt.verify();
+            rt.reset();
+
+            res.map(|_| ()).map_err(|e| e.exit_code())
+        };
+
+        // Accounts have to pay the fee with `PredictWithModel`.
+        assert_eq!(query(OTHER, 0), Err(ExitCode::USR_FORBIDDEN));
+        assert_eq!(query(SYSTEM_ACTOR_ADDR, 0), Err(ExitCode::USR_NOT_FOUND));
+    }
+
+    #[test]
+    fn storage_usage_covers_models_and_datasets() {
+        let rt = construct(Default::default());
+        let usage = || -> StorageUsage {
+            call(&rt, OTHER, Method::GetStorageUsage, &OTHER)
+                .unwrap()
+                .unwrap()
+                .deserialize()
+                .unwrap()
+        };
+        let deleted = |typ: &str, key: &str, id: u64, bytes: u64| ActorEvent {
+            entries: vec![
+                event_entry("$type", Flags::FLAG_INDEXED_ALL, typ.as_bytes().to_vec()),
+                event_entry(key, Flags::FLAG_INDEXED_ALL, id.to_be_bytes().to_vec()),
+                event_entry("owner", Flags::FLAG_INDEXED_ALL, OTHER.to_bytes()),
+                event_entry("bytes", Flags::empty(), bytes.to_be_bytes().to_vec()),
+            ],
+        };
+
+        rt.expect_emitted_event(ActorEvent {
+            entries: vec![
+                event_entry(
+                    "$type",
+                    Flags::FLAG_INDEXED_ALL,
+                    EVENT_PREDICTION_FEE_SET.as_bytes().to_vec(),
+                ),
+                event_entry(
+                    "model_id",
+                    Flags::FLAG_INDEXED_ALL,
+                    0u64.to_be_bytes().to_vec(),
+                ),
+                event_entry("owner", Flags::FLAG_INDEXED_ALL, OTHER.to_bytes()),
+                event_entry("caller", Flags::FLAG_INDEXED_ALL, OTHER.to_bytes()),
+                event_entry(
+                    "fee",
+                    Flags::empty(),
+                    TokenAmount::zero().atto().to_signed_bytes_be(),
+                ),
+            ],
+        });
+        let model_id: ModelId = call(
+            &rt,
+            OTHER,
+            Method::ListModel,
+            &ListModelParams {
+                algorithm: Algorithm::LinearRegression,
+                model: vec![0; 10],
+                scaler: None,
+                prediction_fee: TokenAmount::zero(),
+                dataset_hash: Default::default(),
+                hyperparameters: Default::default(),
+            },
+        )
+        .unwrap()
+        .unwrap()
+        .deserialize()
+        .unwrap();
+
+        let dataset_id: DatasetId = call(
+            &rt,
+            OTHER,
+            Method::CreateDataset,
+            &CreateDatasetParams {
+                input_matrix: vec![vec![1, 2], vec![3, 4]].into(),
+            },
+        )
+        .unwrap()
+        .unwrap()
+        .deserialize()
+        .unwrap();
+        let dataset_bytes = Dataset::new(OTHER, vec![vec![1, 2], vec![3, 4]])
+            .unwrap()
+            .size();
+
+        let u = usage();
+        assert_eq!((u.models, u.datasets, u.bytes), (1, 1, 10 + dataset_bytes));
+
+        rt.expect_emitted_event(deleted(EVENT_MODEL_DELETED, "model_id", model_id, 10));
+        call(&rt, OTHER, Method::DeleteModel, &model_id).unwrap();
+        let u = usage();
+        assert_eq!((u.models, u.datasets, u.bytes), (0, 1, dataset_bytes));
+
+        rt.expect_emitted_event(deleted(
+            EVENT_DATASET_DELETED,
+            "dataset_id",
+            dataset_id,
+            dataset_bytes,
+        ));
+        call(&rt, OTHER, Method::DeleteDataset, &dataset_id).unwrap();
+        assert_eq!(
+            usage(),
+            StorageUsage {
+                owner: OTHER,
+                ..Default::default()
+            }
+        );
     }
 }
@@ -1,7 +1,6 @@
 // Copyright 2021-2023 Protocol Labs
 // SPDX-License-Identifier: Apache-2.0, MIT
-#[cfg(feature = "fil-actor")]
 mod actor;
 mod shared;
 
-pub use shared::*;
\ No newline at end of file
+pub use shared::*;
@@ -1,53 +1,1830 @@
 // Copyright 2021-2023 Protocol Labs
 // SPDX-License-Identifier: Apache-2.0, MIT
+use std::collections::BTreeMap;
+
+use cid::Cid;
+use fvm_ipld_amt::Amt;
+use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::tuple::{Deserialize_tuple, Serialize_tuple};
+use fvm_ipld_hamt::{BytesKey, Hamt};
+use fvm_shared::{address::Address, clock::ChainEpoch, econ::TokenAmount, METHOD_CONSTRUCTOR};
 use num_derive::FromPrimitive;
+use serde::{Deserialize, Serialize};
+
+pub use ipc_types::{InputMatrix, SparseMatrix, TrainingInput};
 
 pub const MACHINELEARNING_ACTOR_NAME: &str = "machinelearning";
 
+// the default bitwidth of the scalers AMT
+pub const SCALERS_AMT_BITWIDTH: u32 = 3;
+
+// the default bitwidth of the listed models AMT
+pub const MODELS_AMT_BITWIDTH: u32 = 3;
+
+// the default bitwidth of the datasets AMT
+pub const DATASETS_AMT_BITWIDTH: u32 = 3;
+
+// the default bitwidth of the storage usage HAMT
+pub const STORAGE_HAMT_BITWIDTH: u32 = 5;
+
+/// Maximum number of rows of a sparse input matrix, which is expanded into a dense one before
+/// it's passed to the syscalls. It's the default number of rows the chain accepts.
+pub const MAX_SPARSE_ROWS: u64 = 10_000;
+
+/// Maximum number of columns of a sparse input matrix, for the same reason as [MAX_SPARSE_ROWS].
+pub const MAX_SPARSE_COLS: u64 = 1_000;
+
+/// Features and labels are fixed-point numbers with two decimals, e.g. 150 stands for 1.5.
+pub const FIXED_POINT_SCALE: i64 = 100;
+
+/// Version of the training syscalls, recorded with every listed model. It has to be bumped
+/// whenever the syscalls start producing different models from the same data.
+pub const ALGORITHM_VERSION: u64 = 1;
+
+/// Controls who can call the `Train*` methods, which store arbitrarily large models in the state.
+///
+/// The system actor and the trusted callers are always allowed to train, without paying the fee.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TrainingPermissionMode {
+    /// No restriction, everyone can train
+    Unrestricted,
+    /// Only whitelisted addresses can train
+    AllowList(Vec<Address>),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct State {
+    pub permission_mode: TrainingPermissionMode,
+    /// Minimum value that has to be sent along with every training call; it's kept by the actor.
+    pub training_fee: TokenAmount,
+    /// Actors which train on behalf of others, e.g. to re-execute disputed jobs.
+    pub trusted_callers: Vec<Address>,
+    /// The AMT root cid of the scalers, indexed by [ScalerId].
+    pub scalers: Cid,
+    /// The id the next computed scaler will get.
+    pub next_scaler_id: ScalerId,
+    /// The AMT root cid of the models listed for paid predictions, indexed by [ModelId].
+    pub models: Cid,
+    /// The id the next listed model will get.
+    pub next_model_id: ModelId,
+    /// The HAMT root cid of the storage used by the listed models and the datasets of each
+    /// owner, keyed by the ID address of the owner.
+    pub storage: Cid,
+    /// The AMT root cid of the stored datasets, indexed by [DatasetId].
+    pub datasets: Cid,
+    /// The id the next created dataset will get.
+    pub next_dataset_id: DatasetId,
+    /// Limits on the inputs, checked on every call. Missing from the states created before
+    /// they were added, which are decoded as having none.
+    #[serde(default)]
+    pub limits: Option<InputLimits>,
+}
+
+impl State {
+    pub fn new<BS: Blockstore>(store: &BS, params: ConstructorParams) -> anyhow::Result<Self> {
+        let empty_scalers_cid =
+            match Amt::<(), _>::new_with_bit_width(store, SCALERS_AMT_BITWIDTH).flush() {
+                Ok(cid) => cid,
+                Err(e) => {
+                    return Err(anyhow::anyhow!(
+                        "machinelearning actor failed to create empty Amt: {}",
+                        e
+                    ))
+                }
+            };
+
+        let empty_models_cid =
+            match Amt::<(), _>::new_with_bit_width(store, MODELS_AMT_BITWIDTH).flush() {
+                Ok(cid) => cid,
+                Err(e) => {
+                    return Err(anyhow::anyhow!(
+                        "machinelearning actor failed to create empty Amt: {}",
+                        e
+                    ))
+                }
+            };
+
+        let empty_datasets_cid =
+            match Amt::<(), _>::new_with_bit_width(store, DATASETS_AMT_BITWIDTH).flush() {
+                Ok(cid) => cid,
+                Err(e) => {
+                    return Err(anyhow::anyhow!(
+                        "machinelearning actor failed to create empty Amt: {}",
+                        e
+                    ))
+                }
+            };
+
+        let empty_storage_cid =
+            match Hamt::<_, StorageUsage>::new_with_bit_width(store, STORAGE_HAMT_BITWIDTH).flush()
+            {
+                Ok(cid) => cid,
+                Err(e) => {
+                    return Err(anyhow::anyhow!(
+                        "machinelearning actor failed to create empty Hamt: {}",
+                        e
+                    ))
+                }
+            };
+
+        Ok(Self {
+            permission_mode: params.permission_mode,
+            training_fee: params.training_fee,
+            trusted_callers: params.trusted_callers,
+            scalers: empty_scalers_cid,
+            next_scaler_id: 0,
+            models: empty_models_cid,
+            next_model_id: 0,
+            storage: empty_storage_cid,
+            datasets: empty_datasets_cid,
+            next_dataset_id: 0,
+            limits: params.limits,
+        })
+    }
+
+    /// The storage used by the listed models and the datasets of `owner`, which has to be an ID
+    /// address.
+    pub fn storage_usage<BS: Blockstore>(
+        &self,
+        store: &BS,
+        owner: &Address,
+    ) -> anyhow::Result<StorageUsage> {
+        let storage = self.load_storage(store)?;
+
+        match storage.get(&BytesKey::from(owner.to_bytes())) {
+            Ok(Some(usage)) => Ok(usage.clone()),
+            Ok(None) => Ok(StorageUsage {
+                owner: *owner,
+                ..Default::default()
+            }),
+            Err(e) => Err(anyhow::anyhow!(
+                "failed to get the storage usage of {}, error: {}",
+                owner,
+                e
+            )),
+        }
+    }
+
+    /// Account for a model or a dataset of `bytes` stored by `owner`.
+    pub fn add_storage<BS: Blockstore>(
+        &mut self,
+        store: &BS,
+        owner: &Address,
+        stored: Stored,
+        bytes: u64,
+    ) -> anyhow::Result<()> {
+        let mut usage = self.storage_usage(store, owner)?;
+        match stored {
+            Stored::Model => usage.models += 1,
+            Stored::Dataset => usage.datasets += 1,
+        }
+        usage.bytes += bytes;
+        self.save_storage(store, usage)
+    }
+
+    /// Account for `bytes` more of a dataset of `owner`, e.g. after appending to it.
+    pub fn grow_storage<BS: Blockstore>(
+        &mut self,
+        store: &BS,
+        owner: &Address,
+        bytes: u64,
+    ) -> anyhow::Result<()> {
+        let mut usage = self.storage_usage(store, owner)?;
+        usage.bytes += bytes;
+        self.save_storage(store, usage)
+    }
+
+    /// Release the storage of a deleted model or dataset of `owner`, forgetting owners with
+    /// nothing left.
+    pub fn remove_storage<BS: Blockstore>(
+        &mut self,
+        store: &BS,
+        owner: &Address,
+        stored: Stored,
+        bytes: u64,
+    ) -> anyhow::Result<()> {
+        let mut usage = self.storage_usage(store, owner)?;
+        match stored {
+            Stored::Model => usage.models = usage.models.saturating_sub(1),
+            Stored::Dataset => usage.datasets = usage.datasets.saturating_sub(1),
+        }
+        usage.bytes = usage.bytes.saturating_sub(bytes);
+        self.save_storage(store, usage)
+    }
+
+    /// Rebuild the storage usage of every owner from the listed models and the datasets, e.g.
+    /// for the ones stored before the storage was accounted for.
+    pub fn recount_storage<BS: Blockstore>(&mut self, store: &BS) -> anyhow::Result<()> {
+        let models = match Amt::<ListedModel, _>::load(&self.models, store) {
+            Ok(v) => v,
+            Err(e) => {
+                return Err(anyhow::anyhow!(
+                    "failed to load models from AMT cid {}, error: {}",
+                    self.models,
+                    e
+                ));
+            }
+        };
+
+        let mut usages = BTreeMap::<Vec<u8>, StorageUsage>::new();
+        models
+            .for_each(|_, model| {
+                let key = model.owner.to_bytes();
+                let usage = usages.entry(key).or_insert_with(|| StorageUsage {
+                    owner: model.owner,
+                    ..Default::default()
+                });
+                usage.models += 1;
+                usage.bytes += model.model.len() as u64;
+                Ok(())
+            })
+            .map_err(|e| anyhow::anyhow!("failed to iterate models, error: {}", e))?;
+
+        let datasets = match Amt::<Dataset, _>::load(&self.datasets, store) {
+            Ok(v) => v,
+            Err(e) => {
+                return Err(anyhow::anyhow!(
+                    "failed to load datasets from AMT cid {}, error: {}",
+                    self.datasets,
+                    e
+                ));
+            }
+        };
+
+        datasets
+            .for_each(|_, dataset| {
+                let key = dataset.owner.to_bytes();
+                let usage = usages.entry(key).or_insert_with(|| StorageUsage {
+                    owner: dataset.owner,
+                    ..Default::default()
+                });
+                usage.datasets += 1;
+                usage.bytes += dataset.size();
+                Ok(())
+            })
+            .map_err(|e| anyhow::anyhow!("failed to iterate datasets, error: {}", e))?;
+
+        let mut storage = Hamt::<_, StorageUsage>::new_with_bit_width(store, STORAGE_HAMT_BITWIDTH);
+        for (key, usage) in usages {
+            storage
+                .set(BytesKey::from(key), usage)
+                .map_err(|e| anyhow::anyhow!("failed to set the storage usage, error: {}", e))?;
+        }
+        self.storage = storage
+            .flush()
+            .map_err(|e| anyhow::anyhow!("failed to save the storage usage, error: {}", e))?;
+
+        Ok(())
+    }
+
+    fn load_storage<'a, BS: Blockstore>(
+        &self,
+        store: &'a BS,
+    ) -> anyhow::Result<Hamt<&'a BS, StorageUsage>> {
+        Hamt::load_with_bit_width(&self.storage, store, STORAGE_HAMT_BITWIDTH).map_err(|e| {
+            anyhow::anyhow!(
+                "failed to load the storage usage from HAMT cid {}, error: {}",
+                self.storage,
+                e
+            )
+        })
+    }
+
+    fn save_storage<BS: Blockstore>(
+        &mut self,
+        store: &BS,
+        usage: StorageUsage,
+    ) -> anyhow::Result<()> {
+        let mut storage = self.load_storage(store)?;
+        let key = BytesKey::from(usage.owner.to_bytes());
+
+        let res = if usage.models == 0 && usage.datasets == 0 {
+            storage.delete(&key).map(|_| ())
+        } else {
+            storage.set(key, usage).map(|_| ())
+        };
+        res.map_err(|e| anyhow::anyhow!("failed to update the storage usage, error: {}", e))?;
+
+        self.storage = storage
+            .flush()
+            .map_err(|e| anyhow::anyhow!("failed to save the storage usage, error: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Load the scalers array from the AMT root cid and return the scaler with the given id.
+    pub fn get_scaler<BS: Blockstore>(
+        &self,
+        store: &BS,
+        scaler_id: ScalerId,
+    ) -> anyhow::Result<Option<Scaler>> {
+        let scalers = match Amt::<Scaler, _>::load(&self.scalers, &store) {
+            Ok(v) => v,
+            Err(e) => {
+                return Err(anyhow::anyhow!(
+                    "failed to load scalers from AMT cid {}, error: {}",
+                    self.scalers,
+                    e
+                ));
+            }
+        };
+
+        match scalers.get(scaler_id) {
+            Ok(Some(v)) => Ok(Some(v.clone())),
+            Ok(None) => Ok(None),
+            Err(err) => Err(anyhow::anyhow!(
+                "failed to get scaler {}, error: {}",
+                scaler_id,
+                err
+            )),
+        }
+    }
+
+    /// Load the models array from the AMT root cid and return the model with the given id.
+    pub fn get_model<BS: Blockstore>(
+        &self,
+        store: &BS,
+        model_id: ModelId,
+    ) -> anyhow::Result<Option<ListedModel>> {
+        let models = match Amt::<ListedModel, _>::load(&self.models, &store) {
+            Ok(v) => v,
+            Err(e) => {
+                return Err(anyhow::anyhow!(
+                    "failed to load models from AMT cid {}, error: {}",
+                    self.models,
+                    e
+                ));
+            }
+        };
+
+        match models.get(model_id) {
+            Ok(Some(v)) => Ok(Some(v.clone())),
+            Ok(None) => Ok(None),
+            Err(err) => Err(anyhow::anyhow!(
+                "failed to get model {}, error: {}",
+                model_id,
+                err
+            )),
+        }
+    }
+
+    /// Load the datasets array from the AMT root cid and return the dataset with the given id.
+    pub fn get_dataset<BS: Blockstore>(
+        &self,
+        store: &BS,
+        dataset_id: DatasetId,
+    ) -> anyhow::Result<Option<Dataset>> {
+        let datasets = match Amt::<Dataset, _>::load(&self.datasets, &store) {
+            Ok(v) => v,
+            Err(e) => {
+                return Err(anyhow::anyhow!(
+                    "failed to load datasets from AMT cid {}, error: {}",
+                    self.datasets,
+                    e
+                ));
+            }
+        };
+
+        match datasets.get(dataset_id) {
+            Ok(Some(v)) => Ok(Some(v.clone())),
+            Ok(None) => Ok(None),
+            Err(err) => Err(anyhow::anyhow!(
+                "failed to get dataset {}, error: {}",
+                dataset_id,
+                err
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct ConstructorParams {
+    pub permission_mode: TrainingPermissionMode,
+    pub training_fee: TokenAmount,
+    pub trusted_callers: Vec<Address>,
+    #[serde(default)]
+    pub limits: Option<InputLimits>,
+}
+
+impl Default for ConstructorParams {
+    fn default() -> Self {
+        Self {
+            permission_mode: TrainingPermissionMode::Unrestricted,
+            training_fee: TokenAmount::default(),
+            trusted_callers: Vec::new(),
+            limits: None,
+        }
+    }
+}
+
+/// Limits on the input matrices of the actor, enforced by the actor itself so that they apply
+/// to every caller, including contracts and the jobs actor. They mirror the limits of the
+/// genesis, which the node also checks before executing a message sent to the actor.
+///
+/// The stored datasets are held to the same limits, so that training on one costs no more than
+/// training on a matrix sent with the message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct InputLimits {
+    /// Maximum number of rows of an input matrix.
+    pub max_rows: u64,
+    /// Maximum number of columns of an input matrix.
+    pub max_cols: u64,
+    /// Maximum size of a stored dataset, in bytes.
+    pub max_bytes: u64,
+}
+
+impl InputLimits {
+    /// Check the dimensions of an input matrix.
+    pub fn check(&self, rows: u64, cols: u64) -> Result<(), String> {
+        if rows > self.max_rows || cols > self.max_cols {
+            return Err(format!(
+                "the input matrix is {}x{}; the maximum is {}x{}",
+                rows, cols, self.max_rows, self.max_cols
+            ));
+        }
+        Ok(())
+    }
+
+    /// Check the dimensions and the size of a dataset, e.g. after appending to it.
+    pub fn check_dataset(&self, dataset: &Dataset) -> Result<(), String> {
+        self.check(dataset.num_rows() as u64, dataset.num_cols() as u64)?;
+        let bytes = dataset.size();
+        if bytes > self.max_bytes {
+            return Err(format!(
+                "the dataset is {} bytes; the maximum is {}",
+                bytes, self.max_bytes
+            ));
+        }
+        Ok(())
+    }
+}
+
+pub type ScalerId = u64;
+
+/// Per-column scaling parameters computed on-chain from a training set, so that exactly the
+/// same transformation can be applied to the features at training and at prediction time.
+///
+/// All values are in the same fixed-point units as the features.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Scaler {
+    /// Subtract the mean and divide by the standard deviation of each column.
+    Standard { mean: Vec<i64>, std_dev: Vec<i64> },
+    /// Map the range between the minimum and the maximum of each column to `[0, 1]`.
+    MinMax { min: Vec<i64>, max: Vec<i64> },
+}
+
+impl Scaler {
+    /// Compute the mean and the (population) standard deviation of each column.
+    pub fn standard(input_matrix: &[Vec<i64>]) -> Result<Self, String> {
+        let cols = num_cols(input_matrix)?;
+        let n = input_matrix.len() as i128;
+        let mut mean = Vec::with_capacity(cols);
+        let mut std_dev = Vec::with_capacity(cols);
+
+        for c in 0..cols {
+            let sum: i128 = input_matrix.iter().map(|row| row[c] as i128).sum();
+            let m = div_round(sum, n);
+            let var: i128 = input_matrix
+                .iter()
+                .map(|row| (row[c] as i128 - m).pow(2))
+                .sum::<i128>()
+                / n;
+            mean.push(m as i64);
+            std_dev.push(isqrt(var) as i64);
+        }
+
+        Ok(Self::Standard { mean, std_dev })
+    }
+
+    /// Compute the minimum and the maximum of each column.
+    pub fn min_max(input_matrix: &[Vec<i64>]) -> Result<Self, String> {
+        let cols = num_cols(input_matrix)?;
+        let mut min = vec![i64::MAX; cols];
+        let mut max = vec![i64::MIN; cols];
+
+        for row in input_matrix {
+            for (c, x) in row.iter().enumerate() {
+                min[c] = min[c].min(*x);
+                max[c] = max[c].max(*x);
+            }
+        }
+
+        Ok(Self::MinMax { min, max })
+    }
+
+    /// Number of features the scaler was computed for.
+    pub fn num_cols(&self) -> usize {
+        match self {
+            Self::Standard { mean, .. } => mean.len(),
+            Self::MinMax { min, .. } => min.len(),
+        }
+    }
+
+    /// Apply the scaling to every row. Constant columns are mapped to zero.
+    pub fn transform(&self, input_matrix: Vec<Vec<i64>>) -> Result<Vec<Vec<i64>>, String> {
+        let (offsets, ranges) = match self {
+            Self::Standard { mean, std_dev } => (mean, std_dev.clone()),
+            Self::MinMax { min, max } => (
+                min,
+                min.iter()
+                    .zip(max)
+                    .map(|(min, max)| max.saturating_sub(*min))
+                    .collect(),
+            ),
+        };
+
+        input_matrix
+            .into_iter()
+            .enumerate()
+            .map(|(i, row)| {
+                if row.len() != offsets.len() {
+                    return Err(format!(
+                        "row {} has {} columns; the scaler expects {}",
+                        i,
+                        row.len(),
+                        offsets.len()
+                    ));
+                }
+                Ok(row
+                    .iter()
+                    .zip(offsets.iter().zip(ranges.iter()))
+                    .map(|(x, (offset, range))| {
+                        if *range == 0 {
+                            0
+                        } else {
+                            let scaled = div_round(
+                                (*x as i128 - *offset as i128) * FIXED_POINT_SCALE as i128,
+                                *range as i128,
+                            );
+                            scaled.clamp(i64::MIN as i128, i64::MAX as i128) as i64
+                        }
+                    })
+                    .collect())
+            })
+            .collect()
+    }
+}
+
+fn num_cols(input_matrix: &[Vec<i64>]) -> Result<usize, String> {
+    let cols = match input_matrix.first() {
+        Some(row) if !row.is_empty() => row.len(),
+        _ => return Err("the input matrix is empty".to_owned()),
+    };
+    if input_matrix.iter().any(|row| row.len() != cols) {
+        return Err("the rows of the input matrix have different lengths".to_owned());
+    }
+    Ok(cols)
+}
+
+/// Turn the rows of a matrix into its columns, e.g. the targets of each row into the labels
+/// of each output.
+pub fn transpose(matrix: &[Vec<i64>]) -> Result<Vec<Vec<i64>>, String> {
+    let cols = num_cols(matrix)?;
+    Ok((0..cols)
+        .map(|c| matrix.iter().map(|row| row[c]).collect())
+        .collect())
+}
+
+/// Integer division rounding half away from zero.
+fn div_round(a: i128, b: i128) -> i128 {
+    let q = a / b;
+    let r = a % b;
+    if 2 * r.abs() >= b.abs() {
+        q + a.signum() * b.signum()
+    } else {
+        q
+    }
+}
+
+/// Multiply and divide, rounding half away from zero and saturating on overflow.
+fn mul_div(a: i128, b: i128, c: i128) -> i128 {
+    div_round(a.saturating_mul(b), c)
+}
+
+/// Integer square root, rounding down.
+fn isqrt(n: i128) -> i128 {
+    if n < 2 {
+        return n.max(0);
+    }
+    let mut x = n;
+    let mut y = (x + 1) / 2;
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+    x
+}
+
+/// Fixed-point scale of the coefficients of an autoregressive model, e.g. 500000 stands for 0.5.
+pub const AR_COEFFICIENT_SCALE: i64 = 1_000_000;
+
+/// Maximum lag order of an autoregressive model.
+pub const MAX_AR_LAG_ORDER: u64 = 64;
+
+/// Maximum number of values forecast by a single prediction.
+pub const MAX_AR_FORECAST_STEPS: u64 = 1_000;
+
+/// Autoregressive model of a time series: every value is forecast as the mean plus the weighted
+/// deviations from the mean of the values preceding it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct ArModel {
+    /// Mean of the training series, in the units of the series.
+    pub mean: i64,
+    /// Weight of the value `i + 1` steps back, scaled by [AR_COEFFICIENT_SCALE].
+    pub coefficients: Vec<i64>,
+}
+
+impl ArModel {
+    /// Fit the coefficients with the Yule-Walker equations, solved by the Levinson-Durbin
+    /// recursion in integer arithmetic, so every validator gets exactly the same model.
+    pub fn fit(series: &[i64], lag_order: u64) -> Result<Self, String> {
+        if lag_order == 0 || lag_order > MAX_AR_LAG_ORDER {
+            return Err(format!(
+                "the lag order has to be between 1 and {MAX_AR_LAG_ORDER}; got {lag_order}"
+            ));
+        }
+        let p = lag_order as usize;
+        if series.len() <= p {
+            return Err(format!(
+                "the series has {} values; lag order {} needs at least {}",
+                series.len(),
+                lag_order,
+                p + 1
+            ));
+        }
+
+        let n = series.len() as i128;
+        let mean = div_round(series.iter().map(|x| *x as i128).sum(), n);
+        let dev: Vec<i128> = series.iter().map(|x| *x as i128 - mean).collect();
+
+        // Biased autocovariances, which keep the recursion stable.
+        let r: Vec<i128> = (0..=p)
+            .map(|k| {
+                let sum = dev[k..].iter().zip(&dev).fold(0i128, |acc, (a, b)| {
+                    acc.saturating_add(a.saturating_mul(*b))
+                });
+                div_round(sum, n)
+            })
+            .collect();
+
+        let scale = AR_COEFFICIENT_SCALE as i128;
+        let mut phi = vec![0i128; p];
+        // The prediction error; zero for a constant series, which is forecast as its mean.
+        let mut err = r[0];
+
+        for k in 0..p {
+            if err <= 0 {
+                break;
+            }
+            let acc = (0..k).fold(r[k + 1], |acc, j| {
+                acc.saturating_sub(mul_div(phi[j], r[k - j], scale))
+            });
+            let lambda = mul_div(acc, scale, err).clamp(-scale, scale);
+            let prev = phi.clone();
+            phi[k] = lambda;
+            for j in 0..k {
+                phi[j] = prev[j] - mul_div(lambda, prev[k - 1 - j], scale);
+            }
+            err -= mul_div(mul_div(err, lambda, scale), lambda, scale);
+        }
+
+        Ok(Self {
+            mean: mean as i64,
+            coefficients: phi
+                .into_iter()
+                .map(|c| c.clamp(i64::MIN as i128, i64::MAX as i128) as i64)
+                .collect(),
+        })
+    }
+
+    /// Forecast the `steps` values following `history`, feeding every forecast back as an input
+    /// of the next one.
+    pub fn forecast(&self, history: &[i64], steps: u64) -> Result<Vec<i64>, String> {
+        let p = self.coefficients.len();
+        if history.len() < p {
+            return Err(format!(
+                "the history has {} values; the model needs the last {}",
+                history.len(),
+                p
+            ));
+        }
+        if steps == 0 || steps > MAX_AR_FORECAST_STEPS {
+            return Err(format!(
+                "the number of steps has to be between 1 and {MAX_AR_FORECAST_STEPS}; got {steps}"
+            ));
+        }
+
+        let scale = AR_COEFFICIENT_SCALE as i128;
+        let mean = self.mean as i128;
+        let mut window: Vec<i128> = history[history.len() - p..]
+            .iter()
+            .map(|x| *x as i128)
+            .collect();
+        let mut forecast = Vec::with_capacity(steps as usize);
+
+        for _ in 0..steps {
+            let dev = self
+                .coefficients
+                .iter()
+                .zip(window.iter().rev())
+                .fold(0i128, |acc, (c, x)| {
+                    acc.saturating_add(mul_div(*c as i128, x - mean, scale))
+                });
+            let next = mean
+                .saturating_add(dev)
+                .clamp(i64::MIN as i128, i64::MAX as i128);
+            forecast.push(next as i64);
+            window.push(next);
+            window.remove(0);
+        }
+
+        Ok(forecast)
+    }
+}
+
+/// Fixed-point scale of the weights and the learning rate of gradient descent, e.g. 50000
+/// stands for 0.05.
+pub const GD_SCALE: i64 = 1_000_000;
+
+/// Linear regression trained with batch gradient descent in integer arithmetic, so that the cost
+/// of training is bounded by the number of iterations and metered like any other actor code.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct LinearModel {
+    /// Weight of each feature, scaled by [GD_SCALE].
+    pub weights: Vec<i64>,
+    /// Intercept in the fixed-point units of the labels, scaled by [GD_SCALE].
+    pub bias: i64,
+}
+
+/// How gradient descent went, so callers can tell whether the model is usable.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct Convergence {
+    /// Number of steps taken.
+    pub iterations: u64,
+    /// Mean squared error on the training set after the last step, in fixed-point units.
+    pub final_loss: i64,
+    /// Whether a step improved the loss by at most the tolerance before the iteration cap.
+    pub converged: bool,
+}
+
+impl LinearModel {
+    /// Minimize the mean squared error, starting from zero weights, until a step improves the
+    /// loss by at most `tolerance` or `max_iterations` steps are taken.
+    pub fn fit_gradient_descent(
+        input_matrix: &[Vec<i64>],
+        labels: &[i64],
+        learning_rate: u64,
+        max_iterations: u64,
+        tolerance: u64,
+    ) -> Result<(Self, Convergence), String> {
+        let cols = num_cols(input_matrix)?;
+        if labels.len() != input_matrix.len() {
+            return Err(format!(
+                "there are {} labels for {} rows",
+                labels.len(),
+                input_matrix.len()
+            ));
+        }
+        if learning_rate == 0 || max_iterations == 0 {
+            return Err("the learning rate and the iteration cap have to be positive".into());
+        }
+
+        let scale = GD_SCALE as i128;
+        let learning_rate = learning_rate as i128;
+        let tolerance = (tolerance as i128).saturating_mul(scale);
+
+        let mut model = Self {
+            weights: vec![0; cols],
+            bias: 0,
+        };
+        let mut loss = model.loss(input_matrix, labels);
+        let mut iterations = 0;
+        let mut converged = false;
+
+        while iterations < max_iterations {
+            model.step(input_matrix, labels, learning_rate);
+            iterations += 1;
+
+            let next = model.loss(input_matrix, labels);
+            let improvement = loss.saturating_sub(next).saturating_abs();
+            loss = next;
+
+            if improvement <= tolerance {
+                converged = true;
+                break;
+            }
+        }
+
+        let convergence = Convergence {
+            iterations,
+            final_loss: clamp_i64(div_round(loss, scale)),
+            converged,
+        };
+
+        Ok((model, convergence))
+    }
+
+    /// Predict a label for every row.
+    pub fn predict(&self, input_matrix: &[Vec<i64>]) -> Result<Vec<i64>, String> {
+        input_matrix
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                if row.len() != self.weights.len() {
+                    return Err(format!(
+                        "row {} has {} columns; the model expects {}",
+                        i,
+                        row.len(),
+                        self.weights.len()
+                    ));
+                }
+                Ok(clamp_i64(div_round(self.output(row), GD_SCALE as i128)))
+            })
+            .collect()
+    }
+
+    /// The prediction for a row, scaled by [GD_SCALE] to keep the precision of the weights.
+    fn output(&self, row: &[i64]) -> i128 {
+        row.iter()
+            .zip(&self.weights)
+            .fold(self.bias as i128, |acc, (x, w)| {
+                acc.saturating_add((*x as i128).saturating_mul(*w as i128))
+            })
+    }
+
+    /// The error of the prediction of every row, scaled by [GD_SCALE].
+    fn errors(&self, input_matrix: &[Vec<i64>], labels: &[i64]) -> Vec<i128> {
+        input_matrix
+            .iter()
+            .zip(labels)
+            .map(|(row, y)| {
+                self.output(row)
+                    .saturating_sub((*y as i128).saturating_mul(GD_SCALE as i128))
+            })
+            .collect()
+    }
+
+    /// Mean squared error in fixed-point units, scaled by [GD_SCALE].
+    fn loss(&self, input_matrix: &[Vec<i64>], labels: &[i64]) -> i128 {
+        let errors = self.errors(input_matrix, labels);
+        let sum = errors
+            .iter()
+            .fold(0i128, |acc, e| acc.saturating_add(e.saturating_mul(*e)));
+        div_round(
+            sum,
+            errors.len() as i128 * FIXED_POINT_SCALE as i128 * GD_SCALE as i128,
+        )
+    }
+
+    /// Move the weights against the gradient of the mean squared error.
+    fn step(&mut self, input_matrix: &[Vec<i64>], labels: &[i64], learning_rate: i128) {
+        let errors = self.errors(input_matrix, labels);
+        let n = errors.len() as i128 * GD_SCALE as i128;
+        let rate = learning_rate.saturating_mul(2);
+
+        for (j, w) in self.weights.iter_mut().enumerate() {
+            let grad = input_matrix
+                .iter()
+                .zip(&errors)
+                .fold(0i128, |acc, (row, e)| {
+                    acc.saturating_add(e.saturating_mul(row[j] as i128))
+                });
+            let delta = div_round(
+                rate.saturating_mul(grad),
+                n * (FIXED_POINT_SCALE * FIXED_POINT_SCALE) as i128,
+            );
+            *w = clamp_i64((*w as i128).saturating_sub(delta));
+        }
+
+        let grad = errors.iter().fold(0i128, |acc, e| acc.saturating_add(*e));
+        let delta = div_round(rate.saturating_mul(grad), n);
+        self.bias = clamp_i64((self.bias as i128).saturating_sub(delta));
+    }
+}
+
+fn clamp_i64(x: i128) -> i64 {
+    x.clamp(i64::MIN as i128, i64::MAX as i128) as i64
+}
+
 #[derive(Default, Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct TrainLinearRegressionParams {
-    pub input_matrix: Vec<Vec<i64>>,
+    /// The input matrix, or the id of a stored dataset.
+    pub input_matrix: TrainingInput,
     pub labels: Vec<i64>,
+    /// Scaler applied to the input matrix before training.
+    pub scaler: Option<ScalerId>,
 }
 
 #[derive(Default, Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct PredictLinearRegressionParams {
-    pub input_matrix: Vec<Vec<i64>>,
+    pub input_matrix: InputMatrix,
     pub model: Vec<u8>,
+    /// Scaler applied to the input matrix before predicting; it has to be the one used in training.
+    pub scaler: Option<ScalerId>,
+}
+
+/// Train a linear regression for each column of the labels, to predict vectors of targets.
+#[derive(Default, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct TrainMultiOutputLinearRegressionParams {
+    /// The input matrix, or the id of a stored dataset.
+    pub input_matrix: TrainingInput,
+    /// One row of targets for each row of the input matrix, all with the same number of columns.
+    pub labels: Vec<Vec<i64>>,
+    /// Scaler applied to the input matrix before training.
+    pub scaler: Option<ScalerId>,
+}
+
+#[derive(Default, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct PredictMultiOutputLinearRegressionParams {
+    pub input_matrix: InputMatrix,
+    /// One model for each target column, as returned by `TrainMultiOutputLinearRegression`.
+    pub models: Vec<Vec<u8>>,
+    /// Scaler applied to the input matrix before predicting; it has to be the one used in training.
+    pub scaler: Option<ScalerId>,
+}
+
+#[derive(Default, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct TrainLinearRegressionGDParams {
+    /// The input matrix, or the id of a stored dataset.
+    pub input_matrix: TrainingInput,
+    pub labels: Vec<i64>,
+    /// Scaler applied to the input matrix before training.
+    pub scaler: Option<ScalerId>,
+    /// Step size, scaled by [GD_SCALE].
+    pub learning_rate: u64,
+    /// Training stops after this many steps, converged or not.
+    pub max_iterations: u64,
+    /// Training stops once a step improves the loss by at most this much, in fixed-point units.
+    pub tolerance: u64,
+}
+
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct TrainLinearRegressionGDReturn {
+    pub model: LinearModel,
+    pub convergence: Convergence,
+}
+
+#[derive(Default, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct PredictLinearRegressionGDParams {
+    pub input_matrix: InputMatrix,
+    pub model: LinearModel,
+    /// Scaler applied to the input matrix before predicting; it has to be the one used in training.
+    pub scaler: Option<ScalerId>,
 }
 
 #[derive(Default, Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct TrainLogisticRegressionParams {
-    pub input_matrix: Vec<Vec<i64>>,
+    /// The input matrix, or the id of a stored dataset.
+    pub input_matrix: TrainingInput,
     pub labels: Vec<i64>,
+    /// Scaler applied to the input matrix before training.
+    pub scaler: Option<ScalerId>,
 }
 
 #[derive(Default, Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct PredictLogisticRegressionParams {
-    pub input_matrix: Vec<Vec<i64>>,
+    pub input_matrix: InputMatrix,
     pub model: Vec<u8>,
+    /// Scaler applied to the input matrix before predicting; it has to be the one used in training.
+    pub scaler: Option<ScalerId>,
 }
 
 #[derive(Default, Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct TrainKNNRegressionParams {
-    pub input_matrix: Vec<Vec<i64>>,
+    /// The input matrix, or the id of a stored dataset.
+    pub input_matrix: TrainingInput,
     pub labels: Vec<i64>,
+    /// Scaler applied to the input matrix before training.
+    pub scaler: Option<ScalerId>,
 }
 
 #[derive(Default, Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct PredictKNNRegressionParams {
+    pub input_matrix: InputMatrix,
+    pub model: Vec<u8>,
+    /// Scaler applied to the input matrix before predicting; it has to be the one used in training.
+    pub scaler: Option<ScalerId>,
+}
+
+#[derive(Default, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct TrainARParams {
+    /// Values of the series in chronological order, in fixed-point units.
+    pub series: Vec<i64>,
+    /// Number of preceding values every forecast depends on.
+    pub lag_order: u64,
+}
+
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct PredictARParams {
+    pub model: ArModel,
+    /// The latest values of the series in chronological order; at least as many as the lag order.
+    pub history: Vec<i64>,
+    /// Number of values to forecast after the history.
+    pub steps: u64,
+}
+
+#[derive(Default, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct ScaleParams {
+    pub input_matrix: InputMatrix,
+}
+
+/// The algorithms supported by the machine learning syscalls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Algorithm {
+    LinearRegression,
+    LogisticRegression,
+    KNNRegression,
+}
+
+impl Algorithm {
+    /// Whether the algorithm predicts classes rather than continuous values.
+    pub fn is_classifier(&self) -> bool {
+        matches!(self, Self::LogisticRegression)
+    }
+}
+
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct CrossValidateParams {
+    pub algorithm: Algorithm,
+    /// The input matrix, or the id of a stored dataset.
+    pub input_matrix: TrainingInput,
+    pub labels: Vec<i64>,
+    /// Number of folds, at least 2 and at most the number of rows.
+    pub folds: u64,
+    /// Scaler applied to the input matrix before training and prediction.
+    pub scaler: Option<ScalerId>,
+    /// If set, the rows are shuffled deterministically using this seed before splitting them
+    /// into folds; otherwise each fold is a contiguous range of rows.
+    pub seed: Option<u64>,
+    /// If set, the rows are shuffled with a seed drawn from the hash of the previous block
+    /// instead of `seed`, so the caller can't choose the folds directly. The hash is public
+    /// before the message executes though, so the caller can predict the folds and time the
+    /// submission, and the proposer of that block can grind it. Defaults to `false` so params
+    /// encoded before this field was added still decode.
+    #[serde(default)]
+    pub random_seed: bool,
+}
+
+/// Evaluation of the model trained on all folds but one, on the rows of the held out fold.
+///
+/// Errors are in the same fixed-point units as the labels.
+#[derive(Debug, Clone, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct FoldMetrics {
+    pub fold: u64,
+    pub train_rows: u64,
+    pub test_rows: u64,
+    pub mean_absolute_error: i64,
+    pub root_mean_squared_error: i64,
+    /// Ratio of correctly predicted labels in basis points, for classifiers only.
+    pub accuracy_bps: Option<u64>,
+}
+
+impl FoldMetrics {
+    pub fn new(
+        fold: u64,
+        train_rows: u64,
+        labels: &[i64],
+        predictions: &[i64],
+        classifier: bool,
+    ) -> Self {
+        let n = labels.len().min(predictions.len()) as i128;
+        let errors = labels
+            .iter()
+            .zip(predictions)
+            .map(|(y, p)| *p as i128 - *y as i128);
+
+        let (mae, rmse) = if n == 0 {
+            (0, 0)
+        } else {
+            let abs_sum: i128 = errors.clone().map(|e| e.abs()).sum();
+            let sq_sum: i128 = errors.clone().map(|e| e.saturating_mul(e)).sum();
+            (div_round(abs_sum, n), isqrt(div_round(sq_sum, n)))
+        };
+
+        let accuracy_bps = if classifier && n > 0 {
+            let correct = errors.filter(|e| *e == 0).count() as i128;
+            Some(div_round(correct * 10000, n) as u64)
+        } else {
+            None
+        };
+
+        Self {
+            fold,
+            train_rows,
+            test_rows: n as u64,
+            mean_absolute_error: mae.clamp(0, i64::MAX as i128) as i64,
+            root_mean_squared_error: rmse.clamp(0, i64::MAX as i128) as i64,
+            accuracy_bps,
+        }
+    }
+}
+
+/// Deterministic SplitMix64 generator, for the algorithms that need random numbers.
+#[derive(Debug, Clone)]
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`, with a negligible bias for small `n`.
+    pub fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+}
+
+/// Split `rows` row indices into `folds` folds of (almost) equal size and return the sorted
+/// indices of the test rows of each fold.
+pub fn kfold_test_indices(
+    rows: usize,
+    folds: u64,
+    seed: Option<u64>,
+) -> Result<Vec<Vec<usize>>, String> {
+    let k = folds as usize;
+    if k < 2 || k > rows {
+        return Err(format!(
+            "the number of folds has to be between 2 and the number of rows ({rows}); got {folds}"
+        ));
+    }
+
+    let mut order: Vec<usize> = (0..rows).collect();
+
+    // Fisher-Yates shuffle, so every validator gets the same folds.
+    if let Some(seed) = seed {
+        let mut rng = SplitMix64::new(seed);
+        for i in (1..rows).rev() {
+            order.swap(i, rng.below(i as u64 + 1) as usize);
+        }
+    }
+
+    Ok((0..k)
+        .map(|f| {
+            let mut fold = order[f * rows / k..(f + 1) * rows / k].to_vec();
+            fold.sort_unstable();
+            fold
+        })
+        .collect())
+}
+
+pub type ModelId = u64;
+
+/// Blake2b-256 digest of the DAG-CBOR encoding of the `(input_matrix, labels)` tuple a model
+/// was trained on, before scaling.
+pub type DatasetHash = [u8; 32];
+
+/// A trained model listed by its owner, who is paid a fee for every prediction made with it.
+#[derive(Debug, Clone, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct ListedModel {
+    pub owner: Address,
+    pub algorithm: Algorithm,
+    pub model: Vec<u8>,
+    /// Scaler applied to the inputs, the one the model was trained with.
+    pub scaler: Option<ScalerId>,
+    /// Charged to the caller of every prediction and sent to the owner.
+    pub prediction_fee: TokenAmount,
+    /// Number of paid predictions made with the model.
+    pub predictions: u64,
+    /// Total fees paid to the owner.
+    pub revenue: TokenAmount,
+    /// The [ALGORITHM_VERSION] when the model was listed.
+    pub algorithm_version: u64,
+    /// The data the owner declares to have trained the model on.
+    pub dataset_hash: DatasetHash,
+    /// The hyperparameters the owner declares to have trained the model with.
+    pub hyperparameters: BTreeMap<String, String>,
+    /// The epoch the model was listed in.
+    pub listed_at: ChainEpoch,
+}
+
+impl ListedModel {
+    /// How the model was produced, without the model itself.
+    pub fn info(&self) -> ModelInfo {
+        ModelInfo {
+            trainer: self.owner,
+            algorithm: self.algorithm,
+            algorithm_version: self.algorithm_version,
+            scaler: self.scaler,
+            dataset_hash: self.dataset_hash,
+            hyperparameters: self.hyperparameters.clone(),
+            listed_at: self.listed_at,
+        }
+    }
+
+    /// Split the value sent with a prediction into the fee paid to the owner and the change
+    /// returned to the caller, or `None` if the value doesn't cover the fee.
+    pub fn split_payment(&self, value: &TokenAmount) -> Option<(TokenAmount, TokenAmount)> {
+        if value < &self.prediction_fee {
+            return None;
+        }
+        Some((self.prediction_fee.clone(), value - &self.prediction_fee))
+    }
+
+    /// Count a paid prediction and the fee it earned the owner.
+    pub fn record_prediction(&mut self, fee: &TokenAmount) {
+        self.predictions = self.predictions.saturating_add(1);
+        self.revenue += fee.clone();
+    }
+}
+
+/// The provenance of a listed model, so that the consumers of its predictions can audit it,
+/// e.g. by training it again on the dataset with the same hash and comparing the models.
+#[derive(Debug, Clone, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct ModelInfo {
+    /// The owner of the model, who trained and listed it.
+    pub trainer: Address,
+    pub algorithm: Algorithm,
+    pub algorithm_version: u64,
+    pub scaler: Option<ScalerId>,
+    pub dataset_hash: DatasetHash,
+    pub hyperparameters: BTreeMap<String, String>,
+    pub listed_at: ChainEpoch,
+}
+
+/// The models an owner has listed and the datasets it has created, and the bytes they take up
+/// in the state.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct StorageUsage {
+    pub owner: Address,
+    pub models: u64,
+    pub bytes: u64,
+    /// Missing from the usages recorded before the datasets were accounted for.
+    #[serde(default)]
+    pub datasets: u64,
+}
+
+impl Default for StorageUsage {
+    fn default() -> Self {
+        Self {
+            owner: Address::new_id(0),
+            models: 0,
+            bytes: 0,
+            datasets: 0,
+        }
+    }
+}
+
+/// What the storage accounted to an owner is taken up by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stored {
+    Model,
+    Dataset,
+}
+
+pub type DatasetId = u64;
+
+/// An input matrix stored in the state, so that new observations or features can be added to it
+/// and models trained on it without uploading all of it again.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct Dataset {
+    /// The creator of the dataset, the only one who can append to it or delete it.
+    pub owner: Address,
     pub input_matrix: Vec<Vec<i64>>,
+}
+
+impl Dataset {
+    pub fn new(owner: Address, input_matrix: Vec<Vec<i64>>) -> Result<Self, String> {
+        num_cols(&input_matrix)?;
+        Ok(Self {
+            owner,
+            input_matrix,
+        })
+    }
+
+    pub fn num_rows(&self) -> usize {
+        self.input_matrix.len()
+    }
+
+    pub fn num_cols(&self) -> usize {
+        self.input_matrix
+            .first()
+            .map(|row| row.len())
+            .unwrap_or_default()
+    }
+
+    /// The bytes the input matrix takes up in the state, which are accounted to the owner.
+    pub fn size(&self) -> u64 {
+        fvm_ipld_encoding::to_vec(&self.input_matrix)
+            .map(|bytes| bytes.len() as u64)
+            .unwrap_or_default()
+    }
+
+    /// Add observations, which have to have the same features as the existing rows.
+    pub fn append_rows(&mut self, rows: Vec<Vec<i64>>) -> Result<(), String> {
+        let cols = num_cols(&rows)?;
+        if cols != self.num_cols() {
+            return Err(format!(
+                "the rows have {} columns; the dataset has {}",
+                cols,
+                self.num_cols()
+            ));
+        }
+        self.input_matrix.extend(rows);
+        Ok(())
+    }
+
+    /// Add features, given as one row of new values for each existing row.
+    pub fn append_columns(&mut self, columns: Vec<Vec<i64>>) -> Result<(), String> {
+        num_cols(&columns)?;
+        if columns.len() != self.num_rows() {
+            return Err(format!(
+                "there are {} rows of columns; the dataset has {} rows",
+                columns.len(),
+                self.num_rows()
+            ));
+        }
+        for (row, values) in self.input_matrix.iter_mut().zip(columns) {
+            row.extend(values);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct CreateDatasetParams {
+    pub input_matrix: InputMatrix,
+}
+
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct AppendRowsParams {
+    pub dataset_id: DatasetId,
+    /// The new rows, with as many columns as the dataset.
+    pub rows: InputMatrix,
+}
+
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct AppendColumnsParams {
+    pub dataset_id: DatasetId,
+    /// The values of the new columns, with a row for each row of the dataset.
+    pub columns: InputMatrix,
+}
+
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct ListModelParams {
+    pub algorithm: Algorithm,
     pub model: Vec<u8>,
+    pub scaler: Option<ScalerId>,
+    pub prediction_fee: TokenAmount,
+    pub dataset_hash: DatasetHash,
+    pub hyperparameters: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct SetPredictionFeeParams {
+    pub model_id: ModelId,
+    pub prediction_fee: TokenAmount,
+}
+
+#[derive(Default, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct PredictWithModelParams {
+    pub model_id: ModelId,
+    pub input_matrix: InputMatrix,
 }
 
+/// Event emitted when a model is listed or its fee changes.
+pub const EVENT_PREDICTION_FEE_SET: &str = "prediction-fee-set";
+/// Event emitted when a prediction fee is paid to the owner of a model.
+pub const EVENT_PREDICTION_FEE_PAID: &str = "prediction-fee-paid";
+/// Event emitted when the owner of a model deletes it.
+pub const EVENT_MODEL_DELETED: &str = "model-deleted";
+/// Event emitted when the owner of a dataset deletes it.
+pub const EVENT_DATASET_DELETED: &str = "dataset-deleted";
+
 #[derive(FromPrimitive)]
 #[repr(u64)]
 pub enum Method {
+    Constructor = METHOD_CONSTRUCTOR,
     TrainLinearRegression = frc42_dispatch::method_hash!("TrainLinearRegression"),
     PredictLinearRegression = frc42_dispatch::method_hash!("PredictLinearRegression"),
     TrainLogisticRegression = frc42_dispatch::method_hash!("TrainLogisticRegression"),
     PredictLogisticRegression = frc42_dispatch::method_hash!("PredictLogisticRegression"),
     TrainKNNRegression = frc42_dispatch::method_hash!("TrainKNNRegression"),
     PredictKNNRegression = frc42_dispatch::method_hash!("PredictKNNRegression"),
+    Standardize = frc42_dispatch::method_hash!("Standardize"),
+    MinMaxScale = frc42_dispatch::method_hash!("MinMaxScale"),
+    GetScaler = frc42_dispatch::method_hash!("GetScaler"),
+    CrossValidate = frc42_dispatch::method_hash!("CrossValidate"),
+    ListModel = frc42_dispatch::method_hash!("ListModel"),
+    SetPredictionFee = frc42_dispatch::method_hash!("SetPredictionFee"),
+    PredictWithModel = frc42_dispatch::method_hash!("PredictWithModel"),
+    GetModel = frc42_dispatch::method_hash!("GetModel"),
+    TrainMultiOutputLinearRegression =
+        frc42_dispatch::method_hash!("TrainMultiOutputLinearRegression"),
+    PredictMultiOutputLinearRegression =
+        frc42_dispatch::method_hash!("PredictMultiOutputLinearRegression"),
+    GetModelInfo = frc42_dispatch::method_hash!("GetModelInfo"),
+    TrainAR = frc42_dispatch::method_hash!("TrainAR"),
+    PredictAR = frc42_dispatch::method_hash!("PredictAR"),
+    TrainLinearRegressionGD = frc42_dispatch::method_hash!("TrainLinearRegressionGD"),
+    PredictLinearRegressionGD = frc42_dispatch::method_hash!("PredictLinearRegressionGD"),
+    DeleteModel = frc42_dispatch::method_hash!("DeleteModel"),
+    GetStorageUsage = frc42_dispatch::method_hash!("GetStorageUsage"),
+    QueryPrediction = frc42_dispatch::method_hash!("QueryPrediction"),
+    CreateDataset = frc42_dispatch::method_hash!("CreateDataset"),
+    AppendRows = frc42_dispatch::method_hash!("AppendRows"),
+    AppendColumns = frc42_dispatch::method_hash!("AppendColumns"),
+    GetDataset = frc42_dispatch::method_hash!("GetDataset"),
+    DeleteDataset = frc42_dispatch::method_hash!("DeleteDataset"),
+}
+
+#[cfg(test)]
+mod tests {
+    use fvm_ipld_amt::Amt;
+    use fvm_ipld_blockstore::MemoryBlockstore;
+    use fvm_shared::{address::Address, econ::TokenAmount};
+
+    use super::{
+        kfold_test_indices, transpose, Algorithm, ArModel, CrossValidateParams, Dataset,
+        FoldMetrics, InputLimits, InputMatrix, LinearModel, ListedModel, Scaler, ScalerId,
+        SplitMix64, State, Stored, ALGORITHM_VERSION,
+    };
+
+    #[test]
+    fn standard_scaler() {
+        let input_matrix = vec![vec![100, 500], vec![200, 500], vec![300, 500]];
+        let scaler = Scaler::standard(&input_matrix).unwrap();
+        assert_eq!(
+            scaler,
+            Scaler::Standard {
+                mean: vec![200, 500],
+                std_dev: vec![81, 0]
+            }
+        );
+        assert_eq!(
+            scaler.transform(input_matrix).unwrap(),
+            vec![vec![-123, 0], vec![0, 0], vec![123, 0]]
+        );
+    }
+
+    #[test]
+    fn min_max_scaler() {
+        let input_matrix = vec![vec![100, -50], vec![200, 50], vec![500, 0]];
+        let scaler = Scaler::min_max(&input_matrix).unwrap();
+        assert_eq!(
+            scaler,
+            Scaler::MinMax {
+                min: vec![100, -50],
+                max: vec![500, 50]
+            }
+        );
+        assert_eq!(
+            scaler.transform(input_matrix).unwrap(),
+            vec![vec![0, 0], vec![25, 100], vec![100, 50]]
+        );
+        assert!(scaler.transform(vec![vec![1, 2, 3]]).is_err());
+    }
+
+    #[test]
+    fn scaler_rejects_ragged_matrix() {
+        assert!(Scaler::standard(&[]).is_err());
+        assert!(Scaler::min_max(&[vec![1, 2], vec![3]]).is_err());
+    }
+
+    #[test]
+    fn transpose_labels() {
+        let labels = vec![vec![100, 5], vec![200, 6], vec![300, 7]];
+        let columns = transpose(&labels).unwrap();
+        assert_eq!(columns, vec![vec![100, 200, 300], vec![5, 6, 7]]);
+        assert_eq!(transpose(&columns).unwrap(), labels);
+        assert!(transpose(&[vec![1, 2], vec![3]]).is_err());
+        assert!(transpose(&[]).is_err());
+    }
+
+    #[test]
+    fn ar_model_forecast() {
+        // Alternating around zero: each value is about -0.9 times the previous one.
+        let series: Vec<i64> = (0..10)
+            .map(|i| if i % 2 == 0 { 100 } else { -100 })
+            .collect();
+        let model = ArModel::fit(&series, 1).unwrap();
+        assert_eq!(
+            model,
+            ArModel {
+                mean: 0,
+                coefficients: vec![-900_000]
+            }
+        );
+        assert_eq!(model.forecast(&[-100, 100], 2).unwrap(), vec![-90, 81]);
+
+        let model = ArModel::fit(&series, 2).unwrap();
+        assert_eq!(model.coefficients.len(), 2);
+        assert!(model.forecast(&[100], 1).is_err());
+        assert!(model.forecast(&[100, -100], 0).is_err());
+
+        // A constant series is forecast as its mean.
+        let model = ArModel::fit(&[250; 5], 2).unwrap();
+        assert_eq!(model.forecast(&[250, 250], 3).unwrap(), vec![250; 3]);
+
+        assert!(ArModel::fit(&series, 0).is_err());
+        assert!(ArModel::fit(&series[..2], 2).is_err());
+    }
+
+    #[test]
+    fn gradient_descent_convergence() {
+        // y = 2x + 1
+        let input_matrix = vec![vec![0], vec![100], vec![200], vec![300], vec![400]];
+        let labels = vec![100, 300, 500, 700, 900];
+
+        let (model, convergence) =
+            LinearModel::fit_gradient_descent(&input_matrix, &labels, 50_000, 5_000, 0).unwrap();
+        assert!(convergence.converged);
+        assert_eq!(convergence.iterations, 198);
+        assert_eq!(convergence.final_loss, 0);
+        assert_eq!(model.predict(&[vec![500]]).unwrap(), vec![1100]);
+
+        // Stopped by the iteration cap.
+        let (_, convergence) =
+            LinearModel::fit_gradient_descent(&input_matrix, &labels, 50_000, 10, 0).unwrap();
+        assert!(!convergence.converged);
+        assert_eq!(convergence.iterations, 10);
+        assert_eq!(convergence.final_loss, 1);
+
+        assert!(LinearModel::fit_gradient_descent(&input_matrix, &labels, 0, 10, 0).is_err());
+        assert!(LinearModel::fit_gradient_descent(&input_matrix, &labels[1..], 1, 10, 0).is_err());
+        assert!(model.predict(&[vec![1, 2]]).is_err());
+    }
+
+    #[test]
+    fn storage_accounting() {
+        let store = MemoryBlockstore::new();
+        let mut st = State::new(&store, Default::default()).unwrap();
+        let (alice, bob) = (Address::new_id(100), Address::new_id(101));
+
+        st.add_storage(&store, &alice, Stored::Model, 10).unwrap();
+        st.add_storage(&store, &alice, Stored::Model, 5).unwrap();
+        st.add_storage(&store, &bob, Stored::Model, 7).unwrap();
+        assert_eq!(st.storage_usage(&store, &alice).unwrap().models, 2);
+        assert_eq!(st.storage_usage(&store, &alice).unwrap().bytes, 15);
+
+        st.remove_storage(&store, &alice, Stored::Model, 10)
+            .unwrap();
+        assert_eq!(st.storage_usage(&store, &alice).unwrap().bytes, 5);
+        st.remove_storage(&store, &alice, Stored::Model, 5).unwrap();
+        assert_eq!(st.storage_usage(&store, &alice).unwrap().models, 0);
+        assert_eq!(st.storage_usage(&store, &bob).unwrap().bytes, 7);
+
+        // Owners without models are forgotten.
+        let mut emptied = st.clone();
+        emptied
+            .remove_storage(&store, &bob, Stored::Model, 7)
+            .unwrap();
+        assert_eq!(
+            emptied.storage,
+            State::new(&store, Default::default()).unwrap().storage
+        );
+    }
+
+    #[test]
+    fn storage_recount() {
+        let store = MemoryBlockstore::new();
+        let mut st = State::new(&store, Default::default()).unwrap();
+        let (alice, bob) = (Address::new_id(100), Address::new_id(101));
+
+        // Models listed without accounting for their storage.
+        let model = |owner: Address, bytes: usize| ListedModel {
+            owner,
+            algorithm: Algorithm::LinearRegression,
+            model: vec![0; bytes],
+            scaler: None,
+            prediction_fee: TokenAmount::default(),
+            predictions: 0,
+            revenue: TokenAmount::default(),
+            algorithm_version: ALGORITHM_VERSION,
+            dataset_hash: Default::default(),
+            hyperparameters: Default::default(),
+            listed_at: 0,
+        };
+        let mut models = Amt::<ListedModel, _>::load(&st.models, &store).unwrap();
+        models.set(0, model(alice, 10)).unwrap();
+        models.set(1, model(bob, 7)).unwrap();
+        models.set(2, model(alice, 5)).unwrap();
+        st.models = models.flush().unwrap();
+        assert_eq!(st.storage_usage(&store, &alice).unwrap().models, 0);
+
+        let dataset = Dataset::new(bob, vec![vec![1, 2]]).unwrap();
+        let dataset_bytes = dataset.size();
+        let mut datasets = Amt::<Dataset, _>::load(&st.datasets, &store).unwrap();
+        datasets.set(0, dataset).unwrap();
+        st.datasets = datasets.flush().unwrap();
+
+        st.recount_storage(&store).unwrap();
+        let usage = st.storage_usage(&store, &alice).unwrap();
+        assert_eq!((usage.models, usage.datasets, usage.bytes), (2, 0, 15));
+        let usage = st.storage_usage(&store, &bob).unwrap();
+        assert_eq!(
+            (usage.models, usage.datasets, usage.bytes),
+            (1, 1, 7 + dataset_bytes)
+        );
+    }
+
+    #[test]
+    fn dataset_appends() {
+        let owner = Address::new_id(100);
+        assert!(Dataset::new(owner, Vec::new()).is_err());
+        assert!(Dataset::new(owner, vec![vec![1, 2], vec![3]]).is_err());
+
+        let mut dataset = Dataset::new(owner, vec![vec![1, 2], vec![3, 4]]).unwrap();
+
+        dataset.append_rows(vec![vec![5, 6]]).unwrap();
+        assert_eq!(dataset.num_rows(), 3);
+        assert!(dataset.append_rows(vec![vec![7]]).is_err());
+        assert!(dataset.append_rows(Vec::new()).is_err());
+
+        dataset
+            .append_columns(vec![vec![10], vec![20], vec![30]])
+            .unwrap();
+        assert_eq!(
+            dataset.input_matrix,
+            vec![vec![1, 2, 10], vec![3, 4, 20], vec![5, 6, 30]]
+        );
+        assert!(dataset.append_columns(vec![vec![1], vec![2]]).is_err());
+        assert!(dataset
+            .append_columns(vec![vec![1, 2], vec![3], vec![4, 5]])
+            .is_err());
+        // A failed append leaves the dataset as it was.
+        assert_eq!(dataset.num_cols(), 3);
+
+        let limits = InputLimits {
+            max_rows: 3,
+            max_cols: 3,
+            max_bytes: dataset.size(),
+        };
+        assert!(limits.check_dataset(&dataset).is_ok());
+        dataset.append_rows(vec![vec![7, 8, 9]]).unwrap();
+        assert!(limits.check_dataset(&dataset).is_err());
+        let limits = InputLimits {
+            max_bytes: 1,
+            ..limits
+        };
+        assert!(limits
+            .check_dataset(&Dataset::new(owner, vec![vec![1]]).unwrap())
+            .is_err());
+    }
+
+    #[test]
+    fn kfold_splits_all_rows() {
+        assert_eq!(
+            kfold_test_indices(5, 2, None).unwrap(),
+            vec![vec![0, 1], vec![2, 3, 4]]
+        );
+        assert!(kfold_test_indices(5, 1, None).is_err());
+        assert!(kfold_test_indices(5, 6, None).is_err());
+
+        let folds = kfold_test_indices(10, 3, Some(42)).unwrap();
+        assert_eq!(folds, kfold_test_indices(10, 3, Some(42)).unwrap());
+        let mut all: Vec<usize> = folds.into_iter().flatten().collect();
+        all.sort();
+        assert_eq!(all, (0..10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn splitmix64_matches_reference() {
+        let mut rng = SplitMix64::new(0);
+        assert_eq!(rng.next_u64(), 0xE220A8397B1DCDAF);
+        assert_eq!(rng.next_u64(), 0x6E789E6AA1B965F4);
+        assert!(SplitMix64::new(42).below(3) < 3);
+    }
+
+    #[test]
+    fn fold_metrics() {
+        let m = FoldMetrics::new(0, 4, &[100, 200, 300], &[110, 190, 330], false);
+        assert_eq!(m.test_rows, 3);
+        assert_eq!(m.mean_absolute_error, 17);
+        assert_eq!(m.root_mean_squared_error, 19);
+        assert_eq!(m.accuracy_bps, None);
+
+        let m = FoldMetrics::new(1, 4, &[0, 1, 1, 0], &[0, 1, 0, 0], true);
+        assert_eq!(m.accuracy_bps, Some(7500));
+    }
+
+    #[test]
+    fn listed_model_info() {
+        let model = ListedModel {
+            owner: Address::new_id(100),
+            algorithm: Algorithm::KNNRegression,
+            model: vec![1, 2, 3],
+            scaler: Some(1),
+            prediction_fee: TokenAmount::from_atto(10),
+            predictions: 5,
+            revenue: TokenAmount::from_atto(50),
+            algorithm_version: ALGORITHM_VERSION,
+            dataset_hash: [7; 32],
+            hyperparameters: [("k".to_owned(), "3".to_owned())].into(),
+            listed_at: 42,
+        };
+
+        let bytes = fvm_ipld_encoding::to_vec(&model).unwrap();
+        let decoded: ListedModel = fvm_ipld_encoding::from_slice(&bytes).unwrap();
+        assert_eq!(decoded, model);
+
+        let info = decoded.info();
+        assert_eq!(info.trainer, model.owner);
+        assert_eq!(info.dataset_hash, [7; 32]);
+        assert_eq!(info.hyperparameters["k"], "3");
+        assert_eq!(info.listed_at, 42);
+    }
+
+    #[test]
+    fn prediction_payment_split() {
+        let mut model = ListedModel {
+            owner: Address::new_id(100),
+            algorithm: Algorithm::LinearRegression,
+            model: vec![1, 2, 3],
+            scaler: None,
+            prediction_fee: TokenAmount::from_atto(10),
+            predictions: 0,
+            revenue: TokenAmount::default(),
+            algorithm_version: ALGORITHM_VERSION,
+            dataset_hash: Default::default(),
+            hyperparameters: Default::default(),
+            listed_at: 0,
+        };
+
+        // The owner gets the fee, the caller the rest.
+        assert_eq!(
+            model.split_payment(&TokenAmount::from_atto(25)),
+            Some((TokenAmount::from_atto(10), TokenAmount::from_atto(15)))
+        );
+        assert_eq!(
+            model.split_payment(&TokenAmount::from_atto(10)),
+            Some((TokenAmount::from_atto(10), TokenAmount::default()))
+        );
+        assert_eq!(model.split_payment(&TokenAmount::from_atto(9)), None);
+
+        // With no fee everything sent is returned.
+        model.prediction_fee = TokenAmount::default();
+        assert_eq!(
+            model.split_payment(&TokenAmount::default()),
+            Some((TokenAmount::default(), TokenAmount::default()))
+        );
+        assert_eq!(
+            model.split_payment(&TokenAmount::from_atto(5)),
+            Some((TokenAmount::default(), TokenAmount::from_atto(5)))
+        );
+
+        // Amounts beyond any machine integer don't wrap around.
+        let huge = TokenAmount::from_atto(u128::MAX) * 4;
+        model.prediction_fee = huge.clone();
+        assert_eq!(
+            model.split_payment(&(huge.clone() - TokenAmount::from_atto(1))),
+            None
+        );
+        assert_eq!(
+            model.split_payment(&(huge.clone() * 2)),
+            Some((huge.clone(), huge.clone()))
+        );
+    }
+
+    #[test]
+    fn prediction_accounting_saturates() {
+        let fee = TokenAmount::from_atto(u128::MAX);
+        let mut model = ListedModel {
+            owner: Address::new_id(100),
+            algorithm: Algorithm::LinearRegression,
+            model: vec![1, 2, 3],
+            scaler: None,
+            prediction_fee: fee.clone(),
+            predictions: u64::MAX - 1,
+            revenue: fee.clone(),
+            algorithm_version: ALGORITHM_VERSION,
+            dataset_hash: Default::default(),
+            hyperparameters: Default::default(),
+            listed_at: 0,
+        };
+
+        model.record_prediction(&fee);
+        model.record_prediction(&fee);
+        assert_eq!(model.predictions, u64::MAX);
+        assert_eq!(model.revenue, fee * 3);
+
+        model.record_prediction(&TokenAmount::default());
+        assert_eq!(model.predictions, u64::MAX);
+    }
+
+    #[test]
+    fn cross_validate_params_without_random_seed() {
+        // The layout before `random_seed` was added.
+        let params = (
+            Algorithm::LinearRegression,
+            InputMatrix::Dense(vec![vec![1], vec![2]]),
+            vec![1i64, 2],
+            2u64,
+            None::<ScalerId>,
+            Some(7u64),
+        );
+        let bytes = fvm_ipld_encoding::to_vec(&params).unwrap();
+        let decoded: CrossValidateParams = fvm_ipld_encoding::from_slice(&bytes).unwrap();
+        assert_eq!(decoded.seed, Some(7));
+        assert!(!decoded.random_seed);
+    }
 }
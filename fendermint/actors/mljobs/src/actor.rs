@@ -0,0 +1,648 @@
+// Copyright 2021-2023 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use fil_actors_runtime::actor_dispatch;
+use fil_actors_runtime::actor_error;
+use fil_actors_runtime::builtin::singletons::SYSTEM_ACTOR_ADDR;
+use fil_actors_runtime::extract_send_result;
+use fil_actors_runtime::runtime::{ActorCode, Runtime};
+use fil_actors_runtime::ActorDowncast;
+use fil_actors_runtime::ActorError;
+use fil_actors_runtime::Array;
+use fil_actors_runtime::BURNT_FUNDS_ACTOR_ADDR;
+use fvm_ipld_encoding::ipld_block::IpldBlock;
+use fvm_ipld_encoding::DAG_CBOR;
+use fvm_shared::error::ExitCode;
+use fvm_shared::METHOD_SEND;
+use num_traits::Zero;
+
+use crate::{
+    is_job_method, ChallengeParams, ConstructorParams, Job, JobId, JobStatus, Method,
+    PostResultParams, State, CHALLENGER_REWARD_PERCENT, MAX_FINALIZED_PER_EPOCH, MLJOBS_ACTOR_NAME,
+};
+
+#[cfg(feature = "fil-actor")]
+fil_actors_runtime::wasm_trampoline!(Actor);
+
+pub struct Actor;
+
+impl Actor {
+    fn constructor(rt: &impl Runtime, params: ConstructorParams) -> Result<(), ActorError> {
+        rt.validate_immediate_caller_is(std::iter::once(&SYSTEM_ACTOR_ADDR))?;
+
+        if params.min_deposit.is_zero() {
+            return Err(
+                actor_error!(illegal_argument; "the minimum deposit of jobs must be positive"),
+            );
+        }
+
+        let state = State::new(rt.store(), params).map_err(|e| {
+            e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to create empty AMT")
+        })?;
+
+        rt.create(&state)?;
+
+        Ok(())
+    }
+
+    /// Post the model produced by a training job, along with a deposit which is forfeited
+    /// if anyone can prove that the model is wrong within the challenge window.
+    fn post_result(rt: &impl Runtime, params: PostResultParams) -> Result<JobId, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+
+        if !is_job_method(params.method) {
+            return Err(actor_error!(illegal_argument;
+                "method {} of the machine learning actor can't be executed as a job", params.method));
+        }
+
+        let deposit = rt.message().value_received();
+        let commitment = rt.hash_blake2b(&params.model);
+
+        let job = Job {
+            proposer: rt.message().caller(),
+            method: params.method,
+            params: params.params,
+            model: params.model,
+            commitment,
+            posted_at: rt.curr_epoch(),
+            deposit,
+            status: JobStatus::Pending,
+        };
+
+        rt.transaction(|st: &mut State, rt| {
+            if job.deposit < st.min_deposit {
+                return Err(actor_error!(insufficient_funds;
+                    "posting a result requires a deposit of {}; received {}", st.min_deposit, job.deposit));
+            }
+
+            let job_id = st.next_job_id;
+
+            Self::save_job(st, rt, job_id, job)?;
+
+            st.next_job_id += 1;
+
+            Ok(job_id)
+        })
+    }
+
+    /// Re-execute a pending job and revert it if the result doesn't match the posted model,
+    /// paying part of the deposit to the challenger and burning the rest.
+    ///
+    /// Returns whether the challenge was successful. Only a re-execution which succeeds with a
+    /// different model is proof of fraud; if it fails, e.g. because the challenger didn't give it
+    /// enough gas, the challenge is aborted and the job stays pending.
+    fn challenge(rt: &impl Runtime, params: ChallengeParams) -> Result<bool, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+
+        let st: State = rt.state()?;
+        let job = Self::load_job(&st, rt, params.job_id)?;
+        let caller = rt.message().caller();
+
+        if caller == job.proposer {
+            return Err(actor_error!(forbidden;
+                "the proposer of job {} can't challenge it", params.job_id));
+        }
+        // Jobs posted with any other method are rejected, but don't send arbitrary messages on their behalf.
+        if !is_job_method(job.method) {
+            return Err(actor_error!(illegal_argument;
+                "method {} of job {} is not a training method", job.method, params.job_id));
+        }
+        if job.status != JobStatus::Pending {
+            return Err(actor_error!(illegal_argument;
+                "job {} is not pending: {:?}", params.job_id, job.status));
+        }
+        if rt.curr_epoch() > job.challenge_deadline(st.challenge_window) {
+            return Err(actor_error!(forbidden;
+                "the challenge window of job {} has passed", params.job_id));
+        }
+
+        let res = rt
+            .send_simple(
+                &st.ml_actor,
+                job.method,
+                Some(IpldBlock {
+                    codec: DAG_CBOR,
+                    data: job.params.to_vec(),
+                }),
+                Default::default(),
+            )
+            .map_err(
+                |e| actor_error!(unspecified; "failed to re-execute job {}: {}", params.job_id, e),
+            )?;
+
+        // The failure could be specific to this call, so it can't be held against the proposer.
+        if !res.exit_code.is_success() {
+            return Err(actor_error!(unspecified;
+                "re-executing job {} failed with exit code {}", params.job_id, res.exit_code));
+        }
+
+        let model: Vec<u8> = res
+            .return_data
+            .map(|b| b.deserialize())
+            .transpose()
+            .map_err(|e| actor_error!(serialization; "failed to decode model: {}", e))?
+            .unwrap_or_default();
+
+        if rt.hash_blake2b(&model) == job.commitment {
+            return Ok(false);
+        }
+
+        let deposit = rt.transaction(|st: &mut State, rt| {
+            let mut job = Self::load_job(st, rt, params.job_id)?;
+            job.status = JobStatus::Reverted;
+            let deposit = std::mem::take(&mut job.deposit);
+
+            Self::save_job(st, rt, params.job_id, job)?;
+
+            Ok(deposit)
+        })?;
+
+        // Only pay part of the deposit, otherwise the proposer could challenge their own wrong
+        // result from another address to get it back in full.
+        let reward = deposit.div_floor(100) * CHALLENGER_REWARD_PERCENT;
+        let burn = deposit - &reward;
+
+        if !reward.is_zero() {
+            extract_send_result(rt.send_simple(&caller, METHOD_SEND, None, reward))?;
+        }
+        if !burn.is_zero() {
+            extract_send_result(rt.send_simple(&BURNT_FUNDS_ACTOR_ADDR, METHOD_SEND, None, burn))?;
+        }
+
+        Ok(true)
+    }
+
+    /// Finalize the jobs whose challenge window has passed, returning the deposits to the proposers.
+    ///
+    /// At most [MAX_FINALIZED_PER_EPOCH] jobs are settled at a time; the rest are left for the
+    /// next call. A refund which can't be delivered, e.g. because the proposer rejects transfers,
+    /// stays with the actor rather than holding up the jobs posted after it.
+    fn finalize_expired(rt: &impl Runtime) -> Result<(), ActorError> {
+        rt.validate_immediate_caller_is(std::iter::once(&SYSTEM_ACTOR_ADDR))?;
+
+        let curr_epoch = rt.curr_epoch();
+
+        let refunds = rt.transaction(|st: &mut State, rt| {
+            let mut refunds = Vec::new();
+            let mut settled = 0;
+
+            // jobs expire in the order they were posted
+            while st.first_pending < st.next_job_id && settled < MAX_FINALIZED_PER_EPOCH {
+                let job_id = st.first_pending;
+                let mut job = Self::load_job(st, rt, job_id)?;
+
+                if job.status == JobStatus::Pending {
+                    if job.challenge_deadline(st.challenge_window) >= curr_epoch {
+                        break;
+                    }
+
+                    job.status = JobStatus::Finalized;
+                    refunds.push((job.proposer, std::mem::take(&mut job.deposit)));
+
+                    Self::save_job(st, rt, job_id, job)?;
+                }
+
+                st.first_pending += 1;
+                settled += 1;
+            }
+
+            Ok(refunds)
+        })?;
+
+        for (proposer, deposit) in refunds {
+            if !deposit.is_zero() {
+                let _ = rt.send_simple(&proposer, METHOD_SEND, None, deposit);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn get_job(rt: &impl Runtime, job_id: JobId) -> Result<Option<Job>, ActorError> {
+        let st: State = rt.state()?;
+
+        st.get_job(rt.store(), job_id)
+            .map_err(|e| e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to get job"))
+    }
+
+    fn load_job(st: &State, rt: &impl Runtime, job_id: JobId) -> Result<Job, ActorError> {
+        st.get_job(rt.store(), job_id)
+            .map_err(|e| e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to get job"))?
+            .ok_or_else(|| actor_error!(not_found; "job {} not found", job_id))
+    }
+
+    fn save_job(
+        st: &mut State,
+        rt: &impl Runtime,
+        job_id: JobId,
+        job: Job,
+    ) -> Result<(), ActorError> {
+        let mut jobs = Array::load(&st.jobs, rt.store())
+            .map_err(|e| e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load jobs"))?;
+
+        jobs.set(job_id, job)
+            .map_err(|e| e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to set job"))?;
+
+        st.jobs = jobs
+            .flush()
+            .map_err(|e| e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to save jobs"))?;
+
+        Ok(())
+    }
+}
+
+impl ActorCode for Actor {
+    type Methods = Method;
+
+    fn name() -> &'static str {
+        MLJOBS_ACTOR_NAME
+    }
+
+    actor_dispatch! {
+        Constructor => constructor,
+        PostResult => post_result,
+        Challenge => challenge,
+        FinalizeExpired => finalize_expired,
+        GetJob => get_job,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fendermint_actor_machinelearning::Method as MlMethod;
+    use fil_actors_runtime::runtime::Runtime;
+    use fil_actors_runtime::test_utils::{
+        expect_abort, MockRuntime, ACCOUNT_ACTOR_CODE_ID, SYSTEM_ACTOR_CODE_ID,
+    };
+    use fil_actors_runtime::{BURNT_FUNDS_ACTOR_ADDR, SYSTEM_ACTOR_ADDR};
+    use fvm_ipld_encoding::ipld_block::IpldBlock;
+    use fvm_ipld_encoding::{RawBytes, DAG_CBOR};
+    use fvm_shared::address::Address;
+    use fvm_shared::clock::ChainEpoch;
+    use fvm_shared::econ::TokenAmount;
+    use fvm_shared::error::ExitCode;
+    use fvm_shared::{MethodNum, METHOD_SEND};
+    use num_traits::Zero;
+
+    use super::Actor;
+    use crate::{
+        ChallengeParams, ConstructorParams, JobId, JobStatus, Method, PostResultParams, State,
+        MAX_FINALIZED_PER_EPOCH,
+    };
+
+    const ML_ACTOR: Address = Address::new_id(50);
+    const PROPOSER: Address = Address::new_id(100);
+    const CHALLENGER: Address = Address::new_id(101);
+    const CHALLENGE_WINDOW: ChainEpoch = 10;
+    const TRAIN: MethodNum = MlMethod::TrainLinearRegression as MethodNum;
+
+    fn min_deposit() -> TokenAmount {
+        TokenAmount::from_whole(10)
+    }
+
+    fn construct() -> MockRuntime {
+        let rt = MockRuntime {
+            receiver: Address::new_id(10),
+            ..Default::default()
+        };
+
+        rt.set_caller(*SYSTEM_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR);
+        rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
+
+        rt.call::<Actor>(
+            Method::Constructor as u64,
+            IpldBlock::serialize_cbor(&ConstructorParams {
+                ml_actor: ML_ACTOR,
+                challenge_window: CHALLENGE_WINDOW,
+                min_deposit: min_deposit(),
+            })
+            .unwrap(),
+        )
+        .unwrap();
+        rt.verify();
+        rt.reset();
+
+        rt
+    }
+
+    fn post(
+        rt: &MockRuntime,
+        method: MethodNum,
+        model: Vec<u8>,
+        deposit: TokenAmount,
+    ) -> Result<JobId, ExitCode> {
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, PROPOSER);
+        rt.set_received(deposit.clone());
+        rt.set_balance(rt.current_balance() + deposit);
+        rt.expect_validate_caller_any();
+
+        let res = rt.call::<Actor>(
+            Method::PostResult as u64,
+            IpldBlock::serialize_cbor(&PostResultParams {
+                method,
+                params: RawBytes::new(vec![1, 2, 3]),
+                model,
+            })
+            .unwrap(),
+        );
+        rt.reset();
+
+        res.map(|ret| ret.unwrap().deserialize().unwrap())
+            .map_err(|e| e.exit_code())
+    }
+
+    /// Challenge a job, expecting it to be re-executed with the model returned by the machine
+    /// learning actor, and the deposit to be split between the challenger and the burnt funds
+    /// if the model is different.
+    fn challenge(
+        rt: &MockRuntime,
+        challenger: Address,
+        job_id: JobId,
+        model: Vec<u8>,
+        slashed: bool,
+    ) -> Result<bool, ExitCode> {
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, challenger);
+        rt.set_received(TokenAmount::default());
+        rt.expect_validate_caller_any();
+        rt.expect_send_simple(
+            ML_ACTOR,
+            TRAIN,
+            Some(IpldBlock {
+                codec: DAG_CBOR,
+                data: vec![1, 2, 3],
+            }),
+            TokenAmount::default(),
+            IpldBlock::serialize_cbor(&model).unwrap(),
+            ExitCode::OK,
+        );
+        if slashed {
+            let half = min_deposit().div_floor(2);
+            for to in [challenger, BURNT_FUNDS_ACTOR_ADDR] {
+                rt.expect_send_simple(to, METHOD_SEND, None, half.clone(), None, ExitCode::OK);
+            }
+        }
+
+        let res = rt.call::<Actor>(
+            Method::Challenge as u64,
+            IpldBlock::serialize_cbor(&ChallengeParams { job_id }).unwrap(),
+        );
+        rt.verify();
+        rt.reset();
+
+        res.map(|ret| ret.unwrap().deserialize().unwrap())
+            .map_err(|e| e.exit_code())
+    }
+
+    fn finalize(rt: &MockRuntime) {
+        rt.set_caller(*SYSTEM_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR);
+        rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
+        rt.call::<Actor>(Method::FinalizeExpired as u64, None)
+            .unwrap();
+        rt.verify();
+        rt.reset();
+    }
+
+    fn job_status(rt: &MockRuntime, job_id: JobId) -> JobStatus {
+        let st: State = rt.get_state();
+        st.get_job(rt.store(), job_id).unwrap().unwrap().status
+    }
+
+    #[test]
+    fn construct_requires_deposit() {
+        let rt = MockRuntime::default();
+        rt.set_caller(*SYSTEM_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR);
+        rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
+
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            rt.call::<Actor>(
+                Method::Constructor as u64,
+                IpldBlock::serialize_cbor(&ConstructorParams {
+                    ml_actor: ML_ACTOR,
+                    challenge_window: CHALLENGE_WINDOW,
+                    min_deposit: TokenAmount::default(),
+                })
+                .unwrap(),
+            ),
+        );
+    }
+
+    #[test]
+    fn post_result() {
+        let rt = construct();
+        rt.set_epoch(5);
+
+        assert_eq!(
+            post(
+                &rt,
+                MlMethod::ListModel as MethodNum,
+                vec![7],
+                min_deposit()
+            ),
+            Err(ExitCode::USR_ILLEGAL_ARGUMENT),
+            "only training methods can be executed as jobs"
+        );
+        assert_eq!(
+            post(&rt, TRAIN, vec![7], TokenAmount::from_whole(1)),
+            Err(ExitCode::USR_INSUFFICIENT_FUNDS)
+        );
+        assert_eq!(post(&rt, TRAIN, vec![7], min_deposit()), Ok(0));
+        assert_eq!(post(&rt, TRAIN, vec![8], min_deposit()), Ok(1));
+
+        let st: State = rt.get_state();
+        let job = st.get_job(rt.store(), 0).unwrap().unwrap();
+        assert_eq!(job.proposer, PROPOSER);
+        assert_eq!(job.posted_at, 5);
+        assert_eq!(job.deposit, min_deposit());
+        assert_eq!(job.commitment, rt.hash_blake2b(&[7]));
+        assert_eq!(job.status, JobStatus::Pending);
+
+        let posted = st.jobs_posted_at(rt.store(), 5).unwrap();
+        assert_eq!(posted.iter().map(|(id, _)| *id).collect::<Vec<_>>(), [0, 1]);
+    }
+
+    #[test]
+    fn challenge_correct_result() {
+        let rt = construct();
+        let job_id = post(&rt, TRAIN, vec![7], min_deposit()).unwrap();
+
+        assert_eq!(
+            challenge(&rt, CHALLENGER, job_id, vec![7], false),
+            Ok(false)
+        );
+
+        assert_eq!(job_status(&rt, job_id), JobStatus::Pending);
+    }
+
+    #[test]
+    fn challenge_wrong_result_slashes_deposit() {
+        let rt = construct();
+        let job_id = post(&rt, TRAIN, vec![7], min_deposit()).unwrap();
+
+        // Half of the deposit goes to the challenger, the other half is burnt.
+        assert_eq!(challenge(&rt, CHALLENGER, job_id, vec![8], true), Ok(true));
+
+        let st: State = rt.get_state();
+        let job = st.get_job(rt.store(), job_id).unwrap().unwrap();
+        assert_eq!(job.status, JobStatus::Reverted);
+        assert!(job.deposit.is_zero());
+
+        // A reverted job can't be challenged again.
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, CHALLENGER);
+        rt.expect_validate_caller_any();
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            rt.call::<Actor>(
+                Method::Challenge as u64,
+                IpldBlock::serialize_cbor(&ChallengeParams { job_id }).unwrap(),
+            ),
+        );
+    }
+
+    #[test]
+    fn challenge_aborts_when_reexecution_fails() {
+        let rt = construct();
+        let job_id = post(&rt, TRAIN, vec![7], min_deposit()).unwrap();
+
+        // Running out of gas says nothing about the posted result.
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, CHALLENGER);
+        rt.expect_validate_caller_any();
+        rt.expect_send_simple(
+            ML_ACTOR,
+            TRAIN,
+            Some(IpldBlock {
+                codec: DAG_CBOR,
+                data: vec![1, 2, 3],
+            }),
+            TokenAmount::default(),
+            None,
+            ExitCode::SYS_OUT_OF_GAS,
+        );
+        expect_abort(
+            ExitCode::USR_UNSPECIFIED,
+            rt.call::<Actor>(
+                Method::Challenge as u64,
+                IpldBlock::serialize_cbor(&ChallengeParams { job_id }).unwrap(),
+            ),
+        );
+        rt.verify();
+        rt.reset();
+
+        let st: State = rt.get_state();
+        let job = st.get_job(rt.store(), job_id).unwrap().unwrap();
+        assert_eq!(job.status, JobStatus::Pending);
+        assert_eq!(job.deposit, min_deposit());
+    }
+
+    #[test]
+    fn challenge_forbidden() {
+        let rt = construct();
+        rt.set_epoch(1);
+        let job_id = post(&rt, TRAIN, vec![7], min_deposit()).unwrap();
+
+        // The proposer can't challenge their own job.
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, PROPOSER);
+        rt.expect_validate_caller_any();
+        expect_abort(
+            ExitCode::USR_FORBIDDEN,
+            rt.call::<Actor>(
+                Method::Challenge as u64,
+                IpldBlock::serialize_cbor(&ChallengeParams { job_id }).unwrap(),
+            ),
+        );
+        rt.reset();
+
+        // Nobody can challenge it after the window.
+        rt.set_epoch(1 + CHALLENGE_WINDOW + 1);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, CHALLENGER);
+        rt.expect_validate_caller_any();
+        expect_abort(
+            ExitCode::USR_FORBIDDEN,
+            rt.call::<Actor>(
+                Method::Challenge as u64,
+                IpldBlock::serialize_cbor(&ChallengeParams { job_id }).unwrap(),
+            ),
+        );
+    }
+
+    #[test]
+    fn finalize_expired_jobs() {
+        let rt = construct();
+
+        rt.set_epoch(1);
+        let expired = post(&rt, TRAIN, vec![7], min_deposit()).unwrap();
+        let reverted = post(&rt, TRAIN, vec![7], min_deposit()).unwrap();
+        rt.set_epoch(5);
+        let pending = post(&rt, TRAIN, vec![7], min_deposit()).unwrap();
+
+        assert_eq!(
+            challenge(&rt, CHALLENGER, reverted, vec![8], true),
+            Ok(true)
+        );
+
+        // Nothing has expired yet.
+        rt.set_epoch(1 + CHALLENGE_WINDOW);
+        finalize(&rt);
+        assert_eq!(job_status(&rt, expired), JobStatus::Pending);
+
+        // A refund which can't be delivered doesn't stop the finalization.
+        rt.set_epoch(1 + CHALLENGE_WINDOW + 1);
+        rt.expect_send_simple(
+            PROPOSER,
+            METHOD_SEND,
+            None,
+            min_deposit(),
+            None,
+            ExitCode::USR_FORBIDDEN,
+        );
+        finalize(&rt);
+
+        assert_eq!(job_status(&rt, expired), JobStatus::Finalized);
+        assert_eq!(job_status(&rt, reverted), JobStatus::Reverted);
+        assert_eq!(job_status(&rt, pending), JobStatus::Pending);
+
+        let st: State = rt.get_state();
+        assert_eq!(st.first_pending, pending);
+    }
+
+    #[test]
+    fn finalize_is_bounded() {
+        let rt = construct();
+        rt.set_epoch(1);
+
+        let jobs = MAX_FINALIZED_PER_EPOCH + 1;
+        for _ in 0..jobs {
+            post(&rt, TRAIN, vec![7], min_deposit()).unwrap();
+        }
+
+        rt.set_epoch(1 + CHALLENGE_WINDOW + 1);
+        for _ in 0..MAX_FINALIZED_PER_EPOCH {
+            rt.expect_send_simple(
+                PROPOSER,
+                METHOD_SEND,
+                None,
+                min_deposit(),
+                None,
+                ExitCode::OK,
+            );
+        }
+        finalize(&rt);
+
+        let st: State = rt.get_state();
+        assert_eq!(st.first_pending, MAX_FINALIZED_PER_EPOCH);
+        assert_eq!(job_status(&rt, jobs - 1), JobStatus::Pending);
+
+        rt.expect_send_simple(
+            PROPOSER,
+            METHOD_SEND,
+            None,
+            min_deposit(),
+            None,
+            ExitCode::OK,
+        );
+        finalize(&rt);
+
+        let st: State = rt.get_state();
+        assert_eq!(st.first_pending, jobs);
+    }
+}
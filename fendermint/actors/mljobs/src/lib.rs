@@ -0,0 +1,6 @@
+// Copyright 2021-2023 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+mod actor;
+mod shared;
+
+pub use shared::*;
@@ -0,0 +1,215 @@
+// Copyright 2021-2023 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use cid::Cid;
+use fvm_ipld_amt::Amt;
+use fvm_ipld_blockstore::Blockstore;
+use fvm_ipld_encoding::tuple::{Deserialize_tuple, Serialize_tuple};
+use fvm_ipld_encoding::RawBytes;
+use fvm_shared::{
+    address::Address, clock::ChainEpoch, econ::TokenAmount, MethodNum, METHOD_CONSTRUCTOR,
+};
+use num_derive::FromPrimitive;
+use num_traits::Zero;
+use serde::{Deserialize, Serialize};
+
+pub const MLJOBS_ACTOR_NAME: &str = "mljobs";
+
+// the default number of epochs during which a posted result can be challenged
+pub const DEFAULT_CHALLENGE_WINDOW: ChainEpoch = 100;
+
+// the default bitwidth of the jobs AMT
+pub const JOBS_AMT_BITWIDTH: u32 = 5;
+
+// the maximum number of jobs settled by one call to `FinalizeExpired`, to bound its gas;
+// the rest are settled in the following epochs
+pub const MAX_FINALIZED_PER_EPOCH: u64 = 64;
+
+// the share of a reverted job's deposit paid to the challenger, in percent; the rest is burnt,
+// so that proposers can't get their deposit back by challenging their own results
+pub const CHALLENGER_REWARD_PERCENT: u64 = 50;
+
+pub type JobId = u64;
+
+/// Blake2b-256 digest of the model posted as the result of a job.
+pub type Commitment = [u8; 32];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JobStatus {
+    /// The result can still be challenged.
+    Pending,
+    /// The challenge window has passed without a successful challenge.
+    Finalized,
+    /// A challenge proved that the result was wrong; the model must not be used.
+    Reverted,
+}
+
+/// A training job executed off-chain by a single proposer.
+#[derive(Debug, Clone, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct Job {
+    pub proposer: Address,
+    /// The `Train*` method of the machine learning actor which produces the model.
+    pub method: MethodNum,
+    /// The parameters the training method has to be invoked with.
+    pub params: RawBytes,
+    /// The model the proposer claims the training method returns.
+    pub model: Vec<u8>,
+    pub commitment: Commitment,
+    pub posted_at: ChainEpoch,
+    /// Returned to the proposer once the job is finalized, or paid to a successful challenger.
+    pub deposit: TokenAmount,
+    pub status: JobStatus,
+}
+
+/// Whether a method of the machine learning actor can be executed as a job: only the `Train*`
+/// methods which return the trained model as bytes, which is what the commitment is checked against.
+pub fn is_job_method(method: MethodNum) -> bool {
+    use fendermint_actor_machinelearning::Method as MlMethod;
+
+    [
+        MlMethod::TrainLinearRegression as MethodNum,
+        MlMethod::TrainLogisticRegression as MethodNum,
+        MlMethod::TrainKNNRegression as MethodNum,
+    ]
+    .contains(&method)
+}
+
+impl Job {
+    /// The last epoch in which the result can be challenged.
+    pub fn challenge_deadline(&self, challenge_window: ChainEpoch) -> ChainEpoch {
+        self.posted_at + challenge_window
+    }
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct State {
+    // the machine learning actor used to re-execute challenged jobs
+    pub ml_actor: Address,
+
+    // the number of epochs after posting during which a result can be challenged
+    pub challenge_window: ChainEpoch,
+
+    // the minimum deposit to be sent along with a result
+    pub min_deposit: TokenAmount,
+
+    // the id the next posted job will get
+    pub next_job_id: JobId,
+
+    // the AMT root cid of jobs
+    pub jobs: Cid,
+
+    // the id of the oldest job which might still be pending; all jobs before it are settled,
+    // and since jobs expire in the order they were posted, the ones after it are pending or reverted
+    pub first_pending: JobId,
+}
+
+impl State {
+    pub fn new<BS: Blockstore>(store: &BS, params: ConstructorParams) -> anyhow::Result<Self> {
+        // without a deposit, wrong results could be posted for free
+        if params.min_deposit.is_zero() {
+            return Err(anyhow::anyhow!(
+                "the minimum deposit of jobs must be positive"
+            ));
+        }
+
+        let empty_jobs_cid =
+            match Amt::<(), _>::new_with_bit_width(store, JOBS_AMT_BITWIDTH).flush() {
+                Ok(cid) => cid,
+                Err(e) => {
+                    return Err(anyhow::anyhow!(
+                        "mljobs actor failed to create empty Amt: {}",
+                        e
+                    ))
+                }
+            };
+
+        Ok(Self {
+            ml_actor: params.ml_actor,
+            challenge_window: params.challenge_window,
+            min_deposit: params.min_deposit,
+            next_job_id: 0,
+            jobs: empty_jobs_cid,
+            first_pending: 0,
+        })
+    }
+
+    // loads the jobs array from the AMT root cid and returns the job with the given id
+    pub fn get_job<BS: Blockstore>(
+        &self,
+        store: &BS,
+        job_id: JobId,
+    ) -> anyhow::Result<Option<Job>> {
+        let jobs = match Amt::<Job, _>::load(&self.jobs, &store) {
+            Ok(v) => v,
+            Err(e) => {
+                return Err(anyhow::anyhow!(
+                    "failed to load jobs from AMT cid {}, error: {}",
+                    self.jobs,
+                    e
+                ));
+            }
+        };
+
+        match jobs.get(job_id) {
+            Ok(Some(v)) => Ok(Some(v.clone())),
+            Ok(None) => Ok(None),
+            Err(err) => Err(anyhow::anyhow!(
+                "failed to get job {}, error: {}",
+                job_id,
+                err
+            )),
+        }
+    }
+
+    // returns the pending jobs which were posted at the given epoch
+    pub fn jobs_posted_at<BS: Blockstore>(
+        &self,
+        store: &BS,
+        epoch: ChainEpoch,
+    ) -> anyhow::Result<Vec<(JobId, Job)>> {
+        let mut jobs = Vec::new();
+        // jobs are in the order of posting, so we can stop at the first older one
+        for job_id in (self.first_pending..self.next_job_id).rev() {
+            match self.get_job(store, job_id)? {
+                Some(job) if job.posted_at > epoch => continue,
+                Some(job) if job.posted_at == epoch => {
+                    if job.status == JobStatus::Pending {
+                        jobs.push((job_id, job))
+                    }
+                }
+                _ => break,
+            }
+        }
+        jobs.reverse();
+        Ok(jobs)
+    }
+}
+
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct ConstructorParams {
+    pub ml_actor: Address,
+    pub challenge_window: ChainEpoch,
+    pub min_deposit: TokenAmount,
+}
+
+#[derive(Default, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct PostResultParams {
+    pub method: MethodNum,
+    pub params: RawBytes,
+    pub model: Vec<u8>,
+}
+
+#[derive(Default, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct ChallengeParams {
+    pub job_id: JobId,
+}
+
+#[derive(FromPrimitive)]
+#[repr(u64)]
+pub enum Method {
+    Constructor = METHOD_CONSTRUCTOR,
+    PostResult = frc42_dispatch::method_hash!("PostResult"),
+    Challenge = frc42_dispatch::method_hash!("Challenge"),
+    FinalizeExpired = frc42_dispatch::method_hash!("FinalizeExpired"),
+    GetJob = frc42_dispatch::method_hash!("GetJob"),
+}
@@ -4,7 +4,9 @@ use anyhow::{anyhow, Context};
 use cid::Cid;
 use fendermint_actor_chainmetadata::CHAINMETADATA_ACTOR_NAME;
 use fendermint_actor_eam::IPC_EAM_ACTOR_NAME;
+use fendermint_actor_faucet::FAUCET_ACTOR_NAME;
 use fendermint_actor_machinelearning::MACHINELEARNING_ACTOR_NAME;
+use fendermint_actor_mljobs::MLJOBS_ACTOR_NAME;
 use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::CborStore;
 use std::collections::HashMap;
@@ -14,6 +16,8 @@ pub const REQUIRED_ACTORS: &[&str] = &[
     CHAINMETADATA_ACTOR_NAME,
     IPC_EAM_ACTOR_NAME,
     MACHINELEARNING_ACTOR_NAME,
+    MLJOBS_ACTOR_NAME,
+    FAUCET_ACTOR_NAME,
 ];
 
 /// A mapping of internal actor CIDs to their respective types.
@@ -7,6 +7,7 @@ use crate::parse::parse_eth_address;
 use clap::{Args, Subcommand};
 use fvm_shared::address::Address;
 use ipc_api::subnet_id::SubnetID;
+use tendermint_rpc::Url;
 
 #[derive(Args, Debug)]
 pub struct DebugArgs {
@@ -29,6 +30,12 @@ pub enum DebugIpcCommands {
     ///
     /// This can be used to construct an upgrade to impute missing events.
     ExportTopDownEvents(Box<DebugExportTopDownEventsArgs>),
+
+    /// Fetch the duplicate vote evidence committed in a block and export it to JSON,
+    /// in the format the subnet actor on the parent expects it.
+    ///
+    /// The file can be used to report the validators with `ipc-cli subnet report-double-sign`.
+    ExportDoubleSignEvidence(DebugExportDoubleSignEvidenceArgs),
 }
 
 #[derive(Args, Debug, Clone)]
@@ -65,3 +72,28 @@ pub struct DebugExportTopDownEventsArgs {
     #[arg(long)]
     pub events_file: PathBuf,
 }
+
+#[derive(Args, Debug, Clone)]
+pub struct DebugExportDoubleSignEvidenceArgs {
+    /// The URL of the Tendermint node's RPC endpoint.
+    #[arg(
+        long,
+        short,
+        default_value = "http://127.0.0.1:26657",
+        env = "TENDERMINT_RPC_URL"
+    )]
+    pub url: Url,
+
+    /// An optional HTTP/S proxy through which to submit requests to the
+    /// Tendermint node's RPC endpoint.
+    #[arg(long)]
+    pub proxy_url: Option<Url>,
+
+    /// The block which committed the evidence.
+    #[arg(long)]
+    pub height: u64,
+
+    /// Location of the JSON file to write the evidence to.
+    #[arg(long)]
+    pub evidence_file: PathBuf,
+}
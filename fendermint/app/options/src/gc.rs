@@ -0,0 +1,15 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use clap::Args;
+
+/// Offline garbage collection of the state store.
+///
+/// The node should not be running, because the database can only be opened by one process;
+/// to collect garbage while the node is running enable `db.gc` in the configuration instead.
+#[derive(Args, Debug)]
+pub struct GcArgs {
+    /// Only count the unreachable blocks, don't delete them.
+    #[arg(long, default_value_t = false)]
+    pub dry_run: bool,
+}
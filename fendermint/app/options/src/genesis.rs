@@ -3,15 +3,16 @@
 
 use std::path::PathBuf;
 
+use bytes::Bytes;
 use clap::{Args, Subcommand, ValueEnum};
 use ipc_api::subnet_id::SubnetID;
 
 use super::parse::{
-    parse_eth_address, parse_full_fil, parse_network_version, parse_percentage, parse_signer_addr,
-    parse_token_amount,
+    parse_bytes, parse_eth_address, parse_full_fil, parse_network_version, parse_percentage,
+    parse_signer_addr, parse_token_amount,
 };
 use fendermint_vm_genesis::SignerAddr;
-use fvm_shared::{address::Address, econ::TokenAmount, version::NetworkVersion};
+use fvm_shared::{address::Address, clock::ChainEpoch, econ::TokenAmount, version::NetworkVersion};
 
 #[derive(Debug, Clone, ValueEnum)]
 pub enum AccountKind {
@@ -29,8 +30,18 @@ pub enum GenesisCommands {
     AddMultisig(GenesisAddMultisigArgs),
     /// Add a validator to the genesis file.
     AddValidator(GenesisAddValidatorArgs),
+    /// Add an EVM contract to deploy at genesis, after the IPC contracts.
+    AddContract(GenesisAddContractArgs),
     /// Set the EAM actor permission mode.
     SetEamPermissions(GenesisSetEAMPermissionsArgs),
+    /// Set who can train models with the machine learning actor.
+    SetMlTrainingPolicy(GenesisSetMlTrainingPolicyArgs),
+    /// Fund the faucet distributing test funds, and set how much it gives.
+    SetFaucet(GenesisSetFaucetArgs),
+    /// Set the limits on the inputs of the machine learning actor enforced by consensus.
+    SetMlLimits(GenesisSetMlLimitsArgs),
+    /// Set the gas limits of the implicit messages sent by the system actor in every block.
+    SetImplicitGas(GenesisSetImplicitGasArgs),
     /// IPC commands.
     Ipc {
         #[command(subcommand)]
@@ -60,6 +71,101 @@ pub struct GenesisSetEAMPermissionsArgs {
     pub addresses: Vec<SignerAddr>,
 }
 
+#[derive(Args, Debug)]
+pub struct GenesisSetMlTrainingPolicyArgs {
+    #[arg(
+        long,
+        short,
+        default_value = "unrestricted",
+        help = "Permission mode (unrestricted/allowlist) that controls who can train models in the subnet"
+    )]
+    pub mode: String,
+
+    #[arg(
+        long,
+        short,
+        value_delimiter = ',',
+        value_parser = parse_signer_addr,
+        help = "List of addresses that can train models. Field is ignored if mode is unrestricted"
+    )]
+    pub addresses: Vec<SignerAddr>,
+
+    /// Fee to pay with every training call in full FIL units.
+    #[arg(long, short, default_value = "0", value_parser = parse_full_fil)]
+    pub fee: TokenAmount,
+
+    /// Number of blocks during which the result of an off-chain training job can be challenged.
+    #[arg(long, default_value = "100")]
+    pub job_challenge_window: ChainEpoch,
+
+    /// Deposit the proposer of an off-chain training job result has to lock up, in full FIL units.
+    /// Must be positive.
+    #[arg(long, default_value = "1", value_parser = parse_full_fil)]
+    pub job_deposit: TokenAmount,
+}
+
+#[derive(Args, Debug)]
+pub struct GenesisSetFaucetArgs {
+    /// Funds of the faucet in full FIL units.
+    #[arg(long, value_parser = parse_full_fil)]
+    pub balance: TokenAmount,
+
+    /// Amount sent with every drip in full FIL units.
+    #[arg(long, default_value = "10", value_parser = parse_full_fil)]
+    pub drip_amount: TokenAmount,
+
+    /// Number of blocks an address has to wait between two drips.
+    #[arg(long, default_value = "1000")]
+    pub cooldown: ChainEpoch,
+}
+
+#[derive(Args, Debug)]
+pub struct GenesisSetMlLimitsArgs {
+    /// Maximum number of rows of an input matrix.
+    #[arg(long, default_value = "10000")]
+    pub max_rows: u64,
+
+    /// Maximum number of columns of an input matrix.
+    #[arg(long, default_value = "1000")]
+    pub max_cols: u64,
+
+    /// Maximum size of the parameters of a call to the actor, in bytes.
+    #[arg(long, default_value = "4194304")]
+    pub max_params_bytes: u64,
+
+    /// Maximum sum of the gas limits of the messages sent to the actor in a block.
+    #[arg(long, default_value = "5000000000")]
+    pub max_block_gas: u64,
+}
+
+#[derive(Args, Debug)]
+pub struct GenesisSetImplicitGasArgs {
+    /// Gas limit of the cron message.
+    #[arg(long, default_value = "100000000000000")]
+    pub cron: u64,
+
+    /// Block interval in seconds the cron limit is meant for; when set, the limit is multiplied
+    /// by the number of such intervals elapsed since the parent block.
+    #[arg(long)]
+    pub cron_reference_interval: Option<u64>,
+
+    /// Maximum multiplier applied to the cron gas limit when blocks are far apart.
+    #[arg(long, default_value = "1")]
+    pub cron_max_multiplier: u64,
+
+    /// Gas limit of pushing the block hash to the chainmetadata actor.
+    #[arg(long, default_value = "100000000000000")]
+    pub chain_metadata: u64,
+
+    /// Gas limit of the built-in machine learning messages.
+    #[arg(long, default_value = "100000000000000")]
+    pub machine_learning: u64,
+
+    /// Gas limit of finalizing the machine learning jobs whose challenge window has passed.
+    #[arg(long, default_value = "100000000000000")]
+    pub ml_jobs: u64,
+}
+
 #[derive(Args, Debug)]
 pub struct GenesisArgs {
     /// Path to the genesis JSON file.
@@ -131,6 +237,19 @@ pub struct GenesisAddValidatorArgs {
     pub power: TokenAmount,
 }
 
+#[derive(Args, Debug)]
+pub struct GenesisAddContractArgs {
+    /// Name of the contract, to tell it apart in the logs.
+    #[arg(long, short)]
+    pub name: String,
+    /// Path to a compiled Solidity contract, expected to be in hexadecimal format.
+    #[arg(long, short)]
+    pub contract: PathBuf,
+    /// ABI encoded constructor arguments passed to the EVM, expected to be in hexadecimal format.
+    #[arg(long, short = 'a', value_parser = parse_bytes, default_value = "")]
+    pub constructor_args: Bytes,
+}
+
 #[derive(Args, Debug)]
 pub struct GenesisIntoTendermintArgs {
     /// Output file name for the Tendermint genesis JSON file.
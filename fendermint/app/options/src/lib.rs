@@ -11,18 +11,21 @@ use lazy_static::lazy_static;
 use tracing_subscriber::EnvFilter;
 
 use self::{
-    eth::EthArgs, genesis::GenesisArgs, key::KeyArgs, materializer::MaterializerArgs, rpc::RpcArgs,
-    run::RunArgs,
+    eth::EthArgs, gc::GcArgs, genesis::GenesisArgs, key::KeyArgs, materializer::MaterializerArgs,
+    replay::ReplayArgs, rpc::RpcArgs, run::RunArgs, snapshot::SnapshotArgs,
 };
 
 pub mod config;
 pub mod debug;
 pub mod eth;
+pub mod gc;
 pub mod genesis;
 pub mod key;
 pub mod materializer;
+pub mod replay;
 pub mod rpc;
 pub mod run;
+pub mod snapshot;
 
 mod log;
 mod parse;
@@ -110,6 +113,21 @@ pub struct Options {
     #[arg(long, env = "FM_LOG_FILE_PREFIX")]
     pub log_file_prefix: Option<String>,
 
+    /// Optionally export tracing spans to an OpenTelemetry collector at this OTLP/gRPC endpoint,
+    /// e.g. `http://localhost:4317`.
+    #[arg(long, env = "FM_OTLP_ENDPOINT")]
+    pub otlp_endpoint: Option<String>,
+
+    /// Set the level of the spans exported to the OpenTelemetry collector, independently of the logs.
+    #[arg(
+        long,
+        default_value = "info",
+        value_enum,
+        env = "FM_OTLP_LEVEL",
+        value_parser = parse_log_level,
+    )]
+    otlp_level: LogLevel,
+
     /// Optionally override the default configuration.
     #[arg(short, long, default_value = "dev")]
     pub mode: String,
@@ -135,6 +153,11 @@ pub struct Options {
     )]
     log_file_level: Option<LogLevel>,
 
+    /// Comma separated regular expressions to redact from the logs, on top of the built-in rules
+    /// for private keys, auth tokens and signatures.
+    #[arg(long, env = "FM_LOG_REDACT", value_delimiter = ',')]
+    pub log_redact: Vec<String>,
+
     /// Global options repeated here for discoverability, so they show up in `--help` among the others.
     #[command(flatten)]
     pub global: GlobalArgs,
@@ -162,6 +185,11 @@ impl Options {
         }
     }
 
+    /// Tracing filter for the spans exported over OTLP.
+    pub fn otlp_filter(&self) -> anyhow::Result<EnvFilter> {
+        self.otlp_level.to_filter()
+    }
+
     /// Path to the configuration directories.
     ///
     /// If not specified then returns the default under the home directory.
@@ -195,6 +223,12 @@ pub enum Commands {
     Rpc(RpcArgs),
     /// Subcommands related to the Ethereum API facade.
     Eth(EthArgs),
+    /// Subcommands related to exporting and importing state snapshots.
+    Snapshot(SnapshotArgs),
+    /// Delete blocks from the state store which are not reachable from the state history.
+    Gc(GcArgs),
+    /// Re-execute finalized blocks to find where the state diverged from the recorded one.
+    Replay(ReplayArgs),
     /// Subcommands related to the Testnet Materializer.
     #[clap(aliases  = &["mat", "matr", "mate"])]
     Materializer(MaterializerArgs),
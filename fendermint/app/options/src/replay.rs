@@ -0,0 +1,34 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use clap::Args;
+use tendermint_rpc::Url;
+
+/// Re-execute a range of finalized blocks and compare the state roots with the ones in the state history.
+///
+/// The node should not be running, because the database can only be opened by one process,
+/// but CometBFT has to be, to serve the blocks. Nothing is written to the database.
+#[derive(Args, Debug)]
+pub struct ReplayArgs {
+    /// The first block to replay; the state committed before it has to be in the state history.
+    #[arg(long)]
+    pub from: u64,
+
+    /// The last block to replay; defaults to the latest one in the state history.
+    #[arg(long)]
+    pub to: Option<u64>,
+
+    /// The URL of the Tendermint node's RPC endpoint.
+    #[arg(
+        long,
+        short,
+        default_value = "http://127.0.0.1:26657",
+        env = "TENDERMINT_RPC_URL"
+    )]
+    pub url: Url,
+
+    /// An optional HTTP/S proxy through which to submit requests to the
+    /// Tendermint node's RPC endpoint.
+    #[arg(long)]
+    pub proxy_url: Option<Url>,
+}
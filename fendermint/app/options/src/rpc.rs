@@ -92,6 +92,24 @@ pub enum RpcQueryCommands {
     },
     /// Get the slowly changing state parameters.
     StateParams,
+    /// Predict with a listed machine learning model as it was at the queried height, without paying its fee.
+    MlPredict {
+        /// ID of the listed model.
+        #[arg(long, short)]
+        model_id: u64,
+        /// Input matrix as JSON, either a list of rows or a sparse matrix.
+        #[arg(long, short)]
+        input_matrix: String,
+    },
+    /// Export a listed machine learning model to a CAR file, along with the blocks proving it's part of the state.
+    ExportModel {
+        /// ID of the listed model.
+        #[arg(long, short)]
+        model_id: u64,
+        /// Path of the CAR file to write.
+        #[arg(long, short)]
+        output: PathBuf,
+    },
 }
 
 #[derive(Subcommand, Debug, Clone)]
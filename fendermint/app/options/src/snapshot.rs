@@ -0,0 +1,54 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::path::PathBuf;
+
+use cid::Cid;
+use clap::{Args, Subcommand};
+
+#[derive(Args, Debug)]
+pub struct SnapshotArgs {
+    #[command(subcommand)]
+    pub command: SnapshotCommands,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SnapshotCommands {
+    /// Export the FVM state tree committed at a given height into a CAR file.
+    ///
+    /// The node should not be running, because the database can only be opened by one process.
+    Export(SnapshotExportArgs),
+    /// Import a CAR file created by `snapshot export` and make it the last committed state,
+    /// so that a new node can start from there instead of replaying from genesis.
+    Import(SnapshotImportArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct SnapshotExportArgs {
+    /// Committed block height to export; defaults to the latest committed height.
+    ///
+    /// Only heights still in the state history can be exported, see `db.state_hist_size`.
+    #[arg(long)]
+    pub height: Option<u64>,
+
+    /// Location of the CAR file to write the snapshot to.
+    #[arg(long, short)]
+    pub out_file: PathBuf,
+}
+
+#[derive(Args, Debug)]
+pub struct SnapshotImportArgs {
+    /// Location of the CAR file to read the snapshot from.
+    #[arg(long, short)]
+    pub in_file: PathBuf,
+
+    /// The state root the snapshot is expected to have, obtained from a trusted source.
+    ///
+    /// If given, the import fails unless the snapshot contains exactly this state.
+    #[arg(long)]
+    pub state_root: Option<Cid>,
+
+    /// Overwrite the committed state even if the node has already executed blocks.
+    #[arg(long, default_value_t = false)]
+    pub force: bool,
+}
@@ -29,4 +29,47 @@ pub struct FvmSettings {
     /// Gas premium used when broadcasting transactions.
     #[serde_as(as = "IsHumanReadable")]
     pub gas_premium: TokenAmount,
+
+    /// Checks applied to transactions before they are admitted to the mempool.
+    pub prevalidation: PrevalidationSettings,
+
+    /// Logging of the delivered transactions.
+    pub delivery_log: DeliveryLogSettings,
+}
+
+/// Level of the event logged for every delivered transaction.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DeliveryLogLevel {
+    Off,
+    Trace,
+    Debug,
+    Info,
+}
+
+/// Logging of the delivered transactions; it doesn't affect consensus.
+#[derive(Debug, Deserialize, Clone)]
+pub struct DeliveryLogSettings {
+    /// Level of the event logged for every delivered transaction.
+    pub tx_level: DeliveryLogLevel,
+    /// Log a summary of the transactions of every block at info level.
+    pub block_summary: bool,
+    /// Repeat the fields of the events as a JSON object in a `json` field, for log ingestion.
+    pub json: bool,
+}
+
+/// Limits enforced when transactions are checked, before they are added to the mempool.
+#[serde_as]
+#[derive(Debug, Deserialize, Clone)]
+pub struct PrevalidationSettings {
+    /// Minimum gas fee cap; transactions also have to cover the base fee.
+    #[serde_as(as = "IsHumanReadable")]
+    pub min_gas_fee_cap: TokenAmount,
+    /// Minimum gas premium.
+    #[serde_as(as = "IsHumanReadable")]
+    pub min_gas_premium: TokenAmount,
+    /// Maximum number of rows in the input matrix of machine learning calls.
+    pub ml_max_rows: usize,
+    /// Maximum number of columns in the input matrix of machine learning calls.
+    pub ml_max_cols: usize,
 }
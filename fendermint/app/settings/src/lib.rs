@@ -7,7 +7,7 @@ use fvm_shared::address::Address;
 use fvm_shared::econ::TokenAmount;
 use ipc_api::subnet_id::SubnetID;
 use serde::{Deserialize, Serialize};
-use serde_with::{serde_as, DurationSeconds};
+use serde_with::{serde_as, DurationMilliSeconds, DurationSeconds};
 use std::fmt::{Display, Formatter};
 use std::net::{SocketAddr, ToSocketAddrs};
 use std::path::{Path, PathBuf};
@@ -137,6 +137,25 @@ pub struct DbSettings {
     pub state_hist_size: u64,
     /// How to compact the datastore.
     pub compaction_style: DbCompaction,
+    /// Garbage collection of state which is no longer reachable.
+    pub gc: DbGcSettings,
+}
+
+/// Settings for removing blocks from the state store which are not reachable from any of
+/// the state roots in the history, the length of which is controlled by `state_hist_size`.
+#[serde_as]
+#[derive(Debug, Deserialize, Clone)]
+pub struct DbGcSettings {
+    /// Run garbage collection in the background while the node is running.
+    pub enabled: bool,
+    /// Time to wait between rounds of garbage collection.
+    #[serde_as(as = "DurationSeconds<u64>")]
+    pub interval: Duration,
+    /// Maximum number of blocks to delete in one batch.
+    pub batch_size: usize,
+    /// Pause between batches, to limit the impact on block execution.
+    #[serde_as(as = "DurationMilliSeconds<u64>")]
+    pub batch_delay: Duration,
 }
 
 /// Settings affecting how we deal with failures in trying to send transactions to the local CometBFT node.
@@ -152,6 +171,9 @@ pub struct BroadcastSettings {
     pub retry_delay: Duration,
     /// Any over-estimation to apply on top of the estimate returned by the API.
     pub gas_overestimation_rate: f64,
+    /// Time to wait on shutdown for the broadcasts in flight to finish, before aborting them.
+    #[serde_as(as = "DurationSeconds<u64>")]
+    pub shutdown_timeout: Duration,
 }
 
 #[serde_as]
@@ -207,6 +229,23 @@ pub struct IpcSettings {
     /// The config for top down checkpoint. It's None if subnet id is root or not activating
     /// any top down checkpoint related operations
     pub topdown: Option<TopDownSettings>,
+    /// How validators share their signatures of the bottom-up checkpoints.
+    pub checkpoint_signatures: CheckpointSignatures,
+    /// How long the validators wait for the gossiped signatures of a checkpoint to reach a
+    /// quorum before one of them adds them to the ledger.
+    #[serde_as(as = "DurationSeconds<u64>")]
+    pub checkpoint_quorum_timeout: Duration,
+}
+
+/// How validators share their signatures of the bottom-up checkpoints.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckpointSignatures {
+    /// Each validator adds its signature to the ledger with a transaction.
+    Ledger,
+    /// Validators gossip their signatures through the IPLD Resolver, and once they reach a
+    /// quorum one of them adds them all to the ledger with a single transaction.
+    Gossip,
 }
 
 impl IpcSettings {
@@ -24,7 +24,7 @@ use fendermint_vm_interpreter::fvm::state::{
     FvmUpdatableParams,
 };
 use fendermint_vm_interpreter::fvm::store::ReadOnlyBlockstore;
-use fendermint_vm_interpreter::fvm::{FvmApplyRet, FvmGenesisOutput, PowerUpdates};
+use fendermint_vm_interpreter::fvm::{FvmApplyRet, FvmEndRet, FvmGenesisOutput};
 use fendermint_vm_interpreter::signed::InvalidSignature;
 use fendermint_vm_interpreter::{
     CheckInterpreter, ExecInterpreter, GenesisInterpreter, ProposalInterpreter, QueryInterpreter,
@@ -37,6 +37,7 @@ use fvm_shared::chainid::ChainID;
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::version::NetworkVersion;
+use ipc_api::subnet_id::SubnetID;
 use num_traits::Zero;
 use serde::{Deserialize, Serialize};
 use tendermint::abci::request::CheckTxKind;
@@ -44,6 +45,7 @@ use tendermint::abci::{request, response};
 use tracing::instrument;
 
 use crate::events::{NewBlock, ProposalProcessed};
+use crate::gc::CommitLock;
 use crate::AppExitCode;
 use crate::BlockHeight;
 use crate::{tmconv::*, VERSION};
@@ -80,6 +82,24 @@ pub struct AppState {
 }
 
 impl AppState {
+    /// Create the record of a state which was imported at `block_height`,
+    /// rather than built up by executing the blocks leading up to it.
+    pub fn new(block_height: BlockHeight, state_params: FvmStateParams) -> Self {
+        Self {
+            block_height,
+            oldest_state_height: block_height,
+            state_params,
+        }
+    }
+
+    pub fn block_height(&self) -> BlockHeight {
+        self.block_height
+    }
+
+    pub fn state_params(&self) -> &FvmStateParams {
+        &self.state_params
+    }
+
     pub fn state_root(&self) -> Cid {
         self.state_params.state_root
     }
@@ -114,6 +134,10 @@ pub struct AppConfig<S: KVStore> {
     pub custom_actors_bundle: PathBuf,
     /// Block height where we should gracefully stop the node
     pub halt_height: i64,
+    /// The ID of the subnet this node is part of; recorded in the block lifecycle spans.
+    pub subnet_id: SubnetID,
+    /// Held while committing a block, so the garbage collector can't delete its blocks.
+    pub commit_lock: CommitLock,
 }
 
 /// Handle ABCI requests.
@@ -141,6 +165,8 @@ where
     custom_actors_bundle: PathBuf,
     /// Block height where we should gracefully stop the node
     halt_height: i64,
+    /// The ID of the subnet this node is part of.
+    subnet_id: SubnetID,
     /// Namespace to store app state.
     namespace: S::Namespace,
     /// Collection of past state parameters.
@@ -168,6 +194,8 @@ where
     ///
     /// Zero means unlimited.
     state_hist_size: u64,
+    /// Held from flushing the state of a block until its root is in the history.
+    commit_lock: CommitLock,
 }
 
 impl<DB, SS, S, I> App<DB, SS, S, I>
@@ -195,6 +223,7 @@ where
             builtin_actors_bundle: config.builtin_actors_bundle,
             custom_actors_bundle: config.custom_actors_bundle,
             halt_height: config.halt_height,
+            subnet_id: config.subnet_id,
             namespace: config.app_namespace,
             state_hist: KVCollection::new(config.state_hist_namespace),
             state_hist_size: config.state_hist_size,
@@ -203,6 +232,7 @@ where
             snapshots,
             exec_state: Arc::new(tokio::sync::Mutex::new(None)),
             check_state: Arc::new(tokio::sync::Mutex::new(None)),
+            commit_lock: config.commit_lock,
         };
         app.init_committed_state()?;
         Ok(app)
@@ -243,6 +273,8 @@ where
                     chain_id: 0,
                     power_scale: 0,
                     app_version: 0,
+                    ml_limits: None,
+                    implicit_gas: None,
                 },
             };
             self.set_committed_state(state)?;
@@ -265,6 +297,12 @@ where
         }
     }
 
+    /// The cap on the gas of the machine learning messages in the next block, set in the state.
+    fn max_ml_gas(&self) -> Result<Option<u64>> {
+        let state = self.committed_state()?;
+        Ok(state.state_params.ml_limits.and_then(|l| l.max_block_gas))
+    }
+
     /// Set the last committed state.
     fn set_committed_state(&self, mut state: AppState) -> Result<()> {
         self.db
@@ -410,13 +448,13 @@ where
         Genesis = Vec<u8>,
         Output = FvmGenesisOutput,
     >,
-    I: ProposalInterpreter<State = ChainEnv, Message = Vec<u8>>,
+    I: ProposalInterpreter<State = (Option<u64>, ChainEnv), Message = Vec<u8>>,
     I: ExecInterpreter<
         State = (ChainEnv, FvmExecState<SS>),
         Message = Vec<u8>,
         BeginOutput = FvmApplyRet,
         DeliverOutput = BytesMessageApplyRes,
-        EndOutput = PowerUpdates,
+        EndOutput = FvmEndRet,
     >,
     I: CheckInterpreter<
         State = FvmExecState<ReadOnlyBlockstore<SS>>,
@@ -456,6 +494,9 @@ where
             anyhow!("failed to load custom actor bundle CAR from {custom_actors_bundle:?}: {e}")
         })?;
 
+        // The genesis state is only in the store, not in the history, until the end.
+        let commit_guard = self.commit_lock.lock().await;
+
         let state = FvmGenesisState::new(
             self.state_store_clone(),
             self.multi_engine.clone(),
@@ -511,6 +552,8 @@ where
                 chain_id: out.chain_id.into(),
                 power_scale: out.power_scale,
                 app_version: 0,
+                ml_limits: out.ml_limits,
+                implicit_gas: out.implicit_gas,
             },
         };
 
@@ -530,6 +573,7 @@ where
         );
 
         self.set_committed_state(app_state)?;
+        drop(commit_guard);
 
         Ok(response)
     }
@@ -642,10 +686,11 @@ where
             "prepare proposal"
         );
         let txs = request.txs.into_iter().map(|tx| tx.to_vec()).collect();
+        let max_ml_gas = self.max_ml_gas()?;
 
         let txs = self
             .interpreter
-            .prepare(self.chain_env.clone(), txs)
+            .prepare((max_ml_gas, self.chain_env.clone()), txs)
             .await
             .context("failed to prepare proposal")?;
 
@@ -667,10 +712,11 @@ where
         );
         let txs: Vec<_> = request.txs.into_iter().map(|tx| tx.to_vec()).collect();
         let num_txs = txs.len();
+        let max_ml_gas = self.max_ml_gas()?;
 
         let accept = self
             .interpreter
-            .process(self.chain_env.clone(), txs)
+            .process((max_ml_gas, self.chain_env.clone()), txs)
             .await
             .context("failed to process proposal")?;
 
@@ -690,6 +736,7 @@ where
     }
 
     /// Signals the beginning of a new block, prior to any `DeliverTx` calls.
+    #[instrument(skip_all, fields(subnet_id = %self.subnet_id, height = request.header.height.value(), method = "begin_block"))]
     async fn begin_block(&self, request: request::BeginBlock) -> AbciResult<response::BeginBlock> {
         let block_height = request.header.height.into();
         let block_hash = match request.hash {
@@ -717,12 +764,14 @@ where
             "begin block"
         );
 
+        let parent_timestamp = state_params.timestamp;
         state_params.timestamp = to_timestamp(request.header.time);
 
         let state = FvmExecState::new(db, self.multi_engine.as_ref(), block_height, state_params)
             .context("error creating new state")?
             .with_block_hash(block_hash)
-            .with_validator_id(request.header.proposer_address);
+            .with_validator_id(request.header.proposer_address)
+            .with_parent_timestamp(parent_timestamp);
 
         tracing::debug!("initialized exec state");
 
@@ -737,6 +786,7 @@ where
     }
 
     /// Apply a transaction to the application's state.
+    #[instrument(skip_all, fields(subnet_id = %self.subnet_id, method = "deliver_tx"))]
     async fn deliver_tx(&self, request: request::DeliverTx) -> AbciResult<response::DeliverTx> {
         let msg = request.tx.to_vec();
         let (result, block_hash) = self
@@ -757,7 +807,16 @@ where
                 ChainMessageApplyRet::Signed(Ok(ret)) => {
                     to_deliver_tx(ret.fvm, ret.domain_hash, block_hash)
                 }
-                ChainMessageApplyRet::Ipc(ret) => to_deliver_tx(ret, None, block_hash),
+                ChainMessageApplyRet::Ipc(ret, receipts) => {
+                    let mut response = to_deliver_tx(ret, None, block_hash);
+                    for receipt in receipts.iter() {
+                        response.events.push(
+                            to_topdown_receipt_event(receipt)
+                                .context("failed to convert topdown receipt")?,
+                        );
+                    }
+                    response
+                }
             },
         };
 
@@ -773,10 +832,10 @@ where
     }
 
     /// Signals the end of a block.
+    #[instrument(skip_all, fields(subnet_id = %self.subnet_id, height = request.height, method = "end_block"))]
     async fn end_block(&self, request: request::EndBlock) -> AbciResult<response::EndBlock> {
         tracing::debug!(height = request.height, "end block");
 
-        // TODO: Return events from epoch transitions.
         let ret = self
             .modify_exec_state(|s| self.interpreter.end(s))
             .await
@@ -791,6 +850,9 @@ where
     async fn commit(&self) -> AbciResult<response::Commit> {
         let exec_state = self.take_exec_state().await;
 
+        // Don't let the garbage collector sweep until the new state root is in the history.
+        let commit_guard = self.commit_lock.lock().await;
+
         // Commit the execution state to the datastore.
         let mut state = self.committed_state()?;
         state.block_height = exec_state.block_height().try_into()?;
@@ -803,6 +865,8 @@ where
                 base_fee,
                 circ_supply,
                 power_scale,
+                ml_limits,
+                implicit_gas,
             },
             _,
         ) = exec_state.commit().context("failed to commit FVM")?;
@@ -812,6 +876,8 @@ where
         state.state_params.base_fee = base_fee;
         state.state_params.circ_supply = circ_supply;
         state.state_params.power_scale = power_scale;
+        state.state_params.ml_limits = ml_limits;
+        state.state_params.implicit_gas = implicit_gas;
 
         let app_hash = state.app_hash();
         let block_height = state.block_height;
@@ -866,6 +932,7 @@ where
 
         // Commit app state to the datastore.
         self.set_committed_state(state)?;
+        drop(commit_guard);
 
         emit!(NewBlock { block_height });
 
@@ -985,6 +1052,9 @@ where
                             "received all snapshot chunks",
                         );
 
+                        // The imported blocks are unreachable until the state is in the history.
+                        let commit_guard = self.commit_lock.lock().await;
+
                         // Ideally we would import into some isolated store then validate,
                         // but for now let's trust that all is well.
                         if let Err(e) = snapshot.import(self.state_store_clone(), true).await {
@@ -1007,6 +1077,7 @@ where
                         state.block_height = snapshot.manifest.block_height;
                         state.state_params = snapshot.manifest.state_params;
                         self.set_committed_state(state)?;
+                        drop(commit_guard);
 
                         // TODO: We can remove the `current_download` from the STM
                         // state here which would cause it to get dropped from /tmp,
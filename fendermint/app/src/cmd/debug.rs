@@ -2,9 +2,12 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use anyhow::{anyhow, Context};
+use fendermint_app::evidence::fetch_double_sign_evidence;
 use fendermint_app_options::debug::{
-    DebugArgs, DebugCommands, DebugExportTopDownEventsArgs, DebugIpcCommands,
+    DebugArgs, DebugCommands, DebugExportDoubleSignEvidenceArgs, DebugExportTopDownEventsArgs,
+    DebugIpcCommands,
 };
+use fendermint_rpc::client::http_client;
 use fendermint_vm_topdown::proxy::IPCProviderProxy;
 use ipc_provider::{
     config::subnet::{EVMSubnet, SubnetConfig},
@@ -25,7 +28,9 @@ cmd! {
   DebugIpcCommands(self) {
     match self {
         DebugIpcCommands::ExportTopDownEvents(args) =>
-            export_topdown_events(args).await
+            export_topdown_events(args).await,
+        DebugIpcCommands::ExportDoubleSignEvidence(args) =>
+            export_double_sign_evidence(args).await,
     }
   }
 }
@@ -46,6 +51,11 @@ async fn export_topdown_events(args: &DebugExportTopDownEventsArgs) -> anyhow::R
                 auth_token: args.parent_auth_token.clone(),
                 registry_addr: args.parent_registry,
                 gateway_addr: args.parent_gateway,
+                admin: None,
+                response_cache: None,
+                max_concurrent_requests: None,
+                fallback_provider_http: vec![],
+                gas: None,
             }),
         },
     )?;
@@ -66,3 +76,23 @@ async fn export_topdown_events(args: &DebugExportTopDownEventsArgs) -> anyhow::R
 
     Ok(())
 }
+
+async fn export_double_sign_evidence(
+    args: &DebugExportDoubleSignEvidenceArgs,
+) -> anyhow::Result<()> {
+    let client = http_client(args.url.clone(), args.proxy_url.clone())?;
+
+    let height = tendermint::block::Height::try_from(args.height)?;
+    let evidence = fetch_double_sign_evidence(&client, height)
+        .await
+        .context("failed to fetch double sign evidence")?;
+
+    if evidence.is_empty() {
+        tracing::warn!(height = args.height, "no duplicate vote evidence in block");
+    }
+
+    let json = serde_json::to_string_pretty(&evidence)?;
+    std::fs::write(&args.evidence_file, json)?;
+
+    Ok(())
+}
@@ -0,0 +1,45 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use anyhow::Context;
+use fendermint_app::gc::{BlockstoreGc, GcParams};
+use fendermint_app_options::gc::GcArgs;
+use fendermint_rocksdb::blockstore::NamespaceBlockstore;
+
+use super::run::{open_db, Namespaces};
+use crate::cmd;
+
+cmd! {
+  GcArgs(self, settings) {
+    let ns = Namespaces::default();
+    let db = open_db(&settings, &ns).context("error opening DB")?;
+    let state_store = NamespaceBlockstore::new(db.clone(), ns.state_store)
+        .context("error creating state DB")?;
+
+    // Nothing else is using the database, so there is no need to slow down.
+    let gc = BlockstoreGc::new(
+        db,
+        state_store,
+        ns.state_hist,
+        GcParams {
+            batch_size: settings.db.gc.batch_size,
+            batch_delay: std::time::Duration::ZERO,
+            dry_run: self.dry_run,
+        },
+    );
+
+    let stats = tokio::task::spawn_blocking(move || gc.collect())
+        .await
+        .context("garbage collection panicked")??;
+
+    tracing::info!(
+        roots = stats.roots,
+        reachable = stats.reachable,
+        deleted = stats.deleted,
+        dry_run = self.dry_run,
+        "blockstore garbage collected"
+    );
+
+    Ok(())
+  }
+}
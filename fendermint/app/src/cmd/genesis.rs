@@ -11,8 +11,8 @@ use std::path::PathBuf;
 use fendermint_vm_actor_interface::eam::EthAddress;
 use fendermint_vm_core::{chainid, Timestamp};
 use fendermint_vm_genesis::{
-    ipc, Account, Actor, ActorMeta, Collateral, Genesis, Multisig, PermissionMode, SignerAddr,
-    Validator, ValidatorKey,
+    ipc, Account, Actor, ActorMeta, Collateral, Contract, Faucet, Genesis, ImplicitGasLimits,
+    MlLimits, MlTrainingPolicy, Multisig, PermissionMode, SignerAddr, Validator, ValidatorKey,
 };
 
 use crate::cmd;
@@ -28,8 +28,13 @@ cmd! {
         GenesisCommands::AddAccount(args) => args.exec(genesis_file).await,
         GenesisCommands::AddMultisig(args) => args.exec(genesis_file).await,
         GenesisCommands::AddValidator(args) => args.exec(genesis_file).await,
+        GenesisCommands::AddContract(args) => args.exec(genesis_file).await,
         GenesisCommands::IntoTendermint(args) => args.exec(genesis_file).await,
         GenesisCommands::SetEamPermissions(args) => args.exec(genesis_file).await,
+        GenesisCommands::SetMlTrainingPolicy(args) => args.exec(genesis_file).await,
+        GenesisCommands::SetFaucet(args) => args.exec(genesis_file).await,
+        GenesisCommands::SetMlLimits(args) => args.exec(genesis_file).await,
+        GenesisCommands::SetImplicitGas(args) => args.exec(genesis_file).await,
         GenesisCommands::Ipc { command } => command.exec(genesis_file).await,
     }
   }
@@ -46,6 +51,11 @@ cmd! {
       validators: Vec::new(),
       accounts: Vec::new(),
       eam_permission_mode: PermissionMode::Unrestricted,
+      ml_training_policy: Default::default(),
+      ml_limits: None,
+      implicit_gas: None,
+      faucet: Default::default(),
+      contracts: Vec::new(),
       ipc: None,
     };
 
@@ -74,6 +84,12 @@ cmd! {
   }
 }
 
+cmd! {
+  GenesisAddContractArgs(self, genesis_file: PathBuf) {
+    add_contract(&genesis_file, self)
+  }
+}
+
 cmd! {
   GenesisIntoTendermintArgs(self, genesis_file: PathBuf) {
     into_tendermint(&genesis_file, self)
@@ -86,6 +102,30 @@ cmd! {
   }
 }
 
+cmd! {
+  GenesisSetMlTrainingPolicyArgs(self, genesis_file: PathBuf) {
+    set_ml_training_policy(&genesis_file, self)
+  }
+}
+
+cmd! {
+  GenesisSetFaucetArgs(self, genesis_file: PathBuf) {
+    set_faucet(&genesis_file, self)
+  }
+}
+
+cmd! {
+  GenesisSetMlLimitsArgs(self, genesis_file: PathBuf) {
+    set_ml_limits(&genesis_file, self)
+  }
+}
+
+cmd! {
+  GenesisSetImplicitGasArgs(self, genesis_file: PathBuf) {
+    set_implicit_gas(&genesis_file, self)
+  }
+}
+
 cmd! {
   GenesisIpcCommands(self, genesis_file: PathBuf) {
     match self {
@@ -176,6 +216,24 @@ fn add_validator(genesis_file: &PathBuf, args: &GenesisAddValidatorArgs) -> anyh
     })
 }
 
+fn add_contract(genesis_file: &PathBuf, args: &GenesisAddContractArgs) -> anyhow::Result<()> {
+    update_genesis(genesis_file, |mut genesis| {
+        if genesis.contracts.iter().any(|c| c.name == args.name) {
+            return Err(anyhow!("contract already exists in the genesis file"));
+        }
+        let contract_hex =
+            std::fs::read_to_string(&args.contract).context("failed to read contract")?;
+        let mut init_code =
+            hex::decode(contract_hex.trim()).context("failed to parse contract from hex")?;
+        init_code.extend_from_slice(&args.constructor_args);
+        genesis.contracts.push(Contract {
+            name: args.name.clone(),
+            init_code,
+        });
+        Ok(genesis)
+    })
+}
+
 fn read_genesis(genesis_file: &PathBuf) -> anyhow::Result<Genesis> {
     let json = std::fs::read_to_string(genesis_file).context("failed to read genesis")?;
     let genesis = serde_json::from_str::<Genesis>(&json).context("failed to parse genesis")?;
@@ -210,6 +268,69 @@ fn set_eam_permissions(
     })
 }
 
+fn set_ml_training_policy(
+    genesis_file: &PathBuf,
+    args: &GenesisSetMlTrainingPolicyArgs,
+) -> anyhow::Result<()> {
+    update_genesis(genesis_file, |mut genesis| {
+        let permission_mode = match args.mode.to_lowercase().as_str() {
+            "unrestricted" => PermissionMode::Unrestricted,
+            "allowlist" => {
+                let addresses = args.addresses.clone();
+                PermissionMode::AllowList { addresses }
+            }
+            _ => return Err(anyhow!("unknown ml training permission mode")),
+        };
+        genesis.ml_training_policy = MlTrainingPolicy {
+            permission_mode,
+            training_fee: args.fee.clone(),
+            job_challenge_window: args.job_challenge_window,
+            job_deposit: args.job_deposit.clone(),
+        };
+        Ok(genesis)
+    })
+}
+
+fn set_faucet(genesis_file: &PathBuf, args: &GenesisSetFaucetArgs) -> anyhow::Result<()> {
+    update_genesis(genesis_file, |mut genesis| {
+        genesis.faucet = Faucet {
+            balance: args.balance.clone(),
+            drip_amount: args.drip_amount.clone(),
+            cooldown: args.cooldown,
+        };
+        Ok(genesis)
+    })
+}
+
+fn set_ml_limits(genesis_file: &PathBuf, args: &GenesisSetMlLimitsArgs) -> anyhow::Result<()> {
+    update_genesis(genesis_file, |mut genesis| {
+        genesis.ml_limits = Some(MlLimits {
+            max_rows: args.max_rows,
+            max_cols: args.max_cols,
+            max_params_bytes: args.max_params_bytes,
+            max_block_gas: Some(args.max_block_gas),
+        });
+        Ok(genesis)
+    })
+}
+
+fn set_implicit_gas(
+    genesis_file: &PathBuf,
+    args: &GenesisSetImplicitGasArgs,
+) -> anyhow::Result<()> {
+    update_genesis(genesis_file, |mut genesis| {
+        genesis.implicit_gas = Some(ImplicitGasLimits {
+            cron: args.cron,
+            cron_reference_interval: args.cron_reference_interval,
+            cron_max_multiplier: args.cron_max_multiplier,
+            chain_metadata: args.chain_metadata,
+            machine_learning: args.machine_learning,
+            ml_jobs: args.ml_jobs,
+        });
+        Ok(genesis)
+    })
+}
+
 fn into_tendermint(genesis_file: &PathBuf, args: &GenesisIntoTendermintArgs) -> anyhow::Result<()> {
     let genesis = read_genesis(genesis_file)?;
     let genesis_json = serde_json::to_value(&genesis)?;
@@ -297,6 +418,11 @@ async fn new_genesis_from_parent(
                 auth_token: args.parent_auth_token.clone(),
                 registry_addr: args.parent_registry,
                 gateway_addr: args.parent_gateway,
+                admin: None,
+                response_cache: None,
+                max_concurrent_requests: None,
+                fallback_provider_http: vec![],
+                gas: None,
             }),
         },
     )?;
@@ -325,6 +451,11 @@ async fn new_genesis_from_parent(
         validators: Vec::new(),
         accounts: Vec::new(),
         eam_permission_mode: PermissionMode::Unrestricted,
+        ml_training_policy: Default::default(),
+        ml_limits: None,
+        implicit_gas: None,
+        faucet: Default::default(),
+        contracts: Vec::new(),
         ipc: Some(ipc_params),
     };
 
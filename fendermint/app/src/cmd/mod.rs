@@ -13,11 +13,14 @@ use async_trait::async_trait;
 pub mod config;
 pub mod debug;
 pub mod eth;
+pub mod gc;
 pub mod genesis;
 pub mod key;
 pub mod materializer;
+pub mod replay;
 pub mod rpc;
 pub mod run;
+pub mod snapshot;
 
 #[async_trait]
 pub trait Cmd {
@@ -69,6 +72,9 @@ pub async fn exec(opts: &Options) -> anyhow::Result<()> {
         Commands::Genesis(args) => args.exec(()).await,
         Commands::Rpc(args) => args.exec(()).await,
         Commands::Eth(args) => args.exec(settings(opts)?.eth).await,
+        Commands::Gc(args) => args.exec(settings(opts)?).await,
+        Commands::Replay(args) => args.exec(settings(opts)?).await,
+        Commands::Snapshot(args) => args.exec(settings(opts)?).await,
         Commands::Materializer(args) => args.exec(()).await,
     }
 }
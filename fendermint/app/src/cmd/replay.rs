@@ -0,0 +1,114 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Context};
+use fendermint_app::{AppState, AppStore, AppStoreKey, BlockHeight};
+use fendermint_app_options::replay::ReplayArgs;
+use fendermint_rocksdb::{blockstore::NamespaceBlockstore, RocksDb};
+use fendermint_storage::{KVCollection, KVRead, KVReadable};
+use fendermint_vm_interpreter::chain::{ChainEnv, CheckpointPool};
+use fendermint_vm_interpreter::fvm::state::FvmStateParams;
+use fendermint_vm_interpreter::fvm::store::overlay::OverlayBlockstore;
+use fendermint_vm_interpreter::replay::{CometBftBlockSource, Replayer, WithStateRoots};
+use fendermint_vm_topdown::voting::VoteTally;
+use fendermint_vm_topdown::Toggle;
+use fvm::engine::MultiEngine;
+use ipc_provider::supervisor::TaskSupervisor;
+
+use super::run::{make_interpreter, open_db, Namespaces};
+use crate::cmd;
+
+cmd! {
+  ReplayArgs(self, settings) {
+    let ns = Namespaces::default();
+    let db = open_db(&settings, &ns).context("error opening DB")?;
+    let state_store = NamespaceBlockstore::new(db.clone(), ns.state_store)
+        .context("error creating state DB")?;
+
+    let state_hist = KVCollection::<AppStore, BlockHeight, FvmStateParams>::new(ns.state_hist);
+
+    // The history is keyed by `block_height + 1`, so the state before block `h` is under `h`,
+    // and the state after it under `h + 1`.
+    let get_state_params = |height: BlockHeight| -> anyhow::Result<Option<FvmStateParams>> {
+        let tx = <RocksDb as KVReadable<AppStore>>::read(&db);
+        state_hist
+            .get(&tx, &height)
+            .context("failed to read the state history")
+    };
+
+    let to = match self.to {
+        Some(to) => to,
+        None => {
+            let tx = <RocksDb as KVReadable<AppStore>>::read(&db);
+            let state: Option<AppState> = tx
+                .get(&ns.app, &AppStoreKey::State)
+                .context("failed to read the app state")?;
+            state.ok_or_else(|| anyhow!("app state not found"))?.block_height()
+        }
+    };
+
+    if self.from == 0 || self.from > to {
+        bail!("invalid block range: {}..={to}", self.from);
+    }
+
+    let state_params = get_state_params(self.from)?.ok_or_else(|| {
+        anyhow!(
+            "the state before block {} is not in the state history; it might have been pruned",
+            self.from
+        )
+    })?;
+
+    let client = fendermint_rpc::client::http_client(self.url.clone(), self.proxy_url.clone())?;
+
+    // Without a validator context nothing is broadcast, and the supervised tasks are never started.
+    let interpreter = make_interpreter::<OverlayBlockstore<NamespaceBlockstore>, _>(
+        &settings,
+        client.clone(),
+        None,
+        TaskSupervisor::new(),
+    )?;
+
+    // Blocks with top-down messages can't be executed without the parent provider,
+    // in which case the replay stops with an error at the first such block.
+    let env = ChainEnv {
+        checkpoint_pool: CheckpointPool::new(),
+        parent_finality_provider: Arc::new(Toggle::disabled()),
+        parent_finality_votes: VoteTally::empty(),
+    };
+
+    let source = WithStateRoots::new(CometBftBlockSource::new(client), |height| {
+        let params = get_state_params(height as BlockHeight + 1)?;
+        Ok(params.map(|p| p.state_root))
+    });
+
+    let replayer = Replayer::new(interpreter, state_store, Arc::new(MultiEngine::new(1)));
+
+    let report = replayer
+        .replay_source(env, state_params, &source, (self.from as i64)..=(to as i64))
+        .await?;
+
+    match report.divergence {
+        Some(d) => {
+            bail!(
+                "block {} diverged: expected state root {}, got {}",
+                d.height,
+                d.expected,
+                d.actual
+            );
+        }
+        None => {
+            tracing::info!(
+                from = self.from,
+                to,
+                blocks = report.blocks,
+                state_root = report.state_params.state_root.to_string(),
+                "replayed blocks without divergence"
+            );
+        }
+    }
+
+    Ok(())
+  }
+}
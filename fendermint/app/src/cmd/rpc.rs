@@ -103,6 +103,30 @@ async fn query(
             let json = json!({ "response": res });
             print_json(&json)?;
         }
+        RpcQueryCommands::MlPredict {
+            model_id,
+            input_matrix,
+        } => {
+            let input_matrix =
+                serde_json::from_str(&input_matrix).context("failed to parse input matrix")?;
+            let res = client.ml_prediction(model_id, input_matrix, height).await?;
+            let json = json!({ "height": res.height, "predictions": res.value });
+            print_json(&json)?;
+        }
+        RpcQueryCommands::ExportModel { model_id, output } => {
+            let res = client.model_proof(model_id, height).await?;
+            let model = res.value.verify()?;
+            res.value.write_car(&output).await?;
+            let json = json!({
+                "height": res.height,
+                "state_root": res.value.state_root.to_string(),
+                "model_id": model_id,
+                "owner": model.owner.to_string(),
+                "model_size": model.model.len(),
+                "blocks": res.value.blocks.len(),
+            });
+            print_json(&json)?;
+        }
     };
     Ok(())
 }
@@ -5,9 +5,11 @@ use anyhow::{anyhow, bail, Context};
 use async_stm::atomically_or_err;
 use fendermint_abci::ApplicationService;
 use fendermint_app::events::{ParentFinalityVoteAdded, ParentFinalityVoteIgnored};
+use fendermint_app::gc::{BlockstoreGc, CommitLock, GcParams};
 use fendermint_app::ipc::{AppParentFinalityQuery, AppVote};
 use fendermint_app::{App, AppConfig, AppStore, BitswapBlockstore};
-use fendermint_app_settings::AccountKind;
+use fendermint_app_settings::fvm::DeliveryLogLevel;
+use fendermint_app_settings::{AccountKind, CheckpointSignatures};
 use fendermint_crypto::SecretKey;
 use fendermint_rocksdb::{blockstore::NamespaceBlockstore, namespaces, RocksDb, RocksDbConfig};
 use fendermint_tracing::emit;
@@ -17,7 +19,11 @@ use fendermint_vm_interpreter::fvm::upgrades::UpgradeScheduler;
 use fendermint_vm_interpreter::{
     bytes::{BytesMessageInterpreter, ProposalPrepareMode},
     chain::{ChainMessageInterpreter, CheckpointPool},
-    fvm::{Broadcaster, FvmMessageInterpreter, ValidatorContext},
+    fvm::{
+        prevalidate::{MinGasPrice, MlInputLimits},
+        Broadcaster, CheckpointSignature, DeliveryLogging, FvmMessageInterpreter,
+        SignatureCollector, SignatureGossip, ValidatorContext,
+    },
     signed::SignedMessageInterpreter,
 };
 use fendermint_vm_resolver::ipld::IpldResolver;
@@ -26,9 +32,12 @@ use fendermint_vm_topdown::proxy::IPCProviderProxy;
 use fendermint_vm_topdown::sync::launch_polling_syncer;
 use fendermint_vm_topdown::voting::{publish_vote_loop, Error as VoteError, VoteTally};
 use fendermint_vm_topdown::{CachedFinalityProvider, IPCParentFinality, Toggle};
+use fvm_ipld_blockstore::Blockstore;
 use fvm_shared::address::{current_network, Address, Network};
+use ipc_api::subnet_id::SubnetID;
 use ipc_ipld_resolver::{Event as ResolverEvent, VoteRecord};
 use ipc_provider::config::subnet::{EVMSubnet, SubnetConfig};
+use ipc_provider::supervisor::TaskSupervisor;
 use ipc_provider::IpcProvider;
 use libp2p::identity::secp256k1;
 use libp2p::identity::Keypair;
@@ -48,7 +57,7 @@ cmd! {
 
 // Database collection names.
 namespaces! {
-    Namespaces {
+    pub(crate) Namespaces {
         app,
         state_hist,
         state_store,
@@ -105,6 +114,23 @@ async fn run(settings: Settings) -> anyhow::Result<()> {
         libp2p::identity::Keypair::from(kp)
     });
 
+    // Background work started by the interpreter, drained when the node shuts down.
+    let supervisor = TaskSupervisor::new();
+
+    // Validators gossiping their checkpoint signatures hand them over to the resolver.
+    let signature_gossip = match settings.ipc.checkpoint_signatures {
+        CheckpointSignatures::Gossip if validator.is_some() => {
+            if !settings.resolver_enabled() {
+                bail!("gossiping checkpoint signatures needs the IPLD Resolver");
+            }
+            let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+            let gossip = SignatureGossip::new(tx, SignatureCollector::new())
+                .with_quorum_timeout(settings.ipc.checkpoint_quorum_timeout);
+            Some((gossip, rx))
+        }
+        _ => None,
+    };
+
     let validator_ctx = validator.map(|(sk, addr)| {
         // For now we are using the validator key for submitting transactions.
         // This allows us to identify transactions coming from empowered validators, to give priority to protocol related transactions.
@@ -117,37 +143,23 @@ async fn run(settings: Settings) -> anyhow::Result<()> {
             settings.fvm.gas_overestimation_rate,
         )
         .with_max_retries(settings.broadcast.max_retries)
-        .with_retry_delay(settings.broadcast.retry_delay);
+        .with_retry_delay(settings.broadcast.retry_delay)
+        .with_shutdown_signal(supervisor.shutdown_signal());
 
-        ValidatorContext::new(sk, broadcaster)
-    });
+        let ctx = ValidatorContext::new(sk, broadcaster);
 
-    let testing_settings = match settings.testing.as_ref() {
-        Some(_) if current_network() == Network::Mainnet => {
-            bail!("testing settings are not allowed on Mainnet");
+        match signature_gossip {
+            Some((ref gossip, _)) => ctx.with_signature_gossip(gossip.clone()),
+            None => ctx,
         }
-        other => other,
-    };
+    });
 
-    let interpreter = FvmMessageInterpreter::<NamespaceBlockstore, _>::new(
+    let interpreter = make_interpreter::<NamespaceBlockstore, _>(
+        &settings,
         tendermint_client.clone(),
         validator_ctx,
-        settings.contracts_dir(),
-        settings.fvm.gas_overestimation_rate,
-        settings.fvm.gas_search_step,
-        settings.fvm.exec_in_check,
-        UpgradeScheduler::new(),
-    )
-    .with_push_chain_meta(testing_settings.map_or(true, |t| t.push_chain_meta));
-
-    let interpreter = SignedMessageInterpreter::new(interpreter);
-    let interpreter = ChainMessageInterpreter::<_, NamespaceBlockstore>::new(interpreter);
-    let interpreter = BytesMessageInterpreter::new(
-        interpreter,
-        ProposalPrepareMode::PrependOnly,
-        false,
-        settings.abci.block_max_msgs,
-    );
+        supervisor.clone(),
+    )?;
 
     let ns = Namespaces::default();
     let db = open_db(&settings, &ns).context("error opening DB")?;
@@ -156,6 +168,27 @@ async fn run(settings: Settings) -> anyhow::Result<()> {
     let state_store =
         NamespaceBlockstore::new(db.clone(), ns.state_store).context("error creating state DB")?;
 
+    // Shared with the garbage collector, so it doesn't delete the blocks of a commit in progress.
+    let commit_lock = CommitLock::default();
+
+    if settings.db.gc.enabled {
+        let gc = BlockstoreGc::new(
+            db.clone(),
+            state_store.clone(),
+            ns.state_hist.clone(),
+            GcParams {
+                batch_size: settings.db.gc.batch_size,
+                batch_delay: settings.db.gc.batch_delay,
+                dry_run: false,
+            },
+        )
+        .with_commit_lock(commit_lock.clone());
+        let interval = settings.db.gc.interval;
+        tokio::spawn(async move { gc.run(interval).await });
+    } else {
+        info!("blockstore garbage collection disabled");
+    }
+
     let checkpoint_pool = CheckpointPool::new();
     let parent_finality_votes = VoteTally::empty();
 
@@ -188,6 +221,19 @@ async fn run(settings: Settings) -> anyhow::Result<()> {
             own_subnet_id.clone(),
         );
 
+        let signature_collector = match (signature_gossip, validator_keypair.clone()) {
+            (Some((gossip, rx)), Some(key)) => {
+                tracing::info!("starting the checkpoint signature gossip loop...");
+                let client = client.clone();
+                let subnet_id = own_subnet_id.clone();
+                tokio::spawn(async move {
+                    publish_checkpoint_signatures(rx, key, subnet_id, client).await
+                });
+                Some(gossip.collector().clone())
+            }
+            _ => None,
+        };
+
         if topdown_enabled {
             if let Some(key) = validator_keypair {
                 let parent_finality_votes = parent_finality_votes.clone();
@@ -216,7 +262,13 @@ async fn run(settings: Settings) -> anyhow::Result<()> {
         let rx = service.subscribe();
         let parent_finality_votes = parent_finality_votes.clone();
         tokio::spawn(async move {
-            dispatch_resolver_events(rx, parent_finality_votes, topdown_enabled).await;
+            dispatch_resolver_events(
+                rx,
+                parent_finality_votes,
+                topdown_enabled,
+                signature_collector,
+            )
+            .await;
         });
 
         tracing::info!("starting the IPLD Resolver Service...");
@@ -293,6 +345,8 @@ async fn run(settings: Settings) -> anyhow::Result<()> {
             builtin_actors_bundle: settings.builtin_actors_bundle(),
             custom_actors_bundle: settings.custom_actors_bundle(),
             halt_height: settings.halt_height,
+            subnet_id: settings.ipc.subnet_id.clone(),
+            commit_lock,
         },
         db,
         state_store,
@@ -366,17 +420,113 @@ async fn run(settings: Settings) -> anyhow::Result<()> {
         .finish()
         .context("error creating ABCI server")?;
 
-    // Run the ABCI server.
-    server
-        .listen(settings.abci.listen.to_string())
-        .await
-        .map_err(|e| anyhow!("error listening: {e}"))?;
+    // Run the ABCI server until the process is asked to stop.
+    tokio::select! {
+        res = server.listen(settings.abci.listen.to_string()) => {
+            res.map_err(|e| anyhow!("error listening: {e}"))?;
+        }
+        res = shutdown_requested() => {
+            res.context("failed to listen for shutdown signals")?;
+            tracing::info!("shutdown requested");
+        }
+    }
+
+    // Let the signature broadcasts in flight finish, so they don't have to be redone after a restart.
+    let report = supervisor
+        .shutdown(settings.broadcast.shutdown_timeout)
+        .await;
+    tracing::info!(
+        completed = report.completed,
+        failed = report.failed,
+        aborted = report.aborted,
+        "background tasks stopped"
+    );
 
     Ok(())
 }
 
+/// Wait for Ctrl-C, or SIGTERM where supported.
+async fn shutdown_requested() -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        let mut sigterm =
+            tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?;
+        tokio::select! {
+            res = tokio::signal::ctrl_c() => res,
+            _ = sigterm.recv() => Ok(()),
+        }
+    }
+    #[cfg(not(unix))]
+    tokio::signal::ctrl_c().await
+}
+
+/// The interpreter stack executing the blocks, configured the same way for running the node
+/// and for replaying blocks, so that the outcome is the same.
+pub(crate) fn make_interpreter<DB, C>(
+    settings: &Settings,
+    client: C,
+    validator_ctx: Option<ValidatorContext<C>>,
+    supervisor: TaskSupervisor,
+) -> anyhow::Result<
+    BytesMessageInterpreter<
+        ChainMessageInterpreter<SignedMessageInterpreter<FvmMessageInterpreter<DB, C>>, DB>,
+    >,
+>
+where
+    DB: Blockstore + Clone + 'static,
+{
+    let testing_settings = match settings.testing.as_ref() {
+        Some(_) if current_network() == Network::Mainnet => {
+            bail!("testing settings are not allowed on Mainnet");
+        }
+        other => other,
+    };
+
+    let interpreter = FvmMessageInterpreter::<DB, _>::new(
+        client,
+        validator_ctx,
+        settings.contracts_dir(),
+        settings.fvm.gas_overestimation_rate,
+        settings.fvm.gas_search_step,
+        settings.fvm.exec_in_check,
+        UpgradeScheduler::new(),
+    )
+    .with_supervisor(supervisor)
+    .with_subnet_id(settings.ipc.subnet_id.clone())
+    .with_push_chain_meta(testing_settings.map_or(true, |t| t.push_chain_meta))
+    .with_delivery_logging(DeliveryLogging {
+        tx_level: match settings.fvm.delivery_log.tx_level {
+            DeliveryLogLevel::Off => None,
+            DeliveryLogLevel::Trace => Some(tracing::Level::TRACE),
+            DeliveryLogLevel::Debug => Some(tracing::Level::DEBUG),
+            DeliveryLogLevel::Info => Some(tracing::Level::INFO),
+        },
+        block_summary: settings.fvm.delivery_log.block_summary,
+        json: settings.fvm.delivery_log.json,
+    })
+    .with_prevalidator(MinGasPrice {
+        min_gas_fee_cap: settings.fvm.prevalidation.min_gas_fee_cap.clone(),
+        min_gas_premium: settings.fvm.prevalidation.min_gas_premium.clone(),
+    })
+    .with_prevalidator(MlInputLimits {
+        max_rows: settings.fvm.prevalidation.ml_max_rows,
+        max_cols: settings.fvm.prevalidation.ml_max_cols,
+    });
+
+    let interpreter = SignedMessageInterpreter::new(interpreter);
+    let interpreter = ChainMessageInterpreter::<_, DB>::new(interpreter);
+    let interpreter = BytesMessageInterpreter::new(
+        interpreter,
+        ProposalPrepareMode::PrependOnly,
+        false,
+        settings.abci.block_max_msgs,
+    );
+
+    Ok(interpreter)
+}
+
 /// Open database with all
-fn open_db(settings: &Settings, ns: &Namespaces) -> anyhow::Result<RocksDb> {
+pub(crate) fn open_db(settings: &Settings, ns: &Namespaces) -> anyhow::Result<RocksDb> {
     let path = settings.data_dir().join("rocksdb");
     info!(
         path = path.to_string_lossy().into_owned(),
@@ -428,6 +578,11 @@ fn make_ipc_provider_proxy(settings: &Settings) -> anyhow::Result<IPCProviderPro
             auth_token: topdown_config.parent_http_auth_token.as_ref().cloned(),
             registry_addr: topdown_config.parent_registry,
             gateway_addr: topdown_config.parent_gateway,
+            admin: None,
+            response_cache: None,
+            max_concurrent_requests: None,
+            fallback_provider_http: vec![],
+            gas: None,
         }),
     };
     info!("init ipc provider with subnet: {}", subnet.id);
@@ -498,17 +653,50 @@ fn to_address(sk: &SecretKey, kind: &AccountKind) -> anyhow::Result<Address> {
     }
 }
 
+/// Publish the checkpoint signatures of the validator as votes.
+async fn publish_checkpoint_signatures(
+    mut rx: tokio::sync::mpsc::UnboundedReceiver<CheckpointSignature>,
+    key: Keypair,
+    subnet_id: SubnetID,
+    client: ipc_ipld_resolver::Client<AppVote>,
+) {
+    while let Some(signature) = rx.recv().await {
+        let height = signature.height;
+        let res = VoteRecord::signed(
+            &key,
+            subnet_id.clone(),
+            AppVote::CheckpointSignature(signature),
+        )
+        .and_then(|vote| client.publish_vote(vote));
+
+        if let Err(e) = res {
+            tracing::error!(
+                error = e.to_string(),
+                height,
+                "failed to publish checkpoint signature"
+            );
+        }
+    }
+}
+
 async fn dispatch_resolver_events(
     mut rx: tokio::sync::broadcast::Receiver<ResolverEvent<AppVote>>,
     parent_finality_votes: VoteTally,
     topdown_enabled: bool,
+    signature_collector: Option<SignatureCollector>,
 ) {
     loop {
         match rx.recv().await {
             Ok(event) => match event {
                 ResolverEvent::ReceivedPreemptive(_, _) => {}
                 ResolverEvent::ReceivedVote(vote) => {
-                    dispatch_vote(*vote, &parent_finality_votes, topdown_enabled).await;
+                    dispatch_vote(
+                        *vote,
+                        &parent_finality_votes,
+                        topdown_enabled,
+                        signature_collector.as_ref(),
+                    )
+                    .await;
                 }
             },
             Err(RecvError::Lagged(n)) => {
@@ -526,8 +714,25 @@ async fn dispatch_vote(
     vote: VoteRecord<AppVote>,
     parent_finality_votes: &VoteTally,
     topdown_enabled: bool,
+    signature_collector: Option<&SignatureCollector>,
 ) {
     match vote.content {
+        AppVote::CheckpointSignature(signature) => {
+            let Some(collector) = signature_collector else {
+                tracing::debug!("ignoring checkpoint signature; not gossiping signatures");
+                return;
+            };
+            let height = signature.height;
+            match collector.add_signature(signature) {
+                Ok(true) => tracing::info!(height, "checkpoint signatures reached a quorum"),
+                Ok(false) => {}
+                Err(e) => tracing::debug!(
+                    error = e.to_string(),
+                    height,
+                    "failed to handle checkpoint signature"
+                ),
+            }
+        }
         AppVote::ParentFinality(f) => {
             if !topdown_enabled {
                 tracing::debug!("ignoring vote; topdown disabled");
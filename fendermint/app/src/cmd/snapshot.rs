@@ -0,0 +1,151 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use anyhow::{anyhow, bail, Context};
+use fendermint_app::{AppState, AppStore, AppStoreKey, BlockHeight};
+use fendermint_app_options::snapshot::{
+    SnapshotArgs, SnapshotCommands, SnapshotExportArgs, SnapshotImportArgs,
+};
+use fendermint_app_settings::Settings;
+use fendermint_rocksdb::{blockstore::NamespaceBlockstore, RocksDb};
+use fendermint_storage::{KVCollection, KVRead, KVReadable, KVWritable, KVWrite};
+use fendermint_vm_interpreter::fvm::state::snapshot::Snapshot;
+use fendermint_vm_interpreter::fvm::state::FvmStateParams;
+use ipc_ipld_resolver::missing_blocks::missing_blocks;
+
+use super::run::{open_db, Namespaces};
+use crate::cmd;
+
+cmd! {
+  SnapshotArgs(self, settings) {
+    match &self.command {
+        SnapshotCommands::Export(args) => export(settings, args).await,
+        SnapshotCommands::Import(args) => import(settings, args).await,
+    }
+  }
+}
+
+/// Export the state tree committed at a height into a CAR file.
+async fn export(settings: Settings, args: &SnapshotExportArgs) -> anyhow::Result<()> {
+    let ns = Namespaces::default();
+    let db = open_db(&settings, &ns).context("error opening DB")?;
+    let state_store = NamespaceBlockstore::new(db.clone(), ns.state_store.clone())
+        .context("error creating state DB")?;
+
+    let committed = committed_state(&db, &ns)?
+        .ok_or_else(|| anyhow!("app state not found; has the node been initialized?"))?;
+
+    let block_height = args.height.unwrap_or(committed.block_height());
+
+    if block_height > committed.block_height() {
+        bail!(
+            "height {block_height} has not been committed yet; the last committed height is {}",
+            committed.block_height()
+        );
+    }
+
+    // The history is keyed by the height where the state *appears*, which is one higher
+    // than the height of the block which committed it; see `AppState::state_height`.
+    let state_hist = KVCollection::<AppStore, BlockHeight, FvmStateParams>::new(ns.state_hist);
+    let tx = <RocksDb as KVReadable<AppStore>>::read(&db);
+    let state_params = state_hist
+        .get(&tx, &(block_height + 1))
+        .context("error looking up history")?
+        .ok_or_else(|| anyhow!("the state at height {block_height} has already been pruned"))?;
+
+    let state_root = state_params.state_root;
+
+    let snapshot = Snapshot::new(state_store, state_params, block_height)
+        .context("failed to create snapshot")?;
+
+    snapshot
+        .write_car(&args.out_file)
+        .await
+        .context("failed to write CAR file")?;
+
+    tracing::info!(
+        block_height,
+        state_root = state_root.to_string(),
+        path = args.out_file.to_string_lossy().to_string(),
+        "exported snapshot"
+    );
+
+    Ok(())
+}
+
+/// Import a CAR file into the state store and make it the last committed state.
+///
+/// CometBFT has to be brought to the same height separately, as its block store is not part of the snapshot.
+async fn import(settings: Settings, args: &SnapshotImportArgs) -> anyhow::Result<()> {
+    let ns = Namespaces::default();
+    let db = open_db(&settings, &ns).context("error opening DB")?;
+    let state_store = NamespaceBlockstore::new(db.clone(), ns.state_store.clone())
+        .context("error creating state DB")?;
+
+    if let Some(committed) = committed_state(&db, &ns)? {
+        if committed.block_height() > 0 && !args.force {
+            bail!(
+                "the node has already committed blocks up to height {}; use --force to overwrite",
+                committed.block_height()
+            );
+        }
+    }
+
+    // Validation checks that every block in the CAR file matches its CID.
+    let snapshot = Snapshot::read_car(&args.in_file, state_store.clone(), true)
+        .await
+        .context("failed to import the snapshot into the blockstore")?;
+
+    let (state_params, block_height) = match snapshot {
+        Snapshot::V1(snapshot) => (snapshot.state_params().clone(), snapshot.block_height()),
+    };
+
+    if let Some(expected) = args.state_root {
+        if state_params.state_root != expected {
+            bail!(
+                "unexpected state root; expected {expected}, imported {}",
+                state_params.state_root
+            );
+        }
+    }
+
+    // Make sure the whole state tree is reachable from the root, not just the blocks which were in the file.
+    let mut store = state_store.clone();
+    let missing = missing_blocks::<_, libipld::DefaultParams>(&mut store, &state_params.state_root)
+        .context("failed to traverse the state tree")?;
+
+    if !missing.is_empty() {
+        bail!(
+            "the snapshot is incomplete: {} blocks are missing under state root {}, e.g. {}",
+            missing.len(),
+            state_params.state_root,
+            missing[0]
+        );
+    }
+
+    let state_root = state_params.state_root;
+    let state = AppState::new(block_height, state_params);
+    let state_hist = KVCollection::<AppStore, BlockHeight, FvmStateParams>::new(ns.state_hist);
+
+    <RocksDb as KVWritable<AppStore>>::with_write(&db, |tx| {
+        state_hist.put(tx, &state.state_height(), state.state_params())?;
+        KVWrite::<AppStore>::put(tx, &ns.app, &AppStoreKey::State, &state)?;
+        Ok(())
+    })
+    .context("failed to write the imported state")?;
+
+    tracing::info!(
+        block_height,
+        state_root = state_root.to_string(),
+        path = args.in_file.to_string_lossy().to_string(),
+        "imported snapshot"
+    );
+
+    Ok(())
+}
+
+/// Read the last committed application state, if any.
+fn committed_state(db: &RocksDb, ns: &Namespaces) -> anyhow::Result<Option<AppState>> {
+    let tx = <RocksDb as KVReadable<AppStore>>::read(db);
+    KVRead::<AppStore>::get(&tx, &ns.app, &AppStoreKey::State).context("get failed")
+}
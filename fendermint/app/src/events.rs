@@ -5,7 +5,8 @@ use crate::BlockHeight;
 
 /// Re-export other events, just to provide the visibility of where they are.
 pub use fendermint_vm_event::{
-    NewBottomUpCheckpoint, NewParentView, ParentFinalityCommitted, ParentFinalityMissingQuorum,
+    BlockExecuted, BlockGasUsed, CronExecuted, ImplicitMessageFailed, NewBottomUpCheckpoint,
+    NewParentView, ParentFinalityCommitted, ParentFinalityMissingQuorum,
 };
 
 /// Hex encoded block hash.
@@ -0,0 +1,103 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+//! Packaging of the misbehaviour evidence committed by CometBFT into the format the subnet
+//! actor on the parent checks it in, so that it can be reported there.
+
+use anyhow::{anyhow, bail, Context};
+use fendermint_vm_actor_interface::eam::EthAddress;
+use fendermint_vm_genesis::ValidatorKey;
+use ipc_api::evidence::DoubleSignEvidence;
+use tendermint::block::Height;
+use tendermint::evidence::{DuplicateVoteEvidence, Evidence};
+use tendermint::vote::Vote;
+use tendermint_rpc::{Client, Paging};
+
+/// Fetch the duplicate vote evidence committed in a block, and package it for the subnet actor.
+///
+/// The evidence is about the votes of an earlier height, whose validator set is looked up to
+/// find the public key, hence the Ethereum address, of the offending validator.
+pub async fn fetch_double_sign_evidence<C>(
+    client: &C,
+    height: Height,
+) -> anyhow::Result<Vec<DoubleSignEvidence>>
+where
+    C: Client + Sync,
+{
+    let block = client
+        .block(height)
+        .await
+        .context("failed to fetch block")?
+        .block;
+
+    let chain_id = block.header().chain_id.clone();
+    let mut packaged = Vec::new();
+
+    for evidence in block.evidence().iter() {
+        let Evidence::DuplicateVote(evidence) = evidence else {
+            tracing::debug!(
+                height = height.value(),
+                "skipping light client attack evidence"
+            );
+            continue;
+        };
+
+        let vote_height = evidence.vote_a.height;
+        let validators = client
+            .validators(vote_height, Paging::All)
+            .await
+            .context("failed to fetch validators")?
+            .validators;
+
+        let validator = validators
+            .into_iter()
+            .find(|v| v.address == evidence.vote_a.validator_address)
+            .ok_or_else(|| {
+                anyhow!(
+                    "validator {} not found at height {}",
+                    evidence.vote_a.validator_address,
+                    vote_height
+                )
+            })?;
+
+        let validator =
+            ValidatorKey::try_from(validator.pub_key).context("unexpected validator public key")?;
+
+        packaged.push(to_double_sign_evidence(evidence, &chain_id, &validator)?);
+    }
+
+    Ok(packaged)
+}
+
+/// Package two conflicting votes of a validator with the bytes CometBFT signed.
+pub fn to_double_sign_evidence(
+    evidence: &DuplicateVoteEvidence,
+    chain_id: &tendermint::chain::Id,
+    validator: &ValidatorKey,
+) -> anyhow::Result<DoubleSignEvidence> {
+    let (vote_a, signature_a) = to_signed_vote(&evidence.vote_a, chain_id)?;
+    let (vote_b, signature_b) = to_signed_vote(&evidence.vote_b, chain_id)?;
+
+    Ok(DoubleSignEvidence {
+        validator: ethers::types::Address::from(EthAddress::from(validator.0).0),
+        height: evidence.vote_a.height.value(),
+        vote_a,
+        signature_a,
+        vote_b,
+        signature_b,
+    })
+}
+
+fn to_signed_vote(
+    vote: &Vote,
+    chain_id: &tendermint::chain::Id,
+) -> anyhow::Result<(Vec<u8>, Vec<u8>)> {
+    let Some(ref signature) = vote.signature else {
+        bail!("vote at height {} is not signed", vote.height);
+    };
+
+    let sign_bytes = vote
+        .to_signable_vec(chain_id.clone())
+        .map_err(|e| anyhow!("failed to encode vote: {e}"))?;
+
+    Ok((sign_bytes, signature.as_bytes().to_vec()))
+}
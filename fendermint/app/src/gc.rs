@@ -0,0 +1,350 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Garbage collection of the state store.
+//!
+//! The FVM never deletes anything from the blockstore, so every intermediate version of the
+//! state tree stays around forever, even after the corresponding entry has been pruned from
+//! the state history. The collector marks every block reachable from the state roots still
+//! in the history, then sweeps the rest.
+//!
+//! The sweep only considers blocks which were already in the store when the collection started.
+//! To run in the background of a live node, the collector shares a [CommitLock] with the app:
+//! the app holds it from flushing the state of a block until its root is in the history, and
+//! the collector holds it while it re-marks from the current roots and deletes a batch. A block
+//! written again by a commit, or made reachable by one, is thus either marked before it could
+//! be deleted, or written after it was. Without a shared lock, e.g. in the `gc` command, the
+//! collection must only run while the node is stopped, which RocksDB enforces by locking the
+//! database to a single process.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context;
+use cid::Cid;
+use fendermint_rocksdb::{blockstore::NamespaceBlockstore, RocksDb};
+use fendermint_storage::{KVCollection, KVReadable};
+use fendermint_vm_interpreter::fvm::state::FvmStateParams;
+use fvm_ipld_blockstore::Blockstore;
+use libipld::prelude::References;
+use libipld::DefaultParams;
+
+use crate::{AppStore, BlockHeight};
+
+/// Lock serializing block commits with the deletions of the collector.
+pub type CommitLock = Arc<tokio::sync::Mutex<()>>;
+
+/// Parameters to limit the impact of the collection on a running node.
+#[derive(Debug, Clone)]
+pub struct GcParams {
+    /// Maximum number of blocks to delete in one batch.
+    pub batch_size: usize,
+    /// Pause between batches.
+    pub batch_delay: Duration,
+    /// Only count the garbage, don't delete it.
+    pub dry_run: bool,
+}
+
+/// Outcome of a round of garbage collection.
+#[derive(Debug, Clone, Default)]
+pub struct GcStats {
+    /// Number of state roots in the history the collection started from.
+    pub roots: usize,
+    /// Number of blocks found to be reachable.
+    pub reachable: usize,
+    /// Number of blocks deleted, or would have been deleted in a dry run.
+    pub deleted: usize,
+}
+
+/// Mark-and-sweep collector over the state store.
+pub struct BlockstoreGc {
+    db: RocksDb,
+    state_store: NamespaceBlockstore,
+    state_hist: KVCollection<AppStore, BlockHeight, FvmStateParams>,
+    params: GcParams,
+    commit_lock: CommitLock,
+}
+
+impl BlockstoreGc {
+    pub fn new(
+        db: RocksDb,
+        state_store: NamespaceBlockstore,
+        state_hist_namespace: String,
+        params: GcParams,
+    ) -> Self {
+        Self {
+            db,
+            state_store,
+            state_hist: KVCollection::new(state_hist_namespace),
+            params,
+            commit_lock: CommitLock::default(),
+        }
+    }
+
+    /// Share the lock the app holds while committing, so the collection can run on a live node.
+    pub fn with_commit_lock(mut self, commit_lock: CommitLock) -> Self {
+        self.commit_lock = commit_lock;
+        self
+    }
+
+    /// Run a full round of collection.
+    ///
+    /// This is a blocking operation; on a running node call it with [tokio::task::spawn_blocking].
+    pub fn collect(&self) -> anyhow::Result<GcStats> {
+        // Open the iterator first, so that blocks written during the marking are not considered.
+        let keys = self.state_store.keys().context("failed to list blocks")?;
+
+        let roots = self.state_roots()?;
+        let mut live = HashSet::new();
+        self.mark(&roots, &mut live)?;
+
+        let mut stats = GcStats {
+            roots: roots.len(),
+            ..Default::default()
+        };
+
+        let mut batch = Vec::new();
+        for key in keys {
+            let key = key?;
+            if !live.contains(&key) {
+                batch.push(key);
+            }
+            if batch.len() >= self.params.batch_size {
+                stats.deleted += self.sweep(&mut live, &mut batch)?;
+                std::thread::sleep(self.params.batch_delay);
+            }
+        }
+        stats.deleted += self.sweep(&mut live, &mut batch)?;
+        stats.reachable = live.len();
+
+        Ok(stats)
+    }
+
+    /// Run collections at regular intervals in the background.
+    pub async fn run(self, interval: Duration) {
+        let gc = std::sync::Arc::new(self);
+        loop {
+            tokio::time::sleep(interval).await;
+
+            let gc = gc.clone();
+            match tokio::task::spawn_blocking(move || gc.collect()).await {
+                Ok(Ok(stats)) => {
+                    tracing::info!(
+                        roots = stats.roots,
+                        reachable = stats.reachable,
+                        deleted = stats.deleted,
+                        "blockstore garbage collected"
+                    );
+                }
+                Ok(Err(e)) => {
+                    tracing::error!(error =? e, "failed to collect blockstore garbage");
+                }
+                Err(e) => {
+                    tracing::error!(error =? e, "blockstore garbage collection panicked");
+                }
+            }
+        }
+    }
+
+    /// Delete a batch of unreachable blocks, after checking that none of them have become
+    /// reachable from a state root committed since the marking started.
+    ///
+    /// No block can be committed until the batch is deleted.
+    fn sweep(&self, live: &mut HashSet<Cid>, batch: &mut Vec<Cid>) -> anyhow::Result<usize> {
+        if batch.is_empty() {
+            return Ok(0);
+        }
+        let _guard = self.commit_lock.blocking_lock();
+
+        let roots = self.state_roots()?;
+        self.mark(&roots, live)?;
+        batch.retain(|cid| !live.contains(cid));
+
+        let deleted = batch.len();
+        if !self.params.dry_run {
+            self.state_store
+                .delete_many(batch.iter())
+                .context("failed to delete blocks")?;
+        }
+        batch.clear();
+
+        Ok(deleted)
+    }
+
+    /// Collect the state roots from the history.
+    fn state_roots(&self) -> anyhow::Result<Vec<Cid>> {
+        let tx = <RocksDb as KVReadable<AppStore>>::read(&self.db);
+        let mut roots = Vec::new();
+        for entry in self.state_hist.iterate(&tx) {
+            let (_, params) = entry.context("failed to read state history")?;
+            roots.push(params.state_root);
+        }
+        Ok(roots)
+    }
+
+    /// Add every block reachable from the roots to the live set.
+    ///
+    /// Blocks already in the set are not traversed again, so re-marking is cheap
+    /// when only a few new roots have been added since the last time.
+    fn mark(&self, roots: &[Cid], live: &mut HashSet<Cid>) -> anyhow::Result<()> {
+        let mut stack = roots.to_vec();
+        while let Some(cid) = stack.pop() {
+            if !live.insert(cid) {
+                continue;
+            }
+            if let Some(data) = self.state_store.get(&cid)? {
+                let block = libipld::Block::<DefaultParams>::new_unchecked(cid, data);
+                block
+                    .references(&mut stack)
+                    .with_context(|| format!("failed to decode block {cid}"))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use cid::Cid;
+    use fendermint_rocksdb::{blockstore::NamespaceBlockstore, RocksDb, RocksDbConfig};
+    use fendermint_storage::{KVCollection, KVWritable};
+    use fendermint_vm_core::Timestamp;
+    use fendermint_vm_interpreter::fvm::state::FvmStateParams;
+    use fvm_ipld_blockstore::Blockstore;
+    use fvm_shared::{econ::TokenAmount, version::NetworkVersion};
+
+    use super::{BlockstoreGc, CommitLock, GcParams};
+    use crate::{AppStore, BlockHeight};
+
+    const STATE_STORE: &str = "state_store";
+    const STATE_HIST: &str = "state_hist";
+
+    struct Fixture {
+        _dir: tempfile::TempDir,
+        db: RocksDb,
+        store: NamespaceBlockstore,
+    }
+
+    fn fixture() -> Fixture {
+        let dir = tempfile::Builder::new()
+            .tempdir()
+            .expect("error creating temporary path for db");
+        let db = RocksDb::open_cf(
+            dir.path().join("rocksdb"),
+            &RocksDbConfig::default(),
+            [STATE_STORE, STATE_HIST].iter(),
+        )
+        .expect("error creating RocksDB");
+        let store = NamespaceBlockstore::new(db.clone(), STATE_STORE.to_owned()).unwrap();
+        Fixture {
+            _dir: dir,
+            db,
+            store,
+        }
+    }
+
+    impl Fixture {
+        fn put(&self, links: Vec<Cid>, data: u64) -> Cid {
+            let block = (links, data);
+            let cid = fendermint_vm_message::cid(&block).unwrap();
+            let bytes = fvm_ipld_encoding::to_vec(&block).unwrap();
+            self.store.put_keyed(&cid, &bytes).unwrap();
+            cid
+        }
+
+        /// Record a state root in the history, the way the app does when it commits.
+        fn commit(&self, height: BlockHeight, state_root: Cid) {
+            let state_hist =
+                KVCollection::<AppStore, BlockHeight, FvmStateParams>::new(STATE_HIST.to_owned());
+            let params = FvmStateParams {
+                state_root,
+                timestamp: Timestamp(0),
+                network_version: NetworkVersion::V21,
+                base_fee: TokenAmount::from_atto(0),
+                circ_supply: TokenAmount::from_atto(0),
+                chain_id: 0,
+                power_scale: 0,
+                app_version: 0,
+                ml_limits: None,
+                implicit_gas: None,
+            };
+            <RocksDb as KVWritable<AppStore>>::with_write(&self.db, |tx| {
+                state_hist.put(tx, &height, &params)
+            })
+            .unwrap();
+        }
+
+        fn gc(&self, dry_run: bool) -> BlockstoreGc {
+            BlockstoreGc::new(
+                self.db.clone(),
+                self.store.clone(),
+                STATE_HIST.to_owned(),
+                GcParams {
+                    batch_size: 1,
+                    batch_delay: Duration::ZERO,
+                    dry_run,
+                },
+            )
+        }
+
+        fn has(&self, cid: &Cid) -> bool {
+            self.store.has(cid).unwrap()
+        }
+    }
+
+    #[test]
+    fn collects_unreachable_blocks() {
+        let f = fixture();
+        let leaf = f.put(Vec::new(), 1);
+        let root = f.put(vec![leaf], 2);
+        let orphan_leaf = f.put(Vec::new(), 3);
+        let orphan = f.put(vec![orphan_leaf], 4);
+        f.commit(1, root);
+
+        let stats = f.gc(true).collect().unwrap();
+        assert_eq!(stats.roots, 1);
+        assert_eq!(stats.reachable, 2);
+        assert_eq!(stats.deleted, 2);
+        assert!(f.has(&orphan));
+
+        let stats = f.gc(false).collect().unwrap();
+        assert_eq!(stats.deleted, 2);
+        assert!(f.has(&root));
+        assert!(f.has(&leaf));
+        assert!(!f.has(&orphan));
+        assert!(!f.has(&orphan_leaf));
+
+        // Nothing left to collect.
+        assert_eq!(f.gc(false).collect().unwrap().deleted, 0);
+    }
+
+    #[test]
+    fn sweep_waits_for_commits() {
+        let f = fixture();
+        let root = f.put(Vec::new(), 1);
+        f.commit(1, root);
+        let orphan = f.put(Vec::new(), 2);
+
+        let commit_lock = CommitLock::default();
+        let gc = f.gc(false).with_commit_lock(commit_lock.clone());
+
+        // While a block is being committed, its blocks are not yet reachable from the history.
+        let guard = commit_lock.blocking_lock();
+        let handle = std::thread::spawn(move || gc.collect());
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(!handle.is_finished());
+        assert!(f.has(&orphan));
+
+        // The commit makes the block reachable before the collector can delete it.
+        let new_root = f.put(vec![orphan], 3);
+        f.commit(2, new_root);
+        drop(guard);
+
+        let stats = handle.join().unwrap().unwrap();
+        assert_eq!(stats.deleted, 0);
+        assert!(f.has(&orphan));
+    }
+}
@@ -9,6 +9,7 @@ use fendermint_vm_genesis::{Power, Validator};
 use fendermint_vm_interpreter::fvm::state::ipc::GatewayCaller;
 use fendermint_vm_interpreter::fvm::state::{FvmExecState, FvmStateParams};
 use fendermint_vm_interpreter::fvm::store::ReadOnlyBlockstore;
+use fendermint_vm_interpreter::fvm::CheckpointSignature;
 use fendermint_vm_topdown::sync::ParentFinalityStateQuery;
 use fendermint_vm_topdown::IPCParentFinality;
 use fvm_ipld_blockstore::Blockstore;
@@ -21,6 +22,8 @@ use serde::{Deserialize, Serialize};
 pub enum AppVote {
     /// The validator considers a certain block final on the parent chain.
     ParentFinality(IPCParentFinality),
+    /// The validator signed a bottom-up checkpoint.
+    CheckpointSignature(CheckpointSignature),
 }
 
 /// Queries the LATEST COMMITTED parent finality from the storage
@@ -2,12 +2,14 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 mod app;
 pub mod events;
+pub mod evidence;
+pub mod gc;
 pub mod ipc;
 pub mod metrics;
 mod store;
 mod tmconv;
 
-pub use app::{App, AppConfig};
+pub use app::{App, AppConfig, AppState, AppStoreKey};
 pub use store::{AppStore, BitswapBlockstore};
 
 // Different type from `ChainEpoch` just because we might use epoch in a more traditional sense for checkpointing.
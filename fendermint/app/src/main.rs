@@ -3,6 +3,7 @@
 
 pub use fendermint_app_options as options;
 pub use fendermint_app_settings as settings;
+use ipc_provider::redact::{RedactingMakeWriter, Redactor};
 use tracing_appender::{
     non_blocking::WorkerGuard,
     rolling::{RollingFileAppender, Rotation},
@@ -15,10 +16,14 @@ mod cmd;
 fn init_tracing(opts: &options::Options) -> Option<WorkerGuard> {
     let console_filter = opts.log_console_filter().expect("invalid filter");
     let file_filter = opts.log_file_filter().expect("invalid filter");
+    let redactor = Redactor::with_patterns(&opts.log_redact).expect("invalid redaction pattern");
 
     // log all traces to stderr (reserving stdout for any actual output such as from the CLI commands)
     let console_layer = fmt::layer()
-        .with_writer(std::io::stderr)
+        .with_writer(RedactingMakeWriter::with_redactor(
+            std::io::stderr,
+            redactor.clone(),
+        ))
         .with_target(false)
         .with_file(true)
         .with_line_number(true)
@@ -44,7 +49,7 @@ fn init_tracing(opts: &options::Options) -> Option<WorkerGuard> {
 
             let file_layer = fmt::layer()
                 .json()
-                .with_writer(non_blocking)
+                .with_writer(RedactingMakeWriter::with_redactor(non_blocking, redactor))
                 .with_span_events(FmtSpan::CLOSE)
                 .with_target(false)
                 .with_file(true)
@@ -62,10 +67,18 @@ fn init_tracing(opts: &options::Options) -> Option<WorkerGuard> {
         None
     };
 
+    // export spans to an OpenTelemetry collector if an endpoint is set
+    let otlp_layer = opts.otlp_endpoint.as_ref().map(|endpoint| {
+        fendermint_tracing::otlp::layer(endpoint, "fendermint")
+            .expect("failed to initialize OTLP exporter")
+            .with_filter(opts.otlp_filter().expect("invalid filter"))
+    });
+
     let registry = tracing_subscriber::registry()
         .with(console_layer)
         .with(file_layer)
-        .with(metrics_layer);
+        .with(metrics_layer)
+        .with(otlp_layer);
 
     tracing::subscriber::set_global_default(registry).expect("Unable to set a global collector");
 
@@ -103,7 +116,13 @@ async fn main() {
 
     init_panic_handler();
 
-    if let Err(e) = cmd::exec(&opts).await {
+    let res = cmd::exec(&opts).await;
+
+    if opts.otlp_endpoint.is_some() {
+        fendermint_tracing::otlp::shutdown();
+    }
+
+    if let Err(e) = res {
         tracing::error!("failed to execute {:?}: {e:?}", opts);
         std::process::exit(fendermint_app::AppExitCode::UnknownError as i32);
     }
@@ -40,6 +40,22 @@ pub mod app {
         BOTTOMUP_CKPT_CONFIG_NUM: IntGauge = "Highest configuration number checkpointed";
         BOTTOMUP_CKPT_NUM_MSGS: IntCounter = "Number of bottom-up messages observed since start";
 
+        CRON_GAS_USED: IntCounter = "Gas used by the cron message since start";
+        CRON_GAS_USED_LAST: IntGauge = "Gas used by the cron message in the last block";
+        CRON_GAS_LIMIT_LAST: IntGauge = "Gas limit of the cron message in the last block";
+
+        BLOCK_TXS: IntCounter = "Number of transactions delivered since start";
+        BLOCK_TXS_LAST: IntGauge = "Number of transactions delivered in the last block";
+        BLOCK_FAILED_TXS: IntCounter = "Number of delivered transactions which failed since start";
+        BLOCK_EVENTS: IntCounter = "Number of events emitted by the delivered transactions since start";
+
+        BLOCK_GAS_USED_LAST: IntGauge = "Gas used by cron and the messages of the last block";
+        BLOCK_GAS_USED_GATEWAY: IntCounter = "Gas used by messages sent to the gateway since start";
+        BLOCK_GAS_USED_ML: IntCounter = "Gas used by messages sent to the machine learning actor since start";
+        BLOCK_GAS_USED_OTHER: IntCounter = "Gas used by messages sent to actors other than cron, the gateway and the machine learning actor since start";
+
+        IMPLICIT_MSG_FAILED: IntCounter = "Number of non-critical implicit messages which failed and were skipped since start";
+
         // This metrics is available in CometBFT as well, but it's something that should increase even without subnets,
         // which can be a useful way to check if metrics work at all.
         ABCI_COMMITTED_BLOCK_HEIGHT: IntGauge = "Highest committed block";
@@ -147,6 +147,26 @@ impl<S: Subscriber> Layer<S> for MetricsLayer<S> {
                 next_configuration_number => set_gauge   ! &am::BOTTOMUP_CKPT_CONFIG_NUM,
                 num_msgs                  => inc_counter ! &am::BOTTOMUP_CKPT_NUM_MSGS,
             },
+            CronExecuted {
+                gas_used                  => inc_counter ! &am::CRON_GAS_USED,
+                gas_used                  => set_gauge   ! &am::CRON_GAS_USED_LAST,
+                gas_limit                 => set_gauge   ! &am::CRON_GAS_LIMIT_LAST,
+            },
+            BlockExecuted {
+                txs                       => inc_counter ! &am::BLOCK_TXS,
+                txs                       => set_gauge   ! &am::BLOCK_TXS_LAST,
+                failed_txs                => inc_counter ! &am::BLOCK_FAILED_TXS,
+                events                    => inc_counter ! &am::BLOCK_EVENTS,
+            },
+            BlockGasUsed {
+                total                     => set_gauge   ! &am::BLOCK_GAS_USED_LAST,
+                gateway                   => inc_counter ! &am::BLOCK_GAS_USED_GATEWAY,
+                machinelearning           => inc_counter ! &am::BLOCK_GAS_USED_ML,
+                other                     => inc_counter ! &am::BLOCK_GAS_USED_OTHER,
+            },
+            ImplicitMessageFailed {
+                block_height              => inc1_counter ! &am::IMPLICIT_MSG_FAILED,
+            },
             NewBlock {
                 block_height              => set_gauge   ! &am::ABCI_COMMITTED_BLOCK_HEIGHT
             }
@@ -6,11 +6,12 @@ use fendermint_vm_core::Timestamp;
 use fendermint_vm_genesis::{Power, Validator};
 use fendermint_vm_interpreter::fvm::{
     state::{BlockHash, FvmStateParams},
-    FvmApplyRet, FvmCheckRet, FvmQueryRet, PowerUpdates,
+    BlockSummary, FvmApplyRet, FvmCheckRet, FvmEndRet, FvmQueryRet, ValidatorChange,
 };
 use fendermint_vm_message::signed::DomainHash;
 use fendermint_vm_snapshot::{SnapshotItem, SnapshotManifest};
 use fvm_shared::{address::Address, error::ExitCode, event::StampedEvent, ActorID};
+use ipc_api::cross::TopDownReceipt;
 use prost::Message;
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, num::NonZeroU32};
@@ -149,17 +150,87 @@ pub fn to_check_tx(ret: FvmCheckRet) -> response::CheckTx {
 }
 
 /// Map the return values from epoch boundary operations to validator updates.
-pub fn to_end_block(power_table: PowerUpdates) -> anyhow::Result<response::EndBlock> {
+pub fn to_end_block(ret: FvmEndRet) -> anyhow::Result<response::EndBlock> {
     let validator_updates =
-        to_validator_updates(power_table.0).context("failed to convert validator updates")?;
+        to_validator_updates(ret.power_updates.0).context("failed to convert validator updates")?;
+
+    let mut events: Vec<Event> = match ret.validator_set_change {
+        Some(change) => change
+            .changes
+            .iter()
+            .map(|c| to_validator_change_event(c, change.configuration_number))
+            .collect(),
+        None => Vec::new(),
+    };
+    events.push(to_block_summary_event(&ret.summary));
 
     Ok(response::EndBlock {
         validator_updates,
         consensus_param_updates: None,
-        events: Vec::new(), // TODO: Events from epoch transitions?
+        events,
     })
 }
 
+/// Event about a validator joining or leaving the validator set, or changing its power,
+/// so that monitoring can subscribe to them over the CometBFT API.
+pub fn to_validator_change_event(change: &ValidatorChange, configuration_number: u64) -> Event {
+    Event::new(
+        "validator_changed".to_string(),
+        vec![
+            EventAttribute {
+                key: "address".to_string(),
+                value: format!("0x{}", hex::encode(change.address().0)),
+                index: true,
+            },
+            EventAttribute {
+                key: "kind".to_string(),
+                value: change.kind.to_string(),
+                index: true,
+            },
+            EventAttribute {
+                key: "public_key".to_string(),
+                value: hex::encode(change.public_key.0.serialize()),
+                index: false,
+            },
+            EventAttribute {
+                key: "previous_power".to_string(),
+                value: change.previous_power.0.to_string(),
+                index: false,
+            },
+            EventAttribute {
+                key: "power".to_string(),
+                value: change.power.0.to_string(),
+                index: false,
+            },
+            EventAttribute {
+                key: "configuration_number".to_string(),
+                value: configuration_number.to_string(),
+                index: false,
+            },
+        ],
+    )
+}
+
+/// Event summarizing what happened in a block, so that its health can be monitored over the
+/// CometBFT API without going through its transactions.
+pub fn to_block_summary_event(summary: &BlockSummary) -> Event {
+    let attr = |key: &str, value: String| EventAttribute {
+        key: key.to_string(),
+        value,
+        index: false,
+    };
+    Event::new(
+        "block_summary".to_string(),
+        vec![
+            attr("txs", summary.txs.to_string()),
+            attr("failed_txs", summary.failed_txs.to_string()),
+            attr("gas_used", summary.gas_used.to_string()),
+            attr("events", summary.events.to_string()),
+            attr("checkpoint_created", summary.checkpoint_created.to_string()),
+        ],
+    )
+}
+
 /// Map the return values from cron operations.
 pub fn to_begin_block(ret: FvmApplyRet) -> response::BeginBlock {
     let events = to_events("event", ret.apply_ret.events, ret.emitters);
@@ -252,6 +323,33 @@ pub fn to_message_event(from: Address, to: Address) -> Event {
     )
 }
 
+/// Event about the execution of a top-down message, indexed by its nonce so that the Ethereum
+/// API can look up its receipt.
+pub fn to_topdown_receipt_event(receipt: &TopDownReceipt) -> anyhow::Result<Event> {
+    let receipt_json =
+        serde_json::to_string(receipt).context("failed to serialize topdown receipt")?;
+    Ok(Event::new(
+        "topdown_receipt".to_string(),
+        vec![
+            EventAttribute {
+                key: "nonce".to_string(),
+                value: receipt.nonce.to_string(),
+                index: true,
+            },
+            EventAttribute {
+                key: "outcome".to_string(),
+                value: receipt.outcome.to_string(),
+                index: true,
+            },
+            EventAttribute {
+                key: "receipt".to_string(),
+                value: receipt_json,
+                index: false,
+            },
+        ],
+    ))
+}
+
 /// Map to query results.
 pub fn to_query(ret: FvmQueryRet, block_height: BlockHeight) -> anyhow::Result<response::Query> {
     let exit_code = match ret {
@@ -466,13 +564,17 @@ pub fn to_app_hash(state_params: &FvmStateParams) -> tendermint::hash::AppHash {
 
 #[cfg(test)]
 mod tests {
+    use fendermint_vm_genesis::{Power, Validator};
+    use fendermint_vm_interpreter::fvm::{
+        BlockSummary, FvmEndRet, ValidatorChange, ValidatorChangeKind, ValidatorSetChange,
+    };
     use fendermint_vm_snapshot::SnapshotItem;
     use fvm_shared::error::ExitCode;
     use tendermint::abci::request;
 
     use crate::tmconv::to_error_msg;
 
-    use super::{from_snapshot, to_app_hash, to_snapshot};
+    use super::{from_snapshot, to_app_hash, to_end_block, to_snapshot};
 
     #[test]
     fn code_error_message() {
@@ -483,6 +585,84 @@ mod tests {
         );
     }
 
+    #[quickcheck_macros::quickcheck]
+    fn end_block_validator_changed(v: Validator<Power>) {
+        let change = ValidatorSetChange {
+            block_height: 10,
+            configuration_number: 3,
+            changes: vec![ValidatorChange {
+                public_key: v.public_key,
+                kind: ValidatorChangeKind::Left,
+                previous_power: v.power,
+                power: Power(0),
+            }],
+        };
+        let ret = FvmEndRet {
+            power_updates: change.power_updates(),
+            validator_set_change: Some(change),
+            summary: BlockSummary {
+                txs: 2,
+                failed_txs: 1,
+                checkpoint_created: true,
+                ..Default::default()
+            },
+        };
+
+        let end_block = to_end_block(ret).unwrap();
+
+        assert_eq!(end_block.validator_updates.len(), 1);
+        assert_eq!(end_block.validator_updates[0].power.value(), 0);
+        assert_eq!(end_block.events.len(), 2);
+
+        let summary = &end_block.events[1];
+        assert_eq!(summary.kind, "block_summary");
+        assert!(summary
+            .attributes
+            .iter()
+            .any(|a| a.key == "checkpoint_created" && a.value == "true"));
+
+        let event = &end_block.events[0];
+        assert_eq!(event.kind, "validator_changed");
+
+        let attr = |key: &str| {
+            event
+                .attributes
+                .iter()
+                .find(|a| a.key == key)
+                .map(|a| a.value.clone())
+        };
+        assert_eq!(attr("kind"), Some("left".to_string()));
+        assert_eq!(attr("previous_power"), Some(v.power.0.to_string()));
+        assert_eq!(attr("configuration_number"), Some("3".to_string()));
+    }
+
+    #[quickcheck_macros::quickcheck]
+    fn end_block_validator_change_invalid_power(v: Validator<Power>) {
+        // Without changes only the summary is published.
+        let end_block = to_end_block(FvmEndRet::default()).unwrap();
+        assert!(end_block.validator_updates.is_empty());
+        assert_eq!(end_block.events.len(), 1);
+        assert_eq!(end_block.events[0].kind, "block_summary");
+
+        // CometBFT can't represent powers beyond `i64::MAX`.
+        let change = ValidatorSetChange {
+            block_height: 10,
+            configuration_number: 3,
+            changes: vec![ValidatorChange {
+                public_key: v.public_key,
+                kind: ValidatorChangeKind::Joined,
+                previous_power: Power(0),
+                power: Power(u64::MAX),
+            }],
+        };
+        let ret = FvmEndRet {
+            power_updates: change.power_updates(),
+            validator_set_change: Some(change),
+            ..Default::default()
+        };
+        assert!(to_end_block(ret).is_err());
+    }
+
     #[quickcheck_macros::quickcheck]
     fn abci_snapshot_metadata(snapshot: SnapshotItem) {
         let abci_snapshot = to_snapshot(snapshot.clone()).unwrap();
@@ -40,6 +40,7 @@ use fil_actors_evm_shared::uints;
 
 use crate::conv::from_eth::{self, to_fvm_message};
 use crate::conv::from_tm::{self, msg_hash, to_chain_message, to_cumulative, to_eth_block_zero};
+use crate::custom_actors;
 use crate::error::{error_with_revert, OutOfSequence};
 use crate::filters::{matches_topics, FilterId, FilterKind, FilterRecords};
 use crate::{
@@ -681,9 +682,19 @@ pub async fn call<C>(
 where
     C: Client + Sync + Send,
 {
-    let msg = to_fvm_message(tx.into(), true)?;
-    let is_create = msg.to == EAM_ACTOR_ADDR;
+    let tx: TypedTransaction = tx.into();
     let height = data.query_height(block_id).await?;
+
+    if let Some(to) = tx.to().and_then(|to| to.as_address()) {
+        if custom_actors::is_custom_actor(to) {
+            let from = to_fvm_address(tx.from().cloned().unwrap_or_default());
+            let input = tx.data().map(|d| d.to_vec()).unwrap_or_default();
+            return custom_actors::call(&data, from, to, &input, height).await;
+        }
+    }
+
+    let msg = to_fvm_message(tx, true)?;
+    let is_create = msg.to == EAM_ACTOR_ADDR;
     let response = data.client.call(msg, height).await?;
     let deliver_tx = response.value;
 
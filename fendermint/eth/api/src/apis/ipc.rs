@@ -0,0 +1,72 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use anyhow::Context;
+use ethers_core::types as et;
+use fendermint_rpc::query::QueryClient;
+use fendermint_vm_message::query::FvmQueryHeight;
+use ipc_api::cross::TopDownReceipt;
+use jsonrpc_v2::Params;
+use tendermint_rpc::Client;
+
+use crate::conv::from_fvm::to_eth_tokens;
+use crate::{JsonRpcData, JsonRpcResult};
+
+/// Returns the result of executing the top-down message with the given nonce in this subnet,
+/// or null if it hasn't been executed yet.
+pub async fn get_top_down_receipt<C>(
+    data: JsonRpcData<C>,
+    Params((nonce,)): Params<(et::U64,)>,
+) -> JsonRpcResult<Option<TopDownReceipt>>
+where
+    C: Client + Sync + Send,
+{
+    let Some(res) = data.tx_by_topdown_nonce(nonce.as_u64()).await? else {
+        return Ok(None);
+    };
+
+    let nonce = nonce.as_u64().to_string();
+    let attr = |event: &tendermint::abci::Event, key: &str| {
+        event
+            .attributes
+            .iter()
+            .find(|a| a.key == key)
+            .map(|a| a.value.clone())
+    };
+
+    // A transaction executes a batch of messages, with an event for each.
+    let receipt = res
+        .tx_result
+        .events
+        .iter()
+        .filter(|e| e.kind == "topdown_receipt")
+        .find(|e| attr(e, "nonce").as_deref() == Some(nonce.as_str()))
+        .and_then(|e| attr(e, "receipt"));
+
+    match receipt {
+        Some(receipt) => Ok(Some(
+            serde_json::from_str(&receipt).context("failed to parse topdown receipt")?,
+        )),
+        None => Ok(None),
+    }
+}
+
+/// Returns the circulating supply of the subnet at the latest height, in atto: the genesis
+/// supply, plus what was funded from the parent, minus what was released to it.
+pub async fn get_circ_supply<C>(data: JsonRpcData<C>) -> JsonRpcResult<et::U256>
+where
+    C: Client + Sync + Send,
+{
+    let res = data.client.state_params(FvmQueryHeight::default()).await?;
+    Ok(to_eth_tokens(&res.value.circ_supply)?)
+}
+
+/// Returns the version of the application at the latest height, which the upgrades of the
+/// subnet bump, so that clients can tell which features it supports.
+pub async fn get_app_version<C>(data: JsonRpcData<C>) -> JsonRpcResult<et::U64>
+where
+    C: Client + Sync + Send,
+{
+    let res = data.client.state_params(FvmQueryHeight::default()).await?;
+    Ok(et::U64::from(res.value.app_version))
+}
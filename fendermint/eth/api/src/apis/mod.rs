@@ -9,6 +9,7 @@ use jsonrpc_v2::{MapRouter, ServerBuilder};
 use paste::paste;
 
 mod eth;
+mod ipc;
 mod net;
 mod web3;
 
@@ -86,10 +87,17 @@ pub fn register_methods(server: ServerBuilder<MapRouter>) -> ServerBuilder<MapRo
         sha3
     });
 
-    with_methods!(server, net, {
+    let server = with_methods!(server, net, {
         version,
         listening,
         peerCount
+    });
+
+    // IPC specific methods, beyond the Ethereum API.
+    with_methods!(server, ipc, {
+        getTopDownReceipt,
+        getCircSupply,
+        getAppVersion
     })
 }
 
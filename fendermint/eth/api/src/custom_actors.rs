@@ -0,0 +1,374 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Expose the read methods of the custom Fendermint actors through `eth_call` with ABI encoding,
+//! so that standard Ethereum tooling can use them without knowing about IPLD.
+//!
+//! The actors are reachable at their masked ID addresses, which are deterministic:
+//!
+//! * `chainmetadata` at `0xff00000000000000000000000000000000000030`:
+//!   * `lookbackLen() returns (uint64)`
+//!   * `getBlockHash(int64 epoch) returns (bytes32)`; zero if the hash is not available
+//!   * `getBlockTimestamp(int64 epoch) returns (uint64)`; seconds since the Unix epoch, zero if
+//!     the timestamp is not available
+//! * `machinelearning` at `0xff00000000000000000000000000000000000031`:
+//!   * `trainLinearRegression(int64[][] input, int64[] labels) returns (bytes)`
+//!   * `trainLogisticRegression(int64[][] input, int64[] labels) returns (bytes)`
+//!   * `trainKNNRegression(int64[][] input, int64[] labels) returns (bytes)`
+//!   * `predictLinearRegression(int64[][] input, bytes model) returns (int64[])`
+//!   * `predictLogisticRegression(int64[][] input, bytes model) returns (int64[])`
+//!   * `predictKNNRegression(int64[][] input, bytes model) returns (int64[])`
+//!
+//! For example with foundry:
+//!
+//! ```text
+//! cast call 0xff00000000000000000000000000000000000030 "getBlockHash(int64)(bytes32)" 100
+//! ```
+
+use anyhow::{anyhow, Context};
+use ethers_core::abi::{self, ParamType, Token};
+use ethers_core::types::{self as et, I256, U256};
+use ethers_core::utils::id;
+use fendermint_rpc::query::QueryClient;
+use fendermint_rpc::response::decode_data;
+use fendermint_vm_actor_interface::chainmetadata::{self, CHAINMETADATA_ACTOR_ID};
+use fendermint_vm_actor_interface::eam::EthAddress;
+use fendermint_vm_actor_interface::machinelearning::{self, MACHINELEARNING_ACTOR_ID};
+use fendermint_vm_message::query::FvmQueryHeight;
+use fvm_ipld_encoding::RawBytes;
+use fvm_shared::address::Address;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::error::ExitCode;
+use fvm_shared::message::Message;
+use fvm_shared::MethodNum;
+use tendermint_rpc::Client;
+
+use crate::{error, JsonRpcData, JsonRpcResult};
+
+/// Check whether the address is one of the actors served by this module.
+pub fn is_custom_actor(to: &et::H160) -> bool {
+    let to = EthAddress(to.0);
+    to == EthAddress::from_id(CHAINMETADATA_ACTOR_ID)
+        || to == EthAddress::from_id(MACHINELEARNING_ACTOR_ID)
+}
+
+/// Translate an ABI encoded call into an actor invocation, execute it as a read-only call,
+/// then translate the result back to ABI.
+pub async fn call<C>(
+    data: &JsonRpcData<C>,
+    from: Address,
+    to: &et::H160,
+    input: &[u8],
+    height: FvmQueryHeight,
+) -> JsonRpcResult<et::Bytes>
+where
+    C: Client + Sync + Send,
+{
+    if input.len() < 4 {
+        return error(ExitCode::USR_ILLEGAL_ARGUMENT, "missing method selector");
+    }
+    let (selector, args) = input.split_at(4);
+
+    let to_id = if EthAddress(to.0) == EthAddress::from_id(CHAINMETADATA_ACTOR_ID) {
+        CHAINMETADATA_ACTOR_ID
+    } else {
+        MACHINELEARNING_ACTOR_ID
+    };
+
+    let Some(method) = CustomMethod::from_selector(to_id, selector) else {
+        return error(
+            ExitCode::USR_UNHANDLED_MESSAGE,
+            format!("unknown selector 0x{}", hex::encode(selector)),
+        );
+    };
+
+    let params = method.encode_params(args)?;
+
+    let msg = Message {
+        version: Default::default(),
+        from,
+        to: Address::new_id(to_id),
+        sequence: 0,
+        value: TokenAmount::default(),
+        method_num: method.method_num(),
+        params,
+        gas_limit: fvm_shared::BLOCK_GAS_LIMIT,
+        gas_fee_cap: TokenAmount::default(),
+        gas_premium: TokenAmount::default(),
+    };
+
+    let response = data.client.call(msg, height).await?;
+    let deliver_tx = response.value;
+
+    if deliver_tx.code.is_err() {
+        return error(ExitCode::new(deliver_tx.code.value()), deliver_tx.info);
+    }
+
+    let return_data = decode_data(&deliver_tx.data).context("error decoding return data")?;
+    let tokens = method.decode_return(&return_data)?;
+
+    Ok(abi::encode(&tokens).into())
+}
+
+/// The ABI methods we map to actor methods.
+enum CustomMethod {
+    LookbackLen,
+    GetBlockHash,
+    GetBlockTimestamp,
+    Train(machinelearning::Method),
+    Predict(machinelearning::Method),
+}
+
+impl CustomMethod {
+    fn from_selector(actor_id: u64, selector: &[u8]) -> Option<Self> {
+        let is = |sig: &str| id(sig) == selector;
+        match actor_id {
+            CHAINMETADATA_ACTOR_ID if is("lookbackLen()") => Some(Self::LookbackLen),
+            CHAINMETADATA_ACTOR_ID if is("getBlockHash(int64)") => Some(Self::GetBlockHash),
+            CHAINMETADATA_ACTOR_ID if is("getBlockTimestamp(int64)") => {
+                Some(Self::GetBlockTimestamp)
+            }
+            MACHINELEARNING_ACTOR_ID if is("trainLinearRegression(int64[][],int64[])") => {
+                Some(Self::Train(machinelearning::Method::TrainLinearRegression))
+            }
+            MACHINELEARNING_ACTOR_ID if is("trainLogisticRegression(int64[][],int64[])") => Some(
+                Self::Train(machinelearning::Method::TrainLogisticRegression),
+            ),
+            MACHINELEARNING_ACTOR_ID if is("trainKNNRegression(int64[][],int64[])") => {
+                Some(Self::Train(machinelearning::Method::TrainKNNRegression))
+            }
+            MACHINELEARNING_ACTOR_ID if is("predictLinearRegression(int64[][],bytes)") => Some(
+                Self::Predict(machinelearning::Method::PredictLinearRegression),
+            ),
+            MACHINELEARNING_ACTOR_ID if is("predictLogisticRegression(int64[][],bytes)") => Some(
+                Self::Predict(machinelearning::Method::PredictLogisticRegression),
+            ),
+            MACHINELEARNING_ACTOR_ID if is("predictKNNRegression(int64[][],bytes)") => {
+                Some(Self::Predict(machinelearning::Method::PredictKNNRegression))
+            }
+            _ => None,
+        }
+    }
+
+    fn method_num(&self) -> MethodNum {
+        match self {
+            Self::LookbackLen => chainmetadata::Method::LookbackLen as u64,
+            Self::GetBlockHash => chainmetadata::Method::GetBlockHash as u64,
+            Self::GetBlockTimestamp => chainmetadata::Method::GetBlockTimestamp as u64,
+            Self::Train(m) | Self::Predict(m) => *m as u64,
+        }
+    }
+
+    /// Decode the ABI arguments and encode them as IPLD parameters for the actor.
+    fn encode_params(&self, args: &[u8]) -> anyhow::Result<RawBytes> {
+        match self {
+            Self::LookbackLen => Ok(RawBytes::default()),
+            Self::GetBlockHash | Self::GetBlockTimestamp => {
+                let tokens = abi::decode(&[ParamType::Int(64)], args)?;
+                let epoch = token_to_i64(&tokens[0])?;
+                Ok(RawBytes::serialize(epoch)?)
+            }
+            Self::Train(_) => {
+                let tokens = abi::decode(
+                    &[
+                        ParamType::Array(Box::new(ParamType::Array(Box::new(ParamType::Int(64))))),
+                        ParamType::Array(Box::new(ParamType::Int(64))),
+                    ],
+                    args,
+                )?;
+                let input_matrix = token_to_matrix(&tokens[0])?;
+                let labels = match &tokens[1] {
+                    Token::Array(labels) => labels
+                        .iter()
+                        .map(token_to_i64)
+                        .collect::<anyhow::Result<Vec<i64>>>()?,
+                    _ => return Err(anyhow!("expected an array of labels")),
+                };
+
+                Ok(RawBytes::serialize(machinelearning::TrainParams {
+                    input_matrix: input_matrix.into(),
+                    labels,
+                    scaler: None,
+                })?)
+            }
+            Self::Predict(_) => {
+                let tokens = abi::decode(
+                    &[
+                        ParamType::Array(Box::new(ParamType::Array(Box::new(ParamType::Int(64))))),
+                        ParamType::Bytes,
+                    ],
+                    args,
+                )?;
+                let input_matrix = token_to_matrix(&tokens[0])?;
+                let model = tokens[1]
+                    .clone()
+                    .into_bytes()
+                    .ok_or_else(|| anyhow!("expected model bytes"))?;
+
+                Ok(RawBytes::serialize(machinelearning::PredictParams {
+                    input_matrix: input_matrix.into(),
+                    model,
+                    scaler: None,
+                })?)
+            }
+        }
+    }
+
+    /// Decode the IPLD return value of the actor and turn it into ABI tokens.
+    fn decode_return(&self, data: &RawBytes) -> anyhow::Result<Vec<Token>> {
+        match self {
+            Self::LookbackLen => {
+                let len: u64 = fvm_ipld_encoding::from_slice(data)?;
+                Ok(vec![Token::Uint(U256::from(len))])
+            }
+            Self::GetBlockHash => {
+                let hash: Option<[u8; 32]> = fvm_ipld_encoding::from_slice(data)?;
+                Ok(vec![Token::FixedBytes(hash.unwrap_or_default().to_vec())])
+            }
+            Self::GetBlockTimestamp => {
+                let timestamp: Option<u64> = fvm_ipld_encoding::from_slice(data)?;
+                Ok(vec![Token::Uint(U256::from(timestamp.unwrap_or_default()))])
+            }
+            Self::Train(_) => {
+                let model: Vec<u8> = fvm_ipld_encoding::from_slice(data)?;
+                Ok(vec![Token::Bytes(model)])
+            }
+            Self::Predict(_) => {
+                let predictions: Vec<i64> = fvm_ipld_encoding::from_slice(data)?;
+                let predictions = predictions
+                    .into_iter()
+                    .map(|p| Token::Int(I256::from(p).into_raw()))
+                    .collect();
+                Ok(vec![Token::Array(predictions)])
+            }
+        }
+    }
+}
+
+fn token_to_i64(token: &Token) -> anyhow::Result<i64> {
+    match token {
+        Token::Int(u) => i64::try_from(I256::from_raw(*u)).map_err(|_| anyhow!("int64 overflow")),
+        _ => Err(anyhow!("expected an int64")),
+    }
+}
+
+fn token_to_matrix(token: &Token) -> anyhow::Result<Vec<Vec<i64>>> {
+    match token {
+        Token::Array(rows) => rows
+            .iter()
+            .map(|row| match row {
+                Token::Array(cols) => cols.iter().map(token_to_i64).collect(),
+                _ => Err(anyhow!("expected an array row")),
+            })
+            .collect(),
+        _ => Err(anyhow!("expected an input matrix")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers_core::abi::{self, Token};
+    use ethers_core::types::{I256, U256};
+    use ethers_core::utils::id;
+    use fendermint_vm_actor_interface::chainmetadata::CHAINMETADATA_ACTOR_ID;
+    use fendermint_vm_actor_interface::eam::EthAddress;
+    use fendermint_vm_actor_interface::machinelearning::{self, MACHINELEARNING_ACTOR_ID};
+    use fvm_ipld_encoding::RawBytes;
+
+    use super::{is_custom_actor, CustomMethod};
+
+    #[test]
+    fn custom_actor_addresses() {
+        for id in [CHAINMETADATA_ACTOR_ID, MACHINELEARNING_ACTOR_ID] {
+            assert!(is_custom_actor(&EthAddress::from_id(id).0.into()));
+        }
+        assert!(!is_custom_actor(&EthAddress::from_id(10).0.into()));
+    }
+
+    #[test]
+    fn predict_params_roundtrip() {
+        let selector = id("predictLinearRegression(int64[][],bytes)");
+        let method = CustomMethod::from_selector(MACHINELEARNING_ACTOR_ID, &selector)
+            .expect("selector should be recognised");
+
+        let args = abi::encode(&[
+            Token::Array(vec![Token::Array(vec![
+                Token::Int(I256::from(-1).into_raw()),
+                Token::Int(I256::from(2).into_raw()),
+            ])]),
+            Token::Bytes(vec![1, 2, 3]),
+        ]);
+
+        let params = method
+            .encode_params(&args)
+            .expect("failed to encode params");
+        let params: machinelearning::PredictParams =
+            fvm_ipld_encoding::from_slice(&params).expect("failed to decode params");
+
+        assert_eq!(
+            params.input_matrix,
+            machinelearning::InputMatrix::Dense(vec![vec![-1, 2]])
+        );
+        assert_eq!(params.model, vec![1, 2, 3]);
+
+        let ret = RawBytes::serialize(vec![-5i64]).unwrap();
+        let tokens = method.decode_return(&ret).expect("failed to decode return");
+        assert_eq!(
+            tokens,
+            vec![Token::Array(vec![Token::Int(I256::from(-5).into_raw())])]
+        );
+    }
+
+    #[test]
+    fn train_params_roundtrip() {
+        let selector = id("trainKNNRegression(int64[][],int64[])");
+        let method = CustomMethod::from_selector(MACHINELEARNING_ACTOR_ID, &selector)
+            .expect("selector should be recognised");
+
+        let args = abi::encode(&[
+            Token::Array(vec![Token::Array(vec![Token::Int(
+                I256::from(3).into_raw(),
+            )])]),
+            Token::Array(vec![Token::Int(I256::from(-4).into_raw())]),
+        ]);
+
+        let params = method
+            .encode_params(&args)
+            .expect("failed to encode params");
+        let params: machinelearning::TrainParams =
+            fvm_ipld_encoding::from_slice(&params).expect("failed to decode params");
+
+        assert_eq!(
+            params.input_matrix,
+            machinelearning::TrainingInput::from(vec![vec![3]])
+        );
+        assert_eq!(params.labels, vec![-4]);
+
+        let ret = RawBytes::serialize(vec![7u8, 8]).unwrap();
+        let tokens = method.decode_return(&ret).expect("failed to decode return");
+        assert_eq!(tokens, vec![Token::Bytes(vec![7, 8])]);
+    }
+
+    #[test]
+    fn block_timestamp_roundtrip() {
+        let selector = id("getBlockTimestamp(int64)");
+        let method = CustomMethod::from_selector(CHAINMETADATA_ACTOR_ID, &selector)
+            .expect("selector should be recognised");
+
+        let args = abi::encode(&[Token::Int(I256::from(100).into_raw())]);
+        let params = method
+            .encode_params(&args)
+            .expect("failed to encode params");
+        let epoch: i64 = fvm_ipld_encoding::from_slice(&params).expect("failed to decode params");
+        assert_eq!(epoch, 100);
+
+        let ret = RawBytes::serialize(Some(1_700_000_000u64)).unwrap();
+        let tokens = method.decode_return(&ret).expect("failed to decode return");
+        assert_eq!(tokens, vec![Token::Uint(U256::from(1_700_000_000u64))]);
+
+        // Missing timestamps are zero.
+        let ret = RawBytes::serialize(None::<u64>).unwrap();
+        let tokens = method.decode_return(&ret).expect("failed to decode return");
+        assert_eq!(tokens, vec![Token::Uint(U256::zero())]);
+    }
+}
@@ -11,6 +11,7 @@ mod apis;
 mod cache;
 mod client;
 mod conv;
+mod custom_actors;
 mod error;
 mod filters;
 mod gas;
@@ -393,6 +393,24 @@ where
         }
     }
 
+    /// Get the Tendermint transaction which executed the top-down message with a given nonce.
+    pub async fn tx_by_topdown_nonce(
+        &self,
+        nonce: u64,
+    ) -> JsonRpcResult<Option<tendermint_rpc::endpoint::tx::Response>> {
+        // Relies on the `topdown_receipt` events emitted for each top-down message.
+        let query = Query::eq("topdown_receipt.nonce", nonce.to_string());
+
+        match self
+            .tm()
+            .tx_search(query, false, 1, 1, Order::Ascending)
+            .await
+        {
+            Ok(res) => Ok(res.txs.into_iter().next()),
+            Err(e) => error(ExitCode::USR_UNSPECIFIED, e),
+        }
+    }
+
     /// Send a message by the system actor to an EVM actor for a read-only query.
     ///
     /// If the actor doesn't exist then the FVM will create a placeholder actor,
@@ -5,7 +5,9 @@ use std::sync::Arc;
 use anyhow::anyhow;
 use cid::Cid;
 use fvm_ipld_blockstore::Blockstore;
-use rocksdb::{BoundColumnFamily, OptimisticTransactionDB, WriteBatchWithTransaction};
+use rocksdb::{
+    BoundColumnFamily, IteratorMode, OptimisticTransactionDB, WriteBatchWithTransaction,
+};
 
 use crate::RocksDb;
 
@@ -66,6 +68,34 @@ impl NamespaceBlockstore {
             .cf_handle(&self.ns)
             .ok_or_else(|| anyhow!("namespace {} does not exist!", self.ns))
     }
+
+    /// Iterate all the CIDs stored in the namespace.
+    ///
+    /// The iterator sees a consistent view of the database as of the time it was created;
+    /// blocks written after that are not returned.
+    pub fn keys(&self) -> anyhow::Result<impl Iterator<Item = anyhow::Result<Cid>> + '_> {
+        let iter = self.db.iterator_cf(&self.cf()?, IteratorMode::Start);
+        Ok(iter.map(|kv| {
+            let (k, _) = kv?;
+            Ok(Cid::try_from(k.as_ref())?)
+        }))
+    }
+
+    /// Delete blocks from the namespace in a single batch.
+    ///
+    /// This is not part of the [`Blockstore`] interface because the FVM never deletes data;
+    /// it is meant for garbage collection of blocks which are not reachable any more.
+    pub fn delete_many<'a, I>(&self, cids: I) -> anyhow::Result<()>
+    where
+        I: IntoIterator<Item = &'a Cid>,
+    {
+        let cf = self.cf()?;
+        let mut batch = WriteBatchWithTransaction::<true>::default();
+        for cid in cids {
+            batch.delete_cf(&cf, cid.to_bytes());
+        }
+        Ok(self.db.write(batch)?)
+    }
 }
 
 impl Blockstore for NamespaceBlockstore {
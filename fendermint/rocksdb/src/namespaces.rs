@@ -16,8 +16,8 @@
 /// ```
 #[macro_export]
 macro_rules! namespaces {
-    ($name:ident { $($col:ident),* }) => {
-        struct $name {
+    ($vis:vis $name:ident { $($col:ident),* }) => {
+        $vis struct $name {
             pub $($col: String),+
         }
 
@@ -8,6 +8,7 @@ use base64::{
 
 pub mod client;
 pub mod message;
+pub mod model;
 pub mod query;
 pub mod response;
 pub mod tx;
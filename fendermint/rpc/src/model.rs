@@ -0,0 +1,265 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Export the models listed with the machine learning actor along with a proof of their
+//! inclusion in the state, so off-chain inference services can check they have what the
+//! chain has.
+//!
+//! A [ModelProof] consists of the IPLD blocks on the path from the root of the state tree to
+//! the model: the nodes of the actors HAMT down to the machine learning actor, the state of
+//! the actor and the nodes of the models AMT down to the model. The blocks are checked against
+//! their CIDs, so given a state root obtained from a node it trusts, or the same one from
+//! several nodes, anyone can verify the model without trusting whoever sent the proof.
+
+use std::cell::RefCell;
+use std::future::Future;
+use std::path::Path;
+
+use anyhow::{anyhow, Context};
+use cid::multihash::{Code, MultihashDigest};
+use cid::Cid;
+use fendermint_actor_machinelearning::{ListedModel, State};
+use fendermint_vm_actor_interface::machinelearning::MACHINELEARNING_ACTOR_ID;
+use futures::AsyncWriteExt;
+use fvm::state_tree::StateTree;
+use fvm_ipld_amt::Amt;
+use fvm_ipld_blockstore::{Blockstore, MemoryBlockstore};
+use fvm_ipld_car::{CarHeader, CarReader};
+use fvm_ipld_encoding::CborStore;
+use tokio_util::compat::{TokioAsyncReadCompatExt, TokioAsyncWriteCompatExt};
+
+/// The blocks proving that a listed model is part of the state with the given root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelProof {
+    pub state_root: Cid,
+    pub model_id: u64,
+    /// The blocks on the path to the model, starting with the state root.
+    pub blocks: Vec<(Cid, Vec<u8>)>,
+}
+
+impl ModelProof {
+    /// Collect the blocks on the path to the model, fetching them one by one as the path is
+    /// walked from the state root.
+    pub async fn fetch<F, R>(state_root: Cid, model_id: u64, mut fetch: F) -> anyhow::Result<Self>
+    where
+        F: FnMut(Cid) -> R,
+        R: Future<Output = anyhow::Result<Option<Vec<u8>>>>,
+    {
+        let store = FetchingBlockstore::default();
+        loop {
+            let res = load_model(&store, &state_root, model_id);
+            let missing = store.missing.take();
+            match (res, missing) {
+                (Ok(_), _) => break,
+                (Err(e), None) => return Err(e),
+                (Err(_), Some(cid)) => {
+                    let data = fetch(cid)
+                        .await?
+                        .ok_or_else(|| anyhow!("block {cid} not found"))?;
+                    check_block(&cid, &data)?;
+                    store.blocks.borrow_mut().push((cid, data));
+                }
+            }
+        }
+        Ok(Self {
+            state_root,
+            model_id,
+            blocks: store.blocks.into_inner(),
+        })
+    }
+
+    /// Check the blocks against their CIDs and return the model they lead to from the state root.
+    pub fn verify(&self) -> anyhow::Result<ListedModel> {
+        let store = MemoryBlockstore::new();
+        for (cid, data) in self.blocks.iter() {
+            check_block(cid, data)?;
+            store.put_keyed(cid, data)?;
+        }
+        load_model(&store, &self.state_root, self.model_id)
+            .with_context(|| format!("the proof doesn't lead to model {}", self.model_id))
+    }
+
+    /// Write the blocks to a CAR file, with the state root as its root.
+    pub async fn write_car(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let file = tokio::fs::File::create(path).await?;
+        let car = CarHeader::new(vec![self.state_root], 1);
+        let mut blocks = futures::stream::iter(self.blocks.clone());
+        let mut writer = file.compat_write();
+        car.write_stream_async(&mut writer, &mut blocks)
+            .await
+            .context("failed to write CAR file")?;
+        writer.flush().await?;
+        Ok(())
+    }
+
+    /// Read the proof of a model from a CAR file written by [ModelProof::write_car].
+    ///
+    /// The blocks are not checked until [ModelProof::verify] is called.
+    pub async fn read_car(path: impl AsRef<Path>, model_id: u64) -> anyhow::Result<Self> {
+        let file = tokio::fs::File::open(path).await?;
+        let mut reader = CarReader::new_unchecked(file.compat())
+            .await
+            .context("failed to open CAR reader")?;
+
+        let state_root = *reader
+            .header
+            .roots
+            .first()
+            .ok_or_else(|| anyhow!("the CAR file has no root"))?;
+
+        let mut blocks = Vec::new();
+        while let Some(block) = reader.next_block().await? {
+            blocks.push((block.cid, block.data));
+        }
+
+        Ok(Self {
+            state_root,
+            model_id,
+            blocks,
+        })
+    }
+}
+
+/// Walk from the state root to the model.
+fn load_model<BS: Blockstore>(
+    store: &BS,
+    state_root: &Cid,
+    model_id: u64,
+) -> anyhow::Result<ListedModel> {
+    let state_tree = StateTree::new_from_root(store, state_root)?;
+    let actor = state_tree
+        .get_actor(MACHINELEARNING_ACTOR_ID)?
+        .ok_or_else(|| anyhow!("the machine learning actor is not in the state"))?;
+    let state: State = store
+        .get_cbor(&actor.state)?
+        .ok_or_else(|| anyhow!("the machine learning actor state is missing"))?;
+    let models = Amt::<ListedModel, _>::load(&state.models, store)?;
+    let model = models
+        .get(model_id)?
+        .ok_or_else(|| anyhow!("model {model_id} is not listed"))?;
+    Ok(model.clone())
+}
+
+/// Check that the data hashes to the CID.
+fn check_block(cid: &Cid, data: &[u8]) -> anyhow::Result<()> {
+    let code = Code::try_from(cid.hash().code())?;
+    if code.digest(data) != *cid.hash() {
+        return Err(anyhow!("block {cid} doesn't match its CID"));
+    }
+    Ok(())
+}
+
+/// Remembers the first block missing while walking to the model, so it can be fetched.
+#[derive(Default)]
+struct FetchingBlockstore {
+    blocks: RefCell<Vec<(Cid, Vec<u8>)>>,
+    missing: RefCell<Option<Cid>>,
+}
+
+impl Blockstore for FetchingBlockstore {
+    fn get(&self, k: &Cid) -> anyhow::Result<Option<Vec<u8>>> {
+        let data = self
+            .blocks
+            .borrow()
+            .iter()
+            .find(|(cid, _)| cid == k)
+            .map(|(_, data)| data.clone());
+
+        if data.is_none() {
+            self.missing.borrow_mut().get_or_insert(*k);
+        }
+        Ok(data)
+    }
+
+    fn put_keyed(&self, _k: &Cid, _block: &[u8]) -> anyhow::Result<()> {
+        Err(anyhow!("the blocks of a proof are read-only"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cid::multihash::Code;
+    use cid::Cid;
+    use fendermint_actor_machinelearning::{Algorithm, ListedModel, State};
+    use fendermint_vm_actor_interface::machinelearning::MACHINELEARNING_ACTOR_ID;
+    use fvm::state_tree::{ActorState, StateTree};
+    use fvm_ipld_amt::Amt;
+    use fvm_ipld_blockstore::{Blockstore, MemoryBlockstore};
+    use fvm_ipld_encoding::CborStore;
+    use fvm_shared::{address::Address, econ::TokenAmount, state::StateTreeVersion};
+
+    use super::ModelProof;
+
+    fn listed_model(model: Vec<u8>) -> ListedModel {
+        ListedModel {
+            owner: Address::new_id(100),
+            algorithm: Algorithm::LinearRegression,
+            model,
+            scaler: None,
+            prediction_fee: TokenAmount::default(),
+            predictions: 0,
+            revenue: TokenAmount::default(),
+            algorithm_version: 1,
+            dataset_hash: [0; 32],
+            hyperparameters: Default::default(),
+            listed_at: 10,
+        }
+    }
+
+    /// A state tree with the machine learning actor and two listed models.
+    fn state_with_models(store: &MemoryBlockstore) -> Cid {
+        let mut state: State = State::new(store, Default::default()).unwrap();
+        let mut models = Amt::<ListedModel, _>::load(&state.models, store).unwrap();
+        models.set(0, listed_model(vec![1, 2, 3])).unwrap();
+        models.set(1, listed_model(vec![4, 5, 6])).unwrap();
+        state.models = models.flush().unwrap();
+        state.next_model_id = 2;
+
+        let mut state_tree = StateTree::new(store, StateTreeVersion::V5).unwrap();
+        state_tree.set_actor(
+            MACHINELEARNING_ACTOR_ID,
+            ActorState {
+                code: Cid::default(),
+                state: store.put_cbor(&state, Code::Blake2b256).unwrap(),
+                sequence: 0,
+                balance: TokenAmount::default(),
+                delegated_address: None,
+            },
+        );
+        state_tree.flush().unwrap()
+    }
+
+    #[tokio::test]
+    async fn model_proof_verifies() {
+        let store = MemoryBlockstore::new();
+        let state_root = state_with_models(&store);
+
+        let proof = ModelProof::fetch(state_root, 1, |cid| {
+            let data = store.get(&cid);
+            async move { data }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(proof.blocks[0].0, state_root);
+        assert_eq!(proof.verify().unwrap().model, vec![4, 5, 6]);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("model.car");
+        proof.write_car(&path).await.unwrap();
+        assert_eq!(ModelProof::read_car(&path, 1).await.unwrap(), proof);
+
+        // Tampered blocks are rejected.
+        let mut tampered = proof.clone();
+        tampered.blocks.last_mut().unwrap().1.push(0);
+        assert!(tampered.verify().is_err());
+
+        // Models which aren't listed can't be proven.
+        assert!(ModelProof::fetch(state_root, 2, |cid| {
+            let data = store.get(&cid);
+            async move { data }
+        })
+        .await
+        .is_err());
+    }
+}
@@ -15,11 +15,16 @@ use cid::Cid;
 use fvm_shared::ActorID;
 use fvm_shared::{address::Address, error::ExitCode};
 
+use fendermint_vm_actor_interface::machinelearning::{
+    self, InputMatrix, PredictWithModelParams, MACHINELEARNING_ACTOR_ADDR,
+};
+use fendermint_vm_actor_interface::system::SYSTEM_ACTOR_ADDR;
 use fendermint_vm_message::query::{
     ActorState, BuiltinActors, FvmQuery, FvmQueryHeight, GasEstimate, StateParams,
 };
 
-use crate::response::encode_data;
+use crate::model::ModelProof;
+use crate::response::{decode_bytes, encode_data};
 
 #[derive(Serialize, Debug, Clone)]
 /// The parsed value from a query, along with the height at which the query was performed.
@@ -128,6 +133,68 @@ pub trait QueryClient: Sync {
         Ok(QueryResponse { height, value })
     }
 
+    /// Predict with a listed model as it was at the given height, without paying its fee.
+    async fn ml_prediction(
+        &self,
+        model_id: u64,
+        input_matrix: InputMatrix,
+        height: FvmQueryHeight,
+    ) -> anyhow::Result<QueryResponse<Vec<i64>>> {
+        let params = PredictWithModelParams {
+            model_id,
+            input_matrix,
+        };
+        let params = fvm_ipld_encoding::RawBytes::serialize(params)?;
+
+        // Only the system actor can predict without paying; the query runs it as an implicit message.
+        let msg = Message {
+            version: Default::default(),
+            from: SYSTEM_ACTOR_ADDR,
+            to: MACHINELEARNING_ACTOR_ADDR,
+            sequence: 0,
+            value: Default::default(),
+            method_num: machinelearning::Method::QueryPrediction as u64,
+            params,
+            gas_limit: 0,
+            gas_fee_cap: Default::default(),
+            gas_premium: Default::default(),
+        };
+
+        let res = self.call(msg, height).await?;
+        if res.value.code.is_err() {
+            return Err(anyhow!(
+                "prediction failed with exit code {}: {}",
+                res.value.code.value(),
+                res.value.info
+            ));
+        }
+        let data = decode_bytes(&res.value)?;
+        let value = fvm_ipld_encoding::from_slice(&data)
+            .context("failed to decode predictions from query")?;
+        Ok(QueryResponse {
+            height: res.height,
+            value,
+        })
+    }
+
+    /// Fetch a listed model along with the blocks proving it's part of the state at the given height.
+    async fn model_proof(
+        &self,
+        model_id: u64,
+        height: FvmQueryHeight,
+    ) -> anyhow::Result<QueryResponse<ModelProof>> {
+        let res = self.state_params(height).await?;
+        let value = ModelProof::fetch(res.value.state_root, model_id, |cid| async move {
+            self.ipld(&cid, height).await
+        })
+        .await
+        .context("model proof query failed")?;
+        Ok(QueryResponse {
+            height: res.height,
+            value,
+        })
+    }
+
     /// Run an ABCI query.
     async fn perform(&self, query: FvmQuery, height: FvmQueryHeight) -> anyhow::Result<AbciQuery>;
 }
@@ -5,7 +5,7 @@ use anyhow::{anyhow, Context, Result};
 use byteorder::{BigEndian, WriteBytesExt};
 use cid::Cid;
 use fendermint_vm_core::Timestamp;
-use fendermint_vm_interpreter::fvm::PowerUpdates;
+use fendermint_vm_interpreter::fvm::FvmEndRet;
 use fvm_shared::{bigint::Zero, clock::ChainEpoch, econ::TokenAmount, version::NetworkVersion};
 use std::{future::Future, sync::Arc};
 
@@ -91,7 +91,7 @@ where
         Message = FvmMessage,
         BeginOutput = FvmApplyRet,
         DeliverOutput = FvmApplyRet,
-        EndOutput = PowerUpdates,
+        EndOutput = FvmEndRet,
     >,
 {
     fn state_store_clone(&self) -> MemoryBlockstore {
@@ -113,6 +113,8 @@ where
                 chain_id: 0,
                 power_scale: 0,
                 app_version: 0,
+                ml_limits: None,
+                implicit_gas: None,
             },
         }
     }
@@ -157,6 +159,8 @@ where
             chain_id: out.chain_id.into(),
             power_scale: out.power_scale,
             app_version: 0,
+            ml_limits: out.ml_limits,
+            implicit_gas: out.implicit_gas,
         };
 
         Ok(())
@@ -232,6 +236,8 @@ where
                 base_fee,
                 circ_supply,
                 power_scale,
+                ml_limits,
+                implicit_gas,
             },
             _,
         ) = exec_state.commit().context("failed to commit FVM")?;
@@ -241,6 +247,8 @@ where
         self.state_params.base_fee = base_fee;
         self.state_params.circ_supply = circ_supply;
         self.state_params.power_scale = power_scale;
+        self.state_params.ml_limits = ml_limits;
+        self.state_params.implicit_gas = implicit_gas;
 
         eprintln!("self.state_params: {:?}", self.state_params);
 
@@ -220,6 +220,11 @@ async fn test_applying_upgrades() {
             balance: TokenAmount::from_atto(0),
         }],
         eam_permission_mode: PermissionMode::Unrestricted,
+        ml_training_policy: Default::default(),
+        ml_limits: None,
+        implicit_gas: None,
+        faucet: Default::default(),
+        contracts: Vec::new(),
         ipc: None,
     };
 
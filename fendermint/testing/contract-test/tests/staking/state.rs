@@ -585,6 +585,11 @@ impl arbitrary::Arbitrary<'_> for StakingState {
             validators: parent_validators,
             accounts: parent_actors,
             eam_permission_mode: PermissionMode::Unrestricted,
+            ml_training_policy: Default::default(),
+            ml_limits: None,
+            implicit_gas: None,
+            faucet: Default::default(),
+            contracts: Vec::new(),
             ipc: Some(parent_ipc),
         };
 
@@ -606,6 +611,11 @@ impl arbitrary::Arbitrary<'_> for StakingState {
             validators: current_configuration,
             accounts: Vec::new(),
             eam_permission_mode: PermissionMode::Unrestricted,
+            ml_training_policy: Default::default(),
+            ml_limits: None,
+            implicit_gas: None,
+            faucet: Default::default(),
+            contracts: Vec::new(),
             ipc: Some(child_ipc),
         };
 
@@ -0,0 +1,24 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+//! A checkpoint read from the contracts doesn't panic the conversion, and stays the same when
+//! it's submitted back.
+#![no_main]
+
+use ethers::abi::AbiDecode;
+use ipc_actors_abis::subnet_actor_checkpointing_facet;
+use ipc_api::checkpoint::BottomUpCheckpoint;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(evm) = subnet_actor_checkpointing_facet::BottomUpCheckpoint::decode(data) else {
+        return;
+    };
+    let Ok(cp) = BottomUpCheckpoint::try_from(evm) else {
+        return;
+    };
+    // Not every checkpoint read can be submitted, e.g. with masked ID addresses in its route,
+    // but those which can must not change.
+    if let Ok(evm) = subnet_actor_checkpointing_facet::BottomUpCheckpoint::try_from(cp.clone()) {
+        assert_eq!(BottomUpCheckpoint::try_from(evm).unwrap(), cp);
+    }
+});
@@ -0,0 +1,16 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+//! A checkpoint decoded from CBOR encodes back into a checkpoint equal to it.
+#![no_main]
+
+use ipc_api::checkpoint::BottomUpCheckpoint;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(cp) = fvm_ipld_encoding::from_slice::<BottomUpCheckpoint>(data) else {
+        return;
+    };
+    let bytes = fvm_ipld_encoding::to_vec(&cp).expect("checkpoint encodes");
+    let decoded: BottomUpCheckpoint = fvm_ipld_encoding::from_slice(&bytes).unwrap();
+    assert_eq!(decoded, cp);
+});
@@ -0,0 +1,21 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+//! Any checkpoint the contracts can represent goes through its EVM representation unchanged.
+#![no_main]
+
+use ethers::abi::{AbiDecode, AbiEncode};
+use fendermint_testing::arb::ArbBottomUpCheckpoint;
+use ipc_actors_abis::subnet_actor_checkpointing_facet;
+use ipc_api::checkpoint::BottomUpCheckpoint;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|cp: ArbBottomUpCheckpoint| {
+    let cp = cp.0;
+
+    let evm = subnet_actor_checkpointing_facet::BottomUpCheckpoint::try_from(cp.clone())
+        .expect("checkpoint converts to EVM");
+    let decoded = subnet_actor_checkpointing_facet::BottomUpCheckpoint::decode(evm.encode())
+        .expect("checkpoint ABI decodes");
+
+    assert_eq!(BottomUpCheckpoint::try_from(decoded).unwrap(), cp);
+});
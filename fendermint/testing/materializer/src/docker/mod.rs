@@ -316,6 +316,8 @@ impl DockerMaterializer {
             IpcCliConfig {
                 keystore_path: Some("~/.ipc".to_string()),
                 subnets: Default::default(),
+                policy: None,
+                watchtower: None,
             }
         } else {
             IpcCliConfig::from_file(&file_name).context("failed to read ipc-cli config")?
@@ -518,6 +520,11 @@ impl DockerMaterializer {
                     auth_token: None,
                     registry_addr: submit_config.deployment.registry.into(),
                     gateway_addr: submit_config.deployment.gateway.into(),
+                    admin: None,
+                    response_cache: None,
+                    max_concurrent_requests: None,
+                    fallback_provider_http: vec![],
+                    gas: None,
                 }),
             })
         })
@@ -687,6 +694,11 @@ impl Materializer<DockerMaterials> for DockerMaterializer {
                     })
                     .collect(),
                 eam_permission_mode: fendermint_vm_genesis::PermissionMode::Unrestricted,
+                ml_training_policy: Default::default(),
+                ml_limits: None,
+                implicit_gas: None,
+                faucet: Default::default(),
+                contracts: Vec::new(),
                 ipc: Some(IpcParams {
                     gateway: GatewayParams {
                         subnet_id: SubnetID::new_root(chain_id.into()),
@@ -1068,6 +1080,8 @@ mod tests {
         let mut config0 = IpcCliConfig {
             keystore_path: Some("~/.ipc".to_string()),
             subnets: Default::default(),
+            policy: None,
+            watchtower: None,
         };
 
         config0.add_subnet(IpcCliSubnet {
@@ -1078,6 +1092,11 @@ mod tests {
                 auth_token: None,
                 registry_addr: ipc::SUBNETREGISTRY_ACTOR_ADDR,
                 gateway_addr: ipc::GATEWAY_ACTOR_ADDR,
+                admin: None,
+                response_cache: None,
+                max_concurrent_requests: None,
+                fallback_provider_http: vec![],
+                gas: None,
             }),
         });
 
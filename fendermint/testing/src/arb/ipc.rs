@@ -0,0 +1,193 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+//! Arbitrary IPC types limited to the values the Solidity contracts can represent, so that they
+//! can be round-tripped between their FVM and EVM representations.
+use fvm_shared::address::Address;
+use ipc_api::address::IPCAddress;
+use ipc_api::checkpoint::{BottomUpCheckpoint, BottomUpMsgBatch};
+use ipc_api::cross::{IpcEnvelope, IpcMsgKind};
+use ipc_api::subnet_id::SubnetID;
+
+use super::ArbTokenAmount;
+
+/// A subnet ID whose route only has delegated Ethereum addresses, like the subnet actors.
+#[derive(Debug, Clone)]
+pub struct ArbEvmSubnetID(pub SubnetID);
+
+/// An address which can be the raw address of an `IPCAddress`: a Secp256k1 or an Ethereum one.
+#[derive(Debug, Clone)]
+pub struct ArbRawAddress(pub Address);
+
+#[derive(Debug, Clone)]
+pub struct ArbIpcEnvelope(pub IpcEnvelope);
+
+#[derive(Debug, Clone)]
+pub struct ArbBottomUpCheckpoint(pub BottomUpCheckpoint);
+
+#[derive(Debug, Clone)]
+pub struct ArbBottomUpMsgBatch(pub BottomUpMsgBatch);
+
+fn eth_address(subaddr: [u8; 20]) -> Address {
+    Address::new_delegated(10, &subaddr).unwrap()
+}
+
+fn msg_kind(i: u8) -> IpcMsgKind {
+    match i % 3 {
+        0 => IpcMsgKind::Transfer,
+        1 => IpcMsgKind::Call,
+        _ => IpcMsgKind::Receipt,
+    }
+}
+
+impl quickcheck::Arbitrary for ArbEvmSubnetID {
+    fn arbitrary(g: &mut quickcheck::Gen) -> Self {
+        let child_count = usize::arbitrary(g) % 4;
+
+        let children = (0..child_count)
+            .map(|_| eth_address(std::array::from_fn(|_| u8::arbitrary(g))))
+            .collect::<Vec<_>>();
+
+        Self(SubnetID::new(u64::arbitrary(g), children))
+    }
+}
+
+impl quickcheck::Arbitrary for ArbRawAddress {
+    fn arbitrary(g: &mut quickcheck::Gen) -> Self {
+        let addr = if bool::arbitrary(g) {
+            let pubkey: [u8; 65] = std::array::from_fn(|_| u8::arbitrary(g));
+            Address::new_secp256k1(&pubkey).unwrap()
+        } else {
+            eth_address(std::array::from_fn(|_| u8::arbitrary(g)))
+        };
+        Self(addr)
+    }
+}
+
+impl quickcheck::Arbitrary for ArbIpcEnvelope {
+    fn arbitrary(g: &mut quickcheck::Gen) -> Self {
+        let mut addr = || {
+            IPCAddress::new(
+                &ArbEvmSubnetID::arbitrary(g).0,
+                &ArbRawAddress::arbitrary(g).0,
+            )
+            .unwrap()
+        };
+        let (from, to) = (addr(), addr());
+
+        Self(IpcEnvelope {
+            kind: msg_kind(u8::arbitrary(g)),
+            to,
+            value: ArbTokenAmount::arbitrary(g).0,
+            from,
+            message: Vec::arbitrary(g),
+            nonce: u64::arbitrary(g),
+        })
+    }
+}
+
+impl quickcheck::Arbitrary for ArbBottomUpCheckpoint {
+    fn arbitrary(g: &mut quickcheck::Gen) -> Self {
+        let msg_count = usize::arbitrary(g) % 4;
+
+        Self(BottomUpCheckpoint {
+            subnet_id: ArbEvmSubnetID::arbitrary(g).0,
+            block_height: u32::arbitrary(g).into(),
+            block_hash: (0..32).map(|_| u8::arbitrary(g)).collect(),
+            next_configuration_number: u64::arbitrary(g),
+            msgs: (0..msg_count)
+                .map(|_| ArbIpcEnvelope::arbitrary(g).0)
+                .collect(),
+        })
+    }
+}
+
+impl quickcheck::Arbitrary for ArbBottomUpMsgBatch {
+    fn arbitrary(g: &mut quickcheck::Gen) -> Self {
+        let msg_count = usize::arbitrary(g) % 4;
+
+        Self(BottomUpMsgBatch {
+            subnet_id: ArbEvmSubnetID::arbitrary(g).0,
+            block_height: u32::arbitrary(g).into(),
+            msgs: (0..msg_count)
+                .map(|_| ArbIpcEnvelope::arbitrary(g).0)
+                .collect(),
+        })
+    }
+}
+
+impl arbitrary::Arbitrary<'_> for ArbEvmSubnetID {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'_>) -> arbitrary::Result<Self> {
+        let child_count = usize::arbitrary(u)? % 4;
+
+        let children = (0..child_count)
+            .map(|_| Ok(eth_address(<[u8; 20]>::arbitrary(u)?)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self(SubnetID::new(u64::arbitrary(u)?, children)))
+    }
+}
+
+impl arbitrary::Arbitrary<'_> for ArbRawAddress {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'_>) -> arbitrary::Result<Self> {
+        let addr = if bool::arbitrary(u)? {
+            let mut pubkey = [0u8; 65];
+            u.fill_buffer(&mut pubkey)?;
+            Address::new_secp256k1(&pubkey).unwrap()
+        } else {
+            eth_address(<[u8; 20]>::arbitrary(u)?)
+        };
+        Ok(Self(addr))
+    }
+}
+
+impl arbitrary::Arbitrary<'_> for ArbIpcEnvelope {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'_>) -> arbitrary::Result<Self> {
+        let mut addr = || -> arbitrary::Result<IPCAddress> {
+            Ok(IPCAddress::new(
+                &ArbEvmSubnetID::arbitrary(u)?.0,
+                &ArbRawAddress::arbitrary(u)?.0,
+            )
+            .unwrap())
+        };
+        let (from, to) = (addr()?, addr()?);
+
+        Ok(Self(IpcEnvelope {
+            kind: msg_kind(u8::arbitrary(u)?),
+            to,
+            value: ArbTokenAmount::arbitrary(u)?.0,
+            from,
+            message: Vec::arbitrary(u)?,
+            nonce: u64::arbitrary(u)?,
+        }))
+    }
+}
+
+impl arbitrary::Arbitrary<'_> for ArbBottomUpCheckpoint {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'_>) -> arbitrary::Result<Self> {
+        let msg_count = usize::arbitrary(u)? % 4;
+
+        Ok(Self(BottomUpCheckpoint {
+            subnet_id: ArbEvmSubnetID::arbitrary(u)?.0,
+            block_height: u32::arbitrary(u)?.into(),
+            block_hash: <[u8; 32]>::arbitrary(u)?.to_vec(),
+            next_configuration_number: u64::arbitrary(u)?,
+            msgs: (0..msg_count)
+                .map(|_| Ok(ArbIpcEnvelope::arbitrary(u)?.0))
+                .collect::<arbitrary::Result<Vec<_>>>()?,
+        }))
+    }
+}
+
+impl arbitrary::Arbitrary<'_> for ArbBottomUpMsgBatch {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'_>) -> arbitrary::Result<Self> {
+        let msg_count = usize::arbitrary(u)? % 4;
+
+        Ok(Self(BottomUpMsgBatch {
+            subnet_id: ArbEvmSubnetID::arbitrary(u)?.0,
+            block_height: u32::arbitrary(u)?.into(),
+            msgs: (0..msg_count)
+                .map(|_| Ok(ArbIpcEnvelope::arbitrary(u)?.0))
+                .collect::<arbitrary::Result<Vec<_>>>()?,
+        }))
+    }
+}
@@ -3,12 +3,16 @@
 
 mod address;
 mod cid;
+mod ipc;
 mod message;
 mod subnetid;
 mod token;
 
 pub use crate::arb::address::ArbAddress;
 pub use crate::arb::cid::ArbCid;
+pub use crate::arb::ipc::{
+    ArbBottomUpCheckpoint, ArbBottomUpMsgBatch, ArbEvmSubnetID, ArbIpcEnvelope, ArbRawAddress,
+};
 pub use crate::arb::message::ArbMessage;
 pub use crate::arb::subnetid::{ArbSubnetAddress, ArbSubnetID};
 pub use crate::arb::token::ArbTokenAmount;
@@ -0,0 +1,117 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+//! Round-trips of the IPC types between their FVM (CBOR) and EVM (ABI) representations.
+//!
+//! The same types are defined in several facets of the contracts; the encodings of all of them
+//! have to agree, since a checkpoint is hashed and signed in one and submitted to another.
+
+use ethers::abi::{AbiDecode, AbiEncode};
+use fendermint_testing::arb::{ArbBottomUpCheckpoint, ArbBottomUpMsgBatch, ArbIpcEnvelope};
+use ipc_actors_abis::{
+    gateway_getter_facet, gateway_messenger_facet, lib_gateway, subnet_actor_checkpointing_facet,
+    xnet_messaging_facet,
+};
+use ipc_api::checkpoint::{BottomUpCheckpoint, BottomUpMsgBatch};
+use ipc_api::cross::IpcEnvelope;
+use quickcheck_macros::quickcheck;
+
+fn cbor_roundtrip<T>(value: &T) -> anyhow::Result<()>
+where
+    T: serde::Serialize + serde::de::DeserializeOwned + PartialEq + std::fmt::Debug,
+{
+    let bytes = fvm_ipld_encoding::to_vec(value)?;
+    let decoded: T = fvm_ipld_encoding::from_slice(&bytes)?;
+    anyhow::ensure!(&decoded == value, "CBOR round-trip mismatch: {decoded:?}");
+    Ok(())
+}
+
+fn abi_roundtrip<T>(value: &T) -> anyhow::Result<Vec<u8>>
+where
+    T: AbiEncode + AbiDecode + Clone + PartialEq + std::fmt::Debug,
+{
+    let bytes = value.clone().encode();
+    let decoded = T::decode(&bytes)?;
+    anyhow::ensure!(&decoded == value, "ABI round-trip mismatch: {decoded:?}");
+    Ok(bytes)
+}
+
+#[quickcheck]
+fn prop_checkpoint_cbor_roundtrip(cp: ArbBottomUpCheckpoint) -> anyhow::Result<()> {
+    cbor_roundtrip(&cp.0)
+}
+
+#[quickcheck]
+fn prop_checkpoint_evm_roundtrip(cp: ArbBottomUpCheckpoint) -> anyhow::Result<()> {
+    let cp = cp.0;
+
+    let submitted = subnet_actor_checkpointing_facet::BottomUpCheckpoint::try_from(cp.clone())?;
+    let submitted_bytes = abi_roundtrip(&submitted)?;
+    anyhow::ensure!(BottomUpCheckpoint::try_from(submitted)? == cp);
+
+    let queried = gateway_getter_facet::BottomUpCheckpoint::try_from(cp.clone())?;
+    let queried_bytes = abi_roundtrip(&queried)?;
+    anyhow::ensure!(BottomUpCheckpoint::try_from(queried)? == cp);
+
+    anyhow::ensure!(
+        submitted_bytes == queried_bytes,
+        "the facets encode the checkpoint differently"
+    );
+    Ok(())
+}
+
+#[quickcheck]
+fn prop_envelope_cbor_roundtrip(msg: ArbIpcEnvelope) -> anyhow::Result<()> {
+    cbor_roundtrip(&msg.0)
+}
+
+#[quickcheck]
+fn prop_envelope_evm_roundtrip(msg: ArbIpcEnvelope) -> anyhow::Result<()> {
+    let msg = msg.0;
+
+    macro_rules! roundtrip {
+        ($module:ident) => {{
+            let evm = $module::IpcEnvelope::try_from(msg.clone())?;
+            let bytes = abi_roundtrip(&evm)?;
+            anyhow::ensure!(
+                IpcEnvelope::try_from(evm)? == msg,
+                "{} round-trip mismatch",
+                stringify!($module)
+            );
+            bytes
+        }};
+    }
+
+    let encodings = [
+        roundtrip!(gateway_getter_facet),
+        roundtrip!(xnet_messaging_facet),
+        roundtrip!(gateway_messenger_facet),
+        roundtrip!(lib_gateway),
+        roundtrip!(subnet_actor_checkpointing_facet),
+    ];
+    anyhow::ensure!(
+        encodings.windows(2).all(|w| w[0] == w[1]),
+        "the facets encode the envelope differently"
+    );
+    Ok(())
+}
+
+#[quickcheck]
+fn prop_msg_batch_cbor_roundtrip(batch: ArbBottomUpMsgBatch) -> anyhow::Result<()> {
+    cbor_roundtrip(&batch.0)
+}
+
+#[quickcheck]
+fn prop_msg_batch_evm_encoding(batch: ArbBottomUpMsgBatch) -> anyhow::Result<()> {
+    let batch: BottomUpMsgBatch = batch.0;
+    let evm = gateway_getter_facet::BottomUpMsgBatch::try_from(batch.clone())?;
+    abi_roundtrip(&evm)?;
+
+    // The batch only converts towards the contracts, so check its messages came through.
+    let msgs = evm
+        .msgs
+        .into_iter()
+        .map(IpcEnvelope::try_from)
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    anyhow::ensure!(msgs == batch.msgs);
+    Ok(())
+}
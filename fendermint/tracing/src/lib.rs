@@ -1,6 +1,46 @@
 // Copyright 2022-2024 Protocol Labs
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+#[cfg(feature = "otlp")]
+pub mod otlp;
+
+/// Names of the span attributes shared by all IPC components.
+///
+/// Using the same keys everywhere is what allows a trace collector to correlate
+/// the spans of a message as it travels from the provider through the interpreter.
+pub mod fields {
+    /// The ID of the subnet the operation is targeting, e.g. `/r314159/t410f...`.
+    pub const SUBNET_ID: &str = "subnet_id";
+    /// The block height the operation is executed at.
+    pub const HEIGHT: &str = "height";
+    /// The name of the operation, e.g. `fund` or `deliver`.
+    pub const METHOD: &str = "method";
+}
+
+/// Create an `INFO` span for an operation on a subnet, with the attributes in [fields],
+/// followed by any other fields in the syntax of [tracing::span!].
+///
+/// The method doubles as the name of the span, so it has to be a literal.
+///
+/// ### Example
+///
+/// ```ignore
+/// let span = op_span!("deliver", subnet_id, height, to = %msg.to);
+/// async move { todo!() }.instrument(span).await
+/// ```
+#[macro_export]
+macro_rules! op_span {
+    ($method:literal, $subnet_id:expr, $height:expr $(, $($rest:tt)*)?) => {
+        tracing::info_span!(
+            $method,
+            { $crate::fields::SUBNET_ID } = %$subnet_id,
+            { $crate::fields::HEIGHT } = $height,
+            { $crate::fields::METHOD } = $method
+            $(, $($rest)*)?
+        )
+    };
+}
+
 /// Emit an event that conforms to a flat event structure type using the [tracing::event!](https://github.com/tokio-rs/tracing/blob/908cc432a5994f6e17c8f36e13c217dc40085704/tracing/src/macros.rs#L854) macro.
 ///
 /// There should be a [Subnscriber](https://docs.rs/tracing/latest/tracing/trait.Subscriber.html) in the application root to check the
@@ -72,4 +112,10 @@ mod tests {
             bar: "spam",
         });
     }
+
+    #[test]
+    fn test_op_span() {
+        let _span = op_span!("deliver", "/r314159", 10, method_num = 2);
+        let _span = op_span!("begin", "/r314159", 10);
+    }
 }
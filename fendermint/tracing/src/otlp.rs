@@ -0,0 +1,52 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Export `tracing` spans to an OpenTelemetry collector over OTLP/gRPC.
+//!
+//! Both the provider and the interpreter annotate their spans with the attributes
+//! listed in [crate::fields], so that once exported, the spans of a cross-net message
+//! can be stitched together across components by the collector.
+
+use anyhow::Context;
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{runtime, trace, Resource};
+use tracing::Subscriber;
+use tracing_subscriber::registry::LookupSpan;
+
+/// Create a [tracing_subscriber::Layer] that exports spans to the OTLP `endpoint`,
+/// e.g. `http://localhost:4317`, tagged with `service_name`.
+///
+/// Must be called from within a Tokio runtime, because the batch exporter is spawned on it.
+pub fn layer<S>(
+    endpoint: &str,
+    service_name: &str,
+) -> anyhow::Result<tracing_opentelemetry::OpenTelemetryLayer<S, trace::Tracer>>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    let exporter = opentelemetry_otlp::new_exporter()
+        .tonic()
+        .with_endpoint(endpoint);
+
+    let config = trace::config().with_resource(Resource::new(vec![KeyValue::new(
+        "service.name",
+        service_name.to_owned(),
+    )]));
+
+    let tracer = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(exporter)
+        .with_trace_config(config)
+        .install_batch(runtime::Tokio)
+        .context("failed to install OTLP trace pipeline")?;
+
+    Ok(tracing_opentelemetry::layer().with_tracer(tracer))
+}
+
+/// Flush any pending spans and shut down the exporter.
+///
+/// Call this before the process exits, otherwise the last batch can be lost.
+pub fn shutdown() {
+    opentelemetry::global::shutdown_tracer_provider();
+}
@@ -1,4 +1,17 @@
 // Copyright 2022-2024 Protocol Labs
 // SPDX-License-Identifier: Apache-2.0, MIT
+use fvm_shared::METHOD_CONSTRUCTOR;
 
 define_id!(CHAINMETADATA { id: 48 });
+
+/// Chain metadata actor methods available.
+///
+/// The values are the FRC-42 hashes of the method names, hardcoded for the same reason as in [crate::evm::Method].
+#[repr(u64)]
+pub enum Method {
+    Constructor = METHOD_CONSTRUCTOR,
+    PushBlockHash = 2637567345,
+    LookbackLen = 883168614,
+    GetBlockHash = 165725014,
+    GetBlockTimestamp = 943857074,
+}
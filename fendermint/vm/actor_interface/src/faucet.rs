@@ -0,0 +1,15 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+use fvm_shared::METHOD_CONSTRUCTOR;
+
+define_id!(FAUCET { id: 51 });
+
+/// Faucet actor methods available.
+///
+/// The values are the FRC-42 hashes of the method names, hardcoded for the same reason as in [crate::evm::Method].
+#[repr(u64)]
+pub enum Method {
+    Constructor = METHOD_CONSTRUCTOR,
+    Drip = 3003946831,
+    NextDrip = 3342236883,
+}
@@ -58,7 +58,7 @@ impl State {
         accounts: &[Actor],
         // Pre-defined IDs for top-level EVM contracts.
         eth_builtin_ids: &BTreeSet<ActorID>,
-        // Number of dynamically deployed EVM library contracts.
+        // Number of dynamically deployed EVM library and application contracts.
         eth_library_count: u64,
     ) -> anyhow::Result<(Self, AddressMap)> {
         // Returning only the addreses that belong to user accounts.
@@ -109,7 +109,7 @@ impl State {
             set_address(addr, *id).context("cannot set ID of eth contract address")?;
         }
 
-        // Insert dynamic EVM library contracts.
+        // Insert dynamic EVM library and application contracts.
         for _ in 0..eth_library_count {
             let addr = Address::from(builtin_actor_eth_addr(next_id));
             set_address(addr, next_id).context("cannot set ID of eth library address")?;
@@ -50,9 +50,11 @@ pub mod diamond;
 pub mod eam;
 pub mod ethaccount;
 pub mod evm;
+pub mod faucet;
 pub mod init;
 pub mod ipc;
 pub mod machinelearning;
+pub mod mljobs;
 pub mod multisig;
 pub mod placeholder;
 pub mod reward;
@@ -1,4 +1,195 @@
 // Copyright 2022-2024 Protocol Labs
 // SPDX-License-Identifier: Apache-2.0, MIT
+use serde_tuple::{Deserialize_tuple, Serialize_tuple};
+
+pub use ipc_types::{InputMatrix, SparseMatrix, TrainingInput};
 
 define_id!(MACHINELEARNING { id: 49 });
+
+/// Machine learning actor methods available.
+///
+/// The values are the FRC-42 hashes of the method names, hardcoded for the same reason as in [crate::evm::Method].
+#[derive(Clone, Copy)]
+#[repr(u64)]
+pub enum Method {
+    TrainLinearRegression = 2118579085,
+    PredictLinearRegression = 3906916149,
+    TrainLogisticRegression = 1646696726,
+    PredictLogisticRegression = 3762305934,
+    TrainKNNRegression = 2091347328,
+    PredictKNNRegression = 3114267161,
+    Standardize = 3614249143,
+    MinMaxScale = 2288721281,
+    GetScaler = 3754033196,
+    CrossValidate = 1680781437,
+    ListModel = 2972906666,
+    SetPredictionFee = 1204388999,
+    PredictWithModel = 2818986683,
+    GetModel = 1639954660,
+    TrainMultiOutputLinearRegression = 2590907167,
+    PredictMultiOutputLinearRegression = 3973340406,
+    GetModelInfo = 628699008,
+    TrainAR = 1444267226,
+    PredictAR = 1014079718,
+    TrainLinearRegressionGD = 1108613903,
+    PredictLinearRegressionGD = 3026938087,
+    DeleteModel = 814048740,
+    GetStorageUsage = 2710988874,
+    QueryPrediction = 988169403,
+    CreateDataset = 619461622,
+    AppendRows = 706982712,
+    AppendColumns = 841967668,
+    GetDataset = 3721225020,
+    DeleteDataset = 1464526177,
+}
+
+/// Parameters shared by all the `Predict*` methods.
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone)]
+pub struct PredictParams {
+    pub input_matrix: InputMatrix,
+    /// Model returned by the corresponding `Train*` method.
+    pub model: Vec<u8>,
+    /// Scaler applied to the input matrix; it has to be the same as in training.
+    pub scaler: Option<u64>,
+}
+
+/// Parameters shared by all the `Train*` methods.
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone)]
+pub struct TrainParams {
+    /// The input matrix, or the id of a dataset created with `CreateDataset`.
+    pub input_matrix: TrainingInput,
+    /// One label for each row of the input matrix.
+    pub labels: Vec<i64>,
+    /// Scaler applied to the input matrix, returned by the `Standardize` or `MinMaxScale` methods.
+    pub scaler: Option<u64>,
+}
+
+/// Parameters of `TrainMultiOutputLinearRegression`, which trains a model per target column.
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone)]
+pub struct TrainMultiOutputParams {
+    pub input_matrix: TrainingInput,
+    /// One row of targets for each row of the input matrix.
+    pub labels: Vec<Vec<i64>>,
+    pub scaler: Option<u64>,
+}
+
+/// Parameters of `PredictMultiOutputLinearRegression`.
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone)]
+pub struct PredictMultiOutputParams {
+    pub input_matrix: InputMatrix,
+    /// One model for each target column, as returned by training.
+    pub models: Vec<Vec<u8>>,
+    pub scaler: Option<u64>,
+}
+
+/// Parameters of `TrainLinearRegressionGD`, which trains with gradient descent in the actor.
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone)]
+pub struct TrainGDParams {
+    pub input_matrix: TrainingInput,
+    pub labels: Vec<i64>,
+    pub scaler: Option<u64>,
+    pub learning_rate: u64,
+    pub max_iterations: u64,
+    pub tolerance: u64,
+}
+
+/// Linear model trained with gradient descent, as returned by `TrainLinearRegressionGD`.
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Default, PartialEq, Eq)]
+pub struct LinearModel {
+    /// Weight of each feature, scaled by a million.
+    pub weights: Vec<i64>,
+    /// Intercept in the fixed-point units of the labels, scaled by a million.
+    pub bias: i64,
+}
+
+/// Parameters of `PredictLinearRegressionGD`.
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone)]
+pub struct PredictGDParams {
+    pub input_matrix: InputMatrix,
+    pub model: LinearModel,
+    pub scaler: Option<u64>,
+}
+
+/// Parameters of the methods computing a scaler, and of `CreateDataset`.
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone)]
+pub struct ScaleParams {
+    pub input_matrix: InputMatrix,
+}
+
+/// Parameters of the `PredictWithModel` method, which charges the fee of a listed model,
+/// and of `QueryPrediction`, which the node uses to answer queries without charging it.
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone)]
+pub struct PredictWithModelParams {
+    pub model_id: u64,
+    pub input_matrix: InputMatrix,
+}
+
+/// Parameters of `AppendRows` and `AppendColumns`, which extend a stored dataset.
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone)]
+pub struct AppendParams {
+    pub dataset_id: u64,
+    /// The new rows, or the values of the new columns for each row of the dataset.
+    pub input_matrix: InputMatrix,
+}
+
+impl Method {
+    /// Look up the method from its number.
+    pub fn from_method_num(method_num: u64) -> Option<Self> {
+        [
+            Self::TrainLinearRegression,
+            Self::PredictLinearRegression,
+            Self::TrainLogisticRegression,
+            Self::PredictLogisticRegression,
+            Self::TrainKNNRegression,
+            Self::PredictKNNRegression,
+            Self::Standardize,
+            Self::MinMaxScale,
+            Self::GetScaler,
+            Self::CrossValidate,
+            Self::ListModel,
+            Self::SetPredictionFee,
+            Self::PredictWithModel,
+            Self::GetModel,
+            Self::TrainMultiOutputLinearRegression,
+            Self::PredictMultiOutputLinearRegression,
+            Self::GetModelInfo,
+            Self::TrainAR,
+            Self::PredictAR,
+            Self::TrainLinearRegressionGD,
+            Self::PredictLinearRegressionGD,
+            Self::DeleteModel,
+            Self::GetStorageUsage,
+            Self::QueryPrediction,
+            Self::CreateDataset,
+            Self::AppendRows,
+            Self::AppendColumns,
+            Self::GetDataset,
+            Self::DeleteDataset,
+        ]
+        .into_iter()
+        .find(|m| *m as u64 == method_num)
+    }
+
+    /// Whether this is one of the methods which train a new model.
+    pub fn is_train(&self) -> bool {
+        matches!(
+            self,
+            Self::TrainLinearRegression | Self::TrainLogisticRegression | Self::TrainKNNRegression
+        )
+    }
+
+    /// Whether this is one of the methods which predict using a trained model.
+    pub fn is_predict(&self) -> bool {
+        matches!(
+            self,
+            Self::PredictLinearRegression
+                | Self::PredictLogisticRegression
+                | Self::PredictKNNRegression
+        )
+    }
+
+    /// Whether this is one of the methods which compute a scaler from an input matrix.
+    pub fn is_scale(&self) -> bool {
+        matches!(self, Self::Standardize | Self::MinMaxScale)
+    }
+}
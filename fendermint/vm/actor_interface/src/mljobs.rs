@@ -0,0 +1,17 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+use fvm_shared::METHOD_CONSTRUCTOR;
+
+define_id!(MLJOBS { id: 50 });
+
+/// Machine learning jobs actor methods available.
+///
+/// The values are the FRC-42 hashes of the method names, hardcoded for the same reason as in [crate::evm::Method].
+#[repr(u64)]
+pub enum Method {
+    Constructor = METHOD_CONSTRUCTOR,
+    PostResult = 3601150449,
+    Challenge = 4117509095,
+    FinalizeExpired = 4183848665,
+    GetJob = 340858087,
+}
@@ -203,3 +203,33 @@ impl<'de> DeserializeAs<'de, TokenAmount> for IsHumanReadable {
         }
     }
 }
+
+impl SerializeAs<Vec<u8>> for IsHumanReadable {
+    /// Serialize bytes as a 0x prefixed hexadecimal string.
+    fn serialize_as<S>(bytes: &Vec<u8>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if serializer.is_human_readable() {
+            format!("0x{}", hex::encode(bytes)).serialize(serializer)
+        } else {
+            bytes.serialize(serializer)
+        }
+    }
+}
+
+impl<'de> DeserializeAs<'de, Vec<u8>> for IsHumanReadable {
+    /// Deserialize bytes from hexadecimal format, with or without the 0x prefix.
+    fn deserialize_as<D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        if deserializer.is_human_readable() {
+            let s = String::deserialize(deserializer)?;
+            hex::decode(s.strip_prefix("0x").unwrap_or(&s))
+                .map_err(|e| D::Error::custom(format!("error deserializing bytes: {}", e)))
+        } else {
+            Vec::<u8>::deserialize(deserializer)
+        }
+    }
+}
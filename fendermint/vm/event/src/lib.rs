@@ -36,3 +36,43 @@ pub struct ParentFinalityMissingQuorum<'a> {
     pub block_height: BlockHeight,
     pub block_hash: BlockHashHex<'a>,
 }
+
+/// The cron message has been executed at the beginning of a block.
+#[derive(Debug, Default)]
+pub struct CronExecuted {
+    pub block_height: BlockHeight,
+    pub gas_limit: u64,
+    pub gas_used: u64,
+}
+
+/// An implicit message which is not critical for the chain failed and was skipped.
+#[derive(Debug, Default)]
+pub struct ImplicitMessageFailed<'a> {
+    pub block_height: BlockHeight,
+    pub message: &'a str,
+    pub reason: &'a str,
+}
+
+/// Gas used by cron and the messages of a block, by the actor they were sent to.
+#[derive(Debug, Default)]
+pub struct BlockGasUsed<'a> {
+    pub block_height: BlockHeight,
+    pub total: u64,
+    pub cron: u64,
+    pub gateway: u64,
+    pub machinelearning: u64,
+    pub other: u64,
+    /// Comma separated `address=gas` pairs of every actor, the most expensive first.
+    pub by_actor: &'a str,
+}
+
+/// The transactions of a block have been executed and the block ended.
+#[derive(Debug, Default)]
+pub struct BlockExecuted {
+    pub block_height: BlockHeight,
+    pub txs: u64,
+    pub failed_txs: u64,
+    pub gas_used: u64,
+    pub events: u64,
+    pub checkpoint_created: bool,
+}
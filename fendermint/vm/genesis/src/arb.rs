@@ -109,6 +109,12 @@ impl Arbitrary for Genesis {
             validators: (0..nv).map(|_| Arbitrary::arbitrary(g)).collect(),
             accounts: (0..na).map(|_| Arbitrary::arbitrary(g)).collect(),
             eam_permission_mode: PermissionMode::Unrestricted,
+            ml_training_policy: Default::default(),
+            ml_limits: None,
+            implicit_gas: None,
+            faucet: Default::default(),
+            // Arbitrary bytecode would fail to deploy.
+            contracts: Vec::new(),
             ipc: if bool::arbitrary(g) {
                 Some(ipc::IpcParams::arbitrary(g))
             } else {
@@ -3,6 +3,8 @@
 //! A Genesis data structure similar to [genesis.Template](https://github.com/filecoin-project/lotus/blob/v1.20.4/genesis/types.go)
 //! in Lotus, which is used to [initialize](https://github.com/filecoin-project/lotus/blob/v1.20.4/chain/gen/genesis/genesis.go) the state tree.
 
+use std::time::Duration;
+
 use anyhow::anyhow;
 use fvm_shared::bigint::{BigInt, Integer};
 use serde::{Deserialize, Serialize};
@@ -10,7 +12,8 @@ use serde_with::serde_as;
 
 use fendermint_actor_eam::PermissionModeParams;
 use fvm_shared::version::NetworkVersion;
-use fvm_shared::{address::Address, econ::TokenAmount};
+use fvm_shared::BLOCK_GAS_LIMIT;
+use fvm_shared::{address::Address, clock::ChainEpoch, econ::TokenAmount};
 
 use fendermint_crypto::{normalize_public_key, PublicKey};
 use fendermint_vm_core::Timestamp;
@@ -44,6 +47,21 @@ pub struct Genesis {
     pub accounts: Vec<Actor>,
     /// The custom eam permission mode that controls who can deploy contracts
     pub eam_permission_mode: PermissionMode,
+    /// Controls who can train models with the machine learning actor.
+    #[serde(default)]
+    pub ml_training_policy: MlTrainingPolicy,
+    /// Limits on the inputs of the machine learning actor enforced by consensus, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ml_limits: Option<MlLimits>,
+    /// Gas limits of the implicit messages sent by the system actor; the defaults apply if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub implicit_gas: Option<ImplicitGasLimits>,
+    /// Funds and limits of the faucet distributing test funds.
+    #[serde(default)]
+    pub faucet: Faucet,
+    /// Application contracts deployed at genesis, in order, after the IPC contracts.
+    #[serde(default)]
+    pub contracts: Vec<Contract>,
     /// IPC related configuration, if enabled.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ipc: Option<ipc::IpcParams>,
@@ -58,6 +76,176 @@ pub enum PermissionMode {
     AllowList { addresses: Vec<SignerAddr> },
 }
 
+/// Restrictions on training models with the machine learning actor, which
+/// can otherwise be used by anyone to bloat the state.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MlTrainingPolicy {
+    /// Who is allowed to call the training methods.
+    pub permission_mode: PermissionMode,
+    /// The minimum value which has to be sent with each training call.
+    #[serde_as(as = "IsHumanReadable")]
+    pub training_fee: TokenAmount,
+    /// Number of epochs during which the result of a job executed off-chain can be challenged.
+    #[serde(default = "default_job_challenge_window")]
+    pub job_challenge_window: ChainEpoch,
+    /// The minimum deposit the proposer of a job result has to lock up until it's finalized.
+    ///
+    /// Must be positive, otherwise wrong results could be posted at no cost.
+    #[serde_as(as = "IsHumanReadable")]
+    #[serde(default = "default_job_deposit")]
+    pub job_deposit: TokenAmount,
+}
+
+fn default_job_challenge_window() -> ChainEpoch {
+    100
+}
+
+fn default_job_deposit() -> TokenAmount {
+    TokenAmount::from_whole(1)
+}
+
+impl Default for MlTrainingPolicy {
+    fn default() -> Self {
+        Self {
+            permission_mode: PermissionMode::Unrestricted,
+            training_fee: TokenAmount::default(),
+            job_challenge_window: default_job_challenge_window(),
+            job_deposit: default_job_deposit(),
+        }
+    }
+}
+
+/// Limits on the inputs of the machine learning actor which every validator checks before
+/// executing a message, so that a single oversized training message can't stall the chain.
+///
+/// The actor is given the row and column limits as well and checks them on every call, so
+/// they also apply to calls made by contracts or by the jobs actor; the check before execution
+/// only rejects the messages sent to the actor directly without running them. The datasets
+/// stored in the actor are held to the same rows and columns, and to `max_params_bytes`.
+///
+/// Unlike the limits of the mempool, which are up to each node, these are part of the state:
+/// they are set at genesis and can only be changed by an upgrade.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MlLimits {
+    /// Maximum number of rows of an input matrix.
+    pub max_rows: u64,
+    /// Maximum number of columns of an input matrix.
+    pub max_cols: u64,
+    /// Maximum size of the raw parameters of a call to the actor, in bytes.
+    pub max_params_bytes: u64,
+    /// Maximum sum of the gas limits of the messages sent to the machine learning actor in a
+    /// block, if any. Proposals over it are rejected; calls made through the mljobs actor or
+    /// EVM contracts aren't counted.
+    ///
+    /// Left out when unset so that the hash of the state of existing chains stays the same.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_block_gas: Option<u64>,
+}
+
+impl Default for MlLimits {
+    fn default() -> Self {
+        Self {
+            max_rows: 10_000,
+            max_cols: 1_000,
+            max_params_bytes: 4 * 1024 * 1024,
+            max_block_gas: Some(5_000_000_000),
+        }
+    }
+}
+
+/// Gas limits of the implicit messages the system actor sends in every block.
+///
+/// Changing these changes the outcome of blocks where the limits are exhausted, so they are
+/// part of the state like [MlLimits]: set at genesis and only changed by an upgrade.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImplicitGasLimits {
+    /// Gas limit of the cron message for blocks at most `cron_reference_interval` apart.
+    pub cron: u64,
+    /// The block interval the cron gas limit is meant for, in seconds.
+    ///
+    /// Lotus sizes cron for 30 second epochs; our blocks are usually much more frequent but
+    /// can also be further apart if the chain stalls, in which case cron has more catching
+    /// up to do. If set, the cron limit is multiplied by the number of reference intervals
+    /// elapsed since the parent block, up to `cron_max_multiplier`.
+    #[serde(default)]
+    pub cron_reference_interval: Option<u64>,
+    /// Upper bound on the multiplier applied to the cron limit.
+    pub cron_max_multiplier: u64,
+    /// Gas limit of pushing the block hash to the chainmetadata actor.
+    pub chain_metadata: u64,
+    /// Gas limit of the built-in machine learning messages.
+    pub machine_learning: u64,
+    /// Gas limit of finalizing the machine learning jobs whose challenge window has passed.
+    pub ml_jobs: u64,
+}
+
+impl Default for ImplicitGasLimits {
+    fn default() -> Self {
+        // Arbitrarily large gas limit (matching how Forest does it, which matches Lotus).
+        let gas_limit = BLOCK_GAS_LIMIT * 10000;
+        Self {
+            cron: gas_limit,
+            cron_reference_interval: None,
+            cron_max_multiplier: 1,
+            chain_metadata: gas_limit,
+            machine_learning: gas_limit,
+            ml_jobs: gas_limit,
+        }
+    }
+}
+
+impl ImplicitGasLimits {
+    /// Gas limit of the cron message, given the time elapsed since the parent block, if known.
+    pub fn cron_gas_limit(&self, block_interval: Option<Duration>) -> u64 {
+        let multiplier = match (self.cron_reference_interval, block_interval) {
+            (Some(reference), Some(interval)) if reference > 0 => {
+                let n = interval.as_millis().div_ceil(u128::from(reference) * 1000);
+                (n as u64).clamp(1, self.cron_max_multiplier.max(1))
+            }
+            _ => 1,
+        };
+        self.cron.saturating_mul(multiplier)
+    }
+}
+
+/// The faucet actor distributes test funds from its balance, to every address at most once
+/// per cooldown. It's empty unless given a balance, which is meant for devnets only.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Faucet {
+    /// The funds of the faucet, taken on top of the balances of the accounts.
+    #[serde_as(as = "IsHumanReadable")]
+    pub balance: TokenAmount,
+    /// The amount sent with every drip.
+    #[serde_as(as = "IsHumanReadable")]
+    pub drip_amount: TokenAmount,
+    /// Number of epochs an address has to wait between two drips.
+    pub cooldown: ChainEpoch,
+}
+
+impl Default for Faucet {
+    fn default() -> Self {
+        Self {
+            balance: TokenAmount::default(),
+            drip_amount: TokenAmount::from_whole(10),
+            cooldown: 1000,
+        }
+    }
+}
+
+/// An EVM contract deployed at genesis, so the subnet comes up with its application stack
+/// already installed. It gets the next dynamic actor ID, and the masked ID address of that.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Contract {
+    /// The name the contract is logged with.
+    pub name: String,
+    /// The bytecode of the contract followed by its ABI encoded constructor arguments.
+    #[serde_as(as = "IsHumanReadable")]
+    pub init_code: Vec<u8>,
+}
+
 /// Wrapper around [`Address`] to provide human readable serialization in JSON format.
 ///
 /// An alternative would be the `serde_with` crate.
@@ -248,11 +436,13 @@ pub mod ipc {
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use fvm_shared::{bigint::BigInt, econ::TokenAmount};
     use num_traits::Num;
     use quickcheck_macros::quickcheck;
 
-    use crate::{Collateral, Genesis};
+    use crate::{Collateral, Contract, Genesis, ImplicitGasLimits};
 
     #[quickcheck]
     fn genesis_json(value0: Genesis) {
@@ -272,6 +462,16 @@ mod tests {
         assert_eq!(value1, value0)
     }
 
+    #[test]
+    fn contract_json() {
+        let contract: Contract =
+            serde_json::from_str(r#"{"name":"Empty","init_code":"0x60006000f3"}"#).unwrap();
+        assert_eq!(contract.init_code, hex::decode("60006000f3").unwrap());
+
+        let json = serde_json::to_string(&contract).unwrap();
+        assert!(json.contains(r#""init_code":"0x60006000f3""#));
+    }
+
     #[test]
     fn tokens_to_power() {
         // Collateral given in atto (18 digits after the decimal)
@@ -312,4 +512,25 @@ mod tests {
             assert_eq!(Collateral::atto_per_power(scale), BigInt::from(atto))
         }
     }
+
+    #[test]
+    fn cron_gas_limit_scales_with_block_interval() {
+        let limits = ImplicitGasLimits {
+            cron: 100,
+            cron_reference_interval: Some(30),
+            cron_max_multiplier: 4,
+            ..Default::default()
+        };
+        assert_eq!(limits.cron_gas_limit(None), 100);
+        assert_eq!(limits.cron_gas_limit(Some(Duration::ZERO)), 100);
+        assert_eq!(limits.cron_gas_limit(Some(Duration::from_secs(1))), 100);
+        assert_eq!(limits.cron_gas_limit(Some(Duration::from_secs(31))), 200);
+        assert_eq!(limits.cron_gas_limit(Some(Duration::from_secs(3600))), 400);
+
+        let limits = ImplicitGasLimits {
+            cron: 100,
+            ..Default::default()
+        };
+        assert_eq!(limits.cron_gas_limit(Some(Duration::from_secs(3600))), 100);
+    }
 }
@@ -0,0 +1,413 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Benchmarks of the machine learning actor methods, executed in blocks by the [Simulator].
+//!
+//! Every method is run on datasets of increasing size. Criterion measures how long the blocks
+//! take to execute, while the gas used by each message is written to a JSON file, which can be
+//! compared with the one of a previous run to catch regressions in the fixed-point math.
+//!
+//! Run with `make bench-ml` in the `fendermint` directory, which points at the actor bundles.
+//! The JSON file is written to `ML_BENCH_GAS_OUTPUT`; if `ML_BENCH_GAS_BASELINE` points at the
+//! output of an earlier run, the benchmark fails if any method uses more than
+//! `ML_BENCH_GAS_TOLERANCE` percent (10 by default) more gas than it did then.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Context};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use fendermint_actor_machinelearning::{Algorithm, CrossValidateParams};
+use fendermint_vm_actor_interface::machinelearning::{
+    Method, PredictParams, ScaleParams, TrainParams, MACHINELEARNING_ACTOR_ADDR,
+};
+use fendermint_vm_core::Timestamp;
+use fendermint_vm_genesis::{
+    Account, Actor, ActorMeta, Genesis, MlTrainingPolicy, PermissionMode, SignerAddr,
+};
+use fendermint_vm_interpreter::fvm::bundle::{
+    bundle_path, contracts_path, custom_actors_bundle_path,
+};
+use fendermint_vm_interpreter::fvm::store::memory::MemoryBlockstore;
+use fendermint_vm_interpreter::fvm::upgrades::UpgradeScheduler;
+use fendermint_vm_interpreter::fvm::{FvmMessage, FvmMessageInterpreter};
+use fendermint_vm_interpreter::simulator::Simulator;
+use fvm_ipld_encoding::RawBytes;
+use fvm_shared::address::Address;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::version::NetworkVersion;
+use fvm_shared::BLOCK_GAS_LIMIT;
+use serde::{Deserialize, Serialize};
+use tendermint_rpc::{MockClient, MockRequestMethodMatcher};
+
+/// Number of rows of the datasets each method is benchmarked with.
+const DATASET_ROWS: [usize; 3] = [10, 100, 1000];
+/// Number of features of every row.
+const DATASET_COLS: usize = 4;
+/// Number of folds of the cross-validation.
+const FOLDS: u64 = 5;
+/// Default regression tolerance, in percent of the baseline gas.
+const DEFAULT_GAS_TOLERANCE: u64 = 10;
+
+type BenchSimulator =
+    Simulator<FvmMessageInterpreter<MemoryBlockstore, MockClient<MockRequestMethodMatcher>>>;
+
+/// Gas used by one method on a dataset.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct GasRecord {
+    method: String,
+    rows: usize,
+    cols: usize,
+    gas_used: u64,
+}
+
+/// A simulator with a single funded account which sends all the messages.
+struct Bench {
+    rt: tokio::runtime::Runtime,
+    sim: BenchSimulator,
+    sender: Address,
+    sequence: u64,
+}
+
+impl Bench {
+    fn new() -> anyhow::Result<Self> {
+        let rt = tokio::runtime::Runtime::new()?;
+        let sender = Address::new_secp256k1(&[4u8; 65])?;
+
+        let (client, _) = MockClient::new(MockRequestMethodMatcher::default());
+        let interpreter = FvmMessageInterpreter::new(
+            client,
+            None,
+            contracts_path(),
+            1.05,
+            1.05,
+            false,
+            UpgradeScheduler::new(),
+        );
+        let bundle = std::fs::read(bundle_path()).context("failed to read bundle")?;
+        let custom_actors_bundle =
+            std::fs::read(custom_actors_bundle_path()).context("failed to read custom bundle")?;
+
+        let sim = rt.block_on(Simulator::new(
+            interpreter,
+            genesis(sender),
+            &bundle,
+            &custom_actors_bundle,
+        ))?;
+
+        Ok(Self {
+            rt,
+            sim,
+            sender,
+            sequence: 0,
+        })
+    }
+
+    /// Execute the method in a block of its own, returning how long the block took, the gas
+    /// used by the message and its return value.
+    fn call<P: Serialize>(
+        &mut self,
+        method: Method,
+        params: &P,
+    ) -> anyhow::Result<(Duration, u64, RawBytes)> {
+        let msg = FvmMessage {
+            version: 0,
+            from: self.sender,
+            to: MACHINELEARNING_ACTOR_ADDR,
+            sequence: self.sequence,
+            value: TokenAmount::from_atto(0),
+            method_num: method as u64,
+            params: RawBytes::serialize(params)?,
+            gas_limit: BLOCK_GAS_LIMIT,
+            gas_fee_cap: TokenAmount::from_atto(100),
+            gas_premium: TokenAmount::from_atto(0),
+        };
+
+        let started = Instant::now();
+        let block = self.rt.block_on(self.sim.produce_block(vec![msg]))?;
+        let elapsed = started.elapsed();
+
+        // The sequence is used up whether the call succeeded or not.
+        self.sequence += 1;
+
+        let receipt = &block.receipts[0].apply_ret.msg_receipt;
+        if !receipt.exit_code.is_success() {
+            return Err(anyhow!(
+                "method {} failed with {}",
+                method as u64,
+                receipt.exit_code
+            ));
+        }
+
+        Ok((elapsed, receipt.gas_used, receipt.return_data.clone()))
+    }
+}
+
+fn genesis(sender: Address) -> Genesis {
+    Genesis {
+        chain_name: "ml-bench".to_string(),
+        timestamp: Timestamp(0),
+        network_version: NetworkVersion::V21,
+        base_fee: TokenAmount::from_atto(100),
+        power_scale: 3,
+        validators: Vec::new(),
+        accounts: vec![Actor {
+            meta: ActorMeta::Account(Account {
+                owner: SignerAddr(sender),
+            }),
+            balance: TokenAmount::from_whole(1_000_000),
+        }],
+        eam_permission_mode: PermissionMode::Unrestricted,
+        ml_training_policy: MlTrainingPolicy::default(),
+        ml_limits: None,
+        implicit_gas: None,
+        faucet: Default::default(),
+        contracts: Vec::new(),
+        ipc: None,
+    }
+}
+
+/// A deterministic dataset of fixed-point features between -10 and 10, with labels which are a
+/// noisy linear combination of them, or the sign of it for classifiers.
+fn dataset(rows: usize, classes: bool) -> (Vec<Vec<i64>>, Vec<i64>) {
+    let features: Vec<Vec<i64>> = (0..rows)
+        .map(|i| {
+            (0..DATASET_COLS)
+                .map(|j| ((i * 7919 + j * 104729) % 2001) as i64 - 1000)
+                .collect()
+        })
+        .collect();
+
+    let labels = features
+        .iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let y = row
+                .iter()
+                .enumerate()
+                .map(|(j, x)| x * (j as i64 + 1))
+                .sum::<i64>()
+                / DATASET_COLS as i64
+                + (i % 11) as i64
+                - 5;
+            if classes {
+                i64::from(y > 0)
+            } else {
+                y
+            }
+        })
+        .collect();
+
+    (features, labels)
+}
+
+fn train_method(algorithm: Algorithm) -> (&'static str, Method) {
+    match algorithm {
+        Algorithm::LinearRegression => ("TrainLinearRegression", Method::TrainLinearRegression),
+        Algorithm::LogisticRegression => {
+            ("TrainLogisticRegression", Method::TrainLogisticRegression)
+        }
+        Algorithm::KNNRegression => ("TrainKNNRegression", Method::TrainKNNRegression),
+    }
+}
+
+fn predict_method(algorithm: Algorithm) -> (&'static str, Method) {
+    match algorithm {
+        Algorithm::LinearRegression => ("PredictLinearRegression", Method::PredictLinearRegression),
+        Algorithm::LogisticRegression => (
+            "PredictLogisticRegression",
+            Method::PredictLogisticRegression,
+        ),
+        Algorithm::KNNRegression => ("PredictKNNRegression", Method::PredictKNNRegression),
+    }
+}
+
+/// Benchmark a method on a dataset size, recording the gas it used on the first run.
+fn bench_call<P: Serialize>(
+    c: &mut Criterion,
+    bench: &mut Bench,
+    records: &mut Vec<GasRecord>,
+    name: &str,
+    rows: usize,
+    method: Method,
+    params: &P,
+) -> RawBytes {
+    let (_, gas_used, ret) = bench
+        .call(method, params)
+        .unwrap_or_else(|e| panic!("{name} on {rows} rows: {e:#}"));
+
+    records.push(GasRecord {
+        method: name.to_string(),
+        rows,
+        cols: DATASET_COLS,
+        gas_used,
+    });
+
+    c.benchmark_group(name)
+        .bench_with_input(BenchmarkId::from_parameter(rows), &rows, |b, _| {
+            b.iter_custom(|iters| {
+                (0..iters)
+                    .map(|_| bench.call(method, params).expect("call failed").0)
+                    .sum()
+            })
+        });
+
+    ret
+}
+
+fn bench_ml_actor(c: &mut Criterion) {
+    let mut bench = Bench::new().expect("failed to create simulator");
+    let mut records = Vec::new();
+
+    for rows in DATASET_ROWS {
+        for algorithm in [
+            Algorithm::LinearRegression,
+            Algorithm::LogisticRegression,
+            Algorithm::KNNRegression,
+        ] {
+            let (input_matrix, labels) = dataset(rows, algorithm.is_classifier());
+            let (train_name, train) = train_method(algorithm);
+            let (predict_name, predict) = predict_method(algorithm);
+
+            let ret = bench_call(
+                c,
+                &mut bench,
+                &mut records,
+                train_name,
+                rows,
+                train,
+                &TrainParams {
+                    input_matrix: input_matrix.clone().into(),
+                    labels: labels.clone(),
+                    scaler: None,
+                },
+            );
+            let model: Vec<u8> = ret.deserialize().expect("failed to decode model");
+
+            bench_call(
+                c,
+                &mut bench,
+                &mut records,
+                predict_name,
+                rows,
+                predict,
+                &PredictParams {
+                    input_matrix: input_matrix.clone().into(),
+                    model,
+                    scaler: None,
+                },
+            );
+
+            bench_call(
+                c,
+                &mut bench,
+                &mut records,
+                &format!("CrossValidate{algorithm:?}"),
+                rows,
+                Method::CrossValidate,
+                &CrossValidateParams {
+                    algorithm,
+                    input_matrix: input_matrix.into(),
+                    labels,
+                    folds: FOLDS,
+                    scaler: None,
+                    seed: Some(42),
+                    random_seed: false,
+                },
+            );
+        }
+
+        let (input_matrix, _) = dataset(rows, false);
+        for (name, method) in [
+            ("Standardize", Method::Standardize),
+            ("MinMaxScale", Method::MinMaxScale),
+        ] {
+            bench_call(
+                c,
+                &mut bench,
+                &mut records,
+                name,
+                rows,
+                method,
+                &ScaleParams {
+                    input_matrix: input_matrix.clone().into(),
+                },
+            );
+        }
+    }
+
+    write_gas_report(&records).expect("failed to write gas report");
+    check_gas_baseline(&records).expect("gas regression");
+}
+
+fn output_path() -> PathBuf {
+    std::env::var("ML_BENCH_GAS_OUTPUT")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| {
+            PathBuf::from(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/../../../target/ml-bench/gas.json"
+            ))
+        })
+}
+
+fn write_gas_report(records: &[GasRecord]) -> anyhow::Result<()> {
+    let path = output_path();
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&path, serde_json::to_string_pretty(records)?)
+        .with_context(|| format!("failed to write {}", path.display()))?;
+    eprintln!("gas report written to {}", path.display());
+    Ok(())
+}
+
+/// Compare the gas used with the baseline, if one is given.
+fn check_gas_baseline(records: &[GasRecord]) -> anyhow::Result<()> {
+    let Ok(path) = std::env::var("ML_BENCH_GAS_BASELINE") else {
+        return Ok(());
+    };
+    let tolerance = match std::env::var("ML_BENCH_GAS_TOLERANCE") {
+        Ok(t) => t.parse().context("invalid ML_BENCH_GAS_TOLERANCE")?,
+        Err(_) => DEFAULT_GAS_TOLERANCE,
+    };
+
+    let baseline: Vec<GasRecord> = serde_json::from_str(
+        &std::fs::read_to_string(&path).with_context(|| format!("failed to read {path}"))?,
+    )?;
+    let baseline: BTreeMap<_, _> = baseline
+        .into_iter()
+        .map(|r| ((r.method, r.rows), r.gas_used))
+        .collect();
+
+    let regressions = records
+        .iter()
+        .filter_map(|r| {
+            let before = *baseline.get(&(r.method.clone(), r.rows))?;
+            let limit = before.saturating_add(before.saturating_mul(tolerance) / 100);
+            (r.gas_used > limit).then(|| {
+                format!(
+                    "{} on {} rows: {} -> {} gas",
+                    r.method, r.rows, before, r.gas_used
+                )
+            })
+        })
+        .collect::<Vec<_>>();
+
+    if regressions.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "gas usage grew by more than {tolerance}%:\n{}",
+            regressions.join("\n")
+        ))
+    }
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets = bench_ml_actor
+}
+criterion_main!(benches);
@@ -3,6 +3,7 @@
 
 use fendermint_testing::arb::{ArbCid, ArbTokenAmount};
 use fendermint_vm_core::{chainid, Timestamp};
+use fendermint_vm_genesis::{ImplicitGasLimits, MlLimits};
 use fvm_shared::version::NetworkVersion;
 use quickcheck::{Arbitrary, Gen};
 
@@ -21,6 +22,28 @@ impl Arbitrary for FvmStateParams {
                 .into(),
             power_scale: *g.choose(&[-1, 0, 3]).unwrap(),
             app_version: *g.choose(&[0, 1, 2]).unwrap(),
+            ml_limits: if bool::arbitrary(g) {
+                Some(MlLimits {
+                    max_rows: u64::arbitrary(g),
+                    max_cols: u64::arbitrary(g),
+                    max_params_bytes: u64::arbitrary(g),
+                    max_block_gas: Option::<u64>::arbitrary(g),
+                })
+            } else {
+                None
+            },
+            implicit_gas: if bool::arbitrary(g) {
+                Some(ImplicitGasLimits {
+                    cron: u64::arbitrary(g),
+                    cron_reference_interval: Option::<u64>::arbitrary(g),
+                    cron_max_multiplier: u64::arbitrary(g),
+                    chain_metadata: u64::arbitrary(g),
+                    machine_learning: u64::arbitrary(g),
+                    ml_jobs: u64::arbitrary(g),
+                })
+            } else {
+                None
+            },
         }
     }
 }
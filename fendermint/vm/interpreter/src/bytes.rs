@@ -1,8 +1,11 @@
 // Copyright 2022-2024 Protocol Labs
 // SPDX-License-Identifier: Apache-2.0, MIT
+use std::collections::HashSet;
+
 use anyhow::{anyhow, Context};
 use async_trait::async_trait;
 use cid::Cid;
+use fendermint_vm_actor_interface::machinelearning::MACHINELEARNING_ACTOR_ADDR;
 use fendermint_vm_genesis::Genesis;
 use fendermint_vm_message::chain::ChainMessage;
 use fvm_ipld_encoding::Error as IpldError;
@@ -61,18 +64,74 @@ impl<I> BytesMessageInterpreter<I> {
     }
 }
 
+/// Leave out the machine learning messages which would take the block over the budget;
+/// they stay in the mempool and can be proposed in a later block. The messages of their
+/// senders which come after them are left out as well, so as not to leave a gap in the nonces.
+fn limit_ml_gas(msgs: Vec<Vec<u8>>, max_ml_gas: u64) -> Vec<Vec<u8>> {
+    let mut ml_gas = 0u64;
+    let mut deferred = HashSet::new();
+    let msgs = msgs
+        .into_iter()
+        .filter(|msg| {
+            let Ok(ChainMessage::Signed(msg)) = fvm_ipld_encoding::from_slice::<ChainMessage>(msg)
+            else {
+                return true;
+            };
+            let msg = msg.message;
+            if deferred.contains(&msg.from) {
+                return false;
+            }
+            if msg.to != MACHINELEARNING_ACTOR_ADDR {
+                return true;
+            }
+            if ml_gas.saturating_add(msg.gas_limit) > max_ml_gas {
+                deferred.insert(msg.from);
+                return false;
+            }
+            ml_gas += msg.gas_limit;
+            true
+        })
+        .collect();
+
+    if !deferred.is_empty() {
+        tracing::debug!(
+            max_ml_gas,
+            ml_gas,
+            senders = deferred.len(),
+            "deferring machine learning messages over the block budget"
+        );
+    }
+    msgs
+}
+
+/// The gas limit of a message sent to the machine learning actor, or `None` for other messages.
+fn ml_gas_limit(msg: &[u8]) -> Option<u64> {
+    match fvm_ipld_encoding::from_slice::<ChainMessage>(msg) {
+        Ok(ChainMessage::Signed(msg)) if msg.message.to == MACHINELEARNING_ACTOR_ADDR => {
+            Some(msg.message.gas_limit)
+        }
+        _ => None,
+    }
+}
+
 #[async_trait]
 impl<I> ProposalInterpreter for BytesMessageInterpreter<I>
 where
     I: ProposalInterpreter<Message = ChainMessage>,
 {
-    type State = I::State;
+    /// The state of the inner interpreter, along with the maximum sum of the gas limits of the
+    /// messages sent to the machine learning actor in a block, if the chain has one.
+    ///
+    /// Only messages sent directly to the machine learning actor are counted; training done
+    /// through the mljobs actor or by EVM contracts calling the actor isn't, because it can't
+    /// be told apart without executing the message, so those are only bounded by the block gas.
+    type State = (Option<u64>, I::State);
     type Message = Vec<u8>;
 
     /// Parse messages in the mempool and pass them into the inner `ChainMessage` interpreter.
     async fn prepare(
         &self,
-        state: Self::State,
+        (max_ml_gas, state): Self::State,
         msgs: Vec<Self::Message>,
     ) -> anyhow::Result<Vec<Self::Message>> {
         // Collect the messages to pass to the inner interpreter.
@@ -106,12 +165,17 @@ where
             })
             .collect::<anyhow::Result<Vec<Self::Message>>>()?;
 
-        let mut all_msgs = match self.prepare_mode {
+        let all_msgs = match self.prepare_mode {
             ProposalPrepareMode::PassThrough => chain_msgs,
             ProposalPrepareMode::AppendOnly => [msgs, chain_msgs].concat(),
             ProposalPrepareMode::PrependOnly => [chain_msgs, msgs].concat(),
         };
 
+        let mut all_msgs = match max_ml_gas {
+            Some(max_ml_gas) => limit_ml_gas(all_msgs, max_ml_gas),
+            None => all_msgs,
+        };
+
         if all_msgs.len() > self.max_msgs {
             tracing::warn!(
                 max_msgs = self.max_msgs,
@@ -125,7 +189,11 @@ where
     }
 
     /// Parse messages in the block, reject if unknown format. Pass the rest to the inner `ChainMessage` interpreter.
-    async fn process(&self, state: Self::State, msgs: Vec<Self::Message>) -> anyhow::Result<bool> {
+    async fn process(
+        &self,
+        (max_ml_gas, state): Self::State,
+        msgs: Vec<Self::Message>,
+    ) -> anyhow::Result<bool> {
         if msgs.len() > self.max_msgs {
             tracing::warn!(
                 block_msgs = msgs.len(),
@@ -134,6 +202,21 @@ where
             return Ok(false);
         }
 
+        if let Some(max_ml_gas) = max_ml_gas {
+            let ml_gas = msgs
+                .iter()
+                .filter_map(|msg| ml_gas_limit(msg))
+                .fold(0u64, u64::saturating_add);
+            if ml_gas > max_ml_gas {
+                tracing::warn!(
+                    ml_gas,
+                    max_ml_gas,
+                    "rejecting block: too much machine learning gas"
+                );
+                return Ok(false);
+            }
+        }
+
         let mut chain_msgs = Vec::new();
         for msg in msgs {
             match fvm_ipld_encoding::from_slice::<ChainMessage>(&msg) {
@@ -315,3 +398,68 @@ fn try_parse_genesis_cbor(bytes: &[u8]) -> anyhow::Result<Genesis> {
     let genesis = fvm_ipld_encoding::from_slice(bytes)?;
     Ok(genesis)
 }
+
+#[cfg(test)]
+mod tests {
+    use fendermint_vm_actor_interface::machinelearning::MACHINELEARNING_ACTOR_ADDR;
+    use fendermint_vm_message::{chain::ChainMessage, signed::SignedMessage};
+    use fvm_shared::{
+        address::Address, crypto::signature::Signature, econ::TokenAmount, message::Message,
+        METHOD_SEND,
+    };
+
+    use super::limit_ml_gas;
+
+    fn msg(from: u64, to: Address, gas_limit: u64) -> Vec<u8> {
+        let message = Message {
+            version: 0,
+            from: Address::new_id(from),
+            to,
+            sequence: 0,
+            value: TokenAmount::from_atto(0),
+            method_num: METHOD_SEND,
+            params: Default::default(),
+            gas_limit,
+            gas_fee_cap: TokenAmount::from_atto(0),
+            gas_premium: TokenAmount::from_atto(0),
+        };
+        let msg = ChainMessage::Signed(SignedMessage {
+            message,
+            signature: Signature::new_secp256k1(vec![]),
+        });
+        fvm_ipld_encoding::to_vec(&msg).unwrap()
+    }
+
+    #[test]
+    fn ml_gas_over_budget_is_deferred() {
+        let msgs = vec![
+            msg(101, MACHINELEARNING_ACTOR_ADDR, 600),
+            msg(102, MACHINELEARNING_ACTOR_ADDR, 600),
+            msg(103, Address::new_id(200), 5000),
+            msg(104, MACHINELEARNING_ACTOR_ADDR, 400),
+        ];
+
+        // The second training job would go over the budget, but the smaller one after it fits.
+        let kept = limit_ml_gas(msgs.clone(), 1000);
+        assert_eq!(
+            kept,
+            vec![msgs[0].clone(), msgs[2].clone(), msgs[3].clone()]
+        );
+    }
+
+    #[test]
+    fn ml_gas_deferral_holds_back_later_messages_of_sender() {
+        let msgs = vec![
+            msg(101, MACHINELEARNING_ACTOR_ADDR, 600),
+            msg(102, MACHINELEARNING_ACTOR_ADDR, 600),
+            // Would fit in the budget, and isn't even a machine learning message,
+            // but has to wait for the deferred message of the same sender.
+            msg(102, Address::new_id(200), 5000),
+            msg(102, MACHINELEARNING_ACTOR_ADDR, 100),
+            msg(103, Address::new_id(200), 5000),
+        ];
+
+        let kept = limit_ml_gas(msgs.clone(), 1000);
+        assert_eq!(kept, vec![msgs[0].clone(), msgs[4].clone()]);
+    }
+}
@@ -1,7 +1,7 @@
 // Copyright 2022-2024 Protocol Labs
 // SPDX-License-Identifier: Apache-2.0, MIT
 use crate::fvm::state::ipc::GatewayCaller;
-use crate::fvm::{topdown, FvmApplyRet, PowerUpdates};
+use crate::fvm::{topdown, FvmApplyRet, FvmEndRet};
 use crate::{
     fvm::state::FvmExecState,
     fvm::FvmMessage,
@@ -29,6 +29,7 @@ use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::RawBytes;
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::econ::TokenAmount;
+use ipc_api::cross::TopDownReceipt;
 use num_traits::Zero;
 use std::sync::Arc;
 
@@ -72,8 +73,9 @@ pub struct IllegalMessage;
 // For now this is the only option, later we can expand.
 pub enum ChainMessageApplyRet {
     Signed(SignedMessageApplyRes),
-    /// The IPC chain message execution result
-    Ipc(FvmApplyRet),
+    /// The IPC chain message execution result, with the receipts of the top-down messages
+    /// it executed, in the order of their nonces.
+    Ipc(FvmApplyRet, Vec<TopDownReceipt>),
 }
 
 /// We only allow signed messages into the mempool.
@@ -226,7 +228,7 @@ where
         Message = VerifiableMessage,
         DeliverOutput = SignedMessageApplyRes,
         State = FvmExecState<DB>,
-        EndOutput = PowerUpdates,
+        EndOutput = FvmEndRet,
     >,
 {
     // The state consists of the resolver pool, which this interpreter needs, and the rest of the
@@ -356,9 +358,10 @@ where
                         "chain interpreter received topdown msgs",
                     );
 
-                    let ret = topdown::execute_topdown_msgs(&self.gateway_caller, &mut state, msgs)
-                        .await
-                        .context("failed to execute top down messages")?;
+                    let (ret, receipts) =
+                        topdown::execute_topdown_msgs(&self.gateway_caller, &mut state, msgs)
+                            .await
+                            .context("failed to execute top down messages")?;
 
                     tracing::debug!("chain interpreter applied topdown msgs");
 
@@ -378,7 +381,7 @@ where
                         "chain interpreter has set new"
                     );
 
-                    Ok(((env, state), ChainMessageApplyRet::Ipc(ret)))
+                    Ok(((env, state), ChainMessageApplyRet::Ipc(ret, receipts)))
                 }
             },
         }
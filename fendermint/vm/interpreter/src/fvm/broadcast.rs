@@ -8,19 +8,22 @@ use ethers::types as et;
 use fendermint_rpc::response::decode_fevm_return_data;
 use fvm_ipld_encoding::RawBytes;
 use fvm_shared::error::ExitCode;
-use fvm_shared::{address::Address, chainid::ChainID, econ::TokenAmount, BLOCK_GAS_LIMIT};
+use fvm_shared::{
+    address::Address, chainid::ChainID, econ::TokenAmount, MethodNum, BLOCK_GAS_LIMIT,
+};
 use num_traits::Zero;
 use tendermint_rpc::Client;
+use tokio_util::sync::CancellationToken;
 
 use fendermint_crypto::SecretKey;
-use fendermint_rpc::message::GasParams;
+use fendermint_rpc::message::{GasParams, MessageFactory};
 use fendermint_rpc::query::QueryClient;
 use fendermint_rpc::tx::{CallClient, TxClient, TxSync};
 use fendermint_rpc::{client::FendermintClient, message::SignedMessageFactory};
 use fendermint_vm_message::query::FvmQueryHeight;
 
 macro_rules! retry {
-    ($max_retries:expr, $retry_delay:expr, $block:expr) => {{
+    ($max_retries:expr, $retry_delay:expr, $shutdown:expr, $block:expr) => {{
         let mut attempt = 0;
         let value = loop {
             match $block {
@@ -35,7 +38,11 @@ macro_rules! retry {
                     break value;
                 }
             }
-            tokio::time::sleep($retry_delay).await;
+            // Give up instead of retrying in the background of a shutdown.
+            tokio::select! {
+                _ = $shutdown.cancelled() => bail!("shutting down, not retrying broadcast"),
+                _ = tokio::time::sleep($retry_delay) => {}
+            }
         };
         value
     }};
@@ -59,6 +66,8 @@ pub struct Broadcaster<C> {
     gas_overestimation_rate: f64,
     max_retries: u8,
     retry_delay: Duration,
+    /// Cancelled when the node is shutting down, to stop retrying.
+    shutdown: CancellationToken,
 }
 
 impl<C> Broadcaster<C>
@@ -84,6 +93,7 @@ where
             max_retries: 0,
             // Set the retry delay to rougly the block creation time.
             retry_delay: Duration::from_secs(1),
+            shutdown: CancellationToken::new(),
         }
     }
 
@@ -97,6 +107,11 @@ where
         self
     }
 
+    pub fn with_shutdown_signal(mut self, shutdown: CancellationToken) -> Self {
+        self.shutdown = shutdown;
+        self
+    }
+
     pub fn retry_delay(&self) -> Duration {
         self.retry_delay
     }
@@ -114,7 +129,7 @@ where
         calldata: et::Bytes,
         chain_id: ChainID,
     ) -> anyhow::Result<tendermint::hash::Hash> {
-        let tx_hash = retry!(self.max_retries, self.retry_delay, {
+        let tx_hash = retry!(self.max_retries, self.retry_delay, self.shutdown, {
             let sequence = self
                 .sequence()
                 .await
@@ -199,6 +214,122 @@ where
         Ok(tx_hash)
     }
 
+    /// Send a transaction to a native actor and return its hash.
+    ///
+    /// Like [Broadcaster::fevm_invoke], it doesn't wait for the execution.
+    pub async fn invoke(
+        &self,
+        to: Address,
+        method_num: MethodNum,
+        params: RawBytes,
+        chain_id: ChainID,
+    ) -> anyhow::Result<tendermint::hash::Hash> {
+        let tx_hash = retry!(self.max_retries, self.retry_delay, self.shutdown, {
+            let sequence = self
+                .sequence()
+                .await
+                .context("failed to get broadcaster sequence")?;
+
+            let factory =
+                SignedMessageFactory::new(self.secret_key.clone(), self.addr, sequence, chain_id);
+
+            let mut client = self.client.clone().bind(factory);
+
+            let mut gas_params = self.gas_params();
+
+            // Not expecting to send any tokens to the actors.
+            let value = TokenAmount::zero();
+
+            let msg = MessageFactory::new(self.addr, sequence).transaction(
+                to,
+                method_num,
+                params.clone(),
+                value.clone(),
+                gas_params.clone(),
+            );
+
+            let gas_estimate = client
+                .estimate_gas(msg, FvmQueryHeight::Committed)
+                .await
+                .context("failed to estimate gas")?;
+
+            if gas_estimate.value.exit_code.is_success() {
+                gas_params.gas_limit =
+                    (gas_estimate.value.gas_limit as f64 * self.gas_overestimation_rate) as u64;
+            } else {
+                bail!(
+                    "failed to estimate gas: {} - {}",
+                    gas_estimate.value.exit_code,
+                    gas_estimate.value.info
+                );
+            }
+
+            let res = TxClient::<TxSync>::transaction(
+                &mut client,
+                to,
+                method_num,
+                params.clone(),
+                value,
+                gas_params,
+            )
+            .await
+            .context("failed to send transaction")?;
+
+            if res.response.code.is_err() {
+                Err((
+                    res.response.code,
+                    format!(
+                        "broadcasted transaction failed during check: {}; log = {}",
+                        res.response.code.value(),
+                        res.response.log
+                    ),
+                ))
+            } else {
+                Ok(res.response.hash)
+            }
+        });
+
+        Ok(tx_hash)
+    }
+
+    /// Execute a message on a native actor in read-only mode, on the committed state,
+    /// to see what would happen if the broadcaster sent it as a transaction.
+    pub async fn call(
+        &self,
+        to: Address,
+        method_num: MethodNum,
+        params: RawBytes,
+    ) -> anyhow::Result<tendermint::abci::response::DeliverTx> {
+        let sequence = self
+            .sequence()
+            .await
+            .context("failed to get broadcaster sequence")?;
+
+        let msg = MessageFactory::new(self.addr, sequence).transaction(
+            to,
+            method_num,
+            params,
+            TokenAmount::zero(),
+            self.gas_params(),
+        );
+
+        let res = self
+            .client
+            .call(msg, FvmQueryHeight::Committed)
+            .await
+            .context("failed to call actor")?;
+
+        Ok(res.value)
+    }
+
+    fn gas_params(&self) -> GasParams {
+        GasParams {
+            gas_limit: BLOCK_GAS_LIMIT,
+            gas_fee_cap: self.gas_fee_cap.clone(),
+            gas_premium: self.gas_premium.clone(),
+        }
+    }
+
     /// Fetch the current nonce to be used in the next message.
     async fn sequence(&self) -> anyhow::Result<u64> {
         // Using the `Pending` state to query just in case there are other transactions initiated by the validator.
@@ -9,7 +9,10 @@ use fvm_shared::{address::Address, error::ExitCode};
 
 use crate::CheckInterpreter;
 
-use super::{state::FvmExecState, store::ReadOnlyBlockstore, FvmMessage, FvmMessageInterpreter};
+use super::{
+    prevalidate::PrevalidationContext, state::FvmExecState, store::ReadOnlyBlockstore, FvmMessage,
+    FvmMessageInterpreter,
+};
 
 /// Transaction check results are expressed by the exit code, so that hopefully
 /// they would result in the same error code if they were applied.
@@ -37,6 +40,7 @@ where
     /// * sender exists
     /// * sender nonce matches the message sequence
     /// * sender has enough funds to cover the gas cost
+    /// * the message passes all the configured prevalidators
     async fn check(
         &self,
         mut state: Self::State,
@@ -78,6 +82,25 @@ where
             );
         }
 
+        let ctx = PrevalidationContext {
+            block_height: state.block_height(),
+            base_fee: state.base_fee(),
+        };
+        let rejection = self
+            .prevalidators
+            .iter()
+            .find_map(|p| p.prevalidate(&ctx, &msg).err());
+
+        if let Some(rejection) = rejection {
+            return checked(
+                state,
+                rejection.exit_code,
+                None,
+                None,
+                Some(format!("prevalidation failure: {}", rejection.info)),
+            );
+        }
+
         // NOTE: This would be a great place for let-else, but clippy runs into a compilation bug.
         let state_tree = state.state_tree_mut();
 
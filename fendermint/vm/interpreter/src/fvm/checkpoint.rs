@@ -6,6 +6,7 @@ use std::time::Duration;
 
 use anyhow::{anyhow, Context};
 use ethers::abi::Tokenizable;
+use serde::{Deserialize, Serialize};
 use tendermint::block::Height;
 use tendermint_rpc::endpoint::commit;
 use tendermint_rpc::{endpoint::validators, Client, Paging};
@@ -17,7 +18,7 @@ use fendermint_crypto::PublicKey;
 use fendermint_crypto::SecretKey;
 use fendermint_tracing::emit;
 use fendermint_vm_actor_interface::eam::EthAddress;
-use fendermint_vm_actor_interface::ipc::BottomUpCheckpoint;
+use fendermint_vm_actor_interface::ipc::{AbiHash, BottomUpCheckpoint};
 use fendermint_vm_event::NewBottomUpCheckpoint;
 use fendermint_vm_genesis::{Power, Validator, ValidatorKey};
 
@@ -25,9 +26,10 @@ use ipc_actors_abis::checkpointing_facet as checkpoint;
 use ipc_actors_abis::gateway_getter_facet as getter;
 use ipc_api::staking::ConfigurationNumber;
 
-use super::state::ipc::tokens_to_burn;
+use super::state::ipc::{sign_checkpoint_hash, tokens_to_burn};
 use super::{
     broadcast::Broadcaster,
+    signatures::{CheckpointSignature, SignatureGossip},
     state::{ipc::GatewayCaller, FvmExecState},
     ValidatorContext,
 };
@@ -40,15 +42,70 @@ pub struct PowerTable(pub Vec<Validator<Power>>);
 #[derive(Debug, Clone, Default)]
 pub struct PowerUpdates(pub Vec<Validator<Power>>);
 
+/// How the membership of a validator changed between two configurations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, strum::Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum ValidatorChangeKind {
+    /// The validator wasn't part of the previous power table.
+    Joined,
+    /// The validator isn't part of the next power table.
+    Left,
+    /// The validator stayed, with a different power.
+    Updated,
+}
+
+/// The change of a single validator in the power table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidatorChange {
+    pub public_key: ValidatorKey,
+    pub kind: ValidatorChangeKind,
+    /// The power before the change; 0 if the validator joined.
+    pub previous_power: Power,
+    /// The power after the change; 0 if the validator left.
+    pub power: Power,
+}
+
+impl ValidatorChange {
+    /// The Ethereum address of the validator, which is how the parent subnet knows it.
+    pub fn address(&self) -> EthAddress {
+        EthAddress::from(self.public_key.0)
+    }
+}
+
+/// The changes of the validator set applied at the end of a block, along with the
+/// configuration of the parent subnet they bring the power table to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidatorSetChange {
+    pub block_height: u64,
+    pub configuration_number: ConfigurationNumber,
+    pub changes: Vec<ValidatorChange>,
+}
+
+impl ValidatorSetChange {
+    /// The updates to return to CometBFT, with the validators who left having 0 power.
+    pub fn power_updates(&self) -> PowerUpdates {
+        PowerUpdates(
+            self.changes
+                .iter()
+                .map(|c| Validator {
+                    public_key: c.public_key.clone(),
+                    power: c.power,
+                })
+                .collect(),
+        )
+    }
+}
+
 /// Construct and store a checkpoint if this is the end of the checkpoint period.
 /// Perform end-of-checkpoint-period transitions in the ledger.
 ///
-/// If we are the boundary, return the validators eligible to sign and any updates
-/// to the power table, along with the checkpoint that needs to be signed by validators.
+/// If we are the boundary, return the checkpoint that needs to be signed by validators,
+/// along with the changes to the validator set, if there were any.
 pub fn maybe_create_checkpoint<DB>(
     gateway: &GatewayCaller<DB>,
     state: &mut FvmExecState<DB>,
-) -> anyhow::Result<Option<(checkpoint::BottomUpCheckpoint, PowerUpdates)>>
+) -> anyhow::Result<Option<(checkpoint::BottomUpCheckpoint, Option<ValidatorSetChange>)>>
 where
     DB: Blockstore + Sync + Send + Clone + 'static,
 {
@@ -110,15 +167,25 @@ where
         .context("failed to store checkpoint")?;
 
     // Figure out the power updates if there was some change in the configuration.
-    let power_updates = if next_configuration_number == 0 {
-        PowerUpdates(Vec::new())
+    let validator_set_change = if next_configuration_number == 0 {
+        None
     } else {
         let (next_power_configuration_number, next_power_table) =
             ipc_power_table(gateway, state).context("failed to get next power table")?;
 
         debug_assert_eq!(next_power_configuration_number, next_configuration_number);
 
-        power_diff(curr_power_table, next_power_table)
+        let changes = power_diff(curr_power_table, next_power_table);
+
+        if changes.is_empty() {
+            None
+        } else {
+            Some(ValidatorSetChange {
+                block_height: height.value(),
+                configuration_number: next_configuration_number,
+                changes,
+            })
+        }
     };
 
     emit!(NewBottomUpCheckpoint {
@@ -128,14 +195,14 @@ where
         next_configuration_number,
     });
 
-    Ok(Some((checkpoint, power_updates)))
+    Ok(Some((checkpoint, validator_set_change)))
 }
 
 /// Wait until CometBFT has reached a specific block height.
 ///
 /// This is used so we can wait for the next block where the ledger changes
 /// we have done durign execution has been committed.
-async fn wait_for_commit<C>(
+pub(crate) async fn wait_for_commit<C>(
     client: &C,
     block_height: u64,
     retry_delay: Duration,
@@ -230,17 +297,7 @@ where
             .find(|v| v.public_key.0 == validator_ctx.public_key)
             .cloned()
         {
-            // TODO: Code generation in the ipc-solidity-actors repo should cater for this.
-            let checkpoint = checkpoint::BottomUpCheckpoint {
-                subnet_id: checkpoint::SubnetID {
-                    root: cp.subnet_id.root,
-                    route: cp.subnet_id.route,
-                },
-                block_height: cp.block_height,
-                block_hash: cp.block_hash,
-                next_configuration_number: cp.next_configuration_number,
-                msgs: convert_tokenizables(cp.msgs)?,
-            };
+            let checkpoint = to_checkpointing_checkpoint(cp)?;
 
             // We mustn't do these in parallel because of how nonces are fetched.
             broadcast_signature(
@@ -290,6 +347,145 @@ where
     Ok(())
 }
 
+/// Sign the current and any incomplete checkpoints, gossip the signatures to the other
+/// validators, and add the quorum certificates collected from them to the ledger.
+///
+/// The certificate of the current checkpoint is added by its designated submitter. Those of
+/// older checkpoints, which are still incomplete in the ledger, are added by any validator
+/// which has them, in case the designated submitter failed to.
+pub async fn gossip_incomplete_signatures<C, DB>(
+    client: &C,
+    validator_ctx: &ValidatorContext<C>,
+    gossip: &SignatureGossip,
+    gateway: &GatewayCaller<DB>,
+    chain_id: ChainID,
+    incomplete_checkpoints: Vec<(getter::BottomUpCheckpoint, u64)>,
+    current_height: u64,
+) -> anyhow::Result<()>
+where
+    C: Client + Clone + Send + Sync + 'static,
+    DB: Blockstore + Send + Sync + Clone + 'static,
+{
+    // Make sure that these had time to be added to the ledger.
+    if let Some(highest) = incomplete_checkpoints
+        .iter()
+        .map(|(cp, _)| cp.block_height)
+        .max()
+    {
+        wait_for_commit(
+            client,
+            highest.as_u64() + 1,
+            validator_ctx.broadcaster.retry_delay(),
+        )
+        .await
+        .context("failed to wait for commit")?;
+    }
+
+    let mut heights = Vec::new();
+
+    for (cp, threshold) in incomplete_checkpoints {
+        let height = cp.block_height.as_u64();
+        // Getting the power table from CometBFT where the history is available.
+        let power_table = bft_power_table(client, Height::try_from(height)?)
+            .await
+            .context("failed to get power table")?;
+
+        let checkpoint_hash = to_checkpointing_checkpoint(cp)?.abi_hash();
+
+        gossip
+            .collector()
+            .expect(height, checkpoint_hash, power_table.0.clone(), threshold);
+
+        if power_table
+            .0
+            .iter()
+            .any(|v| v.public_key.0 == validator_ctx.public_key)
+        {
+            let signature = sign_checkpoint_hash(&validator_ctx.secret_key, &checkpoint_hash)?;
+
+            gossip
+                .publish(CheckpointSignature {
+                    height,
+                    checkpoint_hash,
+                    signature: signature.to_vec(),
+                })
+                .context("failed to publish checkpoint signature")?;
+
+            tracing::debug!(height, "gossiped checkpoint signature");
+        }
+
+        heights.push(height);
+    }
+
+    for height in heights {
+        let certificate = if height == current_height {
+            match gossip
+                .collector()
+                .wait_for_certificate(height, gossip.quorum_timeout())
+                .await
+            {
+                Some(c) if c.submitter() == Some(&validator_ctx.public_key) => Some(c),
+                Some(_) => None,
+                None => {
+                    tracing::warn!(
+                        height,
+                        "checkpoint signatures did not reach a quorum in time"
+                    );
+                    None
+                }
+            }
+        } else {
+            gossip.collector().certificate(height)
+        };
+
+        let Some(certificate) = certificate else {
+            continue;
+        };
+
+        let calldata = gateway
+            .add_checkpoint_signatures_calldata(&certificate)
+            .context("failed to produce checkpoint signatures calldata")?;
+
+        // Someone else may have added an older certificate already.
+        match validator_ctx
+            .broadcaster
+            .fevm_invoke(Address::from(gateway.addr()), calldata, chain_id)
+            .await
+        {
+            Ok(tx_hash) => tracing::info!(
+                tx_hash = tx_hash.to_string(),
+                height,
+                signatures = certificate.signatures.len(),
+                "broadcasted checkpoint quorum certificate"
+            ),
+            Err(e) => tracing::warn!(
+                error = e.to_string(),
+                height,
+                "failed to broadcast checkpoint quorum certificate"
+            ),
+        }
+    }
+
+    Ok(())
+}
+
+/// Convert a checkpoint from the getter facet to the type of the checkpointing facet.
+fn to_checkpointing_checkpoint(
+    cp: getter::BottomUpCheckpoint,
+) -> anyhow::Result<checkpoint::BottomUpCheckpoint> {
+    // TODO: Code generation in the ipc-solidity-actors repo should cater for this.
+    Ok(checkpoint::BottomUpCheckpoint {
+        subnet_id: checkpoint::SubnetID {
+            root: cp.subnet_id.root,
+            route: cp.subnet_id.route,
+        },
+        block_height: cp.block_height,
+        block_hash: cp.block_hash,
+        next_configuration_number: cp.next_configuration_number,
+        msgs: convert_tokenizables(cp.msgs)?,
+    })
+}
+
 fn convert_tokenizables<Source: Tokenizable, Target: Tokenizable>(
     tokenizables: Vec<Source>,
 ) -> anyhow::Result<Vec<Target>> {
@@ -381,7 +577,7 @@ where
 /// Calculate the difference between the current and the next power table, to return to CometBFT only what changed:
 /// * include any new validator, or validators whose power has been updated
 /// * include validators to be removed with a power of 0, as [expected](https://github.com/informalsystems/tendermint-rs/blob/bcc0b377812b8e53a02dff156988569c5b3c81a2/rpc/src/dialect/end_block.rs#L12-L14) by CometBFT
-fn power_diff(current: PowerTable, next: PowerTable) -> PowerUpdates {
+fn power_diff(current: PowerTable, next: PowerTable) -> Vec<ValidatorChange> {
     let current = into_power_map(current);
     let next = into_power_map(next);
 
@@ -390,26 +586,33 @@ fn power_diff(current: PowerTable, next: PowerTable) -> PowerUpdates {
     // Validators in `current` but not in `next` should be removed.
     for (k, v) in current.iter() {
         if !next.contains_key(k) {
-            let delete = Validator {
+            diff.push(ValidatorChange {
                 public_key: v.public_key.clone(),
+                kind: ValidatorChangeKind::Left,
+                previous_power: v.power,
                 power: Power(0),
-            };
-            diff.push(delete);
+            });
         }
     }
 
     // Validators in `next` that differ from `current` should be updated.
     for (k, v) in next.into_iter() {
-        let insert = match current.get(&k) {
+        let change = match current.get(&k) {
             Some(w) if *w == v => None,
-            _ => Some(v),
+            Some(w) => Some((ValidatorChangeKind::Updated, w.power)),
+            None => Some((ValidatorChangeKind::Joined, Power(0))),
         };
-        if let Some(insert) = insert {
-            diff.push(insert);
+        if let Some((kind, previous_power)) = change {
+            diff.push(ValidatorChange {
+                public_key: v.public_key,
+                kind,
+                previous_power,
+                power: v.power,
+            });
         }
     }
 
-    PowerUpdates(diff)
+    diff
 }
 
 /// Convert the power list to a `HashMap` to support lookups by the public key.
@@ -434,12 +637,16 @@ mod tests {
 
     use crate::fvm::checkpoint::{into_power_map, power_diff};
 
-    use super::{PowerTable, PowerUpdates};
+    use super::{PowerTable, ValidatorChange, ValidatorChangeKind};
 
-    fn power_update(current: PowerTable, updates: PowerUpdates) -> PowerTable {
+    fn power_update(current: PowerTable, updates: Vec<ValidatorChange>) -> PowerTable {
         let mut current = into_power_map(current);
 
-        for v in updates.0 {
+        for c in updates {
+            let v = Validator {
+                public_key: c.public_key,
+                power: c.power,
+            };
             let k = v.public_key.0.serialize();
             if v.power.0 == 0 {
                 current.remove(&k);
@@ -493,10 +700,34 @@ mod tests {
         assert_eq!(next, expected)
     }
 
+    #[quickcheck]
+    fn prop_power_diff_kinds(powers: TestPowerTables) {
+        let current = into_power_map(powers.current.clone());
+        let next = into_power_map(powers.next.clone());
+
+        for c in power_diff(powers.current, powers.next) {
+            let k = c.public_key.0.serialize();
+            match c.kind {
+                ValidatorChangeKind::Joined => {
+                    assert!(!current.contains_key(&k));
+                    assert_eq!(c.previous_power.0, 0);
+                }
+                ValidatorChangeKind::Left => {
+                    assert!(!next.contains_key(&k));
+                    assert_eq!(c.power.0, 0);
+                }
+                ValidatorChangeKind::Updated => {
+                    assert_eq!(current[&k].power, c.previous_power);
+                    assert_eq!(next[&k].power, c.power);
+                }
+            }
+        }
+    }
+
     #[quickcheck]
     fn prop_power_diff_nochange(v1: Validator<Power>, v2: Validator<Power>) {
         let current = PowerTable(vec![v1.clone(), v2.clone()]);
         let next = PowerTable(vec![v2, v1]);
-        assert!(power_diff(current, next).0.is_empty());
+        assert!(power_diff(current, next).is_empty());
     }
 }
@@ -0,0 +1,153 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Logging of the transactions delivered in a block.
+//!
+//! Busy subnets deliver too many transactions to log every one of them at info level, so they
+//! are logged at a configurable level, debug by default, with a summary of each block at info.
+
+use fvm_shared::{address::Address, clock::ChainEpoch, error::ExitCode, MethodNum};
+use serde_json::json;
+use tracing::Level;
+
+/// How the transactions delivered in a block are logged.
+#[derive(Debug, Clone)]
+pub struct DeliveryLogging {
+    /// Level of the event logged for every transaction; they are not logged if `None`.
+    pub tx_level: Option<Level>,
+    /// Log a summary of the transactions of every block at info level.
+    pub block_summary: bool,
+    /// Repeat the fields of the events as a JSON object in a `json` field, for log ingestion.
+    pub json: bool,
+}
+
+impl Default for DeliveryLogging {
+    fn default() -> Self {
+        Self {
+            tx_level: Some(Level::DEBUG),
+            block_summary: true,
+            json: false,
+        }
+    }
+}
+
+/// The transactions delivered in a block so far.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeliveryStats {
+    pub txs: u64,
+    pub failed: u64,
+    pub gas_used: u64,
+    pub events: u64,
+}
+
+impl DeliveryStats {
+    pub fn record(&mut self, exit_code: ExitCode, gas_used: u64, events: usize) {
+        self.txs += 1;
+        if !exit_code.is_success() {
+            self.failed += 1;
+        }
+        self.gas_used += gas_used;
+        self.events += events as u64;
+    }
+}
+
+/// Log an event at a level only known at runtime.
+macro_rules! event_at {
+    ($level:expr, $($args:tt)+) => {
+        match $level {
+            Level::ERROR => tracing::error!($($args)+),
+            Level::WARN => tracing::warn!($($args)+),
+            Level::INFO => tracing::info!($($args)+),
+            Level::DEBUG => tracing::debug!($($args)+),
+            _ => tracing::trace!($($args)+),
+        }
+    };
+}
+
+impl DeliveryLogging {
+    pub fn log_tx(
+        &self,
+        height: ChainEpoch,
+        from: &Address,
+        to: &Address,
+        method_num: MethodNum,
+        exit_code: ExitCode,
+        gas_used: u64,
+    ) {
+        let Some(level) = self.tx_level else {
+            return;
+        };
+        let json = self.json.then(|| {
+            json!({
+                "event": "tx_delivered",
+                "height": height,
+                "from": from.to_string(),
+                "to": to.to_string(),
+                "method_num": method_num,
+                "exit_code": exit_code.value(),
+                "gas_used": gas_used,
+            })
+            .to_string()
+        });
+        event_at!(
+            level,
+            height,
+            from = from.to_string(),
+            to = to.to_string(),
+            method_num,
+            exit_code = exit_code.value(),
+            gas_used,
+            json = json.as_deref(),
+            "tx delivered"
+        );
+    }
+
+    pub fn log_block(&self, height: ChainEpoch, stats: &DeliveryStats) {
+        if !self.block_summary {
+            return;
+        }
+        let json = self.json.then(|| {
+            json!({
+                "event": "block_delivered",
+                "height": height,
+                "txs": stats.txs,
+                "failed": stats.failed,
+                "gas_used": stats.gas_used,
+                "events": stats.events,
+            })
+            .to_string()
+        });
+        tracing::info!(
+            height,
+            txs = stats.txs,
+            failed = stats.failed,
+            gas_used = stats.gas_used,
+            events = stats.events,
+            json = json.as_deref(),
+            "block delivered"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fvm_shared::error::ExitCode;
+
+    use super::DeliveryStats;
+
+    #[test]
+    fn delivery_stats_count_failures() {
+        let mut stats = DeliveryStats::default();
+        stats.record(ExitCode::OK, 10, 2);
+        stats.record(ExitCode::USR_ILLEGAL_ARGUMENT, 5, 0);
+        assert_eq!(
+            stats,
+            DeliveryStats {
+                txs: 2,
+                failed: 1,
+                gas_used: 15,
+                events: 2,
+            }
+        );
+    }
+}
@@ -1,22 +1,26 @@
 // Copyright 2022-2024 Protocol Labs
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use anyhow::Context;
+use anyhow::{anyhow, Context};
 use async_trait::async_trait;
 use std::{collections::HashMap, slice::from_raw_parts};
 
-use fendermint_vm_actor_interface::{chainmetadata, cron, machinelearning, system};
+use fendermint_tracing::{emit, op_span};
+use fendermint_vm_actor_interface::{chainmetadata, cron, ipc, machinelearning, system};
+use fendermint_vm_event::{BlockExecuted, BlockGasUsed, CronExecuted, ImplicitMessageFailed};
 use fvm::executor::ApplyRet;
 use fvm_ipld_blockstore::Blockstore;
-use fvm_shared::{address::Address, ActorID, MethodNum, BLOCK_GAS_LIMIT};
+use fvm_shared::{address::Address, clock::ChainEpoch, ActorID, MethodNum};
 use tendermint_rpc::Client;
+use tracing::Instrument;
 
 use crate::ExecInterpreter;
 
 use super::{
-    checkpoint::{self, PowerUpdates},
+    checkpoint::{self, PowerUpdates, ValidatorSetChange},
+    mljobs,
     state::FvmExecState,
-    FvmMessage, FvmMessageInterpreter,
+    DeliveryStats, FvmMessage, FvmMessageInterpreter,
 };
 
 /// The return value extended with some things from the message that
@@ -33,6 +37,45 @@ pub struct FvmApplyRet {
     pub emitters: HashMap<ActorID, Address>,
 }
 
+/// The outcome of the epoch transitions performed at the end of a block.
+#[derive(Debug, Clone, Default)]
+pub struct FvmEndRet {
+    /// Validator power updates to return to CometBFT.
+    pub power_updates: PowerUpdates,
+    /// Who joined or left the validator set, or had their power changed, if anyone did.
+    pub validator_set_change: Option<ValidatorSetChange>,
+    /// What happened in the block.
+    pub summary: BlockSummary,
+}
+
+/// What happened in a block, so that its health can be observed without scanning it again.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockSummary {
+    /// Number of transactions delivered.
+    pub txs: u64,
+    /// Number of transactions which failed.
+    pub failed_txs: u64,
+    /// Gas used by the transactions.
+    pub gas_used: u64,
+    /// Number of events emitted by the transactions.
+    pub events: u64,
+    /// Whether a bottom-up checkpoint was created.
+    pub checkpoint_created: bool,
+}
+
+impl BlockSummary {
+    /// Summarize the transactions delivered in the block.
+    pub fn new(stats: &DeliveryStats, checkpoint_created: bool) -> Self {
+        Self {
+            txs: stats.txs,
+            failed_txs: stats.failed,
+            gas_used: stats.gas_used,
+            events: stats.events,
+            checkpoint_created,
+        }
+    }
+}
+
 #[async_trait]
 impl<DB, TC> ExecInterpreter for FvmMessageInterpreter<DB, TC>
 where
@@ -43,95 +86,477 @@ where
     type Message = FvmMessage;
     type BeginOutput = FvmApplyRet;
     type DeliverOutput = FvmApplyRet;
-    /// Return validator power updates.
+    /// Return validator power updates, along with what they changed in the validator set.
     /// Currently ignoring events as there aren't any emitted by the smart contract,
     /// but keep in mind that if there were, those would have to be propagated.
-    type EndOutput = PowerUpdates;
+    type EndOutput = FvmEndRet;
 
     async fn begin(
         &self,
         mut state: Self::State,
     ) -> anyhow::Result<(Self::State, Self::BeginOutput)> {
-        // Block height (FVM epoch) as sequence is intentional
-        let height = state.block_height();
-
-        // check for upgrades in the upgrade_scheduler
-        let chain_id = state.chain_id();
-        let block_height: u64 = state.block_height().try_into().unwrap();
-        if let Some(upgrade) = self.upgrade_scheduler.get(chain_id, block_height) {
-            // TODO: consider using an explicit tracing enum for upgrades
-            tracing::info!(?chain_id, height = block_height, "Executing an upgrade");
-
-            // there is an upgrade scheduled for this height, lets run the migration
-            let res = upgrade.execute(&mut state).context("upgrade failed")?;
-            if let Some(new_app_version) = res {
-                state.update_app_version(|app_version| {
-                    *app_version = new_app_version;
-                });
-
-                tracing::info!(app_version = state.app_version(), "upgraded app version");
+        let span = op_span!("begin", self.subnet_id, state.block_height());
+
+        async move {
+            // Block height (FVM epoch) as sequence is intentional
+            let height = state.block_height();
+
+            // check for upgrades in the upgrade_scheduler
+            let chain_id = state.chain_id();
+            let block_height: u64 = state.block_height().try_into().unwrap();
+            if let Some(upgrade) = self.upgrade_scheduler.get(chain_id, block_height) {
+                // TODO: consider using an explicit tracing enum for upgrades
+                tracing::info!(?chain_id, height = block_height, "Executing an upgrade");
+
+                // there is an upgrade scheduled for this height, lets run the migration
+                let res = upgrade.execute(&mut state).context("upgrade failed")?;
+                if let Some(new_app_version) = res {
+                    state.update_app_version(|app_version| {
+                        *app_version = new_app_version;
+                    });
+
+                    tracing::info!(app_version = state.app_version(), "upgraded app version");
+                }
             }
+
+            let gas_limit = state
+                .implicit_gas_limits()
+                .cron_gas_limit(state.block_interval());
+            let from = system::SYSTEM_ACTOR_ADDR;
+            let to = cron::CRON_ACTOR_ADDR;
+            let method_num = cron::Method::EpochTick as u64;
+
+            // Cron.
+            let msg = FvmMessage {
+                from,
+                to,
+                sequence: height as u64,
+                gas_limit,
+                method_num,
+                params: Default::default(),
+                value: Default::default(),
+                version: Default::default(),
+                gas_fee_cap: Default::default(),
+                gas_premium: Default::default(),
+            };
+
+            let (apply_ret, emitters) = state.execute_implicit(msg)?;
+
+            // Failing cron would be fatal.
+            check_implicit(ImplicitMessage::Cron, height, &apply_ret)?;
+
+            emit!(CronExecuted {
+                block_height: height as u64,
+                gas_limit,
+                gas_used: apply_ret.msg_receipt.gas_used,
+            });
+            state.record_gas_used(&to, apply_ret.msg_receipt.gas_used);
+
+            // Push the current block hash to the chainmetadata actor
+            if self.push_chain_meta {
+                self.push_chain_metadata(&mut state, height)?;
+            }
+
+            self.run_ml_selftest(&mut state, height)?;
+
+            let ret = FvmApplyRet {
+                apply_ret,
+                from,
+                to,
+                method_num,
+                gas_limit,
+                emitters,
+            };
+
+            Ok((state, ret))
         }
+        .instrument(span)
+        .await
+    }
+
+    async fn deliver(
+        &self,
+        mut state: Self::State,
+        msg: Self::Message,
+    ) -> anyhow::Result<(Self::State, Self::DeliverOutput)> {
+        let span = op_span!(
+            "deliver",
+            self.subnet_id,
+            state.block_height(),
+            to = %msg.to,
+            method_num = msg.method_num
+        );
+
+        async move {
+            let from = msg.from;
+            let to = msg.to;
+            let method_num = msg.method_num;
+            let gas_limit = msg.gas_limit;
+
+            let (apply_ret, emitters) = if from == system::SYSTEM_ACTOR_ADDR {
+                state.execute_implicit(msg)?
+            } else {
+                state.execute_explicit(msg)?
+            };
+
+            let exit_code = apply_ret.msg_receipt.exit_code;
+            let gas_used = apply_ret.msg_receipt.gas_used;
+
+            self.delivery_logging.log_tx(
+                state.block_height(),
+                &from,
+                &to,
+                method_num,
+                exit_code,
+                gas_used,
+            );
+
+            state.record_gas_used(&to, gas_used);
+            state.record_delivery(exit_code, gas_used, apply_ret.events.len());
 
-        // Arbitrarily large gas limit for cron (matching how Forest does it, which matches Lotus).
-        // XXX: Our blocks are not necessarily expected to be 30 seconds apart, so the gas limit might be wrong.
-        let gas_limit = BLOCK_GAS_LIMIT * 10000;
-        let from = system::SYSTEM_ACTOR_ADDR;
-        let to = cron::CRON_ACTOR_ADDR;
-        let method_num = cron::Method::EpochTick as u64;
-
-        // Cron.
-        let msg = FvmMessage {
-            from,
-            to,
-            sequence: height as u64,
-            gas_limit,
-            method_num,
-            params: Default::default(),
-            value: Default::default(),
-            version: Default::default(),
-            gas_fee_cap: Default::default(),
-            gas_premium: Default::default(),
-        };
-
-        let (apply_ret, emitters) = state.execute_implicit(msg)?;
-
-        // Failing cron would be fatal.
-        if let Some(err) = apply_ret.failure_info {
-            anyhow::bail!("failed to apply block cron message: {}", err);
+            let ret = FvmApplyRet {
+                apply_ret,
+                from,
+                to,
+                method_num,
+                gas_limit,
+                emitters,
+            };
+
+            Ok((state, ret))
         }
+        .instrument(span)
+        .await
+    }
 
-        // Push the current block hash to the chainmetadata actor
-        if self.push_chain_meta {
-            if let Some(block_hash) = state.block_hash() {
-                let params = fvm_ipld_encoding::RawBytes::serialize(
-                    fendermint_actor_chainmetadata::PushBlockParams {
-                        epoch: height,
-                        block: block_hash,
-                    },
-                )?;
-
-                let msg = FvmMessage {
-                    from: system::SYSTEM_ACTOR_ADDR,
-                    to: chainmetadata::CHAINMETADATA_ACTOR_ADDR,
-                    sequence: height as u64,
-                    gas_limit,
-                    method_num: fendermint_actor_chainmetadata::Method::PushBlockHash as u64,
-                    params,
-                    value: Default::default(),
-                    version: Default::default(),
-                    gas_fee_cap: Default::default(),
-                    gas_premium: Default::default(),
-                };
-
-                let (apply_ret, _) = state.execute_implicit(msg)?;
-
-                if let Some(err) = apply_ret.failure_info {
-                    anyhow::bail!("failed to apply chainmetadata message: {}", err);
+    async fn end(&self, mut state: Self::State) -> anyhow::Result<(Self::State, Self::EndOutput)> {
+        let span = op_span!("end", self.subnet_id, state.block_height());
+
+        async move {
+            let gas_limit = state.implicit_gas_limits().ml_jobs;
+            mljobs::finalize_expired_jobs(&mut state, gas_limit)
+                .context("failed to finalize ml jobs")?;
+
+            // Asynchronously verify the jobs posted in this block, if validating.
+            if let Some(ref ctx) = self.validator_ctx {
+                // Jobs from past blocks have already been finalized or are being verified by others.
+                if !self.syncing().await {
+                    let jobs = match mljobs::posted_jobs(&state) {
+                        Ok(jobs) => jobs,
+                        Err(e) => {
+                            tracing::error!(error =? e, "failed to fetch posted ml jobs");
+                            Vec::new()
+                        }
+                    };
+
+                    if !jobs.is_empty() {
+                        let client = self.client.clone();
+                        let chain_id = state.chain_id();
+                        let height = state.block_height() as u64;
+                        let validator_ctx = ctx.clone();
+
+                        self.supervisor.spawn("verify ml jobs", async move {
+                            let res = mljobs::challenge_invalid_jobs(
+                                &client,
+                                &validator_ctx.broadcaster,
+                                chain_id,
+                                height,
+                                jobs,
+                            )
+                            .await;
+
+                            if let Err(e) = res {
+                                tracing::error!(error =? e, height, "error verifying ml jobs");
+                            }
+                        });
+                    }
                 }
             }
+
+            let checkpoint = checkpoint::maybe_create_checkpoint(&self.gateway, &mut state)
+                .context("failed to create checkpoint")?;
+            let checkpoint_created = checkpoint.is_some();
+
+            let validator_set_change = if let Some((checkpoint, validator_set_change)) = checkpoint {
+                // Asynchronously broadcast signature, if validating.
+                if let Some(ref ctx) = self.validator_ctx {
+                    // Do not resend past signatures.
+                    if !self.syncing().await {
+                        // Fetch any incomplete checkpoints synchronously because the state can't be shared across threads.
+                        let incomplete_checkpoints =
+                            checkpoint::unsigned_checkpoints(&self.gateway, &mut state, ctx.public_key)
+                                .context("failed to fetch incomplete checkpoints")?;
+
+                        debug_assert!(
+                            incomplete_checkpoints
+                                .iter()
+                                .any(|cp| cp.block_height == checkpoint.block_height
+                                    && cp.block_hash == checkpoint.block_hash),
+                            "the current checkpoint is incomplete"
+                        );
+
+                        let client = self.client.clone();
+                        let gateway = self.gateway.clone();
+                        let chain_id = state.chain_id();
+                        let height = checkpoint.block_height;
+                        let validator_ctx = ctx.clone();
+
+                        if let Some(ref gossip) = ctx.signature_gossip {
+                            // The collector needs the weight each quorum has to reach.
+                            let incomplete_checkpoints = incomplete_checkpoints
+                                .into_iter()
+                                .map(|cp| {
+                                    let height = cp.block_height.as_u64();
+                                    let threshold = self
+                                        .gateway
+                                        .checkpoint_quorum_threshold(&mut state, height)?;
+                                    Ok((cp, threshold))
+                                })
+                                .collect::<anyhow::Result<Vec<_>>>()
+                                .context("failed to fetch checkpoint quorum thresholds")?;
+
+                            let gossip = gossip.clone();
+
+                            self.supervisor.spawn("gossip checkpoint signatures", async move {
+                                let res = checkpoint::gossip_incomplete_signatures(
+                                    &client,
+                                    &validator_ctx,
+                                    &gossip,
+                                    &gateway,
+                                    chain_id,
+                                    incomplete_checkpoints,
+                                    height.as_u64(),
+                                )
+                                .await;
+
+                                if let Err(e) = res {
+                                    tracing::error!(error =? e, height = height.as_u64(), "error gossiping checkpoint signature");
+                                }
+                            });
+                        } else {
+                            self.supervisor.spawn("broadcast checkpoint signatures", async move {
+                                let res = checkpoint::broadcast_incomplete_signatures(
+                                    &client,
+                                    &validator_ctx,
+                                    &gateway,
+                                    chain_id,
+                                    incomplete_checkpoints,
+                                )
+                                .await;
+
+                                if let Err(e) = res {
+                                    tracing::error!(error =? e, height = height.as_u64(), "error broadcasting checkpoint signature");
+                                }
+                            });
+                        }
+                    }
+                }
+
+                validator_set_change
+            } else {
+                None
+            };
+
+            let power_updates = match validator_set_change {
+                Some(ref change) => {
+                    tracing::info!(
+                        configuration_number = change.configuration_number,
+                        num_changes = change.changes.len(),
+                        "validator set changed"
+                    );
+                    // Nobody might be listening, which is fine.
+                    let _ = self.validator_changes.send(change.clone());
+                    change.power_updates()
+                }
+                None => PowerUpdates::default(),
+            };
+
+            emit_block_gas_used(&state);
+            self.delivery_logging
+                .log_block(state.block_height(), state.delivery_stats());
+
+            let summary = BlockSummary::new(state.delivery_stats(), checkpoint_created);
+
+            emit!(BlockExecuted {
+                block_height: state.block_height() as u64,
+                txs: summary.txs,
+                failed_txs: summary.failed_txs,
+                gas_used: summary.gas_used,
+                events: summary.events,
+                checkpoint_created: summary.checkpoint_created,
+            });
+
+            let ret = FvmEndRet {
+                power_updates,
+                validator_set_change,
+                summary,
+            };
+
+            Ok((state, ret))
+        }
+        .instrument(span)
+        .await
+    }
+}
+
+/// Summarize the gas used by cron and the messages of the block, by the actor they were sent to.
+fn emit_block_gas_used<DB>(state: &FvmExecState<DB>)
+where
+    DB: Blockstore + Clone + 'static,
+{
+    let gas = GasBreakdown::new(state.gas_used_by_actor());
+
+    emit!(BlockGasUsed {
+        block_height: state.block_height() as u64,
+        total: gas.total,
+        cron: gas.cron,
+        gateway: gas.gateway,
+        machinelearning: gas.machinelearning,
+        other: gas.other(),
+        by_actor: &gas.by_actor,
+    });
+}
+
+/// The gas used in a block, split between the actors tracked by the metrics.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct GasBreakdown {
+    total: u64,
+    cron: u64,
+    gateway: u64,
+    machinelearning: u64,
+    /// Comma separated `address=gas` pairs of every actor, the most expensive first.
+    by_actor: String,
+}
+
+impl GasBreakdown {
+    fn new(usage: &HashMap<Address, u64>) -> Self {
+        let gas_used = |addr| usage.get(&addr).copied().unwrap_or_default();
+
+        let mut by_actor = usage.iter().collect::<Vec<_>>();
+        by_actor.sort_by_key(|(addr, gas)| (std::cmp::Reverse(**gas), addr.to_string()));
+        let by_actor = by_actor
+            .into_iter()
+            .map(|(addr, gas)| format!("{addr}={gas}"))
+            .collect::<Vec<_>>()
+            .join(",");
+
+        Self {
+            total: usage.values().sum(),
+            cron: gas_used(cron::CRON_ACTOR_ADDR),
+            gateway: gas_used(ipc::GATEWAY_ACTOR_ADDR),
+            machinelearning: gas_used(machinelearning::MACHINELEARNING_ACTOR_ADDR),
+            by_actor,
+        }
+    }
+
+    /// The gas used by every actor not broken out on its own.
+    fn other(&self) -> u64 {
+        self.total - self.cron - self.gateway - self.machinelearning
+    }
+}
+
+/// The implicit messages executed by the system at the beginning of every block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ImplicitMessage {
+    Cron,
+    ChainMetadata,
+    MlSelfTest,
+}
+
+impl ImplicitMessage {
+    /// Whether a failure has to halt the block processing. The others only feed auxiliary
+    /// actors, which shouldn't be able to halt the chain.
+    fn is_critical(&self) -> bool {
+        matches!(self, Self::Cron)
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Cron => "cron",
+            Self::ChainMetadata => "chainmetadata",
+            Self::MlSelfTest => "ml_selftest",
+        }
+    }
+}
+
+/// Return an error if a critical implicit message failed, or log and skip the failure of
+/// the others, returning whether the message succeeded.
+///
+/// Only the exit codes of the messages are skipped: every validator applies the same implicit
+/// messages to the same state, so they all skip the same failures. Errors executing them,
+/// e.g. failing to read the blockstore, can differ between nodes and are returned by the callers.
+fn check_implicit(
+    msg: ImplicitMessage,
+    height: ChainEpoch,
+    apply_ret: &ApplyRet,
+) -> anyhow::Result<bool> {
+    let Some(err) = &apply_ret.failure_info else {
+        return Ok(true);
+    };
+    if msg.is_critical() {
+        return Err(anyhow!("failed to apply {} message: {}", msg.name(), err));
+    }
+    let reason = err.to_string();
+    tracing::warn!(
+        height,
+        message = msg.name(),
+        error = reason,
+        "non-critical implicit message failed"
+    );
+    emit!(ImplicitMessageFailed {
+        block_height: height as u64,
+        message: msg.name(),
+        reason: &reason,
+    });
+    Ok(false)
+}
+
+impl<DB, TC> FvmMessageInterpreter<DB, TC>
+where
+    DB: Blockstore + Clone + 'static + Send + Sync,
+    TC: Client + Clone + Send + Sync + 'static,
+{
+    /// Push the hash and the timestamp of the current block to the chainmetadata actor.
+    fn push_chain_metadata(
+        &self,
+        state: &mut FvmExecState<DB>,
+        height: ChainEpoch,
+    ) -> anyhow::Result<()> {
+        if let Some(block_hash) = state.block_hash() {
+            let params = fvm_ipld_encoding::RawBytes::serialize(
+                fendermint_actor_chainmetadata::PushBlockParams {
+                    epoch: height,
+                    block: block_hash,
+                    timestamp: state.timestamp().0,
+                },
+            )?;
+
+            let msg = FvmMessage {
+                from: system::SYSTEM_ACTOR_ADDR,
+                to: chainmetadata::CHAINMETADATA_ACTOR_ADDR,
+                sequence: height as u64,
+                gas_limit: state.implicit_gas_limits().chain_metadata,
+                method_num: fendermint_actor_chainmetadata::Method::PushBlockHash as u64,
+                params,
+                value: Default::default(),
+                version: Default::default(),
+                gas_fee_cap: Default::default(),
+                gas_premium: Default::default(),
+            };
+
+            let (apply_ret, _) = state.execute_implicit(msg)?;
+            check_implicit(ImplicitMessage::ChainMetadata, height, &apply_ret)?;
         }
+        Ok(())
+    }
 
+    /// Train and predict with each algorithm of the machine learning actor on a fixed dataset,
+    /// as a smoke test of the syscalls.
+    fn run_ml_selftest(
+        &self,
+        state: &mut FvmExecState<DB>,
+        height: ChainEpoch,
+    ) -> anyhow::Result<()> {
         {
             tracing::info!("Running linear regression test");
             let input_matrix: Vec<Vec<i64>> = vec![
@@ -158,8 +583,9 @@ where
             ];
             let params = fvm_ipld_encoding::RawBytes::serialize(
                 fendermint_actor_machinelearning::TrainLinearRegressionParams {
-                    input_matrix,
+                    input_matrix: input_matrix.into(),
                     labels,
+                    scaler: None,
                 },
             )?;
 
@@ -167,7 +593,7 @@ where
                 from: system::SYSTEM_ACTOR_ADDR,
                 to: machinelearning::MACHINELEARNING_ACTOR_ADDR,
                 sequence: height as u64,
-                gas_limit,
+                gas_limit: state.implicit_gas_limits().machine_learning,
                 method_num: fendermint_actor_machinelearning::Method::TrainLinearRegression as u64,
                 params,
                 value: Default::default(),
@@ -178,11 +604,11 @@ where
 
             let (apply_ret, _) = state.execute_implicit(msg)?;
 
-            if let Some(err) = apply_ret.failure_info {
-                anyhow::bail!("failed to apply customsyscall message: {}", err);
+            if !check_implicit(ImplicitMessage::MlSelfTest, height, &apply_ret)? {
+                return Ok(());
             }
 
-            let val: Vec<u8> = apply_ret.msg_receipt.return_data.deserialize().unwrap();
+            let val: Vec<u8> = apply_ret.msg_receipt.return_data.deserialize()?;
             tracing::info!(
                 "machinelearning actor address: {}",
                 machinelearning::MACHINELEARNING_ACTOR_ADDR
@@ -206,8 +632,9 @@ where
 
             let predict_params = fvm_ipld_encoding::RawBytes::serialize(
                 fendermint_actor_machinelearning::PredictLinearRegressionParams {
-                    input_matrix: prediction_input_matrix,
+                    input_matrix: prediction_input_matrix.into(),
                     model: val,
+                    scaler: None,
                 },
             )?;
 
@@ -215,7 +642,7 @@ where
                 from: system::SYSTEM_ACTOR_ADDR,
                 to: machinelearning::MACHINELEARNING_ACTOR_ADDR,
                 sequence: height as u64,
-                gas_limit,
+                gas_limit: state.implicit_gas_limits().machine_learning,
                 method_num: fendermint_actor_machinelearning::Method::PredictLinearRegression
                     as u64,
                 params: predict_params,
@@ -227,18 +654,12 @@ where
 
             let (predict_apply_ret, _) = state.execute_implicit(predict_msg)?;
 
-            if let Some(err) = predict_apply_ret.failure_info {
-                anyhow::bail!(
-                    "failed to apply predict_linear_regression_syscall message: {}",
-                    err
-                );
+            if !check_implicit(ImplicitMessage::MlSelfTest, height, &predict_apply_ret)? {
+                return Ok(());
             }
 
-            let prediction_results: Vec<i64> = predict_apply_ret
-                .msg_receipt
-                .return_data
-                .deserialize()
-                .unwrap();
+            let prediction_results: Vec<i64> =
+                predict_apply_ret.msg_receipt.return_data.deserialize()?;
 
             tracing::info!("the prediction results are: {:?}", prediction_results);
         }
@@ -271,8 +692,9 @@ where
             let labels: Vec<i64> = vec![0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1];
             let params = fvm_ipld_encoding::RawBytes::serialize(
                 fendermint_actor_machinelearning::TrainLinearRegressionParams {
-                    input_matrix,
+                    input_matrix: input_matrix.into(),
                     labels,
+                    scaler: None,
                 },
             )?;
 
@@ -280,7 +702,7 @@ where
                 from: system::SYSTEM_ACTOR_ADDR,
                 to: machinelearning::MACHINELEARNING_ACTOR_ADDR,
                 sequence: height as u64,
-                gas_limit,
+                gas_limit: state.implicit_gas_limits().machine_learning,
                 method_num: fendermint_actor_machinelearning::Method::TrainLogisticRegression
                     as u64,
                 params,
@@ -292,11 +714,11 @@ where
 
             let (apply_ret, _) = state.execute_implicit(msg)?;
 
-            if let Some(err) = apply_ret.failure_info {
-                anyhow::bail!("failed to apply customsyscall message: {}", err);
+            if !check_implicit(ImplicitMessage::MlSelfTest, height, &apply_ret)? {
+                return Ok(());
             }
 
-            let val: Vec<u8> = apply_ret.msg_receipt.return_data.deserialize().unwrap();
+            let val: Vec<u8> = apply_ret.msg_receipt.return_data.deserialize()?;
             tracing::info!(
                 "machinelearning actor address: {}",
                 machinelearning::MACHINELEARNING_ACTOR_ADDR
@@ -317,8 +739,9 @@ where
 
             let predict_params = fvm_ipld_encoding::RawBytes::serialize(
                 fendermint_actor_machinelearning::PredictLogisticRegressionParams {
-                    input_matrix: prediction_input_matrix,
+                    input_matrix: prediction_input_matrix.into(),
                     model: val,
+                    scaler: None,
                 },
             )?;
 
@@ -326,7 +749,7 @@ where
                 from: system::SYSTEM_ACTOR_ADDR,
                 to: machinelearning::MACHINELEARNING_ACTOR_ADDR,
                 sequence: height as u64,
-                gas_limit,
+                gas_limit: state.implicit_gas_limits().machine_learning,
                 method_num: fendermint_actor_machinelearning::Method::PredictLogisticRegression
                     as u64,
                 params: predict_params,
@@ -338,18 +761,12 @@ where
 
             let (predict_apply_ret, _) = state.execute_implicit(predict_msg)?;
 
-            if let Some(err) = predict_apply_ret.failure_info {
-                anyhow::bail!(
-                    "failed to apply predict_logistic_regression_syscall message: {}",
-                    err
-                );
+            if !check_implicit(ImplicitMessage::MlSelfTest, height, &predict_apply_ret)? {
+                return Ok(());
             }
 
-            let prediction_results: Vec<i64> = predict_apply_ret
-                .msg_receipt
-                .return_data
-                .deserialize()
-                .unwrap();
+            let prediction_results: Vec<i64> =
+                predict_apply_ret.msg_receipt.return_data.deserialize()?;
 
             tracing::info!("the prediction results are: {:?}", prediction_results);
         }
@@ -367,8 +784,9 @@ where
             let labels: Vec<i64> = vec![100, 200, 300, 400, 500];
             let params = fvm_ipld_encoding::RawBytes::serialize(
                 fendermint_actor_machinelearning::TrainKNNRegressionParams {
-                    input_matrix,
+                    input_matrix: input_matrix.into(),
                     labels,
+                    scaler: None,
                 },
             )?;
 
@@ -376,7 +794,7 @@ where
                 from: system::SYSTEM_ACTOR_ADDR,
                 to: machinelearning::MACHINELEARNING_ACTOR_ADDR,
                 sequence: height as u64,
-                gas_limit,
+                gas_limit: state.implicit_gas_limits().machine_learning,
                 method_num: fendermint_actor_machinelearning::Method::TrainKNNRegression as u64,
                 params,
                 value: Default::default(),
@@ -387,11 +805,11 @@ where
 
             let (apply_ret, _) = state.execute_implicit(msg)?;
 
-            if let Some(err) = apply_ret.failure_info {
-                anyhow::bail!("failed to apply customsyscall message: {}", err);
+            if !check_implicit(ImplicitMessage::MlSelfTest, height, &apply_ret)? {
+                return Ok(());
             }
 
-            let val: Vec<u8> = apply_ret.msg_receipt.return_data.deserialize().unwrap();
+            let val: Vec<u8> = apply_ret.msg_receipt.return_data.deserialize()?;
             tracing::info!(
                 "machinelearning actor address: {}",
                 machinelearning::MACHINELEARNING_ACTOR_ADDR
@@ -412,8 +830,9 @@ where
 
             let predict_params = fvm_ipld_encoding::RawBytes::serialize(
                 fendermint_actor_machinelearning::PredictKNNRegressionParams {
-                    input_matrix: prediction_input_matrix,
+                    input_matrix: prediction_input_matrix.into(),
                     model: val,
+                    scaler: None,
                 },
             )?;
 
@@ -421,7 +840,7 @@ where
                 from: system::SYSTEM_ACTOR_ADDR,
                 to: machinelearning::MACHINELEARNING_ACTOR_ADDR,
                 sequence: height as u64,
-                gas_limit,
+                gas_limit: state.implicit_gas_limits().machine_learning,
                 method_num: fendermint_actor_machinelearning::Method::PredictKNNRegression as u64,
                 params: predict_params,
                 value: Default::default(),
@@ -432,122 +851,111 @@ where
 
             let (predict_apply_ret, _) = state.execute_implicit(predict_msg)?;
 
-            if let Some(err) = predict_apply_ret.failure_info {
-                anyhow::bail!(
-                    "failed to apply predict_knn_regression_syscall message: {}",
-                    err
-                );
+            if !check_implicit(ImplicitMessage::MlSelfTest, height, &predict_apply_ret)? {
+                return Ok(());
             }
 
-            let prediction_results: Vec<i64> = predict_apply_ret
-                .msg_receipt
-                .return_data
-                .deserialize()
-                .unwrap();
+            let prediction_results: Vec<i64> =
+                predict_apply_ret.msg_receipt.return_data.deserialize()?;
 
             tracing::info!("the prediction results are: {:?}", prediction_results);
         }
 
-        let ret = FvmApplyRet {
-            apply_ret,
-            from,
-            to,
-            method_num,
-            gas_limit,
-            emitters,
-        };
-
-        Ok((state, ret))
+        Ok(())
     }
+}
 
-    async fn deliver(
-        &self,
-        mut state: Self::State,
-        msg: Self::Message,
-    ) -> anyhow::Result<(Self::State, Self::DeliverOutput)> {
-        let from = msg.from;
-        let to = msg.to;
-        let method_num = msg.method_num;
-        let gas_limit = msg.gas_limit;
-
-        let (apply_ret, emitters) = if from == system::SYSTEM_ACTOR_ADDR {
-            state.execute_implicit(msg)?
-        } else {
-            state.execute_explicit(msg)?
-        };
-
-        tracing::info!(
-            height = state.block_height(),
-            from = from.to_string(),
-            to = to.to_string(),
-            method_num = method_num,
-            exit_code = apply_ret.msg_receipt.exit_code.value(),
-            gas_used = apply_ret.msg_receipt.gas_used,
-            "tx delivered"
-        );
-
-        let ret = FvmApplyRet {
-            apply_ret,
-            from,
-            to,
-            method_num,
-            gas_limit,
-            emitters,
-        };
-
-        Ok((state, ret))
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use fendermint_vm_actor_interface::{cron, ipc, machinelearning};
+    use fvm::executor::{ApplyFailure, ApplyRet};
+    use fvm_ipld_encoding::RawBytes;
+    use fvm_shared::{address::Address, econ::TokenAmount, error::ExitCode, receipt::Receipt};
+
+    use super::{check_implicit, BlockSummary, DeliveryStats, GasBreakdown, ImplicitMessage};
+
+    fn apply_ret(exit_code: ExitCode) -> ApplyRet {
+        let zero = TokenAmount::from_atto(0);
+        ApplyRet {
+            msg_receipt: Receipt {
+                exit_code,
+                return_data: RawBytes::default(),
+                gas_used: 0,
+                events_root: None,
+            },
+            penalty: zero.clone(),
+            miner_tip: zero.clone(),
+            base_fee_burn: zero.clone(),
+            over_estimation_burn: zero.clone(),
+            refund: zero,
+            gas_refund: 0,
+            gas_burned: 0,
+            failure_info: (!exit_code.is_success())
+                .then(|| ApplyFailure::PreValidation("out of gas".into())),
+            exec_trace: Vec::new(),
+            events: Vec::new(),
+        }
     }
 
-    async fn end(&self, mut state: Self::State) -> anyhow::Result<(Self::State, Self::EndOutput)> {
-        let updates = if let Some((checkpoint, updates)) =
-            checkpoint::maybe_create_checkpoint(&self.gateway, &mut state)
-                .context("failed to create checkpoint")?
-        {
-            // Asynchronously broadcast signature, if validating.
-            if let Some(ref ctx) = self.validator_ctx {
-                // Do not resend past signatures.
-                if !self.syncing().await {
-                    // Fetch any incomplete checkpoints synchronously because the state can't be shared across threads.
-                    let incomplete_checkpoints =
-                        checkpoint::unsigned_checkpoints(&self.gateway, &mut state, ctx.public_key)
-                            .context("failed to fetch incomplete checkpoints")?;
-
-                    debug_assert!(
-                        incomplete_checkpoints
-                            .iter()
-                            .any(|cp| cp.block_height == checkpoint.block_height
-                                && cp.block_hash == checkpoint.block_hash),
-                        "the current checkpoint is incomplete"
-                    );
+    #[test]
+    fn only_critical_implicit_failures_halt() {
+        let ok = apply_ret(ExitCode::OK);
+        let fail = apply_ret(ExitCode::SYS_OUT_OF_GAS);
+        assert!(check_implicit(ImplicitMessage::Cron, 1, &fail).is_err());
+        assert!(!check_implicit(ImplicitMessage::ChainMetadata, 1, &fail).unwrap());
+        assert!(!check_implicit(ImplicitMessage::MlSelfTest, 1, &fail).unwrap());
+        assert!(check_implicit(ImplicitMessage::Cron, 1, &ok).unwrap());
+    }
 
-                    let client = self.client.clone();
-                    let gateway = self.gateway.clone();
-                    let chain_id = state.chain_id();
-                    let height = checkpoint.block_height;
-                    let validator_ctx = ctx.clone();
-
-                    tokio::spawn(async move {
-                        let res = checkpoint::broadcast_incomplete_signatures(
-                            &client,
-                            &validator_ctx,
-                            &gateway,
-                            chain_id,
-                            incomplete_checkpoints,
-                        )
-                        .await;
-
-                        if let Err(e) = res {
-                            tracing::error!(error =? e, height = height.as_u64(), "error broadcasting checkpoint signature");
-                        }
-                    });
-                }
+    #[test]
+    fn block_summary_counts_failures() {
+        let mut stats = DeliveryStats::default();
+        assert_eq!(BlockSummary::new(&stats, false), BlockSummary::default());
+
+        stats.record(ExitCode::OK, 10, 2);
+        stats.record(ExitCode::USR_ILLEGAL_ARGUMENT, 5, 0);
+        assert_eq!(
+            BlockSummary::new(&stats, true),
+            BlockSummary {
+                txs: 2,
+                failed_txs: 1,
+                gas_used: 15,
+                events: 2,
+                checkpoint_created: true,
             }
+        );
+    }
 
-            updates
-        } else {
-            PowerUpdates::default()
-        };
-
-        Ok((state, updates))
+    #[test]
+    fn gas_breakdown_by_actor() {
+        assert_eq!(GasBreakdown::new(&HashMap::new()), GasBreakdown::default());
+
+        let usage = HashMap::from([
+            (cron::CRON_ACTOR_ADDR, 10),
+            (ipc::GATEWAY_ACTOR_ADDR, 20),
+            (machinelearning::MACHINELEARNING_ACTOR_ADDR, 30),
+            (Address::new_id(1000), 50),
+            (Address::new_id(1001), 50),
+        ]);
+        let gas = GasBreakdown::new(&usage);
+
+        assert_eq!(gas.total, 160);
+        assert_eq!(gas.cron, 10);
+        assert_eq!(gas.gateway, 20);
+        assert_eq!(gas.machinelearning, 30);
+        assert_eq!(gas.other(), 100);
+
+        // The most expensive first, with ties in the order of the addresses.
+        assert_eq!(
+            gas.by_actor,
+            format!(
+                "f01000=50,f01001=50,{}=30,{}=20,{}=10",
+                machinelearning::MACHINELEARNING_ACTOR_ADDR,
+                ipc::GATEWAY_ACTOR_ADDR,
+                cron::CRON_ACTOR_ADDR
+            )
+        );
     }
 }
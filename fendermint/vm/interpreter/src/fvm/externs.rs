@@ -14,6 +14,7 @@ use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::{CborStore, DAG_CBOR};
 use fvm_shared::clock::ChainEpoch;
 
+use super::migrations::ChainMetadataStateV0;
 use super::store::ReadOnlyBlockstore;
 
 pub struct FendermintExterns<DB>
@@ -94,7 +95,7 @@ where
 
         // get the chain metadata actor state from the blockstore
         let actor_state: fendermint_actor_chainmetadata::State =
-            match state_tree.store().get_cbor(&actor_state_cid) {
+            match load_chainmetadata_state(state_tree.store(), &actor_state_cid) {
                 Ok(Some(v)) => v,
                 Ok(None) => {
                     return Err(anyhow!(
@@ -125,3 +126,26 @@ where
 }
 
 impl<DB> Externs for FendermintExterns<DB> where DB: Blockstore + Clone + 'static {}
+
+/// Decode the state of the chainmetadata actor, which has the layout from before the block
+/// timestamps were recorded until the upgrade running
+/// [chainmetadata_timestamps](super::migrations::chainmetadata_timestamps), so that contracts
+/// can look up the block hashes in the meantime.
+///
+/// The old layout is returned without the timestamps, which must not be looked up.
+fn load_chainmetadata_state<BS: Blockstore>(
+    store: &BS,
+    cid: &Cid,
+) -> anyhow::Result<Option<fendermint_actor_chainmetadata::State>> {
+    match store.get_cbor::<fendermint_actor_chainmetadata::State>(cid) {
+        Ok(state) => Ok(state),
+        Err(err) => match store.get_cbor::<ChainMetadataStateV0>(cid) {
+            Ok(state) => Ok(state.map(|old| fendermint_actor_chainmetadata::State {
+                blockhashes: old.blockhashes,
+                lookback_len: old.lookback_len,
+                timestamps: Cid::default(),
+            })),
+            Err(_) => Err(err),
+        },
+    }
+}
@@ -10,16 +10,19 @@ use async_trait::async_trait;
 use ethers::abi::Tokenize;
 use ethers::core::types as et;
 use fendermint_actor_eam::PermissionModeParams;
+use fendermint_actor_machinelearning::{InputLimits, TrainingPermissionMode};
 use fendermint_eth_hardhat::{Hardhat, FQN};
 use fendermint_vm_actor_interface::diamond::{EthContract, EthContractMap};
 use fendermint_vm_actor_interface::eam::EthAddress;
 use fendermint_vm_actor_interface::ipc::IPC_CONTRACTS;
 use fendermint_vm_actor_interface::{
-    account, burntfunds, chainmetadata, cron, eam, init, ipc, machinelearning, reward, system,
-    EMPTY_ARR,
+    account, burntfunds, chainmetadata, cron, eam, faucet, init, ipc, machinelearning, mljobs,
+    reward, system, EMPTY_ARR,
 };
 use fendermint_vm_core::{chainid, Timestamp};
-use fendermint_vm_genesis::{ActorMeta, Genesis, Power, PowerScale, Validator};
+use fendermint_vm_genesis::{
+    ActorMeta, Genesis, ImplicitGasLimits, MlLimits, PermissionMode, Power, PowerScale, Validator,
+};
 use fvm_ipld_blockstore::Blockstore;
 use fvm_shared::chainid::ChainID;
 use fvm_shared::econ::TokenAmount;
@@ -41,6 +44,8 @@ pub struct FvmGenesisOutput {
     pub power_scale: PowerScale,
     pub circ_supply: TokenAmount,
     pub validators: Vec<Validator<Power>>,
+    pub ml_limits: Option<MlLimits>,
+    pub implicit_gas: Option<ImplicitGasLimits>,
 }
 
 #[async_trait]
@@ -67,6 +72,7 @@ where
     /// * rewards (placeholder)
     /// * accounts
     /// * IPC
+    /// * application contracts
     ///
     /// TODO:
     /// * faucet?
@@ -108,6 +114,8 @@ where
             base_fee: genesis.base_fee,
             power_scale: genesis.power_scale,
             validators,
+            ml_limits: genesis.ml_limits,
+            implicit_gas: genesis.implicit_gas,
         };
 
         // STAGE 0: Declare the built-in EVM contracts we'll have to deploy.
@@ -164,7 +172,7 @@ where
             genesis.chain_name.clone(),
             &genesis.accounts,
             &eth_builtin_ids,
-            eth_libs.len() as u64,
+            (eth_libs.len() + genesis.contracts.len()) as u64,
         )
         .context("failed to create init state")?;
 
@@ -248,16 +256,74 @@ where
             )
             .context("failed to create chainmetadata actor")?;
 
-        // Initialize the customsyscall actor which gives an example of calling a custom syscall
+        // Initialize the machine learning actor, with the policy restricting who can train models.
+        let ml_state = fendermint_actor_machinelearning::State::new(
+            &state.store(),
+            fendermint_actor_machinelearning::ConstructorParams {
+                permission_mode: match genesis.ml_training_policy.permission_mode {
+                    PermissionMode::Unrestricted => TrainingPermissionMode::Unrestricted,
+                    PermissionMode::AllowList { addresses } => TrainingPermissionMode::AllowList(
+                        addresses.into_iter().map(|a| a.0).collect(),
+                    ),
+                },
+                training_fee: genesis.ml_training_policy.training_fee,
+                // Challenges of off-chain jobs are re-executed by the jobs actor.
+                trusted_callers: vec![mljobs::MLJOBS_ACTOR_ADDR],
+                // The actor checks the limits itself as well, for the calls the node can't see.
+                limits: genesis.ml_limits.map(|limits| InputLimits {
+                    max_rows: limits.max_rows,
+                    max_cols: limits.max_cols,
+                    max_bytes: limits.max_params_bytes,
+                }),
+            },
+        )?;
         state
             .create_custom_actor(
                 fendermint_actor_machinelearning::MACHINELEARNING_ACTOR_NAME,
                 machinelearning::MACHINELEARNING_ACTOR_ID,
-                &EMPTY_ARR,
+                &ml_state,
+                TokenAmount::zero(),
+                None,
+            )
+            .context("failed to create machinelearning actor")?;
+
+        // Initialize the machine learning jobs actor, which accepts the results of training jobs
+        // executed off-chain, and reverts them if they are successfully challenged.
+        let mljobs_state = fendermint_actor_mljobs::State::new(
+            &state.store(),
+            fendermint_actor_mljobs::ConstructorParams {
+                ml_actor: machinelearning::MACHINELEARNING_ACTOR_ADDR,
+                challenge_window: genesis.ml_training_policy.job_challenge_window,
+                min_deposit: genesis.ml_training_policy.job_deposit,
+            },
+        )?;
+        state
+            .create_custom_actor(
+                fendermint_actor_mljobs::MLJOBS_ACTOR_NAME,
+                mljobs::MLJOBS_ACTOR_ID,
+                &mljobs_state,
                 TokenAmount::zero(),
                 None,
             )
-            .context("failed to create customsyscall actor")?;
+            .context("failed to create mljobs actor")?;
+
+        // Initialize the faucet actor, which distributes its balance as test funds.
+        let faucet_state = fendermint_actor_faucet::State::new(
+            &state.store(),
+            fendermint_actor_faucet::ConstructorParams {
+                drip_amount: genesis.faucet.drip_amount,
+                cooldown: genesis.faucet.cooldown,
+            },
+        )?;
+        state
+            .create_custom_actor(
+                fendermint_actor_faucet::FAUCET_ACTOR_NAME,
+                faucet::FAUCET_ACTOR_ID,
+                &faucet_state,
+                genesis.faucet.balance,
+                None,
+            )
+            .context("failed to create faucet actor")?;
 
         let eam_state = fendermint_actor_eam::State::new(
             state.store(),
@@ -384,6 +450,26 @@ where
             };
         }
 
+        // STAGE 4: Deploy the application contracts, with the dynamic IDs after the libraries.
+        for contract in genesis.contracts {
+            let eth_addr = state
+                .create_evm_actor(next_id, contract.init_code)
+                .with_context(|| format!("failed to create contract actor {}", contract.name))?;
+
+            let id_addr = et::Address::from(EthAddress::from_id(next_id).0);
+            let eth_addr = et::Address::from(eth_addr.0);
+
+            tracing::info!(
+                actor_id = next_id,
+                ?eth_addr,
+                ?id_addr,
+                contract_name = contract.name.as_str(),
+                "deployed application contract"
+            );
+
+            next_id += 1;
+        }
+
         Ok((state, out))
     }
 }
@@ -531,11 +617,11 @@ where
     }
 }
 
-/// Sum of balances in the genesis accounts.
+/// Sum of balances in the genesis accounts and the faucet.
 fn circ_supply(g: &Genesis) -> TokenAmount {
     g.accounts
         .iter()
-        .fold(TokenAmount::zero(), |s, a| s + a.balance.clone())
+        .fold(g.faucet.balance.clone(), |s, a| s + a.balance.clone())
 }
 
 #[cfg(test)]
@@ -543,7 +629,7 @@ mod tests {
     use std::{str::FromStr, sync::Arc};
 
     use cid::Cid;
-    use fendermint_vm_genesis::{ipc::IpcParams, Genesis};
+    use fendermint_vm_genesis::{ipc::IpcParams, Contract, Genesis};
     use fvm::engine::MultiEngine;
     use quickcheck::Arbitrary;
     use tendermint_rpc::{MockClient, MockRequestMethodMatcher};
@@ -626,6 +712,39 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn load_genesis_with_contracts() {
+        let bundle = read_bundle();
+        let custom_actors_bundle = read_custom_actors_bundle();
+        let interpreter = make_interpreter();
+        let multi_engine = Arc::new(MultiEngine::default());
+
+        // PUSH1 0 PUSH1 0 RETURN deploys a contract without code; REVERT instead fails.
+        for (init_code, ok) in [("60006000f3", true), ("60006000fd", false)] {
+            let mut genesis = make_genesis();
+            genesis.contracts.push(Contract {
+                name: "Empty".to_string(),
+                init_code: hex::decode(init_code).unwrap(),
+            });
+
+            let store = MemoryBlockstore::new();
+            let state =
+                FvmGenesisState::new(store, multi_engine.clone(), &bundle, &custom_actors_bundle)
+                    .await
+                    .expect("failed to create state");
+
+            let res = interpreter.init(state, genesis).await;
+
+            match res {
+                Ok(_) => assert!(ok, "reverting contract deployed"),
+                Err(e) => {
+                    assert!(!ok, "failed to deploy contract: {e:#}");
+                    assert!(e.to_string().contains("Empty"));
+                }
+            }
+        }
+    }
+
     // This is a sort of canary test, if it fails means something changed in the way we do genesis,
     // which is probably fine, but it's better to know about it, and if anybody doesn't get the same
     // then we might have some non-determinism.
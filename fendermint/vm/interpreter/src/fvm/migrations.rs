@@ -0,0 +1,324 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Migrations of the custom actor states whose layout changed, to be scheduled with the
+//! [UpgradeScheduler](super::upgrades::UpgradeScheduler) on chains created before the change.
+//!
+//! The migrations only rewrite the state; the upgrade has to deploy the new code of the actor as well.
+
+use anyhow::{bail, Context};
+use cid::{multihash::Code, Cid};
+use fendermint_actor_machinelearning::{
+    ConstructorParams as MlConstructorParams, InputLimits, ModelId, ScalerId,
+    TrainingPermissionMode,
+};
+use fendermint_vm_actor_interface::{
+    chainmetadata::CHAINMETADATA_ACTOR_ID, machinelearning::MACHINELEARNING_ACTOR_ID,
+};
+use fendermint_vm_genesis::{ImplicitGasLimits, MlLimits};
+use fvm_ipld_blockstore::Blockstore;
+use fvm_ipld_encoding::{
+    tuple::{Deserialize_tuple, Serialize_tuple},
+    CborStore,
+};
+use fvm_shared::{address::Address, econ::TokenAmount, ActorID};
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::state::FvmExecState;
+
+/// Layout of the chainmetadata actor state before the block timestamps were recorded.
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub(super) struct ChainMetadataStateV0 {
+    pub blockhashes: Cid,
+    pub lookback_len: u64,
+}
+
+/// Add the empty AMT of the block timestamps to the chainmetadata actor state.
+///
+/// The timestamps of the blocks before the upgrade are unknown, so looking them up returns nothing.
+pub fn chainmetadata_timestamps<DB>(state: &mut FvmExecState<DB>) -> anyhow::Result<()>
+where
+    DB: Blockstore + Clone + 'static,
+{
+    let Some(old) = read_actor_state::<_, ChainMetadataStateV0>(state, CHAINMETADATA_ACTOR_ID)?
+    else {
+        return Ok(());
+    };
+
+    let new =
+        fendermint_actor_chainmetadata::State::new(state.state_tree().store(), old.lookback_len)?;
+
+    let new = fendermint_actor_chainmetadata::State {
+        blockhashes: old.blockhashes,
+        ..new
+    };
+
+    write_actor_state(state, CHAINMETADATA_ACTOR_ID, &new)
+}
+
+/// Give the machine learning actor the state it has since training can be restricted; chains
+/// created before that have an empty state. Training stays open to everyone, without a fee.
+pub fn machinelearning_state<DB>(state: &mut FvmExecState<DB>) -> anyhow::Result<()>
+where
+    DB: Blockstore + Clone + 'static,
+{
+    machinelearning_state_with(state, MlConstructorParams::default())
+}
+
+/// Same as [machinelearning_state], with the permission mode, the fee and the trusted callers
+/// to start with, e.g. to schedule with a closure setting them.
+pub fn machinelearning_state_with<DB>(
+    state: &mut FvmExecState<DB>,
+    params: MlConstructorParams,
+) -> anyhow::Result<()>
+where
+    DB: Blockstore + Clone + 'static,
+{
+    let Some(old) = read_actor_state::<_, Vec<()>>(state, MACHINELEARNING_ACTOR_ID)? else {
+        return Ok(());
+    };
+    if !old.is_empty() {
+        bail!("the machine learning actor state is not empty");
+    }
+
+    let new = fendermint_actor_machinelearning::State::new(state.state_tree().store(), params)?;
+
+    write_actor_state(state, MACHINELEARNING_ACTOR_ID, &new)
+}
+
+/// Layout of the machine learning actor state before the storage of the listed models was
+/// accounted for.
+#[derive(Serialize_tuple, Deserialize_tuple)]
+struct MachineLearningStateV1 {
+    permission_mode: TrainingPermissionMode,
+    training_fee: TokenAmount,
+    trusted_callers: Vec<Address>,
+    scalers: Cid,
+    next_scaler_id: ScalerId,
+    models: Cid,
+    next_model_id: ModelId,
+}
+
+/// Account for the storage of the models listed before the machine learning actor did, and
+/// add the empty AMT of the datasets.
+pub fn machinelearning_storage<DB>(state: &mut FvmExecState<DB>) -> anyhow::Result<()>
+where
+    DB: Blockstore + Clone + 'static,
+{
+    let Some(old) = read_actor_state::<_, MachineLearningStateV1>(state, MACHINELEARNING_ACTOR_ID)?
+    else {
+        return Ok(());
+    };
+
+    let params = MlConstructorParams {
+        permission_mode: old.permission_mode,
+        training_fee: old.training_fee,
+        trusted_callers: old.trusted_callers,
+        limits: None,
+    };
+    let new = fendermint_actor_machinelearning::State::new(state.state_tree().store(), params)?;
+
+    let mut new = fendermint_actor_machinelearning::State {
+        scalers: old.scalers,
+        next_scaler_id: old.next_scaler_id,
+        models: old.models,
+        next_model_id: old.next_model_id,
+        ..new
+    };
+    new.recount_storage(state.state_tree().store())?;
+
+    write_actor_state(state, MACHINELEARNING_ACTOR_ID, &new)
+}
+
+/// Change the limits on the inputs of the machine learning actor, both the ones the node checks
+/// before executing a message and the ones the actor checks on every call, which have to match.
+///
+/// This isn't a layout change, but the limits can only be changed by an upgrade like this one.
+pub fn machinelearning_limits<DB>(
+    state: &mut FvmExecState<DB>,
+    limits: Option<MlLimits>,
+) -> anyhow::Result<()>
+where
+    DB: Blockstore + Clone + 'static,
+{
+    state.update_ml_limits(|l| *l = limits);
+
+    let Some(mut st) = read_actor_state::<_, fendermint_actor_machinelearning::State>(
+        state,
+        MACHINELEARNING_ACTOR_ID,
+    )?
+    else {
+        return Ok(());
+    };
+    st.limits = limits.map(|limits| InputLimits {
+        max_rows: limits.max_rows,
+        max_cols: limits.max_cols,
+        max_bytes: limits.max_params_bytes,
+    });
+
+    write_actor_state(state, MACHINELEARNING_ACTOR_ID, &st)
+}
+
+/// Change the gas limits of the implicit messages sent by the system actor in every block,
+/// or go back to the defaults with `None`.
+///
+/// Like the limits of the machine learning actor, these affect consensus, so they can only be
+/// changed by an upgrade scheduled at the same height on every validator.
+pub fn implicit_gas_limits<DB>(
+    state: &mut FvmExecState<DB>,
+    limits: Option<ImplicitGasLimits>,
+) -> anyhow::Result<()>
+where
+    DB: Blockstore + Clone + 'static,
+{
+    state.update_implicit_gas(|l| *l = limits);
+    Ok(())
+}
+
+/// Decode the state of an actor, or return `None` if the chain doesn't have it.
+fn read_actor_state<DB, S>(state: &FvmExecState<DB>, id: ActorID) -> anyhow::Result<Option<S>>
+where
+    DB: Blockstore + Clone + 'static,
+    S: DeserializeOwned,
+{
+    let Some(actor) = state.state_tree().get_actor(id)? else {
+        return Ok(None);
+    };
+    let st = state
+        .state_tree()
+        .store()
+        .get_cbor(&actor.state)
+        .with_context(|| format!("failed to decode the state of actor {id}"))?
+        .with_context(|| format!("the state of actor {id} is missing"))?;
+    Ok(Some(st))
+}
+
+/// Replace the state of an existing actor.
+fn write_actor_state<DB, S>(state: &mut FvmExecState<DB>, id: ActorID, st: &S) -> anyhow::Result<()>
+where
+    DB: Blockstore + Clone + 'static,
+    S: Serialize,
+{
+    let mut actor = state
+        .state_tree()
+        .get_actor(id)?
+        .with_context(|| format!("actor {id} not found"))?;
+
+    actor.state = state
+        .state_tree_mut()
+        .store()
+        .put_cbor(st, Code::Blake2b256)
+        .with_context(|| format!("failed to store the state of actor {id}"))?;
+
+    state.state_tree_mut().set_actor(id, actor);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use fendermint_vm_actor_interface::chainmetadata::CHAINMETADATA_ACTOR_ID;
+    use fendermint_vm_genesis::Genesis;
+    use fvm::externs::Chain;
+    use quickcheck::Arbitrary;
+    use tendermint_rpc::{MockClient, MockRequestMethodMatcher};
+
+    use crate::fvm::bundle::{bundle_path, contracts_path, custom_actors_bundle_path};
+    use crate::fvm::externs::FendermintExterns;
+    use crate::fvm::state::FvmExecState;
+    use crate::fvm::store::memory::MemoryBlockstore;
+    use crate::fvm::upgrades::UpgradeScheduler;
+    use crate::fvm::FvmMessageInterpreter;
+    use crate::simulator::Simulator;
+
+    use super::{
+        chainmetadata_timestamps, read_actor_state, write_actor_state, ChainMetadataStateV0,
+    };
+
+    type TestSimulator =
+        Simulator<FvmMessageInterpreter<MemoryBlockstore, MockClient<MockRequestMethodMatcher>>>;
+
+    async fn make_simulator() -> TestSimulator {
+        let (client, _) = MockClient::new(MockRequestMethodMatcher::default());
+        let interpreter = FvmMessageInterpreter::new(
+            client,
+            None,
+            contracts_path(),
+            1.05,
+            1.05,
+            false,
+            UpgradeScheduler::new(),
+        );
+        let bundle = std::fs::read(bundle_path()).expect("failed to read bundle");
+        let custom_actors_bundle =
+            std::fs::read(custom_actors_bundle_path()).expect("failed to read custom bundle");
+        let genesis = Genesis::arbitrary(&mut quickcheck::Gen::new(5));
+
+        Simulator::new(interpreter, genesis, &bundle, &custom_actors_bundle)
+            .await
+            .expect("failed to create simulator")
+    }
+
+    /// Put the state of the chainmetadata actor back into the layout it had before the
+    /// timestamps, keeping the block hashes recorded so far.
+    fn downgrade_chainmetadata(sim: &TestSimulator) -> FvmExecState<MemoryBlockstore> {
+        let mut state = sim.exec_state().unwrap();
+        let new = read_actor_state::<_, fendermint_actor_chainmetadata::State>(
+            &state,
+            CHAINMETADATA_ACTOR_ID,
+        )
+        .unwrap()
+        .expect("chainmetadata actor exists");
+
+        let old = ChainMetadataStateV0 {
+            blockhashes: new.blockhashes,
+            lookback_len: new.lookback_len,
+        };
+        write_actor_state(&mut state, CHAINMETADATA_ACTOR_ID, &old).unwrap();
+        state
+    }
+
+    #[tokio::test]
+    async fn chainmetadata_timestamps_migrates_old_state() {
+        let mut sim = make_simulator().await;
+        let block = sim.produce_block(Vec::new()).await.unwrap();
+
+        // Before the upgrade the block hashes can still be looked up in the old layout.
+        let (state_root, _, _) = downgrade_chainmetadata(&sim).commit().unwrap();
+        let externs = FendermintExterns::new(sim.store().clone(), state_root);
+        let tipset_cid = externs.get_tipset_cid(block.height).unwrap();
+        assert_ne!(tipset_cid, cid::Cid::default());
+
+        let mut state = downgrade_chainmetadata(&sim);
+        assert!(
+            read_actor_state::<_, fendermint_actor_chainmetadata::State>(
+                &state,
+                CHAINMETADATA_ACTOR_ID
+            )
+            .is_err()
+        );
+
+        chainmetadata_timestamps(&mut state).unwrap();
+
+        let migrated = read_actor_state::<_, fendermint_actor_chainmetadata::State>(
+            &state,
+            CHAINMETADATA_ACTOR_ID,
+        )
+        .unwrap()
+        .expect("chainmetadata actor exists");
+
+        let store = state.state_tree().store();
+        assert_eq!(
+            migrated.get_block_hash(store, block.height).unwrap(),
+            Some(block.block_hash)
+        );
+        // The timestamps of the blocks before the upgrade are unknown.
+        assert_eq!(
+            migrated.get_block_timestamp(store, block.height).unwrap(),
+            None
+        );
+
+        let (state_root, _, _) = state.commit().unwrap();
+        let externs = FendermintExterns::new(sim.store().clone(), state_root);
+        assert_eq!(externs.get_tipset_cid(block.height).unwrap(), tipset_cid);
+    }
+}
@@ -0,0 +1,154 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Support for machine learning jobs which are too heavy to run on every validator.
+//!
+//! A proposer executes the training off-chain and posts the model to the jobs actor.
+//! The validators re-execute the job lazily, in the background, and challenge the
+//! result if it's wrong, which makes the actor re-execute it on-chain and revert it.
+//! Jobs which haven't been challenged within the window are finalized at the end of a block.
+
+use anyhow::Context;
+use fendermint_actor_mljobs::{ChallengeParams, Job, JobId, State};
+use fendermint_rpc::response::decode_bytes;
+use fendermint_vm_actor_interface::{mljobs, system};
+use fvm_ipld_blockstore::Blockstore;
+use fvm_ipld_encoding::{CborStore, RawBytes};
+use fvm_shared::chainid::ChainID;
+use tendermint_rpc::Client;
+
+use super::{broadcast::Broadcaster, checkpoint, state::FvmExecState, FvmMessage};
+
+/// Finalize the jobs whose challenge window has passed.
+///
+/// Chains created before the jobs actor was introduced don't have it, in which case this is a no-op.
+/// The actor settles a bounded number of jobs per call; if it fails, the failure is logged and the
+/// jobs are left for the next block, rather than failing the block.
+pub fn finalize_expired_jobs<DB>(state: &mut FvmExecState<DB>, gas_limit: u64) -> anyhow::Result<()>
+where
+    DB: Blockstore + Clone + 'static + Send + Sync,
+{
+    if state
+        .state_tree()
+        .get_actor(mljobs::MLJOBS_ACTOR_ID)?
+        .is_none()
+    {
+        return Ok(());
+    }
+
+    let msg = FvmMessage {
+        from: system::SYSTEM_ACTOR_ADDR,
+        to: mljobs::MLJOBS_ACTOR_ADDR,
+        sequence: state.block_height() as u64,
+        gas_limit,
+        method_num: mljobs::Method::FinalizeExpired as u64,
+        params: Default::default(),
+        value: Default::default(),
+        version: Default::default(),
+        gas_fee_cap: Default::default(),
+        gas_premium: Default::default(),
+    };
+
+    let (apply_ret, _) = state.execute_implicit(msg)?;
+
+    if let Some(err) = apply_ret.failure_info {
+        tracing::warn!(
+            height = state.block_height(),
+            error = err.to_string(),
+            "failed to finalize expired ml jobs"
+        );
+    }
+
+    Ok(())
+}
+
+/// Collect the jobs posted in the current block, which the validator has to verify.
+pub fn posted_jobs<DB>(state: &FvmExecState<DB>) -> anyhow::Result<Vec<(JobId, Job)>>
+where
+    DB: Blockstore + Clone + 'static + Send + Sync,
+{
+    let actor = match state.state_tree().get_actor(mljobs::MLJOBS_ACTOR_ID)? {
+        Some(actor) => actor,
+        None => return Ok(Vec::new()),
+    };
+
+    let store = state.state_tree().store();
+
+    let st: State = store
+        .get_cbor(&actor.state)?
+        .context("mljobs actor state not found")?;
+
+    st.jobs_posted_at(store, state.block_height())
+}
+
+/// Re-execute the jobs and challenge the ones with a wrong result.
+///
+/// The challenge is first tried as a read-only call, which re-executes the job on this node only;
+/// it's only sent as a transaction if the call shows that the result is wrong.
+pub async fn challenge_invalid_jobs<C>(
+    client: &C,
+    broadcaster: &Broadcaster<C>,
+    chain_id: ChainID,
+    block_height: u64,
+    jobs: Vec<(JobId, Job)>,
+) -> anyhow::Result<()>
+where
+    C: Client + Clone + Send + Sync + 'static,
+{
+    // Make sure the jobs are in the committed state we are going to call.
+    checkpoint::wait_for_commit(client, block_height + 1, broadcaster.retry_delay())
+        .await
+        .context("failed to wait for commit")?;
+
+    for (job_id, job) in jobs {
+        let params = RawBytes::serialize(ChallengeParams { job_id })?;
+
+        let ret = broadcaster
+            .call(
+                mljobs::MLJOBS_ACTOR_ADDR,
+                mljobs::Method::Challenge as u64,
+                params.clone(),
+            )
+            .await
+            .with_context(|| format!("failed to re-execute ml job {job_id}"))?;
+
+        if ret.code.is_err() {
+            // Someone else might have challenged it already, or the job failed to re-execute,
+            // which isn't proof that the result is wrong.
+            tracing::warn!(
+                job_id,
+                code = ret.code.value(),
+                info = %ret.info,
+                "failed to re-execute ml job"
+            );
+            continue;
+        }
+
+        let invalid: bool = decode_bytes(&ret)
+            .and_then(|bz| Ok(bz.deserialize()?))
+            .context("failed to decode challenge result")?;
+
+        if !invalid {
+            tracing::debug!(job_id, "ml job result verified");
+            continue;
+        }
+
+        tracing::info!(
+            job_id,
+            proposer = job.proposer.to_string(),
+            "ml job result is invalid; challenging"
+        );
+
+        broadcaster
+            .invoke(
+                mljobs::MLJOBS_ACTOR_ADDR,
+                mljobs::Method::Challenge as u64,
+                params,
+                chain_id,
+            )
+            .await
+            .with_context(|| format!("failed to challenge ml job {job_id}"))?;
+    }
+
+    Ok(())
+}
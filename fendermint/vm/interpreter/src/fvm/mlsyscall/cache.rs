@@ -0,0 +1,132 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Cache of the results of the prediction syscalls, shared by every execution in the process,
+//! so that oracles polling a model with the same features don't re-run the inference each time.
+//!
+//! The predictions are a pure function of the model and the inputs, and the syscalls don't
+//! charge gas for the inference itself, so a cache hit changes neither the result nor the gas
+//! used; nodes with a cold cache reach the same state.
+
+use std::sync::{Mutex, OnceLock};
+
+use cid::multihash::{Code, MultihashDigest};
+use fvm_ipld_encoding::RawBytes;
+use lru_time_cache::LruCache;
+
+/// Number of predictions kept by the process-wide cache.
+const PREDICTION_CACHE_CAPACITY: usize = 256;
+
+/// The prediction algorithms, so that the same bytes given to different syscalls don't clash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PredictionKind {
+    LinearRegression = 1,
+    LogisticRegression = 2,
+    KNNRegression = 3,
+}
+
+/// Blake2b-256 digest of the algorithm, the model and the input matrix.
+type PredictionKey = [u8; 32];
+
+pub struct PredictionCache {
+    // The `LruCache` is wrapped in `Mutex` because even reading requires mutation.
+    cache: Mutex<LruCache<PredictionKey, RawBytes>>,
+}
+
+impl PredictionCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            cache: Mutex::new(LruCache::with_capacity(capacity)),
+        }
+    }
+
+    /// Return the cached prediction, or compute it with `f` and cache it if it succeeds.
+    pub fn get_or_insert_with<F, E>(
+        &self,
+        kind: PredictionKind,
+        model: &[u8],
+        data: &[u8],
+        f: F,
+    ) -> Result<RawBytes, E>
+    where
+        F: FnOnce() -> Result<RawBytes, E>,
+    {
+        let key = Self::key(kind, model, data);
+
+        if let Some(prediction) = self.lock().get(&key).cloned() {
+            return Ok(prediction);
+        }
+
+        // The lock is not held while predicting, so concurrent misses may compute it twice.
+        let prediction = f()?;
+        self.lock().insert(key, prediction.clone());
+
+        Ok(prediction)
+    }
+
+    fn key(kind: PredictionKind, model: &[u8], data: &[u8]) -> PredictionKey {
+        // The length of the model separates it from the data.
+        let mut bytes = Vec::with_capacity(9 + model.len() + data.len());
+        bytes.push(kind as u8);
+        bytes.extend_from_slice(&(model.len() as u64).to_be_bytes());
+        bytes.extend_from_slice(model);
+        bytes.extend_from_slice(data);
+
+        let mut key = PredictionKey::default();
+        key.copy_from_slice(Code::Blake2b256.digest(&bytes).digest());
+        key
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, LruCache<PredictionKey, RawBytes>> {
+        self.cache.lock().expect("prediction cache poisoned")
+    }
+}
+
+/// The cache shared by every kernel in the process.
+pub fn prediction_cache() -> &'static PredictionCache {
+    static CACHE: OnceLock<PredictionCache> = OnceLock::new();
+    CACHE.get_or_init(|| PredictionCache::new(PREDICTION_CACHE_CAPACITY))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use fvm_ipld_encoding::RawBytes;
+
+    use super::{PredictionCache, PredictionKind};
+
+    #[test]
+    fn predictions_are_cached() {
+        let cache = PredictionCache::new(2);
+        let calls = Cell::new(0);
+        let predict = |kind, model: &[u8], data: &[u8]| {
+            cache
+                .get_or_insert_with(kind, model, data, || {
+                    calls.set(calls.get() + 1);
+                    Ok::<_, ()>(RawBytes::new(vec![calls.get()]))
+                })
+                .unwrap()
+        };
+
+        let first = predict(PredictionKind::LinearRegression, &[1, 2], &[3]);
+        assert_eq!(
+            predict(PredictionKind::LinearRegression, &[1, 2], &[3]),
+            first
+        );
+        assert_eq!(calls.get(), 1);
+
+        // The boundary between the model and the data is part of the key, and so is the kind.
+        predict(PredictionKind::LinearRegression, &[1], &[2, 3]);
+        predict(PredictionKind::KNNRegression, &[1, 2], &[3]);
+        assert_eq!(calls.get(), 3);
+
+        // Failures are not cached.
+        let failed = cache.get_or_insert_with(PredictionKind::LogisticRegression, &[], &[], || {
+            Err::<RawBytes, _>("invalid model")
+        });
+        assert!(failed.is_err());
+        predict(PredictionKind::LogisticRegression, &[], &[]);
+        assert_eq!(calls.get(), 4);
+    }
+}
@@ -9,7 +9,7 @@ use fvm::kernel::{
     SelfOps, SendOps, SyscallHandler, UpgradeOps,
 };
 use fvm::syscalls::Linker;
-use fvm::DefaultKernel;
+use fvm::{syscall_error, DefaultKernel};
 use fvm_ipld_encoding::RawBytes;
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::randomness::RANDOMNESS_LENGTH;
@@ -26,8 +26,11 @@ use smartcore::neighbors::knn_regressor::{KNNRegressor, KNNRegressorParameters};
 use std::cmp;
 
 use ambassador::Delegate;
+use cid::multihash::{Code, MultihashDigest};
 use cid::Cid;
 
+use super::cache::{prediction_cache, PredictionKind};
+
 pub trait MLSyscallKernel: Kernel {
     fn train_linear_regression_syscall(&self, data: &[u8], label: &[u8]) -> Result<RawBytes>;
     fn predict_linear_regression_syscall(&self, model: &[u8], test_data: &[u8])
@@ -40,6 +43,32 @@ pub trait MLSyscallKernel: Kernel {
     ) -> Result<RawBytes>;
     fn train_knn_regression_syscall(&self, data: &[u8], labels: &[u8]) -> Result<RawBytes>;
     fn predict_knn_regression_syscall(&self, model: &[u8], test_data: &[u8]) -> Result<RawBytes>;
+    fn ml_randomness_syscall(
+        &self,
+        epoch: ChainEpoch,
+        entropy: &[u8],
+    ) -> Result<[u8; RANDOMNESS_LENGTH]>;
+}
+
+/// Domain separation of the randomness drawn by the machine learning actor.
+const ML_RANDOMNESS_DST: &[u8] = b"fendermint/ml-randomness";
+
+/// Mix the hash of a block with the epoch and the entropy given by the actor, so every
+/// validator draws the same randomness while different uses in a block get different values.
+///
+/// The proposer of the block can grind its hash, so this must not decide anything that
+/// affects rewards or slashing; it's only meant to keep stochastic algorithms deterministic.
+pub fn ml_randomness(block_hash: &[u8], epoch: ChainEpoch, entropy: &[u8]) -> [u8; 32] {
+    let mut bytes =
+        Vec::with_capacity(ML_RANDOMNESS_DST.len() + block_hash.len() + 8 + entropy.len());
+    bytes.extend_from_slice(ML_RANDOMNESS_DST);
+    bytes.extend_from_slice(block_hash);
+    bytes.extend_from_slice(&epoch.to_be_bytes());
+    bytes.extend_from_slice(entropy);
+
+    let mut randomness = [0u8; 32];
+    randomness.copy_from_slice(Code::Blake2b256.digest(&bytes).digest());
+    randomness
 }
 
 // our mlsyscall kernel extends the filecoin kernel
@@ -311,6 +340,20 @@ where
         let ser_result_raw = fvm_ipld_encoding::RawBytes::serialize(result).unwrap();
         Ok(ser_result_raw)
     }
+
+    fn ml_randomness_syscall(
+        &self,
+        epoch: ChainEpoch,
+        entropy: &[u8],
+    ) -> Result<[u8; RANDOMNESS_LENGTH]> {
+        // The block hashes come from the chainmetadata actor, which also limits how far back
+        // the epoch can be; the lookup charges its own gas.
+        let block_hash = self.tipset_cid(epoch)?;
+        if block_hash == Cid::default() {
+            return Err(syscall_error!(NotFound; "no block hash at epoch {}", epoch).into());
+        }
+        Ok(ml_randomness(block_hash.hash().digest(), epoch, entropy))
+    }
 }
 
 impl<C> Kernel for MLSyscallKernelImpl<C>
@@ -412,6 +455,11 @@ where
             "predict_knn_regression_syscall",
             predict_knn_regression_syscall,
         )?;
+        linker.link_syscall(
+            "mlsyscall_kernel",
+            "ml_randomness_syscall",
+            ml_randomness_syscall,
+        )?;
 
         Ok(())
     }
@@ -472,9 +520,16 @@ pub fn predict_linear_regression_syscall(
         .memory
         .try_slice(data_offset as u32, data_length as u32)?;
 
-    let ser_result_raw = context
-        .kernel
-        .predict_linear_regression_syscall(model_array, data_array)?;
+    let ser_result_raw = prediction_cache().get_or_insert_with(
+        PredictionKind::LinearRegression,
+        model_array,
+        data_array,
+        || {
+            context
+                .kernel
+                .predict_linear_regression_syscall(model_array, data_array)
+        },
+    )?;
 
     let ser_result: &[u8] = ser_result_raw.bytes();
 
@@ -543,9 +598,16 @@ pub fn predict_logistic_regression_syscall(
         .memory
         .try_slice(data_offset as u32, data_length as u32)?;
 
-    let ser_result_raw = context
-        .kernel
-        .predict_logistic_regression_syscall(model_array, data_array)?;
+    let ser_result_raw = prediction_cache().get_or_insert_with(
+        PredictionKind::LogisticRegression,
+        model_array,
+        data_array,
+        || {
+            context
+                .kernel
+                .predict_logistic_regression_syscall(model_array, data_array)
+        },
+    )?;
 
     let ser_result: &[u8] = ser_result_raw.bytes();
 
@@ -614,9 +676,16 @@ pub fn predict_knn_regression_syscall(
         .memory
         .try_slice(data_offset as u32, data_length as u32)?;
 
-    let ser_result_raw = context
-        .kernel
-        .predict_knn_regression_syscall(model_array, data_array)?;
+    let ser_result_raw = prediction_cache().get_or_insert_with(
+        PredictionKind::KNNRegression,
+        model_array,
+        data_array,
+        || {
+            context
+                .kernel
+                .predict_knn_regression_syscall(model_array, data_array)
+        },
+    )?;
 
     let ser_result: &[u8] = ser_result_raw.bytes();
 
@@ -629,3 +698,13 @@ pub fn predict_knn_regression_syscall(
 
     Ok(length as u32)
 }
+
+pub fn ml_randomness_syscall(
+    context: fvm::syscalls::Context<'_, impl MLSyscallKernel>,
+    epoch: ChainEpoch,
+    entropy_offset: u32,
+    entropy_length: u32,
+) -> Result<[u8; RANDOMNESS_LENGTH]> {
+    let entropy = context.memory.try_slice(entropy_offset, entropy_length)?;
+    context.kernel.ml_randomness_syscall(epoch, entropy)
+}
@@ -1,3 +1,4 @@
 // Copyright 2021-2023 Protocol Labs
 // SPDX-License-Identifier: Apache-2.0, MIT
+pub mod cache;
 pub mod mlsyscallkernel;
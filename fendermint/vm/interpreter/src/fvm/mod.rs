@@ -1,15 +1,21 @@
 // Copyright 2022-2024 Protocol Labs
 // SPDX-License-Identifier: Apache-2.0, MIT
 use std::path::PathBuf;
+use std::sync::Arc;
 
 mod broadcast;
 mod check;
 mod checkpoint;
+mod delivery;
 mod exec;
 mod externs;
 mod genesis;
+pub mod migrations;
+mod mljobs;
 pub mod mlsyscall;
+pub mod prevalidate;
 mod query;
+mod signatures;
 pub mod state;
 pub mod store;
 pub mod upgrades;
@@ -19,21 +25,31 @@ pub mod bundle;
 pub(crate) mod topdown;
 
 pub use check::FvmCheckRet;
-pub use checkpoint::PowerUpdates;
-pub use exec::FvmApplyRet;
+pub use checkpoint::{PowerUpdates, ValidatorChange, ValidatorChangeKind, ValidatorSetChange};
+pub use delivery::{DeliveryLogging, DeliveryStats};
+pub use exec::{BlockSummary, FvmApplyRet, FvmEndRet};
 use fendermint_crypto::{PublicKey, SecretKey};
 use fendermint_eth_hardhat::Hardhat;
 pub use fendermint_vm_message::query::FvmQuery;
 use fvm_ipld_blockstore::Blockstore;
 pub use genesis::FvmGenesisOutput;
+use ipc_api::subnet_id::SubnetID;
+use ipc_provider::supervisor::TaskSupervisor;
 pub use query::FvmQueryRet;
+pub use signatures::{
+    CheckpointSignature, QuorumCertificate, SignatureCollector, SignatureGossip,
+    DEFAULT_QUORUM_TIMEOUT,
+};
 use tendermint_rpc::Client;
 
 pub use self::broadcast::Broadcaster;
-use self::{state::ipc::GatewayCaller, upgrades::UpgradeScheduler};
+use self::{prevalidate::Prevalidator, state::ipc::GatewayCaller, upgrades::UpgradeScheduler};
 
 pub type FvmMessage = fvm_shared::message::Message;
 
+/// Number of validator set changes buffered for each subscriber before the oldest are dropped.
+pub const VALIDATOR_CHANGES_CAPACITY: usize = 64;
+
 #[derive(Clone)]
 pub struct ValidatorContext<C> {
     /// The secret key the validator uses to produce blocks.
@@ -43,6 +59,8 @@ pub struct ValidatorContext<C> {
     /// Used to broadcast transactions. It might use a different secret key for
     /// signing transactions than the validator's block producing key.
     broadcaster: Broadcaster<C>,
+    /// Gossip the checkpoint signatures instead of adding them to the ledger one by one.
+    signature_gossip: Option<SignatureGossip>,
 }
 
 impl<C> ValidatorContext<C> {
@@ -53,8 +71,14 @@ impl<C> ValidatorContext<C> {
             secret_key,
             public_key,
             broadcaster,
+            signature_gossip: None,
         }
     }
+
+    pub fn with_signature_gossip(mut self, signature_gossip: SignatureGossip) -> Self {
+        self.signature_gossip = Some(signature_gossip);
+        self
+    }
 }
 
 /// Interpreter working on already verified unsigned messages.
@@ -79,9 +103,19 @@ where
     exec_in_check: bool,
     /// Indicate whether the chain metadata should be pushed into the ledger.
     push_chain_meta: bool,
+    /// How the delivered transactions are logged.
+    delivery_logging: DeliveryLogging,
+    /// Additional checks to reject transactions before they are added to the mempool.
+    prevalidators: Vec<Arc<dyn Prevalidator>>,
     gateway: GatewayCaller<DB>,
     /// Upgrade scheduler stores all the upgrades to be executed at given heights.
     upgrade_scheduler: UpgradeScheduler<DB>,
+    /// Runs the work which outlives the block, like broadcasting checkpoint signatures.
+    supervisor: TaskSupervisor,
+    /// Notifies the subscribers about the changes of the validator set applied in `end`.
+    validator_changes: tokio::sync::broadcast::Sender<ValidatorSetChange>,
+    /// The subnet the blocks are executed in, to tag the spans with.
+    subnet_id: SubnetID,
 }
 
 impl<DB, C> FvmMessageInterpreter<DB, C>
@@ -105,15 +139,52 @@ where
             gas_search_step,
             exec_in_check,
             push_chain_meta: true,
+            delivery_logging: DeliveryLogging::default(),
+            prevalidators: Vec::new(),
             gateway: GatewayCaller::default(),
             upgrade_scheduler,
+            supervisor: TaskSupervisor::new(),
+            validator_changes: tokio::sync::broadcast::channel(VALIDATOR_CHANGES_CAPACITY).0,
+            subnet_id: SubnetID::default(),
         }
     }
 
+    pub fn with_subnet_id(mut self, subnet_id: SubnetID) -> Self {
+        self.subnet_id = subnet_id;
+        self
+    }
+
     pub fn with_push_chain_meta(mut self, push_chain_meta: bool) -> Self {
         self.push_chain_meta = push_chain_meta;
         self
     }
+
+    pub fn with_delivery_logging(mut self, delivery_logging: DeliveryLogging) -> Self {
+        self.delivery_logging = delivery_logging;
+        self
+    }
+
+    pub fn with_prevalidator(mut self, prevalidator: impl Prevalidator + 'static) -> Self {
+        self.prevalidators.push(Arc::new(prevalidator));
+        self
+    }
+
+    /// Run the background tasks under the given supervisor, so they can be drained on shutdown.
+    pub fn with_supervisor(mut self, supervisor: TaskSupervisor) -> Self {
+        self.supervisor = supervisor;
+        self
+    }
+
+    /// Subscribe to the changes of the validator set, as they are applied at the end of blocks,
+    /// e.g. to alert when it changes unexpectedly.
+    ///
+    /// Blocks replayed while the node is catching up are included. A subscriber lagging more
+    /// than [VALIDATOR_CHANGES_CAPACITY] changes behind misses the oldest ones.
+    pub fn subscribe_validator_changes(
+        &self,
+    ) -> tokio::sync::broadcast::Receiver<ValidatorSetChange> {
+        self.validator_changes.subscribe()
+    }
 }
 
 impl<DB, C> FvmMessageInterpreter<DB, C>
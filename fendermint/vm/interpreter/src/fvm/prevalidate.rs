@@ -0,0 +1,497 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Checks performed on transactions before they are admitted into the mempool.
+//!
+//! These are cheap, mostly stateless checks which reject messages that could never succeed,
+//! or that the operator doesn't want to include in blocks, before they waste block space.
+//! They run during `CheckTx` only, so they don't affect consensus; the limits on the inputs of
+//! the machine learning actor stored in the state are checked with [MlInputLimits] as well, but
+//! before every message is executed.
+
+use fendermint_vm_actor_interface::machinelearning::{
+    self, AppendParams, InputMatrix, PredictGDParams, PredictMultiOutputParams, PredictParams,
+    PredictWithModelParams, ScaleParams, SparseMatrix, TrainGDParams, TrainMultiOutputParams,
+    TrainParams, MACHINELEARNING_ACTOR_ADDR,
+};
+use fendermint_vm_genesis::MlLimits;
+use fvm_shared::{clock::ChainEpoch, econ::TokenAmount, error::ExitCode};
+
+use super::FvmMessage;
+
+/// Information available to the checks about the pending state.
+pub struct PrevalidationContext<'a> {
+    pub block_height: ChainEpoch,
+    pub base_fee: &'a TokenAmount,
+}
+
+/// The reason a message was rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rejection {
+    pub exit_code: ExitCode,
+    pub info: String,
+}
+
+impl Rejection {
+    pub fn new(exit_code: ExitCode, info: impl Into<String>) -> Self {
+        Self {
+            exit_code,
+            info: info.into(),
+        }
+    }
+}
+
+/// Extension point to reject messages during `CheckTx`.
+pub trait Prevalidator: Send + Sync {
+    fn prevalidate(&self, ctx: &PrevalidationContext, msg: &FvmMessage) -> Result<(), Rejection>;
+}
+
+/// Reject transactions which don't pay at least the base fee, or a configured minimum.
+#[derive(Debug, Clone, Default)]
+pub struct MinGasPrice {
+    pub min_gas_fee_cap: TokenAmount,
+    pub min_gas_premium: TokenAmount,
+}
+
+impl Prevalidator for MinGasPrice {
+    fn prevalidate(&self, ctx: &PrevalidationContext, msg: &FvmMessage) -> Result<(), Rejection> {
+        let min_gas_fee_cap = std::cmp::max(ctx.base_fee, &self.min_gas_fee_cap);
+        if msg.gas_fee_cap < *min_gas_fee_cap {
+            return Err(Rejection::new(
+                ExitCode::USR_ILLEGAL_ARGUMENT,
+                format!(
+                    "gas fee cap {} is below the minimum {}",
+                    msg.gas_fee_cap, min_gas_fee_cap
+                ),
+            ));
+        }
+        if msg.gas_premium < self.min_gas_premium {
+            return Err(Rejection::new(
+                ExitCode::USR_ILLEGAL_ARGUMENT,
+                format!(
+                    "gas premium {} is below the minimum {}",
+                    msg.gas_premium, self.min_gas_premium
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Reject calls to the machine learning actor with malformed or oversized inputs.
+#[derive(Debug, Clone)]
+pub struct MlInputLimits {
+    /// Maximum number of rows in the input matrix.
+    pub max_rows: usize,
+    /// Maximum number of columns in the input matrix.
+    pub max_cols: usize,
+}
+
+impl MlInputLimits {
+    fn check_matrix(&self, input_matrix: &[Vec<i64>]) -> Result<(), Rejection> {
+        let illegal = |info: String| Err(Rejection::new(ExitCode::USR_ILLEGAL_ARGUMENT, info));
+
+        let Some(first) = input_matrix.first() else {
+            return illegal("the input matrix is empty".into());
+        };
+        if input_matrix.len() > self.max_rows {
+            return illegal(format!(
+                "the input matrix has {} rows; the maximum is {}",
+                input_matrix.len(),
+                self.max_rows
+            ));
+        }
+        let cols = first.len();
+        if cols == 0 || cols > self.max_cols {
+            return illegal(format!(
+                "the input matrix has {cols} columns; it should be between 1 and {}",
+                self.max_cols
+            ));
+        }
+        if let Some(i) = input_matrix.iter().position(|row| row.len() != cols) {
+            return illegal(format!(
+                "row {i} of the input matrix has {} columns instead of {cols}",
+                input_matrix[i].len()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Check a sparse matrix without expanding it, so that its dimensions can't be used to
+    /// make validators allocate large dense matrices.
+    fn check_sparse(&self, m: &SparseMatrix) -> Result<(), Rejection> {
+        let illegal = |info: String| Err(Rejection::new(ExitCode::USR_ILLEGAL_ARGUMENT, info));
+
+        if m.rows == 0 {
+            return illegal("the input matrix is empty".into());
+        }
+        if m.rows > self.max_rows as u64 {
+            return illegal(format!(
+                "the input matrix has {} rows; the maximum is {}",
+                m.rows, self.max_rows
+            ));
+        }
+        if m.cols == 0 || m.cols > self.max_cols as u64 {
+            return illegal(format!(
+                "the input matrix has {} columns; it should be between 1 and {}",
+                m.cols, self.max_cols
+            ));
+        }
+        if let Err(e) = m.validate() {
+            return illegal(format!("invalid sparse matrix: {e}"));
+        }
+        Ok(())
+    }
+
+    fn check_input(&self, input_matrix: &InputMatrix) -> Result<(), Rejection> {
+        match input_matrix {
+            InputMatrix::Dense(m) => self.check_matrix(m),
+            InputMatrix::Sparse(m) => self.check_sparse(m),
+        }
+    }
+
+    /// Check the inputs of a call to the machine learning actor; other messages pass.
+    pub fn check(&self, msg: &FvmMessage) -> Result<(), Rejection> {
+        if msg.to != MACHINELEARNING_ACTOR_ADDR {
+            return Ok(());
+        }
+        let Some(method) = machinelearning::Method::from_method_num(msg.method_num) else {
+            return Ok(());
+        };
+        let invalid_params = |e: fvm_ipld_encoding::Error| {
+            Rejection::new(
+                ExitCode::USR_SERIALIZATION,
+                format!("failed to decode parameters: {e}"),
+            )
+        };
+        if method.is_train() {
+            let params: TrainParams = msg.params.deserialize().map_err(invalid_params)?;
+            // The stored datasets were checked when they were created and appended to.
+            if let Some(input_matrix) = params.input_matrix.matrix() {
+                self.check_input(input_matrix)?;
+                if params.labels.len() != input_matrix.num_rows() {
+                    return Err(Rejection::new(
+                        ExitCode::USR_ILLEGAL_ARGUMENT,
+                        format!(
+                            "there are {} labels for {} rows",
+                            params.labels.len(),
+                            input_matrix.num_rows()
+                        ),
+                    ));
+                }
+            }
+        } else if method.is_predict() {
+            let params: PredictParams = msg.params.deserialize().map_err(invalid_params)?;
+            self.check_input(&params.input_matrix)?;
+        } else if method.is_scale() || matches!(method, machinelearning::Method::CreateDataset) {
+            let params: ScaleParams = msg.params.deserialize().map_err(invalid_params)?;
+            self.check_input(&params.input_matrix)?;
+        } else if let machinelearning::Method::PredictWithModel = method {
+            let params: PredictWithModelParams =
+                msg.params.deserialize().map_err(invalid_params)?;
+            self.check_input(&params.input_matrix)?;
+        } else if let machinelearning::Method::TrainLinearRegressionGD = method {
+            let params: TrainGDParams = msg.params.deserialize().map_err(invalid_params)?;
+            if let Some(input_matrix) = params.input_matrix.matrix() {
+                self.check_input(input_matrix)?;
+                if params.labels.len() != input_matrix.num_rows() {
+                    return Err(Rejection::new(
+                        ExitCode::USR_ILLEGAL_ARGUMENT,
+                        format!(
+                            "there are {} labels for {} rows",
+                            params.labels.len(),
+                            input_matrix.num_rows()
+                        ),
+                    ));
+                }
+            }
+        } else if let machinelearning::Method::PredictLinearRegressionGD = method {
+            let params: PredictGDParams = msg.params.deserialize().map_err(invalid_params)?;
+            self.check_input(&params.input_matrix)?;
+        } else if let machinelearning::Method::TrainMultiOutputLinearRegression = method {
+            let params: TrainMultiOutputParams =
+                msg.params.deserialize().map_err(invalid_params)?;
+            if let Some(input_matrix) = params.input_matrix.matrix() {
+                self.check_input(input_matrix)?;
+                if params.labels.len() != input_matrix.num_rows() {
+                    return Err(Rejection::new(
+                        ExitCode::USR_ILLEGAL_ARGUMENT,
+                        format!(
+                            "there are {} rows of labels for {} rows",
+                            params.labels.len(),
+                            input_matrix.num_rows()
+                        ),
+                    ));
+                }
+            }
+            // Every target column is a separate training run.
+            self.check_matrix(&params.labels)?;
+        } else if let machinelearning::Method::PredictMultiOutputLinearRegression = method {
+            let params: PredictMultiOutputParams =
+                msg.params.deserialize().map_err(invalid_params)?;
+            self.check_input(&params.input_matrix)?;
+            if params.models.is_empty() || params.models.len() > self.max_cols {
+                return Err(Rejection::new(
+                    ExitCode::USR_ILLEGAL_ARGUMENT,
+                    format!(
+                        "there are {} models; there should be between 1 and {}",
+                        params.models.len(),
+                        self.max_cols
+                    ),
+                ));
+            }
+        } else if let machinelearning::Method::AppendRows | machinelearning::Method::AppendColumns =
+            method
+        {
+            let params: AppendParams = msg.params.deserialize().map_err(invalid_params)?;
+            self.check_input(&params.input_matrix)?;
+        }
+        Ok(())
+    }
+}
+
+impl Prevalidator for MlInputLimits {
+    fn prevalidate(&self, _ctx: &PrevalidationContext, msg: &FvmMessage) -> Result<(), Rejection> {
+        self.check(msg)
+    }
+}
+
+/// Check a message against the limits on the inputs of the machine learning actor in the state,
+/// the way the mempool checks its own. The size of the parameters is checked before decoding them.
+pub fn check_ml_limits(limits: &MlLimits, msg: &FvmMessage) -> Result<(), Rejection> {
+    if msg.to == MACHINELEARNING_ACTOR_ADDR && msg.params.len() as u64 > limits.max_params_bytes {
+        return Err(Rejection::new(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            format!(
+                "the parameters are {} bytes; the maximum is {}",
+                msg.params.len(),
+                limits.max_params_bytes
+            ),
+        ));
+    }
+    MlInputLimits {
+        max_rows: usize::try_from(limits.max_rows).unwrap_or(usize::MAX),
+        max_cols: usize::try_from(limits.max_cols).unwrap_or(usize::MAX),
+    }
+    .check(msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use fendermint_vm_actor_interface::machinelearning::{
+        AppendParams, Method, SparseMatrix, TrainMultiOutputParams, TrainParams, TrainingInput,
+        MACHINELEARNING_ACTOR_ADDR,
+    };
+    use fvm_ipld_encoding::RawBytes;
+    use fvm_shared::{address::Address, econ::TokenAmount, error::ExitCode};
+
+    use fendermint_vm_genesis::MlLimits;
+
+    use super::{check_ml_limits, MinGasPrice, MlInputLimits, PrevalidationContext, Prevalidator};
+    use crate::fvm::FvmMessage;
+
+    fn message(to: Address, method_num: u64, params: RawBytes) -> FvmMessage {
+        FvmMessage {
+            version: 0,
+            from: Address::new_id(100),
+            to,
+            sequence: 0,
+            value: TokenAmount::default(),
+            method_num,
+            params,
+            gas_limit: 1000,
+            gas_fee_cap: TokenAmount::from_atto(10),
+            gas_premium: TokenAmount::from_atto(1),
+        }
+    }
+
+    fn train(input_matrix: impl Into<TrainingInput>, labels: Vec<i64>) -> FvmMessage {
+        let params = RawBytes::serialize(TrainParams {
+            input_matrix: input_matrix.into(),
+            labels,
+            scaler: None,
+        })
+        .unwrap();
+        message(
+            MACHINELEARNING_ACTOR_ADDR,
+            Method::TrainLinearRegression as u64,
+            params,
+        )
+    }
+
+    #[test]
+    fn ml_input_limits() {
+        let base_fee = TokenAmount::default();
+        let ctx = PrevalidationContext {
+            block_height: 1,
+            base_fee: &base_fee,
+        };
+        let limits = MlInputLimits {
+            max_rows: 2,
+            max_cols: 2,
+        };
+        let check = |msg| limits.prevalidate(&ctx, &msg).map_err(|r| r.exit_code);
+
+        assert_eq!(
+            check(train(vec![vec![1, 2], vec![3, 4]], vec![1, 2])),
+            Ok(())
+        );
+        assert_eq!(
+            check(train(vec![vec![1, 2], vec![3]], vec![1, 2])),
+            Err(ExitCode::USR_ILLEGAL_ARGUMENT)
+        );
+        assert_eq!(
+            check(train(vec![vec![1], vec![2], vec![3]], vec![1, 2, 3])),
+            Err(ExitCode::USR_ILLEGAL_ARGUMENT)
+        );
+        assert_eq!(
+            check(train(vec![vec![1, 2, 3]], vec![1])),
+            Err(ExitCode::USR_ILLEGAL_ARGUMENT)
+        );
+        assert_eq!(
+            check(train(vec![vec![1, 2]], vec![1, 2])),
+            Err(ExitCode::USR_ILLEGAL_ARGUMENT)
+        );
+        // A stored dataset is checked by the actor, which knows its dimensions.
+        assert_eq!(check(train(TrainingInput::Dataset(0), vec![1])), Ok(()));
+        let sparse = SparseMatrix::from_dense(&[vec![0, 2], vec![3, 0]]).unwrap();
+        assert_eq!(check(train(sparse.clone(), vec![1, 2])), Ok(()));
+        assert_eq!(
+            check(train(sparse.clone(), vec![1])),
+            Err(ExitCode::USR_ILLEGAL_ARGUMENT)
+        );
+        // Too big, even though it has no non-zero values to expand.
+        let empty = SparseMatrix {
+            rows: 1,
+            cols: 1_000_000,
+            row_offsets: vec![0, 0],
+            ..Default::default()
+        };
+        assert_eq!(
+            check(train(empty, vec![1])),
+            Err(ExitCode::USR_ILLEGAL_ARGUMENT)
+        );
+        let mut invalid = sparse;
+        invalid.col_indices[0] = 2;
+        assert_eq!(
+            check(train(invalid, vec![1, 2])),
+            Err(ExitCode::USR_ILLEGAL_ARGUMENT)
+        );
+        assert_eq!(
+            check(message(
+                MACHINELEARNING_ACTOR_ADDR,
+                Method::TrainKNNRegression as u64,
+                RawBytes::new(vec![1, 2, 3])
+            )),
+            Err(ExitCode::USR_SERIALIZATION)
+        );
+        let train_multi = |labels: Vec<Vec<i64>>| {
+            let params = RawBytes::serialize(TrainMultiOutputParams {
+                input_matrix: vec![vec![1, 2], vec![3, 4]].into(),
+                labels,
+                scaler: None,
+            })
+            .unwrap();
+            message(
+                MACHINELEARNING_ACTOR_ADDR,
+                Method::TrainMultiOutputLinearRegression as u64,
+                params,
+            )
+        };
+        assert_eq!(check(train_multi(vec![vec![1, 2], vec![3, 4]])), Ok(()));
+        assert_eq!(
+            check(train_multi(vec![vec![1, 2]])),
+            Err(ExitCode::USR_ILLEGAL_ARGUMENT)
+        );
+        assert_eq!(
+            check(train_multi(vec![vec![1, 2, 3], vec![4, 5, 6]])),
+            Err(ExitCode::USR_ILLEGAL_ARGUMENT)
+        );
+        let append_rows = |input_matrix: Vec<Vec<i64>>| {
+            let params = RawBytes::serialize(AppendParams {
+                dataset_id: 0,
+                input_matrix: input_matrix.into(),
+            })
+            .unwrap();
+            message(
+                MACHINELEARNING_ACTOR_ADDR,
+                Method::AppendRows as u64,
+                params,
+            )
+        };
+        assert_eq!(check(append_rows(vec![vec![1, 2]])), Ok(()));
+        assert_eq!(
+            check(append_rows(vec![vec![1, 2, 3]])),
+            Err(ExitCode::USR_ILLEGAL_ARGUMENT)
+        );
+        // Other actors are not checked.
+        assert_eq!(
+            check(message(
+                Address::new_id(1000),
+                Method::TrainKNNRegression as u64,
+                RawBytes::default()
+            )),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn ml_consensus_limits() {
+        let limits = MlLimits {
+            max_rows: 2,
+            max_cols: 2,
+            max_params_bytes: 64,
+            max_block_gas: None,
+        };
+        let check = |msg| check_ml_limits(&limits, &msg).map_err(|r| r.exit_code);
+
+        assert_eq!(
+            check(train(vec![vec![1, 2], vec![3, 4]], vec![1, 2])),
+            Ok(())
+        );
+        assert_eq!(
+            check(train(vec![vec![1], vec![2], vec![3]], vec![1, 2, 3])),
+            Err(ExitCode::USR_ILLEGAL_ARGUMENT)
+        );
+        // Oversized parameters are rejected without being decoded.
+        assert_eq!(
+            check(message(
+                MACHINELEARNING_ACTOR_ADDR,
+                Method::TrainKNNRegression as u64,
+                RawBytes::new(vec![0; 65])
+            )),
+            Err(ExitCode::USR_ILLEGAL_ARGUMENT)
+        );
+        assert_eq!(
+            check(message(
+                Address::new_id(1000),
+                0,
+                RawBytes::new(vec![0; 65])
+            )),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn min_gas_price() {
+        let msg = message(Address::new_id(1000), 0, RawBytes::default());
+        let min = MinGasPrice {
+            min_gas_fee_cap: TokenAmount::from_atto(5),
+            min_gas_premium: TokenAmount::from_atto(1),
+        };
+
+        let base_fee = TokenAmount::from_atto(10);
+        let ctx = PrevalidationContext {
+            block_height: 1,
+            base_fee: &base_fee,
+        };
+        assert!(min.prevalidate(&ctx, &msg).is_ok());
+
+        let base_fee = TokenAmount::from_atto(11);
+        let ctx = PrevalidationContext {
+            block_height: 1,
+            base_fee: &base_fee,
+        };
+        assert!(min.prevalidate(&ctx, &msg).is_err());
+    }
+}
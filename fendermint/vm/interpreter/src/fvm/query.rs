@@ -148,6 +148,8 @@ where
                     circ_supply: state_params.circ_supply.clone(),
                     chain_id: state_params.chain_id,
                     network_version: state_params.network_version,
+                    app_version: state_params.app_version,
+                    state_root: state_params.state_root,
                 };
                 Ok((state, FvmQueryRet::StateParams(state_params)))
             }
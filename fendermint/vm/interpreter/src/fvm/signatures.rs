@@ -0,0 +1,382 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+//! Collection of bottom-up checkpoint signatures gossiped between validators.
+//!
+//! Instead of every validator adding its signature to the ledger with a transaction of its
+//! own, validators can publish their signatures over the gossip network of the IPLD Resolver.
+//! Each validator collects the signatures it receives, and once they reach the quorum of the
+//! checkpoint, one of them adds them all to the ledger at once.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context};
+use ethers::types as et;
+use fendermint_crypto::PublicKey;
+use fendermint_vm_actor_interface::eam::EthAddress;
+use fendermint_vm_genesis::{Power, Validator};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, Notify};
+
+/// Number of checkpoints, ordered by height, whose signatures are kept in memory.
+const MAX_TRACKED_CHECKPOINTS: usize = 32;
+
+/// Number of signatures kept for a checkpoint this node hasn't created yet.
+const MAX_EARLY_SIGNATURES: usize = 1024;
+
+/// How long the validator of a new checkpoint waits for its quorum to be gossiped before
+/// leaving the submission to the next checkpoint.
+pub const DEFAULT_QUORUM_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The signature of a bottom-up checkpoint by a validator, as gossiped to the others.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckpointSignature {
+    /// The height of the checkpoint.
+    pub height: u64,
+    /// The ABI hash of the checkpoint, which is what's signed.
+    pub checkpoint_hash: [u8; 32],
+    /// Ethereum style signature, which the gateway can recover the signatory from.
+    pub signature: Vec<u8>,
+}
+
+/// Signatures of a checkpoint by validators whose power reaches its quorum.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuorumCertificate {
+    pub height: u64,
+    pub checkpoint_hash: [u8; 32],
+    /// The validators who could sign the checkpoint, to prove the membership of the signatories.
+    pub power_table: Vec<Validator<Power>>,
+    pub signatures: Vec<(Validator<Power>, Vec<u8>)>,
+}
+
+impl QuorumCertificate {
+    /// The validator expected to add the certificate to the ledger: the most powerful of the
+    /// signatories, which is the most likely to be part of every certificate of the checkpoint.
+    pub fn submitter(&self) -> Option<&PublicKey> {
+        self.signatures
+            .iter()
+            .map(|(v, _)| v)
+            .max_by(|a, b| {
+                a.power
+                    .0
+                    .cmp(&b.power.0)
+                    .then_with(|| b.public_key.0.serialize().cmp(&a.public_key.0.serialize()))
+            })
+            .map(|v| &v.public_key.0)
+    }
+}
+
+/// The signatures collected so far for a checkpoint.
+struct PendingQuorum {
+    checkpoint_hash: [u8; 32],
+    power_table: Vec<Validator<Power>>,
+    threshold: u64,
+    signatures: BTreeMap<EthAddress, (Validator<Power>, Vec<u8>)>,
+    weight: u64,
+}
+
+impl PendingQuorum {
+    fn is_reached(&self) -> bool {
+        self.weight >= self.threshold
+    }
+
+    /// Check the signature against the power table of the checkpoint and accumulate it;
+    /// return whether it was new.
+    fn add(&mut self, sig: CheckpointSignature) -> anyhow::Result<bool> {
+        if sig.checkpoint_hash != self.checkpoint_hash {
+            bail!(
+                "signature of checkpoint {} for hash {}, expected {}",
+                sig.height,
+                hex::encode(sig.checkpoint_hash),
+                hex::encode(self.checkpoint_hash)
+            );
+        }
+
+        let signatory = et::Signature::try_from(sig.signature.as_slice())
+            .context("invalid checkpoint signature")?
+            .recover(et::H256::from(sig.checkpoint_hash))
+            .context("failed to recover checkpoint signatory")?;
+        let signatory = EthAddress::from(signatory);
+
+        if self.signatures.contains_key(&signatory) {
+            return Ok(false);
+        }
+
+        let validator = self
+            .power_table
+            .iter()
+            .find(|v| EthAddress::from(v.public_key.0) == signatory)
+            .ok_or_else(|| {
+                anyhow!(
+                    "signatory {} is not a validator of checkpoint {}",
+                    hex::encode(signatory.0),
+                    sig.height
+                )
+            })?;
+
+        self.weight = self.weight.saturating_add(validator.power.0);
+        self.signatures
+            .insert(signatory, (validator.clone(), sig.signature));
+
+        Ok(true)
+    }
+}
+
+#[derive(Default)]
+struct Collection {
+    quorums: BTreeMap<u64, PendingQuorum>,
+    /// Signatures received before this node created the checkpoint they sign.
+    early: BTreeMap<u64, Vec<CheckpointSignature>>,
+}
+
+impl Collection {
+    fn prune(&mut self) {
+        while self.quorums.len() > MAX_TRACKED_CHECKPOINTS {
+            self.quorums.pop_first();
+        }
+        while self.early.len() > MAX_TRACKED_CHECKPOINTS {
+            self.early.pop_last();
+        }
+    }
+}
+
+/// Accumulates the gossiped signatures of the checkpoints, into quorum certificates.
+#[derive(Clone, Default)]
+pub struct SignatureCollector {
+    collection: Arc<Mutex<Collection>>,
+    certified: Arc<Notify>,
+}
+
+impl SignatureCollector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start collecting the signatures of a checkpoint created by this node, including the ones
+    /// which arrived before.
+    pub fn expect(
+        &self,
+        height: u64,
+        checkpoint_hash: [u8; 32],
+        power_table: Vec<Validator<Power>>,
+        threshold: u64,
+    ) {
+        let mut collection = self.collection.lock().unwrap();
+
+        if collection.quorums.contains_key(&height) {
+            return;
+        }
+
+        let mut quorum = PendingQuorum {
+            checkpoint_hash,
+            power_table,
+            threshold,
+            signatures: BTreeMap::new(),
+            weight: 0,
+        };
+
+        for sig in collection.early.remove(&height).unwrap_or_default() {
+            if let Err(e) = quorum.add(sig) {
+                tracing::debug!(error = e.to_string(), height, "ignoring early signature");
+            }
+        }
+
+        let reached = quorum.is_reached();
+        collection.quorums.insert(height, quorum);
+        collection.prune();
+
+        if reached {
+            self.certified.notify_waiters();
+        }
+    }
+
+    /// Add a signature of a checkpoint, returning whether it completed its quorum.
+    ///
+    /// Signatures of checkpoints this node hasn't created yet are kept until it does.
+    pub fn add_signature(&self, sig: CheckpointSignature) -> anyhow::Result<bool> {
+        let mut collection = self.collection.lock().unwrap();
+
+        let Some(quorum) = collection.quorums.get_mut(&sig.height) else {
+            let early = collection.early.entry(sig.height).or_default();
+            if early.len() < MAX_EARLY_SIGNATURES {
+                early.push(sig);
+            }
+            collection.prune();
+            return Ok(false);
+        };
+
+        let was_reached = quorum.is_reached();
+        let added = quorum.add(sig)?;
+        let reached = added && !was_reached && quorum.is_reached();
+
+        if reached {
+            self.certified.notify_waiters();
+        }
+
+        Ok(reached)
+    }
+
+    /// The certificate of a checkpoint, if its signatures have reached the quorum.
+    pub fn certificate(&self, height: u64) -> Option<QuorumCertificate> {
+        let collection = self.collection.lock().unwrap();
+        let quorum = collection.quorums.get(&height)?;
+
+        if !quorum.is_reached() {
+            return None;
+        }
+
+        Some(QuorumCertificate {
+            height,
+            checkpoint_hash: quorum.checkpoint_hash,
+            power_table: quorum.power_table.clone(),
+            signatures: quorum.signatures.values().cloned().collect(),
+        })
+    }
+
+    /// Wait until the signatures of a checkpoint reach the quorum, or the timeout expires.
+    pub async fn wait_for_certificate(
+        &self,
+        height: u64,
+        timeout: Duration,
+    ) -> Option<QuorumCertificate> {
+        let wait = async {
+            loop {
+                // Register for the notification before checking, so that it can't be missed.
+                let certified = self.certified.notified();
+                tokio::pin!(certified);
+                certified.as_mut().enable();
+
+                if let Some(certificate) = self.certificate(height) {
+                    return certificate;
+                }
+                certified.await;
+            }
+        };
+        tokio::time::timeout(timeout, wait).await.ok()
+    }
+}
+
+/// Distribution of checkpoint signatures over gossip, as opposed to the ledger.
+#[derive(Clone)]
+pub struct SignatureGossip {
+    /// Signatures to be published by the IPLD Resolver.
+    publisher: mpsc::UnboundedSender<CheckpointSignature>,
+    collector: SignatureCollector,
+    quorum_timeout: Duration,
+}
+
+impl SignatureGossip {
+    pub fn new(
+        publisher: mpsc::UnboundedSender<CheckpointSignature>,
+        collector: SignatureCollector,
+    ) -> Self {
+        Self {
+            publisher,
+            collector,
+            quorum_timeout: DEFAULT_QUORUM_TIMEOUT,
+        }
+    }
+
+    pub fn with_quorum_timeout(mut self, quorum_timeout: Duration) -> Self {
+        self.quorum_timeout = quorum_timeout;
+        self
+    }
+
+    pub fn collector(&self) -> &SignatureCollector {
+        &self.collector
+    }
+
+    pub fn quorum_timeout(&self) -> Duration {
+        self.quorum_timeout
+    }
+
+    /// Count our own signature, and hand it over to be gossiped to the other validators.
+    pub fn publish(&self, sig: CheckpointSignature) -> anyhow::Result<()> {
+        self.collector.add_signature(sig.clone())?;
+        self.publisher
+            .send(sig)
+            .map_err(|_| anyhow!("the checkpoint signature publisher stopped"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use fendermint_crypto::SecretKey;
+    use fendermint_vm_genesis::{Power, Validator, ValidatorKey};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::{CheckpointSignature, SignatureCollector};
+    use crate::fvm::state::ipc::sign_checkpoint_hash;
+
+    const HASH: [u8; 32] = [7; 32];
+
+    fn validators(powers: &[u64]) -> Vec<(SecretKey, Validator<Power>)> {
+        let mut rng = StdRng::seed_from_u64(42);
+        powers
+            .iter()
+            .map(|p| {
+                let sk = SecretKey::random(&mut rng);
+                let v = Validator {
+                    public_key: ValidatorKey::new(sk.public_key()),
+                    power: Power(*p),
+                };
+                (sk, v)
+            })
+            .collect()
+    }
+
+    fn sign(sk: &SecretKey, height: u64) -> CheckpointSignature {
+        CheckpointSignature {
+            height,
+            checkpoint_hash: HASH,
+            signature: sign_checkpoint_hash(sk, &HASH).unwrap().to_vec(),
+        }
+    }
+
+    #[tokio::test]
+    async fn signatures_reach_quorum() {
+        let vals = validators(&[10, 20, 30, 40]);
+        let power_table = vals.iter().map(|(_, v)| v.clone()).collect::<Vec<_>>();
+        let outsider = SecretKey::random(&mut StdRng::seed_from_u64(1));
+
+        let collector = SignatureCollector::new();
+
+        // Gossiped before this node created the checkpoint.
+        assert!(!collector.add_signature(sign(&vals[3].0, 10)).unwrap());
+
+        collector.expect(10, HASH, power_table, 67);
+        assert!(collector.certificate(10).is_none());
+
+        // Not a validator of the checkpoint.
+        assert!(collector.add_signature(sign(&outsider, 10)).is_err());
+        // A different checkpoint at the same height.
+        let mut forked = sign(&vals[0].0, 10);
+        forked.checkpoint_hash = [8; 32];
+        assert!(collector.add_signature(forked).is_err());
+
+        assert!(!collector.add_signature(sign(&vals[0].0, 10)).unwrap());
+        // Duplicates don't count twice.
+        assert!(!collector.add_signature(sign(&vals[0].0, 10)).unwrap());
+        assert!(collector
+            .wait_for_certificate(10, Duration::from_millis(10))
+            .await
+            .is_none());
+
+        assert!(collector.add_signature(sign(&vals[2].0, 10)).unwrap());
+
+        let certificate = collector
+            .wait_for_certificate(10, Duration::from_millis(10))
+            .await
+            .expect("quorum reached");
+
+        assert_eq!(certificate.signatures.len(), 3);
+        assert_eq!(
+            certificate.submitter(),
+            Some(vals[3].1.public_key.public_key())
+        );
+    }
+}
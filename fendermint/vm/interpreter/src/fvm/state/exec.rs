@@ -5,7 +5,7 @@ use std::collections::{HashMap, HashSet};
 
 use anyhow::Ok;
 use cid::Cid;
-use fendermint_vm_genesis::PowerScale;
+use fendermint_vm_genesis::{ImplicitGasLimits, MlLimits, PowerScale};
 use fvm::{
     call_manager::DefaultCallManager,
     engine::MultiEngine,
@@ -22,7 +22,12 @@ use fvm_shared::{
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 
-use crate::fvm::{externs::FendermintExterns, mlsyscall::mlsyscallkernel::MLSyscallKernelImpl};
+use crate::fvm::{
+    delivery::DeliveryStats,
+    externs::FendermintExterns,
+    mlsyscall::mlsyscallkernel::MLSyscallKernelImpl,
+    prevalidate::{check_ml_limits, Rejection},
+};
 use fendermint_vm_core::{chainid::HasChainID, Timestamp};
 use fendermint_vm_encoding::IsHumanReadable;
 
@@ -64,6 +69,14 @@ pub struct FvmStateParams {
     /// The application protocol version.
     #[serde(default)]
     pub app_version: u64,
+    /// Limits on the inputs of the machine learning actor, if any, which are only changed by
+    /// upgrades. Left out when unset so that the hash of the state stays the same.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ml_limits: Option<MlLimits>,
+    /// Gas limits of the implicit messages, if they differ from the defaults; only changed by
+    /// upgrades, and left out when unset for the same reason as `ml_limits`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub implicit_gas: Option<ImplicitGasLimits>,
 }
 
 /// Parts of the state which can be updated by message execution, apart from the actor state.
@@ -85,6 +98,10 @@ pub struct FvmUpdatableParams {
     /// Doesn't change at the moment but in theory it could,
     /// and it doesn't have a place within the FVM.
     pub power_scale: PowerScale,
+    /// Limits on the inputs of the machine learning actor, changed by upgrades.
+    pub ml_limits: Option<MlLimits>,
+    /// Gas limits of the implicit messages, changed by upgrades.
+    pub implicit_gas: Option<ImplicitGasLimits>,
 }
 
 pub type MachineBlockstore<DB> = <DefaultMachine<DB, FendermintExterns<DB>> as Machine>::Blockstore;
@@ -107,11 +124,20 @@ where
 
     /// ID of the validator who created this block. For queries and checks this is empty.
     validator_id: Option<ValidatorId>,
+
+    /// Timestamp of the parent block, if known, to tell how much time has passed since.
+    parent_timestamp: Option<Timestamp>,
     /// State of parameters that are outside the control of the FVM but can change and need to be persisted.
     params: FvmUpdatableParams,
 
     /// Indicate whether the parameters have been updated.
     params_dirty: bool,
+
+    /// Gas used by the messages of the block so far, by the actor they were sent to.
+    gas_used_by_actor: HashMap<Address, u64>,
+
+    /// The transactions delivered in the block so far.
+    delivery_stats: DeliveryStats,
 }
 
 impl<DB> FvmExecState<DB>
@@ -151,13 +177,18 @@ where
             executor,
             block_hash: None,
             validator_id: None,
+            parent_timestamp: None,
             params: FvmUpdatableParams {
                 app_version: params.app_version,
                 base_fee: params.base_fee,
                 circ_supply: params.circ_supply,
                 power_scale: params.power_scale,
+                ml_limits: params.ml_limits,
+                implicit_gas: params.implicit_gas,
             },
             params_dirty: false,
+            gas_used_by_actor: HashMap::new(),
+            delivery_stats: DeliveryStats::default(),
         })
     }
 
@@ -187,6 +218,13 @@ where
         if let Err(e) = msg.check() {
             return Ok(check_error(e));
         }
+        // System messages are trusted; the limits only apply to what users send. The actor
+        // checks the dimensions itself as well; this only rejects direct calls without running them.
+        if let (ApplyKind::Explicit, Some(limits)) = (&kind, &self.params.ml_limits) {
+            if let Err(rejection) = check_ml_limits(limits, &msg) {
+                return Ok(rejection_error(rejection));
+            }
+        }
 
         // TODO: We could preserve the message length by changing the input type.
         let raw_length = fvm_ipld_encoding::to_vec(&msg).map(|bz| bz.len())?;
@@ -195,6 +233,31 @@ where
         Ok((ret, addrs))
     }
 
+    /// Add the gas used by a message to the tally of the actor it was sent to, under its ID
+    /// address if it has one, so that messages sent to its delegated address count as well.
+    pub fn record_gas_used(&mut self, to: &Address, gas_used: u64) {
+        let to = match self.state_tree().lookup_id(to) {
+            std::result::Result::Ok(Some(id)) => Address::new_id(id),
+            _ => *to,
+        };
+        *self.gas_used_by_actor.entry(to).or_default() += gas_used;
+    }
+
+    /// Gas used by the messages recorded so far, by the actor they were sent to.
+    pub fn gas_used_by_actor(&self) -> &HashMap<Address, u64> {
+        &self.gas_used_by_actor
+    }
+
+    /// Count a delivered transaction in the stats of the block.
+    pub fn record_delivery(&mut self, exit_code: ExitCode, gas_used: u64, events: usize) {
+        self.delivery_stats.record(exit_code, gas_used, events);
+    }
+
+    /// The transactions delivered in the block so far.
+    pub fn delivery_stats(&self) -> &DeliveryStats {
+        &self.delivery_stats
+    }
+
     /// Commit the state. It must not fail, but we're returning a result so that error
     /// handling can be done in the application root.
     ///
@@ -212,6 +275,12 @@ where
         self.executor.context().epoch
     }
 
+    /// Set the timestamp of the parent block during execution.
+    pub fn with_parent_timestamp(mut self, parent_timestamp: Timestamp) -> Self {
+        self.parent_timestamp = Some(parent_timestamp);
+        self
+    }
+
     /// Identity of the block being executed, if we are indeed executing any blocks.
     pub fn block_hash(&self) -> Option<BlockHash> {
         self.block_hash
@@ -227,6 +296,12 @@ where
         Timestamp(self.executor.context().timestamp)
     }
 
+    /// Time elapsed since the parent block, if we know when it was created.
+    pub fn block_interval(&self) -> Option<std::time::Duration> {
+        self.parent_timestamp
+            .map(|p| std::time::Duration::from_secs(self.timestamp().0.saturating_sub(p.0)))
+    }
+
     /// Conversion between collateral and voting power.
     pub fn power_scale(&self) -> PowerScale {
         self.params.power_scale
@@ -236,6 +311,21 @@ where
         self.params.app_version
     }
 
+    /// Limits on the inputs of the machine learning actor, if any.
+    pub fn ml_limits(&self) -> Option<&MlLimits> {
+        self.params.ml_limits.as_ref()
+    }
+
+    /// Gas limits of the implicit messages sent by the system actor.
+    pub fn implicit_gas_limits(&self) -> ImplicitGasLimits {
+        self.params.implicit_gas.unwrap_or_default()
+    }
+
+    /// The base fee of the current block.
+    pub fn base_fee(&self) -> &TokenAmount {
+        &self.params.base_fee
+    }
+
     /// Get a mutable reference to the underlying [StateTree].
     pub fn state_tree_mut(&mut self) -> &mut StateTree<MachineBlockstore<DB>> {
         self.executor.state_tree_mut()
@@ -301,6 +391,27 @@ where
         self.update_params(|p| f(&mut p.circ_supply))
     }
 
+    /// Update the limits on the inputs of the machine learning actor, effective from the next
+    /// message. Only meant to be called by upgrades, through
+    /// [machinelearning_limits](crate::fvm::migrations::machinelearning_limits), which updates
+    /// the copy in the actor state as well.
+    pub fn update_ml_limits<F>(&mut self, f: F)
+    where
+        F: FnOnce(&mut Option<MlLimits>),
+    {
+        self.update_params(|p| f(&mut p.ml_limits))
+    }
+
+    /// Update the gas limits of the implicit messages, effective from the next one sent.
+    /// Only meant to be called by upgrades, through
+    /// [implicit_gas_limits](crate::fvm::migrations::implicit_gas_limits).
+    pub fn update_implicit_gas<F>(&mut self, f: F)
+    where
+        F: FnOnce(&mut Option<ImplicitGasLimits>),
+    {
+        self.update_params(|p| f(&mut p.implicit_gas))
+    }
+
     /// Update the parameters and mark them as dirty.
     fn update_params<F>(&mut self, f: F)
     where
@@ -328,10 +439,18 @@ where
 /// use ABCI++ to filter out messages from blocks, but that doesn't affect queries, so we
 /// might as well encode it as an error. To keep the types simpler, let's fabricate an `ApplyRet`.
 fn check_error(e: anyhow::Error) -> (ApplyRet, ActorAddressMap) {
+    rejection_error(Rejection::new(
+        ExitCode::SYS_ASSERTION_FAILED,
+        format!("{:#}", e),
+    ))
+}
+
+/// Fabricate an `ApplyRet` for a message rejected before execution, like [check_error].
+fn rejection_error(rejection: Rejection) -> (ApplyRet, ActorAddressMap) {
     let zero = TokenAmount::from_atto(0);
     let ret = ApplyRet {
         msg_receipt: Receipt {
-            exit_code: ExitCode::SYS_ASSERTION_FAILED,
+            exit_code: rejection.exit_code,
             return_data: RawBytes::default(),
             gas_used: 0,
             events_root: None,
@@ -343,7 +462,7 @@ fn check_error(e: anyhow::Error) -> (ApplyRet, ActorAddressMap) {
         refund: zero,
         gas_refund: 0,
         gas_burned: 0,
-        failure_info: Some(ApplyFailure::PreValidation(format!("{:#}", e))),
+        failure_info: Some(ApplyFailure::PreValidation(rejection.info)),
         exec_trace: Vec::new(),
         events: Vec::new(),
     };
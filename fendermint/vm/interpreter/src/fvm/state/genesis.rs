@@ -154,6 +154,8 @@ where
                     chain_id,
                     power_scale,
                     app_version: 0,
+                    ml_limits: None,
+                    implicit_gas: None,
                 };
 
                 let exec_state =
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use anyhow::{anyhow, Context};
+use ethers::abi::Tokenizable;
 use ethers::types as et;
 
 use fvm_ipld_blockstore::Blockstore;
@@ -26,13 +27,14 @@ use ipc_actors_abis::gateway_getter_facet::{self as getter, gateway_getter_facet
 use ipc_actors_abis::top_down_finality_facet::TopDownFinalityFacet;
 use ipc_actors_abis::xnet_messaging_facet::XnetMessagingFacet;
 use ipc_actors_abis::{checkpointing_facet, top_down_finality_facet, xnet_messaging_facet};
-use ipc_api::cross::IpcEnvelope;
+use ipc_api::cross::{CrossMsgOutcome, IpcEnvelope, IpcMsgKind, TopDownReceipt};
 use ipc_api::staking::{ConfigurationNumber, StakingChangeRequest};
 
 use super::{
     fevm::{ContractCaller, MockProvider, NoRevert},
     FvmExecState,
 };
+use crate::fvm::signatures::QuorumCertificate;
 use crate::fvm::FvmApplyRet;
 
 #[derive(Clone)]
@@ -189,11 +191,7 @@ impl<DB: Blockstore + Clone> GatewayCaller<DB> {
         let weight = et::U256::from(validator.power.0);
 
         let hash = checkpoint.abi_hash();
-
-        let signature = sign_secp256k1(secret_key, &hash);
-        let signature =
-            from_fvm::to_eth_signature(&signature, false).context("invalid signature")?;
-        let signature = et::Bytes::from(signature.to_vec());
+        let signature = sign_checkpoint_hash(secret_key, &hash)?;
 
         let tree =
             ValidatorMerkleTree::new(power_table).context("failed to construct Merkle tree")?;
@@ -219,6 +217,48 @@ impl<DB: Blockstore + Clone> GatewayCaller<DB> {
         Ok(calldata)
     }
 
+    /// Construct the input parameters for adding all the signatures of a quorum certificate to
+    /// the checkpoint at once.
+    ///
+    /// This will need to be broadcasted as a transaction.
+    pub fn add_checkpoint_signatures_calldata(
+        &self,
+        certificate: &QuorumCertificate,
+    ) -> anyhow::Result<et::Bytes> {
+        let tree = ValidatorMerkleTree::new(&certificate.power_table)
+            .context("failed to construct Merkle tree")?;
+
+        let mut membership_proofs = Vec::new();
+        let mut weights = Vec::new();
+        let mut signatures = Vec::new();
+
+        for (validator, signature) in certificate.signatures.iter() {
+            let proof = tree
+                .prove(validator)
+                .context("failed to construct Merkle proof")?
+                .into_iter()
+                .map(|p| p.into())
+                .collect();
+
+            membership_proofs.push(proof);
+            weights.push(et::U256::from(validator.power.0));
+            signatures.push(et::Bytes::from(signature.clone()));
+        }
+
+        let call = self.checkpointing.contract().add_checkpoint_signatures(
+            et::U256::from(certificate.height),
+            membership_proofs,
+            weights,
+            signatures,
+        );
+
+        let calldata = call
+            .calldata()
+            .ok_or_else(|| anyhow!("no calldata for adding signatures"))?;
+
+        Ok(calldata)
+    }
+
     /// Commit the parent finality to the gateway and returns the previously committed finality.
     /// None implies there is no previously committed finality.
     pub fn commit_parent_finality(
@@ -287,6 +327,54 @@ impl<DB: Blockstore + Clone> GatewayCaller<DB> {
         Ok(r.into_return())
     }
 
+    /// Apply a single cross message and work out how it went from the receipt the gateway
+    /// committed for it in the bottom-up batch. The gateway doesn't revert when a message fails,
+    /// so the return value of the call itself says nothing about it.
+    pub fn apply_cross_message(
+        &self,
+        state: &mut FvmExecState<DB>,
+        cross_message: IpcEnvelope,
+    ) -> anyhow::Result<(FvmApplyRet, TopDownReceipt)> {
+        let nonce = cross_message.nonce;
+        let message = xnet_messaging_facet::IpcEnvelope::try_from(cross_message)
+            .context("failed to convert cross message")?;
+        let id = envelope_id(&message);
+
+        let ret = self
+            .xnet
+            .call_with_return(state, |c| c.apply_cross_messages(vec![message]))?
+            .into_return();
+
+        // Receipts are committed to the batch of the next checkpoint, which is where a batch
+        // cut short for being full puts the messages following the cut, too.
+        let height = state.block_height() as u64;
+        let period = self.bottom_up_check_period(state)?;
+        let batch = self.bottom_up_msg_batch(state, (height / period + 1) * period)?;
+
+        let result = batch
+            .msgs
+            .iter()
+            .rev()
+            .filter(|msg| msg.kind == IpcMsgKind::Receipt as u8)
+            .filter_map(|msg| decode_result_msg(&msg.message).ok())
+            .find(|(res_id, _, _)| *res_id == id);
+
+        let (outcome, return_data) = match result {
+            Some((_, outcome, ret)) => (outcome, ret),
+            None => (CrossMsgOutcome::Forwarded, Vec::new()),
+        };
+
+        let receipt = TopDownReceipt {
+            nonce,
+            block_height: height,
+            outcome,
+            return_data,
+            gas_used: ret.apply_ret.msg_receipt.gas_used,
+        };
+
+        Ok((ret, receipt))
+    }
+
     pub fn get_latest_parent_finality(
         &self,
         state: &mut FvmExecState<DB>,
@@ -297,6 +385,20 @@ impl<DB: Blockstore + Clone> GatewayCaller<DB> {
         Ok(IPCParentFinality::from(r))
     }
 
+    /// Get the weight of the signatures needed for a checkpoint to reach a quorum.
+    pub fn checkpoint_quorum_threshold(
+        &self,
+        state: &mut FvmExecState<DB>,
+        height: u64,
+    ) -> anyhow::Result<u64> {
+        let info = self.getter.call(state, |c| {
+            c.get_checkpoint_info(ethers::types::U256::from(height))
+        })?;
+
+        // The threshold is a share of the total power, which fits in a `u64`.
+        Ok(info.threshold.low_u64())
+    }
+
     /// Get the Ethereum adresses of validators who signed a checkpoint.
     pub fn checkpoint_signatories(
         &self,
@@ -313,6 +415,13 @@ impl<DB: Blockstore + Clone> GatewayCaller<DB> {
     }
 }
 
+/// Sign the ABI hash of a checkpoint the way the gateway expects it, recoverable with `ECDSA.sol`.
+pub fn sign_checkpoint_hash(secret_key: &SecretKey, hash: &[u8; 32]) -> anyhow::Result<et::Bytes> {
+    let signature = sign_secp256k1(secret_key, hash);
+    let signature = from_fvm::to_eth_signature(&signature, false).context("invalid signature")?;
+    Ok(et::Bytes::from(signature.to_vec()))
+}
+
 /// Total amount of tokens to mint as a result of top-down messages arriving at the subnet.
 pub fn tokens_to_mint(msgs: &[ipc_api::cross::IpcEnvelope]) -> TokenAmount {
     msgs.iter()
@@ -336,6 +445,38 @@ pub fn tokens_to_burn(msgs: &[checkpointing_facet::IpcEnvelope]) -> TokenAmount
         })
 }
 
+/// The ID the gateway gives to a cross message in the receipts it sends, i.e. the hash of its
+/// ABI encoding.
+fn envelope_id(msg: &xnet_messaging_facet::IpcEnvelope) -> [u8; 32] {
+    ethers::utils::keccak256(ethers::abi::encode(&[msg.clone().into_token()]))
+}
+
+/// Decode the `ResultMsg` carried by a receipt into the ID of the message it's about, the outcome
+/// and the return data.
+fn decode_result_msg(bytes: &[u8]) -> anyhow::Result<([u8; 32], CrossMsgOutcome, Vec<u8>)> {
+    use ethers::abi::{ParamType, Token};
+
+    let result_msg = ParamType::Tuple(vec![
+        ParamType::FixedBytes(32),
+        ParamType::Uint(8),
+        ParamType::Bytes,
+    ]);
+    let tokens = ethers::abi::decode(&[result_msg], bytes).context("failed to decode result")?;
+
+    match tokens.as_slice() {
+        [Token::Tuple(fields)] => match fields.as_slice() {
+            [Token::FixedBytes(id), Token::Uint(outcome), Token::Bytes(ret)] if id.len() == 32 => {
+                let mut msg_id = [0u8; 32];
+                msg_id.copy_from_slice(id);
+                let outcome = CrossMsgOutcome::from_outcome_type(outcome.low_u32() as u8)?;
+                Ok((msg_id, outcome, ret.clone()))
+            }
+            _ => Err(anyhow!("unexpected result fields")),
+        },
+        _ => Err(anyhow!("unexpected result encoding")),
+    }
+}
+
 /// Convert the collaterals and metadata in the membership to the public key and power expected by the system.
 fn membership_to_power_table(
     m: &gateway_getter_facet::Membership,
@@ -356,3 +497,28 @@ fn membership_to_power_table(
 
     pt
 }
+
+#[cfg(test)]
+mod tests {
+    use ethers::abi::Token;
+    use ipc_api::cross::CrossMsgOutcome;
+
+    use super::decode_result_msg;
+
+    #[test]
+    fn result_msgs_are_decoded() {
+        let id = [7u8; 32];
+        let encoded = ethers::abi::encode(&[Token::Tuple(vec![
+            Token::FixedBytes(id.to_vec()),
+            Token::Uint(2.into()),
+            Token::Bytes(vec![0xde, 0xad]),
+        ])]);
+
+        let (msg_id, outcome, ret) = decode_result_msg(&encoded).unwrap();
+        assert_eq!(msg_id, id);
+        assert_eq!(outcome, CrossMsgOutcome::ActorErr);
+        assert_eq!(ret, vec![0xde, 0xad]);
+
+        assert!(decode_result_msg(&encoded[..40]).is_err());
+    }
+}
@@ -375,6 +375,8 @@ mod tests {
             chain_id: 1024,
             power_scale: 0,
             app_version: 0,
+            ml_limits: None,
+            implicit_gas: None,
         };
         let block_height = 2048;
 
@@ -398,4 +400,35 @@ mod tests {
             &loaded_snapshot.state_tree,
         );
     }
+
+    #[tokio::test]
+    async fn test_car_corrupted() {
+        let (state_root, state_tree) = prepare_state_tree(100);
+        let state_params = FvmStateParams {
+            state_root,
+            timestamp: Timestamp(100),
+            network_version: NetworkVersion::V1,
+            base_fee: Default::default(),
+            circ_supply: Default::default(),
+            chain_id: 1024,
+            power_scale: 0,
+            app_version: 0,
+            ml_limits: None,
+            implicit_gas: None,
+        };
+
+        let db = ReadOnlyBlockstore::new(state_tree.into_store());
+        let snapshot = Snapshot::new(db, state_params, 2048).unwrap();
+
+        let tmp_file = tempfile::NamedTempFile::new().unwrap();
+        snapshot.write_car(tmp_file.path()).await.unwrap();
+
+        // Flip the last byte of the file, which is in the data of the last block.
+        let mut bytes = std::fs::read(tmp_file.path()).unwrap();
+        *bytes.last_mut().unwrap() ^= 0xff;
+        std::fs::write(tmp_file.path(), bytes).unwrap();
+
+        let r = Snapshot::read_car(tmp_file.path(), MemoryBlockstore::new(), true).await;
+        assert!(r.is_err(), "corrupted block should fail validation");
+    }
 }
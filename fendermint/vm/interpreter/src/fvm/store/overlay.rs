@@ -0,0 +1,46 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use anyhow::Result;
+use cid::Cid;
+use fvm_ipld_blockstore::Blockstore;
+
+use super::memory::MemoryBlockstore;
+
+/// A blockstore which reads through to an underlying store, but keeps all writes in memory.
+///
+/// Useful for executing messages against historical state without leaving any trace in the database.
+#[derive(Clone)]
+pub struct OverlayBlockstore<DB> {
+    base: DB,
+    overlay: MemoryBlockstore,
+}
+
+impl<DB> OverlayBlockstore<DB> {
+    pub fn new(base: DB) -> Self {
+        Self {
+            base,
+            overlay: MemoryBlockstore::new(),
+        }
+    }
+}
+
+impl<DB> Blockstore for OverlayBlockstore<DB>
+where
+    DB: Blockstore,
+{
+    fn has(&self, k: &Cid) -> Result<bool> {
+        Ok(self.overlay.has(k)? || self.base.has(k)?)
+    }
+
+    fn get(&self, k: &Cid) -> Result<Option<Vec<u8>>> {
+        match self.overlay.get(k)? {
+            Some(data) => Ok(Some(data)),
+            None => self.base.get(k),
+        }
+    }
+
+    fn put_keyed(&self, k: &Cid, block: &[u8]) -> Result<()> {
+        self.overlay.put_keyed(k, block)
+    }
+}
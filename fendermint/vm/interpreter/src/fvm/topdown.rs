@@ -9,7 +9,7 @@ use crate::fvm::FvmApplyRet;
 use anyhow::Context;
 use fendermint_vm_topdown::{BlockHeight, IPCParentFinality, ParentViewProvider};
 use fvm_ipld_blockstore::Blockstore;
-use ipc_api::cross::IpcEnvelope;
+use ipc_api::cross::{IpcEnvelope, TopDownReceipt};
 
 use super::state::ipc::tokens_to_mint;
 
@@ -40,11 +40,14 @@ where
 
 /// Execute the top down messages implicitly. Before the execution, mint to the gateway of the funds
 /// transferred in the messages, and increase the circulating supply with the incoming value.
+///
+/// The messages are applied one by one, so that each of them gets a receipt of its own; the
+/// returned result adds up the gas and events of all of them.
 pub async fn execute_topdown_msgs<DB>(
     gateway_caller: &GatewayCaller<DB>,
     state: &mut FvmExecState<DB>,
     messages: Vec<IpcEnvelope>,
-) -> anyhow::Result<FvmApplyRet>
+) -> anyhow::Result<(FvmApplyRet, Vec<TopDownReceipt>)>
 where
     DB: Blockstore + Sync + Send + Clone + 'static,
 {
@@ -61,5 +64,44 @@ where
         });
     }
 
-    gateway_caller.apply_cross_messages(state, messages)
+    let mut total: Option<FvmApplyRet> = None;
+    let mut receipts = Vec::with_capacity(messages.len());
+
+    for msg in messages {
+        let (ret, receipt) = gateway_caller.apply_cross_message(state, msg)?;
+
+        tracing::debug!(
+            nonce = receipt.nonce,
+            outcome = receipt.outcome.to_string(),
+            gas_used = receipt.gas_used,
+            "applied topdown msg"
+        );
+
+        receipts.push(receipt);
+        total = Some(match total {
+            None => ret,
+            Some(total) => merge_apply_ret(total, ret),
+        });
+    }
+
+    let ret = match total {
+        Some(ret) => ret,
+        None => gateway_caller.apply_cross_messages(state, Vec::new())?,
+    };
+
+    Ok((ret, receipts))
+}
+
+/// Fold the result of an implicit call into the one of the previous calls. These calls pay no
+/// fees, so only the gas, the events and the traces need adding up.
+fn merge_apply_ret(mut total: FvmApplyRet, ret: FvmApplyRet) -> FvmApplyRet {
+    let receipt = &mut total.apply_ret.msg_receipt;
+    receipt.gas_used += ret.apply_ret.msg_receipt.gas_used;
+    // The events no longer match a single root.
+    receipt.events_root = None;
+
+    total.apply_ret.events.extend(ret.apply_ret.events);
+    total.apply_ret.exec_trace.extend(ret.apply_ret.exec_trace);
+    total.emitters.extend(ret.emitters);
+    total
 }
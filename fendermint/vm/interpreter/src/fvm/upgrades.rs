@@ -3,7 +3,7 @@
 
 use std::collections::BTreeMap;
 
-use anyhow::bail;
+use anyhow::{bail, Context};
 use fendermint_vm_core::chainid;
 use fvm_ipld_blockstore::Blockstore;
 use fvm_shared::chainid::ChainID;
@@ -35,6 +35,64 @@ impl Ord for UpgradeKey {
 // TODO: Add missing parameters
 pub type MigrationFunc<DB> = fn(state: &mut FvmExecState<DB>) -> anyhow::Result<()>;
 
+/// a function type for the validations run before and after a migration, e.g. that an actor
+/// code CID is in the manifest or that a state invariant holds
+pub type CheckFunc<DB> = fn(state: &mut FvmExecState<DB>) -> anyhow::Result<()>;
+
+/// UpgradeStage is a named migration along with the checks it relies on and the ones
+/// confirming it did what it should
+#[derive(Clone)]
+pub struct UpgradeStage<DB>
+where
+    DB: Blockstore + 'static + Clone,
+{
+    name: String,
+    pre_checks: Vec<CheckFunc<DB>>,
+    migration: MigrationFunc<DB>,
+    post_checks: Vec<CheckFunc<DB>>,
+}
+
+impl<DB> UpgradeStage<DB>
+where
+    DB: Blockstore + 'static + Clone,
+{
+    pub fn new(name: impl ToString, migration: MigrationFunc<DB>) -> Self {
+        Self {
+            name: name.to_string(),
+            pre_checks: Vec::new(),
+            migration,
+            post_checks: Vec::new(),
+        }
+    }
+
+    /// Add a check that has to pass before the migration runs.
+    pub fn with_pre_check(mut self, check: CheckFunc<DB>) -> Self {
+        self.pre_checks.push(check);
+        self
+    }
+
+    /// Add a check that has to pass after the migration ran.
+    pub fn with_post_check(mut self, check: CheckFunc<DB>) -> Self {
+        self.post_checks.push(check);
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn execute(&self, state: &mut FvmExecState<DB>) -> anyhow::Result<()> {
+        for (i, check) in self.pre_checks.iter().enumerate() {
+            check(state).with_context(|| format!("pre-check #{i} failed"))?;
+        }
+        (self.migration)(state).context("migration failed")?;
+        for (i, check) in self.post_checks.iter().enumerate() {
+            check(state).with_context(|| format!("post-check #{i} failed"))?;
+        }
+        Ok(())
+    }
+}
+
 /// Upgrade represents a single upgrade to be executed at a given height
 #[derive(Clone)]
 pub struct Upgrade<DB>
@@ -47,8 +105,8 @@ where
     block_height: BlockHeight,
     /// the application version after the upgrade (or None if not affected)
     new_app_version: Option<u64>,
-    /// the migration function to be executed
-    migration: MigrationFunc<DB>,
+    /// the stages to be executed, in order
+    stages: Vec<UpgradeStage<DB>>,
 }
 
 impl<DB> Upgrade<DB>
@@ -61,12 +119,12 @@ where
         new_app_version: Option<u64>,
         migration: MigrationFunc<DB>,
     ) -> anyhow::Result<Self> {
-        Ok(Self {
-            chain_id: chainid::from_str_hashed(&chain_name.to_string())?,
+        Self::new_staged(
+            chain_name,
             block_height,
             new_app_version,
-            migration,
-        })
+            vec![UpgradeStage::new("migration", migration)],
+        )
     }
 
     pub fn new_by_id(
@@ -79,12 +137,41 @@ where
             chain_id,
             block_height,
             new_app_version,
-            migration,
+            stages: vec![UpgradeStage::new("migration", migration)],
         }
     }
 
+    /// Create an upgrade which executes the stages in order.
+    pub fn new_staged(
+        chain_name: impl ToString,
+        block_height: BlockHeight,
+        new_app_version: Option<u64>,
+        stages: Vec<UpgradeStage<DB>>,
+    ) -> anyhow::Result<Self> {
+        if stages.is_empty() {
+            bail!("an upgrade needs at least one stage");
+        }
+        Ok(Self {
+            chain_id: chainid::from_str_hashed(&chain_name.to_string())?,
+            block_height,
+            new_app_version,
+            stages,
+        })
+    }
+
+    /// Execute the stages in order. If any of them fails, the changes made to the state tree
+    /// by the upgrade are reverted and the error names the stage.
     pub fn execute(&self, state: &mut FvmExecState<DB>) -> anyhow::Result<Option<u64>> {
-        (self.migration)(state)?;
+        state.state_tree_mut().begin_transaction();
+
+        let res = self.stages.iter().enumerate().try_for_each(|(i, stage)| {
+            stage
+                .execute(state)
+                .with_context(|| format!("upgrade stage #{i} '{}' failed", stage.name))
+        });
+
+        state.state_tree_mut().end_transaction(res.is_err())?;
+        res?;
 
         Ok(self.new_app_version)
     }
@@ -171,3 +258,19 @@ fn test_validate_upgrade_schedule() {
     assert!(upgrade_scheduler.get(mychain_id, 10).is_some());
     assert!(upgrade_scheduler.get(otherhain_id, 10).is_none());
 }
+
+#[test]
+fn test_staged_upgrade_needs_stages() {
+    use crate::fvm::store::memory::MemoryBlockstore;
+
+    let res = Upgrade::<MemoryBlockstore>::new_staged("mychain", 10, None, vec![]);
+    assert!(res.is_err());
+
+    let stage = UpgradeStage::new("deploy", |_state| Ok(()))
+        .with_pre_check(|_state| Ok(()))
+        .with_post_check(|_state| Ok(()));
+    assert_eq!(stage.name(), "deploy");
+
+    let upgrade = Upgrade::new_staged("mychain", 10, Some(1), vec![stage]).unwrap();
+    assert_eq!(upgrade.stages.len(), 1);
+}
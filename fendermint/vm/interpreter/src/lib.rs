@@ -5,7 +5,9 @@ use async_trait::async_trait;
 pub mod bytes;
 pub mod chain;
 pub mod fvm;
+pub mod replay;
 pub mod signed;
+pub mod simulator;
 
 #[cfg(feature = "arb")]
 mod arb;
@@ -0,0 +1,601 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Deterministic re-execution of historical blocks.
+//!
+//! The [Replayer] takes the state parameters committed before a range of blocks, re-executes
+//! the messages of every block in the range through an interpreter, and compares the resulting
+//! state roots with the ones recorded by the node which originally executed them. The first
+//! block where they differ is reported, which is where a consensus fault was introduced.
+//!
+//! All writes go to an in-memory overlay, so replaying leaves no trace in the database.
+//!
+//! The blocks can be given directly, or fetched one by one from a [BlockSource], such as the
+//! [CometBftBlockSource] which reads them from the node that finalized them.
+
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context};
+use async_trait::async_trait;
+use cid::Cid;
+use fendermint_vm_core::Timestamp;
+use fvm::engine::MultiEngine;
+use fvm_ipld_blockstore::Blockstore;
+use fvm_shared::clock::ChainEpoch;
+
+use crate::fvm::state::{BlockHash, FvmExecState, FvmStateParams, FvmUpdatableParams};
+use crate::fvm::store::overlay::OverlayBlockstore;
+use crate::ExecInterpreter;
+
+/// A historical block to re-execute.
+#[derive(Clone)]
+pub struct ReplayBlock<M> {
+    pub height: ChainEpoch,
+    pub timestamp: Timestamp,
+    pub block_hash: BlockHash,
+    pub proposer: tendermint::account::Id,
+    /// Messages in the order they were delivered.
+    pub messages: Vec<M>,
+    /// The state root committed at the end of the block, as originally recorded, if known.
+    pub expected_state_root: Option<Cid>,
+}
+
+/// Where the historical blocks to replay are read from.
+#[async_trait]
+pub trait BlockSource: Sync {
+    type Message: Send;
+
+    /// Fetch the block at the given height.
+    async fn block(&self, height: ChainEpoch) -> anyhow::Result<ReplayBlock<Self::Message>>;
+}
+
+/// Read the blocks from CometBFT through its RPC API, with their transactions as raw bytes.
+///
+/// CometBFT doesn't know about state roots, so the blocks have no expected state root,
+/// unless they are filled in with [WithStateRoots].
+pub struct CometBftBlockSource<C> {
+    client: C,
+}
+
+impl<C> CometBftBlockSource<C> {
+    pub fn new(client: C) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl<C> BlockSource for CometBftBlockSource<C>
+where
+    C: tendermint_rpc::Client + Sync,
+{
+    type Message = Vec<u8>;
+
+    async fn block(&self, height: ChainEpoch) -> anyhow::Result<ReplayBlock<Self::Message>> {
+        let tm_height = tendermint::block::Height::try_from(height)
+            .with_context(|| format!("invalid block height: {height}"))?;
+
+        let res = self
+            .client
+            .block(tm_height)
+            .await
+            .with_context(|| format!("failed to fetch block {height}"))?;
+
+        let block_hash = match res.block_id.hash {
+            tendermint::Hash::Sha256(h) => h,
+            tendermint::Hash::None => return Err(anyhow!("block {height} has no hash")),
+        };
+
+        let header = res.block.header;
+        let timestamp = header
+            .time
+            .unix_timestamp()
+            .try_into()
+            .map(Timestamp)
+            .with_context(|| format!("block {height} has a negative timestamp"))?;
+
+        Ok(ReplayBlock {
+            height,
+            timestamp,
+            block_hash,
+            proposer: header.proposer_address,
+            messages: res.block.data,
+            expected_state_root: None,
+        })
+    }
+}
+
+/// Fill in the expected state roots of the blocks of another source,
+/// e.g. from the state history of the application.
+pub struct WithStateRoots<S, F> {
+    source: S,
+    state_root: F,
+}
+
+impl<S, F> WithStateRoots<S, F> {
+    /// Look up the state root committed at the end of each block with `state_root`.
+    pub fn new(source: S, state_root: F) -> Self {
+        Self { source, state_root }
+    }
+}
+
+#[async_trait]
+impl<S, F> BlockSource for WithStateRoots<S, F>
+where
+    S: BlockSource,
+    F: Fn(ChainEpoch) -> anyhow::Result<Option<Cid>> + Sync,
+{
+    type Message = S::Message;
+
+    async fn block(&self, height: ChainEpoch) -> anyhow::Result<ReplayBlock<Self::Message>> {
+        let mut block = self.source.block(height).await?;
+        if block.expected_state_root.is_none() {
+            block.expected_state_root = (self.state_root)(height)
+                .with_context(|| format!("failed to look up the state root of block {height}"))?;
+        }
+        Ok(block)
+    }
+}
+
+/// The first block where the replayed state root differs from the recorded one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    pub height: ChainEpoch,
+    pub expected: Cid,
+    pub actual: Cid,
+}
+
+/// Outcome of replaying a range of blocks.
+#[derive(Debug, Clone)]
+pub struct ReplayReport {
+    /// Number of blocks executed, including the divergent one, if any.
+    pub blocks: usize,
+    /// State parameters after the last executed block.
+    pub state_params: FvmStateParams,
+    /// The first divergence; replay stops there.
+    pub divergence: Option<Divergence>,
+}
+
+/// Re-execute blocks through the full interpreter stack.
+///
+/// The interpreter state is expected to be an environment `E` paired with the FVM state,
+/// as is the case for the chain and bytes interpreters.
+pub struct Replayer<I, DB> {
+    interpreter: I,
+    store: DB,
+    multi_engine: Arc<MultiEngine>,
+}
+
+impl<I, DB> Replayer<I, DB>
+where
+    DB: Blockstore + Clone + 'static,
+{
+    pub fn new(interpreter: I, store: DB, multi_engine: Arc<MultiEngine>) -> Self {
+        Self {
+            interpreter,
+            store,
+            multi_engine,
+        }
+    }
+
+    /// Replay `blocks` on top of `state_params`, stopping at the first divergence.
+    pub async fn replay<E, M, B>(
+        &self,
+        env: E,
+        mut state_params: FvmStateParams,
+        blocks: B,
+    ) -> anyhow::Result<ReplayReport>
+    where
+        E: Clone + Send,
+        I: ExecInterpreter<State = (E, FvmExecState<OverlayBlockstore<DB>>), Message = M>,
+        B: IntoIterator<Item = ReplayBlock<M>>,
+    {
+        // Share the overlay between blocks, so that each block can see what the previous one wrote.
+        let store = OverlayBlockstore::new(self.store.clone());
+        let mut count = 0;
+
+        for block in blocks {
+            let (params, divergence) = self
+                .replay_and_compare(env.clone(), store.clone(), state_params, block)
+                .await?;
+
+            state_params = params;
+            count += 1;
+
+            if divergence.is_some() {
+                return Ok(ReplayReport {
+                    blocks: count,
+                    state_params,
+                    divergence,
+                });
+            }
+        }
+
+        Ok(ReplayReport {
+            blocks: count,
+            state_params,
+            divergence: None,
+        })
+    }
+
+    /// Replay the blocks at `heights` from `source` on top of `state_params`, stopping at the first divergence.
+    ///
+    /// The blocks are fetched one at a time, so long ranges don't have to fit into memory.
+    pub async fn replay_source<E, S>(
+        &self,
+        env: E,
+        mut state_params: FvmStateParams,
+        source: &S,
+        heights: RangeInclusive<ChainEpoch>,
+    ) -> anyhow::Result<ReplayReport>
+    where
+        E: Clone + Send,
+        S: BlockSource,
+        I: ExecInterpreter<State = (E, FvmExecState<OverlayBlockstore<DB>>), Message = S::Message>,
+    {
+        let store = OverlayBlockstore::new(self.store.clone());
+        let mut count = 0;
+
+        for height in heights {
+            let block = source.block(height).await?;
+
+            let (params, divergence) = self
+                .replay_and_compare(env.clone(), store.clone(), state_params, block)
+                .await?;
+
+            state_params = params;
+            count += 1;
+
+            if divergence.is_some() {
+                return Ok(ReplayReport {
+                    blocks: count,
+                    state_params,
+                    divergence,
+                });
+            }
+        }
+
+        Ok(ReplayReport {
+            blocks: count,
+            state_params,
+            divergence: None,
+        })
+    }
+
+    /// Replay a block and compare the resulting state root with the expected one, if known.
+    async fn replay_and_compare<E, M>(
+        &self,
+        env: E,
+        store: OverlayBlockstore<DB>,
+        state_params: FvmStateParams,
+        block: ReplayBlock<M>,
+    ) -> anyhow::Result<(FvmStateParams, Option<Divergence>)>
+    where
+        E: Send,
+        I: ExecInterpreter<State = (E, FvmExecState<OverlayBlockstore<DB>>), Message = M>,
+    {
+        let height = block.height;
+        let expected = block.expected_state_root;
+
+        let state_params = self
+            .replay_block(env, store, state_params, block)
+            .await
+            .with_context(|| format!("failed to replay block {height}"))?;
+
+        match expected {
+            Some(expected) if expected != state_params.state_root => {
+                tracing::warn!(
+                    height,
+                    expected = expected.to_string(),
+                    actual = state_params.state_root.to_string(),
+                    "replay diverged"
+                );
+                let divergence = Divergence {
+                    height,
+                    expected,
+                    actual: state_params.state_root,
+                };
+                Ok((state_params, Some(divergence)))
+            }
+            _ => {
+                tracing::debug!(
+                    height,
+                    state_root = state_params.state_root.to_string(),
+                    "replayed block"
+                );
+                Ok((state_params, None))
+            }
+        }
+    }
+
+    /// Execute a single block the same way the application does, returning the parameters it would commit.
+    async fn replay_block<E, M>(
+        &self,
+        env: E,
+        store: OverlayBlockstore<DB>,
+        mut state_params: FvmStateParams,
+        block: ReplayBlock<M>,
+    ) -> anyhow::Result<FvmStateParams>
+    where
+        E: Send,
+        I: ExecInterpreter<State = (E, FvmExecState<OverlayBlockstore<DB>>), Message = M>,
+    {
+        let parent_timestamp = state_params.timestamp;
+        state_params.timestamp = block.timestamp;
+
+        let state = FvmExecState::new(
+            store,
+            self.multi_engine.as_ref(),
+            block.height,
+            state_params.clone(),
+        )
+        .context("error creating new state")?
+        .with_block_hash(block.block_hash)
+        .with_validator_id(block.proposer)
+        .with_parent_timestamp(parent_timestamp);
+
+        let (mut state, _) = self
+            .interpreter
+            .begin((env, state))
+            .await
+            .context("begin failed")?;
+
+        for msg in block.messages {
+            let (s, _) = self
+                .interpreter
+                .deliver(state, msg)
+                .await
+                .context("deliver failed")?;
+            state = s;
+        }
+
+        let ((_, state), _) = self.interpreter.end(state).await.context("end failed")?;
+
+        let (
+            state_root,
+            FvmUpdatableParams {
+                app_version,
+                base_fee,
+                circ_supply,
+                power_scale,
+                ml_limits,
+                implicit_gas,
+            },
+            _,
+        ) = state.commit().context("failed to commit FVM")?;
+
+        state_params.state_root = state_root;
+        state_params.app_version = app_version;
+        state_params.base_fee = base_fee;
+        state_params.circ_supply = circ_supply;
+        state_params.power_scale = power_scale;
+        state_params.ml_limits = ml_limits;
+        state_params.implicit_gas = implicit_gas;
+
+        Ok(state_params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use async_trait::async_trait;
+    use cid::Cid;
+    use fendermint_vm_genesis::{Account, Actor, ActorMeta, Genesis, SignerAddr};
+    use fvm_shared::address::Address;
+    use fvm_shared::clock::ChainEpoch;
+    use fvm_shared::econ::TokenAmount;
+    use fvm_shared::METHOD_SEND;
+    use quickcheck::Arbitrary;
+    use tendermint_rpc::{MockClient, MockRequestMethodMatcher};
+
+    use crate::fvm::bundle::{bundle_path, contracts_path, custom_actors_bundle_path};
+    use crate::fvm::state::FvmStateParams;
+    use crate::fvm::store::memory::MemoryBlockstore;
+    use crate::fvm::store::overlay::OverlayBlockstore;
+    use crate::fvm::upgrades::UpgradeScheduler;
+    use crate::fvm::{FvmMessage, FvmMessageInterpreter};
+    use crate::simulator::Simulator;
+    use crate::ExecInterpreter;
+
+    use super::{BlockSource, Divergence, ReplayBlock, Replayer, WithStateRoots};
+
+    type TestInterpreter<DB> = FvmMessageInterpreter<DB, MockClient<MockRequestMethodMatcher>>;
+
+    /// Pair the FVM state with an empty environment, the way the chain interpreter does with its own.
+    struct WithEnv<I>(I);
+
+    #[async_trait]
+    impl<I> ExecInterpreter for WithEnv<I>
+    where
+        I: ExecInterpreter,
+    {
+        type State = ((), I::State);
+        type Message = I::Message;
+        type BeginOutput = I::BeginOutput;
+        type DeliverOutput = I::DeliverOutput;
+        type EndOutput = I::EndOutput;
+
+        async fn begin(
+            &self,
+            (env, state): Self::State,
+        ) -> anyhow::Result<(Self::State, Self::BeginOutput)> {
+            let (state, ret) = self.0.begin(state).await?;
+            Ok(((env, state), ret))
+        }
+
+        async fn deliver(
+            &self,
+            (env, state): Self::State,
+            msg: Self::Message,
+        ) -> anyhow::Result<(Self::State, Self::DeliverOutput)> {
+            let (state, ret) = self.0.deliver(state, msg).await?;
+            Ok(((env, state), ret))
+        }
+
+        async fn end(
+            &self,
+            (env, state): Self::State,
+        ) -> anyhow::Result<(Self::State, Self::EndOutput)> {
+            let (state, ret) = self.0.end(state).await?;
+            Ok(((env, state), ret))
+        }
+    }
+
+    /// Serve the blocks produced by the simulator, without their state roots.
+    struct SimulatedSource {
+        blocks: HashMap<ChainEpoch, ReplayBlock<FvmMessage>>,
+    }
+
+    #[async_trait]
+    impl BlockSource for SimulatedSource {
+        type Message = FvmMessage;
+
+        async fn block(&self, height: ChainEpoch) -> anyhow::Result<ReplayBlock<FvmMessage>> {
+            self.blocks
+                .get(&height)
+                .cloned()
+                .ok_or_else(|| anyhow::anyhow!("no block at height {height}"))
+        }
+    }
+
+    fn make_interpreter<DB>() -> TestInterpreter<DB>
+    where
+        DB: fvm_ipld_blockstore::Blockstore + Clone + 'static,
+    {
+        let (client, _) = MockClient::new(MockRequestMethodMatcher::default());
+        FvmMessageInterpreter::new(
+            client,
+            None,
+            contracts_path(),
+            1.05,
+            1.05,
+            false,
+            UpgradeScheduler::new(),
+        )
+    }
+
+    fn transfer(from: Address, to: Address, sequence: u64, value: TokenAmount) -> FvmMessage {
+        FvmMessage {
+            version: 0,
+            from,
+            to,
+            sequence,
+            value,
+            method_num: METHOD_SEND,
+            params: Default::default(),
+            gas_limit: 10_000_000,
+            gas_fee_cap: TokenAmount::from_atto(100),
+            gas_premium: TokenAmount::from_atto(0),
+        }
+    }
+
+    /// Produce a few blocks with the simulator, returning the parameters of the genesis,
+    /// the blocks and the state roots they committed.
+    async fn make_history(
+        sender: Address,
+        recipient: Address,
+    ) -> (
+        Simulator<TestInterpreter<MemoryBlockstore>>,
+        FvmStateParams,
+        SimulatedSource,
+        HashMap<ChainEpoch, Cid>,
+    ) {
+        let mut g = quickcheck::Gen::new(5);
+        let mut genesis = Genesis::arbitrary(&mut g);
+        genesis.base_fee = TokenAmount::from_atto(100);
+        genesis.accounts.push(Actor {
+            meta: ActorMeta::Account(Account {
+                owner: SignerAddr(sender),
+            }),
+            balance: TokenAmount::from_whole(10),
+        });
+
+        let bundle = std::fs::read(bundle_path()).expect("failed to read bundle");
+        let custom_actors_bundle =
+            std::fs::read(custom_actors_bundle_path()).expect("failed to read custom bundle");
+
+        let mut sim = Simulator::new(make_interpreter(), genesis, &bundle, &custom_actors_bundle)
+            .await
+            .expect("failed to create simulator");
+
+        let genesis_params = sim.state_params().clone();
+        let mut blocks = HashMap::new();
+        let mut state_roots = HashMap::new();
+
+        for sequence in 0..3 {
+            let messages = vec![transfer(
+                sender,
+                recipient,
+                sequence,
+                TokenAmount::from_whole(1),
+            )];
+            let block = sim
+                .produce_block(messages.clone())
+                .await
+                .expect("failed to produce block");
+            state_roots.insert(block.height, block.state_root);
+            blocks.insert(
+                block.height,
+                ReplayBlock {
+                    height: block.height,
+                    timestamp: block.timestamp,
+                    block_hash: block.block_hash,
+                    proposer: tendermint::account::Id::new([0; 20]),
+                    messages,
+                    expected_state_root: None,
+                },
+            );
+        }
+
+        (sim, genesis_params, SimulatedSource { blocks }, state_roots)
+    }
+
+    #[tokio::test]
+    async fn replay_detects_divergence() {
+        let sender = Address::new_secp256k1(&[4u8; 65]).unwrap();
+        let recipient = Address::new_secp256k1(&[5u8; 65]).unwrap();
+        let (sim, genesis_params, source, state_roots) = make_history(sender, recipient).await;
+
+        let mut source = WithStateRoots::new(source, {
+            let state_roots = state_roots.clone();
+            move |h| Ok(state_roots.get(&h).cloned())
+        });
+
+        let replayer = Replayer::new(
+            WithEnv(make_interpreter::<OverlayBlockstore<MemoryBlockstore>>()),
+            sim.store().clone(),
+            Default::default(),
+        );
+
+        // Replaying the same messages reproduces the recorded state roots.
+        let report = replayer
+            .replay_source((), genesis_params.clone(), &source, 1..=3)
+            .await
+            .expect("failed to replay");
+
+        assert_eq!(report.blocks, 3);
+        assert_eq!(report.divergence, None);
+        assert_eq!(report.state_params.state_root, state_roots[&3]);
+
+        // Tamper with the second block, as if it had been executed differently originally.
+        source.source.blocks.get_mut(&2).unwrap().messages[0].value = TokenAmount::from_whole(2);
+
+        let report = replayer
+            .replay_source((), genesis_params, &source, 1..=3)
+            .await
+            .expect("failed to replay");
+
+        assert_eq!(report.blocks, 2);
+        assert_eq!(
+            report.divergence,
+            Some(Divergence {
+                height: 2,
+                expected: state_roots[&2],
+                actual: report.state_params.state_root,
+            })
+        );
+        assert_ne!(report.state_params.state_root, state_roots[&2]);
+    }
+}
@@ -0,0 +1,398 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Block production without consensus.
+//!
+//! The [Simulator] initializes the ledger from a genesis file in memory, then executes batches
+//! of messages as blocks through the interpreter, the same way the application does when
+//! CometBFT finalizes a block, but without a node, a mempool or any networking. It is meant for
+//! actor developers who want to check the outcome of their messages quickly, and for tests and
+//! benchmarks which need a ledger that moves forward block by block.
+//!
+//! Block hashes and timestamps are derived from the height, so the same genesis and the same
+//! messages always result in the same state roots.
+
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context};
+use cid::Cid;
+use fendermint_vm_core::Timestamp;
+use fendermint_vm_genesis::Genesis;
+use fvm::engine::MultiEngine;
+use fvm_shared::clock::ChainEpoch;
+
+use crate::fvm::state::{
+    BlockHash, FvmExecState, FvmGenesisState, FvmStateParams, FvmUpdatableParams,
+};
+use crate::fvm::store::memory::MemoryBlockstore;
+use crate::fvm::{FvmApplyRet, FvmEndRet, FvmGenesisOutput, FvmMessage};
+use crate::{ExecInterpreter, GenesisInterpreter};
+
+/// Seconds between the timestamps of consecutive blocks, unless configured otherwise.
+const DEFAULT_BLOCK_INTERVAL_SECS: u64 = 1;
+
+/// The outcome of a simulated block.
+pub struct SimulatedBlock {
+    pub height: ChainEpoch,
+    pub timestamp: Timestamp,
+    pub block_hash: BlockHash,
+    /// The state root committed at the end of the block.
+    pub state_root: Cid,
+    /// Results of the implicit messages executed at the beginning of the block.
+    pub begin: FvmApplyRet,
+    /// Results of the messages, in the order they were delivered.
+    pub receipts: Vec<FvmApplyRet>,
+    /// Validator power changes applied at the end of the block.
+    pub end: FvmEndRet,
+}
+
+/// Execute blocks of messages on top of a genesis state kept in memory.
+pub struct Simulator<I> {
+    interpreter: I,
+    store: MemoryBlockstore,
+    multi_engine: Arc<MultiEngine>,
+    state_params: FvmStateParams,
+    genesis_timestamp: Timestamp,
+    block_interval_secs: u64,
+    height: ChainEpoch,
+}
+
+impl<I> Simulator<I>
+where
+    I: GenesisInterpreter<
+        State = FvmGenesisState<MemoryBlockstore>,
+        Genesis = Genesis,
+        Output = FvmGenesisOutput,
+    >,
+    I: ExecInterpreter<
+        State = FvmExecState<MemoryBlockstore>,
+        Message = FvmMessage,
+        BeginOutput = FvmApplyRet,
+        DeliverOutput = FvmApplyRet,
+        EndOutput = FvmEndRet,
+    >,
+{
+    /// Initialize the ledger from the genesis, using the given actor bundles.
+    ///
+    /// The genesis itself is at height 0; the first produced block is at height 1.
+    pub async fn new(
+        interpreter: I,
+        genesis: Genesis,
+        bundle: &[u8],
+        custom_actors_bundle: &[u8],
+    ) -> anyhow::Result<Self> {
+        let store = MemoryBlockstore::new();
+        let multi_engine = Arc::new(MultiEngine::default());
+
+        let state = FvmGenesisState::new(
+            store.clone(),
+            multi_engine.clone(),
+            bundle,
+            custom_actors_bundle,
+        )
+        .await
+        .context("failed to create genesis state")?;
+
+        let (state, out) = interpreter
+            .init(state, genesis)
+            .await
+            .context("failed to init from genesis")?;
+
+        let state_root = state.commit().context("failed to commit genesis state")?;
+
+        tracing::debug!(
+            state_root = state_root.to_string(),
+            "simulator initialized from genesis"
+        );
+
+        Ok(Self {
+            interpreter,
+            store,
+            multi_engine,
+            genesis_timestamp: out.timestamp,
+            state_params: FvmStateParams {
+                state_root,
+                timestamp: out.timestamp,
+                network_version: out.network_version,
+                base_fee: out.base_fee,
+                circ_supply: out.circ_supply,
+                chain_id: out.chain_id.into(),
+                power_scale: out.power_scale,
+                app_version: 0,
+                ml_limits: out.ml_limits,
+                implicit_gas: out.implicit_gas,
+            },
+            block_interval_secs: DEFAULT_BLOCK_INTERVAL_SECS,
+            height: 0,
+        })
+    }
+
+    /// Set the number of seconds between the timestamps of consecutive blocks.
+    pub fn with_block_interval(mut self, secs: u64) -> Self {
+        self.block_interval_secs = secs;
+        self
+    }
+
+    /// Height of the last produced block, 0 if only the genesis has been executed.
+    pub fn height(&self) -> ChainEpoch {
+        self.height
+    }
+
+    /// State parameters committed by the last block.
+    pub fn state_params(&self) -> &FvmStateParams {
+        &self.state_params
+    }
+
+    /// The store holding the ledger, to look up data by CID.
+    pub fn store(&self) -> &MemoryBlockstore {
+        &self.store
+    }
+
+    /// A state on top of the last committed one, to inspect actors or run read-only calls.
+    ///
+    /// Changes made through it are discarded unless they go through [Simulator::produce_block].
+    pub fn exec_state(&self) -> anyhow::Result<FvmExecState<MemoryBlockstore>> {
+        FvmExecState::new(
+            self.store.clone(),
+            self.multi_engine.as_ref(),
+            self.height,
+            self.state_params.clone(),
+        )
+        .context("error creating new state")
+    }
+
+    /// Execute the messages in a new block and commit its state.
+    ///
+    /// Messages sent by the system actor are executed as implicit messages, all others have to
+    /// come from existing accounts with the right sequence and enough funds to pay for gas, but
+    /// their signatures are not checked.
+    pub async fn produce_block(
+        &mut self,
+        messages: Vec<FvmMessage>,
+    ) -> anyhow::Result<SimulatedBlock> {
+        let height = self.height + 1;
+        let timestamp = self.block_timestamp(height)?;
+        let block_hash = block_hash(height);
+
+        let mut state_params = self.state_params.clone();
+        let parent_timestamp = state_params.timestamp;
+        state_params.timestamp = timestamp;
+
+        let state = FvmExecState::new(
+            self.store.clone(),
+            self.multi_engine.as_ref(),
+            height,
+            state_params.clone(),
+        )
+        .context("error creating new state")?
+        .with_block_hash(block_hash)
+        .with_parent_timestamp(parent_timestamp);
+
+        let (mut state, begin) = self
+            .interpreter
+            .begin(state)
+            .await
+            .with_context(|| format!("begin failed at height {height}"))?;
+
+        let mut receipts = Vec::with_capacity(messages.len());
+        for msg in messages {
+            let (s, ret) = self
+                .interpreter
+                .deliver(state, msg)
+                .await
+                .with_context(|| format!("deliver failed at height {height}"))?;
+            state = s;
+            receipts.push(ret);
+        }
+
+        let (state, end) = self
+            .interpreter
+            .end(state)
+            .await
+            .with_context(|| format!("end failed at height {height}"))?;
+
+        let (
+            state_root,
+            FvmUpdatableParams {
+                app_version,
+                base_fee,
+                circ_supply,
+                power_scale,
+                ml_limits,
+                implicit_gas,
+            },
+            _,
+        ) = state.commit().context("failed to commit FVM")?;
+
+        state_params.state_root = state_root;
+        state_params.app_version = app_version;
+        state_params.base_fee = base_fee;
+        state_params.circ_supply = circ_supply;
+        state_params.power_scale = power_scale;
+        state_params.ml_limits = ml_limits;
+        state_params.implicit_gas = implicit_gas;
+
+        self.state_params = state_params;
+        self.height = height;
+
+        tracing::debug!(
+            height,
+            state_root = state_root.to_string(),
+            messages = receipts.len(),
+            "simulated block"
+        );
+
+        Ok(SimulatedBlock {
+            height,
+            timestamp,
+            block_hash,
+            state_root,
+            begin,
+            receipts,
+            end,
+        })
+    }
+
+    /// Produce empty blocks until the given height, e.g. to let time based logic kick in.
+    pub async fn advance_to(&mut self, height: ChainEpoch) -> anyhow::Result<()> {
+        while self.height < height {
+            self.produce_block(Vec::new()).await?;
+        }
+        Ok(())
+    }
+
+    fn block_timestamp(&self, height: ChainEpoch) -> anyhow::Result<Timestamp> {
+        let elapsed = u64::try_from(height)
+            .ok()
+            .and_then(|h| h.checked_mul(self.block_interval_secs))
+            .ok_or_else(|| anyhow!("timestamp overflow at height {height}"))?;
+        Ok(Timestamp(self.genesis_timestamp.0 + elapsed))
+    }
+}
+
+/// A made up block hash, unique to the height.
+fn block_hash(height: ChainEpoch) -> BlockHash {
+    let mut hash = BlockHash::default();
+    hash[..8].copy_from_slice(&height.to_be_bytes());
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use fendermint_vm_genesis::{Account, Actor, ActorMeta, Genesis, SignerAddr};
+    use fvm_shared::address::Address;
+    use fvm_shared::econ::TokenAmount;
+    use fvm_shared::METHOD_SEND;
+    use quickcheck::Arbitrary;
+    use tendermint_rpc::{MockClient, MockRequestMethodMatcher};
+
+    use crate::fvm::bundle::{bundle_path, contracts_path, custom_actors_bundle_path};
+    use crate::fvm::store::memory::MemoryBlockstore;
+    use crate::fvm::upgrades::UpgradeScheduler;
+    use crate::fvm::{FvmMessage, FvmMessageInterpreter};
+
+    use super::Simulator;
+
+    type TestSimulator =
+        Simulator<FvmMessageInterpreter<MemoryBlockstore, MockClient<MockRequestMethodMatcher>>>;
+
+    async fn make_simulator(genesis: Genesis) -> TestSimulator {
+        let (client, _) = MockClient::new(MockRequestMethodMatcher::default());
+        let interpreter = FvmMessageInterpreter::new(
+            client,
+            None,
+            contracts_path(),
+            1.05,
+            1.05,
+            false,
+            UpgradeScheduler::new(),
+        );
+        let bundle = std::fs::read(bundle_path()).expect("failed to read bundle");
+        let custom_actors_bundle =
+            std::fs::read(custom_actors_bundle_path()).expect("failed to read custom bundle");
+
+        Simulator::new(interpreter, genesis, &bundle, &custom_actors_bundle)
+            .await
+            .expect("failed to create simulator")
+    }
+
+    /// A genesis with a funded account we can send messages from.
+    fn make_genesis(sender: Address) -> Genesis {
+        let mut g = quickcheck::Gen::new(5);
+        let mut genesis = Genesis::arbitrary(&mut g);
+        genesis.base_fee = TokenAmount::from_atto(100);
+        genesis.accounts.push(Actor {
+            meta: ActorMeta::Account(Account {
+                owner: SignerAddr(sender),
+            }),
+            balance: TokenAmount::from_whole(10),
+        });
+        genesis
+    }
+
+    fn transfer(from: Address, to: Address, sequence: u64, value: TokenAmount) -> FvmMessage {
+        FvmMessage {
+            version: 0,
+            from,
+            to,
+            sequence,
+            value,
+            method_num: METHOD_SEND,
+            params: Default::default(),
+            gas_limit: 10_000_000,
+            gas_fee_cap: TokenAmount::from_atto(100),
+            gas_premium: TokenAmount::from_atto(0),
+        }
+    }
+
+    #[tokio::test]
+    async fn produce_blocks_deterministically() {
+        let sender = Address::new_secp256k1(&[4u8; 65]).unwrap();
+        let recipient = Address::new_secp256k1(&[5u8; 65]).unwrap();
+        let genesis = make_genesis(sender);
+
+        let mut roots = Vec::new();
+        for _ in 0..2 {
+            let mut sim = make_simulator(genesis.clone()).await;
+            sim.advance_to(2)
+                .await
+                .expect("failed to produce empty blocks");
+
+            let block = sim
+                .produce_block(vec![transfer(
+                    sender,
+                    recipient,
+                    0,
+                    TokenAmount::from_whole(1),
+                )])
+                .await
+                .expect("failed to produce block");
+
+            assert_eq!(block.height, 3);
+            assert_eq!(block.receipts.len(), 1);
+            assert!(block.receipts[0]
+                .apply_ret
+                .msg_receipt
+                .exit_code
+                .is_success());
+            assert_eq!(sim.state_params().state_root, block.state_root);
+
+            let state = sim.exec_state().expect("failed to create state");
+            let id = state
+                .state_tree()
+                .lookup_id(&recipient)
+                .expect("failed to look up recipient")
+                .expect("recipient should exist");
+            let actor = state
+                .state_tree()
+                .get_actor(id)
+                .expect("failed to get recipient")
+                .expect("recipient should exist");
+            assert_eq!(actor.balance, TokenAmount::from_whole(1));
+
+            roots.push(block.state_root);
+        }
+
+        assert_eq!(roots[0], roots[1], "state roots differ between runs");
+    }
+}
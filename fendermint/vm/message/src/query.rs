@@ -142,6 +142,14 @@ pub struct StateParams {
     pub chain_id: u64,
     /// Current network version.
     pub network_version: NetworkVersion,
+    /// Version of the application, bumped by the upgrades scheduled for the chain.
+    ///
+    /// Nodes which predate it don't return it, so it reads as 0 from them.
+    #[serde(default)]
+    pub app_version: u64,
+    /// Root of the state tree the query was answered from.
+    #[serde_as(as = "IsHumanReadable")]
+    pub state_root: Cid,
 }
 
 #[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
@@ -182,3 +190,55 @@ mod arb {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use cid::Cid;
+    use fendermint_vm_encoding::IsHumanReadable;
+    use fvm_shared::{econ::TokenAmount, version::NetworkVersion};
+    use serde::Serialize;
+    use serde_with::serde_as;
+
+    use super::StateParams;
+
+    /// The state parameters as returned by nodes before the app version was added.
+    #[serde_as]
+    #[derive(Serialize)]
+    struct StateParamsV0 {
+        #[serde_as(as = "IsHumanReadable")]
+        base_fee: TokenAmount,
+        #[serde_as(as = "IsHumanReadable")]
+        circ_supply: TokenAmount,
+        chain_id: u64,
+        network_version: NetworkVersion,
+        #[serde_as(as = "IsHumanReadable")]
+        state_root: Cid,
+    }
+
+    #[test]
+    fn state_params_app_version() {
+        let params = StateParams {
+            base_fee: TokenAmount::from_atto(100),
+            circ_supply: TokenAmount::from_whole(1000),
+            chain_id: 123,
+            network_version: NetworkVersion::V21,
+            app_version: 2,
+            state_root: crate::cid(&"state").unwrap(),
+        };
+        let bytes = fvm_ipld_encoding::to_vec(&params).unwrap();
+        let decoded: StateParams = fvm_ipld_encoding::from_slice(&bytes).unwrap();
+        assert_eq!(decoded, params);
+
+        let old = StateParamsV0 {
+            base_fee: params.base_fee.clone(),
+            circ_supply: params.circ_supply.clone(),
+            chain_id: params.chain_id,
+            network_version: params.network_version,
+            state_root: params.state_root,
+        };
+        let bytes = fvm_ipld_encoding::to_vec(&old).unwrap();
+        let decoded: StateParams = fvm_ipld_encoding::from_slice(&bytes).unwrap();
+        assert_eq!(decoded.app_version, 0);
+        assert_eq!(decoded.chain_id, params.chain_id);
+    }
+}
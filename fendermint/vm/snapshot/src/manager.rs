@@ -483,6 +483,8 @@ mod tests {
             chain_id: out.chain_id.into(),
             power_scale: out.power_scale,
             app_version: 0,
+            ml_limits: out.ml_limits,
+            implicit_gas: out.implicit_gas,
         };
 
         (state_params, store)
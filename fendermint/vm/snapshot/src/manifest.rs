@@ -190,6 +190,8 @@ mod arb {
                         .into(),
                     power_scale: *g.choose(&[-1, 0, 3]).unwrap(),
                     app_version: 0,
+                    ml_limits: None,
+                    implicit_gas: None,
                 },
                 version: Arbitrary::arbitrary(g),
             }
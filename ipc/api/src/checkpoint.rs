@@ -152,5 +152,11 @@ mod tests {
         let r: T = serde_json::from_str(&s).unwrap();
 
         assert_eq!(r, t);
+
+        // Bytes stay bytes in CBOR.
+        let bz = fvm_ipld_encoding::to_vec(&t).unwrap();
+        let r: T = fvm_ipld_encoding::from_slice(&bz).unwrap();
+
+        assert_eq!(r, t);
     }
 }
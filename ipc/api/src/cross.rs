@@ -6,8 +6,10 @@ use crate::address::IPCAddress;
 use crate::subnet_id::SubnetID;
 use crate::HumanReadable;
 use anyhow::anyhow;
+use ethers::utils::hex;
 use fvm_shared::address::Address;
 use fvm_shared::econ::TokenAmount;
+use fvm_shared::error::ExitCode;
 use serde::{Deserialize, Serialize};
 use serde_tuple::{Deserialize_tuple, Serialize_tuple};
 use serde_with::serde_as;
@@ -135,6 +137,82 @@ impl TryFrom<u8> for IpcMsgKind {
     }
 }
 
+/// How the destination subnet handled a cross-net message, as reported by the gateway in the
+/// receipt it sends back to the sender.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize, strum::Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum CrossMsgOutcome {
+    /// The message was executed successfully.
+    Ok,
+    /// The gateway rejected the message, e.g. because of an unexpected nonce or destination;
+    /// the return data is an ABI encoded IPC error.
+    SystemErr,
+    /// The called contract reverted; the return data is the revert reason.
+    ActorErr,
+    /// The message was not executed in this subnet: it was put in the postbox to be
+    /// propagated further, or it was a receipt itself, which gets no receipt back.
+    Forwarded,
+}
+
+impl CrossMsgOutcome {
+    /// The `OutcomeType` of the gateway contract, which doesn't know about forwarded messages.
+    pub fn from_outcome_type(outcome: u8) -> anyhow::Result<Self> {
+        Ok(match outcome {
+            0 => CrossMsgOutcome::Ok,
+            1 => CrossMsgOutcome::SystemErr,
+            2 => CrossMsgOutcome::ActorErr,
+            _ => return Err(anyhow!("invalid cross-msg outcome type: {outcome}")),
+        })
+    }
+
+    /// The FVM exit code matching the outcome: reverted contracts exit with the code of the
+    /// EVM actor, and messages rejected by the gateway with an illegal argument.
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            CrossMsgOutcome::Ok | CrossMsgOutcome::Forwarded => ExitCode::OK,
+            CrossMsgOutcome::SystemErr => ExitCode::USR_ILLEGAL_ARGUMENT,
+            CrossMsgOutcome::ActorErr => ExitCode::new(EVM_CONTRACT_REVERTED),
+        }
+    }
+}
+
+/// The exit code of the EVM actor when a contract reverts.
+const EVM_CONTRACT_REVERTED: u32 = 33;
+
+/// The result of executing a top-down message in the child subnet, keyed by its nonce.
+#[serde_as]
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+pub struct TopDownReceipt {
+    /// The top-down nonce of the message.
+    pub nonce: u64,
+    /// The height of the child block which executed the message.
+    pub block_height: u64,
+    pub outcome: CrossMsgOutcome,
+    /// The data returned by the call, or the reason of the failure.
+    #[serde_as(as = "HumanReadable")]
+    pub return_data: Vec<u8>,
+    /// The gas used to apply the message, including the gateway's own bookkeeping.
+    pub gas_used: u64,
+}
+
+impl TopDownReceipt {
+    pub fn exit_code(&self) -> ExitCode {
+        self.outcome.exit_code()
+    }
+}
+
+impl std::fmt::Display for TopDownReceipt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "nonce: {}", self.nonce)?;
+        writeln!(f, "block height: {}", self.block_height)?;
+        writeln!(f, "outcome: {}", self.outcome)?;
+        writeln!(f, "exit code: {}", self.exit_code().value())?;
+        writeln!(f, "gas used: {}", self.gas_used)?;
+        write!(f, "return data: 0x{}", hex::encode(&self.return_data))
+    }
+}
+
 #[derive(PartialEq, Eq)]
 pub enum IPCMsgType {
     BottomUp,
@@ -210,6 +288,25 @@ mod tests {
         bottom_up("/r123/f01/f02", "/r123/f01/f02/f03", false);
     }
 
+    #[test]
+    fn test_topdown_receipt_json() {
+        let receipt = TopDownReceipt {
+            nonce: 3,
+            block_height: 1042,
+            outcome: CrossMsgOutcome::ActorErr,
+            return_data: vec![0xde, 0xad],
+            gas_used: 1000,
+        };
+        let json = serde_json::to_value(&receipt).unwrap();
+        assert_eq!(json["outcome"], "actor_err");
+        assert_eq!(json["return_data"], "dead");
+        assert_eq!(
+            serde_json::from_value::<TopDownReceipt>(json).unwrap(),
+            receipt
+        );
+        assert_eq!(receipt.exit_code().value(), 33);
+    }
+
     fn bottom_up(a: &str, b: &str, res: bool) {
         assert_eq!(
             is_bottomup(
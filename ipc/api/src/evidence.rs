@@ -0,0 +1,38 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Evidence of validator misbehaviour, to be reported to the subnet actor on the parent.
+
+use crate::HumanReadable;
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+
+/// Two conflicting votes signed by a validator for the same height and round of the subnet,
+/// in the format the subnet actor checks them in.
+#[serde_as]
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+pub struct DoubleSignEvidence {
+    /// The Ethereum address of the validator who signed both votes.
+    pub validator: ethers::types::Address,
+    /// The height of the votes in the subnet.
+    pub height: u64,
+    /// The length delimited protobuf encoding of the first canonical vote, as signed by CometBFT.
+    #[serde_as(as = "HumanReadable")]
+    pub vote_a: Vec<u8>,
+    /// The 64 byte secp256k1 signature of the SHA256 hash of `vote_a`.
+    #[serde_as(as = "HumanReadable")]
+    pub signature_a: Vec<u8>,
+    #[serde_as(as = "HumanReadable")]
+    pub vote_b: Vec<u8>,
+    #[serde_as(as = "HumanReadable")]
+    pub signature_b: Vec<u8>,
+}
+
+impl std::fmt::Display for DoubleSignEvidence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "double sign by {:?} at height {}",
+            self.validator, self.height
+        )
+    }
+}
@@ -143,9 +143,15 @@ macro_rules! bottom_up_checkpoint_conversion {
             type Error = anyhow::Error;
 
             fn try_from(value: $module::BottomUpCheckpoint) -> Result<Self, Self::Error> {
+                if value.block_height > U256::from(ChainEpoch::MAX as u64) {
+                    return Err(anyhow!(
+                        "checkpoint height {} out of range",
+                        value.block_height
+                    ));
+                }
                 Ok(BottomUpCheckpoint {
                     subnet_id: SubnetID::try_from(value.subnet_id)?,
-                    block_height: value.block_height.as_u128() as ChainEpoch,
+                    block_height: value.block_height.as_u64() as ChainEpoch,
                     block_hash: value.block_hash.to_vec(),
                     next_configuration_number: value.next_configuration_number,
                     msgs: value
@@ -297,9 +303,12 @@ pub fn vec_to_bytes32(v: Vec<u8>) -> anyhow::Result<[u8; 32]> {
 
 #[cfg(test)]
 mod tests {
+    use crate::checkpoint::BottomUpCheckpoint;
     use crate::evm::subnet_id_to_evm_addresses;
     use crate::subnet_id::SubnetID;
+    use ethers::types::U256;
     use fvm_shared::address::Address;
+    use ipc_actors_abis::subnet_actor_checkpointing_facet;
     use ipc_types::EthAddress;
     use std::str::FromStr;
 
@@ -320,4 +329,13 @@ mod tests {
 
         assert_eq!(addrs, vec![a, b]);
     }
+
+    #[test]
+    fn test_checkpoint_height_out_of_range() {
+        let checkpoint = subnet_actor_checkpointing_facet::BottomUpCheckpoint {
+            block_height: U256::from(u64::MAX),
+            ..Default::default()
+        };
+        assert!(BottomUpCheckpoint::try_from(checkpoint).is_err());
+    }
 }
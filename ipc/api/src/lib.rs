@@ -11,11 +11,16 @@ pub mod address;
 pub mod checkpoint;
 pub mod cross;
 pub mod error;
+pub mod evidence;
 pub mod gateway;
+pub mod model;
+pub mod receipt;
 #[cfg(feature = "fil-actor")]
 mod runtime;
 pub mod subnet;
 pub mod subnet_id;
+pub mod token;
+pub mod uri;
 pub mod validator;
 
 pub mod evm;
@@ -96,7 +101,11 @@ impl serde_with::SerializeAs<Vec<u8>> for HumanReadable {
     where
         S: Serializer,
     {
-        hex::encode(source).serialize(serializer)
+        if serializer.is_human_readable() {
+            hex::encode(source).serialize(serializer)
+        } else {
+            source.serialize(serializer)
+        }
     }
 }
 
@@ -114,3 +123,49 @@ impl<'de> serde_with::DeserializeAs<'de, Vec<u8>> for HumanReadable {
         }
     }
 }
+
+serialise_human_readable_str!(Address);
+
+impl<'de> serde_with::DeserializeAs<'de, Address> for HumanReadable {
+    fn deserialize_as<D>(deserializer: D) -> Result<Address, D::Error>
+    where
+        D: serde::de::Deserializer<'de>,
+    {
+        if deserializer.is_human_readable() {
+            let s = String::deserialize(deserializer)?;
+            Address::from_str(&s).map_err(|e| D::Error::custom(format!("cannot parse address {e}")))
+        } else {
+            Address::deserialize(deserializer)
+        }
+    }
+}
+
+/// Token amounts are in atto in their human readable form, to be read back exactly.
+impl serde_with::SerializeAs<TokenAmount> for HumanReadable {
+    fn serialize_as<S>(source: &TokenAmount, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if serializer.is_human_readable() {
+            source.atto().to_string().serialize(serializer)
+        } else {
+            source.serialize(serializer)
+        }
+    }
+}
+
+impl<'de> serde_with::DeserializeAs<'de, TokenAmount> for HumanReadable {
+    fn deserialize_as<D>(deserializer: D) -> Result<TokenAmount, D::Error>
+    where
+        D: serde::de::Deserializer<'de>,
+    {
+        if deserializer.is_human_readable() {
+            let s = String::deserialize(deserializer)?;
+            let atto = fvm_shared::bigint::BigInt::from_str(&s)
+                .map_err(|e| D::Error::custom(format!("cannot parse token amount {e}")))?;
+            Ok(TokenAmount::from_atto(atto))
+        } else {
+            TokenAmount::deserialize(deserializer)
+        }
+    }
+}
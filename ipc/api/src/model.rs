@@ -0,0 +1,60 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Types used to share machine learning models between subnets.
+//!
+//! Models are carried by `Call` cross-net messages between instances of the `ModelExchange`
+//! contract from the SDK, and are identified by the SHA-256 digest of their bytes, which the
+//! receiving contract checks before storing the model.
+//!
+//! It also has helpers to encode the inputs of the machine learning actor compactly.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Content identifier of a model.
+pub type ModelHash = [u8; 32];
+
+/// Compute the identifier of a model, the same way the `ModelExchange` contract does.
+pub fn model_hash(model: &[u8]) -> ModelHash {
+    Sha256::digest(model).into()
+}
+
+/// State of a model sent to another subnet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, strum::Display)]
+#[strum(serialize_all = "snake_case")]
+pub enum ModelTransferStatus {
+    /// The model hasn't arrived at the destination yet.
+    Pending,
+    /// The model has been verified and stored by the destination contract.
+    Imported,
+}
+
+/// ID of the machine learning actor deployed by Fendermint, reachable by EVM tooling at its
+/// masked ID address.
+pub const MACHINELEARNING_ACTOR_ID: u64 = 49;
+
+/// The algorithms of the machine learning actor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::EnumString, strum::VariantNames)]
+#[strum(serialize_all = "snake_case")]
+pub enum MlAlgorithm {
+    LinearRegression,
+    LogisticRegression,
+    KnnRegression,
+}
+
+/// The input matrices of the machine learning actor, with the same type the actor decodes.
+pub use ipc_types::{InputMatrix, SparseMatrix};
+
+#[cfg(test)]
+mod tests {
+    use super::model_hash;
+
+    #[test]
+    fn test_model_hash() {
+        // sha256("abc")
+        assert_eq!(
+            ethers::utils::hex::encode(model_hash(b"abc")),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+}
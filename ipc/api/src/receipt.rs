@@ -0,0 +1,144 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Machine-readable outcome of the transactions sent by the provider.
+
+use std::collections::BTreeMap;
+
+use anyhow::anyhow;
+use ethers::abi::{Event, RawLog};
+use ethers::types::{Bytes, TransactionReceipt, H160, H256};
+use fvm_shared::clock::ChainEpoch;
+use ipc_actors_abis as ia;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+lazy_static! {
+    /// The events of the IPC contracts, used to decode the logs of receipts.
+    static ref IPC_EVENTS: Vec<Event> = [
+        &*ia::gateway_manager_facet::GATEWAYMANAGERFACET_ABI,
+        &*ia::gateway_messenger_facet::GATEWAYMESSENGERFACET_ABI,
+        &*ia::lib_gateway::LIBGATEWAY_ABI,
+        &*ia::lib_staking_change_log::LIBSTAKINGCHANGELOG_ABI,
+        &*ia::register_subnet_facet::REGISTERSUBNETFACET_ABI,
+        &*ia::subnet_actor_manager_facet::SUBNETACTORMANAGERFACET_ABI,
+        &*ia::subnet_actor_checkpointing_facet::SUBNETACTORCHECKPOINTINGFACET_ABI,
+    ]
+    .into_iter()
+    .flat_map(|abi| abi.events().cloned())
+    .collect();
+}
+
+/// The receipt of an executed transaction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxReceipt {
+    pub tx_hash: H256,
+    /// The epoch of the block the transaction was included in.
+    pub epoch: ChainEpoch,
+    pub gas_used: Option<u64>,
+    pub success: bool,
+    pub events: Vec<TxEvent>,
+}
+
+/// A log emitted by a transaction, decoded if it's one of the IPC contract events.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxEvent {
+    pub address: H160,
+    /// The name of the event, if it could be decoded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// The decoded parameters of the event, formatted as strings.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub params: BTreeMap<String, String>,
+    pub topics: Vec<H256>,
+    pub data: Bytes,
+}
+
+impl TryFrom<TransactionReceipt> for TxReceipt {
+    type Error = anyhow::Error;
+
+    fn try_from(receipt: TransactionReceipt) -> Result<Self, Self::Error> {
+        let epoch = receipt
+            .block_number
+            .ok_or_else(|| anyhow!("cannot get block number"))?;
+
+        Ok(Self {
+            tx_hash: receipt.transaction_hash,
+            epoch: epoch.as_u64() as ChainEpoch,
+            gas_used: receipt.gas_used.map(|g| g.low_u64()),
+            // Receipts from before Byzantium have no status; they are only returned for executed transactions.
+            success: receipt.status.map(|s| s.as_u64() == 1).unwrap_or(true),
+            events: receipt.logs.into_iter().map(TxEvent::from).collect(),
+        })
+    }
+}
+
+impl From<ethers::types::Log> for TxEvent {
+    fn from(log: ethers::types::Log) -> Self {
+        let raw = RawLog {
+            topics: log.topics.clone(),
+            data: log.data.to_vec(),
+        };
+
+        let decoded = log.topics.first().and_then(|topic| {
+            IPC_EVENTS
+                .iter()
+                .filter(|e| !e.anonymous && e.signature() == *topic)
+                .find_map(|e| e.parse_log(raw.clone()).ok().map(|l| (e.name.clone(), l)))
+        });
+
+        let (name, params) = match decoded {
+            Some((name, l)) => (
+                Some(name),
+                l.params
+                    .into_iter()
+                    .map(|p| (p.name, p.value.to_string()))
+                    .collect(),
+            ),
+            None => (None, BTreeMap::new()),
+        };
+
+        Self {
+            address: log.address,
+            name,
+            params,
+            topics: log.topics,
+            data: log.data,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::abi::{encode, Token};
+    use ethers::types::{Log, H160, U256};
+    use ethers::utils::keccak256;
+
+    use super::TxEvent;
+
+    #[test]
+    fn test_decode_ipc_event() {
+        let log = Log {
+            address: H160::repeat_byte(1),
+            topics: vec![keccak256("NewStakingChangeRequest(uint8,address,bytes,uint64)").into()],
+            data: encode(&[
+                Token::Uint(U256::from(1)),
+                Token::Address(H160::repeat_byte(2)),
+                Token::Bytes(vec![3]),
+                Token::Uint(U256::from(4)),
+            ])
+            .into(),
+            ..Default::default()
+        };
+
+        let event = TxEvent::from(log);
+        assert_eq!(event.name.as_deref(), Some("NewStakingChangeRequest"));
+        assert_eq!(event.params.len(), 4);
+
+        let unknown = TxEvent::from(Log {
+            topics: vec![keccak256("Unknown()").into()],
+            ..Default::default()
+        });
+        assert_eq!(unknown.name, None);
+        assert!(unknown.params.is_empty());
+    }
+}
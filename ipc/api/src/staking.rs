@@ -3,12 +3,13 @@
 
 //! Staking module related types and functions
 
-use crate::{eth_to_fil_amount, ethers_address_to_fil_address};
+use crate::{eth_to_fil_amount, ethers_address_to_fil_address, HumanReadable};
 use ethers::utils::hex;
 use fvm_shared::address::Address;
 use fvm_shared::econ::TokenAmount;
 use ipc_actors_abis::{lib_staking_change_log, subnet_actor_getter_facet};
 use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
 use std::fmt::{Display, Formatter};
 
 pub type ConfigurationNumber = u64;
@@ -55,13 +56,33 @@ impl TryFrom<lib_staking_change_log::NewStakingChangeRequestFilter> for StakingC
 }
 
 /// The staking validator information
-#[derive(Clone, Debug)]
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ValidatorStakingInfo {
+    #[serde_as(as = "HumanReadable")]
     confirmed_collateral: TokenAmount,
+    #[serde_as(as = "HumanReadable")]
     total_collateral: TokenAmount,
+    #[serde_as(as = "HumanReadable")]
     metadata: Vec<u8>,
 }
 
+impl ValidatorStakingInfo {
+    /// The collateral that has been confirmed in the subnet.
+    pub fn confirmed_collateral(&self) -> &TokenAmount {
+        &self.confirmed_collateral
+    }
+
+    /// The collateral including the changes not yet confirmed in the subnet.
+    pub fn total_collateral(&self) -> &TokenAmount {
+        &self.total_collateral
+    }
+
+    pub fn metadata(&self) -> &[u8] {
+        &self.metadata
+    }
+}
+
 impl Display for ValidatorStakingInfo {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -87,7 +108,7 @@ impl TryFrom<subnet_actor_getter_facet::ValidatorInfo> for ValidatorStakingInfo
 }
 
 /// The full validator information with
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ValidatorInfo {
     pub staking: ValidatorStakingInfo,
     /// If the validator is active in block production
@@ -105,3 +126,58 @@ impl Display for ValidatorInfo {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use fvm_shared::address::Address;
+    use fvm_shared::econ::TokenAmount;
+    use ipc_actors_abis::subnet_actor_getter_facet;
+
+    use crate::staking::{ValidatorInfo, ValidatorStakingInfo};
+    use crate::validator::Validator;
+
+    #[test]
+    fn test_validator_info_roundtrip() {
+        let info = ValidatorInfo {
+            staking: ValidatorStakingInfo::try_from(subnet_actor_getter_facet::ValidatorInfo {
+                federated_power: 0.into(),
+                confirmed_collateral: 10.into(),
+                total_collateral: 20.into(),
+                metadata: vec![4; 65].into(),
+            })
+            .unwrap(),
+            is_active: true,
+            is_waiting: false,
+        };
+        let validator = Validator {
+            addr: Address::new_id(101),
+            metadata: vec![4; 65],
+            weight: TokenAmount::from_atto(10),
+        };
+
+        let s = serde_json::to_string(&validator).unwrap();
+        assert_eq!(
+            s,
+            format!(
+                r#"{{"addr":"{}","metadata":"{}","weight":"10"}}"#,
+                Address::new_id(101),
+                "04".repeat(65)
+            )
+        );
+        assert_eq!(serde_json::from_str::<Validator>(&s).unwrap(), validator);
+
+        let s = serde_json::to_string(&info).unwrap();
+        assert_eq!(serde_json::from_str::<ValidatorInfo>(&s).unwrap(), info);
+
+        let bz = fvm_ipld_encoding::to_vec(&validator).unwrap();
+        assert_eq!(
+            fvm_ipld_encoding::from_slice::<Validator>(&bz).unwrap(),
+            validator
+        );
+        let bz = fvm_ipld_encoding::to_vec(&info).unwrap();
+        assert_eq!(
+            fvm_ipld_encoding::from_slice::<ValidatorInfo>(&bz).unwrap(),
+            info
+        );
+    }
+}
@@ -10,6 +10,7 @@ use crate::subnet_id::SubnetID;
 use fvm_ipld_encoding::repr::*;
 use fvm_shared::{address::Address, clock::ChainEpoch, econ::TokenAmount};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// ID used in the builtin-actors bundle manifest
 pub const MANIFEST_ID: &str = "ipc_subnet_actor";
@@ -37,6 +38,33 @@ pub enum PermissionMode {
     Static,
 }
 
+/// The parameters of a subnet which its validators can change with governance proposals,
+/// in the order of the `SubnetParam` enum of the subnet actor.
+#[repr(u8)]
+#[derive(
+    Copy,
+    Debug,
+    Clone,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    strum::Display,
+    strum::EnumString,
+    strum::VariantNames,
+    strum::FromRepr,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum SubnetParam {
+    /// Validators needed for the subnet to bootstrap
+    MinValidators,
+    /// Percentage of the validator power needed to reach a quorum, between 51 and 100
+    MajorityPercentage,
+    /// EVM chain ID of the subnet, which double sign evidence has to be signed for
+    ChainId,
+}
+
 /// Defines the supply source of a subnet on its parent subnet.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct SupplySource {
@@ -77,6 +105,10 @@ pub struct ConstructParams {
     pub min_cross_msg_fee: TokenAmount,
     pub permission_mode: PermissionMode,
     pub supply_source: SupplySource,
+    /// The balances of the accounts in the genesis of the subnet, each pre-funded by the
+    /// account itself once the subnet actor is created.
+    #[serde(default)]
+    pub genesis_balances: BTreeMap<Address, TokenAmount>,
 }
 
 /// Consensus types supported by hierarchical consensus
@@ -0,0 +1,175 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Parsing and formatting of token amounts in their denominations.
+//!
+//! Amounts are parsed from decimal strings exactly, without going through floats, so that
+//! `"0.1 FIL"` is `100000000000000000` atto and not a rounding of it.
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use fvm_shared::bigint::BigInt;
+use fvm_shared::econ::TokenAmount;
+use num_traits::{Signed, Zero};
+use serde::Serialize;
+
+/// The decimals of FIL, and of the native token of every subnet.
+pub const FIL_DECIMALS: u32 = 18;
+
+/// The units amounts can be written in, with the power of ten of their atto amount.
+const UNITS: &[(&str, u32)] = &[
+    ("fil", 18),
+    ("millifil", 15),
+    ("microfil", 12),
+    ("nanofil", 9),
+    ("picofil", 6),
+    ("femtofil", 3),
+    ("attofil", 0),
+    ("eth", 18),
+    ("ether", 18),
+    ("gwei", 9),
+    ("wei", 0),
+];
+
+/// The token of an amount, which tells how many decimals it's formatted with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Denomination {
+    pub symbol: String,
+    pub decimals: u32,
+}
+
+impl Denomination {
+    /// The native token of the subnets.
+    pub fn fil() -> Self {
+        Self {
+            symbol: "FIL".to_string(),
+            decimals: FIL_DECIMALS,
+        }
+    }
+}
+
+impl Display for Denomination {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.symbol)
+    }
+}
+
+pub trait TokenAmountExt: Sized {
+    /// Parse an amount followed by its unit, like `"1.5 FIL"`, `"1000 attoFIL"` or `"2 gwei"`.
+    /// Amounts without a unit are in whole FIL.
+    fn from_denominated_str(s: &str) -> anyhow::Result<Self>;
+
+    /// Parse an amount of whole tokens with `decimals`, like `"1.5"` of a token with 6 decimals.
+    fn from_decimal_str(s: &str, decimals: u32) -> anyhow::Result<Self>;
+
+    /// Format the amount in whole tokens of `denomination`, like `"1.5 FIL"`.
+    fn to_denominated_string(&self, denomination: &Denomination) -> String;
+}
+
+impl TokenAmountExt for TokenAmount {
+    fn from_denominated_str(s: &str) -> anyhow::Result<Self> {
+        let s = s.trim();
+        let split = s.find(|c: char| c.is_ascii_alphabetic()).unwrap_or(s.len());
+        let (amount, unit) = s.split_at(split);
+
+        let decimals = if unit.is_empty() {
+            FIL_DECIMALS
+        } else {
+            UNITS
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(unit))
+                .map(|(_, decimals)| *decimals)
+                .ok_or_else(|| anyhow!("unknown unit {unit} in amount {s}"))?
+        };
+
+        Self::from_decimal_str(amount.trim(), decimals)
+    }
+
+    fn from_decimal_str(s: &str, decimals: u32) -> anyhow::Result<Self> {
+        let (int, frac) = s.split_once('.').unwrap_or((s, ""));
+        let is_digits = |p: &str| p.chars().all(|c| c.is_ascii_digit());
+        if (int.is_empty() && frac.is_empty()) || !is_digits(int) || !is_digits(frac) {
+            return Err(anyhow!("invalid amount {s}"));
+        }
+        if frac.len() > decimals as usize {
+            return Err(anyhow!(
+                "amount {s} has more than the {decimals} decimals of its unit"
+            ));
+        }
+
+        let digits = format!("{int}{frac:0<width$}", width = decimals as usize);
+        let atto = BigInt::from_str(&digits).map_err(|e| anyhow!("invalid amount {s}: {e}"))?;
+        Ok(TokenAmount::from_atto(atto))
+    }
+
+    fn to_denominated_string(&self, denomination: &Denomination) -> String {
+        let atto = self.atto();
+        let sign = if atto.is_negative() { "-" } else { "" };
+        let digits = atto.abs().to_string();
+
+        let decimals = denomination.decimals as usize;
+        let digits = format!("{digits:0>width$}", width = decimals + 1);
+        let (int, frac) = digits.split_at(digits.len() - decimals);
+        let frac = frac.trim_end_matches('0');
+
+        if frac.is_empty() || atto.is_zero() {
+            format!("{sign}{int} {denomination}")
+        } else {
+            format!("{sign}{int}.{frac} {denomination}")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fvm_shared::econ::TokenAmount;
+
+    use super::{Denomination, TokenAmountExt};
+
+    #[test]
+    fn parse_denominated_amounts() {
+        let parse = |s| TokenAmount::from_denominated_str(s).unwrap();
+
+        assert_eq!(parse("1.5 FIL"), TokenAmount::from_nano(1_500_000_000u64));
+        assert_eq!(parse("1.5fil"), parse("1.5 FIL"));
+        assert_eq!(parse("1000 attoFIL"), TokenAmount::from_atto(1000));
+        assert_eq!(parse("0.1 ETH"), TokenAmount::from_nano(100_000_000u64));
+        assert_eq!(parse("2 gwei"), TokenAmount::from_nano(2));
+        assert_eq!(
+            parse("1000000.1"),
+            TokenAmount::from_nano(1000000100000000u128)
+        );
+        assert_eq!(parse(".5"), TokenAmount::from_nano(500_000_000u64));
+
+        assert!(TokenAmount::from_denominated_str("1.5 DOGE").is_err());
+        assert!(TokenAmount::from_denominated_str("0.5 attoFIL").is_err());
+        assert!(TokenAmount::from_denominated_str("-1 FIL").is_err());
+        assert!(TokenAmount::from_denominated_str("FIL").is_err());
+    }
+
+    #[test]
+    fn format_amounts_with_decimals() {
+        let fil = Denomination::fil();
+        assert_eq!(
+            TokenAmount::from_nano(1_500_000_000u64).to_denominated_string(&fil),
+            "1.5 FIL"
+        );
+        assert_eq!(
+            TokenAmount::from_atto(1).to_denominated_string(&fil),
+            "0.000000000000000001 FIL"
+        );
+        assert_eq!(
+            TokenAmount::from_atto(0).to_denominated_string(&fil),
+            "0 FIL"
+        );
+
+        let usdc = Denomination {
+            symbol: "USDC".to_string(),
+            decimals: 6,
+        };
+        let amount = TokenAmount::from_decimal_str("12.34", 6).unwrap();
+        assert_eq!(amount, TokenAmount::from_atto(12_340_000));
+        assert_eq!(amount.to_denominated_string(&usdc), "12.34 USDC");
+    }
+}
@@ -0,0 +1,192 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! `ipc:` URIs, requesting a payment or a join of a subnet.
+//!
+//! Wallets and docs can link to, or show as a QR code, a URI like
+//! `ipc:pay?subnet=/r314159/t410f...&to=t1...&amount=1000000000000000000`, which the CLI and SDK
+//! turn back into the operation to pre-fill. Amounts are in attoFIL, so that they are exact
+//! whatever the wallet displays them in.
+
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Context};
+use fvm_shared::address::Address;
+use fvm_shared::bigint::BigInt;
+use fvm_shared::econ::TokenAmount;
+
+use crate::subnet_id::SubnetID;
+
+pub const URI_SCHEME: &str = "ipc";
+
+/// A request encoded in an `ipc:` URI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IpcUri {
+    /// The subnet the request is made in.
+    pub subnet: SubnetID,
+    pub action: UriAction,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UriAction {
+    /// Send `amount` to `to`, or an amount left to the payer if `None`.
+    Pay {
+        to: Address,
+        amount: Option<TokenAmount>,
+    },
+    /// Join the subnet as a validator staking `collateral`, or a collateral left to the
+    /// validator if `None`.
+    Join { collateral: Option<TokenAmount> },
+}
+
+impl IpcUri {
+    pub fn pay(subnet: SubnetID, to: Address, amount: Option<TokenAmount>) -> Self {
+        Self {
+            subnet,
+            action: UriAction::Pay { to, amount },
+        }
+    }
+
+    pub fn join(subnet: SubnetID, collateral: Option<TokenAmount>) -> Self {
+        Self {
+            subnet,
+            action: UriAction::Join { collateral },
+        }
+    }
+}
+
+impl Display for IpcUri {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Subnet IDs and addresses only have characters allowed in a query as they are.
+        match &self.action {
+            UriAction::Pay { to, amount } => {
+                write!(f, "{URI_SCHEME}:pay?subnet={}&to={to}", self.subnet)?;
+                if let Some(amount) = amount {
+                    write!(f, "&amount={}", amount.atto())?;
+                }
+            }
+            UriAction::Join { collateral } => {
+                write!(f, "{URI_SCHEME}:join?subnet={}", self.subnet)?;
+                if let Some(collateral) = collateral {
+                    write!(f, "&amount={}", collateral.atto())?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for IpcUri {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rest = s
+            .trim()
+            .strip_prefix(URI_SCHEME)
+            .and_then(|rest| rest.strip_prefix(':'))
+            .ok_or_else(|| anyhow!("not an {URI_SCHEME}: URI: {s}"))?;
+        let (action, query) = rest.split_once('?').unwrap_or((rest, ""));
+
+        let mut params = BTreeMap::new();
+        for param in query.split('&').filter(|p| !p.is_empty()) {
+            let (key, value) = param
+                .split_once('=')
+                .ok_or_else(|| anyhow!("parameter without a value in URI: {param}"))?;
+            if params.insert(key, value).is_some() {
+                return Err(anyhow!("repeated parameter in URI: {key}"));
+            }
+        }
+
+        let subnet = params
+            .remove("subnet")
+            .ok_or_else(|| anyhow!("the URI has no subnet"))?;
+        let subnet = SubnetID::from_str(subnet).context("invalid subnet in URI")?;
+        let amount = params.remove("amount").map(parse_atto).transpose()?;
+
+        let action = match action {
+            "pay" => {
+                let to = params
+                    .remove("to")
+                    .ok_or_else(|| anyhow!("the payment URI has no recipient"))?;
+                let to = Address::from_str(to).context("invalid recipient in URI")?;
+                UriAction::Pay { to, amount }
+            }
+            "join" => UriAction::Join { collateral: amount },
+            _ => return Err(anyhow!("unknown action in URI: {action}")),
+        };
+
+        // A parameter we don't understand could change what is asked, so it isn't ignored.
+        if let Some(key) = params.keys().next() {
+            return Err(anyhow!("unknown parameter in URI: {key}"));
+        }
+
+        Ok(Self { subnet, action })
+    }
+}
+
+fn parse_atto(s: &str) -> anyhow::Result<TokenAmount> {
+    if s.is_empty() || !s.chars().all(|c| c.is_ascii_digit()) {
+        return Err(anyhow!("invalid amount in URI, expected attoFIL: {s}"));
+    }
+    Ok(TokenAmount::from_atto(BigInt::from_str(s)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use fvm_shared::address::Address;
+    use fvm_shared::econ::TokenAmount;
+
+    use super::IpcUri;
+    use crate::subnet_id::SubnetID;
+
+    #[test]
+    fn uris_roundtrip() {
+        let subnet = SubnetID::new(314159, vec![Address::new_id(1001)]);
+
+        let pay = IpcUri::pay(
+            subnet.clone(),
+            Address::new_id(100),
+            Some(TokenAmount::from_whole(1)),
+        );
+        let s = pay.to_string();
+        assert_eq!(
+            s,
+            format!(
+                "ipc:pay?subnet={subnet}&to={}&amount=1000000000000000000",
+                Address::new_id(100)
+            )
+        );
+        assert_eq!(IpcUri::from_str(&s).unwrap(), pay);
+
+        let join = IpcUri::join(subnet.clone(), None);
+        assert_eq!(IpcUri::from_str(&join.to_string()).unwrap(), join);
+
+        // The order of the parameters doesn't matter.
+        let s = format!("ipc:join?amount=5&subnet={subnet}");
+        assert_eq!(
+            IpcUri::from_str(&s).unwrap(),
+            IpcUri::join(subnet, Some(TokenAmount::from_atto(5)))
+        );
+    }
+
+    #[test]
+    fn invalid_uris_are_refused() {
+        let subnet = SubnetID::new(314159, vec![Address::new_id(1001)]);
+        let to = Address::new_id(100);
+
+        for s in [
+            format!("eth:pay?subnet={subnet}&to={to}"),
+            format!("ipc:pay?subnet={subnet}"),
+            format!("ipc:stake?subnet={subnet}"),
+            format!("ipc:pay?subnet={subnet}&to={to}&amount=1.5"),
+            format!("ipc:pay?subnet={subnet}&to={to}&to={to}"),
+            format!("ipc:pay?subnet={subnet}&to={to}&memo=hello"),
+            format!("ipc:join?to={to}"),
+        ] {
+            assert!(IpcUri::from_str(&s).is_err(), "{s} should be refused");
+        }
+    }
+}
@@ -3,16 +3,23 @@
 
 use fvm_shared::{address::Address, econ::TokenAmount};
 use ipc_actors_abis::subnet_actor_getter_facet;
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
 
 use crate::{
     eth_to_fil_amount, ethers_address_to_fil_address,
     evm::{fil_to_eth_amount, payload_to_evm_address},
+    HumanReadable,
 };
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Validator {
+    #[serde_as(as = "HumanReadable")]
     pub addr: Address,
+    #[serde_as(as = "HumanReadable")]
     pub metadata: Vec<u8>,
+    #[serde_as(as = "HumanReadable")]
     pub weight: TokenAmount,
 }
 
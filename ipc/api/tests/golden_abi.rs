@@ -0,0 +1,171 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Golden vectors of the ABI of the IPC contracts, pinning the event topics and call selectors
+//! the Rust side relies on, and the encodings of the types it converts into their Solidity
+//! representation.
+//!
+//! The vectors in `golden/abi.json` were computed independently of the Rust encoders, from the
+//! Solidity definitions. A failure here means that the bindings or the conversions drifted from
+//! the contracts: the event filters would stop matching, or the contracts would reject, or hash
+//! differently, what the Rust side sends them. Only update the vectors along with the contracts.
+
+use ethers::abi::Tokenizable;
+use ethers::contract::{EthCall, EthEvent};
+use ethers::utils::keccak256;
+use fvm_shared::address::Address;
+use fvm_shared::econ::TokenAmount;
+use ipc_actors_abis::{
+    gateway_manager_facet, gateway_messenger_facet, lib_gateway, lib_quorum,
+    lib_staking_change_log, register_subnet_facet, subnet_actor_checkpointing_facet,
+    subnet_actor_manager_facet, top_down_finality_facet,
+};
+use ipc_api::address::IPCAddress;
+use ipc_api::checkpoint::BottomUpCheckpoint;
+use ipc_api::cross::{IpcEnvelope, IpcMsgKind};
+use ipc_api::subnet_id::SubnetID;
+use serde_json::Value;
+
+const GOLDEN: &str = include_str!("../golden/abi.json");
+
+fn golden() -> Value {
+    serde_json::from_str(GOLDEN).expect("invalid golden file")
+}
+
+fn golden_str<'a>(golden: &'a Value, path: &[&str]) -> &'a str {
+    path.iter()
+        .fold(golden, |v, k| &v[k])
+        .as_str()
+        .unwrap_or_else(|| panic!("missing golden vector {}", path.join("/")))
+}
+
+fn hex0x(bytes: impl AsRef<[u8]>) -> String {
+    format!("0x{}", ethers::utils::hex::encode(bytes))
+}
+
+macro_rules! check_event {
+    ($golden:expr, $module:ident :: $event:ident) => {{
+        let name = concat!(stringify!($module), "::", stringify!($event));
+        assert_eq!(
+            <$module::$event as EthEvent>::abi_signature(),
+            golden_str($golden, &["events", name, "signature"]),
+            "signature of {name}"
+        );
+        assert_eq!(
+            hex0x(<$module::$event as EthEvent>::signature()),
+            golden_str($golden, &["events", name, "topic"]),
+            "topic of {name}"
+        );
+    }};
+}
+
+macro_rules! check_call {
+    ($golden:expr, $module:ident :: $call:ident) => {{
+        let name = concat!(stringify!($module), "::", stringify!($call));
+        assert_eq!(
+            <$module::$call as EthCall>::abi_signature(),
+            golden_str($golden, &["calls", name, "signature"]),
+            "signature of {name}"
+        );
+        assert_eq!(
+            hex0x(<$module::$call as EthCall>::selector()),
+            golden_str($golden, &["calls", name, "selector"]),
+            "selector of {name}"
+        );
+    }};
+}
+
+#[test]
+fn event_topics() {
+    let golden = golden();
+    check_event!(&golden, lib_gateway::NewTopDownMessageFilter);
+    check_event!(&golden, lib_gateway::NewBottomUpMsgBatchFilter);
+    check_event!(
+        &golden,
+        lib_staking_change_log::NewStakingChangeRequestFilter
+    );
+    check_event!(&golden, lib_quorum::QuorumReachedFilter);
+    check_event!(&golden, register_subnet_facet::SubnetDeployedFilter);
+}
+
+#[test]
+fn call_selectors() {
+    let golden = golden();
+    check_call!(
+        &golden,
+        subnet_actor_checkpointing_facet::SubmitCheckpointCall
+    );
+    check_call!(&golden, subnet_actor_manager_facet::JoinCall);
+    check_call!(&golden, subnet_actor_manager_facet::StakeCall);
+    check_call!(&golden, subnet_actor_manager_facet::UnstakeCall);
+    check_call!(&golden, subnet_actor_manager_facet::LeaveCall);
+    check_call!(&golden, gateway_manager_facet::FundCall);
+    check_call!(&golden, gateway_manager_facet::ReleaseCall);
+    check_call!(&golden, gateway_messenger_facet::PropagateCall);
+    check_call!(&golden, top_down_finality_facet::CommitParentFinalityCall);
+}
+
+fn eth_addr(b: u8) -> Address {
+    Address::new_delegated(10, &[b; 20]).unwrap()
+}
+
+/// The subnet of the golden vectors, a child of `/r314159`.
+fn child() -> SubnetID {
+    SubnetID::new(314159, vec![eth_addr(0x11)])
+}
+
+/// The envelope of the golden vectors: a transfer of 1 FIL from an account of the child subnet
+/// to one in its parent.
+fn envelope() -> IpcEnvelope {
+    let child = child();
+    let parent = child.parent().unwrap();
+
+    IpcEnvelope {
+        kind: IpcMsgKind::Transfer,
+        from: IPCAddress::new(&child, &eth_addr(0x33)).unwrap(),
+        to: IPCAddress::new(&parent, &eth_addr(0x44)).unwrap(),
+        value: TokenAmount::from_whole(1),
+        nonce: 7,
+        message: vec![],
+    }
+}
+
+fn checkpoint() -> BottomUpCheckpoint {
+    BottomUpCheckpoint {
+        subnet_id: child(),
+        block_height: 100,
+        block_hash: vec![0x22; 32],
+        next_configuration_number: 3,
+        msgs: vec![envelope()],
+    }
+}
+
+#[test]
+fn envelope_encoding() {
+    let golden = golden();
+    let envelope = lib_gateway::IpcEnvelope::try_from(envelope()).unwrap();
+
+    // Encoded the way `abi.encode(envelope)` does, as a single tuple.
+    let abi = ethers::abi::encode(&[envelope.into_token()]);
+    assert_eq!(
+        hex0x(abi),
+        golden_str(&golden, &["encodings", "envelope", "abi"])
+    );
+}
+
+#[test]
+fn checkpoint_encoding() {
+    let golden = golden();
+    let checkpoint =
+        subnet_actor_checkpointing_facet::BottomUpCheckpoint::try_from(checkpoint()).unwrap();
+
+    // The contract checks the signatures of validators against `keccak256(abi.encode(checkpoint))`.
+    let abi = ethers::abi::encode(&[checkpoint.into_token()]);
+    assert_eq!(
+        hex0x(&abi),
+        golden_str(&golden, &["encodings", "checkpoint", "abi"])
+    );
+    assert_eq!(
+        hex0x(keccak256(&abi)),
+        golden_str(&golden, &["encodings", "checkpoint", "hash"])
+    );
+}
@@ -0,0 +1,37 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! List aliases cli command handler.
+
+use async_trait::async_trait;
+use clap::Args;
+use std::fmt::Debug;
+
+use super::entry_to_text;
+use crate::{get_ipc_provider, print_result, CommandLineHandler, GlobalArguments};
+
+/// The command to list the aliases of the address book.
+pub(crate) struct ListAliases;
+
+#[async_trait]
+impl CommandLineHandler for ListAliases {
+    type Arguments = ListAliasesArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("list aliases with args: {:?}", arguments);
+
+        let provider = get_ipc_provider(global)?;
+        let entries = provider.list_aliases()?;
+
+        print_result(global, &entries, |entries| {
+            entries
+                .iter()
+                .map(entry_to_text)
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(about = "List the aliases of the address book")]
+pub(crate) struct ListAliasesArgs {}
@@ -0,0 +1,46 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+use crate::{CommandLineHandler, GlobalArguments};
+
+use clap::{Args, Subcommand};
+use ipc_provider::address_book::Entry;
+
+use self::list::{ListAliases, ListAliasesArgs};
+use self::remove::{RemoveAlias, RemoveAliasArgs};
+use self::set::{SetAlias, SetAliasArgs};
+
+mod list;
+mod remove;
+mod set;
+
+#[derive(Debug, Args)]
+#[command(
+    name = "address-book",
+    about = "aliases accepted in place of addresses and subnets"
+)]
+#[command(args_conflicts_with_subcommands = true)]
+pub(crate) struct AddressBookCommandsArgs {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+impl AddressBookCommandsArgs {
+    pub async fn handle(&self, global: &GlobalArguments) -> anyhow::Result<()> {
+        match &self.command {
+            Commands::Set(args) => SetAlias::handle(global, args).await,
+            Commands::List(args) => ListAliases::handle(global, args).await,
+            Commands::Remove(args) => RemoveAlias::handle(global, args).await,
+        }
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum Commands {
+    Set(SetAliasArgs),
+    List(ListAliasesArgs),
+    Remove(RemoveAliasArgs),
+}
+
+fn entry_to_text(e: &Entry) -> String {
+    format!("{}: {} {}", e.alias, e.kind, e.value)
+}
@@ -0,0 +1,37 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Remove alias cli command handler.
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use clap::Args;
+use std::fmt::Debug;
+
+use crate::{get_ipc_provider, CommandLineHandler, GlobalArguments};
+
+/// The command to remove an alias from the address book.
+pub(crate) struct RemoveAlias;
+
+#[async_trait]
+impl CommandLineHandler for RemoveAlias {
+    type Arguments = RemoveAliasArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("remove alias with args: {:?}", arguments);
+
+        let provider = get_ipc_provider(global)?;
+        let entry = provider
+            .remove_alias(&arguments.alias)?
+            .ok_or_else(|| anyhow!("no alias {} in the address book", arguments.alias))?;
+
+        println!("removed alias {} of {}", entry.alias, entry.value);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(about = "Remove an alias from the address book")]
+pub(crate) struct RemoveAliasArgs {
+    #[arg(help = "The alias to remove")]
+    pub alias: String,
+}
@@ -0,0 +1,43 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Set alias cli command handler.
+
+use async_trait::async_trait;
+use clap::Args;
+use ipc_api::subnet_id::SubnetID;
+use std::{fmt::Debug, str::FromStr};
+
+use super::entry_to_text;
+use crate::{get_ipc_provider, print_result, CommandLineHandler, GlobalArguments};
+
+/// The command to name an address or a subnet in the address book.
+pub(crate) struct SetAlias;
+
+#[async_trait]
+impl CommandLineHandler for SetAlias {
+    type Arguments = SetAliasArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("set alias with args: {:?}", arguments);
+
+        let provider = get_ipc_provider(global)?;
+        // Subnet IDs start with the root, anything else has to be an address.
+        let entry = if arguments.value.starts_with('/') {
+            let subnet = SubnetID::from_str(&arguments.value)?;
+            provider.set_subnet_alias(&arguments.alias, &subnet)?
+        } else {
+            provider.set_address_alias(&arguments.alias, &arguments.value)?
+        };
+
+        print_result(global, &entry, entry_to_text)
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(about = "Name an address or a subnet, replacing what the alias stood for before")]
+pub(crate) struct SetAliasArgs {
+    #[arg(help = "The alias, made of letters, digits, '-', '_' and '.'")]
+    pub alias: String,
+    #[arg(help = "The Filecoin or 0x address, or the subnet ID, the alias stands for")]
+    pub value: String,
+}
@@ -0,0 +1,71 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! List signatures cli command handler.
+
+use async_trait::async_trait;
+use clap::Args;
+use ipc_provider::audit::AuditQuery;
+use std::fmt::Debug;
+
+use super::record_to_text;
+use crate::{
+    get_ipc_provider, print_result, require_fil_addr_from_str, require_subnet_id_from_str,
+    CommandLineHandler, GlobalArguments,
+};
+
+/// The command to list the signatures of the audit log.
+pub(crate) struct ListSignatures;
+
+#[async_trait]
+impl CommandLineHandler for ListSignatures {
+    type Arguments = ListSignaturesArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("list signatures with args: {:?}", arguments);
+
+        let provider = get_ipc_provider(global)?;
+        let query = AuditQuery {
+            key: match &arguments.key {
+                Some(key) => Some(require_fil_addr_from_str(key)?),
+                None => None,
+            },
+            subnet: match &arguments.subnet {
+                Some(subnet) => Some(require_subnet_id_from_str(subnet)?),
+                None => None,
+            },
+            since: arguments.since,
+            until: arguments.until,
+            limit: arguments.limit,
+        };
+
+        let records = provider.query_audit_log(&query)?;
+        print_result(global, &records, |records| {
+            records
+                .iter()
+                .map(record_to_text)
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(about = "List the signatures made with the keys of the keystore, oldest first")]
+pub(crate) struct ListSignaturesArgs {
+    #[arg(long, help = "Only list the signatures of this key")]
+    pub key: Option<String>,
+    #[arg(long, help = "Only list the signatures made for this subnet")]
+    pub subnet: Option<String>,
+    #[arg(
+        long,
+        help = "Only list the signatures made at or after this time, in seconds since the unix epoch"
+    )]
+    pub since: Option<u64>,
+    #[arg(
+        long,
+        help = "Only list the signatures made at or before this time, in seconds since the unix epoch"
+    )]
+    pub until: Option<u64>,
+    #[arg(long, help = "Only list the latest signatures, up to this many")]
+    pub limit: Option<usize>,
+}
@@ -0,0 +1,45 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+use crate::{CommandLineHandler, GlobalArguments};
+
+use clap::{Args, Subcommand};
+use ipc_provider::audit::AuditRecord;
+
+use self::list::{ListSignatures, ListSignaturesArgs};
+
+mod list;
+
+#[derive(Debug, Args)]
+#[command(
+    name = "audit",
+    about = "audit log of the signatures made with the keys of the keystore"
+)]
+#[command(args_conflicts_with_subcommands = true)]
+pub(crate) struct AuditCommandsArgs {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+impl AuditCommandsArgs {
+    pub async fn handle(&self, global: &GlobalArguments) -> anyhow::Result<()> {
+        match &self.command {
+            Commands::List(args) => ListSignatures::handle(global, args).await,
+        }
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum Commands {
+    List(ListSignaturesArgs),
+}
+
+fn record_to_text(r: &AuditRecord) -> String {
+    let mut text = format!(
+        "{}: {} signed {} {} on {}",
+        r.timestamp, r.key, r.operation, r.digest, r.subnet
+    );
+    if let Some(to) = &r.to {
+        text.push_str(&format!(" to {to}"));
+    }
+    text
+}
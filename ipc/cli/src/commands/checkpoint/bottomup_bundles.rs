@@ -3,15 +3,15 @@
 //! List bottom up bundles
 
 use std::fmt::Debug;
-use std::str::FromStr;
 
 use async_trait::async_trait;
 use clap::Args;
+use futures_util::StreamExt;
 use fvm_shared::clock::ChainEpoch;
-use ipc_api::subnet_id::SubnetID;
+use ipc_provider::range::{RangeQueryOptions, DEFAULT_CHUNK_SIZE, DEFAULT_CONCURRENCY};
 
 use crate::commands::get_ipc_provider;
-use crate::{CommandLineHandler, GlobalArguments};
+use crate::{require_subnet_id_from_str, CommandLineHandler, GlobalArguments};
 
 /// The command to get bottom up bundles at height.
 pub(crate) struct GetBottomUpBundles;
@@ -24,13 +24,21 @@ impl CommandLineHandler for GetBottomUpBundles {
         log::debug!("get bottom up bundles with args: {:?}", arguments);
 
         let provider = get_ipc_provider(global)?;
-        let subnet = SubnetID::from_str(&arguments.subnet)?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
 
-        for h in arguments.from_epoch..=arguments.to_epoch {
-            let Some(bundle) = provider.get_bottom_up_bundle(&subnet, h).await? else {
-                continue;
-            };
+        let options = RangeQueryOptions {
+            chunk_size: arguments.chunk_size,
+            concurrency: arguments.concurrency,
+        };
+        let mut bundles = std::pin::pin!(provider.stream_bottom_up_bundles(
+            &subnet,
+            arguments.from_epoch,
+            arguments.to_epoch,
+            options
+        ));
 
+        while let Some(bundle) = bundles.next().await {
+            let (h, bundle) = bundle?;
             println!("bottom up checkpoint bundle at height: {}", h);
             println!("{}", serde_json::to_string(&bundle)?);
         }
@@ -48,4 +56,16 @@ pub(crate) struct GetBottomUpBundlesArgs {
     pub from_epoch: ChainEpoch,
     #[arg(long, help = "Include checkpoints up to this epoch")]
     pub to_epoch: ChainEpoch,
+    #[arg(
+        long,
+        default_value_t = DEFAULT_CHUNK_SIZE,
+        help = "Number of consecutive epochs fetched one after the other by each task"
+    )]
+    pub chunk_size: usize,
+    #[arg(
+        long,
+        default_value_t = DEFAULT_CONCURRENCY,
+        help = "Number of chunks of epochs fetched at the same time"
+    )]
+    pub concurrency: usize,
 }
@@ -2,14 +2,12 @@
 // SPDX-License-Identifier: MIT
 
 use std::fmt::Debug;
-use std::str::FromStr;
 
 use async_trait::async_trait;
 use clap::Args;
-use ipc_api::subnet_id::SubnetID;
 
 use crate::commands::get_ipc_provider;
-use crate::{CommandLineHandler, GlobalArguments};
+use crate::{require_subnet_id_from_str, CommandLineHandler, GlobalArguments};
 
 /// The command to get the last bottom up checkpoint height in a subnet.
 pub(crate) struct LastBottomUpCheckpointHeight;
@@ -25,7 +23,7 @@ impl CommandLineHandler for LastBottomUpCheckpointHeight {
         );
 
         let provider = get_ipc_provider(global)?;
-        let subnet = SubnetID::from_str(&arguments.subnet)?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
 
         let height = provider.last_bottom_up_checkpoint_height(&subnet).await?;
         println!("height: {height}");
@@ -11,6 +11,8 @@ use crate::commands::checkpoint::quorum_reached::{
     GetQuorumReacehdEvents, GetQuorumReachedEventsArgs,
 };
 use crate::commands::checkpoint::relayer::{BottomUpRelayer, BottomUpRelayerArgs};
+use crate::commands::checkpoint::schedule::{CheckpointSchedule, CheckpointScheduleArgs};
+use crate::commands::checkpoint::verify::{VerifyCheckpoint, VerifyCheckpointArgs};
 use crate::{CommandLineHandler, GlobalArguments};
 use clap::{Args, Subcommand};
 
@@ -19,6 +21,8 @@ mod bottomup_height;
 mod list_validator_changes;
 mod quorum_reached;
 mod relayer;
+mod schedule;
+mod verify;
 
 #[derive(Debug, Args)]
 #[command(name = "checkpoint", about = "checkpoint related commands")]
@@ -42,6 +46,8 @@ impl CheckpointCommandsArgs {
             Commands::LastBottomupCheckpointHeight(args) => {
                 LastBottomUpCheckpointHeight::handle(global, args).await
             }
+            Commands::Schedule(args) => CheckpointSchedule::handle(global, args).await,
+            Commands::Verify(args) => VerifyCheckpoint::handle(global, args).await,
         }
     }
 }
@@ -53,4 +59,6 @@ pub(crate) enum Commands {
     ListBottomupBundle(GetBottomUpBundlesArgs),
     QuorumReachedEvents(GetQuorumReachedEventsArgs),
     LastBottomupCheckpointHeight(LastBottomUpCheckpointHeightArgs),
+    Schedule(CheckpointScheduleArgs),
+    Verify(VerifyCheckpointArgs),
 }
@@ -3,15 +3,13 @@
 //! List quorum reached events
 
 use std::fmt::Debug;
-use std::str::FromStr;
 
 use async_trait::async_trait;
 use clap::Args;
 use fvm_shared::clock::ChainEpoch;
-use ipc_api::subnet_id::SubnetID;
 
 use crate::commands::get_ipc_provider;
-use crate::{CommandLineHandler, GlobalArguments};
+use crate::{require_subnet_id_from_str, CommandLineHandler, GlobalArguments};
 
 /// The command to list quorum reached at height.
 pub(crate) struct GetQuorumReacehdEvents;
@@ -24,7 +22,7 @@ impl CommandLineHandler for GetQuorumReacehdEvents {
         log::debug!("get quorum reached events with args: {:?}", arguments);
 
         let provider = get_ipc_provider(global)?;
-        let subnet = SubnetID::from_str(&arguments.subnet)?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
 
         for h in arguments.from_epoch..=arguments.to_epoch {
             let events = provider.quorum_reached_events(&subnet, h).await?;
@@ -2,22 +2,38 @@
 // SPDX-License-Identifier: MIT
 
 use crate::commands::get_subnet_config;
-use crate::{require_fil_addr_from_str, CommandLineHandler, GlobalArguments};
-use anyhow::anyhow;
+use crate::{
+    parse_token_amount, require_fil_addr_from_str, require_subnet_id_from_str, CommandLineHandler,
+    GlobalArguments,
+};
+use anyhow::{anyhow, Context};
 use async_trait::async_trait;
 use clap::Args;
 use fvm_shared::address::Address;
 use fvm_shared::clock::ChainEpoch;
-use ipc_api::subnet_id::SubnetID;
-use ipc_provider::checkpoint::BottomUpCheckpointManager;
-use ipc_provider::config::Config;
-use ipc_provider::new_evm_keystore_from_config;
+use fvm_shared::econ::TokenAmount;
+use ipc_provider::checkpoint::{BottomUpCheckpointManager, DEFAULT_PIPELINE_CAPACITY};
+use ipc_provider::idempotency::IdempotencyStore;
+use ipc_provider::manager::EthSubnetManager;
+use ipc_provider::propagator::{PostboxPropagator, PropagationConfig, DEFAULT_PROPAGATION_MIN_AGE};
+use ipc_provider::roles::SUBMIT_CHECKPOINT;
+use ipc_provider::stats::register_metrics;
+use ipc_provider::supervisor::TaskSupervisor;
+use ipc_provider::{
+    new_audit_log_from_config, new_evm_keystore_from_config, new_key_roles_from_config,
+    new_provider_store_from_config, new_response_cache_from_config,
+};
 use ipc_wallet::EvmKeyStore;
-use std::str::FromStr;
+use std::net::SocketAddr;
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
 
 const DEFAULT_POLLING_INTERVAL: u64 = 15;
+/// Seconds after which a checkpoint submission interrupted by a restart is tried again; the
+/// parent rejects the checkpoints that were already committed.
+const PENDING_SUBMISSION_RETRY_SECS: u64 = 600;
+/// Seconds given to the checkpoint submissions in flight to finish when interrupted.
+const SHUTDOWN_GRACE_SECS: u64 = 60;
 
 /// The command to run the bottom up relayer in the background.
 pub(crate) struct BottomUpRelayer;
@@ -29,35 +45,60 @@ impl CommandLineHandler for BottomUpRelayer {
     async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
         log::debug!("start bottom up relayer with args: {:?}", arguments);
 
-        let config_path = global.config_path();
-        let config = Arc::new(Config::from_file(&config_path)?);
-        let mut keystore = new_evm_keystore_from_config(config)?;
-        let submitter = match (arguments.submitter.as_ref(), keystore.get_default()?) {
-            (Some(submitter), _) => require_fil_addr_from_str(submitter)?,
-            (None, Some(addr)) => {
-                log::info!("using default address: {addr:?}");
-                Address::try_from(addr)?
-            }
-            _ => {
-                return Err(anyhow!("no submitter address provided"));
-            }
+        let config = Arc::new(global.config()?);
+        let mut keystore = new_evm_keystore_from_config(config.clone())?;
+        let audit_log = new_audit_log_from_config(config.clone())?;
+        let idempotency = IdempotencyStore::new(new_provider_store_from_config(config.clone())?)
+            .with_retry_pending_after(Duration::from_secs(PENDING_SUBMISSION_RETRY_SECS));
+        let key_roles = new_key_roles_from_config(config.clone())?;
+        let submitter = match arguments.submitter.as_ref() {
+            Some(submitter) => require_fil_addr_from_str(submitter)?,
+            None => match (
+                key_roles.sender_for(SUBMIT_CHECKPOINT)?,
+                keystore.get_default()?,
+            ) {
+                (Some(addr), _) => {
+                    log::info!("using the key with the role of {SUBMIT_CHECKPOINT}: {addr}");
+                    addr
+                }
+                (None, Some(addr)) => {
+                    log::info!("using default address: {addr:?}");
+                    Address::try_from(addr)?
+                }
+                _ => {
+                    return Err(anyhow!("no submitter address provided"));
+                }
+            },
         };
+        key_roles.check(SUBMIT_CHECKPOINT, &submitter)?;
 
-        let subnet = SubnetID::from_str(&arguments.subnet)?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
         let parent = subnet
             .parent()
             .ok_or_else(|| anyhow!("root does not have parent"))?;
 
-        let child = get_subnet_config(&config_path, &subnet)?;
-        let parent = get_subnet_config(&config_path, &parent)?;
+        let child = get_subnet_config(&config, &subnet)?;
+        let parent = get_subnet_config(&config, &parent)?;
 
+        let keystore = Arc::new(RwLock::new(keystore));
         let mut manager = BottomUpCheckpointManager::new_evm_manager(
             parent.clone(),
             child.clone(),
-            Arc::new(RwLock::new(keystore)),
+            keystore.clone(),
+            Some(Arc::new(audit_log)),
             arguments.max_parallelism,
         )
-        .await?;
+        .await?
+        .with_idempotency_store(Arc::new(idempotency))
+        .with_pipeline_capacity(arguments.pipeline_capacity);
+
+        if let Some(cache) = new_response_cache_from_config(&config, &child)? {
+            log::info!(
+                "caching the responses of the child in {}",
+                cache.path().display()
+            );
+            manager = manager.with_response_cache(Arc::new(cache));
+        }
 
         if let Some(v) = arguments.finalization_blocks {
             manager = manager.with_finalization_blocks(v as ChainEpoch);
@@ -68,7 +109,58 @@ impl CommandLineHandler for BottomUpRelayer {
                 .checkpoint_interval_sec
                 .unwrap_or(DEFAULT_POLLING_INTERVAL),
         );
-        manager.run(submitter, interval).await;
+
+        if let Some(listen) = arguments.metrics_listen {
+            let registry = prometheus::Registry::new();
+            register_metrics(&registry).context("failed to register metrics")?;
+            let mut builder = prometheus_exporter::Builder::new(listen);
+            builder.with_registry(registry);
+            let _ = builder.start().context("failed to start metrics server")?;
+            log::info!("serving metrics on {listen}");
+        }
+
+        // Run until interrupted, then let the submissions in flight finish.
+        let supervisor = TaskSupervisor::new();
+        let manager = manager.with_shutdown_signal(supervisor.shutdown_signal());
+        supervisor.spawn("bottom-up relayer", manager.run(submitter, interval));
+
+        // The bottom-up messages going further than the parent wait in its postbox.
+        if let Some(fee_budget) = arguments.propagation_fee_budget.clone() {
+            let allowed_destinations = if arguments.propagate_to.is_empty() {
+                None
+            } else {
+                Some(
+                    arguments
+                        .propagate_to
+                        .iter()
+                        .map(|s| require_subnet_id_from_str(s))
+                        .collect::<anyhow::Result<_>>()?,
+                )
+            };
+            let handler = EthSubnetManager::from_subnet_with_wallet_store(&parent, Some(keystore))?;
+            let propagator = PostboxPropagator::new(
+                parent.clone(),
+                handler,
+                PropagationConfig {
+                    min_age: arguments.propagation_min_age,
+                    fee_budget,
+                    allowed_destinations,
+                },
+            )
+            .with_shutdown_signal(supervisor.shutdown_signal());
+            supervisor.spawn("postbox propagator", propagator.run(submitter, interval));
+        }
+
+        tokio::signal::ctrl_c().await?;
+        log::info!("shutting down the bottom up relayer");
+        let report = supervisor
+            .shutdown(Duration::from_secs(SHUTDOWN_GRACE_SECS))
+            .await;
+        if report.aborted > 0 {
+            return Err(anyhow!(
+                "checkpoint submissions interrupted by the shutdown"
+            ));
+        }
 
         Ok(())
     }
@@ -79,7 +171,10 @@ impl CommandLineHandler for BottomUpRelayer {
 pub(crate) struct BottomUpRelayerArgs {
     #[arg(long, help = "The subnet id of the checkpointing subnet")]
     pub subnet: String,
-    #[arg(long, help = "The number of seconds to submit checkpoint")]
+    #[arg(
+        long,
+        help = "The maximum number of seconds between rounds of submissions; rounds are timed by when the next checkpoint is due"
+    )]
     pub checkpoint_interval_sec: Option<u64>,
     #[arg(
         long,
@@ -95,4 +190,32 @@ pub(crate) struct BottomUpRelayerArgs {
         help = "The max parallelism for submitting checkpoints"
     )]
     pub max_parallelism: usize,
+    #[arg(
+        long,
+        default_value_t = DEFAULT_PIPELINE_CAPACITY,
+        help = "The number of checkpoints each stage of the relayer can queue up before waiting for the next one"
+    )]
+    pub pipeline_capacity: usize,
+    #[arg(
+        long,
+        help = "The address to serve the Prometheus metrics of the relayer on, e.g. 127.0.0.1:9185"
+    )]
+    pub metrics_listen: Option<SocketAddr>,
+    #[arg(
+        long,
+        value_parser = parse_token_amount,
+        help = "Propagate the messages waiting in the postbox of the parent, spending at most this much in fees (in whole FIL, or with a unit like 1000 nanoFIL)"
+    )]
+    pub propagation_fee_budget: Option<TokenAmount>,
+    #[arg(
+        long,
+        default_value_t = DEFAULT_PROPAGATION_MIN_AGE,
+        help = "The number of parent epochs a message waits in the postbox before it is propagated"
+    )]
+    pub propagation_min_age: ChainEpoch,
+    #[arg(
+        long,
+        help = "Only propagate the messages to this subnet; can be repeated, all subnets if unset"
+    )]
+    pub propagate_to: Vec<String>,
 }
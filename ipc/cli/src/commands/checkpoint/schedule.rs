@@ -0,0 +1,40 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Checkpoint schedule cli command
+
+use std::fmt::Debug;
+
+use async_trait::async_trait;
+use clap::Args;
+
+use crate::{
+    get_ipc_provider, print_result, require_subnet_id_from_str, CommandLineHandler, GlobalArguments,
+};
+
+/// The command to show where the bottom-up checkpoints of a subnet stand.
+pub(crate) struct CheckpointSchedule;
+
+#[async_trait]
+impl CommandLineHandler for CheckpointSchedule {
+    type Arguments = CheckpointScheduleArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("checkpoint schedule with args: {:?}", arguments);
+
+        let provider = get_ipc_provider(global)?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
+
+        let schedule = provider.checkpoint_schedule(&subnet).await?;
+        print_result(global, &schedule, |s| s.to_string())
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(
+    name = "schedule",
+    about = "The checkpoint period of a subnet, the last checkpoint committed in the parent and when the next one is due"
+)]
+pub(crate) struct CheckpointScheduleArgs {
+    #[arg(long, help = "The subnet to query")]
+    pub subnet: String,
+}
@@ -0,0 +1,60 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Verify a bottom up checkpoint cli command
+
+use std::fmt::Debug;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use clap::Args;
+use ipc_api::checkpoint::BottomUpCheckpointBundle;
+
+use crate::{
+    get_ipc_provider, print_result, require_subnet_id_from_str, CommandLineHandler, GlobalArguments,
+};
+
+/// The command to verify the signatures of a bottom up checkpoint against the membership of
+/// its subnet.
+pub(crate) struct VerifyCheckpoint;
+
+#[async_trait]
+impl CommandLineHandler for VerifyCheckpoint {
+    type Arguments = VerifyCheckpointArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("verify checkpoint with args: {:?}", arguments);
+
+        let provider = get_ipc_provider(global)?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
+
+        let bundle = std::fs::read_to_string(&arguments.bundle)
+            .with_context(|| format!("failed to read {}", arguments.bundle))?;
+        let bundle: BottomUpCheckpointBundle =
+            serde_json::from_str(&bundle).context("failed to parse the checkpoint bundle")?;
+
+        let signatures = bundle
+            .signatories
+            .into_iter()
+            .zip(bundle.signatures)
+            .collect();
+        let verification = provider
+            .verify_checkpoint(&subnet, bundle.checkpoint, signatures)
+            .await?;
+        print_result(global, &verification, |v| v.to_string())
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(
+    name = "verify",
+    about = "Verify that the signatures of a bottom up checkpoint reach the quorum of its subnet"
+)]
+pub(crate) struct VerifyCheckpointArgs {
+    #[arg(long, help = "The subnet of the checkpoint")]
+    pub subnet: String,
+    #[arg(
+        long,
+        help = "File with the checkpoint and its signatures as JSON, as listed by list-bottomup-bundle"
+    )]
+    pub bundle: String,
+}
@@ -0,0 +1,90 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+use std::path::Path;
+
+use async_trait::async_trait;
+use clap::Args;
+use ipc_provider::IpcProvider;
+use zeroize::Zeroizing;
+
+use crate::commands::get_ipc_provider;
+use crate::{CommandLineHandler, GlobalArguments};
+
+/// The environment variable the passphrase of a backup is read from. It's never taken as an
+/// argument, which would show up in the process list and the history of the shell.
+const PASSPHRASE_ENV: &str = "IPC_BACKUP_PASSPHRASE";
+
+/// The passphrase from [PASSPHRASE_ENV], or typed in if it isn't set. A new one has to be typed
+/// twice, so that a typo doesn't make the backup impossible to restore.
+fn read_passphrase(confirm: bool) -> anyhow::Result<Zeroizing<String>> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV) {
+        return Ok(Zeroizing::new(passphrase));
+    }
+    let passphrase = Zeroizing::new(rpassword::prompt_password("Backup passphrase: ")?);
+    if passphrase.is_empty() {
+        return Err(anyhow::anyhow!("the passphrase can't be empty"));
+    }
+    if confirm {
+        let repeated = Zeroizing::new(rpassword::prompt_password("Repeat the passphrase: ")?);
+        if repeated != passphrase {
+            return Err(anyhow::anyhow!("the passphrases don't match"));
+        }
+    }
+    Ok(passphrase)
+}
+
+/// The command to write an encrypted backup of the config and the keystore.
+pub(crate) struct BackupConfig;
+
+#[async_trait]
+impl CommandLineHandler for BackupConfig {
+    type Arguments = BackupConfigArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        let provider = get_ipc_provider(global)?;
+        let passphrase = read_passphrase(true)?;
+        provider.backup(&arguments.path, &passphrase)?;
+        log::info!("backup written to {}", arguments.path);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(about = "Write an encrypted backup of the config, the keystores and the address book")]
+pub(crate) struct BackupConfigArgs {
+    #[arg(long, help = "The file to write the backup to")]
+    pub path: String,
+}
+
+/// The command to restore a backup written by [BackupConfig].
+pub(crate) struct RestoreConfig;
+
+#[async_trait]
+impl CommandLineHandler for RestoreConfig {
+    type Arguments = RestoreConfigArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        let config_path = global.config_path();
+        if Path::new(&config_path).exists() {
+            return Err(anyhow::anyhow!(
+                "{config_path} already exists, refusing to overwrite it"
+            ));
+        }
+
+        let passphrase = read_passphrase(false)?;
+        let provider = IpcProvider::restore(&arguments.path, &passphrase)?;
+        if let Some(parent) = Path::new(&config_path).parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        provider.config().write_to_file_async(&config_path).await?;
+        log::info!("backup restored, config written to {config_path}");
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(about = "Restore the config, the keystores and the address book from a backup")]
+pub(crate) struct RestoreConfigArgs {
+    #[arg(long, help = "The file to read the backup from")]
+    pub path: String,
+}
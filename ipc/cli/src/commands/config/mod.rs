@@ -2,11 +2,15 @@
 // SPDX-License-Identifier: MIT
 //! This mod triggers a config reload in the IPC-Agent Json RPC server.
 
+mod backup;
 mod init;
 
 use clap::{Args, Subcommand};
 use std::fmt::Debug;
 
+use crate::commands::config::backup::{
+    BackupConfig, BackupConfigArgs, RestoreConfig, RestoreConfigArgs,
+};
 use crate::commands::config::init::{InitConfig, InitConfigArgs};
 use crate::{CommandLineHandler, GlobalArguments};
 
@@ -22,6 +26,8 @@ impl ConfigCommandsArgs {
     pub async fn handle(&self, global: &GlobalArguments) -> anyhow::Result<()> {
         match &self.command {
             Commands::Init(args) => InitConfig::handle(global, args).await,
+            Commands::Backup(args) => BackupConfig::handle(global, args).await,
+            Commands::Restore(args) => RestoreConfig::handle(global, args).await,
         }
     }
 }
@@ -29,4 +35,6 @@ impl ConfigCommandsArgs {
 #[derive(Debug, Subcommand)]
 pub(crate) enum Commands {
     Init(InitConfigArgs),
+    Backup(BackupConfigArgs),
+    Restore(RestoreConfigArgs),
 }
@@ -0,0 +1,89 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Cross-net message cost estimation cli command handler.
+
+use async_trait::async_trait;
+use clap::Args;
+use fvm_shared::econ::TokenAmount;
+use ipc_api::address::IPCAddress;
+use ipc_api::cross::{IpcEnvelope, IpcMsgKind};
+use std::fmt::Debug;
+
+use crate::commands::{get_ipc_provider, parse_token_amount, print_result};
+use crate::{
+    require_fil_addr_from_str, require_subnet_id_from_str, CommandLineHandler, GlobalArguments,
+};
+
+/// The command to estimate what a cross-net message costs over all the hops of its route.
+pub(crate) struct EstimateCost;
+
+#[async_trait]
+impl CommandLineHandler for EstimateCost {
+    type Arguments = EstimateCostArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("estimate cross-net message cost with args: {:?}", arguments);
+
+        let provider = get_ipc_provider(global)?;
+        let from_subnet = require_subnet_id_from_str(&arguments.from_subnet)?;
+        let to_subnet = require_subnet_id_from_str(&arguments.to_subnet)?;
+        let to = require_fil_addr_from_str(&arguments.to)?;
+        let from = match &arguments.from {
+            Some(address) => require_fil_addr_from_str(address)?,
+            None => to,
+        };
+        let message = match &arguments.payload {
+            Some(payload) => hex::decode(payload.trim_start_matches("0x"))?,
+            None => vec![],
+        };
+
+        let msg = IpcEnvelope {
+            kind: if message.is_empty() {
+                IpcMsgKind::Transfer
+            } else {
+                IpcMsgKind::Call
+            },
+            from: IPCAddress::new(&from_subnet, &from)?,
+            to: IPCAddress::new(&to_subnet, &to)?,
+            value: arguments.value.clone(),
+            message,
+            nonce: 0,
+        };
+
+        let cost = provider
+            .estimate_cross_msg_cost(&from_subnet, &to_subnet, &msg)
+            .await?;
+        print_result(global, &cost, |c| c.to_string())
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(
+    name = "estimate-cost",
+    about = "Estimate the fees of sending a cross-net message over all the hops between two subnets"
+)]
+pub(crate) struct EstimateCostArgs {
+    #[arg(long, help = "The subnet the message is sent from")]
+    pub from_subnet: String,
+    #[arg(long, help = "The subnet the message is sent to")]
+    pub to_subnet: String,
+    #[arg(
+        long,
+        help = "The address sending the message, the recipient by default"
+    )]
+    pub from: Option<String>,
+    #[arg(long, help = "The address receiving the message")]
+    pub to: String,
+    #[arg(
+        long,
+        default_value = "0",
+        help = "The value sent with the message (in whole FIL, or with a unit like 1000 nanoFIL)",
+        value_parser = parse_token_amount,
+    )]
+    pub value: TokenAmount,
+    #[arg(
+        long,
+        help = "The hex encoded payload of the message, if it calls a contract"
+    )]
+    pub payload: Option<String>,
+}
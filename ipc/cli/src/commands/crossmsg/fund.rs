@@ -2,17 +2,17 @@
 // SPDX-License-Identifier: MIT
 //! Fund cli command handler.
 
+use anyhow::anyhow;
 use async_trait::async_trait;
 use clap::Args;
 use fvm_shared::bigint::BigInt;
 use fvm_shared::econ::TokenAmount;
-use ipc_api::subnet_id::SubnetID;
 use num_traits::Num;
-use std::{fmt::Debug, str::FromStr};
+use std::fmt::Debug;
 
 use crate::{
-    f64_to_token_amount, get_ipc_provider, require_fil_addr_from_str, CommandLineHandler,
-    GlobalArguments,
+    get_ipc_provider, parse_token_amount, print_result, require_fil_addr_from_str,
+    require_subnet_id_from_str, CommandLineHandler, GlobalArguments,
 };
 
 /// The command to send funds to a subnet from parent
@@ -26,7 +26,7 @@ impl CommandLineHandler for Fund {
         log::debug!("fund operation with args: {:?}", arguments);
 
         let mut provider = get_ipc_provider(global)?;
-        let subnet = SubnetID::from_str(&arguments.subnet)?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
         let from = match &arguments.from {
             Some(address) => Some(require_fil_addr_from_str(address)?),
             None => None,
@@ -40,20 +40,39 @@ impl CommandLineHandler for Fund {
             None => None,
         };
 
-        println!(
-            "fund performed in epoch: {:?}",
-            provider
-                .fund(
-                    subnet,
-                    gateway_addr,
-                    from,
-                    to,
-                    f64_to_token_amount(arguments.amount)?,
-                )
-                .await?,
+        let idempotency = match &arguments.idempotency_key {
+            Some(key) => {
+                // The funds are sent from the parent, where the transaction is looked up.
+                let parent = subnet
+                    .parent()
+                    .ok_or_else(|| anyhow!("subnet {subnet} has no parent"))?;
+                let conn = provider
+                    .connection(&parent)
+                    .ok_or_else(|| anyhow!("parent subnet not found: {parent}"))?;
+                Some((key, provider.idempotency_store()?, conn))
+            }
+            None => None,
+        };
+
+        let fund = provider.fund(
+            subnet.clone(),
+            gateway_addr,
+            from,
+            to,
+            arguments.amount.clone(),
         );
+        let receipt = match idempotency {
+            Some((key, store, conn)) => {
+                store
+                    .run(key, "fund", &subnet, conn.manager(), fund)
+                    .await?
+            }
+            None => fund.await?,
+        };
 
-        Ok(())
+        print_result(global, &receipt, |r| {
+            format!("fund performed in epoch: {:?}", r.epoch)
+        })
     }
 }
 
@@ -71,8 +90,13 @@ pub(crate) struct FundArgs {
     pub to: Option<String>,
     #[arg(long, help = "The subnet to fund")]
     pub subnet: String,
-    #[arg(help = "The amount to fund in FIL, in whole FIL")]
-    pub amount: f64,
+    #[arg(help = "The amount to fund (in whole FIL, or with a unit like 1000 nanoFIL)", value_parser = parse_token_amount)]
+    pub amount: TokenAmount,
+    #[arg(
+        long,
+        help = "Submit only once under this key, returning the recorded receipt if it was already submitted"
+    )]
+    pub idempotency_key: Option<String>,
 }
 
 pub struct PreFund;
@@ -85,21 +109,19 @@ impl CommandLineHandler for PreFund {
         log::debug!("pre-fund subnet with args: {:?}", arguments);
 
         let mut provider = get_ipc_provider(global)?;
-        let subnet = SubnetID::from_str(&arguments.subnet)?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
         let from = match &arguments.from {
             Some(address) => Some(require_fil_addr_from_str(address)?),
             None => None,
         };
-        provider
-            .pre_fund(
-                subnet.clone(),
-                from,
-                f64_to_token_amount(arguments.initial_balance)?,
-            )
+        let receipt = provider
+            .pre_fund(subnet.clone(), from, arguments.initial_balance.clone())
             .await?;
         log::info!("address pre-funded successfully");
 
-        Ok(())
+        print_result(global, &receipt, |r| {
+            format!("pre-fund performed in epoch: {:?}", r.epoch)
+        })
     }
 }
 
@@ -113,8 +135,8 @@ pub struct PreFundArgs {
     pub from: Option<String>,
     #[arg(long, help = "The subnet to add balance to")]
     pub subnet: String,
-    #[arg(help = "Add an initial balance for the address in genesis in the subnet")]
-    pub initial_balance: f64,
+    #[arg(help = "Add an initial balance for the address in genesis in the subnet", value_parser = parse_token_amount)]
+    pub initial_balance: TokenAmount,
 }
 
 /// The command to send ERC20 tokens to a subnet from parent
@@ -128,7 +150,7 @@ impl CommandLineHandler for FundWithToken {
         log::debug!("fund with token operation with args: {:?}", arguments);
 
         let mut provider = get_ipc_provider(global)?;
-        let subnet = SubnetID::from_str(&arguments.subnet)?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
         let from = match &arguments.from {
             Some(address) => Some(require_fil_addr_from_str(address)?),
             None => None,
@@ -142,21 +164,27 @@ impl CommandLineHandler for FundWithToken {
             .map_err(|e| anyhow::anyhow!("not a token amount: {e}"))
             .map(TokenAmount::from_atto)?;
 
-        if arguments.approve {
-            println!(
-                "approve token performed in epoch: {:?}",
+        let approve = if arguments.approve {
+            Some(
                 provider
                     .approve_token(subnet.clone(), from, amount.clone())
                     .await?,
-            );
-        }
+            )
+        } else {
+            None
+        };
 
-        println!(
-            "fund with token performed in epoch: {:?}",
-            provider.fund_with_token(subnet, from, to, amount).await?,
-        );
+        let fund = provider.fund_with_token(subnet, from, to, amount).await?;
+
+        let result = serde_json::json!({ "approve": approve, "fund": fund });
 
-        Ok(())
+        print_result(global, &result, |_| {
+            let mut text = String::new();
+            if let Some(r) = &approve {
+                text += &format!("approve token performed in epoch: {:?}\n", r.epoch);
+            }
+            text + &format!("fund with token performed in epoch: {:?}", fund.epoch)
+        })
     }
 }
 
@@ -1,20 +1,23 @@
 // Copyright 2022-2024 Protocol Labs
 // SPDX-License-Identifier: MIT
+use self::estimate_cost::{EstimateCost, EstimateCostArgs};
 use self::fund::{FundWithToken, FundWithTokenArgs, PreFund, PreFundArgs};
 use self::release::{PreRelease, PreReleaseArgs};
 use self::topdown_cross::{
     LatestParentFinality, LatestParentFinalityArgs, ListTopdownMsgs, ListTopdownMsgsArgs,
+    TopdownReceipt, TopdownReceiptArgs,
 };
 use crate::commands::crossmsg::fund::Fund;
-use crate::commands::crossmsg::propagate::Propagate;
+use crate::commands::crossmsg::propagate::{ListPostbox, Propagate};
 use crate::commands::crossmsg::release::Release;
 use crate::{CommandLineHandler, GlobalArguments};
 use fund::FundArgs;
-use propagate::PropagateArgs;
+use propagate::{ListPostboxArgs, PropagateArgs};
 use release::ReleaseArgs;
 
 use clap::{Args, Subcommand};
 
+mod estimate_cost;
 pub mod fund;
 pub mod propagate;
 pub mod release;
@@ -37,8 +40,11 @@ impl CrossMsgsCommandsArgs {
             Commands::Release(args) => Release::handle(global, args).await,
             Commands::PreRelease(args) => PreRelease::handle(global, args).await,
             Commands::Propagate(args) => Propagate::handle(global, args).await,
+            Commands::ListPostbox(args) => ListPostbox::handle(global, args).await,
             Commands::ListTopdownMsgs(args) => ListTopdownMsgs::handle(global, args).await,
             Commands::ParentFinality(args) => LatestParentFinality::handle(global, args).await,
+            Commands::TopdownReceipt(args) => TopdownReceipt::handle(global, args).await,
+            Commands::EstimateCost(args) => EstimateCost::handle(global, args).await,
         }
     }
 }
@@ -51,6 +57,9 @@ pub(crate) enum Commands {
     Release(ReleaseArgs),
     PreRelease(PreReleaseArgs),
     Propagate(PropagateArgs),
+    ListPostbox(ListPostboxArgs),
     ListTopdownMsgs(ListTopdownMsgsArgs),
     ParentFinality(LatestParentFinalityArgs),
+    TopdownReceipt(TopdownReceiptArgs),
+    EstimateCost(EstimateCostArgs),
 }
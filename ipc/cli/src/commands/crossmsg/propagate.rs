@@ -6,7 +6,8 @@ use async_trait::async_trait;
 use clap::Args;
 use std::fmt::Debug;
 
-use crate::{CommandLineHandler, GlobalArguments};
+use crate::commands::{get_ipc_provider, print_result};
+use crate::{require_subnet_id_from_str, CommandLineHandler, GlobalArguments};
 
 /// The command to propagate a message in the postbox.
 pub(crate) struct Propagate;
@@ -32,3 +33,36 @@ pub(crate) struct PropagateArgs {
     #[arg(help = "The message cid to propagate")]
     pub postbox_msg_key: String,
 }
+
+/// The command to list the messages waiting in the postbox.
+pub(crate) struct ListPostbox;
+
+#[async_trait]
+impl CommandLineHandler for ListPostbox {
+    type Arguments = ListPostboxArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("list postbox with args: {:?}", arguments);
+
+        let provider = get_ipc_provider(global)?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
+
+        let msgs = provider.list_postbox(&subnet).await?;
+        print_result(global, &msgs, |msgs| {
+            if msgs.is_empty() {
+                return "no messages in the postbox".to_string();
+            }
+            msgs.iter()
+                .map(|m| m.to_string())
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(about = "List the messages waiting in the postbox to be propagated")]
+pub(crate) struct ListPostboxArgs {
+    #[arg(long, help = "The subnet whose gateway holds the messages")]
+    pub subnet: String,
+}
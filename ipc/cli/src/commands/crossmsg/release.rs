@@ -2,14 +2,15 @@
 // SPDX-License-Identifier: MIT
 //! Release cli command handler.
 
+use anyhow::anyhow;
 use async_trait::async_trait;
 use clap::Args;
-use ipc_api::subnet_id::SubnetID;
-use std::{fmt::Debug, str::FromStr};
+use fvm_shared::econ::TokenAmount;
+use std::fmt::Debug;
 
 use crate::{
-    f64_to_token_amount, get_ipc_provider, require_fil_addr_from_str, CommandLineHandler,
-    GlobalArguments,
+    get_ipc_provider, parse_token_amount, print_result, require_fil_addr_from_str,
+    require_subnet_id_from_str, CommandLineHandler, GlobalArguments,
 };
 
 /// The command to release funds from a child to a parent
@@ -23,7 +24,7 @@ impl CommandLineHandler for Release {
         log::debug!("release operation with args: {:?}", arguments);
 
         let mut provider = get_ipc_provider(global)?;
-        let subnet = SubnetID::from_str(&arguments.subnet)?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
         let from = match &arguments.from {
             Some(address) => Some(require_fil_addr_from_str(address)?),
             None => None,
@@ -37,20 +38,35 @@ impl CommandLineHandler for Release {
             None => None,
         };
 
-        println!(
-            "release performed in epoch: {:?}",
-            provider
-                .release(
-                    subnet,
-                    gateway_addr,
-                    from,
-                    to,
-                    f64_to_token_amount(arguments.amount)?,
-                )
-                .await?,
+        let idempotency = match &arguments.idempotency_key {
+            Some(key) => {
+                let conn = provider
+                    .connection(&subnet)
+                    .ok_or_else(|| anyhow!("target subnet not found: {subnet}"))?;
+                Some((key, provider.idempotency_store()?, conn))
+            }
+            None => None,
+        };
+
+        let release = provider.release(
+            subnet.clone(),
+            gateway_addr,
+            from,
+            to,
+            arguments.amount.clone(),
         );
+        let receipt = match idempotency {
+            Some((key, store, conn)) => {
+                store
+                    .run(key, "release", &subnet, conn.manager(), release)
+                    .await?
+            }
+            None => release.await?,
+        };
 
-        Ok(())
+        print_result(global, &receipt, |r| {
+            format!("release performed in epoch: {:?}", r.epoch)
+        })
     }
 }
 
@@ -68,8 +84,13 @@ pub(crate) struct ReleaseArgs {
     pub to: Option<String>,
     #[arg(long, help = "The subnet to release funds from")]
     pub subnet: String,
-    #[arg(help = "The amount to release in FIL, in whole FIL")]
-    pub amount: f64,
+    #[arg(help = "The amount to release (in whole FIL, or with a unit like 1000 nanoFIL)", value_parser = parse_token_amount)]
+    pub amount: TokenAmount,
+    #[arg(
+        long,
+        help = "Submit only once under this key, returning the recorded receipt if it was already submitted"
+    )]
+    pub idempotency_key: Option<String>,
 }
 
 pub struct PreRelease;
@@ -82,17 +103,19 @@ impl CommandLineHandler for PreRelease {
         log::debug!("pre-release subnet with args: {:?}", arguments);
 
         let mut provider = get_ipc_provider(global)?;
-        let subnet = SubnetID::from_str(&arguments.subnet)?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
         let from = match &arguments.from {
             Some(address) => Some(require_fil_addr_from_str(address)?),
             None => None,
         };
-        provider
-            .pre_release(subnet.clone(), from, f64_to_token_amount(arguments.amount)?)
+        let receipt = provider
+            .pre_release(subnet.clone(), from, arguments.amount.clone())
             .await?;
         log::info!("address pre-release successfully");
 
-        Ok(())
+        print_result(global, &receipt, |r| {
+            format!("pre-release performed in epoch: {:?}", r.epoch)
+        })
     }
 }
 
@@ -106,6 +129,6 @@ pub struct PreReleaseArgs {
     pub from: Option<String>,
     #[arg(long, help = "The subnet to release balance from")]
     pub subnet: String,
-    #[arg(help = "Amount to release from the genesis balance of a child subnet")]
-    pub amount: f64,
+    #[arg(help = "Amount to release from the genesis balance of a child subnet", value_parser = parse_token_amount)]
+    pub amount: TokenAmount,
 }
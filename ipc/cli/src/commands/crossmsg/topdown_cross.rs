@@ -3,15 +3,15 @@
 //! List top down cross messages
 
 use std::fmt::Debug;
-use std::str::FromStr;
 
 use async_trait::async_trait;
 use clap::Args;
+use futures_util::StreamExt;
 use fvm_shared::clock::ChainEpoch;
-use ipc_api::subnet_id::SubnetID;
+use ipc_provider::range::{RangeQueryOptions, DEFAULT_CHUNK_SIZE, DEFAULT_CONCURRENCY};
 
-use crate::commands::get_ipc_provider;
-use crate::{CommandLineHandler, GlobalArguments};
+use crate::commands::{get_ipc_provider, print_result};
+use crate::{require_subnet_id_from_str, CommandLineHandler, GlobalArguments};
 
 /// The command to list top down cross messages in a subnet
 pub(crate) struct ListTopdownMsgs;
@@ -24,10 +24,21 @@ impl CommandLineHandler for ListTopdownMsgs {
         log::debug!("list topdown messages with args: {:?}", arguments);
 
         let provider = get_ipc_provider(global)?;
-        let subnet = SubnetID::from_str(&arguments.subnet)?;
-
-        for h in arguments.from..=arguments.to {
-            let result = provider.get_top_down_msgs(&subnet, h).await?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
+
+        let options = RangeQueryOptions {
+            chunk_size: arguments.chunk_size,
+            concurrency: arguments.concurrency,
+        };
+        let mut results = std::pin::pin!(provider.stream_top_down_msgs(
+            &subnet,
+            arguments.from,
+            arguments.to,
+            options
+        ));
+
+        while let Some(result) = results.next().await {
+            let (h, result) = result?;
             println!(
                 "block height: {}, block hash: {}, number of messages: {}",
                 h,
@@ -58,6 +69,18 @@ pub(crate) struct ListTopdownMsgsArgs {
     pub from: ChainEpoch,
     #[arg(long, help = "Include topdown messages to this epoch")]
     pub to: ChainEpoch,
+    #[arg(
+        long,
+        default_value_t = DEFAULT_CHUNK_SIZE,
+        help = "Number of consecutive epochs fetched one after the other by each task"
+    )]
+    pub chunk_size: usize,
+    #[arg(
+        long,
+        default_value_t = DEFAULT_CONCURRENCY,
+        help = "Number of chunks of epochs fetched at the same time"
+    )]
+    pub concurrency: usize,
 }
 
 pub(crate) struct LatestParentFinality;
@@ -70,7 +93,7 @@ impl CommandLineHandler for LatestParentFinality {
         log::debug!("latest parent finality: {:?}", arguments);
 
         let provider = get_ipc_provider(global)?;
-        let subnet = SubnetID::from_str(&arguments.subnet)?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
 
         println!("{}", provider.latest_parent_finality(&subnet).await?);
         Ok(())
@@ -83,3 +106,36 @@ pub(crate) struct LatestParentFinalityArgs {
     #[arg(long, help = "The subnet id to check parent finality")]
     pub subnet: String,
 }
+
+/// The command to show how a top-down message went in the child subnet.
+pub(crate) struct TopdownReceipt;
+
+#[async_trait]
+impl CommandLineHandler for TopdownReceipt {
+    type Arguments = TopdownReceiptArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("topdown receipt with args: {:?}", arguments);
+
+        let provider = get_ipc_provider(global)?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
+
+        let receipt = provider.top_down_receipt(&subnet, arguments.nonce).await?;
+        print_result(global, &receipt, |r| match r {
+            Some(r) => r.to_string(),
+            None => format!("top-down message {} not executed yet", arguments.nonce),
+        })
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(about = "Show the result of executing a top-down message in the child subnet")]
+pub(crate) struct TopdownReceiptArgs {
+    #[arg(
+        long,
+        help = "The subnet id of the child subnet the message was sent to"
+    )]
+    pub subnet: String,
+    #[arg(long, help = "The top-down nonce of the message")]
+    pub nonce: u64,
+}
@@ -0,0 +1,45 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+use crate::commands::ml::predict::{Predict, PredictArgs};
+use crate::commands::ml::train::{Train, TrainArgs};
+use crate::{CommandLineHandler, GlobalArguments};
+use anyhow::Context;
+use clap::{Args, Subcommand};
+use serde::de::DeserializeOwned;
+use std::path::Path;
+
+mod predict;
+mod train;
+
+#[derive(Debug, Args)]
+#[command(
+    name = "ml",
+    about = "machine learning related commands; features and labels are fixed-point numbers with two decimals, e.g. 150 stands for 1.5"
+)]
+#[command(args_conflicts_with_subcommands = true)]
+pub(crate) struct MlCommandsArgs {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+impl MlCommandsArgs {
+    pub async fn handle(&self, global: &GlobalArguments) -> anyhow::Result<()> {
+        match &self.command {
+            Commands::Train(args) => Train::handle(global, args).await,
+            Commands::Predict(args) => Predict::handle(global, args).await,
+        }
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum Commands {
+    Train(TrainArgs),
+    Predict(PredictArgs),
+}
+
+/// Read a JSON file, such as an input matrix or the list of labels.
+pub(crate) fn read_json<T: DeserializeOwned>(path: &Path) -> anyhow::Result<T> {
+    let json = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    serde_json::from_str(&json).with_context(|| format!("failed to parse {}", path.display()))
+}
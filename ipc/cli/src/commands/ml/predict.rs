@@ -0,0 +1,66 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Predict with a model using the machine learning actor of a subnet
+
+use std::fmt::Debug;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use clap::Args;
+use ipc_api::model::MlAlgorithm;
+
+use crate::commands::get_ipc_provider;
+use crate::commands::ml::read_json;
+use crate::{
+    require_fil_addr_from_str, require_subnet_id_from_str, CommandLineHandler, GlobalArguments,
+};
+
+/// The command to predict with a trained model.
+pub(crate) struct Predict;
+
+#[async_trait]
+impl CommandLineHandler for Predict {
+    type Arguments = PredictArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("predict with args: {:?}", arguments);
+
+        let mut provider = get_ipc_provider(global)?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
+        let from = match &arguments.from {
+            Some(address) => Some(require_fil_addr_from_str(address)?),
+            None => None,
+        };
+
+        let input_matrix: Vec<Vec<i64>> = read_json(&arguments.input)?;
+        let model = std::fs::read(&arguments.model)?;
+
+        let predictions = provider
+            .ml_predict(&subnet, from, arguments.algorithm, input_matrix, model)
+            .await?;
+
+        println!("{}", serde_json::to_string(&predictions)?);
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(about = "Predict labels with a model trained by the machine learning actor")]
+pub(crate) struct PredictArgs {
+    #[arg(long, help = "The subnet to predict in")]
+    pub subnet: String,
+    #[arg(long, help = "The address calling the actor")]
+    pub from: Option<String>,
+    #[arg(
+        long,
+        help = "The algorithm the model was trained with: linear_regression, logistic_regression or knn_regression",
+        value_parser = MlAlgorithm::from_str,
+    )]
+    pub algorithm: MlAlgorithm,
+    #[arg(long, help = "JSON file with the input matrix, an array of rows")]
+    pub input: PathBuf,
+    #[arg(long, help = "File with the model written by `ml train`")]
+    pub model: PathBuf,
+}
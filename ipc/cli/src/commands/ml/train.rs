@@ -0,0 +1,78 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Train a model with the machine learning actor of a subnet
+
+use std::fmt::Debug;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use clap::Args;
+use ipc_api::model::MlAlgorithm;
+
+use crate::commands::get_ipc_provider;
+use crate::commands::ml::read_json;
+use crate::{
+    require_fil_addr_from_str, require_subnet_id_from_str, CommandLineHandler, GlobalArguments,
+};
+
+/// The command to train a model.
+pub(crate) struct Train;
+
+#[async_trait]
+impl CommandLineHandler for Train {
+    type Arguments = TrainArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("train model with args: {:?}", arguments);
+
+        let mut provider = get_ipc_provider(global)?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
+        let from = match &arguments.from {
+            Some(address) => Some(require_fil_addr_from_str(address)?),
+            None => None,
+        };
+
+        let input_matrix: Vec<Vec<i64>> = read_json(&arguments.input)?;
+        let labels: Vec<i64> = read_json(&arguments.labels)?;
+
+        let model = provider
+            .ml_train(&subnet, from, arguments.algorithm, input_matrix, labels)
+            .await?;
+
+        match &arguments.output {
+            Some(path) => {
+                std::fs::write(path, &model)?;
+                log::info!(
+                    "model of {} bytes written to {}",
+                    model.len(),
+                    path.display()
+                );
+            }
+            None => println!("{}", hex::encode(model)),
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(about = "Train a model with the machine learning actor of a subnet")]
+pub(crate) struct TrainArgs {
+    #[arg(long, help = "The subnet to train in")]
+    pub subnet: String,
+    #[arg(long, help = "The address calling the actor")]
+    pub from: Option<String>,
+    #[arg(
+        long,
+        help = "The algorithm: linear_regression, logistic_regression or knn_regression",
+        value_parser = MlAlgorithm::from_str,
+    )]
+    pub algorithm: MlAlgorithm,
+    #[arg(long, help = "JSON file with the input matrix, an array of rows")]
+    pub input: PathBuf,
+    #[arg(long, help = "JSON file with the array of labels, one for each row")]
+    pub labels: PathBuf,
+    #[arg(long, help = "File to write the model to; printed as hex if missing")]
+    pub output: Option<PathBuf>,
+}
@@ -2,39 +2,53 @@
 // SPDX-License-Identifier: MIT
 //! This mod contains the different command line implementations.
 
+mod address_book;
+mod audit;
 mod checkpoint;
 mod config;
 mod crossmsg;
+mod ml;
+mod offline;
 // mod daemon;
+mod submission;
 mod subnet;
+mod uri;
 mod util;
 mod wallet;
 
+use crate::commands::address_book::AddressBookCommandsArgs;
+use crate::commands::audit::AuditCommandsArgs;
 use crate::commands::checkpoint::CheckpointCommandsArgs;
 use crate::commands::crossmsg::CrossMsgsCommandsArgs;
+use crate::commands::ml::MlCommandsArgs;
+use crate::commands::offline::OfflineCommandsArgs;
+use crate::commands::submission::SubmissionCommandsArgs;
+use crate::commands::uri::UriCommandsArgs;
 use crate::commands::util::UtilCommandsArgs;
-use crate::GlobalArguments;
+use crate::{GlobalArguments, OutputFormat};
 use anyhow::{anyhow, Context, Result};
 
 use clap::{Command, CommandFactory, Parser, Subcommand};
 use clap_complete::{generate, Generator, Shell};
 use fvm_shared::econ::TokenAmount;
-use ipc_api::ethers_address_to_fil_address;
 
 use fvm_shared::address::set_current_network;
 use ipc_api::subnet_id::SubnetID;
+use ipc_api::token::TokenAmountExt;
+use ipc_provider::address_book::{parse_address, AddressBook};
 use ipc_provider::config::{Config, Subnet};
+use serde::Serialize;
 use std::fmt::Debug;
 use std::io;
-use std::path::Path;
 use std::str::FromStr;
+use std::sync::{Arc, OnceLock};
 
 use crate::commands::config::ConfigCommandsArgs;
 use crate::commands::wallet::WalletCommandsArgs;
 use subnet::SubnetCommandsArgs;
 
-/// We only support up to 9 decimal digits for transaction
-const FIL_AMOUNT_NANO_DIGITS: u32 = 9;
+/// The address book of the provider, whose aliases are accepted in place of addresses and subnets.
+static ADDRESS_BOOK: OnceLock<AddressBook> = OnceLock::new();
 
 /// The collection of all subcommands to be called, see clap's documentation for usage. Internal
 /// to the current mode. Register a new command accordingly.
@@ -46,7 +60,13 @@ enum Commands {
     Wallet(WalletCommandsArgs),
     CrossMsg(CrossMsgsCommandsArgs),
     Checkpoint(CheckpointCommandsArgs),
+    Ml(MlCommandsArgs),
     Util(UtilCommandsArgs),
+    Submission(SubmissionCommandsArgs),
+    AddressBook(AddressBookCommandsArgs),
+    Audit(AuditCommandsArgs),
+    Offline(OfflineCommandsArgs),
+    Uri(UriCommandsArgs),
 }
 
 #[derive(Debug, Parser)]
@@ -124,6 +144,7 @@ pub async fn cli() -> anyhow::Result<()> {
         Ok(())
     } else {
         let global = &args.global_params;
+        load_address_book(global);
         if let Some(c) = &args.command {
             let r = match &c {
                 // Commands::Daemon(args) => LaunchDaemon::handle(global, args).await,
@@ -132,7 +153,13 @@ pub async fn cli() -> anyhow::Result<()> {
                 Commands::CrossMsg(args) => args.handle(global).await,
                 Commands::Wallet(args) => args.handle(global).await,
                 Commands::Checkpoint(args) => args.handle(global).await,
+                Commands::Ml(args) => args.handle(global).await,
                 Commands::Util(args) => args.handle(global).await,
+                Commands::Submission(args) => args.handle(global).await,
+                Commands::AddressBook(args) => args.handle(global).await,
+                Commands::Audit(args) => args.handle(global).await,
+                Commands::Offline(args) => args.handle(global).await,
+                Commands::Uri(args) => args.handle(global).await,
             };
 
             r.with_context(|| format!("error processing command {:?}", args.command))
@@ -146,36 +173,62 @@ fn print_completions<G: Generator>(gen: G, cmd: &mut Command) {
     generate(gen, cmd, cmd.get_name().to_string(), &mut io::stdout());
 }
 
+/// Load the address book next to the keystore, if there is a config to find it with.
+fn load_address_book(global: &GlobalArguments) {
+    let book = global
+        .config()
+        .and_then(|config| ipc_provider::new_address_book_from_config(Arc::new(config)));
+    match book {
+        Ok(book) => {
+            let _ = ADDRESS_BOOK.set(book);
+        }
+        Err(e) => log::debug!("no address book loaded: {e}"),
+    }
+}
+
 pub(crate) fn get_ipc_provider(global: &GlobalArguments) -> Result<ipc_provider::IpcProvider> {
-    ipc_provider::IpcProvider::new_from_config(global.config_path())
+    ipc_provider::IpcProvider::new_from_config_with_profile(global.config_path(), global.profile())
+}
+
+/// Print the result of a command, as JSON if requested with `--output json`, otherwise as text.
+pub(crate) fn print_result<T: Serialize>(
+    global: &GlobalArguments,
+    result: &T,
+    text: impl FnOnce(&T) -> String,
+) -> Result<()> {
+    match global.output() {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(result)?),
+        OutputFormat::Text => println!("{}", text(result)),
+    }
+    Ok(())
 }
 
-pub(crate) fn f64_to_token_amount(f: f64) -> anyhow::Result<TokenAmount> {
-    // no rounding, just the integer part
-    let nano = f64::trunc(f * (10u64.pow(FIL_AMOUNT_NANO_DIGITS) as f64));
-    Ok(TokenAmount::from_nano(nano as u128))
+/// Parses an amount like `1.5`, `1.5 FIL` or `1000 attoFIL` exactly; amounts without a unit
+/// are in whole FIL. Meant to be used as the `value_parser` of amount arguments.
+pub(crate) fn parse_token_amount(s: &str) -> anyhow::Result<TokenAmount> {
+    TokenAmount::from_denominated_str(s)
 }
 
 /// Receives a f/eth-address as an input and returns the corresponding
-/// filecoin or delegated address, respectively
+/// filecoin or delegated address, respectively, or the address an alias of the address book
+/// stands for.
 pub(crate) fn require_fil_addr_from_str(s: &str) -> anyhow::Result<fvm_shared::address::Address> {
-    let addr = match fvm_shared::address::Address::from_str(s) {
-        Err(_) => {
-            // see if it is an eth address
-            let addr = ethers::types::Address::from_str(s)?;
-            ethers_address_to_fil_address(&addr)?
-        }
-        Ok(addr) => addr,
-    };
-    Ok(addr)
+    match ADDRESS_BOOK.get() {
+        Some(book) => book.resolve_address(s),
+        None => parse_address(s),
+    }
+}
+
+/// Receives a subnet ID, or an alias of the address book, and returns the subnet ID.
+pub(crate) fn require_subnet_id_from_str(s: &str) -> anyhow::Result<SubnetID> {
+    match ADDRESS_BOOK.get() {
+        Some(book) => book.resolve_subnet(s),
+        None => Ok(SubnetID::from_str(s)?),
+    }
 }
 
-/// Get the subnet configuration from the config path
-pub(crate) fn get_subnet_config(
-    config_path: impl AsRef<Path>,
-    subnet: &SubnetID,
-) -> Result<Subnet> {
-    let config = Config::from_file(&config_path)?;
+/// Get the subnet configuration from the config
+pub(crate) fn get_subnet_config(config: &Config, subnet: &SubnetID) -> Result<Subnet> {
     Ok(config
         .subnets
         .get(subnet)
@@ -185,12 +238,15 @@ pub(crate) fn get_subnet_config(
 
 #[cfg(test)]
 mod tests {
-    use crate::f64_to_token_amount;
+    use crate::parse_token_amount;
     use fvm_shared::econ::TokenAmount;
 
     #[test]
     fn test_amount() {
-        let amount = f64_to_token_amount(1000000.1f64).unwrap();
+        let amount = parse_token_amount("1000000.1").unwrap();
         assert_eq!(amount, TokenAmount::from_nano(1000000100000000u128));
+
+        let amount = parse_token_amount("0.000000000000000001 FIL").unwrap();
+        assert_eq!(amount, TokenAmount::from_atto(1));
     }
 }
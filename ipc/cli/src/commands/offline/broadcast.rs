@@ -0,0 +1,51 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Broadcast signed transaction cli command handler.
+
+use async_trait::async_trait;
+use clap::Args;
+use ipc_provider::offline::{BroadcastReceipt, SignedTx};
+use serde_json::json;
+use std::fmt::Debug;
+
+use crate::{get_ipc_provider, print_result, CommandLineHandler, GlobalArguments};
+
+/// The command to broadcast a transaction signed with `offline sign`.
+pub(crate) struct BroadcastSigned;
+
+#[async_trait]
+impl CommandLineHandler for BroadcastSigned {
+    type Arguments = BroadcastSignedArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("broadcast signed tx with args: {:?}", arguments);
+
+        let provider = get_ipc_provider(global)?;
+        let signed = SignedTx::read_from_file(&arguments.input)?;
+
+        match provider.broadcast_signed(&signed).await? {
+            BroadcastReceipt::Evm(receipt) => print_result(global, &receipt, |r| {
+                format!("transaction {:?} executed in epoch: {}", r.tx_hash, r.epoch)
+            }),
+            BroadcastReceipt::Fvm(receipt) => {
+                let receipt = json!({
+                    "cid": receipt.cid.map(|cid| cid.to_string()),
+                    "height": receipt.height,
+                    "exit_code": receipt.exit_code,
+                    "gas_used": receipt.gas_used,
+                    "return_data": hex::encode(&receipt.return_data),
+                });
+                print_result(global, &receipt, |r| {
+                    format!("message {} executed at height {}", r["cid"], r["height"])
+                })
+            }
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(about = "Broadcast a transaction signed offline, and wait for it to be executed")]
+pub(crate) struct BroadcastSignedArgs {
+    #[arg(long, help = "The file of the signed transaction")]
+    pub input: String,
+}
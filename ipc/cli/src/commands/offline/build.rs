@@ -0,0 +1,125 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Build offline transaction cli command handlers.
+
+use async_trait::async_trait;
+use clap::Args;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::MethodNum;
+use ipc_provider::fvm::FvmMessage;
+use ipc_provider::offline::OfflineOp;
+use std::fmt::Debug;
+
+use super::unsigned_to_text;
+use crate::{
+    get_ipc_provider, parse_token_amount, print_result, require_fil_addr_from_str,
+    require_subnet_id_from_str, CommandLineHandler, GlobalArguments,
+};
+
+/// The command to build a transaction of an EVM subnet to sign offline.
+pub(crate) struct BuildTx;
+
+#[async_trait]
+impl CommandLineHandler for BuildTx {
+    type Arguments = BuildTxArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("build unsigned tx with args: {:?}", arguments);
+
+        let mut provider = get_ipc_provider(global)?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
+        let from = match &arguments.from {
+            Some(address) => Some(require_fil_addr_from_str(address)?),
+            None => None,
+        };
+        let calldata = match &arguments.calldata {
+            Some(calldata) => hex::decode(calldata.trim_start_matches("0x"))?,
+            None => vec![],
+        };
+        let op = OfflineOp::Evm {
+            from,
+            to: require_fil_addr_from_str(&arguments.to)?,
+            calldata,
+            value: arguments.value.clone(),
+        };
+
+        let tx = provider.build_unsigned(&subnet, op).await?;
+        tx.write_to_file(&arguments.output)?;
+        print_result(global, &tx, |tx| {
+            format!("{}, written to {}", unsigned_to_text(tx), arguments.output)
+        })
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(about = "Build a transaction of an EVM subnet, to be signed offline")]
+pub(crate) struct BuildTxArgs {
+    #[arg(long, help = "The subnet to send the transaction to")]
+    pub subnet: String,
+    #[arg(
+        long,
+        help = "The address sending the transaction, the default one if not set"
+    )]
+    pub from: Option<String>,
+    #[arg(long, help = "The address the transaction is sent to")]
+    pub to: String,
+    #[arg(long, help = "The hex encoded calldata of the contract call, if any")]
+    pub calldata: Option<String>,
+    #[arg(long, default_value = "0", help = "The amount to send (in whole FIL, or with a unit like 1000 nanoFIL)", value_parser = parse_token_amount)]
+    pub value: TokenAmount,
+    #[arg(long, help = "The file to write the unsigned transaction to")]
+    pub output: String,
+}
+
+/// The command to build a message to an actor of an FVM subnet to sign offline.
+pub(crate) struct BuildMessage;
+
+#[async_trait]
+impl CommandLineHandler for BuildMessage {
+    type Arguments = BuildMessageArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("build unsigned message with args: {:?}", arguments);
+
+        let mut provider = get_ipc_provider(global)?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
+        let params = match &arguments.params {
+            Some(params) => hex::decode(params.trim_start_matches("0x"))?,
+            None => vec![],
+        };
+        let msg = FvmMessage::new(
+            require_fil_addr_from_str(&arguments.from)?,
+            require_fil_addr_from_str(&arguments.to)?,
+            arguments.method,
+            params,
+        )
+        .with_value(arguments.value.clone());
+
+        let tx = provider
+            .build_unsigned(&subnet, OfflineOp::Fvm(msg))
+            .await?;
+        tx.write_to_file(&arguments.output)?;
+        print_result(global, &tx, |tx| {
+            format!("{}, written to {}", unsigned_to_text(tx), arguments.output)
+        })
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(about = "Build a message to an actor of an FVM subnet, to be signed offline")]
+pub(crate) struct BuildMessageArgs {
+    #[arg(long, help = "The subnet to send the message to")]
+    pub subnet: String,
+    #[arg(long, help = "The address sending the message")]
+    pub from: String,
+    #[arg(long, help = "The actor the message is sent to")]
+    pub to: String,
+    #[arg(long, default_value = "0", help = "The method of the actor to call")]
+    pub method: MethodNum,
+    #[arg(long, help = "The hex encoded CBOR parameters of the method, if any")]
+    pub params: Option<String>,
+    #[arg(long, default_value = "0", help = "The amount to send (in whole FIL, or with a unit like 1000 nanoFIL)", value_parser = parse_token_amount)]
+    pub value: TokenAmount,
+    #[arg(long, help = "The file to write the unsigned message to")]
+    pub output: String,
+}
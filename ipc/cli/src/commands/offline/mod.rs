@@ -0,0 +1,66 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+use crate::{CommandLineHandler, GlobalArguments};
+
+use clap::{Args, Subcommand};
+use ipc_api::eth_to_fil_amount;
+use ipc_provider::offline::{UnsignedMessage, UnsignedTx};
+
+use self::broadcast::{BroadcastSigned, BroadcastSignedArgs};
+use self::build::{BuildMessage, BuildMessageArgs, BuildTx, BuildTxArgs};
+use self::sign::{SignOffline, SignOfflineArgs};
+
+mod broadcast;
+mod build;
+mod sign;
+
+#[derive(Debug, Args)]
+#[command(
+    name = "offline",
+    about = "build transactions to sign offline, and broadcast them once signed"
+)]
+#[command(args_conflicts_with_subcommands = true)]
+pub(crate) struct OfflineCommandsArgs {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+impl OfflineCommandsArgs {
+    pub async fn handle(&self, global: &GlobalArguments) -> anyhow::Result<()> {
+        match &self.command {
+            Commands::BuildTx(args) => BuildTx::handle(global, args).await,
+            Commands::BuildMessage(args) => BuildMessage::handle(global, args).await,
+            Commands::Sign(args) => SignOffline::handle(global, args).await,
+            Commands::Broadcast(args) => BroadcastSigned::handle(global, args).await,
+        }
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum Commands {
+    BuildTx(BuildTxArgs),
+    BuildMessage(BuildMessageArgs),
+    Sign(SignOfflineArgs),
+    Broadcast(BroadcastSignedArgs),
+}
+
+/// What the transaction does, to be checked before signing it.
+fn unsigned_to_text(tx: &UnsignedTx) -> String {
+    match &tx.message {
+        UnsignedMessage::Evm(evm_tx) => format!(
+            "{} sends {} to {} on {} with nonce {}",
+            tx.from,
+            eth_to_fil_amount(&evm_tx.value().copied().unwrap_or_default()).unwrap_or_default(),
+            evm_tx
+                .to_addr()
+                .map(|to| format!("{to:?}"))
+                .unwrap_or_default(),
+            tx.subnet,
+            evm_tx.nonce().copied().unwrap_or_default(),
+        ),
+        UnsignedMessage::Fvm(msg) => format!(
+            "{} sends {} to {} calling method {} on {} with nonce {}",
+            tx.from, msg.value, msg.to, msg.method_num, tx.subnet, msg.sequence
+        ),
+    }
+}
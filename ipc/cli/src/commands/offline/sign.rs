@@ -0,0 +1,47 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Sign offline cli command handler.
+
+use async_trait::async_trait;
+use clap::Args;
+use ipc_provider::offline::UnsignedTx;
+use std::fmt::Debug;
+
+use super::unsigned_to_text;
+use crate::{get_ipc_provider, print_result, CommandLineHandler, GlobalArguments};
+
+/// The command to sign a transaction built with `offline build-tx` or `offline build-message`.
+pub(crate) struct SignOffline;
+
+#[async_trait]
+impl CommandLineHandler for SignOffline {
+    type Arguments = SignOfflineArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("sign offline with args: {:?}", arguments);
+
+        let provider = get_ipc_provider(global)?;
+        let tx = UnsignedTx::read_from_file(&arguments.input)?;
+        let signed = provider.sign_offline(&tx).await?;
+        signed.write_to_file(&arguments.output)?;
+
+        print_result(global, &signed, |signed| {
+            format!(
+                "signed: {}, written to {}",
+                unsigned_to_text(&signed.tx),
+                arguments.output
+            )
+        })
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(
+    about = "Sign a transaction built to be signed offline, without connecting to its subnet"
+)]
+pub(crate) struct SignOfflineArgs {
+    #[arg(long, help = "The file of the unsigned transaction")]
+    pub input: String,
+    #[arg(long, help = "The file to write the signed transaction to")]
+    pub output: String,
+}
@@ -0,0 +1,42 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Forget submission cli command handler.
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use clap::Args;
+use std::fmt::Debug;
+
+use crate::{get_ipc_provider, CommandLineHandler, GlobalArguments};
+
+/// The command to forget an operation, so that it can be submitted again under the same key.
+pub(crate) struct ForgetSubmission;
+
+#[async_trait]
+impl CommandLineHandler for ForgetSubmission {
+    type Arguments = ForgetSubmissionArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("forget submission with args: {:?}", arguments);
+
+        let provider = get_ipc_provider(global)?;
+        let submission = provider
+            .forget_submission(&arguments.key)?
+            .ok_or_else(|| anyhow!("nothing submitted under key {}", arguments.key))?;
+
+        println!(
+            "forgot {} of {} under key {}",
+            submission.operation, submission.subnet, submission.key
+        );
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(
+    about = "Forget the operation submitted under an idempotency key, after checking whether it went through, so it can be retried"
+)]
+pub(crate) struct ForgetSubmissionArgs {
+    #[arg(help = "The idempotency key")]
+    pub key: String,
+}
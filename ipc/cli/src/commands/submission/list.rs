@@ -0,0 +1,45 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! List submissions cli command handler.
+
+use async_trait::async_trait;
+use clap::Args;
+use ipc_provider::idempotency::SubmissionStatus;
+use std::fmt::Debug;
+
+use super::submission_to_text;
+use crate::{get_ipc_provider, print_result, CommandLineHandler, GlobalArguments};
+
+/// The command to list the operations submitted under idempotency keys.
+pub(crate) struct ListSubmissions;
+
+#[async_trait]
+impl CommandLineHandler for ListSubmissions {
+    type Arguments = ListSubmissionsArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("list submissions with args: {:?}", arguments);
+
+        let provider = get_ipc_provider(global)?;
+        let submissions = provider
+            .list_submissions()?
+            .into_iter()
+            .filter(|s| !arguments.pending || s.status == SubmissionStatus::Pending)
+            .collect::<Vec<_>>();
+
+        print_result(global, &submissions, |submissions| {
+            submissions
+                .iter()
+                .map(submission_to_text)
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(about = "List the operations submitted under idempotency keys")]
+pub(crate) struct ListSubmissionsArgs {
+    #[arg(long, help = "Only list the submissions whose outcome is unknown")]
+    pub pending: bool,
+}
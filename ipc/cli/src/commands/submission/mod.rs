@@ -0,0 +1,59 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+use crate::{CommandLineHandler, GlobalArguments};
+
+use clap::{Args, Subcommand};
+use ipc_provider::idempotency::Submission;
+
+use self::forget::{ForgetSubmission, ForgetSubmissionArgs};
+use self::list::{ListSubmissions, ListSubmissionsArgs};
+use self::show::{ShowSubmission, ShowSubmissionArgs};
+
+mod forget;
+mod list;
+mod show;
+
+#[derive(Debug, Args)]
+#[command(
+    name = "submission",
+    about = "operations submitted under idempotency keys"
+)]
+#[command(args_conflicts_with_subcommands = true)]
+pub(crate) struct SubmissionCommandsArgs {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+impl SubmissionCommandsArgs {
+    pub async fn handle(&self, global: &GlobalArguments) -> anyhow::Result<()> {
+        match &self.command {
+            Commands::Show(args) => ShowSubmission::handle(global, args).await,
+            Commands::List(args) => ListSubmissions::handle(global, args).await,
+            Commands::Forget(args) => ForgetSubmission::handle(global, args).await,
+        }
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum Commands {
+    Show(ShowSubmissionArgs),
+    List(ListSubmissionsArgs),
+    Forget(ForgetSubmissionArgs),
+}
+
+fn submission_to_text(s: &Submission) -> String {
+    let mut text = format!(
+        "{}: {} on {} is {:?} (created at {}, updated at {})",
+        s.key, s.operation, s.subnet, s.status, s.created_at, s.updated_at
+    );
+    for tx_hash in &s.tx_hashes {
+        text.push_str(&format!("\n  transaction: {tx_hash:?}"));
+    }
+    if let Some(error) = &s.error {
+        text.push_str(&format!("\n  error: {error}"));
+    }
+    if let Some(result) = &s.result {
+        text.push_str(&format!("\n  result: {result}"));
+    }
+    text
+}
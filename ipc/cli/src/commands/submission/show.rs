@@ -0,0 +1,37 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Show submission cli command handler.
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use clap::Args;
+use std::fmt::Debug;
+
+use super::submission_to_text;
+use crate::{get_ipc_provider, print_result, CommandLineHandler, GlobalArguments};
+
+/// The command to look up an operation by its idempotency key.
+pub(crate) struct ShowSubmission;
+
+#[async_trait]
+impl CommandLineHandler for ShowSubmission {
+    type Arguments = ShowSubmissionArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("show submission with args: {:?}", arguments);
+
+        let provider = get_ipc_provider(global)?;
+        let submission = provider
+            .submission(&arguments.key)?
+            .ok_or_else(|| anyhow!("nothing submitted under key {}", arguments.key))?;
+
+        print_result(global, &submission, submission_to_text)
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(about = "Show the operation submitted under an idempotency key")]
+pub(crate) struct ShowSubmissionArgs {
+    #[arg(help = "The idempotency key")]
+    pub key: String,
+}
@@ -0,0 +1,205 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Cli command handlers of the administrative operations approved by a multisig.
+
+use async_trait::async_trait;
+use clap::{Args, Subcommand};
+use fvm_shared::address::Address;
+use ipc_api::subnet::{ConsensusType, ConstructParams, SupplySource};
+use ipc_provider::multisig::{AdminOperation, Proposal};
+use ipc_provider::IpcProvider;
+use std::fmt::Debug;
+
+use super::create::DEFAULT_ACTIVE_VALIDATORS;
+use super::{CreateSubnetArgs, KillSubnetArgs, SetFederatedPowerArgs};
+use crate::{
+    get_ipc_provider, print_result, require_fil_addr_from_str, require_subnet_id_from_str,
+    CommandLineHandler, GlobalArguments,
+};
+
+/// The command to propose an administrative operation to the multisig admin.
+pub(crate) struct ProposeAdminOperation;
+
+#[async_trait]
+impl CommandLineHandler for ProposeAdminOperation {
+    type Arguments = ProposeAdminOperationArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("propose admin operation with args: {:?}", arguments);
+
+        let mut provider = get_ipc_provider(global)?;
+        let (from, op) = admin_operation(&provider, &arguments.operation)?;
+
+        let proposal = provider.propose_admin_operation(from, op).await?;
+        print_result(global, &proposal, proposal_to_text)
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(
+    name = "propose",
+    about = "Propose an administrative operation to the multisig administering the subnet"
+)]
+pub(crate) struct ProposeAdminOperationArgs {
+    #[command(subcommand)]
+    pub operation: AdminOperationArgs,
+}
+
+/// The command to approve an administrative operation proposed to the multisig admin.
+pub(crate) struct ApproveAdminOperation;
+
+#[async_trait]
+impl CommandLineHandler for ApproveAdminOperation {
+    type Arguments = ApproveAdminOperationArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("approve admin operation with args: {:?}", arguments);
+
+        let mut provider = get_ipc_provider(global)?;
+        let (from, op) = admin_operation(&provider, &arguments.operation)?;
+
+        let proposal = provider.approve_admin_operation(from, op).await?;
+        print_result(global, &proposal, proposal_to_text)
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(
+    name = "approve",
+    about = "Approve an administrative operation proposed to the multisig administering the subnet, with the same arguments it was proposed with"
+)]
+pub(crate) struct ApproveAdminOperationArgs {
+    #[command(subcommand)]
+    pub operation: AdminOperationArgs,
+}
+
+/// The command to list the proposals pending approval in the multisig admin.
+pub(crate) struct ListPendingProposals;
+
+#[async_trait]
+impl CommandLineHandler for ListPendingProposals {
+    type Arguments = ListPendingProposalsArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("list pending proposals with args: {:?}", arguments);
+
+        let provider = get_ipc_provider(global)?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
+
+        let proposals = provider.pending_admin_proposals(&subnet).await?;
+        print_result(global, &proposals, |proposals| {
+            proposals
+                .iter()
+                .map(proposal_to_text)
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(
+    name = "pending-proposals",
+    about = "List the administrative operations pending approval in the multisig administering a subnet"
+)]
+pub(crate) struct ListPendingProposalsArgs {
+    #[arg(long, help = "The subnet whose multisig admin to query")]
+    pub subnet: String,
+}
+
+/// The administrative operations that can be proposed to a multisig.
+#[derive(Debug, Subcommand)]
+pub(crate) enum AdminOperationArgs {
+    Create(CreateSubnetArgs),
+    Kill(KillSubnetArgs),
+    SetFederatedPower(SetFederatedPowerArgs),
+}
+
+/// Parse the arguments of an operation into the signer approving it and the operation itself.
+fn admin_operation(
+    provider: &IpcProvider,
+    args: &AdminOperationArgs,
+) -> anyhow::Result<(Option<Address>, AdminOperation)> {
+    let from = |from: &Option<String>| match from {
+        Some(address) => require_fil_addr_from_str(address).map(Some),
+        None => Ok(None),
+    };
+
+    match args {
+        AdminOperationArgs::Create(args) => {
+            let parent = require_subnet_id_from_str(&args.parent)?;
+            let conn = provider
+                .connection(&parent)
+                .ok_or_else(|| anyhow::anyhow!("subnet not found: {parent}"))?;
+            let token_address = match &args.supply_source_address {
+                Some(addr) => Some(require_fil_addr_from_str(addr)?),
+                None => None,
+            };
+            let params = ConstructParams {
+                parent,
+                ipc_gateway_addr: conn.subnet().gateway_addr(),
+                consensus: ConsensusType::Fendermint,
+                min_validators: args.min_validators,
+                min_validator_stake: args.min_validator_stake.clone(),
+                bottomup_check_period: args.bottomup_check_period,
+                active_validators_limit: args
+                    .active_validators_limit
+                    .unwrap_or(DEFAULT_ACTIVE_VALIDATORS),
+                min_cross_msg_fee: args.min_cross_msg_fee.clone(),
+                permission_mode: args.permission_mode,
+                supply_source: SupplySource {
+                    kind: args.supply_source_kind,
+                    token_address,
+                },
+                // The multisig would be credited with the balances it pre-funds.
+                genesis_balances: Default::default(),
+            };
+            Ok((from(&args.from)?, AdminOperation::CreateSubnet(params)))
+        }
+        AdminOperationArgs::Kill(args) => Ok((
+            from(&args.from)?,
+            AdminOperation::KillSubnet(require_subnet_id_from_str(&args.subnet)?),
+        )),
+        AdminOperationArgs::SetFederatedPower(args) => {
+            let validators = args
+                .validator_addresses
+                .iter()
+                .map(|address| require_fil_addr_from_str(address))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            let public_keys = args
+                .validator_pubkeys
+                .iter()
+                .map(hex::decode)
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok((
+                Some(require_fil_addr_from_str(&args.from)?),
+                AdminOperation::SetFederatedPower {
+                    subnet: require_subnet_id_from_str(&args.subnet)?,
+                    validators,
+                    public_keys,
+                    federated_power: args.validator_power.clone(),
+                },
+            ))
+        }
+    }
+}
+
+fn proposal_to_text(p: &Proposal) -> String {
+    let status = if p.executed { "executed" } else { "pending" };
+    let mut text = format!(
+        "{}: {status} with {}/{} approvals",
+        p.id,
+        p.approvals.len(),
+        p.threshold
+    );
+    if let Some(to) = &p.to {
+        text.push_str(&format!("\n  to: {to}"));
+    }
+    if let Some(calldata) = &p.calldata {
+        text.push_str(&format!("\n  calldata: {calldata}"));
+    }
+    for approval in &p.approvals {
+        text.push_str(&format!("\n  approved by: {approval}"));
+    }
+    text
+}
@@ -4,10 +4,12 @@
 
 use async_trait::async_trait;
 use clap::Args;
-use ipc_api::subnet_id::SubnetID;
-use std::{fmt::Debug, str::FromStr};
+use std::fmt::Debug;
 
-use crate::{get_ipc_provider, require_fil_addr_from_str, CommandLineHandler, GlobalArguments};
+use crate::{
+    get_ipc_provider, require_fil_addr_from_str, require_subnet_id_from_str, CommandLineHandler,
+    GlobalArguments,
+};
 
 /// The command to add a bootstrap subnet
 pub struct AddBootstrap;
@@ -20,7 +22,7 @@ impl CommandLineHandler for AddBootstrap {
         log::debug!("add subnet bootstrap with args: {:?}", arguments);
 
         let mut provider = get_ipc_provider(global)?;
-        let subnet = SubnetID::from_str(&arguments.subnet)?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
         let from = match &arguments.from {
             Some(address) => Some(require_fil_addr_from_str(address)?),
             None => None,
@@ -54,7 +56,7 @@ impl CommandLineHandler for ListBootstraps {
         log::debug!("add subnet bootstrap with args: {:?}", arguments);
 
         let provider = get_ipc_provider(global)?;
-        let subnet = SubnetID::from_str(&arguments.subnet)?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
 
         print!(
             "{}",
@@ -2,20 +2,25 @@
 // SPDX-License-Identifier: MIT
 //! Create subnet cli command handler.
 
+use std::collections::BTreeMap;
 use std::fmt::Debug;
 use std::str::FromStr;
 
 use async_trait::async_trait;
 use clap::Args;
+use fvm_shared::address::Address;
 use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
 
 use ipc_api::subnet::{PermissionMode, SupplyKind, SupplySource};
-use ipc_api::subnet_id::SubnetID;
 
 use crate::commands::get_ipc_provider;
-use crate::{f64_to_token_amount, require_fil_addr_from_str, CommandLineHandler, GlobalArguments};
+use crate::{
+    parse_token_amount, require_fil_addr_from_str, require_subnet_id_from_str, CommandLineHandler,
+    GlobalArguments,
+};
 
-const DEFAULT_ACTIVE_VALIDATORS: u16 = 100;
+pub(crate) const DEFAULT_ACTIVE_VALIDATORS: u16 = 100;
 
 /// The command to create a new subnet actor.
 pub struct CreateSubnet;
@@ -26,7 +31,7 @@ impl CreateSubnet {
         arguments: &CreateSubnetArgs,
     ) -> anyhow::Result<String> {
         let mut provider = get_ipc_provider(global)?;
-        let parent = SubnetID::from_str(&arguments.parent)?;
+        let parent = require_subnet_id_from_str(&arguments.parent)?;
 
         let from = match &arguments.from {
             Some(address) => Some(require_fil_addr_from_str(address)?),
@@ -42,19 +47,31 @@ impl CreateSubnet {
             kind: arguments.supply_source_kind,
             token_address,
         };
+        let genesis_balances = arguments
+            .genesis_balance
+            .iter()
+            .map(|b| parse_genesis_balance(b))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let mut balances = BTreeMap::new();
+        for (address, amount) in genesis_balances {
+            if balances.insert(address, amount).is_some() {
+                return Err(anyhow::anyhow!("duplicate genesis balance of {address}"));
+            }
+        }
         let addr = provider
             .create_subnet(
                 from,
                 parent,
                 arguments.min_validators,
-                f64_to_token_amount(arguments.min_validator_stake)?,
+                arguments.min_validator_stake.clone(),
                 arguments.bottomup_check_period,
                 arguments
                     .active_validators_limit
                     .unwrap_or(DEFAULT_ACTIVE_VALIDATORS),
-                f64_to_token_amount(arguments.min_cross_msg_fee)?,
+                arguments.min_cross_msg_fee.clone(),
                 arguments.permission_mode,
                 supply_source,
+                balances,
             )
             .await?;
 
@@ -62,6 +79,17 @@ impl CreateSubnet {
     }
 }
 
+/// Parse a genesis balance given as `<ADDRESS>=<AMOUNT>`.
+fn parse_genesis_balance(s: &str) -> anyhow::Result<(Address, TokenAmount)> {
+    let (address, amount) = s.split_once('=').ok_or_else(|| {
+        anyhow::anyhow!("invalid genesis balance {s}: expected <ADDRESS>=<AMOUNT>")
+    })?;
+    Ok((
+        require_fil_addr_from_str(address.trim())?,
+        parse_token_amount(amount.trim())?,
+    ))
+}
+
 #[async_trait]
 impl CommandLineHandler for CreateSubnet {
     type Arguments = CreateSubnetArgs;
@@ -90,9 +118,10 @@ pub struct CreateSubnetArgs {
     pub parent: String,
     #[arg(
         long,
-        help = "The minimum number of collateral required for validators in (in whole FIL; the minimum is 1 nanoFIL)"
+        help = "The minimum number of collateral required for validators in (in whole FIL, or with a unit like 1000 nanoFIL)",
+        value_parser = parse_token_amount,
     )]
-    pub min_validator_stake: f64,
+    pub min_validator_stake: TokenAmount,
     #[arg(
         long,
         help = "Minimum number of validators required to bootstrap the subnet"
@@ -105,9 +134,10 @@ pub struct CreateSubnetArgs {
     #[arg(
         long,
         default_value = "0.000001",
-        help = "Minimum fee for cross-net messages in subnet (in whole FIL; the minimum is 1 nanoFIL)"
+        help = "Minimum fee for cross-net messages in subnet (in whole FIL, or with a unit like 1000 nanoFIL)",
+        value_parser = parse_token_amount,
     )]
-    pub min_cross_msg_fee: f64,
+    pub min_cross_msg_fee: TokenAmount,
     #[arg(
         long,
         help = "The permission mode for the subnet: collateral, federated and static",
@@ -129,4 +159,9 @@ pub struct CreateSubnetArgs {
         help = "The address of supply source of a subnet on its parent subnet. None if kind is native"
     )]
     pub supply_source_address: Option<String>,
+    #[arg(
+        long,
+        help = "A balance in the genesis of the subnet, as <ADDRESS>=<AMOUNT>, pre-funded by the address once the subnet is created. Can be repeated, and the keys of the addresses have to be in the keystore"
+    )]
+    pub genesis_balance: Vec<String>,
 }
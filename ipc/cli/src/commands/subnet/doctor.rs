@@ -0,0 +1,44 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Subnet doctor cli command handler.
+
+use async_trait::async_trait;
+use clap::Args;
+use std::fmt::Debug;
+
+use crate::{
+    get_ipc_provider, print_result, require_subnet_id_from_str, CommandLineHandler, GlobalArguments,
+};
+
+/// The command to diagnose the connection to a subnet.
+pub(crate) struct Doctor;
+
+#[async_trait]
+impl CommandLineHandler for Doctor {
+    type Arguments = DoctorArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("doctor subnet with args: {:?}", arguments);
+
+        let provider = get_ipc_provider(global)?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
+
+        let report = provider.doctor(&subnet).await?;
+        print_result(global, &report, |r| r.to_string().trim_end().to_string())?;
+
+        if !report.is_healthy() {
+            return Err(anyhow::anyhow!("some checks failed for subnet {subnet}"));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(
+    name = "doctor",
+    about = "Check the config and the network of a subnet, with hints to fix any issue"
+)]
+pub(crate) struct DoctorArgs {
+    #[arg(long, help = "The subnet to check")]
+    pub subnet: String,
+}
@@ -4,11 +4,9 @@
 
 use async_trait::async_trait;
 use clap::Args;
-use ipc_api::subnet_id::SubnetID;
 use std::fmt::Debug;
-use std::str::FromStr;
 
-use crate::{get_ipc_provider, CommandLineHandler, GlobalArguments};
+use crate::{get_ipc_provider, require_subnet_id_from_str, CommandLineHandler, GlobalArguments};
 
 /// The command to get the genensis epoch.
 pub(crate) struct GenesisEpoch;
@@ -21,7 +19,7 @@ impl CommandLineHandler for GenesisEpoch {
         log::debug!("get genesis epoch with args: {:?}", arguments);
 
         let provider = get_ipc_provider(global)?;
-        let subnet = SubnetID::from_str(&arguments.subnet)?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
 
         let ls = provider.genesis_epoch(&subnet).await?;
         println!("genesis epoch: {}", ls);
@@ -0,0 +1,143 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Cli command handlers of the subnet parameter changes voted on by the validators.
+
+use async_trait::async_trait;
+use clap::Args;
+use ipc_api::receipt::TxReceipt;
+use ipc_api::subnet::SubnetParam;
+use serde::Serialize;
+use std::fmt::Debug;
+use std::str::FromStr;
+
+use crate::{
+    get_ipc_provider, print_result, require_fil_addr_from_str, require_subnet_id_from_str,
+    CommandLineHandler, GlobalArguments,
+};
+
+/// The command to propose a change of a subnet parameter to the validators of the subnet.
+pub(crate) struct ProposeParamChange;
+
+#[derive(Serialize)]
+struct ProposedParamChange {
+    id: u64,
+    #[serde(flatten)]
+    receipt: TxReceipt,
+}
+
+#[async_trait]
+impl CommandLineHandler for ProposeParamChange {
+    type Arguments = ProposeParamChangeArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("propose param change with args: {:?}", arguments);
+
+        let mut provider = get_ipc_provider(global)?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
+        let from = match &arguments.from {
+            Some(address) => Some(require_fil_addr_from_str(address)?),
+            None => None,
+        };
+
+        let (id, receipt) = provider
+            .propose_change(subnet, from, arguments.param, arguments.value)
+            .await?;
+
+        print_result(global, &ProposedParamChange { id, receipt }, |p| {
+            format!("proposal {} created at epoch: {}", p.id, p.receipt.epoch)
+        })
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(
+    name = "propose-param-change",
+    about = "Propose to the active validators of a subnet to change one of its parameters"
+)]
+pub(crate) struct ProposeParamChangeArgs {
+    #[arg(long, help = "The address of the proposing validator")]
+    pub from: Option<String>,
+    #[arg(long, help = "The subnet to change the parameter of")]
+    pub subnet: String,
+    #[arg(
+        long,
+        help = "The parameter to change: min_validators, majority_percentage or chain_id",
+        value_parser = SubnetParam::from_str,
+    )]
+    pub param: SubnetParam,
+    #[arg(long, help = "The new value of the parameter")]
+    pub value: u64,
+}
+
+/// The command to vote for a parameter change proposal as a validator of the subnet.
+pub(crate) struct VoteParamChange;
+
+#[async_trait]
+impl CommandLineHandler for VoteParamChange {
+    type Arguments = VoteParamChangeArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("vote param change with args: {:?}", arguments);
+
+        let mut provider = get_ipc_provider(global)?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
+        let from = match &arguments.from {
+            Some(address) => Some(require_fil_addr_from_str(address)?),
+            None => None,
+        };
+
+        let receipt = provider.vote(subnet, from, arguments.id).await?;
+
+        print_result(global, &receipt, |r| {
+            format!("voted for proposal {} at epoch: {}", arguments.id, r.epoch)
+        })
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(
+    name = "vote-param-change",
+    about = "Vote for a parameter change proposal of a subnet as one of its active validators"
+)]
+pub(crate) struct VoteParamChangeArgs {
+    #[arg(long, help = "The address of the voting validator")]
+    pub from: Option<String>,
+    #[arg(long, help = "The subnet the proposal was made to")]
+    pub subnet: String,
+    #[arg(long, help = "The ID of the proposal")]
+    pub id: u64,
+}
+
+/// The command to list the parameter change proposals of a subnet.
+pub(crate) struct ListParamChanges;
+
+#[async_trait]
+impl CommandLineHandler for ListParamChanges {
+    type Arguments = ListParamChangesArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("list param changes with args: {:?}", arguments);
+
+        let provider = get_ipc_provider(global)?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
+
+        let proposals = provider.list_proposals(&subnet).await?;
+        print_result(global, &proposals, |proposals| {
+            proposals
+                .iter()
+                .map(|p| p.to_string())
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(
+    name = "list-param-changes",
+    about = "List the parameter change proposals of a subnet, open and closed"
+)]
+pub(crate) struct ListParamChangesArgs {
+    #[arg(long, help = "The subnet to list the proposals of")]
+    pub subnet: String,
+}
@@ -4,13 +4,13 @@
 
 use async_trait::async_trait;
 use clap::Args;
-use ipc_api::subnet_id::SubnetID;
+use fvm_shared::econ::TokenAmount;
 use num_traits::Zero;
-use std::{fmt::Debug, str::FromStr};
+use std::fmt::Debug;
 
 use crate::{
-    f64_to_token_amount, get_ipc_provider, require_fil_addr_from_str, CommandLineHandler,
-    GlobalArguments,
+    get_ipc_provider, parse_token_amount, print_result, require_fil_addr_from_str,
+    require_subnet_id_from_str, CommandLineHandler, GlobalArguments,
 };
 
 /// The command to join a subnet
@@ -24,23 +24,31 @@ impl CommandLineHandler for JoinSubnet {
         log::debug!("join subnet with args: {:?}", arguments);
 
         let mut provider = get_ipc_provider(global)?;
-        let subnet = SubnetID::from_str(&arguments.subnet)?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
         let from = match &arguments.from {
             Some(address) => Some(require_fil_addr_from_str(address)?),
             None => None,
         };
-        if let Some(initial_balance) = arguments.initial_balance.filter(|x| !x.is_zero()) {
-            log::info!("pre-funding address with {initial_balance}");
-            provider
-                .pre_fund(subnet.clone(), from, f64_to_token_amount(initial_balance)?)
-                .await?;
-        }
-        let epoch = provider
-            .join_subnet(subnet, from, f64_to_token_amount(arguments.collateral)?)
+        let pre_fund = match arguments.initial_balance.clone().filter(|x| !x.is_zero()) {
+            Some(initial_balance) => {
+                log::info!("pre-funding address with {initial_balance}");
+                Some(
+                    provider
+                        .pre_fund(subnet.clone(), from, initial_balance)
+                        .await?,
+                )
+            }
+            None => None,
+        };
+        let join = provider
+            .join_subnet(subnet, from, arguments.collateral.clone())
             .await?;
-        println!("joined at epoch: {epoch}");
 
-        Ok(())
+        let result = serde_json::json!({ "pre_fund": pre_fund, "join": join });
+
+        print_result(global, &result, |_| {
+            format!("joined at epoch: {}", join.epoch)
+        })
     }
 }
 
@@ -53,14 +61,16 @@ pub struct JoinSubnetArgs {
     pub subnet: String,
     #[arg(
         long,
-        help = "The collateral to stake in the subnet (in whole FIL units)"
+        help = "The collateral to stake in the subnet (in whole FIL, or with a unit like 1000 nanoFIL)",
+        value_parser = parse_token_amount,
     )]
-    pub collateral: f64,
+    pub collateral: TokenAmount,
     #[arg(
         long,
-        help = "Optionally add an initial balance to the validator in genesis in the subnet"
+        help = "Optionally add an initial balance to the validator in genesis in the subnet",
+        value_parser = parse_token_amount,
     )]
-    pub initial_balance: Option<f64>,
+    pub initial_balance: Option<TokenAmount>,
 }
 
 /// The command to stake in a subnet from validator
@@ -74,14 +84,18 @@ impl CommandLineHandler for StakeSubnet {
         log::debug!("join subnet with args: {:?}", arguments);
 
         let mut provider = get_ipc_provider(global)?;
-        let subnet = SubnetID::from_str(&arguments.subnet)?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
         let from = match &arguments.from {
             Some(address) => Some(require_fil_addr_from_str(address)?),
             None => None,
         };
-        provider
-            .stake(subnet, from, f64_to_token_amount(arguments.collateral)?)
-            .await
+        let receipt = provider
+            .stake(subnet, from, arguments.collateral.clone())
+            .await?;
+
+        print_result(global, &receipt, |r| {
+            format!("staked at epoch: {}", r.epoch)
+        })
     }
 }
 
@@ -94,9 +108,10 @@ pub struct StakeSubnetArgs {
     pub subnet: String,
     #[arg(
         long,
-        help = "The collateral to stake in the subnet (in whole FIL units)"
+        help = "The collateral to stake in the subnet (in whole FIL, or with a unit like 1000 nanoFIL)",
+        value_parser = parse_token_amount,
     )]
-    pub collateral: f64,
+    pub collateral: TokenAmount,
 }
 
 /// The command to unstake in a subnet from validator
@@ -110,14 +125,18 @@ impl CommandLineHandler for UnstakeSubnet {
         log::debug!("join subnet with args: {:?}", arguments);
 
         let mut provider = get_ipc_provider(global)?;
-        let subnet = SubnetID::from_str(&arguments.subnet)?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
         let from = match &arguments.from {
             Some(address) => Some(require_fil_addr_from_str(address)?),
             None => None,
         };
-        provider
-            .unstake(subnet, from, f64_to_token_amount(arguments.collateral)?)
-            .await
+        let receipt = provider
+            .unstake(subnet, from, arguments.collateral.clone())
+            .await?;
+
+        print_result(global, &receipt, |r| {
+            format!("unstaked at epoch: {}", r.epoch)
+        })
     }
 }
 
@@ -133,7 +152,8 @@ pub struct UnstakeSubnetArgs {
     pub subnet: String,
     #[arg(
         long,
-        help = "The collateral to unstake from the subnet (in whole FIL units)"
+        help = "The collateral to unstake from the subnet (in whole FIL, or with a unit like 1000 nanoFIL)",
+        value_parser = parse_token_amount,
     )]
-    pub collateral: f64,
+    pub collateral: TokenAmount,
 }
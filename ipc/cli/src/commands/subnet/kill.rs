@@ -4,10 +4,12 @@
 
 use async_trait::async_trait;
 use clap::Args;
-use ipc_api::subnet_id::SubnetID;
-use std::{fmt::Debug, str::FromStr};
+use std::fmt::Debug;
 
-use crate::{get_ipc_provider, require_fil_addr_from_str, CommandLineHandler, GlobalArguments};
+use crate::{
+    get_ipc_provider, print_result, require_fil_addr_from_str, require_subnet_id_from_str,
+    CommandLineHandler, GlobalArguments,
+};
 
 /// The command to kill an existing subnet.
 pub struct KillSubnet;
@@ -20,13 +22,17 @@ impl CommandLineHandler for KillSubnet {
         log::debug!("kill subnet with args: {:?}", arguments);
 
         let mut provider = get_ipc_provider(global)?;
-        let subnet = SubnetID::from_str(&arguments.subnet)?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
         let from = match &arguments.from {
             Some(address) => Some(require_fil_addr_from_str(address)?),
             None => None,
         };
 
-        provider.kill_subnet(subnet, from).await
+        let receipt = provider.kill_subnet(subnet, from).await?;
+
+        print_result(global, &receipt, |r| {
+            format!("subnet killed at epoch: {}", r.epoch)
+        })
     }
 }
 
@@ -4,10 +4,12 @@
 
 use async_trait::async_trait;
 use clap::Args;
-use ipc_api::subnet_id::SubnetID;
-use std::{fmt::Debug, str::FromStr};
+use std::fmt::Debug;
 
-use crate::{get_ipc_provider, require_fil_addr_from_str, CommandLineHandler, GlobalArguments};
+use crate::{
+    get_ipc_provider, print_result, require_fil_addr_from_str, require_subnet_id_from_str,
+    CommandLineHandler, GlobalArguments,
+};
 
 /// The command to leave a new subnet.
 pub struct LeaveSubnet;
@@ -20,12 +22,16 @@ impl CommandLineHandler for LeaveSubnet {
         log::debug!("leave subnet with args: {:?}", arguments);
 
         let mut provider = get_ipc_provider(global)?;
-        let subnet = SubnetID::from_str(&arguments.subnet)?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
         let from = match &arguments.from {
             Some(address) => Some(require_fil_addr_from_str(address)?),
             None => None,
         };
-        provider.leave_subnet(subnet, from).await
+        let receipt = provider.leave_subnet(subnet, from).await?;
+
+        print_result(global, &receipt, |r| {
+            format!("left subnet at epoch: {}", r.epoch)
+        })
     }
 }
 
@@ -49,12 +55,16 @@ impl CommandLineHandler for Claim {
         log::debug!("leave subnet with args: {:?}", arguments);
 
         let mut provider = get_ipc_provider(global)?;
-        let subnet = SubnetID::from_str(&arguments.subnet)?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
         let from = match &arguments.from {
             Some(address) => Some(require_fil_addr_from_str(address)?),
             None => None,
         };
-        provider.claim_collateral(subnet, from).await
+        let receipt = provider.claim_collateral(subnet, from).await?;
+
+        print_result(global, &receipt, |r| {
+            format!("collateral claimed at epoch: {}", r.epoch)
+        })
     }
 }
 
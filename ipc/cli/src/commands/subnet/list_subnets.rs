@@ -4,11 +4,12 @@
 
 use async_trait::async_trait;
 use clap::Args;
-use ipc_api::subnet_id::SubnetID;
 use std::fmt::Debug;
-use std::str::FromStr;
 
-use crate::{get_ipc_provider, require_fil_addr_from_str, CommandLineHandler, GlobalArguments};
+use crate::{
+    get_ipc_provider, require_fil_addr_from_str, require_subnet_id_from_str, CommandLineHandler,
+    GlobalArguments,
+};
 
 /// The command to create a new subnet actor.
 pub(crate) struct ListSubnets;
@@ -21,7 +22,7 @@ impl CommandLineHandler for ListSubnets {
         log::debug!("list subnets with args: {:?}", arguments);
 
         let provider = get_ipc_provider(global)?;
-        let subnet = SubnetID::from_str(&arguments.parent)?;
+        let subnet = require_subnet_id_from_str(&arguments.parent)?;
 
         let gateway_addr = match &arguments.gateway_address {
             Some(address) => Some(require_fil_addr_from_str(address)?),
@@ -1,39 +1,65 @@
 // Copyright 2022-2024 Protocol Labs
 // SPDX-License-Identifier: MIT
 
+use crate::commands::subnet::admin::{
+    ApproveAdminOperation, ApproveAdminOperationArgs, ListPendingProposals,
+    ListPendingProposalsArgs, ProposeAdminOperation, ProposeAdminOperationArgs,
+};
 pub use crate::commands::subnet::create::{CreateSubnet, CreateSubnetArgs};
+use crate::commands::subnet::doctor::{Doctor, DoctorArgs};
 use crate::commands::subnet::genesis_epoch::{GenesisEpoch, GenesisEpochArgs};
+use crate::commands::subnet::governance::{
+    ListParamChanges, ListParamChangesArgs, ProposeParamChange, ProposeParamChangeArgs,
+    VoteParamChange, VoteParamChangeArgs,
+};
 pub use crate::commands::subnet::join::{JoinSubnet, JoinSubnetArgs};
 pub use crate::commands::subnet::kill::{KillSubnet, KillSubnetArgs};
 pub use crate::commands::subnet::leave::{LeaveSubnet, LeaveSubnetArgs};
 use crate::commands::subnet::list_subnets::{ListSubnets, ListSubnetsArgs};
+use crate::commands::subnet::predict_address::{PredictSubnetAddress, PredictSubnetAddressArgs};
+use crate::commands::subnet::report_double_sign::{ReportDoubleSign, ReportDoubleSignArgs};
 use crate::commands::subnet::rpc::{RPCSubnet, RPCSubnetArgs};
 use crate::commands::subnet::send_value::{SendValue, SendValueArgs};
 use crate::commands::subnet::set_federated_power::{SetFederatedPower, SetFederatedPowerArgs};
 use crate::commands::subnet::show_gateway_contract_commit_sha::{
     ShowGatewayContractCommitSha, ShowGatewayContractCommitShaArgs,
 };
+use crate::commands::subnet::state_diff::{StateDiff, StateDiffArgs};
+use crate::commands::subnet::status::{SubnetStatus, SubnetStatusArgs};
+use crate::commands::subnet::supply_report::{SupplyReport, SupplyReportArgs};
 use crate::commands::subnet::validator::{ValidatorInfo, ValidatorInfoArgs};
+use crate::commands::subnet::watch_stake::{WatchStake, WatchStakeArgs};
+use crate::commands::subnet::watchtower::{RunWatchtower, WatchtowerArgs};
 use crate::{CommandLineHandler, GlobalArguments};
 use clap::{Args, Subcommand};
 
 use self::bootstrap::{AddBootstrap, AddBootstrapArgs, ListBootstraps, ListBootstrapsArgs};
 use self::join::{StakeSubnet, StakeSubnetArgs, UnstakeSubnet, UnstakeSubnetArgs};
 use self::leave::{Claim, ClaimArgs};
-use self::rpc::{ChainIdSubnet, ChainIdSubnetArgs};
+use self::rpc::{ChainIdSubnet, ChainIdSubnetArgs, ChainParamsSubnet, ChainParamsSubnetArgs};
 
+mod admin;
 pub mod bootstrap;
 pub mod create;
+mod doctor;
 mod genesis_epoch;
+mod governance;
 pub mod join;
 pub mod kill;
 pub mod leave;
 pub mod list_subnets;
+mod predict_address;
+mod report_double_sign;
 pub mod rpc;
 pub mod send_value;
 mod set_federated_power;
 pub mod show_gateway_contract_commit_sha;
+mod state_diff;
+mod status;
+mod supply_report;
 mod validator;
+mod watch_stake;
+mod watchtower;
 
 #[derive(Debug, Args)]
 #[command(
@@ -54,8 +80,10 @@ impl SubnetCommandsArgs {
             Commands::Join(args) => JoinSubnet::handle(global, args).await,
             Commands::Rpc(args) => RPCSubnet::handle(global, args).await,
             Commands::ChainId(args) => ChainIdSubnet::handle(global, args).await,
+            Commands::ChainParams(args) => ChainParamsSubnet::handle(global, args).await,
             Commands::Leave(args) => LeaveSubnet::handle(global, args).await,
             Commands::Kill(args) => KillSubnet::handle(global, args).await,
+            Commands::ReportDoubleSign(args) => ReportDoubleSign::handle(global, args).await,
             Commands::SendValue(args) => SendValue::handle(global, args).await,
             Commands::Stake(args) => StakeSubnet::handle(global, args).await,
             Commands::Unstake(args) => UnstakeSubnet::handle(global, args).await,
@@ -68,6 +96,19 @@ impl SubnetCommandsArgs {
                 ShowGatewayContractCommitSha::handle(global, args).await
             }
             Commands::SetFederatedPower(args) => SetFederatedPower::handle(global, args).await,
+            Commands::Doctor(args) => Doctor::handle(global, args).await,
+            Commands::Status(args) => SubnetStatus::handle(global, args).await,
+            Commands::WatchStake(args) => WatchStake::handle(global, args).await,
+            Commands::Propose(args) => ProposeAdminOperation::handle(global, args).await,
+            Commands::Approve(args) => ApproveAdminOperation::handle(global, args).await,
+            Commands::PendingProposals(args) => ListPendingProposals::handle(global, args).await,
+            Commands::ProposeParamChange(args) => ProposeParamChange::handle(global, args).await,
+            Commands::VoteParamChange(args) => VoteParamChange::handle(global, args).await,
+            Commands::ListParamChanges(args) => ListParamChanges::handle(global, args).await,
+            Commands::StateDiff(args) => StateDiff::handle(global, args).await,
+            Commands::SupplyReport(args) => SupplyReport::handle(global, args).await,
+            Commands::Watchtower(args) => RunWatchtower::handle(global, args).await,
+            Commands::PredictAddress(args) => PredictSubnetAddress::handle(global, args).await,
         }
     }
 }
@@ -79,8 +120,10 @@ pub(crate) enum Commands {
     Join(JoinSubnetArgs),
     Rpc(RPCSubnetArgs),
     ChainId(ChainIdSubnetArgs),
+    ChainParams(ChainParamsSubnetArgs),
     Leave(LeaveSubnetArgs),
     Kill(KillSubnetArgs),
+    ReportDoubleSign(ReportDoubleSignArgs),
     SendValue(SendValueArgs),
     Stake(StakeSubnetArgs),
     Unstake(UnstakeSubnetArgs),
@@ -91,4 +134,17 @@ pub(crate) enum Commands {
     GetValidator(ValidatorInfoArgs),
     ShowGatewayContractCommitSha(ShowGatewayContractCommitShaArgs),
     SetFederatedPower(SetFederatedPowerArgs),
+    Doctor(DoctorArgs),
+    Status(SubnetStatusArgs),
+    WatchStake(WatchStakeArgs),
+    Propose(ProposeAdminOperationArgs),
+    Approve(ApproveAdminOperationArgs),
+    PendingProposals(ListPendingProposalsArgs),
+    ProposeParamChange(ProposeParamChangeArgs),
+    VoteParamChange(VoteParamChangeArgs),
+    ListParamChanges(ListParamChangesArgs),
+    StateDiff(StateDiffArgs),
+    SupplyReport(SupplyReportArgs),
+    Watchtower(WatchtowerArgs),
+    PredictAddress(PredictSubnetAddressArgs),
 }
@@ -0,0 +1,61 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Predict the address of a subnet actor cli command
+
+use async_trait::async_trait;
+use clap::Args;
+use std::fmt::Debug;
+
+use crate::{
+    get_ipc_provider, print_result, require_fil_addr_from_str, require_subnet_id_from_str,
+    CommandLineHandler, GlobalArguments,
+};
+
+/// The command to predict the subnet created next in a parent.
+pub(crate) struct PredictSubnetAddress;
+
+#[async_trait]
+impl CommandLineHandler for PredictSubnetAddress {
+    type Arguments = PredictSubnetAddressArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("predict subnet address with args: {:?}", arguments);
+
+        let provider = get_ipc_provider(global)?;
+        let parent = require_subnet_id_from_str(&arguments.parent)?;
+        let creator = match &arguments.creator {
+            Some(address) => Some(require_fil_addr_from_str(address)?),
+            None => None,
+        };
+
+        let predicted = provider
+            .predict_subnet_address(&parent, creator, arguments.nonce)
+            .await?;
+        print_result(global, &predicted, |p| {
+            format!(
+                "subnet id: {}\nsubnet actor: {:?}\ncreated by {:?} with nonce {}",
+                p.subnet_id, p.address, p.creator, p.nonce
+            )
+        })
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(
+    name = "predict-address",
+    about = "Predict the address and ID of the subnet created next in a parent"
+)]
+pub(crate) struct PredictSubnetAddressArgs {
+    #[arg(long, help = "The parent subnet the subnet is created in")]
+    pub parent: String,
+    #[arg(
+        long,
+        help = "The f410 or 0x address creating the subnet actor, the registry of the parent by default"
+    )]
+    pub creator: Option<String>,
+    #[arg(
+        long,
+        help = "The nonce of the creator, its current one by default, so the prediction holds until another subnet is created"
+    )]
+    pub nonce: Option<u64>,
+}
@@ -0,0 +1,73 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Report double signing validators cli command handler.
+
+use anyhow::Context;
+use async_trait::async_trait;
+use clap::Args;
+use ipc_api::evidence::DoubleSignEvidence;
+use std::fmt::Debug;
+use std::path::PathBuf;
+
+use crate::{
+    get_ipc_provider, print_result, require_fil_addr_from_str, require_subnet_id_from_str,
+    CommandLineHandler, GlobalArguments,
+};
+
+/// The command to report validators who signed conflicting votes to the subnet actor.
+pub struct ReportDoubleSign;
+
+#[async_trait]
+impl CommandLineHandler for ReportDoubleSign {
+    type Arguments = ReportDoubleSignArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("report double sign with args: {:?}", arguments);
+
+        let mut provider = get_ipc_provider(global)?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
+        let from = match &arguments.from {
+            Some(address) => Some(require_fil_addr_from_str(address)?),
+            None => None,
+        };
+
+        let json = std::fs::read_to_string(&arguments.evidence_file)
+            .context("failed to read evidence file")?;
+        let evidence: Vec<DoubleSignEvidence> =
+            serde_json::from_str(&json).context("failed to parse evidence file")?;
+
+        let mut receipts = Vec::with_capacity(evidence.len());
+        for e in evidence {
+            log::debug!("reporting {e}");
+            let receipt = provider
+                .report_double_sign(subnet.clone(), from, e.clone())
+                .await
+                .with_context(|| format!("failed to report {e}"))?;
+            receipts.push(receipt);
+        }
+
+        print_result(global, &receipts, |rs| {
+            rs.iter()
+                .map(|r| format!("double sign reported at epoch: {}", r.epoch))
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(
+    name = "report-double-sign",
+    about = "Report validators who signed conflicting votes in a subnet to its subnet actor"
+)]
+pub struct ReportDoubleSignArgs {
+    #[arg(long, help = "The address that sends the report")]
+    pub from: Option<String>,
+    #[arg(long, help = "The subnet the validators signed the votes in")]
+    pub subnet: String,
+    #[arg(
+        long,
+        help = "JSON file with the evidence, as exported by `fendermint debug ipc export-double-sign-evidence`"
+    )]
+    pub evidence_file: PathBuf,
+}
@@ -4,11 +4,11 @@
 
 use async_trait::async_trait;
 use clap::Args;
-use ipc_api::subnet_id::SubnetID;
 use std::fmt::Debug;
-use std::str::FromStr;
 
-use crate::{get_ipc_provider, CommandLineHandler, GlobalArguments};
+use crate::{
+    get_ipc_provider, print_result, require_subnet_id_from_str, CommandLineHandler, GlobalArguments,
+};
 
 /// The command to get the RPC endpoint for a subnet
 pub struct RPCSubnet;
@@ -21,7 +21,7 @@ impl CommandLineHandler for RPCSubnet {
         log::debug!("get rpc for subnet with args: {:?}", arguments);
 
         let provider = get_ipc_provider(global)?;
-        let subnet = SubnetID::from_str(&arguments.network)?;
+        let subnet = require_subnet_id_from_str(&arguments.network)?;
         let conn = match provider.connection(&subnet) {
             None => return Err(anyhow::anyhow!("target subnet not found")),
             Some(conn) => conn,
@@ -51,7 +51,7 @@ impl CommandLineHandler for ChainIdSubnet {
         log::debug!("get chain-id for subnet with args: {:?}", arguments);
 
         let provider = get_ipc_provider(global)?;
-        let subnet = SubnetID::from_str(&arguments.network)?;
+        let subnet = require_subnet_id_from_str(&arguments.network)?;
         let conn = match provider.connection(&subnet) {
             None => return Err(anyhow::anyhow!("target subnet not found")),
             Some(conn) => conn,
@@ -68,3 +68,31 @@ pub struct ChainIdSubnetArgs {
     #[arg(long, help = "The network to get the Chain ID from")]
     pub network: String,
 }
+
+/// The command to get the parameters transactions to a subnet are timed and priced by
+pub struct ChainParamsSubnet;
+
+#[async_trait]
+impl CommandLineHandler for ChainParamsSubnet {
+    type Arguments = ChainParamsSubnetArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("get chain params for subnet with args: {:?}", arguments);
+
+        let provider = get_ipc_provider(global)?;
+        let subnet = require_subnet_id_from_str(&arguments.network)?;
+
+        let params = provider.chain_params(&subnet).await?;
+        print_result(global, &params, |p| p.to_string())
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(
+    name = "chain-params",
+    about = "Chain ID, block time, gas price floor and base fee of a subnet"
+)]
+pub struct ChainParamsSubnetArgs {
+    #[arg(long, help = "The network to get the parameters of")]
+    pub network: String,
+}
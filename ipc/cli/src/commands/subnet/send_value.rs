@@ -4,12 +4,12 @@
 
 use async_trait::async_trait;
 use clap::Args;
-use ipc_api::subnet_id::SubnetID;
-use std::{fmt::Debug, str::FromStr};
+use fvm_shared::econ::TokenAmount;
+use std::fmt::Debug;
 
 use crate::{
-    f64_to_token_amount, get_ipc_provider, require_fil_addr_from_str, CommandLineHandler,
-    GlobalArguments,
+    get_ipc_provider, parse_token_amount, print_result, require_fil_addr_from_str,
+    require_subnet_id_from_str, CommandLineHandler, GlobalArguments,
 };
 
 pub(crate) struct SendValue;
@@ -22,20 +22,24 @@ impl CommandLineHandler for SendValue {
         log::debug!("send value in subnet with args: {:?}", arguments);
 
         let mut provider = get_ipc_provider(global)?;
-        let subnet = SubnetID::from_str(&arguments.subnet)?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
         let from = match &arguments.from {
             Some(address) => Some(require_fil_addr_from_str(address)?),
             None => None,
         };
 
-        provider
+        let receipt = provider
             .send_value(
                 &subnet,
                 from,
                 require_fil_addr_from_str(&arguments.to)?,
-                f64_to_token_amount(arguments.amount)?,
+                arguments.amount.clone(),
             )
-            .await
+            .await?;
+
+        print_result(global, &receipt, |r| {
+            format!("value sent at epoch: {}", r.epoch)
+        })
     }
 }
 
@@ -48,6 +52,6 @@ pub(crate) struct SendValueArgs {
     pub to: String,
     #[arg(long, help = "The subnet of the addresses")]
     pub subnet: String,
-    #[arg(help = "The amount to send (in whole FIL units)")]
-    pub amount: f64,
+    #[arg(help = "The amount to send (in whole FIL, or with a unit like 1000 nanoFIL)", value_parser = parse_token_amount)]
+    pub amount: TokenAmount,
 }
@@ -2,13 +2,13 @@
 // SPDX-License-Identifier: MIT
 //! Set federated power cli handler
 
-use crate::commands::{get_ipc_provider, require_fil_addr_from_str};
+use crate::commands::{
+    get_ipc_provider, print_result, require_fil_addr_from_str, require_subnet_id_from_str,
+};
 use crate::{CommandLineHandler, GlobalArguments};
 use async_trait::async_trait;
 use clap::Args;
 use fvm_shared::address::Address;
-use ipc_api::subnet_id::SubnetID;
-use std::str::FromStr;
 
 /// The command to set federated power.
 pub struct SetFederatedPower;
@@ -21,7 +21,7 @@ impl CommandLineHandler for crate::commands::subnet::SetFederatedPower {
         log::debug!("set federated power with args: {:?}", arguments);
 
         let provider = get_ipc_provider(global)?;
-        let subnet = SubnetID::from_str(&arguments.subnet)?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
 
         let addresses: Vec<Address> = arguments
             .validator_addresses
@@ -37,7 +37,7 @@ impl CommandLineHandler for crate::commands::subnet::SetFederatedPower {
 
         let from_address = require_fil_addr_from_str(&arguments.from).unwrap();
 
-        let chain_epoch = provider
+        let receipt = provider
             .set_federated_power(
                 &from_address,
                 &subnet,
@@ -46,9 +46,9 @@ impl CommandLineHandler for crate::commands::subnet::SetFederatedPower {
                 &arguments.validator_power,
             )
             .await?;
-        println!("New federated power is set at epoch {chain_epoch}");
-
-        Ok(())
+        print_result(global, &receipt, |r| {
+            format!("New federated power is set at epoch {}", r.epoch)
+        })
     }
 }
 
@@ -3,12 +3,10 @@
 
 use async_trait::async_trait;
 use clap::Args;
-use ipc_api::subnet_id::SubnetID;
 use std::fmt::Debug;
 use std::str::from_utf8;
-use std::str::FromStr;
 
-use crate::{get_ipc_provider, CommandLineHandler, GlobalArguments};
+use crate::{get_ipc_provider, require_subnet_id_from_str, CommandLineHandler, GlobalArguments};
 
 pub(crate) struct ShowGatewayContractCommitSha;
 
@@ -20,7 +18,7 @@ impl CommandLineHandler for ShowGatewayContractCommitSha {
         log::debug!("show contract commit sha with args: {:?}", arguments);
 
         let provider = get_ipc_provider(global)?;
-        let subnet = SubnetID::from_str(&arguments.network)?;
+        let subnet = require_subnet_id_from_str(&arguments.network)?;
 
         let commit_sha = provider.get_commit_sha(&subnet).await?;
         let commit_sha_str = from_utf8(&commit_sha).unwrap();
@@ -0,0 +1,57 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Compare the state of an IPC actor between two epochs cli command
+
+use async_trait::async_trait;
+use clap::Args;
+use fvm_shared::clock::ChainEpoch;
+use ipc_provider::state_diff::StateActor;
+use std::fmt::Debug;
+
+use crate::{
+    get_ipc_provider, print_result, require_subnet_id_from_str, CommandLineHandler, GlobalArguments,
+};
+
+/// The command to compare the state of an IPC actor of a subnet between two epochs.
+pub(crate) struct StateDiff;
+
+#[async_trait]
+impl CommandLineHandler for StateDiff {
+    type Arguments = StateDiffArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("state diff with args: {:?}", arguments);
+
+        let provider = get_ipc_provider(global)?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
+        let actor = match arguments.actor.as_str() {
+            "gateway" => StateActor::Gateway,
+            "machine-learning" => StateActor::MachineLearning,
+            child => StateActor::SubnetActor(require_subnet_id_from_str(child)?),
+        };
+
+        let diff = provider
+            .state_diff(&subnet, actor, arguments.from_epoch, arguments.to_epoch)
+            .await?;
+        print_result(global, &diff, |d| d.to_string())
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(
+    name = "state-diff",
+    about = "Show how the state of an IPC actor of a subnet changed between two epochs"
+)]
+pub(crate) struct StateDiffArgs {
+    #[arg(long, help = "The subnet the actor is deployed in")]
+    pub subnet: String,
+    #[arg(
+        long,
+        help = "The actor to compare: gateway, machine-learning, or the ID of a child subnet for its subnet actor"
+    )]
+    pub actor: String,
+    #[arg(long, help = "The epoch to compare from")]
+    pub from_epoch: ChainEpoch,
+    #[arg(long, help = "The epoch to compare to")]
+    pub to_epoch: ChainEpoch,
+}
@@ -0,0 +1,39 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Subnet status cli command handler.
+
+use async_trait::async_trait;
+use clap::Args;
+use std::fmt::Debug;
+
+use crate::{
+    get_ipc_provider, print_result, require_subnet_id_from_str, CommandLineHandler, GlobalArguments,
+};
+
+/// The command to show where a subnet is in its lifecycle.
+pub(crate) struct SubnetStatus;
+
+#[async_trait]
+impl CommandLineHandler for SubnetStatus {
+    type Arguments = SubnetStatusArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("subnet status with args: {:?}", arguments);
+
+        let provider = get_ipc_provider(global)?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
+
+        let status = provider.subnet_status(&subnet).await?;
+        print_result(global, &status, |s| s.to_string())
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(
+    name = "status",
+    about = "Show the lifecycle state of a subnet: created, bootstrapping, active, unhealthy or killed"
+)]
+pub(crate) struct SubnetStatusArgs {
+    #[arg(long, help = "The subnet to get the status of")]
+    pub subnet: String,
+}
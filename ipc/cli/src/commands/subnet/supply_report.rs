@@ -0,0 +1,47 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Audit the supply of a subnet cli command
+
+use async_trait::async_trait;
+use clap::Args;
+use std::fmt::Debug;
+
+use crate::{
+    get_ipc_provider, print_result, require_subnet_id_from_str, CommandLineHandler, GlobalArguments,
+};
+
+/// The command to account for the supply of a subnet between its parent and itself.
+pub(crate) struct SupplyReport;
+
+#[async_trait]
+impl CommandLineHandler for SupplyReport {
+    type Arguments = SupplyReportArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("supply report with args: {:?}", arguments);
+
+        let provider = get_ipc_provider(global)?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
+
+        let report = provider.supply_report(&subnet).await?;
+        print_result(global, &report, |r| r.to_string())?;
+
+        if !report.is_solvent() {
+            return Err(anyhow::anyhow!(
+                "the supply of {subnet} breaks {} accounting rules",
+                report.violations.len()
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(
+    name = "supply-report",
+    about = "Account for the supply of a subnet between its parent and itself, and flag conservation violations"
+)]
+pub(crate) struct SupplyReportArgs {
+    #[arg(long, help = "The subnet to audit")]
+    pub subnet: String,
+}
@@ -4,12 +4,12 @@
 
 use async_trait::async_trait;
 use clap::Args;
-use fvm_shared::address::Address;
-use ipc_api::subnet_id::SubnetID;
 use std::fmt::Debug;
-use std::str::FromStr;
 
-use crate::{get_ipc_provider, CommandLineHandler, GlobalArguments};
+use crate::{
+    get_ipc_provider, require_fil_addr_from_str, require_subnet_id_from_str, CommandLineHandler,
+    GlobalArguments,
+};
 
 /// The command to get the validator information
 pub(crate) struct ValidatorInfo;
@@ -22,8 +22,8 @@ impl CommandLineHandler for ValidatorInfo {
         log::debug!("get validator info with args: {:?}", arguments);
 
         let provider = get_ipc_provider(global)?;
-        let subnet = SubnetID::from_str(&arguments.subnet)?;
-        let validator = Address::from_str(&arguments.validator)?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
+        let validator = require_fil_addr_from_str(&arguments.validator)?;
 
         let validator_info = provider.get_validator_info(&subnet, &validator).await?;
         println!("{}", validator_info);
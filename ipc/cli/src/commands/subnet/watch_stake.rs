@@ -0,0 +1,123 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Watch stake cli command handler.
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use clap::Args;
+use fvm_shared::address::Address;
+use fvm_shared::econ::TokenAmount;
+use ipc_provider::stake_watcher::{StakeWatcher, StakeWatcherConfig, TopUpConfig};
+use ipc_provider::supervisor::TaskSupervisor;
+use std::fmt::Debug;
+use std::time::Duration;
+
+use crate::{
+    get_ipc_provider, parse_token_amount, require_fil_addr_from_str, require_subnet_id_from_str,
+    CommandLineHandler, GlobalArguments,
+};
+
+/// Seconds given to a top-up in progress to finish when interrupted.
+const SHUTDOWN_GRACE_SECS: u64 = 60;
+
+/// The command to watch the collateral of a validator, and top it up.
+pub(crate) struct WatchStake;
+
+#[async_trait]
+impl CommandLineHandler for WatchStake {
+    type Arguments = WatchStakeArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("watch stake with args: {:?}", arguments);
+
+        let provider = get_ipc_provider(global)?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
+        let validator = match &arguments.validator {
+            Some(address) => require_fil_addr_from_str(address)?,
+            None => {
+                let wallet = provider.evm_wallet()?;
+                let default = wallet.write().unwrap().get_default()?;
+                let default =
+                    default.ok_or_else(|| anyhow!("no validator provided nor default address"))?;
+                Address::try_from(default)?
+            }
+        };
+        let top_up = match &arguments.reserve {
+            Some(reserve) => Some(TopUpConfig {
+                reserve: require_fil_addr_from_str(reserve)?,
+                max_amount: arguments
+                    .max_top_up
+                    .clone()
+                    .unwrap_or_else(|| arguments.threshold.clone()),
+            }),
+            None => None,
+        };
+
+        let watcher = StakeWatcher::new(
+            provider,
+            StakeWatcherConfig {
+                subnet,
+                validator,
+                threshold: arguments.threshold.clone(),
+                top_up,
+            },
+        );
+
+        // Run until interrupted, without cutting a top-up short.
+        let supervisor = TaskSupervisor::new();
+        let watcher = watcher.with_shutdown_signal(supervisor.shutdown_signal());
+        supervisor.spawn(
+            "stake watcher",
+            watcher.run(Duration::from_secs(arguments.interval_sec), move |event| {
+                let level = if event.is_alert() { "ALERT" } else { "info" };
+                println!("[{level}] {validator}: {event}");
+            }),
+        );
+
+        tokio::signal::ctrl_c().await?;
+        log::info!("shutting down the stake watcher");
+        let report = supervisor
+            .shutdown(Duration::from_secs(SHUTDOWN_GRACE_SECS))
+            .await;
+        if report.aborted > 0 {
+            return Err(anyhow!("top-up interrupted by the shutdown"));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(
+    name = "watch-stake",
+    about = "Watch the collateral of a validator, and top it up from a reserve account when it falls below a threshold"
+)]
+pub(crate) struct WatchStakeArgs {
+    #[arg(long, help = "The subnet the validator validates")]
+    pub subnet: String,
+    #[arg(long, help = "The validator to watch, the default address if unset")]
+    pub validator: Option<String>,
+    #[arg(
+        long,
+        help = "The collateral under which the validator is reported (in whole FIL, or with a unit like 1000 nanoFIL)",
+        value_parser = parse_token_amount,
+    )]
+    pub threshold: TokenAmount,
+    #[arg(
+        long,
+        help = "The account to top up the validator from; the validator is only reported if unset"
+    )]
+    pub reserve: Option<String>,
+    #[arg(
+        long,
+        help = "The largest top-up sent at once, the threshold if unset",
+        value_parser = parse_token_amount,
+    )]
+    pub max_top_up: Option<TokenAmount>,
+    #[arg(
+        long,
+        default_value = "60",
+        help = "The number of seconds between checks"
+    )]
+    pub interval_sec: u64,
+}
@@ -0,0 +1,68 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Watchtower cli command handler.
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use clap::Args;
+use ipc_provider::supervisor::TaskSupervisor;
+use ipc_provider::watchtower::{Watchtower, DEFAULT_WATCHTOWER_INTERVAL};
+use std::fmt::Debug;
+use std::time::Duration;
+
+use crate::{get_ipc_provider, CommandLineHandler, GlobalArguments};
+
+/// Seconds given to the checks in progress to finish when interrupted.
+const SHUTDOWN_GRACE_SECS: u64 = 30;
+
+/// The command to check the subnets of the `[watchtower]` config continuously, and push alerts.
+pub(crate) struct RunWatchtower;
+
+#[async_trait]
+impl CommandLineHandler for RunWatchtower {
+    type Arguments = WatchtowerArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("watchtower with args: {:?}", arguments);
+
+        let config = global
+            .config()?
+            .watchtower
+            .ok_or_else(|| anyhow!("no [watchtower] section in the config"))?;
+        if config.subnets.is_empty() {
+            return Err(anyhow!("no subnet to watch in the config"));
+        }
+        let interval = match arguments.interval_sec {
+            Some(secs) => Duration::from_secs(secs),
+            None => config.interval.unwrap_or(DEFAULT_WATCHTOWER_INTERVAL),
+        };
+
+        let provider = get_ipc_provider(global)?;
+        let watchtower = Watchtower::from_config(provider, &config)?;
+
+        let supervisor = TaskSupervisor::new();
+        let watchtower = watchtower.with_shutdown_signal(supervisor.shutdown_signal());
+        supervisor.spawn("watchtower", watchtower.run(interval));
+
+        tokio::signal::ctrl_c().await?;
+        log::info!("shutting down the watchtower");
+        supervisor
+            .shutdown(Duration::from_secs(SHUTDOWN_GRACE_SECS))
+            .await;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(
+    name = "watchtower",
+    about = "Check the health, checkpoint lag and solvency of the subnets in the [watchtower] config continuously, and push alerts to its sinks"
+)]
+pub(crate) struct WatchtowerArgs {
+    #[arg(
+        long,
+        help = "The number of seconds between checks, overriding the config"
+    )]
+    pub interval_sec: Option<u64>,
+}
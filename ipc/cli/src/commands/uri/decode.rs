@@ -0,0 +1,48 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Decode URI cli command handler.
+
+use async_trait::async_trait;
+use clap::Args;
+use ipc_api::uri::{IpcUri, UriAction};
+use serde_json::json;
+use std::fmt::Debug;
+use std::str::FromStr;
+
+use super::uri_to_text;
+use crate::{print_result, CommandLineHandler, GlobalArguments};
+
+/// The command to show what a URI asks for, without doing it.
+pub(crate) struct DecodeUri;
+
+#[async_trait]
+impl CommandLineHandler for DecodeUri {
+    type Arguments = DecodeUriArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("decode uri with args: {:?}", arguments);
+
+        let uri = IpcUri::from_str(&arguments.uri)?;
+        let result = match &uri.action {
+            UriAction::Pay { to, amount } => json!({
+                "action": "pay",
+                "subnet": uri.subnet.to_string(),
+                "to": to.to_string(),
+                "amount": amount.as_ref().map(|a| a.atto().to_string()),
+            }),
+            UriAction::Join { collateral } => json!({
+                "action": "join",
+                "subnet": uri.subnet.to_string(),
+                "collateral": collateral.as_ref().map(|c| c.atto().to_string()),
+            }),
+        };
+        print_result(global, &result, |_| uri_to_text(&uri))
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(about = "Show what an ipc: URI asks for, without doing it")]
+pub(crate) struct DecodeUriArgs {
+    #[arg(help = "The URI to decode")]
+    pub uri: String,
+}
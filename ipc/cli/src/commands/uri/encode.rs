@@ -0,0 +1,71 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Encode URI cli command handlers.
+
+use async_trait::async_trait;
+use clap::Args;
+use fvm_shared::econ::TokenAmount;
+use ipc_api::uri::IpcUri;
+use std::fmt::Debug;
+
+use crate::{
+    parse_token_amount, print_result, require_fil_addr_from_str, require_subnet_id_from_str,
+    CommandLineHandler, GlobalArguments,
+};
+
+/// The command to encode a payment request as a URI.
+pub(crate) struct EncodePay;
+
+#[async_trait]
+impl CommandLineHandler for EncodePay {
+    type Arguments = EncodePayArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("encode payment uri with args: {:?}", arguments);
+
+        let uri = IpcUri::pay(
+            require_subnet_id_from_str(&arguments.subnet)?,
+            require_fil_addr_from_str(&arguments.to)?,
+            arguments.amount.clone(),
+        );
+        print_result(global, &uri.to_string(), |uri| uri.clone())
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(about = "Encode a request to be paid in a subnet as an ipc: URI")]
+pub(crate) struct EncodePayArgs {
+    #[arg(long, help = "The subnet to be paid in")]
+    pub subnet: String,
+    #[arg(long, help = "The address to be paid")]
+    pub to: String,
+    #[arg(long, help = "The amount requested (in whole FIL, or with a unit like 1000 nanoFIL), left to the payer if not set", value_parser = parse_token_amount)]
+    pub amount: Option<TokenAmount>,
+}
+
+/// The command to encode an invitation to join a subnet as a URI.
+pub(crate) struct EncodeJoin;
+
+#[async_trait]
+impl CommandLineHandler for EncodeJoin {
+    type Arguments = EncodeJoinArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("encode join uri with args: {:?}", arguments);
+
+        let uri = IpcUri::join(
+            require_subnet_id_from_str(&arguments.subnet)?,
+            arguments.collateral.clone(),
+        );
+        print_result(global, &uri.to_string(), |uri| uri.clone())
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(about = "Encode an invitation to join a subnet as a validator as an ipc: URI")]
+pub(crate) struct EncodeJoinArgs {
+    #[arg(long, help = "The subnet to join")]
+    pub subnet: String,
+    #[arg(long, help = "The collateral to stake (in whole FIL, or with a unit like 1000 nanoFIL), left to the validator if not set", value_parser = parse_token_amount)]
+    pub collateral: Option<TokenAmount>,
+}
@@ -0,0 +1,62 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+use crate::{CommandLineHandler, GlobalArguments};
+
+use clap::{Args, Subcommand};
+use fvm_shared::econ::TokenAmount;
+use ipc_api::token::{Denomination, TokenAmountExt};
+use ipc_api::uri::{IpcUri, UriAction};
+
+use self::decode::{DecodeUri, DecodeUriArgs};
+use self::encode::{EncodeJoin, EncodeJoinArgs, EncodePay, EncodePayArgs};
+use self::open::{OpenUri, OpenUriArgs};
+
+mod decode;
+mod encode;
+mod open;
+
+#[derive(Debug, Args)]
+#[command(
+    name = "uri",
+    about = "ipc: URIs requesting payments and joins of subnets, to link to or show as QR codes"
+)]
+#[command(args_conflicts_with_subcommands = true)]
+pub(crate) struct UriCommandsArgs {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+impl UriCommandsArgs {
+    pub async fn handle(&self, global: &GlobalArguments) -> anyhow::Result<()> {
+        match &self.command {
+            Commands::EncodePay(args) => EncodePay::handle(global, args).await,
+            Commands::EncodeJoin(args) => EncodeJoin::handle(global, args).await,
+            Commands::Decode(args) => DecodeUri::handle(global, args).await,
+            Commands::Open(args) => OpenUri::handle(global, args).await,
+        }
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum Commands {
+    EncodePay(EncodePayArgs),
+    EncodeJoin(EncodeJoinArgs),
+    Decode(DecodeUriArgs),
+    Open(OpenUriArgs),
+}
+
+/// What the URI asks for, to be checked before opening it.
+fn uri_to_text(uri: &IpcUri) -> String {
+    let fil = |amount: &Option<TokenAmount>| match amount {
+        Some(amount) => amount.to_denominated_string(&Denomination::fil()),
+        None => "an amount of your choice".to_string(),
+    };
+    match &uri.action {
+        UriAction::Pay { to, amount } => {
+            format!("pay {} to {} on {}", fil(amount), to, uri.subnet)
+        }
+        UriAction::Join { collateral } => {
+            format!("join {} staking {}", uri.subnet, fil(collateral))
+        }
+    }
+}
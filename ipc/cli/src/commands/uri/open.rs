@@ -0,0 +1,84 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Open URI cli command handler.
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use clap::Args;
+use fvm_shared::econ::TokenAmount;
+use ipc_api::uri::{IpcUri, UriAction};
+use std::fmt::Debug;
+use std::str::FromStr;
+
+use super::uri_to_text;
+use crate::{
+    get_ipc_provider, parse_token_amount, print_result, require_fil_addr_from_str,
+    CommandLineHandler, GlobalArguments,
+};
+
+/// The command to do what a URI asks for, a payment or a join of a subnet.
+pub(crate) struct OpenUri;
+
+#[async_trait]
+impl CommandLineHandler for OpenUri {
+    type Arguments = OpenUriArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("open uri with args: {:?}", arguments);
+
+        let uri = IpcUri::from_str(&arguments.uri)?;
+        log::info!("opening uri: {}", uri_to_text(&uri));
+
+        let mut provider = get_ipc_provider(global)?;
+        let from = match &arguments.from {
+            Some(address) => Some(require_fil_addr_from_str(address)?),
+            None => None,
+        };
+
+        let receipt = match uri.action {
+            UriAction::Pay { to, amount } => {
+                let amount = requested_amount(amount, &arguments.amount)?;
+                provider.send_value(&uri.subnet, from, to, amount).await?
+            }
+            UriAction::Join { collateral } => {
+                let collateral = requested_amount(collateral, &arguments.amount)?;
+                provider.join_subnet(uri.subnet, from, collateral).await?
+            }
+        };
+
+        print_result(global, &receipt, |r| format!("done at epoch: {}", r.epoch))
+    }
+}
+
+/// The amount the URI requests, or the one given if it leaves it open. Amounts set by the URI
+/// can't be overridden, so that a payment isn't made for another amount than the one asked.
+fn requested_amount(
+    requested: Option<TokenAmount>,
+    given: &Option<TokenAmount>,
+) -> anyhow::Result<TokenAmount> {
+    match (requested, given) {
+        (Some(requested), None) => Ok(requested),
+        (None, Some(given)) => Ok(given.clone()),
+        (Some(requested), Some(given)) if &requested == given => Ok(requested),
+        (Some(requested), Some(_)) => Err(anyhow!(
+            "the URI requests {requested}, which can't be overridden"
+        )),
+        (None, None) => Err(anyhow!(
+            "the URI leaves the amount open, set it with --amount"
+        )),
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(about = "Pay or join a subnet as an ipc: URI asks")]
+pub(crate) struct OpenUriArgs {
+    #[arg(help = "The URI to open")]
+    pub uri: String,
+    #[arg(
+        long,
+        help = "The address paying or joining, the default one if not set"
+    )]
+    pub from: Option<String>,
+    #[arg(long, help = "The amount to pay or stake if the URI leaves it open (in whole FIL, or with a unit like 1000 nanoFIL)", value_parser = parse_token_amount)]
+    pub amount: Option<TokenAmount>,
+}
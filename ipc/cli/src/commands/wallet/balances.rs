@@ -7,11 +7,11 @@ use clap::Args;
 use futures_util::future::join_all;
 use fvm_shared::{address::Address, econ::TokenAmount};
 use ipc_api::ethers_address_to_fil_address;
-use ipc_api::subnet_id::SubnetID;
+use ipc_api::token::TokenAmountExt;
 use ipc_wallet::{EthKeyAddress, EvmKeyStore, WalletType};
 use std::{fmt::Debug, str::FromStr};
 
-use crate::{get_ipc_provider, CommandLineHandler, GlobalArguments};
+use crate::{get_ipc_provider, require_subnet_id_from_str, CommandLineHandler, GlobalArguments};
 
 pub(crate) struct WalletBalances;
 
@@ -25,7 +25,8 @@ impl CommandLineHandler for WalletBalances {
         let provider = get_ipc_provider(global)?;
 
         let wallet_type = WalletType::from_str(&arguments.wallet_type)?;
-        let subnet = SubnetID::from_str(&arguments.subnet)?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
+        let denomination = provider.supply_source_denomination(&subnet).await?;
         let mut errors = Vec::new();
 
         match wallet_type {
@@ -56,7 +57,11 @@ impl CommandLineHandler for WalletBalances {
                         Ok(i) => {
                             let (balance, addr) = i;
                             if addr.to_string() != "default-key" {
-                                println!("{} - Balance: {}", addr, balance);
+                                println!(
+                                    "{} - Balance: {}",
+                                    addr,
+                                    balance.to_denominated_string(&denomination)
+                                );
                             }
                         }
                         Err(e) => {
@@ -96,7 +101,11 @@ impl CommandLineHandler for WalletBalances {
                     .into_iter()
                     .collect::<anyhow::Result<Vec<(TokenAmount, &Address)>>>()?;
                 for (balance, addr) in r {
-                    println!("{:?} - Balance: {}", addr, balance);
+                    println!(
+                        "{:?} - Balance: {}",
+                        addr,
+                        balance.to_denominated_string(&denomination)
+                    );
                 }
             }
         };
@@ -0,0 +1,49 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Faucet cli handler
+
+use async_trait::async_trait;
+use clap::Args;
+use std::fmt::Debug;
+
+use crate::{
+    get_ipc_provider, print_result, require_fil_addr_from_str, require_subnet_id_from_str,
+    CommandLineHandler, GlobalArguments,
+};
+
+pub(crate) struct WalletFaucet;
+
+#[async_trait]
+impl CommandLineHandler for WalletFaucet {
+    type Arguments = WalletFaucetArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("ask the faucet for funds with args: {:?}", arguments);
+
+        let mut provider = get_ipc_provider(global)?;
+        let subnet = require_subnet_id_from_str(&arguments.subnet)?;
+        let from = match &arguments.from {
+            Some(address) => Some(require_fil_addr_from_str(address)?),
+            None => None,
+        };
+
+        let receipt = provider
+            .faucet_drip(&subnet, from, require_fil_addr_from_str(&arguments.to)?)
+            .await?;
+
+        print_result(global, &receipt, |r| {
+            format!("funds dripped at epoch: {}", r.epoch)
+        })
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(about = "Ask the faucet of a devnet subnet for test funds")]
+pub(crate) struct WalletFaucetArgs {
+    #[arg(long, help = "The address sending the request, paying for its gas")]
+    pub from: Option<String>,
+    #[arg(long, help = "The subnet of the faucet")]
+    pub subnet: String,
+    #[arg(help = "The address to send the funds to")]
+    pub to: String,
+}
@@ -10,17 +10,23 @@ use self::default::{
     WalletGetDefault, WalletGetDefaultArgs, WalletSetDefault, WalletSetDefaultArgs,
 };
 use self::export::{WalletExport, WalletExportArgs, WalletPublicKey, WalletPublicKeyArgs};
+use self::faucet::{WalletFaucet, WalletFaucetArgs};
 use self::import::{WalletImport, WalletImportArgs};
 use self::list::{WalletList, WalletListArgs};
 use self::remove::{WalletRemove, WalletRemoveArgs};
+use self::role::{
+    WalletAddRole, WalletListRoles, WalletListRolesArgs, WalletRemoveRole, WalletRoleArgs,
+};
 
 mod balances;
 mod default;
 mod export;
+mod faucet;
 mod import;
 mod list;
 mod new;
 mod remove;
+mod role;
 
 #[derive(Debug, Args)]
 #[command(name = "wallet", about = "wallet related commands")]
@@ -42,6 +48,10 @@ impl WalletCommandsArgs {
             Commands::GetDefault(args) => WalletGetDefault::handle(global, args).await,
             Commands::PubKey(args) => WalletPublicKey::handle(global, args).await,
             Commands::List(args) => WalletList::handle(global, args).await,
+            Commands::Faucet(args) => WalletFaucet::handle(global, args).await,
+            Commands::AddRole(args) => WalletAddRole::handle(global, args).await,
+            Commands::RemoveRole(args) => WalletRemoveRole::handle(global, args).await,
+            Commands::Roles(args) => WalletListRoles::handle(global, args).await,
         }
     }
 }
@@ -57,4 +67,8 @@ pub(crate) enum Commands {
     GetDefault(WalletGetDefaultArgs),
     PubKey(WalletPublicKeyArgs),
     List(WalletListArgs),
+    Faucet(WalletFaucetArgs),
+    AddRole(WalletRoleArgs),
+    RemoveRole(WalletRoleArgs),
+    Roles(WalletListRolesArgs),
 }
@@ -0,0 +1,85 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Wallet key roles cli handlers
+
+use async_trait::async_trait;
+use clap::Args;
+use ipc_provider::roles::KeyRole;
+use std::fmt::Debug;
+
+use crate::{get_ipc_provider, print_result, CommandLineHandler, GlobalArguments};
+
+/// The command to give a role to a key.
+pub(crate) struct WalletAddRole;
+
+#[async_trait]
+impl CommandLineHandler for WalletAddRole {
+    type Arguments = WalletRoleArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("add key role with args: {:?}", arguments);
+
+        let provider = get_ipc_provider(global)?;
+        let addr = provider.resolve_address(&arguments.address)?;
+        provider.assign_key_role(&addr, arguments.role)?;
+        Ok(())
+    }
+}
+
+/// The command to take a role from a key.
+pub(crate) struct WalletRemoveRole;
+
+#[async_trait]
+impl CommandLineHandler for WalletRemoveRole {
+    type Arguments = WalletRoleArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("remove key role with args: {:?}", arguments);
+
+        let provider = get_ipc_provider(global)?;
+        let addr = provider.resolve_address(&arguments.address)?;
+        if !provider.unassign_key_role(&addr, arguments.role)? {
+            log::warn!("{addr} doesn't have the {} role", arguments.role);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(about = "Give a role to a key, or take it from the key")]
+pub(crate) struct WalletRoleArgs {
+    #[arg(long, help = "Address of the key, or its alias")]
+    pub address: String,
+    #[arg(long, help = "The role: validator, relayer, treasury or test")]
+    pub role: KeyRole,
+}
+
+/// The command to list the roles of the keys.
+pub(crate) struct WalletListRoles;
+
+#[async_trait]
+impl CommandLineHandler for WalletListRoles {
+    type Arguments = WalletListRolesArgs;
+
+    async fn handle(global: &GlobalArguments, arguments: &Self::Arguments) -> anyhow::Result<()> {
+        log::debug!("list key roles with args: {:?}", arguments);
+
+        let provider = get_ipc_provider(global)?;
+        let roles = provider.list_key_roles()?;
+
+        print_result(global, &roles, |roles| {
+            roles
+                .iter()
+                .map(|(addr, roles)| {
+                    let roles = roles.iter().map(|r| r.to_string()).collect::<Vec<_>>();
+                    format!("{addr}: {}", roles.join(", "))
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(about = "List the roles of the keys")]
+pub(crate) struct WalletListRolesArgs {}
@@ -39,6 +39,10 @@ pub struct GlobalArguments {
     )]
     config_path: Option<String>,
 
+    /// The profile of the config to use, e.g. `calibration`; the top of the config if unset.
+    #[arg(long, env = "IPC_CLI_PROFILE")]
+    profile: Option<String>,
+
     /// Set the FVM Address Network. It's value affects whether `f` (main) or `t` (test) prefixed addresses are accepted.
     #[arg(long = "network", default_value = "testnet", env = "IPC_NETWORK", value_parser = parse_network)]
     _network: Network,
@@ -46,6 +50,18 @@ pub struct GlobalArguments {
     /// Legacy env var for network
     #[arg(long = "__network", hide = true, env = "NETWORK", value_parser = parse_network)]
     __network: Option<Network>,
+
+    /// Print the results as human readable text, or as JSON for scripts.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text, env = "IPC_CLI_OUTPUT")]
+    output: OutputFormat,
+}
+
+/// The format the commands print their results in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
 }
 
 impl GlobalArguments {
@@ -55,14 +71,27 @@ impl GlobalArguments {
             .unwrap_or_else(ipc_provider::default_config_path)
     }
 
+    /// The config of the profile if one was selected, the whole config otherwise.
     pub fn config(&self) -> Result<Config> {
         let config_path = self.config_path();
-        Config::from_file(config_path)
+        let config = Config::from_file(config_path)?;
+        match &self.profile {
+            Some(name) => config.profile(name),
+            None => Ok(config),
+        }
+    }
+
+    pub fn profile(&self) -> Option<&str> {
+        self.profile.as_deref()
     }
 
     pub fn network(&self) -> Network {
         self.__network.unwrap_or(self._network)
     }
+
+    pub fn output(&self) -> OutputFormat {
+        self.output
+    }
 }
 
 /// Parse the FVM network and set the global value.
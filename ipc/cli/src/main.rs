@@ -1,9 +1,39 @@
 // Copyright 2022-2024 Protocol Labs
 // SPDX-License-Identifier: MIT
 
+use std::io::Write;
+
+use ipc_provider::redact::Redactor;
+
+/// Comma separated regular expressions redacted from the logs, on top of the default ones.
+const LOG_REDACT_ENV: &str = "IPC_LOG_REDACT";
+
 #[tokio::main]
 async fn main() {
-    env_logger::init_from_env(env_logger::Env::new().default_filter_or("info"));
+    let patterns = std::env::var(LOG_REDACT_ENV).unwrap_or_default();
+    let patterns = patterns
+        .split(',')
+        .filter(|p| !p.is_empty())
+        .collect::<Vec<_>>();
+    let redactor = Redactor::with_patterns(&patterns).unwrap_or_else(|e| {
+        eprintln!("invalid {LOG_REDACT_ENV}: {e}");
+        std::process::exit(1);
+    });
+
+    // same layout as the default format, with the secrets scrubbed from the message
+    env_logger::Builder::from_env(env_logger::Env::new().default_filter_or("info"))
+        .format(move |buf, record| {
+            let message = record.args().to_string();
+            writeln!(
+                buf,
+                "[{} {} {}] {}",
+                buf.timestamp(),
+                buf.default_styled_level(record.level()),
+                record.target(),
+                redactor.redact(&message)
+            )
+        })
+        .init();
 
     if let Err(e) = ipc_cli::cli().await {
         log::error!("main process failed: {e:#}");
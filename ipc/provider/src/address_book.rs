@@ -0,0 +1,259 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! A book of human-readable aliases for addresses and subnets.
+//!
+//! Operators give names like `relayer` or `calibration-child` to the `f410`/`0x` addresses
+//! and subnet IDs they use often, and pass the names wherever an address or a subnet is
+//! expected instead of pasting the full strings.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use anyhow::{anyhow, Context};
+use fvm_shared::address::Address;
+use ipc_api::ethers_address_to_fil_address;
+use ipc_api::subnet_id::SubnetID;
+use serde::{Deserialize, Serialize};
+
+/// The name of the file the address book is persisted to, in the keystore directory.
+pub const DEFAULT_ADDRESS_BOOK_NAME: &str = "address_book.json";
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, strum::Display, strum::EnumString,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum EntryKind {
+    Address,
+    Subnet,
+}
+
+/// An alias and the address or subnet it stands for.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    pub alias: String,
+    pub kind: EntryKind,
+    /// The address, as a Filecoin or an `0x` address, or the subnet ID.
+    pub value: String,
+}
+
+/// Persists the aliases to a JSON file.
+#[derive(Debug)]
+pub struct AddressBook {
+    path: PathBuf,
+    entries: Mutex<BTreeMap<String, Entry>>,
+}
+
+impl AddressBook {
+    /// Open the address book persisted at `path`, which is created on the first alias.
+    pub fn new(path: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        let path = path.into();
+        let entries = if path.exists() {
+            let content = std::fs::read_to_string(&path)
+                .with_context(|| format!("cannot read address book from {}", path.display()))?;
+            serde_json::from_str::<Vec<Entry>>(&content)
+                .with_context(|| format!("cannot parse address book in {}", path.display()))?
+                .into_iter()
+                .map(|e| (e.alias.clone(), e))
+                .collect()
+        } else {
+            BTreeMap::new()
+        };
+
+        Ok(Self {
+            path,
+            entries: Mutex::new(entries),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Look up the entry of `alias`.
+    pub fn get(&self, alias: &str) -> Option<Entry> {
+        self.entries.lock().unwrap().get(alias).cloned()
+    }
+
+    /// All the entries, ordered by alias.
+    pub fn list(&self) -> Vec<Entry> {
+        self.entries.lock().unwrap().values().cloned().collect()
+    }
+
+    /// Name `address` as `alias`, replacing what the alias stood for before.
+    pub fn set_address(&self, alias: &str, address: &str) -> anyhow::Result<Entry> {
+        parse_address(address)?;
+        self.insert(alias, EntryKind::Address, address.to_string())
+    }
+
+    /// Name `subnet` as `alias`, replacing what the alias stood for before.
+    pub fn set_subnet(&self, alias: &str, subnet: &SubnetID) -> anyhow::Result<Entry> {
+        self.insert(alias, EntryKind::Subnet, subnet.to_string())
+    }
+
+    /// Remove `alias` from the book.
+    pub fn remove(&self, alias: &str) -> anyhow::Result<Option<Entry>> {
+        let mut entries = self.entries.lock().unwrap();
+        let removed = entries.remove(alias);
+        if removed.is_some() {
+            self.persist(&entries)?;
+        }
+        Ok(removed)
+    }
+
+    /// The address named `alias`, if any.
+    pub fn address(&self, alias: &str) -> anyhow::Result<Option<Address>> {
+        match self.get(alias) {
+            Some(e) if e.kind == EntryKind::Address => parse_address(&e.value).map(Some),
+            Some(e) => Err(anyhow!(
+                "alias {alias} stands for a {}, not an address",
+                e.kind
+            )),
+            None => Ok(None),
+        }
+    }
+
+    /// The subnet named `alias`, if any.
+    pub fn subnet(&self, alias: &str) -> anyhow::Result<Option<SubnetID>> {
+        match self.get(alias) {
+            Some(e) if e.kind == EntryKind::Subnet => Ok(Some(SubnetID::from_str(&e.value)?)),
+            Some(e) => Err(anyhow!(
+                "alias {alias} stands for an {}, not a subnet",
+                e.kind
+            )),
+            None => Ok(None),
+        }
+    }
+
+    /// Parse `s` as an alias of an address, or as an address itself.
+    pub fn resolve_address(&self, s: &str) -> anyhow::Result<Address> {
+        match self.address(s)? {
+            Some(address) => Ok(address),
+            None => parse_address(s).map_err(|_| anyhow!("{s} is neither an alias nor an address")),
+        }
+    }
+
+    /// Parse `s` as an alias of a subnet, or as a subnet ID itself.
+    pub fn resolve_subnet(&self, s: &str) -> anyhow::Result<SubnetID> {
+        match self.subnet(s)? {
+            Some(subnet) => Ok(subnet),
+            None => SubnetID::from_str(s)
+                .map_err(|e| anyhow!("{s} is neither an alias nor a subnet: {e}")),
+        }
+    }
+
+    fn insert(&self, alias: &str, kind: EntryKind, value: String) -> anyhow::Result<Entry> {
+        validate_alias(alias)?;
+
+        let entry = Entry {
+            alias: alias.to_string(),
+            kind,
+            value,
+        };
+        let mut entries = self.entries.lock().unwrap();
+        entries.insert(entry.alias.clone(), entry.clone());
+        self.persist(&entries)?;
+        Ok(entry)
+    }
+
+    /// Write the entries to a temporary file first, so a crash can't leave it truncated.
+    fn persist(&self, entries: &BTreeMap<String, Entry>) -> anyhow::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let content = serde_json::to_string_pretty(&entries.values().collect::<Vec<_>>())?;
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, content)?;
+        std::fs::rename(&tmp, &self.path)
+            .with_context(|| format!("cannot persist address book to {}", self.path.display()))
+    }
+}
+
+/// Parse a Filecoin address, or an `0x` address as its delegated Filecoin address.
+///
+/// It's the parser of addresses given to the CLI and to the APIs of the provider.
+pub fn parse_address(s: &str) -> anyhow::Result<Address> {
+    match Address::from_str(s) {
+        Ok(addr) => Ok(addr),
+        Err(_) => {
+            let addr = ethers::types::Address::from_str(s)
+                .map_err(|_| anyhow!("{s} is not a Filecoin or Ethereum address"))?;
+            ethers_address_to_fil_address(&addr)
+        }
+    }
+}
+
+/// Aliases are names, so that they can't be mistaken for the addresses and subnets they stand for.
+fn validate_alias(alias: &str) -> anyhow::Result<()> {
+    let is_name = alias
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic())
+        && alias
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.');
+    if !is_name {
+        return Err(anyhow!(
+            "invalid alias {alias}: use letters, digits, '-', '_' and '.', starting with a letter"
+        ));
+    }
+    if parse_address(alias).is_ok() {
+        return Err(anyhow!("invalid alias {alias}: it is an address"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use fvm_shared::address::Address;
+    use ipc_api::subnet_id::SubnetID;
+
+    use super::{AddressBook, EntryKind};
+
+    const ETH_ADDRESS: &str = "0x6be1ccf648c74800380d0520d797a170c808b624";
+
+    #[test]
+    fn aliases_are_persisted() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("address_book.json");
+        let subnet = SubnetID::from_str("/r123/f0100").unwrap();
+
+        let book = AddressBook::new(&path).unwrap();
+        book.set_address("relayer", ETH_ADDRESS).unwrap();
+        book.set_subnet("child", &subnet).unwrap();
+
+        // Reopen as if the process restarted.
+        let book = AddressBook::new(&path).unwrap();
+        assert_eq!(book.list().len(), 2);
+        assert_eq!(book.get("relayer").unwrap().kind, EntryKind::Address);
+        assert_eq!(book.resolve_subnet("child").unwrap(), subnet);
+        assert_eq!(
+            book.resolve_address("relayer").unwrap(),
+            book.resolve_address(ETH_ADDRESS).unwrap()
+        );
+
+        // Aliases of subnets are not addresses.
+        assert!(book.resolve_address("child").is_err());
+
+        assert!(book.remove("relayer").unwrap().is_some());
+        assert!(book.resolve_address("relayer").is_err());
+    }
+
+    #[test]
+    fn addresses_are_not_aliases() {
+        let dir = tempfile::tempdir().unwrap();
+        let book = AddressBook::new(dir.path().join("address_book.json")).unwrap();
+
+        assert!(book.set_address(ETH_ADDRESS, ETH_ADDRESS).is_err());
+        assert!(book
+            .set_address(&Address::new_id(100).to_string(), ETH_ADDRESS)
+            .is_err());
+        assert!(book.set_address("-relayer", ETH_ADDRESS).is_err());
+        assert!(book.set_address("relayer", "not-an-address").is_err());
+        assert!(book.list().is_empty());
+    }
+}
@@ -0,0 +1,428 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! An append-only audit log of the signatures made with the keys of the keystores.
+//!
+//! Every signature is written as a line of JSON before it is handed back to be broadcast, so
+//! the log shows everything the keys approved, including transactions that never made it
+//! on-chain. A signature that can't be recorded is refused.
+
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Context;
+use async_trait::async_trait;
+use ethers::signers::{LocalWallet, Signer, WalletError};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::transaction::eip712::Eip712;
+use ethers::types::{NameOrAddress, Signature, H256};
+use ethers::utils::hash_message;
+use fvm_shared::address::Address;
+use ipc_api::ethers_address_to_fil_address;
+use ipc_api::subnet_id::SubnetID;
+use serde::{Deserialize, Serialize};
+
+use crate::idempotency;
+
+/// The name of the file the audit log is appended to, in the keystore directory.
+pub const DEFAULT_AUDIT_LOG_NAME: &str = "audit.jsonl";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, strum::Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum SigningOperation {
+    /// An EVM transaction.
+    Transaction,
+    /// An EIP-191 personal message.
+    Message,
+    /// EIP-712 typed data.
+    TypedData,
+    /// A Filecoin message.
+    FilecoinMessage,
+}
+
+/// A signature made with one of the keys.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// Seconds since the unix epoch.
+    pub timestamp: u64,
+    /// The Filecoin address of the key that signed.
+    pub key: String,
+    pub operation: SigningOperation,
+    /// The hash of what was signed, or the CID of a Filecoin message.
+    pub digest: String,
+    /// The subnet the signature was made for.
+    pub subnet: String,
+    /// The recipient of the transaction or message, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<String>,
+}
+
+/// Filters the records of the audit log; unset fields match every record.
+#[derive(Debug, Clone, Default)]
+pub struct AuditQuery {
+    pub key: Option<Address>,
+    pub subnet: Option<SubnetID>,
+    /// The first second of the records, since the unix epoch.
+    pub since: Option<u64>,
+    /// The last second of the records, since the unix epoch.
+    pub until: Option<u64>,
+    /// Only return the latest records, up to this many.
+    pub limit: Option<usize>,
+}
+
+impl AuditQuery {
+    fn matches(&self, record: &AuditRecord) -> bool {
+        // The network prefix of the key depends on the process that wrote the record.
+        let same_key = |key: &Address| record.key.get(1..) == key.to_string().get(1..);
+
+        self.key.as_ref().map_or(true, same_key)
+            && self
+                .subnet
+                .as_ref()
+                .map_or(true, |s| record.subnet == s.to_string())
+            && self.since.map_or(true, |t| record.timestamp >= t)
+            && self.until.map_or(true, |t| record.timestamp <= t)
+    }
+}
+
+/// Appends the signatures to a JSON lines file.
+#[derive(Debug)]
+pub struct AuditLog {
+    path: PathBuf,
+    /// Serializes the appends of the process, so the lines aren't interleaved.
+    lock: Mutex<()>,
+}
+
+impl AuditLog {
+    /// Open the audit log at `path`, which is created on the first signature.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Record a signature made now by `key` for `subnet`.
+    pub fn record(
+        &self,
+        key: &Address,
+        operation: SigningOperation,
+        digest: String,
+        subnet: &SubnetID,
+        to: Option<String>,
+    ) -> anyhow::Result<AuditRecord> {
+        let record = AuditRecord {
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+            key: key.to_string(),
+            operation,
+            digest,
+            subnet: subnet.to_string(),
+            to,
+        };
+        self.append(&record)?;
+        Ok(record)
+    }
+
+    /// Append `record` to the end of the log.
+    pub fn append(&self, record: &AuditRecord) -> anyhow::Result<()> {
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+
+        let _guard = self.lock.lock().unwrap();
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("cannot open audit log {}", self.path.display()))?;
+        // A single write, so that the appends of other processes don't split the line.
+        file.write_all(line.as_bytes())?;
+        file.sync_data()
+            .with_context(|| format!("cannot write to audit log {}", self.path.display()))
+    }
+
+    /// The records matching `query`, oldest first.
+    pub fn query(&self, query: &AuditQuery) -> anyhow::Result<Vec<AuditRecord>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let file = std::fs::File::open(&self.path)
+            .with_context(|| format!("cannot open audit log {}", self.path.display()))?;
+
+        let mut records = Vec::new();
+        for (i, line) in BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record = serde_json::from_str::<AuditRecord>(&line).with_context(|| {
+                format!(
+                    "cannot parse line {} of audit log {}",
+                    i + 1,
+                    self.path.display()
+                )
+            })?;
+            if query.matches(&record) {
+                records.push(record);
+            }
+        }
+
+        if let Some(limit) = query.limit {
+            records.drain(..records.len().saturating_sub(limit));
+        }
+        Ok(records)
+    }
+}
+
+/// Where the signatures of a signer are recorded: the log, and the subnet it signs for.
+#[derive(Debug, Clone)]
+pub struct SigningAudit {
+    pub log: Arc<AuditLog>,
+    pub subnet: SubnetID,
+}
+
+impl SigningAudit {
+    pub fn new(log: Arc<AuditLog>, subnet: SubnetID) -> Self {
+        Self { log, subnet }
+    }
+
+    pub fn record(
+        &self,
+        key: &Address,
+        operation: SigningOperation,
+        digest: String,
+        to: Option<String>,
+    ) -> anyhow::Result<AuditRecord> {
+        self.log.record(key, operation, digest, &self.subnet, to)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AuditedWalletError {
+    #[error(transparent)]
+    Wallet(#[from] WalletError),
+    #[error("cannot encode typed data: {0}")]
+    Eip712(String),
+    #[error("refusing to sign without recording it in the audit log: {0}")]
+    Audit(String),
+    #[error("refusing to sign for chain ID {found}, the chain ID of the subnet is {expected}")]
+    ChainIdMismatch { expected: u64, found: u64 },
+    #[error("refusing to sign without recording it with its idempotency key: {0}")]
+    Idempotency(String),
+}
+
+/// A local wallet recording its signatures in the audit log, if there is one.
+///
+/// The wallet is bound to the chain ID of the subnet it signs for, and refuses transactions
+/// and typed data for any other chain, so that a key shared by several networks can't be made
+/// to sign something replayable on another one.
+#[derive(Debug, Clone)]
+pub struct AuditedWallet {
+    wallet: LocalWallet,
+    audit: Option<SigningAudit>,
+}
+
+impl AuditedWallet {
+    pub fn new(wallet: LocalWallet, audit: Option<SigningAudit>) -> Self {
+        Self { wallet, audit }
+    }
+
+    fn record(
+        &self,
+        operation: SigningOperation,
+        digest: H256,
+        to: Option<String>,
+    ) -> Result<(), AuditedWalletError> {
+        let Some(audit) = &self.audit else {
+            return Ok(());
+        };
+        ethers_address_to_fil_address(&self.wallet.address())
+            .and_then(|key| audit.record(&key, operation, format!("{digest:?}"), to))
+            .map(|_| ())
+            .map_err(|e| AuditedWalletError::Audit(e.to_string()))
+    }
+
+    /// Refuse to sign for another chain than the one of the wallet.
+    fn check_chain_id(&self, found: u64) -> Result<(), AuditedWalletError> {
+        let expected = self.wallet.chain_id();
+        if found != expected {
+            return Err(AuditedWalletError::ChainIdMismatch { expected, found });
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Signer for AuditedWallet {
+    type Error = AuditedWalletError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(
+        &self,
+        message: S,
+    ) -> Result<Signature, Self::Error> {
+        let digest = hash_message(message.as_ref());
+        let signature = self.wallet.sign_message(message).await?;
+        self.record(SigningOperation::Message, digest, None)?;
+        Ok(signature)
+    }
+
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature, Self::Error> {
+        // The wallet signs for its own chain if the transaction doesn't have one.
+        let mut tx = tx.clone();
+        match tx.chain_id() {
+            Some(chain_id) => self.check_chain_id(chain_id.as_u64())?,
+            None => {
+                tx.set_chain_id(self.wallet.chain_id());
+            }
+        }
+        let signature = self.wallet.sign_transaction(&tx).await?;
+
+        let to = tx.to().map(|to| match to {
+            NameOrAddress::Address(address) => format!("{address:?}"),
+            NameOrAddress::Name(name) => name.clone(),
+        });
+        self.record(SigningOperation::Transaction, tx.sighash(), to)?;
+
+        // Recorded before the signed transaction can be broadcast, so that a retry of the
+        // operation can find out whether it went through.
+        idempotency::record_tx(tx.hash(&signature))
+            .map_err(|e| AuditedWalletError::Idempotency(e.to_string()))?;
+        Ok(signature)
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        payload: &T,
+    ) -> Result<Signature, Self::Error> {
+        let domain = payload
+            .domain()
+            .map_err(|e| AuditedWalletError::Eip712(e.to_string()))?;
+        if let Some(chain_id) = domain.chain_id {
+            self.check_chain_id(chain_id.as_u64())?;
+        }
+        let digest = payload
+            .encode_eip712()
+            .map_err(|e| AuditedWalletError::Eip712(e.to_string()))?;
+        let signature = self.wallet.sign_typed_data(payload).await?;
+        self.record(SigningOperation::TypedData, H256::from(digest), None)?;
+        Ok(signature)
+    }
+
+    fn address(&self) -> ethers::types::Address {
+        self.wallet.address()
+    }
+
+    fn chain_id(&self) -> u64 {
+        self.wallet.chain_id()
+    }
+
+    fn with_chain_id<T: Into<u64>>(mut self, chain_id: T) -> Self {
+        self.wallet = self.wallet.with_chain_id(chain_id);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use std::sync::Arc;
+
+    use ethers::signers::{LocalWallet, Signer};
+    use ethers::types::transaction::eip2718::TypedTransaction;
+    use ethers::types::TransactionRequest;
+    use ipc_api::ethers_address_to_fil_address;
+    use ipc_api::subnet_id::SubnetID;
+
+    use super::{
+        AuditLog, AuditQuery, AuditedWallet, AuditedWalletError, SigningAudit, SigningOperation,
+    };
+
+    const PRIVATE_KEY: &str = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+
+    #[tokio::test]
+    async fn signatures_are_recorded() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = Arc::new(AuditLog::new(dir.path().join("audit.jsonl")));
+        let subnet = SubnetID::from_str("/r123/f0100").unwrap();
+
+        let wallet = LocalWallet::from_str(PRIVATE_KEY)
+            .unwrap()
+            .with_chain_id(123u64);
+        let key = ethers_address_to_fil_address(&wallet.address()).unwrap();
+        let wallet = AuditedWallet::new(wallet, Some(SigningAudit::new(log.clone(), subnet)));
+
+        let tx: TypedTransaction = TransactionRequest::new()
+            .to(ethers::types::Address::zero())
+            .value(1)
+            .chain_id(123u64)
+            .into();
+        wallet.sign_transaction(&tx).await.unwrap();
+        wallet.sign_message("hello").await.unwrap();
+
+        let records = log.query(&AuditQuery::default()).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].operation, SigningOperation::Transaction);
+        assert_eq!(records[0].digest, format!("{:?}", tx.sighash()));
+        assert_eq!(
+            records[0].to,
+            Some(format!("{:?}", ethers::types::Address::zero()))
+        );
+        assert_eq!(records[1].operation, SigningOperation::Message);
+
+        let query = AuditQuery {
+            key: Some(key),
+            limit: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(log.query(&query).unwrap(), records[1..]);
+
+        let query = AuditQuery {
+            subnet: Some(SubnetID::from_str("/r123/f0101").unwrap()),
+            ..Default::default()
+        };
+        assert!(log.query(&query).unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn signatures_for_other_chains_are_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = Arc::new(AuditLog::new(dir.path().join("audit.jsonl")));
+        let subnet = SubnetID::from_str("/r123/f0100").unwrap();
+
+        let wallet = LocalWallet::from_str(PRIVATE_KEY)
+            .unwrap()
+            .with_chain_id(123u64);
+        let wallet = AuditedWallet::new(wallet, Some(SigningAudit::new(log.clone(), subnet)));
+
+        let tx: TypedTransaction = TransactionRequest::new()
+            .to(ethers::types::Address::zero())
+            .value(1)
+            .chain_id(124u64)
+            .into();
+        let err = wallet.sign_transaction(&tx).await.unwrap_err();
+        assert!(matches!(
+            err,
+            AuditedWalletError::ChainIdMismatch {
+                expected: 123,
+                found: 124
+            }
+        ));
+        assert!(log.query(&AuditQuery::default()).unwrap().is_empty());
+
+        // Transactions without a chain ID are bound to the one of the wallet.
+        let tx: TypedTransaction = TransactionRequest::new()
+            .to(ethers::types::Address::zero())
+            .value(1)
+            .into();
+        wallet.sign_transaction(&tx).await.unwrap();
+        assert_eq!(log.query(&AuditQuery::default()).unwrap().len(), 1);
+    }
+}
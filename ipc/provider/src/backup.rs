@@ -0,0 +1,264 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Encrypted backups of the config and the keystore of a provider.
+//!
+//! A backup bundles the config with the keystores, the address book and the roles of the keys
+//! found in its keystore directory and in those of its profiles, encrypted with a passphrase,
+//! so that a validator can be moved to another machine or recovered after losing one with a
+//! single file.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+use crate::address_book::DEFAULT_ADDRESS_BOOK_NAME;
+use crate::config::profile::DEFAULT_PROFILES_DIR;
+use crate::config::Config;
+use crate::expand_tilde;
+use crate::roles::DEFAULT_KEY_ROLES_NAME;
+
+/// Prepended to the encrypted bundle, to recognize backups and their version.
+const BACKUP_MAGIC: &[u8] = b"IPCBACKUP1";
+
+/// The files of a keystore directory which are backed up, if they exist.
+pub const BACKUP_FILES: &[&str] = &[
+    ipc_wallet::KEYSTORE_NAME,
+    ipc_wallet::ENCRYPTED_KEYSTORE_NAME,
+    ipc_wallet::DEFAULT_KEYSTORE_NAME,
+    DEFAULT_ADDRESS_BOOK_NAME,
+    DEFAULT_KEY_ROLES_NAME,
+];
+
+/// The config and the files of its keystore directories.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Backup {
+    /// The config, as TOML.
+    pub config: String,
+    /// The contents of the backed up files, base64 encoded. The files of the keystore of the
+    /// config are keyed by their name, those of a profile by `profiles/<profile>/<name>`.
+    pub files: BTreeMap<String, String>,
+}
+
+impl Backup {
+    /// Collect the config and the files of its keystore directory and of those of its profiles,
+    /// wherever they are.
+    pub fn collect(config: &Config) -> anyhow::Result<Self> {
+        let mut files = BTreeMap::new();
+        collect_files(&keystore_dir(config)?, "", &mut files)?;
+        for profile in config.profiles.keys() {
+            if let Some(dir) = profile_keystore_dir(config, profile)? {
+                let prefix = format!("{DEFAULT_PROFILES_DIR}/{profile}/");
+                collect_files(&dir, &prefix, &mut files)?;
+            }
+        }
+        Ok(Self {
+            config: toml::to_string(config)?,
+            files,
+        })
+    }
+
+    /// The config of the backup.
+    pub fn config(&self) -> anyhow::Result<Config> {
+        Config::from_toml_str(&self.config).context("cannot parse the config of the backup")
+    }
+
+    /// Encrypt the backup with the passphrase.
+    pub fn seal(&self, passphrase: &str) -> anyhow::Result<Vec<u8>> {
+        let mut data = BACKUP_MAGIC.to_vec();
+        data.extend(ipc_wallet::encrypt_with_passphrase(
+            passphrase,
+            &serde_json::to_vec(self)?,
+        )?);
+        Ok(data)
+    }
+
+    /// Decrypt a backup encrypted with [Backup::seal].
+    pub fn open(data: &[u8], passphrase: &str) -> anyhow::Result<Self> {
+        let data = data
+            .strip_prefix(BACKUP_MAGIC)
+            .ok_or_else(|| anyhow!("not an IPC backup"))?;
+        let data = ipc_wallet::decrypt_with_passphrase(passphrase, data)?;
+        serde_json::from_slice(&data).context("cannot parse the backup")
+    }
+
+    /// Write the files of the backup to the keystore directories of its config and profiles.
+    /// Files which exist with other contents are not overwritten, so that no key is lost by
+    /// restoring a backup over another keystore.
+    pub fn restore_files(&self) -> anyhow::Result<()> {
+        let config = self.config()?;
+        let mut files = Vec::new();
+        for (key, content) in self.files.iter() {
+            let (dir, name) = match key.strip_prefix(&format!("{DEFAULT_PROFILES_DIR}/")) {
+                Some(rest) => {
+                    let (profile, name) = rest
+                        .rsplit_once('/')
+                        .ok_or_else(|| anyhow!("unexpected file in the backup: {key}"))?;
+                    let dir = profile_keystore_dir(&config, profile)?
+                        .ok_or_else(|| anyhow!("profile {profile} has no keystore"))?;
+                    (dir, name)
+                }
+                None => (keystore_dir(&config)?, key.as_str()),
+            };
+            if !BACKUP_FILES.contains(&name) {
+                return Err(anyhow!("unexpected file in the backup: {key}"));
+            }
+            let content = base64::engine::general_purpose::STANDARD.decode(content)?;
+            let path = dir.join(name);
+            if path.exists() && std::fs::read(&path)? != content {
+                return Err(anyhow!(
+                    "{} already exists, refusing to overwrite it",
+                    path.display()
+                ));
+            }
+            files.push((dir, path, content));
+        }
+
+        for (dir, path, content) in files {
+            std::fs::create_dir_all(&dir)
+                .with_context(|| format!("cannot create keystore directory {}", dir.display()))?;
+            std::fs::write(&path, content)
+                .with_context(|| format!("cannot write {}", path.display()))?;
+        }
+        Ok(())
+    }
+}
+
+/// Add the backed up files of a keystore directory, keyed by their name after `prefix`.
+fn collect_files(
+    dir: &Path,
+    prefix: &str,
+    files: &mut BTreeMap<String, String>,
+) -> anyhow::Result<()> {
+    for name in BACKUP_FILES {
+        let path = dir.join(name);
+        if !path.exists() {
+            continue;
+        }
+        let content =
+            std::fs::read(&path).with_context(|| format!("cannot read {}", path.display()))?;
+        files.insert(
+            format!("{prefix}{name}"),
+            base64::engine::general_purpose::STANDARD.encode(content),
+        );
+    }
+    Ok(())
+}
+
+fn keystore_dir(config: &Config) -> anyhow::Result<PathBuf> {
+    let path = config
+        .keystore_path
+        .as_ref()
+        .ok_or_else(|| anyhow!("No keystore repo found in config"))?;
+    Ok(expand_tilde(Path::new(path)))
+}
+
+/// The keystore directory of a profile, if it has one.
+fn profile_keystore_dir(config: &Config, profile: &str) -> anyhow::Result<Option<PathBuf>> {
+    let path = config.profile(profile)?.keystore_path;
+    Ok(path.map(|path| expand_tilde(Path::new(&path))))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::profile::Profile;
+    use crate::config::Config;
+
+    use super::Backup;
+
+    #[test]
+    fn backup_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let keystore = dir.path().join("keystore");
+        std::fs::create_dir(&keystore).unwrap();
+        std::fs::write(keystore.join("evm_keystore.json"), b"[]").unwrap();
+        std::fs::write(keystore.join("address_book.json"), b"[]").unwrap();
+        std::fs::write(keystore.join("unrelated.txt"), b"skipped").unwrap();
+
+        let mut config = Config::new();
+        config.keystore_path = Some(keystore.to_string_lossy().into_owned());
+        let backup = Backup::collect(&config).unwrap();
+        assert_eq!(backup.files.len(), 2);
+
+        let sealed = backup.seal("passphrase").unwrap();
+        assert!(Backup::open(&sealed, "wrong").is_err());
+        let opened = Backup::open(&sealed, "passphrase").unwrap();
+        assert_eq!(opened, backup);
+        assert_eq!(opened.config().unwrap(), config);
+
+        // Restoring over the same files is fine, over different ones it isn't.
+        opened.restore_files().unwrap();
+        std::fs::write(keystore.join("evm_keystore.json"), b"[{}]").unwrap();
+        assert!(opened.restore_files().is_err());
+
+        std::fs::remove_dir_all(&keystore).unwrap();
+        opened.restore_files().unwrap();
+        assert_eq!(
+            std::fs::read(keystore.join("evm_keystore.json")).unwrap(),
+            b"[]"
+        );
+        assert!(!keystore.join("unrelated.txt").exists());
+    }
+
+    #[test]
+    fn backup_roundtrip_with_profile() {
+        let dir = tempfile::tempdir().unwrap();
+        let keystore = dir.path().join("keystore");
+        let localnet = keystore.join("profiles").join("localnet");
+        let custom = dir.path().join("calibration");
+        std::fs::create_dir_all(&localnet).unwrap();
+        std::fs::create_dir(&custom).unwrap();
+        std::fs::write(keystore.join("evm_keystore.json"), b"[]").unwrap();
+        std::fs::write(localnet.join("evm_keystore.json"), b"[1]").unwrap();
+        std::fs::write(custom.join("evm_keystore.json"), b"[2]").unwrap();
+
+        let mut config = Config::new();
+        config.keystore_path = Some(keystore.to_string_lossy().into_owned());
+        config
+            .profiles
+            .insert("localnet".to_string(), Profile::default());
+        config.profiles.insert(
+            "calibration".to_string(),
+            Profile {
+                keystore_path: Some(custom.to_string_lossy().into_owned()),
+                ..Default::default()
+            },
+        );
+        let backup = Backup::collect(&config).unwrap();
+        assert_eq!(
+            backup.files.keys().cloned().collect::<Vec<_>>(),
+            vec![
+                "evm_keystore.json",
+                "profiles/calibration/evm_keystore.json",
+                "profiles/localnet/evm_keystore.json",
+            ]
+        );
+
+        let opened = Backup::open(&backup.seal("passphrase").unwrap(), "passphrase").unwrap();
+        std::fs::remove_dir_all(&keystore).unwrap();
+        std::fs::remove_dir_all(&custom).unwrap();
+        opened.restore_files().unwrap();
+        assert_eq!(
+            std::fs::read(keystore.join("evm_keystore.json")).unwrap(),
+            b"[]"
+        );
+        assert_eq!(
+            std::fs::read(localnet.join("evm_keystore.json")).unwrap(),
+            b"[1]"
+        );
+        assert_eq!(
+            std::fs::read(custom.join("evm_keystore.json")).unwrap(),
+            b"[2]"
+        );
+
+        // Only the files of the keystores in the config can be restored, not arbitrary paths.
+        let mut tampered = opened;
+        let content = tampered.files["evm_keystore.json"].clone();
+        tampered
+            .files
+            .insert("profiles/localnet/../../x".to_string(), content);
+        assert!(tampered.restore_files().is_err());
+    }
+}
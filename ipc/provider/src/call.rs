@@ -0,0 +1,90 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Encoding of generic contract calls from a function signature and textual arguments.
+//!
+//! [crate::IpcProvider::call_contract] and [crate::IpcProvider::send_contract_tx] take raw
+//! calldata; these helpers build it, and decode the returned data, without bindings for the
+//! contract.
+
+use anyhow::{anyhow, Context};
+use ethers::abi::token::{LenientTokenizer, Tokenizer};
+use ethers::abi::{AbiParser, Function, Token};
+
+/// Parse a function from its signature, either as `transfer(address,uint256)` or in the human
+/// readable ABI, like `function balanceOf(address) view returns (uint256)`.
+pub fn parse_function(signature: &str) -> anyhow::Result<Function> {
+    let signature = signature.trim();
+    let signature = if signature.starts_with("function ") {
+        signature.to_string()
+    } else {
+        format!("function {signature}")
+    };
+    AbiParser::default()
+        .parse_function(&signature)
+        .map_err(|e| anyhow!("invalid function signature {signature}: {e}"))
+}
+
+/// The calldata of a call to `function` with `args`, each in the textual form of its type,
+/// like `0x...` for addresses and bytes, decimal numbers or `[1,2]` for arrays.
+pub fn encode_call<S: AsRef<str>>(function: &Function, args: &[S]) -> anyhow::Result<Vec<u8>> {
+    if args.len() != function.inputs.len() {
+        return Err(anyhow!(
+            "{} takes {} arguments, got {}",
+            function.name,
+            function.inputs.len(),
+            args.len()
+        ));
+    }
+    let tokens = function
+        .inputs
+        .iter()
+        .zip(args)
+        .map(|(param, arg)| {
+            LenientTokenizer::tokenize(&param.kind, arg.as_ref())
+                .with_context(|| format!("invalid {} argument {}", param.kind, arg.as_ref()))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    Ok(function.encode_input(&tokens)?)
+}
+
+/// The values returned by a call to `function`.
+pub fn decode_output(function: &Function, data: &[u8]) -> anyhow::Result<Vec<Token>> {
+    function
+        .decode_output(data)
+        .with_context(|| format!("cannot decode the output of {}", function.name))
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::abi::Token;
+    use ethers::types::U256;
+
+    use super::{decode_output, encode_call, parse_function};
+
+    #[test]
+    fn test_encode_call() {
+        let f = parse_function("transfer(address,uint256)").unwrap();
+        let data =
+            encode_call(&f, &["0x4e59b44847b379578588920cA78FbF26c0B4956C", "1000"]).unwrap();
+
+        // The selector of `transfer(address,uint256)`.
+        assert_eq!(data[..4], [0xa9, 0x05, 0x9c, 0xbb]);
+        assert_eq!(data.len(), 4 + 2 * 32);
+        assert_eq!(U256::from_big_endian(&data[36..]), U256::from(1000));
+
+        assert!(encode_call(&f, &["1000"]).is_err());
+        assert!(encode_call(&f, &["not an address", "1000"]).is_err());
+    }
+
+    #[test]
+    fn test_decode_output() {
+        let f = parse_function("function balanceOf(address) view returns (uint256)").unwrap();
+        let mut data = [0u8; 32];
+        data[31] = 42;
+
+        assert_eq!(
+            decode_output(&f, &data).unwrap(),
+            vec![Token::Uint(U256::from(42))]
+        );
+    }
+}
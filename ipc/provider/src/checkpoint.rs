@@ -2,19 +2,52 @@
 // SPDX-License-Identifier: MIT
 //! Bottom up checkpoint manager
 
+use crate::audit::AuditLog;
 use crate::config::Subnet;
-use crate::manager::{BottomUpCheckpointRelayer, EthSubnetManager};
-use anyhow::{anyhow, Result};
+use crate::evm_keystore::EvmKeyStoreBackend;
+use crate::idempotency::IdempotencyStore;
+use crate::manager::{BottomUpCheckpointRelayer, EthSubnetManager, TransactionQuery};
+use crate::response_cache::ResponseCache;
+use crate::stats;
+use anyhow::{anyhow, Context, Result};
+use ethers::abi::Tokenize;
+use ethers::types::{Signature, H256};
+use ethers::utils::keccak256;
 use futures_util::future::try_join_all;
 use fvm_shared::address::Address;
 use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
+use ipc_actors_abis::subnet_actor_checkpointing_facet;
 use ipc_api::checkpoint::{BottomUpCheckpointBundle, QuorumReachedEvent};
-use ipc_wallet::{EthKeyAddress, PersistentKeyStore};
+use ipc_api::evm::payload_to_evm_address;
+use ipc_api::staking::ConfigurationNumber;
+use ipc_api::subnet_id::SubnetID;
+use ipc_api::validator::Validator;
+use ipc_api::HumanReadable;
+use num_traits::Zero;
+use prometheus::IntGauge;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DurationMilliSeconds};
 use std::cmp::max;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Display, Formatter};
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
+use tokio::sync::mpsc::{self, error::TrySendError};
 use tokio::sync::Semaphore;
+use tokio_util::sync::CancellationToken;
+
+/// Number of items each stage of the submission pipeline can queue up for the next one,
+/// unless configured otherwise.
+pub const DEFAULT_PIPELINE_CAPACITY: usize = 16;
+
+/// Shortest wait between two rounds of submissions when the block time of the parent is
+/// unknown.
+pub const DEFAULT_MIN_SUBMISSION_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Number of parent blocks a submission is expected to take to be executed.
+const SUBMISSION_LANDING_BLOCKS: u32 = 2;
 
 /// Tracks the config required for bottom up checkpoint submissions
 /// parent/child subnet and checkpoint period.
@@ -34,6 +67,15 @@ pub struct BottomUpCheckpointManager<T> {
     /// The number of blocks away from the chain head that is considered final
     finalization_blocks: ChainEpoch,
     submission_semaphore: Arc<Semaphore>,
+    /// Number of items each stage can queue up for the next one before it has to wait.
+    pipeline_capacity: usize,
+    /// Records the submitted checkpoints, so they aren't submitted again after a restart.
+    idempotency: Option<Arc<IdempotencyStore>>,
+    /// Stops the daemon once the submissions in flight are done.
+    shutdown: CancellationToken,
+    /// Keeps the events and bundles of the final blocks of the child, so a restarted relayer
+    /// doesn't fetch them again.
+    response_cache: Option<Arc<ResponseCache>>,
 }
 
 impl<T: BottomUpCheckpointRelayer> BottomUpCheckpointManager<T> {
@@ -58,6 +100,10 @@ impl<T: BottomUpCheckpointRelayer> BottomUpCheckpointManager<T> {
             child_handler,
             finalization_blocks: 0,
             submission_semaphore: Arc::new(Semaphore::new(max_parallelism)),
+            pipeline_capacity: DEFAULT_PIPELINE_CAPACITY,
+            idempotency: None,
+            shutdown: CancellationToken::new(),
+            response_cache: None,
         })
     }
 
@@ -65,19 +111,45 @@ impl<T: BottomUpCheckpointRelayer> BottomUpCheckpointManager<T> {
         self.finalization_blocks = finalization_blocks;
         self
     }
+
+    pub fn with_pipeline_capacity(mut self, capacity: usize) -> Self {
+        self.pipeline_capacity = capacity.max(1);
+        self
+    }
+
+    pub fn with_idempotency_store(mut self, store: Arc<IdempotencyStore>) -> Self {
+        self.idempotency = Some(store);
+        self
+    }
+
+    pub fn with_response_cache(mut self, cache: Arc<ResponseCache>) -> Self {
+        self.response_cache = Some(cache);
+        self
+    }
+
+    /// Stop submitting when the signal is cancelled, e.g. by [crate::supervisor::TaskSupervisor].
+    pub fn with_shutdown_signal(mut self, shutdown: CancellationToken) -> Self {
+        self.shutdown = shutdown;
+        self
+    }
 }
 
 impl BottomUpCheckpointManager<EthSubnetManager> {
     pub async fn new_evm_manager(
         parent: Subnet,
         child: Subnet,
-        keystore: Arc<RwLock<PersistentKeyStore<EthKeyAddress>>>,
+        keystore: Arc<RwLock<EvmKeyStoreBackend>>,
+        audit_log: Option<Arc<AuditLog>>,
         max_parallelism: usize,
     ) -> Result<Self> {
-        let parent_handler =
+        let mut parent_handler =
             EthSubnetManager::from_subnet_with_wallet_store(&parent, Some(keystore.clone()))?;
-        let child_handler =
+        let mut child_handler =
             EthSubnetManager::from_subnet_with_wallet_store(&child, Some(keystore))?;
+        if let Some(log) = audit_log {
+            parent_handler = parent_handler.with_audit_log(log.clone(), parent.id.clone());
+            child_handler = child_handler.with_audit_log(log, child.id.clone());
+        }
         Self::new(
             parent,
             child,
@@ -99,7 +171,9 @@ impl<T: BottomUpCheckpointRelayer> Display for BottomUpCheckpointManager<T> {
     }
 }
 
-impl<T: BottomUpCheckpointRelayer + Send + Sync + 'static> BottomUpCheckpointManager<T> {
+impl<T: BottomUpCheckpointRelayer + TransactionQuery + Send + Sync + 'static>
+    BottomUpCheckpointManager<T>
+{
     /// Getter for the parent subnet this checkpoint manager is handling
     pub fn parent_subnet(&self) -> &Subnet {
         &self.metadata.parent
@@ -115,19 +189,81 @@ impl<T: BottomUpCheckpointRelayer + Send + Sync + 'static> BottomUpCheckpointMan
         self.metadata.period
     }
 
-    /// Run the bottom up checkpoint submission daemon in the foreground
+    /// Run the bottom up checkpoint submission daemon in the foreground, until the shutdown
+    /// signal is cancelled and the submissions in flight have finished.
+    ///
+    /// After each round the daemon sleeps until the next checkpoint is due, as estimated from
+    /// the block time of the child, but never longer than `submission_interval`.
     pub async fn run(self, submitter: Address, submission_interval: Duration) {
         tracing::info!("launching {self} for {submitter}");
 
+        let scheduler = SubmissionScheduler {
+            period: self.metadata.period,
+            finalization_blocks: self.finalization_blocks,
+            max_interval: submission_interval,
+        };
+
         loop {
             if let Err(e) = self.submit_next_epoch(submitter).await {
                 tracing::error!("cannot submit checkpoint for submitter: {submitter} due to {e}");
             }
-            tokio::time::sleep(submission_interval).await;
+            let wait = match self.next_schedule(&scheduler).await {
+                Ok(schedule) => schedule.wait,
+                Err(e) => {
+                    tracing::warn!("cannot schedule the next submission: {e:#}");
+                    submission_interval
+                }
+            };
+            tokio::select! {
+                _ = self.shutdown.cancelled() => break,
+                _ = tokio::time::sleep(wait) => {}
+            }
         }
+
+        tracing::info!("stopped {self} for {submitter}");
+    }
+
+    /// Work out when the next checkpoint is due from the state of both subnets.
+    async fn next_schedule(&self, scheduler: &SubmissionScheduler) -> Result<SubmissionSchedule> {
+        let (last_committed, child_head, child_block_time, parent_block_time) = tokio::try_join!(
+            self.parent_handler
+                .last_bottom_up_checkpoint_height(&self.metadata.child.id),
+            self.child_handler.current_epoch(),
+            self.child_handler.block_time(),
+            self.parent_handler.block_time(),
+        )?;
+
+        let schedule = scheduler.schedule(
+            last_committed,
+            child_head,
+            child_block_time,
+            parent_block_time,
+        );
+        tracing::debug!(
+            "next checkpoint at height {} due in {:?}, waiting {:?}",
+            schedule.next_checkpoint,
+            schedule.due_in,
+            schedule.wait
+        );
+        stats::RELAYER_NEXT_CHECKPOINT_HEIGHT.set(schedule.next_checkpoint);
+        if let Some(due_in) = schedule.due_in {
+            stats::RELAYER_NEXT_CHECKPOINT_DUE.set(due_in.as_secs() as i64);
+        }
+        if schedule.misses_window() {
+            tracing::warn!(
+                "submissions take about {:?} to land in the parent, longer than the {:?} between checkpoints",
+                schedule.landing,
+                schedule.window
+            );
+        }
+        Ok(schedule)
     }
 
     /// Checks if the relayer has already submitted at the next submission epoch, if not it submits it.
+    ///
+    /// The heights are scanned for checkpoints, the checkpoints assembled with their signatures
+    /// and submitted by stages connected with bounded channels, so a slow parent makes the
+    /// other stages wait instead of queueing up the whole range.
     async fn submit_next_epoch(&self, submitter: Address) -> Result<()> {
         let last_checkpoint_epoch = self
             .parent_handler
@@ -139,24 +275,55 @@ impl<T: BottomUpCheckpointRelayer + Send + Sync + 'static> BottomUpCheckpointMan
         tracing::info!("last submission height: {last_checkpoint_epoch}");
 
         let current_height = self.child_handler.current_epoch().await?;
+        if let Some(cache) = &self.response_cache {
+            cache.observe_head(current_height);
+        }
         let finalized_height = max(1, current_height - self.finalization_blocks);
 
         tracing::debug!("last submission height: {last_checkpoint_epoch}, current height: {current_height}, finalized_height: {finalized_height}");
 
+        stats::RELAYER_FINALIZED_HEIGHT.set(finalized_height);
+        stats::RELAYER_LAG.set(max(0, finalized_height - last_checkpoint_epoch));
+
         if finalized_height <= last_checkpoint_epoch {
             return Ok(());
         }
 
+        let (events_tx, events_rx) = mpsc::channel(self.pipeline_capacity);
+        let (bundles_tx, bundles_rx) = mpsc::channel(self.pipeline_capacity);
+
+        let res = tokio::try_join!(
+            self.poll_events(last_checkpoint_epoch, finalized_height, events_tx),
+            self.assemble_bundles(events_rx, bundles_tx),
+            self.submit_bundles(submitter, bundles_rx),
+        );
+
+        // Whatever is left in the queues after a failure is dropped with the channels.
+        stats::RELAYER_ASSEMBLY_QUEUE.set(0);
+        stats::RELAYER_SUBMISSION_QUEUE.set(0);
+
+        res.map(|_| ())
+    }
+
+    /// First stage: scan the final heights for the checkpoints which reached a quorum.
+    async fn poll_events(
+        &self,
+        last_checkpoint_epoch: ChainEpoch,
+        finalized_height: ChainEpoch,
+        events_tx: mpsc::Sender<QuorumReachedEvent>,
+    ) -> Result<()> {
         let start = last_checkpoint_epoch + 1;
         tracing::debug!(
             "start querying quorum reached events from : {start} to {finalized_height}"
         );
 
-        let mut count = 0;
-        let mut all_submit_tasks = vec![];
-
         for h in start..=finalized_height {
-            let events = self.child_handler.quorum_reached_events(h).await?;
+            if self.shutdown.is_cancelled() {
+                tracing::info!("shutting down, not submitting checkpoints from height {h}");
+                break;
+            }
+            let events = self.quorum_reached_events(h).await?;
+            stats::RELAYER_POLLED_HEIGHT.set(h);
             if events.is_empty() {
                 tracing::debug!("no reached events at height : {h}");
                 continue;
@@ -173,48 +340,119 @@ impl<T: BottomUpCheckpointRelayer + Send + Sync + 'static> BottomUpCheckpointMan
                     tracing::debug!("event height already committed: {}", event.height);
                     continue;
                 }
+                send_to_stage(&events_tx, event, &stats::RELAYER_ASSEMBLY_QUEUE).await?;
+            }
+        }
 
-                let bundle = self
-                    .child_handler
-                    .checkpoint_bundle_at(event.height)
-                    .await?
-                    .ok_or_else(|| {
-                        anyhow!(
-                            "expected checkpoint at height {} but none found",
-                            event.height
-                        )
-                    })?;
-
-                log::debug!("bottom up bundle: {bundle:?}");
-
-                // We support parallel checkpoint submission using FIFO order with a limited parallelism (controlled by
-                // the size of submission_semaphore).
-                // We need to acquire a permit (from a limited permit pool) before submitting a checkpoint.
-                // We may wait here until a permit is available.
-                let parent_handler_clone = Arc::clone(&self.parent_handler);
-                let submission_permit = self
-                    .submission_semaphore
-                    .clone()
-                    .acquire_owned()
-                    .await
-                    .unwrap();
-                all_submit_tasks.push(tokio::task::spawn(async move {
-                    let height = event.height;
-                    let result =
-                        Self::submit_checkpoint(parent_handler_clone, submitter, bundle, event)
+        Ok(())
+    }
+
+    /// Second stage: fetch the signature bundles of the checkpoints.
+    async fn assemble_bundles(
+        &self,
+        mut events_rx: mpsc::Receiver<QuorumReachedEvent>,
+        bundles_tx: mpsc::Sender<(QuorumReachedEvent, BottomUpCheckpointBundle)>,
+    ) -> Result<()> {
+        while let Some(event) = events_rx.recv().await {
+            stats::RELAYER_ASSEMBLY_QUEUE.dec();
+
+            let bundle = self
+                .checkpoint_bundle_at(event.height)
+                .await?
+                .ok_or_else(|| {
+                    anyhow!(
+                        "expected checkpoint at height {} but none found",
+                        event.height
+                    )
+                })?;
+
+            log::debug!("bottom up bundle: {bundle:?}");
+
+            // The parent would reject the submission, after we paid for it.
+            let bundle = tokio::task::spawn_blocking(move || {
+                verify_bundle_signatures(&bundle).map(|()| bundle)
+            })
+            .await?
+            .with_context(|| {
+                format!(
+                    "invalid signatures in checkpoint bundle at height {}",
+                    event.height
+                )
+            })?;
+
+            send_to_stage(
+                &bundles_tx,
+                (event, bundle),
+                &stats::RELAYER_SUBMISSION_QUEUE,
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Last stage: submit the checkpoints to the parent, and wait for the submissions to finish.
+    async fn submit_bundles(
+        &self,
+        submitter: Address,
+        mut bundles_rx: mpsc::Receiver<(QuorumReachedEvent, BottomUpCheckpointBundle)>,
+    ) -> Result<()> {
+        let mut count = 0;
+        let mut all_submit_tasks = vec![];
+
+        while let Some((event, bundle)) = bundles_rx.recv().await {
+            stats::RELAYER_SUBMISSION_QUEUE.dec();
+
+            // We support parallel checkpoint submission using FIFO order with a limited parallelism (controlled by
+            // the size of submission_semaphore).
+            // We need to acquire a permit (from a limited permit pool) before submitting a checkpoint.
+            // We may wait here until a permit is available, which holds back the other stages.
+            let parent_handler_clone = Arc::clone(&self.parent_handler);
+            let idempotency = self.idempotency.clone();
+            let parent = self.metadata.parent.id.clone();
+            let key = checkpoint_idempotency_key(&self.metadata.child.id, event.height);
+            let submission_permit = self
+                .submission_semaphore
+                .clone()
+                .acquire_owned()
+                .await
+                .unwrap();
+            all_submit_tasks.push(tokio::task::spawn(async move {
+                let height = event.height;
+                let parent_handler = parent_handler_clone.clone();
+                let submission =
+                    Self::submit_checkpoint(parent_handler_clone, submitter, bundle, event);
+                let result = match idempotency {
+                    Some(store) => {
+                        store
+                            .run(
+                                &key,
+                                "submit_checkpoint",
+                                &parent,
+                                parent_handler.as_ref(),
+                                submission,
+                            )
                             .await
-                            .inspect_err(|err| {
-                                tracing::error!(
-                                    "Fail to submit checkpoint at height {height}: {err}"
-                                );
-                            });
-                    drop(submission_permit);
-                    result
-                }));
-
-                count += 1;
-                tracing::debug!("This round has asynchronously submitted {count} checkpoints",);
-            }
+                    }
+                    None => submission.await,
+                };
+                match &result {
+                    Ok(_) => {
+                        if height > stats::RELAYER_SUBMITTED_HEIGHT.get() {
+                            stats::RELAYER_SUBMITTED_HEIGHT.set(height);
+                        }
+                    }
+                    Err(err) => {
+                        stats::RELAYER_SUBMISSION_FAILURE.inc();
+                        tracing::error!("Fail to submit checkpoint at height {height}: {err}");
+                    }
+                }
+                drop(submission_permit);
+                result
+            }));
+
+            count += 1;
+            tracing::debug!("This round has asynchronously submitted {count} checkpoints",);
         }
 
         tracing::debug!("Waiting for all submissions to finish");
@@ -224,12 +462,45 @@ impl<T: BottomUpCheckpointRelayer + Send + Sync + 'static> BottomUpCheckpointMan
         Ok(())
     }
 
+    /// The quorum reached events of the child at `height`, from the cache if there.
+    async fn quorum_reached_events(&self, height: ChainEpoch) -> Result<Vec<QuorumReachedEvent>> {
+        let fetch = self.child_handler.quorum_reached_events(height);
+        match &self.response_cache {
+            Some(cache) => {
+                cache
+                    .get_or_fetch("quorum_reached_events", &height, height, fetch, || {
+                        self.child_handler.current_epoch()
+                    })
+                    .await
+            }
+            None => fetch.await,
+        }
+    }
+
+    /// The checkpoint bundle of the child at `height`, from the cache if there.
+    async fn checkpoint_bundle_at(
+        &self,
+        height: ChainEpoch,
+    ) -> Result<Option<BottomUpCheckpointBundle>> {
+        let fetch = self.child_handler.checkpoint_bundle_at(height);
+        match &self.response_cache {
+            Some(cache) => {
+                cache
+                    .get_or_fetch("checkpoint_bundle_at", &height, height, fetch, || {
+                        self.child_handler.current_epoch()
+                    })
+                    .await
+            }
+            None => fetch.await,
+        }
+    }
+
     async fn submit_checkpoint(
         parent_handler: Arc<T>,
         submitter: Address,
         bundle: BottomUpCheckpointBundle,
         event: QuorumReachedEvent,
-    ) -> Result<(), anyhow::Error> {
+    ) -> Result<ChainEpoch, anyhow::Error> {
         let epoch = parent_handler
             .submit_checkpoint(
                 &submitter,
@@ -250,6 +521,490 @@ impl<T: BottomUpCheckpointRelayer + Send + Sync + 'static> BottomUpCheckpointMan
             event.height,
             epoch
         );
-        Ok(())
+        Ok(epoch)
+    }
+}
+
+/// Times the rounds of the relayer by when the checkpoints are due in the child.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SubmissionScheduler {
+    pub period: ChainEpoch,
+    pub finalization_blocks: ChainEpoch,
+    /// Longest wait between two rounds, in case the estimates are off.
+    pub max_interval: Duration,
+}
+
+/// When the next checkpoint is due, and how long to wait for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SubmissionSchedule {
+    /// Height of the next checkpoint expected in the child.
+    pub next_checkpoint: ChainEpoch,
+    /// Estimated time until the next checkpoint is final in the child, zero if it already is;
+    /// `None` if the block time of the child is unknown.
+    pub due_in: Option<Duration>,
+    /// Estimated time between two checkpoints, which a submission has to land within not to
+    /// fall behind.
+    pub window: Option<Duration>,
+    /// Estimated time for a submission to be executed by the parent.
+    pub landing: Option<Duration>,
+    /// How long to wait before the next round.
+    pub wait: Duration,
+}
+
+impl SubmissionSchedule {
+    /// Whether submissions take longer to land than the time between two checkpoints, so the
+    /// relayer can't keep up whatever its schedule.
+    pub fn misses_window(&self) -> bool {
+        matches!((self.landing, self.window), (Some(landing), Some(window)) if landing > window)
+    }
+}
+
+impl SubmissionScheduler {
+    /// Work out when the checkpoint after `last_committed` is due, given the head of the child
+    /// and the block times of both subnets, if known.
+    ///
+    /// The wait is at least a block of the parent, since the last committed height, which the
+    /// next round starts from, can't change any sooner.
+    pub fn schedule(
+        &self,
+        last_committed: ChainEpoch,
+        child_head: ChainEpoch,
+        child_block_time: Option<Duration>,
+        parent_block_time: Option<Duration>,
+    ) -> SubmissionSchedule {
+        let next_checkpoint = last_committed + self.period;
+        let blocks_to_go = (next_checkpoint + self.finalization_blocks - child_head).max(0);
+
+        let blocks = |t: Duration, n: i64| u32::try_from(n).ok().and_then(|n| t.checked_mul(n));
+        let due_in = child_block_time.and_then(|t| blocks(t, blocks_to_go));
+        let window = child_block_time.and_then(|t| blocks(t, self.period));
+        let landing = parent_block_time.and_then(|t| t.checked_mul(SUBMISSION_LANDING_BLOCKS));
+
+        let min_interval = parent_block_time.unwrap_or(DEFAULT_MIN_SUBMISSION_INTERVAL);
+        let wait = due_in
+            .unwrap_or(self.max_interval)
+            .max(min_interval)
+            .min(self.max_interval);
+
+        SubmissionSchedule {
+            next_checkpoint,
+            due_in,
+            window,
+            landing,
+            wait,
+        }
+    }
+}
+
+/// Where the bottom-up checkpoints of a subnet stand, as returned by
+/// [crate::IpcProvider::checkpoint_schedule].
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckpointSchedule {
+    #[serde_as(as = "HumanReadable")]
+    pub subnet: SubnetID,
+    /// The number of child blocks between two checkpoints.
+    pub period: ChainEpoch,
+    /// Height of the last checkpoint committed in the parent.
+    pub last_committed: ChainEpoch,
+    /// The chain head of the child.
+    pub child_head: ChainEpoch,
+    /// Height of the next checkpoint to be committed.
+    pub next_checkpoint: ChainEpoch,
+    /// The average time between the recent blocks of the child, if there are enough of them.
+    #[serde_as(as = "Option<DurationMilliSeconds<u64>>")]
+    #[serde(rename = "block_time_ms")]
+    pub block_time: Option<Duration>,
+    /// Estimated time until the child reaches the next checkpoint, zero if it already has and
+    /// the checkpoint is waiting to be committed; `None` if the block time is unknown.
+    #[serde_as(as = "Option<DurationMilliSeconds<u64>>")]
+    #[serde(rename = "eta_ms")]
+    pub eta: Option<Duration>,
+}
+
+impl CheckpointSchedule {
+    pub fn new(
+        subnet: SubnetID,
+        period: ChainEpoch,
+        last_committed: ChainEpoch,
+        child_head: ChainEpoch,
+        block_time: Option<Duration>,
+    ) -> Self {
+        let next_checkpoint = last_committed + period;
+        let blocks_to_go = u32::try_from((next_checkpoint - child_head).max(0)).ok();
+        let eta = block_time
+            .zip(blocks_to_go)
+            .and_then(|(t, n)| t.checked_mul(n));
+        Self {
+            subnet,
+            period,
+            last_committed,
+            child_head,
+            next_checkpoint,
+            block_time,
+            eta,
+        }
+    }
+
+    /// The checkpoints the child has reached but the parent hasn't committed yet.
+    pub fn pending(&self) -> ChainEpoch {
+        if self.period <= 0 {
+            return 0;
+        }
+        (self.child_head - self.last_committed).max(0) / self.period
+    }
+}
+
+impl Display for CheckpointSchedule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "subnet: {}", self.subnet)?;
+        writeln!(f, "checkpoint period: {}", self.period)?;
+        writeln!(f, "last committed checkpoint: {}", self.last_committed)?;
+        writeln!(f, "child chain head: {}", self.child_head)?;
+        writeln!(f, "next checkpoint: {}", self.next_checkpoint)?;
+        writeln!(f, "pending checkpoints: {}", self.pending())?;
+        match self.eta {
+            Some(eta) => write!(f, "next checkpoint due in: {eta:?}"),
+            None => write!(f, "next checkpoint due in: unknown"),
+        }
+    }
+}
+
+/// Check that each signature of the bundle was made over the checkpoint by its signatory, the
+/// way the parent does when the checkpoint is submitted. The signatures are recovered in
+/// parallel, since there is one per validator in the quorum.
+pub fn verify_bundle_signatures(bundle: &BottomUpCheckpointBundle) -> Result<()> {
+    if bundle.signatures.len() != bundle.signatories.len() {
+        return Err(anyhow!(
+            "{} signatures for {} signatories",
+            bundle.signatures.len(),
+            bundle.signatories.len()
+        ));
+    }
+
+    let checkpoint =
+        subnet_actor_checkpointing_facet::BottomUpCheckpoint::try_from(bundle.checkpoint.clone())?;
+    // Solidity encodes a single struct as a tuple.
+    let hash = H256::from(keccak256(ethers::abi::encode(&(checkpoint,).into_tokens())));
+
+    bundle
+        .signatures
+        .par_iter()
+        .zip(bundle.signatories.par_iter())
+        .try_for_each(|(signature, signatory)| {
+            let expected = payload_to_evm_address(signatory.payload())?;
+            let recovered = Signature::try_from(signature.as_slice())
+                .and_then(|signature| signature.recover(hash))
+                .map_err(|e| anyhow!("invalid signature of {signatory}: {e}"))?;
+            if recovered != expected {
+                return Err(anyhow!(
+                    "signature of {signatory} was made by {recovered:?}"
+                ));
+            }
+            Ok(())
+        })
+}
+
+/// A bottom-up checkpoint whose signatures reach the quorum of the membership of its subnet,
+/// as returned by [crate::IpcProvider::verify_checkpoint].
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckpointVerification {
+    #[serde_as(as = "HumanReadable")]
+    pub subnet: SubnetID,
+    pub height: ChainEpoch,
+    /// The configuration number of the membership the signatures were checked against.
+    pub configuration_number: ConfigurationNumber,
+    /// The weight of the validators who signed.
+    #[serde_as(as = "HumanReadable")]
+    pub signed_weight: TokenAmount,
+    /// The weight of the whole membership.
+    #[serde_as(as = "HumanReadable")]
+    pub total_weight: TokenAmount,
+    /// The weight the signatures have to reach, the majority percentage of the total.
+    #[serde_as(as = "HumanReadable")]
+    pub threshold: TokenAmount,
+}
+
+impl Display for CheckpointVerification {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "checkpoint of {} at height {} is signed by {} of {} weight (threshold {}) of configuration {}",
+            self.subnet,
+            self.height,
+            self.signed_weight.atto(),
+            self.total_weight.atto(),
+            self.threshold.atto(),
+            self.configuration_number
+        )
+    }
+}
+
+/// Check the signatures of the bundle and that their signatories are members of `validators`
+/// weighing at least `majority_percentage` of the membership, the way the parent does when the
+/// checkpoint is submitted, without trusting whoever assembled the bundle.
+pub fn verify_checkpoint_quorum(
+    bundle: &BottomUpCheckpointBundle,
+    configuration_number: ConfigurationNumber,
+    validators: &[Validator],
+    majority_percentage: u8,
+) -> Result<CheckpointVerification> {
+    verify_bundle_signatures(bundle)?;
+
+    let weights = validators
+        .iter()
+        .map(|v| (v.addr, &v.weight))
+        .collect::<HashMap<_, _>>();
+    let mut signed = HashSet::new();
+    let mut signed_weight = TokenAmount::zero();
+    for signatory in &bundle.signatories {
+        let weight = weights.get(signatory).ok_or_else(|| {
+            anyhow!("{signatory} is not a validator of configuration {configuration_number}")
+        })?;
+        if !signed.insert(*signatory) {
+            return Err(anyhow!("{signatory} signed more than once"));
+        }
+        signed_weight += *weight;
+    }
+
+    let total_weight = validators
+        .iter()
+        .fold(TokenAmount::zero(), |total, v| total + &v.weight);
+    let threshold = TokenAmount::from_atto(total_weight.atto() * majority_percentage / 100u8);
+
+    let verification = CheckpointVerification {
+        subnet: bundle.checkpoint.subnet_id.clone(),
+        height: bundle.checkpoint.block_height,
+        configuration_number,
+        signed_weight,
+        total_weight,
+        threshold,
+    };
+    if verification.signed_weight < verification.threshold {
+        return Err(anyhow!(
+            "the signatures do not reach the quorum: {verification}"
+        ));
+    }
+    Ok(verification)
+}
+
+/// Hand an item over to the next stage, waiting for it to catch up if its queue is full.
+async fn send_to_stage<T>(tx: &mpsc::Sender<T>, item: T, queue: &IntGauge) -> Result<()> {
+    let item = match tx.try_send(item) {
+        Ok(()) => {
+            queue.inc();
+            return Ok(());
+        }
+        Err(TrySendError::Full(item)) => item,
+        Err(TrySendError::Closed(_)) => return Err(anyhow!("the next stage has stopped")),
+    };
+
+    stats::RELAYER_BACKPRESSURE.inc();
+    tracing::debug!("waiting for the next stage to catch up");
+    tx.send(item)
+        .await
+        .map_err(|_| anyhow!("the next stage has stopped"))?;
+    queue.inc();
+    Ok(())
+}
+
+/// The idempotency key of the submission of the checkpoint of `child` cut at `height`.
+fn checkpoint_idempotency_key(child: &SubnetID, height: ChainEpoch) -> String {
+    format!("checkpoint:{child}:{height}")
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::abi::Tokenize;
+    use ethers::signers::{LocalWallet, Signer};
+    use ethers::types::H256;
+    use ethers::utils::keccak256;
+    use fvm_shared::address::Address;
+    use fvm_shared::econ::TokenAmount;
+    use ipc_actors_abis::subnet_actor_checkpointing_facet;
+    use ipc_api::checkpoint::{BottomUpCheckpoint, BottomUpCheckpointBundle};
+    use ipc_api::ethers_address_to_fil_address;
+    use ipc_api::subnet_id::SubnetID;
+    use ipc_api::validator::Validator;
+    use prometheus::IntGauge;
+    use tokio::sync::mpsc;
+
+    use std::time::Duration;
+
+    use super::{send_to_stage, verify_bundle_signatures, CheckpointSchedule, SubmissionScheduler};
+    use crate::stats::RELAYER_BACKPRESSURE;
+
+    /// A checkpoint signed by `n` validators, the way fendermint signs it.
+    fn signed_bundle(n: u8) -> BottomUpCheckpointBundle {
+        let subnet = SubnetID::new(314159, vec![Address::new_delegated(10, &[7; 20]).unwrap()]);
+        let checkpoint = BottomUpCheckpoint {
+            subnet_id: subnet,
+            block_height: 100,
+            block_hash: vec![1; 32],
+            next_configuration_number: 2,
+            msgs: vec![],
+        };
+        let tokens = (
+            subnet_actor_checkpointing_facet::BottomUpCheckpoint::try_from(checkpoint.clone())
+                .unwrap(),
+        )
+            .into_tokens();
+        let hash = H256::from(keccak256(ethers::abi::encode(&tokens)));
+
+        let (signatures, signatories) = (1..=n)
+            .map(|i| {
+                let wallet = LocalWallet::from_bytes(&[i; 32]).unwrap();
+                let signature = wallet.sign_hash(hash).unwrap();
+                (
+                    signature.to_vec(),
+                    ethers_address_to_fil_address(&wallet.address()).unwrap(),
+                )
+            })
+            .unzip();
+
+        BottomUpCheckpointBundle {
+            checkpoint,
+            signatures,
+            signatories,
+        }
+    }
+
+    #[test]
+    fn bundle_signatures_are_verified() {
+        let bundle = signed_bundle(20);
+        verify_bundle_signatures(&bundle).unwrap();
+
+        let mut swapped = bundle.clone();
+        swapped.signatories.swap(3, 4);
+        assert!(verify_bundle_signatures(&swapped).is_err());
+
+        let mut tampered = bundle.clone();
+        tampered.checkpoint.block_height += 1;
+        assert!(verify_bundle_signatures(&tampered).is_err());
+
+        let mut truncated = bundle;
+        truncated.signatures.pop();
+        assert!(verify_bundle_signatures(&truncated).is_err());
+    }
+
+    #[test]
+    fn checkpoint_quorum_is_verified() {
+        // Validators 1 to 3 of the bundle, with the third weighing as much as the other two.
+        let bundle = signed_bundle(3);
+        let validators = bundle
+            .signatories
+            .iter()
+            .zip([1, 1, 2])
+            .map(|(addr, weight)| Validator {
+                addr: *addr,
+                metadata: vec![],
+                weight: TokenAmount::from_whole(weight),
+            })
+            .collect::<Vec<_>>();
+
+        let verification = verify_checkpoint_quorum(&bundle, 5, &validators, 66).unwrap();
+        assert_eq!(verification.configuration_number, 5);
+        assert_eq!(verification.signed_weight, TokenAmount::from_whole(4));
+        assert_eq!(
+            verification.threshold,
+            TokenAmount::from_atto(2_640_000_000_000_000_000u64)
+        );
+
+        // The first two only weigh half of the membership.
+        let mut minority = bundle.clone();
+        minority.signatures.pop();
+        minority.signatories.pop();
+        assert!(verify_checkpoint_quorum(&minority, 5, &validators, 66).is_err());
+
+        // Signatures of validators outside the membership don't count.
+        assert!(verify_checkpoint_quorum(&bundle, 5, &validators[1..], 50).is_err());
+
+        let mut duplicated = bundle;
+        duplicated.signatures.push(duplicated.signatures[2].clone());
+        duplicated.signatories.push(duplicated.signatories[2]);
+        assert!(verify_checkpoint_quorum(&duplicated, 5, &validators, 66).is_err());
+    }
+
+    #[tokio::test]
+    async fn full_stages_hold_back_the_previous_one() {
+        let queue = IntGauge::new("test_queue", "test").unwrap();
+        let (tx, mut rx) = mpsc::channel(2);
+
+        send_to_stage(&tx, 1, &queue).await.unwrap();
+        send_to_stage(&tx, 2, &queue).await.unwrap();
+        assert_eq!(queue.get(), 2);
+
+        let before = RELAYER_BACKPRESSURE.get();
+        let blocked = send_to_stage(&tx, 3, &queue);
+        tokio::pin!(blocked);
+        assert!(
+            tokio::time::timeout(std::time::Duration::from_millis(50), &mut blocked)
+                .await
+                .is_err(),
+            "a full stage should not take more items"
+        );
+        assert_eq!(RELAYER_BACKPRESSURE.get(), before + 1);
+
+        assert_eq!(rx.recv().await, Some(1));
+        blocked.await.unwrap();
+        assert_eq!(queue.get(), 3);
+
+        drop(rx);
+        assert!(send_to_stage(&tx, 4, &queue).await.is_err());
+    }
+
+    #[test]
+    fn submissions_are_scheduled_when_checkpoints_are_due() {
+        let secs = Duration::from_secs;
+        let scheduler = SubmissionScheduler {
+            period: 100,
+            finalization_blocks: 5,
+            max_interval: secs(300),
+        };
+
+        // Checkpoint 200 is final at 205, 45 blocks of 1s away.
+        let schedule = scheduler.schedule(100, 160, Some(secs(1)), Some(secs(30)));
+        assert_eq!(schedule.next_checkpoint, 200);
+        assert_eq!(schedule.due_in, Some(secs(45)));
+        assert_eq!(schedule.wait, secs(45));
+        assert!(!schedule.misses_window());
+
+        // Already due: wait for the parent to move on rather than spinning.
+        let schedule = scheduler.schedule(100, 210, Some(secs(1)), Some(secs(30)));
+        assert_eq!(schedule.due_in, Some(Duration::ZERO));
+        assert_eq!(schedule.wait, secs(30));
+
+        // Far away, or unknown: check again at the longest interval.
+        let schedule = scheduler.schedule(100, 101, Some(secs(10)), None);
+        assert_eq!(schedule.wait, secs(300));
+        let schedule = scheduler.schedule(100, 160, None, None);
+        assert_eq!(schedule.due_in, None);
+        assert_eq!(schedule.wait, secs(300));
+
+        // A checkpoint every 100ms can't be landed by a parent with 1s blocks.
+        let schedule = scheduler.schedule(100, 160, Some(Duration::from_millis(1)), Some(secs(1)));
+        assert!(schedule.misses_window());
+        assert_eq!(schedule.wait, secs(1));
+    }
+
+    #[test]
+    fn checkpoint_schedule_estimates_the_next_checkpoint() {
+        let subnet = SubnetID::new(314159, vec![Address::new_id(100)]);
+
+        let schedule =
+            CheckpointSchedule::new(subnet.clone(), 100, 200, 260, Some(Duration::from_secs(2)));
+        assert_eq!(schedule.next_checkpoint, 300);
+        assert_eq!(schedule.eta, Some(Duration::from_secs(80)));
+        assert_eq!(schedule.pending(), 0);
+        assert_eq!(
+            serde_json::to_string(&schedule).unwrap(),
+            r#"{"subnet":"/r314159/f0100","period":100,"last_committed":200,"child_head":260,"next_checkpoint":300,"block_time_ms":2000,"eta_ms":80000}"#
+        );
+
+        // The child is past two checkpoints the parent hasn't committed.
+        let schedule = CheckpointSchedule::new(subnet, 100, 200, 420, None);
+        assert_eq!(schedule.eta, None);
+        assert_eq!(schedule.pending(), 2);
     }
 }
@@ -6,22 +6,31 @@
 //! [`Config`] struct.
 
 pub mod deserialize;
+pub mod policy;
+pub mod profile;
+pub mod remote_keystore;
 pub mod subnet;
+pub mod watchtower;
 
 pub mod serialize;
 #[cfg(test)]
 mod tests;
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::Path;
 
-use anyhow::{Context, Result};
+use crate::roles::KeyRole;
+use anyhow::{anyhow, Context, Result};
 use deserialize::deserialize_subnets_from_vec;
 use ipc_api::subnet_id::SubnetID;
+use policy::PolicyConfig;
+use profile::Profile;
+use remote_keystore::RemoteKeyStoreConfig;
 use serde::{Deserialize, Serialize};
 use serialize::serialize_subnets_to_str;
 pub use subnet::Subnet;
+use watchtower::WatchtowerConfig;
 
 pub const JSON_RPC_VERSION: &str = "2.0";
 
@@ -56,9 +65,25 @@ registry_addr = "0x0b4e239FF21b40120cDa817fba77bD1B366c1bcD"
 pub struct Config {
     /// Directory of the keystore that wants to be made available by the provider.
     pub keystore_path: Option<String>,
+    /// The service holding the EVM keys instead of the keystore directory, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_keystore: Option<RemoteKeyStoreConfig>,
     #[serde(deserialize_with = "deserialize_subnets_from_vec", default)]
     #[serde(serialize_with = "serialize_subnets_to_str")]
     pub subnets: HashMap<SubnetID, Subnet>,
+    /// Limits on what the keys can send, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy: Option<PolicyConfig>,
+    /// The subnets to watch and where to send their alerts, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub watchtower: Option<WatchtowerConfig>,
+    /// The role of the keys allowed to send each type of operation, by the name of the
+    /// operation, e.g. `submit_checkpoint = "relayer"`. See [crate::roles].
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub operation_roles: BTreeMap<String, KeyRole>,
+    /// Named networks the provider can be switched to, with [Config::profile].
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Profile>,
 }
 
 impl Config {
@@ -66,7 +91,12 @@ impl Config {
     pub fn new() -> Self {
         Config {
             keystore_path: None,
+            remote_keystore: None,
             subnets: Default::default(),
+            policy: None,
+            watchtower: None,
+            operation_roles: Default::default(),
+            profiles: Default::default(),
         }
     }
 
@@ -103,6 +133,27 @@ impl Config {
         Ok(())
     }
 
+    /// The config of the profile `name`: its keystore and subnets replace those at the top of
+    /// the config, which only lends it its spending policy if it has none, and the roles of
+    /// its operations.
+    pub fn profile(&self, name: &str) -> Result<Self> {
+        let profile = self.profiles.get(name).ok_or_else(|| {
+            anyhow!(
+                "unknown profile {name}, the config has: {}",
+                self.profiles.keys().cloned().collect::<Vec<_>>().join(", ")
+            )
+        })?;
+        Ok(Config {
+            keystore_path: profile.keystore_path(name, self.keystore_path.as_deref()),
+            remote_keystore: profile.remote_keystore.clone(),
+            subnets: profile.subnets.clone(),
+            policy: profile.policy.clone().or_else(|| self.policy.clone()),
+            watchtower: profile.watchtower.clone(),
+            operation_roles: self.operation_roles.clone(),
+            profiles: Default::default(),
+        })
+    }
+
     pub fn add_subnet(&mut self, subnet: Subnet) {
         self.subnets.insert(subnet.id.clone(), subnet);
     }
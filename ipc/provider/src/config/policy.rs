@@ -0,0 +1,22 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! The spending policy declared in the config.
+
+use serde::{Deserialize, Serialize};
+
+/// Limits on what the keys of the keystore can send, enforced by [crate::policy::SpendingPolicy]
+/// before anything is signed. Unset limits don't apply.
+///
+/// Amounts are in whole FIL, or carry their unit, like `"500 milliFIL"`.
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct PolicyConfig {
+    /// The largest value a single operation can send.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_value_per_tx: Option<String>,
+    /// The most each key can send per UTC day.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daily_budget: Option<String>,
+    /// The only subnets value and messages can be sent to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_destinations: Option<Vec<String>>,
+}
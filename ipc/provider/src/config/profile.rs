@@ -0,0 +1,58 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! The named profiles declared in the config.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use ipc_api::subnet_id::SubnetID;
+use serde::{Deserialize, Serialize};
+
+use crate::config::deserialize::deserialize_subnets_from_vec;
+use crate::config::policy::PolicyConfig;
+use crate::config::remote_keystore::RemoteKeyStoreConfig;
+use crate::config::serialize::serialize_subnets_to_str;
+use crate::config::watchtower::WatchtowerConfig;
+use crate::config::Subnet;
+
+/// The directory of the keystore of a profile without one, under the keystore of the config.
+pub const DEFAULT_PROFILES_DIR: &str = "profiles";
+
+/// A network the provider can be switched to as a whole, e.g. `mainnet`, `calibration` or
+/// `localnet`, so that the keys and subnets of one are never used with the other.
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Profile {
+    /// Directory of the keystore of the profile; `<keystore_path>/profiles/<name>` if unset, so
+    /// that every profile has keys of its own.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keystore_path: Option<String>,
+    /// The service holding the EVM keys of the profile instead of its keystore, if any; the
+    /// one at the top of the config is not inherited, so that profiles never share keys.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_keystore: Option<RemoteKeyStoreConfig>,
+    /// The subnets of the profile, used instead of those at the top of the config.
+    #[serde(deserialize_with = "deserialize_subnets_from_vec", default)]
+    #[serde(serialize_with = "serialize_subnets_to_str")]
+    pub subnets: HashMap<SubnetID, Subnet>,
+    /// Limits on what the keys can send; those at the top of the config if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy: Option<PolicyConfig>,
+    /// The subnets to watch and where to send their alerts, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub watchtower: Option<WatchtowerConfig>,
+}
+
+impl Profile {
+    /// The keystore of the profile `name`, given the keystore of the config.
+    pub fn keystore_path(&self, name: &str, config_keystore_path: Option<&str>) -> Option<String> {
+        self.keystore_path.clone().or_else(|| {
+            config_keystore_path.map(|path| {
+                Path::new(path)
+                    .join(DEFAULT_PROFILES_DIR)
+                    .join(name)
+                    .to_string_lossy()
+                    .into_owned()
+            })
+        })
+    }
+}
@@ -0,0 +1,68 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! The remote keystore declared in the config.
+
+use std::time::Duration;
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DurationSeconds};
+use url::Url;
+
+/// How long the keys fetched from the service are used if unset.
+const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(60);
+/// The timeout of the requests to the service if unset.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A secrets service holding the EVM keys instead of the keystore file, so that replicas of a
+/// relayer can share keys managed in one place. See [ipc_wallet::RemoteKeyStore].
+#[serde_as]
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct RemoteKeyStoreConfig {
+    /// Base URL of the service, which must be `https`.
+    pub url: Url,
+    /// The environment variable holding the bearer token of the service, so that the token
+    /// isn't written in the config.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_env: Option<String>,
+    /// Path to the PEM certificate of the only CA trusted to certify the service; the roots of
+    /// the system if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_cert: Option<String>,
+    /// How long the keys fetched from the service are used; a minute if unset.
+    #[serde_as(as = "Option<DurationSeconds<u64>>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_ttl: Option<Duration>,
+    /// The timeout of the requests to the service; 10 seconds if unset.
+    #[serde_as(as = "Option<DurationSeconds<u64>>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<Duration>,
+}
+
+impl RemoteKeyStoreConfig {
+    /// The settings of the keystore, with the token and the certificate read from where the
+    /// config points to.
+    pub fn keystore_config(&self) -> anyhow::Result<ipc_wallet::RemoteKeyStoreConfig> {
+        let token = match &self.token_env {
+            Some(var) => Some(
+                std::env::var(var)
+                    .with_context(|| format!("remote keystore token not set in {var}"))?,
+            ),
+            None => None,
+        };
+        let ca_cert = match &self.ca_cert {
+            Some(path) => Some(
+                std::fs::read(crate::expand_tilde(path))
+                    .with_context(|| format!("cannot read remote keystore CA from {path}"))?,
+            ),
+            None => None,
+        };
+        Ok(ipc_wallet::RemoteKeyStoreConfig {
+            url: self.url.clone(),
+            token,
+            ca_cert,
+            cache_ttl: self.cache_ttl.unwrap_or(DEFAULT_CACHE_TTL),
+            timeout: self.timeout.unwrap_or(DEFAULT_TIMEOUT),
+        })
+    }
+}
@@ -121,7 +121,12 @@ mod tests {
     fn test_serialization() {
         let mut config = Config {
             keystore_path: Some(String::from("~/.ipc")),
+            remote_keystore: None,
             subnets: Default::default(),
+            policy: None,
+            watchtower: None,
+            operation_roles: Default::default(),
+            profiles: Default::default(),
         };
 
         let eth_addr1 = EthAddress::from_str("0x6BE1Ccf648c74800380d0520D797a170c808b624").unwrap();
@@ -133,6 +138,11 @@ mod tests {
                 provider_timeout: None,
                 auth_token: None,
                 registry_addr: Address::from(eth_addr1),
+                admin: None,
+                response_cache: None,
+                max_concurrent_requests: None,
+                fallback_provider_http: vec![],
+                gas: None,
             }),
         };
         config.add_subnet(subnet2);
@@ -1,9 +1,14 @@
+use std::path::PathBuf;
 use std::time::Duration;
 
 // Copyright 2022-2024 Protocol Labs
 // SPDX-License-Identifier: MIT
+use anyhow::Context;
 use fvm_shared::address::Address;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
 use ipc_api::subnet_id::SubnetID;
+use ipc_api::token::TokenAmountExt;
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DurationSeconds};
 use url::Url;
@@ -56,6 +61,16 @@ impl Subnet {
         }
     }
 
+    /// The RPC endpoints of the subnet, the primary one first, then the fallbacks in the order
+    /// they are configured.
+    pub fn rpc_endpoints(&self) -> Vec<&Url> {
+        match &self.config {
+            SubnetConfig::Fevm(s) => std::iter::once(&s.provider_http)
+                .chain(&s.fallback_provider_http)
+                .collect(),
+        }
+    }
+
     pub fn rpc_timeout(&self) -> Option<Duration> {
         match &self.config {
             SubnetConfig::Fevm(s) => s.provider_timeout,
@@ -67,6 +82,45 @@ impl Subnet {
             SubnetConfig::Fevm(s) => s.gateway_addr,
         }
     }
+
+    pub fn registry_addr(&self) -> Address {
+        match &self.config {
+            SubnetConfig::Fevm(s) => s.registry_addr,
+        }
+    }
+
+    /// The multisig that administers the subnet, if its admin is not a single key.
+    pub fn admin(&self) -> Option<&MultisigConfig> {
+        match &self.config {
+            SubnetConfig::Fevm(s) => s.admin.as_ref(),
+        }
+    }
+
+    /// The on-disk cache of the responses about the finalized history of the subnet, if enabled.
+    pub fn response_cache(&self) -> Option<&ResponseCacheConfig> {
+        match &self.config {
+            SubnetConfig::Fevm(s) => s.response_cache.as_ref(),
+        }
+    }
+
+    /// The maximum number of requests each connection to the subnet has in flight, if limited.
+    pub fn max_concurrent_requests(&self) -> Option<usize> {
+        match &self.config {
+            SubnetConfig::Fevm(s) => s.max_concurrent_requests,
+        }
+    }
+
+    /// The gas fees overridden for the transactions sent to the subnet; none if unset.
+    pub fn gas_overrides(&self) -> anyhow::Result<GasOverrides> {
+        match &self.config {
+            SubnetConfig::Fevm(s) => s
+                .gas
+                .as_ref()
+                .map(GasOverrides::try_from)
+                .transpose()
+                .map(Option::unwrap_or_default),
+        }
+    }
 }
 
 /// The FVM subnet config parameters
@@ -95,4 +149,105 @@ pub struct EVMSubnet {
     #[serde(deserialize_with = "deserialize_eth_address_from_str")]
     #[serde(serialize_with = "serialize_eth_address_to_str")]
     pub gateway_addr: Address,
+
+    /// Set if the subnet actor is owned by a multisig, whose administrative operations are
+    /// proposed to it rather than sent directly.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub admin: Option<MultisigConfig>,
+
+    /// Set to persist the responses about the finalized history of the subnet, so they aren't
+    /// fetched again after a restart.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_cache: Option<ResponseCacheConfig>,
+
+    /// Set to limit the number of requests each connection to the subnet has in flight, so one
+    /// busy consumer of the provider can't take up the capacity of the endpoint.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrent_requests: Option<usize>,
+
+    /// Endpoints serving the same subnet, used when `provider_http` is degraded or down.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallback_provider_http: Vec<Url>,
+
+    /// Set to override the estimated gas fees of the transactions sent to the subnet, for
+    /// subnets whose fee floors differ from what their endpoints estimate.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas: Option<GasConfig>,
+}
+
+/// The kinds of multisig that can administer a subnet.
+#[derive(
+    Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq, strum::EnumString, strum::Display,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum MultisigKind {
+    /// A Safe-style multisig contract, with owners approving transaction hashes.
+    Safe,
+    /// A Filecoin built-in multisig actor.
+    Msig,
+}
+
+/// A multisig administering a subnet, declared in the config of the subnet.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct MultisigConfig {
+    pub kind: MultisigKind,
+    /// The address of the multisig in the parent: a `0x` address for a Safe, an `f2` or `t2`
+    /// address for a Filecoin msig.
+    pub address: String,
+}
+
+/// The on-disk cache of the responses of a subnet, declared in the config of the subnet.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct ResponseCacheConfig {
+    /// The number of blocks behind the chain head after which blocks are final, and the
+    /// responses about them can be cached.
+    pub finality: ChainEpoch,
+    /// The file the responses are persisted to; one per subnet in the keystore directory if
+    /// unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+}
+
+/// The gas fees of the transactions sent to a subnet, overriding the estimated ones. Unset fees
+/// are still estimated.
+///
+/// Amounts are per unit of gas, in whole FIL, or carry their unit, like `"2 gwei"`.
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct GasConfig {
+    /// The most paid per unit of gas: the `GasFeeCap` of Filecoin messages, and the
+    /// `maxFeePerGas` of EIP-1559 transactions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_cap: Option<String>,
+    /// The `GasPremium` paid to the miner of Filecoin messages.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub premium: Option<String>,
+    /// The `maxPriorityFeePerGas` paid to the validator of EIP-1559 transactions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tip: Option<String>,
+}
+
+/// The fees of a [GasConfig], parsed.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GasOverrides {
+    pub fee_cap: Option<TokenAmount>,
+    pub premium: Option<TokenAmount>,
+    pub tip: Option<TokenAmount>,
+}
+
+impl TryFrom<&GasConfig> for GasOverrides {
+    type Error = anyhow::Error;
+
+    fn try_from(config: &GasConfig) -> Result<Self, Self::Error> {
+        let amount = |s: &Option<String>| {
+            s.as_deref()
+                .map(TokenAmount::from_denominated_str)
+                .transpose()
+        };
+        Ok(Self {
+            fee_cap: amount(&config.fee_cap).context("invalid gas fee_cap")?,
+            premium: amount(&config.premium).context("invalid gas premium")?,
+            tip: amount(&config.tip).context("invalid gas tip")?,
+        })
+    }
 }
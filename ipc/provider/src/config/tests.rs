@@ -1,13 +1,18 @@
 // Copyright 2022-2024 Protocol Labs
 // SPDX-License-Identifier: MIT
 use std::str::FromStr;
+use std::time::Duration;
 
 use fvm_shared::address::Address;
+use fvm_shared::econ::TokenAmount;
 use indoc::formatdoc;
 use ipc_api::subnet_id::SubnetID;
 use ipc_types::EthAddress;
 use url::Url;
 
+use crate::config::policy::PolicyConfig;
+use crate::config::subnet::{GasOverrides, MultisigConfig, MultisigKind, ResponseCacheConfig};
+use crate::config::watchtower::{AlertSinkConfig, WatchedSubnetConfig, WatchtowerConfig};
 use crate::config::Config;
 
 // Arguments for the config's fields
@@ -40,6 +45,292 @@ fn check_subnets_config() {
     );
     assert_eq!(*child.rpc_http(), Url::from_str(PROVIDER_HTTP).unwrap(),);
     assert_eq!(child.auth_token().as_ref().unwrap(), CHILD_AUTH_TOKEN);
+    assert_eq!(child.admin(), None);
+    assert_eq!(child.response_cache(), None);
+    assert_eq!(child.max_concurrent_requests(), None);
+}
+
+#[test]
+fn check_subnet_admin_config() {
+    let config = formatdoc!(
+        r#"
+        {}
+        [subnets.config.admin]
+        kind = "safe"
+        address = "{ETH_ADDRESS}"
+        "#,
+        config_str()
+    );
+    let config = Config::from_toml_str(&config).unwrap().subnets;
+
+    let child = &config[&SubnetID::from_str(CHILD_ID).unwrap()];
+    assert_eq!(
+        child.admin(),
+        Some(&MultisigConfig {
+            kind: MultisigKind::Safe,
+            address: ETH_ADDRESS.to_string(),
+        })
+    );
+}
+
+#[test]
+fn check_subnet_response_cache_config() {
+    let config = formatdoc!(
+        r#"
+        {}
+        [subnets.config.response_cache]
+        finality = 900
+        "#,
+        config_str()
+    );
+    let config = Config::from_toml_str(&config).unwrap();
+    let child = &config.subnets[&SubnetID::from_str(CHILD_ID).unwrap()];
+    assert_eq!(
+        child.response_cache(),
+        Some(&ResponseCacheConfig {
+            finality: 900,
+            path: None,
+        })
+    );
+
+    let cache = crate::new_response_cache_from_config(&config, child)
+        .unwrap()
+        .unwrap();
+    assert!(cache.path().ends_with("rpc-cache/r123_f0100.jsonl"));
+}
+
+#[test]
+fn check_subnet_max_concurrent_requests_config() {
+    let config = formatdoc!(
+        r#"
+        {}
+        max_concurrent_requests = 4
+        "#,
+        config_str()
+    );
+    let config = Config::from_toml_str(&config).unwrap();
+    let child_id = SubnetID::from_str(CHILD_ID).unwrap();
+    let child = &config.subnets[&child_id];
+    assert_eq!(child.max_concurrent_requests(), Some(4));
+
+    let provider = crate::IpcProvider::new_with_subnet(None, child.clone()).unwrap();
+    let conn = provider.connection(&child_id).unwrap();
+    assert_eq!(conn.available_requests(), Some(4));
+}
+
+#[test]
+fn check_subnet_fallback_endpoints_config() {
+    let child_id = SubnetID::from_str(CHILD_ID).unwrap();
+    let config = read_config();
+    assert_eq!(config.subnets[&child_id].rpc_endpoints().len(), 1);
+
+    let config = formatdoc!(
+        r#"
+        {}
+        fallback_provider_http = ["http://127.0.0.1:3031/rpc/v1", "http://127.0.0.1:3032/rpc/v1"]
+        "#,
+        config_str()
+    );
+    let config = Config::from_toml_str(&config).unwrap();
+    let endpoints = config.subnets[&child_id]
+        .rpc_endpoints()
+        .into_iter()
+        .map(|url| url.port().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(endpoints, vec![3030, 3031, 3032]);
+}
+
+#[test]
+fn check_subnet_gas_config() {
+    let child_id = SubnetID::from_str(CHILD_ID).unwrap();
+    let config = read_config();
+    assert_eq!(
+        config.subnets[&child_id].gas_overrides().unwrap(),
+        GasOverrides::default()
+    );
+
+    let config = formatdoc!(
+        r#"
+        {}
+        [subnets.config.gas]
+        fee_cap = "2 gwei"
+        tip = "100 attoFIL"
+        "#,
+        config_str()
+    );
+    let config = Config::from_toml_str(&config).unwrap();
+    assert_eq!(
+        config.subnets[&child_id].gas_overrides().unwrap(),
+        GasOverrides {
+            fee_cap: Some(TokenAmount::from_nano(2)),
+            premium: None,
+            tip: Some(TokenAmount::from_atto(100)),
+        }
+    );
+
+    let config = formatdoc!(
+        r#"
+        {}
+        [subnets.config.gas]
+        premium = "2 DOGE"
+        "#,
+        config_str()
+    );
+    let config = Config::from_toml_str(&config).unwrap();
+    assert!(config.subnets[&child_id].gas_overrides().is_err());
+}
+
+#[test]
+fn check_policy_config() {
+    assert_eq!(read_config().policy, None);
+
+    let config = formatdoc!(
+        r#"
+        {}
+        [policy]
+        max_value_per_tx = "10"
+        daily_budget = "500 milliFIL"
+        allowed_destinations = ["{CHILD_ID}"]
+        "#,
+        config_str()
+    );
+    let config = Config::from_toml_str(&config).unwrap();
+    assert_eq!(
+        config.policy,
+        Some(PolicyConfig {
+            max_value_per_tx: Some("10".to_string()),
+            daily_budget: Some("500 milliFIL".to_string()),
+            allowed_destinations: Some(vec![CHILD_ID.to_string()]),
+        })
+    );
+}
+
+#[test]
+fn check_watchtower_config() {
+    assert_eq!(read_config().watchtower, None);
+
+    let config = formatdoc!(
+        r#"
+        {}
+        [watchtower]
+        interval = 30
+
+        [[watchtower.sinks]]
+        type = "slack"
+        webhook_url = "https://hooks.slack.com/services/T0/B0/X"
+
+        [[watchtower.sinks]]
+        type = "pagerduty"
+        routing_key = "ROUTING_KEY"
+
+        [[watchtower.subnets]]
+        id = "{CHILD_ID}"
+        max_checkpoint_lag = 100
+        alert_after = 3
+        "#,
+        config_str()
+    );
+    let config = Config::from_toml_str(&config).unwrap();
+    assert_eq!(
+        config.watchtower,
+        Some(WatchtowerConfig {
+            interval: Some(Duration::from_secs(30)),
+            sinks: vec![
+                AlertSinkConfig::Slack {
+                    webhook_url: Url::parse("https://hooks.slack.com/services/T0/B0/X").unwrap(),
+                },
+                AlertSinkConfig::Pagerduty {
+                    routing_key: "ROUTING_KEY".to_string(),
+                    url: None,
+                },
+            ],
+            subnets: vec![WatchedSubnetConfig {
+                id: CHILD_ID.to_string(),
+                max_checkpoint_lag: Some(100),
+                min_active_validators: None,
+                check_solvency: true,
+                alert_after: Some(3),
+            }],
+        })
+    );
+}
+
+#[test]
+fn check_profiles_config() {
+    let config = formatdoc!(
+        r#"
+        {}
+        [policy]
+        max_value_per_tx = "10"
+
+        [profiles.localnet]
+        keystore_path = "/tmp/localnet"
+
+        [[profiles.localnet.subnets]]
+        id = "/r31337"
+
+        [profiles.localnet.subnets.config]
+        network_type = "fevm"
+        provider_http = "{PROVIDER_HTTP}"
+        registry_addr = "{ETH_ADDRESS}"
+        gateway_addr = "{ETH_ADDRESS}"
+
+        [profiles.calibration]
+        "#,
+        config_str()
+    );
+    let config = Config::from_toml_str(&config).unwrap();
+
+    let localnet = config.profile("localnet").unwrap();
+    assert_eq!(localnet.keystore_path, Some("/tmp/localnet".to_string()));
+    assert_eq!(
+        localnet.subnets.keys().collect::<Vec<_>>(),
+        vec![&SubnetID::new_root(31337)]
+    );
+    assert_eq!(localnet.policy, config.policy);
+    assert!(localnet.profiles.is_empty());
+
+    // A profile without a keystore has one of its own, and none of the subnets at the top.
+    let calibration = config.profile("calibration").unwrap();
+    assert_eq!(
+        calibration.keystore_path,
+        Some(format!("{REPO_PATH}/profiles/calibration"))
+    );
+    assert!(calibration.subnets.is_empty());
+
+    assert!(config.profile("mainnet").is_err());
+}
+
+#[test]
+fn check_remote_keystore_config() {
+    let config = formatdoc!(
+        r#"
+        {}
+        [remote_keystore]
+        url = "https://secrets.example.com/ipc"
+        token_env = "IPC_KEYSTORE_TOKEN"
+        cache_ttl = 30
+
+        [profiles.localnet]
+        "#,
+        config_str()
+    );
+    let config = Config::from_toml_str(&config).unwrap();
+
+    let remote = config.remote_keystore.as_ref().unwrap();
+    assert_eq!(
+        remote.url,
+        Url::parse("https://secrets.example.com/ipc").unwrap()
+    );
+    assert_eq!(remote.cache_ttl, Some(Duration::from_secs(30)));
+    assert_eq!(remote.timeout, None);
+
+    // Profiles don't share the keys at the top of the config.
+    assert!(config
+        .profile("localnet")
+        .unwrap()
+        .remote_keystore
+        .is_none());
 }
 
 fn config_str() -> String {
@@ -0,0 +1,65 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! The watchtower declared in the config.
+
+use std::time::Duration;
+
+use fvm_shared::clock::ChainEpoch;
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DurationSeconds};
+use url::Url;
+
+/// The subnets [crate::watchtower::Watchtower] checks, and where it sends its alerts.
+#[serde_as]
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct WatchtowerConfig {
+    /// How often the subnets are checked; every minute if unset.
+    #[serde_as(as = "Option<DurationSeconds<u64>>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval: Option<Duration>,
+    #[serde(default)]
+    pub sinks: Vec<AlertSinkConfig>,
+    #[serde(default)]
+    pub subnets: Vec<WatchedSubnetConfig>,
+}
+
+/// Where alerts are pushed.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum AlertSinkConfig {
+    /// Posts every alert as JSON to the URL.
+    Webhook { url: Url },
+    /// Posts every alert as a message to a Slack incoming webhook.
+    Slack { webhook_url: Url },
+    /// Triggers and resolves incidents with the PagerDuty Events API v2, or a compatible one.
+    Pagerduty {
+        routing_key: String,
+        /// The PagerDuty events endpoint if unset.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        url: Option<Url>,
+    },
+}
+
+/// A subnet to check, with its thresholds. Unset thresholds fall back to the defaults.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct WatchedSubnetConfig {
+    pub id: String,
+    /// The epochs the last bottom-up checkpoint can lag behind the head of the subnet; two
+    /// checkpoint periods if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_checkpoint_lag: Option<ChainEpoch>,
+    /// The active validators under which the subnet is reported; the minimum of the subnet
+    /// actor if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_active_validators: Option<u16>,
+    /// Whether to audit the supply of the subnet, which has to run Fendermint.
+    #[serde(default = "default_check_solvency")]
+    pub check_solvency: bool,
+    /// The consecutive failed checks before an alert is sent; once if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alert_after: Option<u32>,
+}
+
+fn default_check_solvency() -> bool {
+    true
+}
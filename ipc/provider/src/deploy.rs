@@ -0,0 +1,159 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Deployment of application contracts on FEVM subnets.
+//!
+//! Contracts are either created by a plain transaction, at an address depending on the nonce
+//! of the sender, or with `CREATE2` through the deterministic deployment proxy, at an address
+//! depending only on a salt and the init code, which is the same in every subnet the proxy is
+//! deployed in.
+//!
+//! Subnet actors are created by the registry of their parent with `CREATE`, so the address of
+//! the next one only depends on the nonce of the registry, and can be predicted ahead of
+//! [crate::IpcProvider::create_subnet].
+
+use std::str::FromStr;
+
+use ethers::types::Address;
+use fvm_shared::address::Payload;
+use ipc_api::ethers_address_to_fil_address;
+use ipc_api::receipt::TxReceipt;
+use ipc_api::subnet_id::SubnetID;
+use serde::Serialize;
+
+use crate::lotus::message::serialize::serialize_subnet_id_to_str;
+
+/// The address of the deterministic deployment proxy, which creates the init code following a
+/// 32 bytes salt in its calldata with `CREATE2`. It has to be deployed in the subnet first,
+/// with its presigned transaction.
+pub const CREATE2_FACTORY: &str = "0x4e59b44847b379578588920cA78FbF26c0B4956C";
+
+/// A contract deployed in a subnet.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeployedContract {
+    pub address: Address,
+    #[serde(flatten)]
+    pub receipt: TxReceipt,
+}
+
+/// The code run to create a contract: its bytecode followed by the ABI encoded arguments of
+/// its constructor.
+pub fn init_code(bytecode: &[u8], constructor_args: &[u8]) -> Vec<u8> {
+    [bytecode, constructor_args].concat()
+}
+
+/// The address of the deterministic deployment proxy.
+pub fn create2_factory() -> Address {
+    Address::from_str(CREATE2_FACTORY).expect("valid factory address")
+}
+
+/// The address the deterministic deployment proxy creates `init_code` at with `salt`.
+pub fn create2_address(salt: [u8; 32], init_code: &[u8]) -> Address {
+    ethers::utils::get_create2_address(create2_factory(), salt, init_code)
+}
+
+/// The address a contract created with `CREATE` by `creator` with `nonce` is deployed at.
+pub fn create_address(creator: Address, nonce: u64) -> Address {
+    ethers::utils::get_contract_address(creator, nonce)
+}
+
+/// A subnet actor before it's created, and the subnet it would be the actor of.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PredictedSubnet {
+    #[serde(serialize_with = "serialize_subnet_id_to_str")]
+    pub subnet_id: SubnetID,
+    pub address: Address,
+    /// The account creating the subnet actor, the registry of the parent for
+    /// [crate::IpcProvider::create_subnet].
+    pub creator: Address,
+    /// The nonce of the creator the prediction holds for.
+    pub nonce: u64,
+}
+
+/// The subnet actor `creator` creates in `parent` with `nonce`.
+///
+/// FEVM derives the addresses of the contracts created by an actor from its `f410` address,
+/// like the EVM does from the `0x` one, so `creator` can't be given by its actor ID. The actor
+/// ID of the subnet actor is only assigned when it's created, which is why subnets are named
+/// after the `f410` address of their actor.
+pub fn predict_subnet_address(
+    parent: &SubnetID,
+    creator: &fvm_shared::address::Address,
+    nonce: u64,
+) -> anyhow::Result<PredictedSubnet> {
+    let creator = match creator.payload() {
+        Payload::Delegated(_) => ipc_api::evm::payload_to_evm_address(creator.payload())?,
+        _ => {
+            return Err(anyhow::anyhow!(
+                "the creator {creator} has to be given by its f410 or 0x address, which the address of the subnet actor derives from"
+            ))
+        }
+    };
+    let address = create_address(creator, nonce);
+    Ok(PredictedSubnet {
+        subnet_id: SubnetID::new_from_parent(parent, ethers_address_to_fil_address(&address)?),
+        address,
+        creator,
+        nonce,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use ethers::types::Address;
+    use ipc_api::ethers_address_to_fil_address;
+    use ipc_api::subnet_id::SubnetID;
+
+    use super::{create2_address, create_address, init_code, predict_subnet_address};
+
+    #[test]
+    fn test_create2_address() {
+        // Vector from EIP-1014, for a factory at the zero address.
+        let address = ethers::utils::get_create2_address(Address::zero(), [0u8; 32], [0u8]);
+        assert_eq!(
+            address,
+            Address::from_str("0x4D1A2e2bB4F88F0250f26Ffff098B0b30B26BF38").unwrap()
+        );
+
+        let code = init_code(&[0x60, 0x80], &[0u8; 32]);
+        assert_eq!(code.len(), 34);
+
+        // The address only depends on the salt and the init code.
+        let a = create2_address([1u8; 32], &code);
+        assert_eq!(a, create2_address([1u8; 32], &code));
+        assert_ne!(a, create2_address([2u8; 32], &code));
+        assert_ne!(a, create2_address([1u8; 32], &code[..2]));
+    }
+
+    #[test]
+    fn test_predict_subnet_address() {
+        // The first contract created by the account.
+        let creator = Address::from_str("0x6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0").unwrap();
+        assert_eq!(
+            create_address(creator, 0),
+            Address::from_str("0xcd234a471b72ba2f1ccf0a70fcaba648a5eecd8d").unwrap()
+        );
+
+        let parent = SubnetID::from_str("/r314159").unwrap();
+        let predicted = predict_subnet_address(
+            &parent,
+            &ethers_address_to_fil_address(&creator).unwrap(),
+            1,
+        )
+        .unwrap();
+        assert_eq!(predicted.address, create_address(creator, 1));
+        assert_eq!(predicted.subnet_id.parent(), Some(parent.clone()));
+        assert_eq!(
+            predicted.subnet_id,
+            SubnetID::new_from_parent(
+                &parent,
+                ethers_address_to_fil_address(&predicted.address).unwrap()
+            )
+        );
+
+        // An actor ID doesn't tell the address contracts are created from.
+        let id = fvm_shared::address::Address::new_id(1000);
+        assert!(predict_subnet_address(&parent, &id, 1).is_err());
+    }
+}
@@ -0,0 +1,412 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Diagnostics of the connection to a subnet, to find out why operations fail.
+
+use std::fmt::{Display, Formatter};
+
+use fvm_shared::{clock::ChainEpoch, econ::TokenAmount};
+use ipc_api::subnet_id::SubnetID;
+use serde::Serialize;
+
+use crate::config;
+use crate::health::EndpointStatus;
+use crate::manager::SubnetManager;
+use crate::IpcProvider;
+
+/// Balance under which the default account is reported as running low.
+const LOW_BALANCE_WHOLE_FIL: u64 = 1;
+/// Number of checkpoint periods the parent can lag behind before it's reported.
+pub(crate) const MAX_CHECKPOINT_LAG_PERIODS: ChainEpoch = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Ok,
+    Warn,
+    Fail,
+    /// The check could not run, because of an earlier failure or missing configuration.
+    Skip,
+}
+
+impl Display for CheckStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            CheckStatus::Ok => "ok",
+            CheckStatus::Warn => "warn",
+            CheckStatus::Fail => "fail",
+            CheckStatus::Skip => "skip",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// The outcome of a single check, with a hint on how to fix it if it didn't pass.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Check {
+    pub name: String,
+    pub status: CheckStatus,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+impl Check {
+    fn new(name: &str, status: CheckStatus, message: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status,
+            message: message.into(),
+            hint: None,
+        }
+    }
+
+    fn ok(name: &str, message: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Ok, message)
+    }
+
+    fn skip(name: &str, message: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Skip, message)
+    }
+
+    fn warn(name: &str, message: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Warn, message).with_hint(hint)
+    }
+
+    fn fail(name: &str, message: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Fail, message).with_hint(hint)
+    }
+
+    fn with_hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+}
+
+/// The checks run against a subnet by [IpcProvider::doctor].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DoctorReport {
+    pub subnet: String,
+    pub checks: Vec<Check>,
+}
+
+impl DoctorReport {
+    /// True if none of the checks failed; warnings don't prevent operations.
+    pub fn is_healthy(&self) -> bool {
+        self.checks.iter().all(|c| c.status != CheckStatus::Fail)
+    }
+}
+
+impl Display for DoctorReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "subnet {}", self.subnet)?;
+        for check in &self.checks {
+            writeln!(f, "[{}] {}: {}", check.status, check.name, check.message)?;
+            if let Some(hint) = &check.hint {
+                writeln!(f, "       hint: {hint}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether an RPC error means the node rejected the credentials rather than being unreachable.
+fn is_auth_error(e: &anyhow::Error) -> bool {
+    let msg = format!("{e:#}").to_lowercase();
+    msg.contains("401") || msg.contains("403") || msg.contains("unauthorized")
+}
+
+impl IpcProvider {
+    /// Run a battery of checks against the configuration and the network of `subnet`, and
+    /// report how to remediate the ones that fail.
+    ///
+    /// Only fails if the report itself can't be put together; failed checks are in the report.
+    pub async fn doctor(&self, subnet: &SubnetID) -> anyhow::Result<DoctorReport> {
+        let mut checks = Vec::new();
+        let report = |checks: Vec<Check>| DoctorReport {
+            subnet: subnet.to_string(),
+            checks,
+        };
+
+        let Some(conn) = self.connection(subnet) else {
+            checks.push(Check::fail(
+                "config",
+                "subnet not found in the config",
+                "add the subnet to the config with `ipc-cli config init` or by editing config.toml",
+            ));
+            return Ok(report(checks));
+        };
+        let config = conn.subnet();
+        checks.push(Check::ok(
+            "config",
+            format!("rpc endpoint {}", config.rpc_http()),
+        ));
+        checks.push(self.check_endpoints(config).await);
+
+        let head = match self.chain_head(subnet).await {
+            Ok(head) => {
+                checks.push(Check::ok("rpc", format!("reachable, chain head at {head}")));
+                let auth = if config.auth_token().is_some() {
+                    "auth token accepted"
+                } else {
+                    "no auth token configured"
+                };
+                checks.push(Check::ok("auth", auth));
+                head
+            }
+            Err(e) if is_auth_error(&e) => {
+                checks.push(Check::ok("rpc", "reachable"));
+                checks.push(Check::fail(
+                    "auth",
+                    format!("the node rejected the request: {e:#}"),
+                    "set a valid `auth_token` for the subnet in config.toml",
+                ));
+                return Ok(report(checks));
+            }
+            Err(e) => {
+                checks.push(Check::fail(
+                    "rpc",
+                    format!("cannot reach {}: {e:#}", config.rpc_http()),
+                    "check `provider_http` in config.toml and that the node is running",
+                ));
+                checks.push(Check::skip("auth", "the rpc endpoint is unreachable"));
+                return Ok(report(checks));
+            }
+        };
+
+        checks.push(self.check_chain_id(subnet).await);
+        checks.push(
+            check_contract(
+                conn.manager(),
+                "gateway",
+                &config.gateway_addr(),
+                "gateway_addr",
+            )
+            .await,
+        );
+        checks.push(
+            check_contract(
+                conn.manager(),
+                "registry",
+                &config.registry_addr(),
+                "registry_addr",
+            )
+            .await,
+        );
+        checks.push(self.check_balance(subnet).await);
+        checks.push(self.check_checkpoint_lag(subnet, head).await);
+
+        Ok(report(checks))
+    }
+
+    /// Probe every endpoint of the subnet, so the following checks go through a healthy one.
+    async fn check_endpoints(&self, config: &config::Subnet) -> Check {
+        self.health.probe([config]).await;
+        let subnet = config.id.to_string();
+        let endpoints = self
+            .health
+            .health()
+            .into_iter()
+            .filter(|h| h.subnet == subnet)
+            .collect::<Vec<_>>();
+
+        let unhealthy = endpoints
+            .iter()
+            .filter(|h| h.status != EndpointStatus::Healthy)
+            .map(|h| format!("{} is {}", h.url, h.status))
+            .collect::<Vec<_>>();
+        let message = format!(
+            "{} of {} endpoints healthy",
+            endpoints.len() - unhealthy.len(),
+            endpoints.len()
+        );
+
+        if unhealthy.is_empty() {
+            Check::ok("endpoints", message)
+        } else if unhealthy.len() < endpoints.len() {
+            Check::warn(
+                "endpoints",
+                format!("{message}; {}", unhealthy.join(", ")),
+                "requests are routed to the healthy endpoints; check the nodes of the others",
+            )
+        } else {
+            Check::fail(
+                "endpoints",
+                format!("{message}; {}", unhealthy.join(", ")),
+                "check that the nodes are running and synced, or add `fallback_provider_http`",
+            )
+        }
+    }
+
+    async fn check_chain_id(&self, subnet: &SubnetID) -> Check {
+        let expected = subnet.chain_id();
+        match self.get_chain_id(subnet).await {
+            Ok(chain_id) if chain_id == expected.to_string() => {
+                Check::ok("chain_id", format!("{chain_id} matches the subnet ID"))
+            }
+            Ok(chain_id) => Check::fail(
+                "chain_id",
+                format!("the node reports chain ID {chain_id}, expected {expected} for {subnet}"),
+                "`provider_http` points to the node of another network",
+            ),
+            Err(e) => Check::fail(
+                "chain_id",
+                format!("cannot get the chain ID: {e:#}"),
+                "check that the node serves the Ethereum JSON-RPC API",
+            ),
+        }
+    }
+
+    async fn check_balance(&self, subnet: &SubnetID) -> Check {
+        let sender = match self.sender {
+            Some(sender) => Ok(Some(sender)),
+            None => self.evm_wallet().and_then(|wallet| {
+                let default = wallet.write().unwrap().get_default()?;
+                let default = default
+                    .map(fvm_shared::address::Address::try_from)
+                    .transpose()?;
+                Ok(default)
+            }),
+        };
+
+        let sender = match sender {
+            Ok(Some(sender)) => sender,
+            Ok(None) => {
+                return Check::warn(
+                    "balance",
+                    "no default account to send transactions from",
+                    "set one with `ipc-cli wallet set-default`",
+                )
+            }
+            Err(e) => {
+                return Check::warn(
+                    "balance",
+                    format!("cannot read the wallet: {e:#}"),
+                    "check `keystore_path` in config.toml",
+                )
+            }
+        };
+
+        match self.wallet_balance(subnet, &sender).await {
+            Ok(balance) if balance.is_zero() => Check::fail(
+                "balance",
+                format!("{sender} has no funds to pay for gas"),
+                "fund the address, e.g. with `ipc-cli cross-msg fund` from the parent",
+            ),
+            Ok(balance) if balance < TokenAmount::from_whole(LOW_BALANCE_WHOLE_FIL) => Check::warn(
+                "balance",
+                format!("{sender} is running low at {balance} FIL"),
+                "top up the address before sending more transactions",
+            ),
+            Ok(balance) => Check::ok("balance", format!("{sender} has {balance} FIL")),
+            Err(e) => Check::fail(
+                "balance",
+                format!("cannot get the balance of {sender}: {e:#}"),
+                "check that the node is synced",
+            ),
+        }
+    }
+
+    /// How far the last checkpoint committed in the parent is behind the head of the subnet.
+    async fn check_checkpoint_lag(&self, subnet: &SubnetID, head: ChainEpoch) -> Check {
+        let Some(parent) = subnet.parent() else {
+            return Check::skip("checkpoint_lag", "the root network has no checkpoints");
+        };
+        if self.connection(&parent).is_none() {
+            return Check::skip(
+                "checkpoint_lag",
+                format!("the parent {parent} is not in the config"),
+            );
+        }
+
+        let lag = async {
+            let last = self.last_bottom_up_checkpoint_height(subnet).await?;
+            let period = self
+                .get_genesis_info(subnet)
+                .await?
+                .bottom_up_checkpoint_period;
+            anyhow::Ok((last, period as ChainEpoch))
+        };
+
+        match lag.await {
+            Ok((last, period)) if head - last > MAX_CHECKPOINT_LAG_PERIODS * period => {
+                Check::warn(
+                    "checkpoint_lag",
+                    format!(
+                        "last checkpoint committed at {last}, {} epochs behind with a period of {period}",
+                        head - last
+                    ),
+                    "make sure a relayer is running with `ipc-cli checkpoint relayer`",
+                )
+            }
+            Ok((last, _)) => Check::ok(
+                "checkpoint_lag",
+                format!("last checkpoint committed at {last}"),
+            ),
+            Err(e) => Check::fail(
+                "checkpoint_lag",
+                format!("cannot get the checkpoints from the parent: {e:#}"),
+                "check the config of the parent subnet",
+            ),
+        }
+    }
+}
+
+async fn check_contract(
+    manager: &dyn SubnetManager,
+    name: &str,
+    address: &fvm_shared::address::Address,
+    config_key: &str,
+) -> Check {
+    match manager.contract_code_size(address).await {
+        Ok(0) => Check::fail(
+            name,
+            format!("no contract deployed at {address}"),
+            format!("check `{config_key}` in config.toml against the deployment"),
+        ),
+        Ok(size) => Check::ok(name, format!("{address} has {size} bytes of code")),
+        Err(e) => Check::fail(
+            name,
+            format!("cannot get the code at {address}: {e:#}"),
+            "check that the node serves the Ethereum JSON-RPC API",
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_auth_error, Check, CheckStatus, DoctorReport};
+
+    #[test]
+    fn report_health() {
+        let mut report = DoctorReport {
+            subnet: "/r314159".to_string(),
+            checks: vec![
+                Check::ok("rpc", "reachable"),
+                Check::warn("balance", "running low", "top up"),
+            ],
+        };
+        assert!(report.is_healthy());
+
+        report
+            .checks
+            .push(Check::fail("gateway", "no contract", "check gateway_addr"));
+        assert!(!report.is_healthy());
+
+        let text = report.to_string();
+        assert!(text.contains("[fail] gateway: no contract"));
+        assert!(text.contains("hint: check gateway_addr"));
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["checks"][2]["status"], "fail");
+        assert!(json["checks"][0].get("hint").is_none());
+        assert_eq!(report.checks[0].status, CheckStatus::Ok);
+    }
+
+    #[test]
+    fn detect_auth_errors() {
+        assert!(is_auth_error(&anyhow::anyhow!(
+            "(code: 401, message: Unauthorized)"
+        )));
+        assert!(!is_auth_error(&anyhow::anyhow!("connection refused")));
+    }
+}
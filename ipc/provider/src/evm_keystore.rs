@@ -0,0 +1,59 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! The EVM keystore of the provider, selected in the config.
+
+use anyhow::Result;
+use ipc_wallet::{EthKeyAddress, EvmKeyInfo, EvmKeyStore, PersistentKeyStore, RemoteKeyStore};
+
+/// The EVM keys are either in a file of the keystore directory, or in a remote secrets service
+/// shared by several hosts if the config declares one.
+pub enum EvmKeyStoreBackend {
+    File(PersistentKeyStore<EthKeyAddress>),
+    Remote(RemoteKeyStore<EthKeyAddress>),
+}
+
+impl EvmKeyStore for EvmKeyStoreBackend {
+    type Key = EthKeyAddress;
+
+    fn get(&self, addr: &Self::Key) -> Result<Option<EvmKeyInfo>> {
+        match self {
+            Self::File(ks) => ks.get(addr),
+            Self::Remote(ks) => ks.get(addr),
+        }
+    }
+
+    fn list(&self) -> Result<Vec<Self::Key>> {
+        match self {
+            Self::File(ks) => ks.list(),
+            Self::Remote(ks) => ks.list(),
+        }
+    }
+
+    fn put(&mut self, info: EvmKeyInfo) -> Result<Self::Key> {
+        match self {
+            Self::File(ks) => ks.put(info),
+            Self::Remote(ks) => ks.put(info),
+        }
+    }
+
+    fn remove(&mut self, addr: &Self::Key) -> Result<()> {
+        match self {
+            Self::File(ks) => ks.remove(addr),
+            Self::Remote(ks) => ks.remove(addr),
+        }
+    }
+
+    fn set_default(&mut self, addr: &Self::Key) -> Result<()> {
+        match self {
+            Self::File(ks) => ks.set_default(addr),
+            Self::Remote(ks) => ks.set_default(addr),
+        }
+    }
+
+    fn get_default(&mut self) -> Result<Option<Self::Key>> {
+        match self {
+            Self::File(ks) => ks.get_default(),
+            Self::Remote(ks) => ks.get_default(),
+        }
+    }
+}
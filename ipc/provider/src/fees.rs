@@ -0,0 +1,195 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Estimation of what a cross-net message costs to send over all the hops of its route.
+//!
+//! The sender pays for the `fund` or `release` transaction moving the message over its first
+//! hop, and someone has to pay for a `propagate` transaction in every subnet the message goes
+//! through on the way to its destination. The gas of these transactions can't be estimated by
+//! the nodes before the message reaches each hop, so it's taken from the typical usage of the
+//! gateway methods on FEVM, priced at the current gas price of each subnet.
+
+use std::fmt::{Display, Formatter};
+
+use fvm_shared::econ::TokenAmount;
+use serde::Serialize;
+
+use crate::lotus::message::serialize::serialize_token_amount_to_atto;
+use crate::routing::{Direction, Hop};
+
+/// Gas typically used by `fund`, without the payload.
+pub const FUND_GAS: u64 = 35_000_000;
+/// Gas typically used by `release`, without the payload.
+pub const RELEASE_GAS: u64 = 35_000_000;
+/// Gas typically used by `propagate`, without the payload.
+pub const PROPAGATE_GAS: u64 = 50_000_000;
+/// Gas added by every byte of the payload, which is stored at each hop.
+pub const PAYLOAD_GAS_PER_BYTE: u64 = 20_000;
+
+/// The transaction moving a message over a hop of its route.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, strum::Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum CrossMsgStep {
+    Fund,
+    Release,
+    Propagate,
+}
+
+impl CrossMsgStep {
+    /// The step moving a message over the hop at `index` of its route: the sender funds or
+    /// releases the first, and the others are propagated from the postbox of each subnet.
+    pub fn of_hop(index: usize, hop: &Hop) -> Self {
+        match (index, hop.direction) {
+            (0, Direction::Down) => Self::Fund,
+            (0, Direction::Up) => Self::Release,
+            _ => Self::Propagate,
+        }
+    }
+
+    /// The gas the step is expected to use for a message with a payload of `payload_len` bytes.
+    pub fn gas(&self, payload_len: usize) -> u64 {
+        let base = match self {
+            Self::Fund => FUND_GAS,
+            Self::Release => RELEASE_GAS,
+            Self::Propagate => PROPAGATE_GAS,
+        };
+        base.saturating_add(PAYLOAD_GAS_PER_BYTE.saturating_mul(payload_len as u64))
+    }
+}
+
+/// The estimated cost of moving a message over one hop, paid in the subnet it leaves.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StepCost {
+    pub hop: Hop,
+    pub step: CrossMsgStep,
+    pub gas: u64,
+    #[serde(serialize_with = "serialize_token_amount_to_atto")]
+    pub gas_price: TokenAmount,
+    #[serde(serialize_with = "serialize_token_amount_to_atto")]
+    pub fee: TokenAmount,
+}
+
+impl StepCost {
+    pub fn new(hop: Hop, step: CrossMsgStep, gas: u64, gas_price: TokenAmount) -> Self {
+        let fee = TokenAmount::from_atto(gas_price.atto() * gas);
+        Self {
+            hop,
+            step,
+            gas,
+            gas_price,
+            fee,
+        }
+    }
+}
+
+/// The estimated cost of a cross-net message over its whole route.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CrossMsgCost {
+    pub steps: Vec<StepCost>,
+    /// The value carried by the message.
+    #[serde(serialize_with = "serialize_token_amount_to_atto")]
+    pub value: TokenAmount,
+    /// The fees of all the steps.
+    #[serde(serialize_with = "serialize_token_amount_to_atto")]
+    pub fees: TokenAmount,
+    /// The value and the fees.
+    #[serde(serialize_with = "serialize_token_amount_to_atto")]
+    pub total: TokenAmount,
+}
+
+impl CrossMsgCost {
+    pub fn new(steps: Vec<StepCost>, value: TokenAmount) -> Self {
+        let fees = steps
+            .iter()
+            .fold(TokenAmount::default(), |acc, s| acc + &s.fee);
+        let total = fees.clone() + &value;
+        Self {
+            steps,
+            value,
+            fees,
+            total,
+        }
+    }
+}
+
+impl Display for CrossMsgCost {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for s in &self.steps {
+            writeln!(
+                f,
+                "{} {}: {} gas at {} attoFIL = {} FIL",
+                s.step,
+                s.hop,
+                s.gas,
+                s.gas_price.atto(),
+                s.fee
+            )?;
+        }
+        writeln!(f, "value: {} FIL", self.value)?;
+        writeln!(f, "fees: {} FIL", self.fees)?;
+        write!(f, "total: {} FIL", self.total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use fvm_shared::econ::TokenAmount;
+    use ipc_api::subnet_id::SubnetID;
+
+    use super::{CrossMsgCost, CrossMsgStep, StepCost, FUND_GAS, PAYLOAD_GAS_PER_BYTE};
+    use crate::routing::route;
+
+    #[test]
+    fn steps_of_a_route() {
+        let from = SubnetID::from_str("/r123/f01/f02").unwrap();
+        let to = SubnetID::from_str("/r123/f01/f03/f04").unwrap();
+        let steps = route(&from, &to)
+            .unwrap()
+            .hops()
+            .iter()
+            .enumerate()
+            .map(|(i, hop)| CrossMsgStep::of_hop(i, hop))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            steps,
+            vec![
+                CrossMsgStep::Release,
+                CrossMsgStep::Propagate,
+                CrossMsgStep::Propagate
+            ]
+        );
+
+        let down = route(&from, &SubnetID::from_str("/r123/f01/f02/f05").unwrap()).unwrap();
+        assert_eq!(CrossMsgStep::of_hop(0, &down.hops()[0]), CrossMsgStep::Fund);
+    }
+
+    #[test]
+    fn cost_adds_up() {
+        let from = SubnetID::from_str("/r123").unwrap();
+        let to = SubnetID::from_str("/r123/f01/f02").unwrap();
+        let hops = route(&from, &to).unwrap().hops();
+
+        let steps = hops
+            .into_iter()
+            .enumerate()
+            .map(|(i, hop)| {
+                let step = CrossMsgStep::of_hop(i, &hop);
+                StepCost::new(hop, step, step.gas(10), TokenAmount::from_atto(2))
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(steps[0].gas, FUND_GAS + 10 * PAYLOAD_GAS_PER_BYTE);
+
+        let cost = CrossMsgCost::new(steps, TokenAmount::from_whole(1));
+        let fees = TokenAmount::from_atto(2 * steps_gas(&cost));
+        assert_eq!(cost.fees, fees);
+        assert_eq!(cost.total, fees + TokenAmount::from_whole(1));
+    }
+
+    fn steps_gas(cost: &CrossMsgCost) -> u64 {
+        cost.steps.iter().map(|s| s.gas).sum()
+    }
+}
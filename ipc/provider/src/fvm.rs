@@ -0,0 +1,80 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Raw messages to the actors of FVM subnets, sent through the Lotus API of their nodes.
+//!
+//! [crate::IpcProvider::push_message] and [crate::IpcProvider::state_call] reach any actor with
+//! any method, signing with the FVM wallet of the provider, for what has no typed API.
+
+use cid::Cid;
+use fvm_shared::address::Address;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::MethodNum;
+
+use crate::lotus::message::mpool::MpoolPushMessage;
+use crate::lotus::message::state::Receipt;
+
+/// A message to an actor; its nonce and gas are filled in when it's pushed.
+#[derive(Debug, Clone)]
+pub struct FvmMessage {
+    pub from: Address,
+    pub to: Address,
+    pub value: TokenAmount,
+    pub method: MethodNum,
+    /// The CBOR encoded parameters of the method.
+    pub params: Vec<u8>,
+}
+
+impl FvmMessage {
+    pub fn new(from: Address, to: Address, method: MethodNum, params: Vec<u8>) -> Self {
+        Self {
+            from,
+            to,
+            value: TokenAmount::default(),
+            method,
+            params,
+        }
+    }
+
+    pub fn with_value(mut self, value: TokenAmount) -> Self {
+        self.value = value;
+        self
+    }
+}
+
+impl From<FvmMessage> for MpoolPushMessage {
+    fn from(msg: FvmMessage) -> Self {
+        let mut m = MpoolPushMessage::new(msg.to, msg.from, msg.method, msg.params);
+        m.value = msg.value;
+        m
+    }
+}
+
+/// The outcome of a message, executed in the chain or only applied by a state call.
+#[derive(Debug, Clone)]
+pub struct FvmReceipt {
+    /// The CID of the message, if it was pushed to the chain.
+    pub cid: Option<Cid>,
+    /// The height the message was executed at, if it was pushed to the chain.
+    pub height: Option<u64>,
+    pub exit_code: u32,
+    /// The raw bytes returned by the actor.
+    pub return_data: Vec<u8>,
+    pub gas_used: u64,
+}
+
+impl FvmReceipt {
+    pub fn from_receipt(receipt: &Receipt) -> anyhow::Result<Self> {
+        Ok(Self {
+            cid: None,
+            height: None,
+            exit_code: receipt.exit_code(),
+            return_data: receipt.return_data()?,
+            gas_used: receipt.gas_used(),
+        })
+    }
+
+    /// Whether the actor exited successfully.
+    pub fn success(&self) -> bool {
+        self.exit_code == 0
+    }
+}
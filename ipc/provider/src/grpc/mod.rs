@@ -0,0 +1,331 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! gRPC facade over the [IpcProvider], enabled with the `grpc` feature, so services written
+//! in other languages can drive subnet operations through the IDL in `proto/ipc_provider.proto`.
+
+use std::net::SocketAddr;
+use std::str::FromStr;
+
+use fvm_shared::{address::Address, bigint::BigInt, econ::TokenAmount};
+use ipc_api::subnet_id::SubnetID;
+use tonic::{Request, Response, Status};
+
+use crate::{address_book, IpcProvider};
+
+/// The code generated from the protobuf definitions.
+pub mod proto {
+    tonic::include_proto!("ipc.provider.v1");
+}
+
+use proto::ipc_provider_service_server::{IpcProviderService, IpcProviderServiceServer};
+
+/// Serves the provider operations; each request works on its own copy of the provider,
+/// which shares the config and the wallets with the others.
+pub struct IpcProviderGrpc {
+    provider: IpcProvider,
+}
+
+impl IpcProviderGrpc {
+    pub fn new(provider: IpcProvider) -> Self {
+        Self { provider }
+    }
+
+    /// Serve the provider on `addr` until the server fails.
+    pub async fn serve(self, addr: SocketAddr) -> anyhow::Result<()> {
+        tracing::info!("serving the ipc provider over gRPC on {addr}");
+        tonic::transport::Server::builder()
+            .add_service(IpcProviderServiceServer::new(self))
+            .serve(addr)
+            .await?;
+        Ok(())
+    }
+}
+
+#[tonic::async_trait]
+impl IpcProviderService for IpcProviderGrpc {
+    async fn list_subnets(
+        &self,
+        request: Request<proto::ListSubnetsRequest>,
+    ) -> Result<Response<proto::ListSubnetsResponse>, Status> {
+        let subnet = parse_subnet(&request.get_ref().subnet)?;
+        let subnets = self
+            .provider
+            .list_child_subnets(None, &subnet)
+            .await
+            .map_err(internal)?;
+
+        let subnets = subnets
+            .into_values()
+            .map(|s| proto::SubnetInfo {
+                id: s.id.to_string(),
+                stake: s.stake.atto().to_string(),
+                circ_supply: s.circ_supply.atto().to_string(),
+                genesis_epoch: s.genesis_epoch,
+            })
+            .collect();
+
+        Ok(Response::new(proto::ListSubnetsResponse { subnets }))
+    }
+
+    async fn wallet_balance(
+        &self,
+        request: Request<proto::WalletBalanceRequest>,
+    ) -> Result<Response<proto::WalletBalanceResponse>, Status> {
+        let request = request.get_ref();
+        let subnet = parse_subnet(&request.subnet)?;
+        let address = parse_address(&request.address)?;
+        let balance = self
+            .provider
+            .wallet_balance(&subnet, &address)
+            .await
+            .map_err(internal)?;
+
+        Ok(Response::new(proto::WalletBalanceResponse {
+            balance: balance.atto().to_string(),
+        }))
+    }
+
+    async fn chain_head(
+        &self,
+        request: Request<proto::SubnetRequest>,
+    ) -> Result<Response<proto::EpochResponse>, Status> {
+        let subnet = parse_subnet(&request.get_ref().subnet)?;
+        let epoch = self.provider.chain_head(&subnet).await.map_err(internal)?;
+        Ok(Response::new(proto::EpochResponse { epoch }))
+    }
+
+    async fn genesis_epoch(
+        &self,
+        request: Request<proto::SubnetRequest>,
+    ) -> Result<Response<proto::EpochResponse>, Status> {
+        let subnet = parse_subnet(&request.get_ref().subnet)?;
+        let epoch = self
+            .provider
+            .genesis_epoch(&subnet)
+            .await
+            .map_err(internal)?;
+        Ok(Response::new(proto::EpochResponse { epoch }))
+    }
+
+    async fn last_bottom_up_checkpoint_height(
+        &self,
+        request: Request<proto::SubnetRequest>,
+    ) -> Result<Response<proto::EpochResponse>, Status> {
+        let subnet = parse_subnet(&request.get_ref().subnet)?;
+        let epoch = self
+            .provider
+            .last_bottom_up_checkpoint_height(&subnet)
+            .await
+            .map_err(internal)?;
+        Ok(Response::new(proto::EpochResponse { epoch }))
+    }
+
+    async fn join_subnet(
+        &self,
+        request: Request<proto::CollateralRequest>,
+    ) -> Result<Response<proto::TxReceipt>, Status> {
+        let (subnet, from, collateral) = parse_collateral(request.get_ref())?;
+        let receipt = self
+            .provider
+            .clone()
+            .join_subnet(subnet, from, collateral)
+            .await;
+        to_response(receipt)
+    }
+
+    async fn stake(
+        &self,
+        request: Request<proto::CollateralRequest>,
+    ) -> Result<Response<proto::TxReceipt>, Status> {
+        let (subnet, from, collateral) = parse_collateral(request.get_ref())?;
+        let receipt = self.provider.clone().stake(subnet, from, collateral).await;
+        to_response(receipt)
+    }
+
+    async fn unstake(
+        &self,
+        request: Request<proto::CollateralRequest>,
+    ) -> Result<Response<proto::TxReceipt>, Status> {
+        let (subnet, from, collateral) = parse_collateral(request.get_ref())?;
+        let receipt = self
+            .provider
+            .clone()
+            .unstake(subnet, from, collateral)
+            .await;
+        to_response(receipt)
+    }
+
+    async fn leave_subnet(
+        &self,
+        request: Request<proto::LeaveSubnetRequest>,
+    ) -> Result<Response<proto::TxReceipt>, Status> {
+        let request = request.get_ref();
+        let subnet = parse_subnet(&request.subnet)?;
+        let from = parse_optional_address(&request.from)?;
+        let receipt = self.provider.clone().leave_subnet(subnet, from).await;
+        to_response(receipt)
+    }
+
+    async fn fund(
+        &self,
+        request: Request<proto::TransferRequest>,
+    ) -> Result<Response<proto::TxReceipt>, Status> {
+        let (subnet, from, to, amount) = parse_transfer(request.get_ref())?;
+        let receipt = self
+            .provider
+            .clone()
+            .fund(subnet, None, from, to, amount)
+            .await;
+        to_response(receipt)
+    }
+
+    async fn release(
+        &self,
+        request: Request<proto::TransferRequest>,
+    ) -> Result<Response<proto::TxReceipt>, Status> {
+        let (subnet, from, to, amount) = parse_transfer(request.get_ref())?;
+        let receipt = self
+            .provider
+            .clone()
+            .release(subnet, None, from, to, amount)
+            .await;
+        to_response(receipt)
+    }
+
+    async fn send_value(
+        &self,
+        request: Request<proto::TransferRequest>,
+    ) -> Result<Response<proto::TxReceipt>, Status> {
+        let (subnet, from, to, amount) = parse_transfer(request.get_ref())?;
+        let to = to.ok_or_else(|| Status::invalid_argument("missing recipient"))?;
+        let receipt = self
+            .provider
+            .clone()
+            .send_value(&subnet, from, to, amount)
+            .await;
+        to_response(receipt)
+    }
+}
+
+impl From<ipc_api::receipt::TxReceipt> for proto::TxReceipt {
+    fn from(receipt: ipc_api::receipt::TxReceipt) -> Self {
+        Self {
+            tx_hash: format!("{:?}", receipt.tx_hash),
+            epoch: receipt.epoch,
+            gas_used: receipt.gas_used,
+            success: receipt.success,
+            events: receipt
+                .events
+                .into_iter()
+                .map(|e| proto::TxEvent {
+                    address: format!("{:?}", e.address),
+                    name: e.name,
+                    params: e.params.into_iter().collect(),
+                    topics: e.topics.iter().map(|t| format!("{t:?}")).collect(),
+                    data: e.data.to_vec(),
+                })
+                .collect(),
+        }
+    }
+}
+
+fn to_response(
+    receipt: anyhow::Result<ipc_api::receipt::TxReceipt>,
+) -> Result<Response<proto::TxReceipt>, Status> {
+    Ok(Response::new(receipt.map_err(internal)?.into()))
+}
+
+fn internal(e: anyhow::Error) -> Status {
+    Status::internal(format!("{e:#}"))
+}
+
+fn parse_subnet(s: &str) -> Result<SubnetID, Status> {
+    SubnetID::from_str(s).map_err(|e| Status::invalid_argument(format!("invalid subnet: {e}")))
+}
+
+fn parse_address(s: &str) -> Result<Address, Status> {
+    address_book::parse_address(s).map_err(|e| Status::invalid_argument(e.to_string()))
+}
+
+fn parse_optional_address(s: &str) -> Result<Option<Address>, Status> {
+    if s.is_empty() {
+        Ok(None)
+    } else {
+        parse_address(s).map(Some)
+    }
+}
+
+fn parse_amount(s: &str) -> Result<TokenAmount, Status> {
+    let atto = BigInt::from_str(s)
+        .map_err(|e| Status::invalid_argument(format!("invalid amount {s}: {e}")))?;
+    Ok(TokenAmount::from_atto(atto))
+}
+
+fn parse_collateral(
+    request: &proto::CollateralRequest,
+) -> Result<(SubnetID, Option<Address>, TokenAmount), Status> {
+    Ok((
+        parse_subnet(&request.subnet)?,
+        parse_optional_address(&request.from)?,
+        parse_amount(&request.collateral)?,
+    ))
+}
+
+fn parse_transfer(
+    request: &proto::TransferRequest,
+) -> Result<(SubnetID, Option<Address>, Option<Address>, TokenAmount), Status> {
+    Ok((
+        parse_subnet(&request.subnet)?,
+        parse_optional_address(&request.from)?,
+        parse_optional_address(&request.to)?,
+        parse_amount(&request.amount)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use ethers::types::{Bytes, H160, H256};
+    use ipc_api::receipt::{TxEvent, TxReceipt};
+
+    use super::{parse_address, parse_amount, proto};
+
+    #[test]
+    fn receipt_to_proto() {
+        let receipt = TxReceipt {
+            tx_hash: H256::repeat_byte(1),
+            epoch: 42,
+            gas_used: Some(21000),
+            success: true,
+            events: vec![TxEvent {
+                address: H160::repeat_byte(2),
+                name: Some("Transfer".to_string()),
+                params: BTreeMap::from([("value".to_string(), "1".to_string())]),
+                topics: vec![H256::repeat_byte(3)],
+                data: Bytes::from(vec![4u8]),
+            }],
+        };
+
+        let proto = proto::TxReceipt::from(receipt);
+        assert_eq!(proto.tx_hash, format!("0x{}", "01".repeat(32)));
+        assert_eq!(proto.epoch, 42);
+        assert_eq!(proto.gas_used, Some(21000));
+        assert_eq!(proto.events[0].name.as_deref(), Some("Transfer"));
+        assert_eq!(proto.events[0].params["value"], "1");
+        assert_eq!(proto.events[0].data, vec![4u8]);
+    }
+
+    #[test]
+    fn parse_arguments() {
+        assert!(parse_address("0x2e714a3c385ea88a09998ed74db265dae9853667").is_ok());
+        assert!(parse_address("f410ffzyuupbyl2uiucmzr3lu3mtf3luyknthaz4xsrq").is_ok());
+        assert!(parse_address("nope").is_err());
+
+        assert_eq!(
+            parse_amount("1000000000000000000").unwrap(),
+            fvm_shared::econ::TokenAmount::from_whole(1)
+        );
+        assert!(parse_amount("1.5").is_err());
+    }
+}
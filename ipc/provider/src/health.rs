@@ -0,0 +1,438 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Health of the RPC endpoints of the subnets.
+//!
+//! A subnet can list fallback endpoints next to `provider_http`. The [HealthMonitor] probes
+//! each of them in the background, timing a request for the chain head and checking that the
+//! head keeps advancing and isn't far behind the other endpoints of the subnet. Connections are
+//! opened to the healthiest endpoint, in the order of the config when they are equally healthy,
+//! with the HTTP client the probes already opened connections with.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+use ethers::providers::{Http, JsonRpcClient};
+use ethers::types::U64;
+use futures_util::future::join_all;
+use ipc_api::subnet_id::SubnetID;
+use reqwest::Client;
+use serde::Serialize;
+use tokio_util::sync::CancellationToken;
+use url::Url;
+
+use crate::config::Subnet;
+use crate::manager::evm::http_client;
+
+/// How often the endpoints are probed, unless configured otherwise.
+pub const DEFAULT_PROBE_INTERVAL: Duration = Duration::from_secs(15);
+/// How long a probe waits for the chain head, unless configured otherwise.
+pub const DEFAULT_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How to probe the endpoints, and when to consider them degraded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProbeOptions {
+    pub interval: Duration,
+    /// Probes taking longer fail, and the endpoint is down.
+    pub timeout: Duration,
+    /// Endpoints answering slower are degraded.
+    pub max_latency: Duration,
+    /// Endpoints whose chain head didn't advance for this many probes in a row are degraded.
+    pub max_stalled_probes: u32,
+    /// Endpoints more blocks behind the highest head of the subnet are degraded.
+    pub max_head_lag: u64,
+}
+
+impl Default for ProbeOptions {
+    fn default() -> Self {
+        Self {
+            interval: DEFAULT_PROBE_INTERVAL,
+            timeout: DEFAULT_PROBE_TIMEOUT,
+            max_latency: Duration::from_secs(2),
+            max_stalled_probes: 3,
+            max_head_lag: 10,
+        }
+    }
+}
+
+/// The status of an endpoint, from the most to the least preferred.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, strum::Display)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum EndpointStatus {
+    Healthy,
+    /// Not probed yet.
+    Unknown,
+    /// Answering, but slowly, stalled or behind the other endpoints.
+    Degraded,
+    /// Not answering.
+    Down,
+}
+
+/// The outcome of the last probe of an endpoint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EndpointHealth {
+    pub subnet: String,
+    pub url: Url,
+    pub status: EndpointStatus,
+    /// Time taken by the last successful probe.
+    pub latency: Option<Duration>,
+    /// Chain head reported by the last successful probe.
+    pub chain_head: Option<u64>,
+    /// Number of probes in a row which didn't see the chain head advance.
+    pub stalled_probes: u32,
+    pub last_error: Option<String>,
+}
+
+impl EndpointHealth {
+    fn new(subnet: &SubnetID, url: Url) -> Self {
+        Self {
+            subnet: subnet.to_string(),
+            url,
+            status: EndpointStatus::Unknown,
+            latency: None,
+            chain_head: None,
+            stalled_probes: 0,
+            last_error: None,
+        }
+    }
+
+    /// Record the outcome of a probe; the status is assessed once all the endpoints of the
+    /// subnet are probed.
+    fn record(&mut self, outcome: anyhow::Result<(u64, Duration)>) {
+        match outcome {
+            Ok((head, latency)) => {
+                if self.chain_head.is_some_and(|prev| head <= prev) {
+                    self.stalled_probes += 1;
+                } else {
+                    self.stalled_probes = 0;
+                }
+                self.chain_head = Some(head);
+                self.latency = Some(latency);
+                self.last_error = None;
+            }
+            Err(e) => {
+                self.latency = None;
+                self.last_error = Some(format!("{e:#}"));
+            }
+        }
+    }
+}
+
+/// Set the status of the endpoints of a subnet after a round of probes.
+fn assess(endpoints: &mut [Endpoint], options: &ProbeOptions) {
+    let best_head = endpoints
+        .iter()
+        .filter(|e| e.health.last_error.is_none())
+        .filter_map(|e| e.health.chain_head)
+        .max();
+
+    for endpoint in endpoints {
+        let health = &mut endpoint.health;
+        health.status = match (
+            health.last_error.as_ref(),
+            health.latency,
+            health.chain_head,
+        ) {
+            (Some(_), _, _) => EndpointStatus::Down,
+            (None, Some(latency), Some(head)) => {
+                let lag = best_head.unwrap_or(head).saturating_sub(head);
+                if latency > options.max_latency
+                    || health.stalled_probes >= options.max_stalled_probes
+                    || lag > options.max_head_lag
+                {
+                    EndpointStatus::Degraded
+                } else {
+                    EndpointStatus::Healthy
+                }
+            }
+            _ => EndpointStatus::Unknown,
+        };
+    }
+}
+
+struct Endpoint {
+    /// Shared with the connections to the endpoint, so they reuse the connections kept open
+    /// by the probes.
+    client: Client,
+    health: EndpointHealth,
+}
+
+/// Probes the endpoints of the subnets and picks the one to connect to.
+#[derive(Default)]
+pub struct HealthMonitor {
+    options: ProbeOptions,
+    endpoints: Mutex<HashMap<SubnetID, Vec<Endpoint>>>,
+}
+
+impl HealthMonitor {
+    pub fn new(options: ProbeOptions) -> Self {
+        Self {
+            options,
+            endpoints: Default::default(),
+        }
+    }
+
+    pub fn options(&self) -> &ProbeOptions {
+        &self.options
+    }
+
+    /// The health of every endpoint probed or connected to so far, by subnet and in the order
+    /// of the config.
+    pub fn health(&self) -> Vec<EndpointHealth> {
+        let endpoints = self.endpoints.lock().unwrap();
+        let mut subnets = endpoints.keys().collect::<Vec<_>>();
+        subnets.sort_by_key(|id| id.to_string());
+        subnets
+            .into_iter()
+            .flat_map(|id| endpoints[id].iter().map(|e| e.health.clone()))
+            .collect()
+    }
+
+    /// The endpoint of `subnet` to connect to, and the client to connect with: the first
+    /// healthy one in the order of the config, or the least unhealthy one.
+    pub fn select(&self, subnet: &Subnet) -> anyhow::Result<(Url, Client)> {
+        let mut endpoints = self.endpoints.lock().unwrap();
+        let endpoints = Self::endpoints_of(&mut endpoints, subnet)?;
+        let selected = endpoints
+            .iter()
+            .min_by_key(|e| e.health.status)
+            .expect("subnets have at least one endpoint");
+        if selected.health.url != *subnet.rpc_http() {
+            tracing::debug!(
+                subnet = subnet.id.to_string(),
+                "routing around the {} primary endpoint to {}",
+                endpoints[0].health.status,
+                selected.health.url
+            );
+        }
+        Ok((selected.health.url.clone(), selected.client.clone()))
+    }
+
+    /// Probe every endpoint of `subnets` once, at the same time.
+    pub async fn probe<'a>(&self, subnets: impl IntoIterator<Item = &'a Subnet>) {
+        let mut targets = Vec::new();
+        {
+            let mut endpoints = self.endpoints.lock().unwrap();
+            for subnet in subnets {
+                match Self::endpoints_of(&mut endpoints, subnet) {
+                    Ok(eps) => targets.extend(
+                        eps.iter()
+                            .map(|e| (subnet.id.clone(), e.health.url.clone(), e.client.clone())),
+                    ),
+                    Err(e) => tracing::warn!("cannot probe the endpoints of {}: {e:#}", subnet.id),
+                }
+            }
+        }
+
+        let outcomes = join_all(targets.into_iter().map(|(subnet, url, client)| async move {
+            let outcome = probe_endpoint(&url, client, self.options.timeout).await;
+            (subnet, url, outcome)
+        }))
+        .await;
+
+        let mut endpoints = self.endpoints.lock().unwrap();
+        let mut probed = Vec::new();
+        for (subnet, url, outcome) in outcomes {
+            let Some(eps) = endpoints.get_mut(&subnet) else {
+                continue;
+            };
+            if let Some(endpoint) = eps.iter_mut().find(|e| e.health.url == url) {
+                if let Err(e) = &outcome {
+                    tracing::debug!("endpoint {url} of {subnet} is down: {e:#}");
+                }
+                endpoint.health.record(outcome);
+            }
+            if !probed.contains(&subnet) {
+                probed.push(subnet);
+            }
+        }
+        for subnet in probed {
+            if let Some(eps) = endpoints.get_mut(&subnet) {
+                assess(eps, &self.options);
+            }
+        }
+    }
+
+    /// Probe the endpoints of `subnets` every interval, until `shutdown` is cancelled.
+    pub async fn run(&self, subnets: Vec<Subnet>, shutdown: CancellationToken) {
+        let mut interval = tokio::time::interval(self.options.interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = interval.tick() => self.probe(&subnets).await,
+            }
+        }
+    }
+
+    /// The endpoints of a subnet, with a client for each, created on first use.
+    fn endpoints_of<'a>(
+        endpoints: &'a mut HashMap<SubnetID, Vec<Endpoint>>,
+        subnet: &Subnet,
+    ) -> anyhow::Result<&'a mut Vec<Endpoint>> {
+        let urls = subnet.rpc_endpoints();
+        let current = endpoints.get(&subnet.id);
+        // The config of the subnet can be replaced with the same ID.
+        let stale = match current {
+            None => true,
+            Some(eps) => {
+                eps.len() != urls.len()
+                    || eps.iter().zip(&urls).any(|(e, url)| e.health.url != **url)
+            }
+        };
+        if stale {
+            let client = http_client(subnet).context("cannot create the HTTP client")?;
+            let eps = urls
+                .into_iter()
+                .map(|url| Endpoint {
+                    client: client.clone(),
+                    health: EndpointHealth::new(&subnet.id, url.clone()),
+                })
+                .collect();
+            endpoints.insert(subnet.id.clone(), eps);
+        }
+        Ok(endpoints.get_mut(&subnet.id).expect("endpoints inserted"))
+    }
+}
+
+/// Time a request for the chain head of an endpoint.
+async fn probe_endpoint(
+    url: &Url,
+    client: Client,
+    timeout: Duration,
+) -> anyhow::Result<(u64, Duration)> {
+    let http = Http::new_with_client(url.clone(), client);
+    let start = Instant::now();
+    let head = tokio::time::timeout(timeout, http.request::<_, U64>("eth_blockNumber", ()))
+        .await
+        .map_err(|_| anyhow::anyhow!("no response within {timeout:?}"))??;
+    Ok((head.as_u64(), start.elapsed()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use std::time::Duration;
+
+    use anyhow::anyhow;
+    use ipc_api::subnet_id::SubnetID;
+    use url::Url;
+
+    use super::{assess, Endpoint, EndpointHealth, EndpointStatus, HealthMonitor, ProbeOptions};
+    use crate::config::subnet::{EVMSubnet, SubnetConfig};
+    use crate::config::Subnet;
+
+    fn endpoints(n: u16) -> Vec<Endpoint> {
+        let subnet = SubnetID::from_str("/r314159").unwrap();
+        (0..n)
+            .map(|i| Endpoint {
+                client: reqwest::Client::new(),
+                health: EndpointHealth::new(
+                    &subnet,
+                    Url::parse(&format!("http://127.0.0.1:{}", 8545 + i)).unwrap(),
+                ),
+            })
+            .collect()
+    }
+
+    fn statuses(endpoints: &[Endpoint]) -> Vec<EndpointStatus> {
+        endpoints.iter().map(|e| e.health.status).collect()
+    }
+
+    fn ok(head: u64, latency_ms: u64) -> anyhow::Result<(u64, Duration)> {
+        Ok((head, Duration::from_millis(latency_ms)))
+    }
+
+    #[test]
+    fn endpoints_are_assessed_against_each_other() {
+        let options = ProbeOptions::default();
+        let mut eps = endpoints(4);
+        assess(&mut eps, &options);
+        assert_eq!(statuses(&eps), vec![EndpointStatus::Unknown; 4]);
+
+        eps[0].health.record(ok(100, 50));
+        eps[1].health.record(ok(100, 5000));
+        eps[2].health.record(ok(80, 50));
+        eps[3].health.record(Err(anyhow!("connection refused")));
+        assess(&mut eps, &options);
+        assert_eq!(
+            statuses(&eps),
+            vec![
+                EndpointStatus::Healthy,
+                EndpointStatus::Degraded,
+                EndpointStatus::Degraded,
+                EndpointStatus::Down
+            ]
+        );
+        assert_eq!(
+            eps[3].health.last_error.as_deref(),
+            Some("connection refused")
+        );
+
+        // The endpoint which was down recovers, the first one stops advancing.
+        for head in 101..=103 {
+            eps[0].health.record(ok(100, 50));
+            eps[3].health.record(ok(head, 50));
+            assess(&mut eps, &options);
+        }
+        assert_eq!(eps[0].health.stalled_probes, 3);
+        assert_eq!(eps[0].health.status, EndpointStatus::Degraded);
+        assert_eq!(eps[3].health.status, EndpointStatus::Healthy);
+        assert_eq!(eps[3].health.last_error, None);
+    }
+
+    #[test]
+    fn degraded_endpoints_are_routed_around() {
+        let subnet = Subnet {
+            id: SubnetID::from_str("/r314159").unwrap(),
+            config: SubnetConfig::Fevm(EVMSubnet {
+                provider_http: Url::parse("http://127.0.0.1:8545").unwrap(),
+                provider_timeout: None,
+                auth_token: None,
+                registry_addr: fvm_shared::address::Address::new_id(1),
+                gateway_addr: fvm_shared::address::Address::new_id(2),
+                admin: None,
+                response_cache: None,
+                max_concurrent_requests: None,
+                fallback_provider_http: vec![
+                    Url::parse("http://127.0.0.1:8546").unwrap(),
+                    Url::parse("http://127.0.0.1:8547").unwrap(),
+                ],
+                gas: None,
+            }),
+        };
+        let monitor = HealthMonitor::default();
+        let selected = |monitor: &HealthMonitor| monitor.select(&subnet).unwrap().0.port();
+
+        // Nothing is known yet, so the primary endpoint comes first.
+        assert_eq!(selected(&monitor), Some(8545));
+        assert_eq!(monitor.health().len(), 3);
+
+        let set_statuses = |statuses: [EndpointStatus; 3]| {
+            let mut endpoints = monitor.endpoints.lock().unwrap();
+            for (e, status) in endpoints
+                .get_mut(&subnet.id)
+                .unwrap()
+                .iter_mut()
+                .zip(statuses)
+            {
+                e.health.status = status;
+            }
+        };
+        set_statuses([
+            EndpointStatus::Degraded,
+            EndpointStatus::Healthy,
+            EndpointStatus::Healthy,
+        ]);
+        assert_eq!(selected(&monitor), Some(8546));
+
+        set_statuses([
+            EndpointStatus::Down,
+            EndpointStatus::Down,
+            EndpointStatus::Degraded,
+        ]);
+        assert_eq!(selected(&monitor), Some(8547));
+    }
+}
@@ -0,0 +1,475 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Hooks run by the provider around the operations it submits.
+//!
+//! Integrators register an [OperationHook] with [crate::IpcProvider::register_hook] to apply
+//! their own policy to everything the provider sends, like spending limits, allowlists of
+//! destinations or alerts, without changing the provider itself.
+
+use std::future::Future;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use fvm_shared::address::Address;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::MethodNum;
+use ipc_api::model::ModelHash;
+use ipc_api::receipt::TxReceipt;
+use ipc_api::subnet::{ConstructParams, SubnetParam};
+use ipc_api::subnet_id::SubnetID;
+use num_traits::Zero;
+
+use crate::deploy::DeployedContract;
+use crate::fvm::FvmReceipt;
+use crate::multisig::{AdminOperation, Proposal};
+use crate::offline::BroadcastReceipt;
+
+/// The operations of the provider which send transactions.
+#[derive(Debug, Clone)]
+pub enum Operation {
+    CreateSubnet(ConstructParams),
+    JoinSubnet {
+        subnet: SubnetID,
+        collateral: TokenAmount,
+    },
+    PreFund {
+        subnet: SubnetID,
+        amount: TokenAmount,
+    },
+    PreRelease {
+        subnet: SubnetID,
+        amount: TokenAmount,
+    },
+    Stake {
+        subnet: SubnetID,
+        collateral: TokenAmount,
+    },
+    Unstake {
+        subnet: SubnetID,
+        collateral: TokenAmount,
+    },
+    LeaveSubnet {
+        subnet: SubnetID,
+    },
+    ClaimCollateral {
+        subnet: SubnetID,
+    },
+    KillSubnet {
+        subnet: SubnetID,
+    },
+    ReportDoubleSign {
+        subnet: SubnetID,
+        validator: ethers::types::Address,
+    },
+    ProposeParamChange {
+        subnet: SubnetID,
+        param: SubnetParam,
+        value: u64,
+    },
+    VoteParamChange {
+        subnet: SubnetID,
+        id: u64,
+    },
+    Fund {
+        subnet: SubnetID,
+        to: Address,
+        amount: TokenAmount,
+    },
+    FundWithToken {
+        subnet: SubnetID,
+        to: Address,
+        amount: TokenAmount,
+    },
+    ApproveToken {
+        subnet: SubnetID,
+        amount: TokenAmount,
+    },
+    Release {
+        subnet: SubnetID,
+        to: Address,
+        amount: TokenAmount,
+    },
+    SendValue {
+        to: Address,
+        amount: TokenAmount,
+    },
+    FaucetDrip {
+        to: Address,
+    },
+    Erc20Transfer {
+        token: Address,
+        to: Address,
+        amount: TokenAmount,
+    },
+    ContractTx {
+        to: Address,
+        value: TokenAmount,
+    },
+    PushMessage {
+        to: Address,
+        method: MethodNum,
+        value: TokenAmount,
+    },
+    DeployContract {
+        salt: Option<[u8; 32]>,
+    },
+    ExportModel {
+        to_subnet: SubnetID,
+        value: TokenAmount,
+    },
+    AddBootstrap {
+        subnet: SubnetID,
+        endpoint: String,
+    },
+    SetFederatedPower {
+        subnet: SubnetID,
+        validators: Vec<Address>,
+    },
+    ProposeAdminOperation(AdminOperation),
+    ApproveAdminOperation(AdminOperation),
+}
+
+impl Operation {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Operation::CreateSubnet(_) => "create_subnet",
+            Operation::JoinSubnet { .. } => "join_subnet",
+            Operation::PreFund { .. } => "pre_fund",
+            Operation::PreRelease { .. } => "pre_release",
+            Operation::Stake { .. } => "stake",
+            Operation::Unstake { .. } => "unstake",
+            Operation::LeaveSubnet { .. } => "leave_subnet",
+            Operation::ClaimCollateral { .. } => "claim_collateral",
+            Operation::KillSubnet { .. } => "kill_subnet",
+            Operation::ReportDoubleSign { .. } => "report_double_sign",
+            Operation::ProposeParamChange { .. } => "propose_param_change",
+            Operation::VoteParamChange { .. } => "vote_param_change",
+            Operation::Fund { .. } => "fund",
+            Operation::FundWithToken { .. } => "fund_with_token",
+            Operation::ApproveToken { .. } => "approve_token",
+            Operation::Release { .. } => "release",
+            Operation::SendValue { .. } => "send_value",
+            Operation::FaucetDrip { .. } => "faucet_drip",
+            Operation::Erc20Transfer { .. } => "erc20_transfer",
+            Operation::ContractTx { .. } => "contract_tx",
+            Operation::PushMessage { .. } => "push_message",
+            Operation::DeployContract { .. } => "deploy_contract",
+            Operation::ExportModel { .. } => "export_model",
+            Operation::AddBootstrap { .. } => "add_bootstrap",
+            Operation::SetFederatedPower { .. } => "set_federated_power",
+            Operation::ProposeAdminOperation(_) => "propose_admin_operation",
+            Operation::ApproveAdminOperation(_) => "approve_admin_operation",
+        }
+    }
+
+    /// The tokens the operation takes from the sender, on top of the gas.
+    ///
+    /// The collateral of `unstake` and the amount of `pre_release` are given back rather than
+    /// taken, and the allowance of `approve_token` is only spent by a later `fund_with_token`.
+    /// The amount of `erc20_transfer` is in the token, not in the native currency.
+    pub fn value(&self) -> TokenAmount {
+        match self {
+            Operation::JoinSubnet { collateral, .. } | Operation::Stake { collateral, .. } => {
+                collateral.clone()
+            }
+            Operation::PreFund { amount, .. }
+            | Operation::Fund { amount, .. }
+            | Operation::FundWithToken { amount, .. }
+            | Operation::Release { amount, .. }
+            | Operation::SendValue { amount, .. } => amount.clone(),
+            Operation::ExportModel { value, .. }
+            | Operation::ContractTx { value, .. }
+            | Operation::PushMessage { value, .. } => value.clone(),
+            _ => TokenAmount::zero(),
+        }
+    }
+
+    /// The subnet the operation moves tokens or messages to, if it's not the network the
+    /// transaction is sent to.
+    pub fn destination(&self) -> Option<SubnetID> {
+        match self {
+            Operation::PreFund { subnet, .. }
+            | Operation::Fund { subnet, .. }
+            | Operation::FundWithToken { subnet, .. } => Some(subnet.clone()),
+            Operation::ExportModel { to_subnet, .. } => Some(to_subnet.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// An operation about to be submitted, with where and by whom.
+#[derive(Debug, Clone)]
+pub struct OperationDescriptor {
+    /// The subnet the transaction is sent to.
+    pub network: SubnetID,
+    /// The address signing the transaction.
+    pub from: Address,
+    pub operation: Operation,
+}
+
+/// What a successful operation returned.
+#[derive(Debug, Clone)]
+pub enum OperationOutput {
+    Receipt(TxReceipt),
+    /// The address of a created subnet.
+    Subnet(Address),
+    Model(ModelHash),
+    Proposal(Proposal),
+    /// The receipt of a message to an FVM actor.
+    Message(FvmReceipt),
+    Done,
+}
+
+impl From<&TxReceipt> for OperationOutput {
+    fn from(receipt: &TxReceipt) -> Self {
+        OperationOutput::Receipt(receipt.clone())
+    }
+}
+
+impl From<&(u64, TxReceipt)> for OperationOutput {
+    fn from((_, receipt): &(u64, TxReceipt)) -> Self {
+        OperationOutput::Receipt(receipt.clone())
+    }
+}
+
+impl From<&DeployedContract> for OperationOutput {
+    fn from(contract: &DeployedContract) -> Self {
+        OperationOutput::Receipt(contract.receipt.clone())
+    }
+}
+
+impl From<&Address> for OperationOutput {
+    fn from(address: &Address) -> Self {
+        OperationOutput::Subnet(*address)
+    }
+}
+
+impl From<&ModelHash> for OperationOutput {
+    fn from(hash: &ModelHash) -> Self {
+        OperationOutput::Model(*hash)
+    }
+}
+
+impl From<&Proposal> for OperationOutput {
+    fn from(proposal: &Proposal) -> Self {
+        OperationOutput::Proposal(proposal.clone())
+    }
+}
+
+impl From<&FvmReceipt> for OperationOutput {
+    fn from(receipt: &FvmReceipt) -> Self {
+        OperationOutput::Message(receipt.clone())
+    }
+}
+
+impl From<&BroadcastReceipt> for OperationOutput {
+    fn from(receipt: &BroadcastReceipt) -> Self {
+        match receipt {
+            BroadcastReceipt::Evm(receipt) => OperationOutput::Receipt(receipt.clone()),
+            BroadcastReceipt::Fvm(receipt) => OperationOutput::Message(receipt.clone()),
+        }
+    }
+}
+
+impl From<&()> for OperationOutput {
+    fn from(_: &()) -> Self {
+        OperationOutput::Done
+    }
+}
+
+/// Called by the provider around every operation it submits, in the order of registration.
+#[async_trait]
+pub trait OperationHook: Send + Sync {
+    /// Called before the operation is signed; an error aborts it, and is returned to the caller.
+    async fn pre_submit(&self, _op: &OperationDescriptor) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Called with the output of an operation which succeeded.
+    async fn post_receipt(&self, _op: &OperationDescriptor, _output: &OperationOutput) {}
+
+    /// Called with the error of an operation which failed, or was aborted by a hook.
+    async fn on_error(&self, _op: &OperationDescriptor, _error: &anyhow::Error) {}
+}
+
+/// The hooks registered with a provider.
+#[derive(Clone, Default)]
+pub struct Hooks(Vec<Arc<dyn OperationHook>>);
+
+impl Hooks {
+    pub fn register(&mut self, hook: Arc<dyn OperationHook>) {
+        self.0.push(hook);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Run `submit` between the hooks of `op`. The future isn't polled, and nothing is signed,
+    /// unless all the pre-submit hooks accept the operation.
+    pub async fn run<T, F>(&self, op: OperationDescriptor, submit: F) -> anyhow::Result<T>
+    where
+        F: Future<Output = anyhow::Result<T>>,
+        for<'a> OperationOutput: From<&'a T>,
+    {
+        self.check(&op).await?;
+        self.run_checked(op, submit).await
+    }
+
+    /// Run the pre-submit hooks of `op`, and the error hooks if one of them refuses it.
+    pub async fn check(&self, op: &OperationDescriptor) -> anyhow::Result<()> {
+        for hook in &self.0 {
+            if let Err(e) = hook.pre_submit(op).await {
+                let e = e.context(format!("{} refused by hook", op.operation.name()));
+                self.on_error(op, &e).await;
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Run `submit` between the hooks of `op` which follow the pre-submit ones, for operations
+    /// which went through those already, like transactions checked when they were signed offline.
+    pub async fn run_checked<T, F>(&self, op: OperationDescriptor, submit: F) -> anyhow::Result<T>
+    where
+        F: Future<Output = anyhow::Result<T>>,
+        for<'a> OperationOutput: From<&'a T>,
+    {
+        match submit.await {
+            Ok(output) => {
+                let hook_output = OperationOutput::from(&output);
+                for hook in &self.0 {
+                    hook.post_receipt(&op, &hook_output).await;
+                }
+                Ok(output)
+            }
+            Err(e) => {
+                self.on_error(&op, &e).await;
+                Err(e)
+            }
+        }
+    }
+
+    async fn on_error(&self, op: &OperationDescriptor, error: &anyhow::Error) {
+        for hook in &self.0 {
+            hook.on_error(op, error).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use std::sync::{Arc, Mutex};
+
+    use anyhow::anyhow;
+    use async_trait::async_trait;
+    use fvm_shared::address::Address;
+    use fvm_shared::econ::TokenAmount;
+    use ipc_api::subnet_id::SubnetID;
+
+    use super::{Hooks, Operation, OperationDescriptor, OperationHook, OperationOutput};
+
+    /// Refuses sends above a limit, and records what it was called with.
+    #[derive(Default)]
+    struct Limit {
+        max: TokenAmount,
+        calls: Mutex<Vec<&'static str>>,
+    }
+
+    #[async_trait]
+    impl OperationHook for Limit {
+        async fn pre_submit(&self, op: &OperationDescriptor) -> anyhow::Result<()> {
+            self.calls.lock().unwrap().push("pre_submit");
+            if op.operation.value() > self.max {
+                return Err(anyhow!("over the limit"));
+            }
+            Ok(())
+        }
+
+        async fn post_receipt(&self, _op: &OperationDescriptor, _output: &OperationOutput) {
+            self.calls.lock().unwrap().push("post_receipt");
+        }
+
+        async fn on_error(&self, _op: &OperationDescriptor, _error: &anyhow::Error) {
+            self.calls.lock().unwrap().push("on_error");
+        }
+    }
+
+    fn send(amount: u64) -> OperationDescriptor {
+        OperationDescriptor {
+            network: SubnetID::from_str("/r123").unwrap(),
+            from: Address::new_id(100),
+            operation: Operation::SendValue {
+                to: Address::new_id(101),
+                amount: TokenAmount::from_whole(amount),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn hooks_run_around_operations() {
+        let limit = Arc::new(Limit {
+            max: TokenAmount::from_whole(10),
+            ..Default::default()
+        });
+        let mut hooks = Hooks::default();
+        hooks.register(limit.clone());
+
+        hooks.run(send(1), async { Ok(()) }).await.unwrap();
+        assert_eq!(*limit.calls.lock().unwrap(), ["pre_submit", "post_receipt"]);
+        limit.calls.lock().unwrap().clear();
+
+        let submitted = Mutex::new(false);
+        let result = hooks
+            .run(send(100), async {
+                *submitted.lock().unwrap() = true;
+                Ok(())
+            })
+            .await;
+        assert!(result.is_err());
+        assert!(
+            !*submitted.lock().unwrap(),
+            "refused operations are not submitted"
+        );
+        assert_eq!(*limit.calls.lock().unwrap(), ["pre_submit", "on_error"]);
+        limit.calls.lock().unwrap().clear();
+
+        let result: anyhow::Result<()> =
+            hooks.run(send(1), async { Err(anyhow!("reverted")) }).await;
+        assert!(result.is_err());
+        assert_eq!(*limit.calls.lock().unwrap(), ["pre_submit", "on_error"]);
+    }
+
+    #[tokio::test]
+    async fn erc20_transfers_are_not_native_value() {
+        let limit = Arc::new(Limit {
+            max: TokenAmount::from_whole(10),
+            ..Default::default()
+        });
+        let mut hooks = Hooks::default();
+        hooks.register(limit.clone());
+
+        let transfer = OperationDescriptor {
+            network: SubnetID::from_str("/r123").unwrap(),
+            from: Address::new_id(100),
+            operation: Operation::Erc20Transfer {
+                token: Address::new_id(200),
+                to: Address::new_id(101),
+                amount: TokenAmount::from_whole(100),
+            },
+        };
+        assert_eq!(transfer.operation.name(), "erc20_transfer");
+        assert!(transfer.operation.value().is_zero());
+
+        // The token amount doesn't count towards a limit on the native currency...
+        hooks.run(transfer, async { Ok(()) }).await.unwrap();
+        assert_eq!(*limit.calls.lock().unwrap(), ["pre_submit", "post_receipt"]);
+        limit.calls.lock().unwrap().clear();
+
+        // ...which still applies to sending the same amount natively.
+        let result = hooks.run(send(100), async { Ok(()) }).await;
+        assert!(result.is_err());
+        assert_eq!(*limit.calls.lock().unwrap(), ["pre_submit", "on_error"]);
+    }
+}
@@ -0,0 +1,588 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Idempotency keys for state-changing operations.
+//!
+//! Callers tag an operation with a key, and the outcome is persisted under that key, so that
+//! a relayer restarted after a crash doesn't submit the same funds or checkpoint twice: a
+//! completed operation returns its recorded result instead of running again.
+//!
+//! The hash of every transaction the operation signs is recorded with it before the
+//! transaction is broadcast. An operation which failed or was interrupted half-way is
+//! resolved against the subnet before it's tried again: if one of its transactions went
+//! through, its outcome is recovered from the receipt; if one is still in the mempool, the
+//! retry is refused.
+//!
+//! The submissions are kept in [Namespace::Idempotency] of the [ProviderStore].
+
+use std::collections::HashSet;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, Context};
+use ethers::types::H256;
+use fvm_shared::clock::ChainEpoch;
+use ipc_api::receipt::TxReceipt;
+use ipc_api::subnet_id::SubnetID;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::manager::{TransactionQuery, TxStatus};
+use crate::store::{Namespace, ProviderStore};
+
+tokio::task_local! {
+    /// The submission the transactions signed by the current task belong to.
+    static JOURNAL: Journal;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SubmissionStatus {
+    /// The operation started but its outcome was never recorded; it may have been broadcast.
+    Pending,
+    Completed,
+    /// The operation returned an error; it's retried unless one of its transactions landed.
+    Failed,
+}
+
+/// An operation submitted under an idempotency key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Submission {
+    pub key: String,
+    pub operation: String,
+    pub subnet: String,
+    pub status: SubmissionStatus,
+    /// The result of the operation once completed, e.g. its transaction receipt.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The transactions signed by the attempts of the operation, recorded before broadcasting.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tx_hashes: Vec<H256>,
+    /// Seconds since the unix epoch.
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+/// The result of an operation sending a single transaction, recovered from the receipt of the
+/// transaction when the process which sent it didn't record it.
+pub trait FromReceipt: Sized {
+    fn from_receipt(receipt: TxReceipt) -> anyhow::Result<Self>;
+}
+
+impl FromReceipt for TxReceipt {
+    fn from_receipt(receipt: TxReceipt) -> anyhow::Result<Self> {
+        Ok(receipt)
+    }
+}
+
+/// The epoch the transaction was included in, e.g. the result of submitting a checkpoint.
+impl FromReceipt for ChainEpoch {
+    fn from_receipt(receipt: TxReceipt) -> anyhow::Result<Self> {
+        Ok(receipt.epoch)
+    }
+}
+
+/// Record a transaction about to be broadcast with the submission the current task runs under,
+/// if any. Called by the wallet once the transaction is signed.
+pub fn record_tx(tx_hash: H256) -> anyhow::Result<()> {
+    JOURNAL
+        .try_with(|journal| journal.record(tx_hash))
+        .unwrap_or(Ok(()))
+}
+
+/// Records the transactions of the submission under `key`.
+struct Journal {
+    store: Arc<dyn ProviderStore>,
+    key: String,
+}
+
+impl Journal {
+    fn record(&self, tx_hash: H256) -> anyhow::Result<()> {
+        let mut submission: Submission = self
+            .store
+            .get_json(Namespace::Idempotency, self.key.as_bytes())?
+            .ok_or_else(|| anyhow!("submission {} not found", self.key))?;
+
+        submission.tx_hashes.push(tx_hash);
+        submission.updated_at = now();
+
+        self.store
+            .put_json(Namespace::Idempotency, self.key.as_bytes(), &submission)
+    }
+}
+
+/// What the subnet knows about the transactions of an earlier attempt.
+#[derive(Debug)]
+enum Resolution {
+    /// One of the transactions was included and succeeded.
+    Succeeded(TxReceipt),
+    /// One of the transactions is still in the mempool.
+    InFlight(H256),
+    /// None of them succeeded, and some are unknown to the subnet; they may have been dropped,
+    /// or may still be broadcast by another node.
+    Unknown,
+    /// No transaction was signed, or all of them reverted.
+    NotSent,
+}
+
+/// Persists the submissions made under idempotency keys in the [ProviderStore].
+pub struct IdempotencyStore {
+    store: Arc<dyn ProviderStore>,
+    /// Submissions whose transactions the subnet doesn't know about are considered dropped
+    /// and can be retried once their last attempt is older than this.
+    retry_pending_after: Option<Duration>,
+    /// The keys of the operations running in this process.
+    in_flight: Mutex<HashSet<String>>,
+}
+
+impl IdempotencyStore {
+    pub fn new(store: Arc<dyn ProviderStore>) -> Self {
+        Self {
+            store,
+            retry_pending_after: None,
+            in_flight: Default::default(),
+        }
+    }
+
+    /// Allow retrying the submissions whose transactions the subnet doesn't know about once
+    /// they are older than `timeout`, for operations that are safe to repeat once the first
+    /// attempt can be assumed to have been dropped.
+    pub fn with_retry_pending_after(mut self, timeout: Duration) -> Self {
+        self.retry_pending_after = Some(timeout);
+        self
+    }
+
+    /// Look up the submission made under `key`.
+    pub fn get(&self, key: &str) -> anyhow::Result<Option<Submission>> {
+        self.store.get_json(Namespace::Idempotency, key.as_bytes())
+    }
+
+    /// All the submissions, ordered by key.
+    pub fn list(&self) -> anyhow::Result<Vec<Submission>> {
+        self.store
+            .scan_prefix(Namespace::Idempotency, b"")?
+            .into_iter()
+            .map(|(key, value)| {
+                serde_json::from_slice(&value).with_context(|| {
+                    format!("cannot parse submission {}", String::from_utf8_lossy(&key))
+                })
+            })
+            .collect()
+    }
+
+    /// Forget the submission made under `key`, so the key can be used again.
+    pub fn remove(&self, key: &str) -> anyhow::Result<Option<Submission>> {
+        let _guard = self.enter(key)?;
+        let removed = self.get(key)?;
+        if removed.is_some() {
+            self.store.delete(Namespace::Idempotency, key.as_bytes())?;
+        }
+        Ok(removed)
+    }
+
+    /// Run `op` unless an operation was already submitted under `key`.
+    ///
+    /// If it completed, its recorded result is returned without running `op`. If it failed or
+    /// was interrupted, its transactions are looked up with `chain`, the subnet they were sent
+    /// to: if one of them succeeded, the result is recovered from its receipt without running
+    /// `op`; if one is still in the mempool, or the subnet doesn't know about them, this fails,
+    /// as they may still be included.
+    pub async fn run<T, F, Q>(
+        &self,
+        key: &str,
+        operation: &str,
+        subnet: &SubnetID,
+        chain: &Q,
+        op: F,
+    ) -> anyhow::Result<T>
+    where
+        T: Serialize + DeserializeOwned + FromReceipt,
+        F: Future<Output = anyhow::Result<T>>,
+        Q: TransactionQuery + ?Sized,
+    {
+        let _guard = self.enter(key)?;
+
+        if let Some(result) = self.start::<T, _>(key, operation, subnet, chain).await? {
+            tracing::info!("{operation} already submitted under key {key}, skipping");
+            return serde_json::from_value(result)
+                .with_context(|| format!("cannot parse the result recorded under key {key}"));
+        }
+
+        let journal = Journal {
+            store: self.store.clone(),
+            key: key.to_string(),
+        };
+        let outcome = JOURNAL.scope(journal, op).await;
+
+        if let Some(mut submission) = self.get(key)? {
+            match &outcome {
+                Ok(result) => {
+                    submission.status = SubmissionStatus::Completed;
+                    submission.result = Some(serde_json::to_value(result)?);
+                    submission.error = None;
+                }
+                Err(e) => {
+                    submission.status = SubmissionStatus::Failed;
+                    submission.error = Some(format!("{e:#}"));
+                }
+            }
+            submission.updated_at = now();
+            self.put(&submission)?;
+        }
+
+        outcome
+    }
+
+    /// Record the start of an operation, or return the result it completed with before.
+    async fn start<T, Q>(
+        &self,
+        key: &str,
+        operation: &str,
+        subnet: &SubnetID,
+        chain: &Q,
+    ) -> anyhow::Result<Option<serde_json::Value>>
+    where
+        T: Serialize + FromReceipt,
+        Q: TransactionQuery + ?Sized,
+    {
+        let now = now();
+        let existing = self.get(key)?;
+
+        if let Some(existing) = &existing {
+            if existing.operation != operation || existing.subnet != subnet.to_string() {
+                return Err(anyhow!(
+                    "idempotency key {key} was already used for {} on subnet {}",
+                    existing.operation,
+                    existing.subnet
+                ));
+            }
+            if existing.status == SubmissionStatus::Completed {
+                return Ok(existing.result.clone());
+            }
+            match resolve(existing, chain).await? {
+                Resolution::Succeeded(receipt) => {
+                    tracing::info!(
+                        "{operation} under key {key} went through in transaction {:?}",
+                        receipt.tx_hash
+                    );
+                    let result = serde_json::to_value(T::from_receipt(receipt)?)?;
+                    self.put(&Submission {
+                        status: SubmissionStatus::Completed,
+                        result: Some(result.clone()),
+                        error: None,
+                        updated_at: now,
+                        ..existing.clone()
+                    })?;
+                    return Ok(Some(result));
+                }
+                Resolution::InFlight(tx_hash) => {
+                    return Err(anyhow!(
+                        "transaction {tx_hash:?} of {operation} under idempotency key {key} is still pending; try again once it's included or dropped"
+                    ));
+                }
+                Resolution::Unknown if !self.is_abandoned(existing, now) => {
+                    return Err(anyhow!(
+                        "the transactions of {operation} under idempotency key {key}, last sent at {}, are unknown to the subnet and may still be included; check whether it went through and forget the key to retry",
+                        existing.updated_at
+                    ));
+                }
+                Resolution::Unknown | Resolution::NotSent => {}
+            }
+        }
+
+        let (created_at, tx_hashes) =
+            existing.map_or((now, Vec::new()), |s| (s.created_at, s.tx_hashes));
+        self.put(&Submission {
+            key: key.to_string(),
+            operation: operation.to_string(),
+            subnet: subnet.to_string(),
+            status: SubmissionStatus::Pending,
+            result: None,
+            error: None,
+            tx_hashes,
+            created_at,
+            updated_at: now,
+        })?;
+
+        Ok(None)
+    }
+
+    /// Mark `key` as in flight until the guard is dropped, so the same operation isn't run
+    /// twice at the same time.
+    fn enter(&self, key: &str) -> anyhow::Result<InFlight<'_>> {
+        if !self.in_flight.lock().unwrap().insert(key.to_string()) {
+            return Err(anyhow!(
+                "an operation under idempotency key {key} is already in flight"
+            ));
+        }
+        Ok(InFlight {
+            store: self,
+            key: key.to_string(),
+        })
+    }
+
+    fn is_abandoned(&self, submission: &Submission, now: u64) -> bool {
+        self.retry_pending_after
+            .map(|timeout| now.saturating_sub(submission.updated_at) >= timeout.as_secs())
+            .unwrap_or_default()
+    }
+
+    fn put(&self, submission: &Submission) -> anyhow::Result<()> {
+        self.store.put_json(
+            Namespace::Idempotency,
+            submission.key.as_bytes(),
+            submission,
+        )
+    }
+}
+
+/// Releases an idempotency key marked as in flight.
+struct InFlight<'a> {
+    store: &'a IdempotencyStore,
+    key: String,
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.store.in_flight.lock().unwrap().remove(&self.key);
+    }
+}
+
+/// Find out what happened to the transactions of an earlier attempt of a submission.
+async fn resolve<Q>(submission: &Submission, chain: &Q) -> anyhow::Result<Resolution>
+where
+    Q: TransactionQuery + ?Sized,
+{
+    let mut resolution = Resolution::NotSent;
+    for tx_hash in &submission.tx_hashes {
+        match chain.tx_status(*tx_hash).await? {
+            TxStatus::Included(receipt) if receipt.success => {
+                return Ok(Resolution::Succeeded(receipt))
+            }
+            TxStatus::Included(_) => {}
+            TxStatus::Pending => resolution = Resolution::InFlight(*tx_hash),
+            TxStatus::Unknown => {
+                if !matches!(resolution, Resolution::InFlight(_)) {
+                    resolution = Resolution::Unknown;
+                }
+            }
+        }
+    }
+    Ok(resolution)
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::str::FromStr;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use anyhow::anyhow;
+    use async_trait::async_trait;
+    use ethers::types::H256;
+    use fvm_shared::clock::ChainEpoch;
+    use ipc_api::receipt::TxReceipt;
+    use ipc_api::subnet_id::SubnetID;
+
+    use super::{record_tx, IdempotencyStore, SubmissionStatus};
+    use crate::manager::{TransactionQuery, TxStatus};
+    use crate::store::{MemoryStore, ProviderStore};
+
+    /// A subnet which knows about the transactions it's told about.
+    #[derive(Default)]
+    struct MockChain(Mutex<HashMap<H256, TxStatus>>);
+
+    impl MockChain {
+        fn set(&self, tx_hash: H256, status: TxStatus) {
+            self.0.lock().unwrap().insert(tx_hash, status);
+        }
+    }
+
+    #[async_trait]
+    impl TransactionQuery for MockChain {
+        async fn tx_status(&self, tx_hash: H256) -> anyhow::Result<TxStatus> {
+            let statuses = self.0.lock().unwrap();
+            Ok(statuses.get(&tx_hash).cloned().unwrap_or(TxStatus::Unknown))
+        }
+    }
+
+    fn memory_store() -> Arc<dyn ProviderStore> {
+        Arc::new(MemoryStore::new().unwrap())
+    }
+
+    fn receipt(tx_hash: H256, success: bool) -> TxReceipt {
+        TxReceipt {
+            tx_hash,
+            epoch: 10,
+            gas_used: None,
+            success,
+            events: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn completed_submissions_are_not_repeated() {
+        let db = memory_store();
+        let chain = MockChain::default();
+        let subnet = SubnetID::from_str("/r314159").unwrap();
+
+        let store = IdempotencyStore::new(db.clone());
+        let r = store
+            .run("k1", "fund", &subnet, &chain, async { Ok(1 as ChainEpoch) })
+            .await;
+        assert_eq!(r.unwrap(), 1);
+
+        // Reopen as if the process restarted; the operation must not run again.
+        let store = IdempotencyStore::new(db);
+        let r = store
+            .run("k1", "fund", &subnet, &chain, async {
+                Err::<ChainEpoch, _>(anyhow!("ran twice"))
+            })
+            .await;
+        assert_eq!(r.unwrap(), 1);
+        assert_eq!(
+            store.get("k1").unwrap().unwrap().status,
+            SubmissionStatus::Completed
+        );
+
+        // The key can't be reused for another operation.
+        assert!(store
+            .run("k1", "release", &subnet, &chain, async {
+                Ok(2 as ChainEpoch)
+            })
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn failed_submissions_can_be_retried() {
+        let store = IdempotencyStore::new(memory_store());
+        let chain = MockChain::default();
+        let subnet = SubnetID::from_str("/r314159").unwrap();
+
+        let r = store
+            .run("k1", "fund", &subnet, &chain, async {
+                Err::<ChainEpoch, _>(anyhow!("reverted"))
+            })
+            .await;
+        assert!(r.is_err());
+        let failed = store.get("k1").unwrap().unwrap();
+        assert_eq!(failed.status, SubmissionStatus::Failed);
+        assert_eq!(failed.error.as_deref(), Some("reverted"));
+
+        let r = store
+            .run("k1", "fund", &subnet, &chain, async { Ok(3 as ChainEpoch) })
+            .await;
+        assert_eq!(r.unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn interrupted_submissions_without_transactions_are_retried() {
+        let db = memory_store();
+        let chain = MockChain::default();
+        let subnet = SubnetID::from_str("/r314159").unwrap();
+
+        // Simulate a crash before anything was signed.
+        IdempotencyStore::new(db.clone())
+            .start::<ChainEpoch, _>("k1", "fund", &subnet, &chain)
+            .await
+            .unwrap();
+
+        let store = IdempotencyStore::new(db);
+        assert_eq!(
+            store.get("k1").unwrap().unwrap().status,
+            SubmissionStatus::Pending
+        );
+        let r = store
+            .run("k1", "fund", &subnet, &chain, async { Ok(1 as ChainEpoch) })
+            .await;
+        assert_eq!(r.unwrap(), 1);
+
+        assert!(store.remove("k1").unwrap().is_some());
+        assert!(store.list().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn submissions_are_resolved_against_the_chain() {
+        let store = IdempotencyStore::new(memory_store());
+        let chain = MockChain::default();
+        let subnet = SubnetID::from_str("/r314159").unwrap();
+        let tx_hash = H256::repeat_byte(1);
+
+        // The transaction was broadcast, but its receipt never came.
+        let r = store
+            .run("k1", "fund", &subnet, &chain, async move {
+                record_tx(tx_hash)?;
+                Err::<TxReceipt, _>(anyhow!("receipt cannot be obtained"))
+            })
+            .await;
+        assert!(r.is_err());
+        assert_eq!(store.get("k1").unwrap().unwrap().tx_hashes, vec![tx_hash]);
+
+        let ran_twice = || async { Err::<TxReceipt, _>(anyhow!("ran twice")) };
+
+        // Unknown to the subnet, so it may still be included.
+        let err = store
+            .run("k1", "fund", &subnet, &chain, ran_twice())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("unknown to the subnet"), "{err}");
+
+        chain.set(tx_hash, TxStatus::Pending);
+        let err = store
+            .run("k1", "fund", &subnet, &chain, ran_twice())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("still pending"), "{err}");
+
+        // It went through, so the receipt is recorded instead of sending it again.
+        chain.set(tx_hash, TxStatus::Included(receipt(tx_hash, true)));
+        let r = store
+            .run("k1", "fund", &subnet, &chain, ran_twice())
+            .await
+            .unwrap();
+        assert_eq!(r, receipt(tx_hash, true));
+        assert_eq!(
+            store.get("k1").unwrap().unwrap().status,
+            SubmissionStatus::Completed
+        );
+    }
+
+    #[tokio::test]
+    async fn dropped_and_reverted_transactions_are_retried() {
+        let chain = MockChain::default();
+        let subnet = SubnetID::from_str("/r314159").unwrap();
+        let store = IdempotencyStore::new(memory_store()).with_retry_pending_after(Duration::ZERO);
+        let (reverted, dropped) = (H256::repeat_byte(1), H256::repeat_byte(2));
+        chain.set(reverted, TxStatus::Included(receipt(reverted, false)));
+
+        for tx_hash in [reverted, dropped] {
+            let key = format!("{tx_hash:?}");
+            let r = store
+                .run(&key, "fund", &subnet, &chain, async move {
+                    record_tx(tx_hash)?;
+                    Err::<ChainEpoch, _>(anyhow!("failed"))
+                })
+                .await;
+            assert!(r.is_err());
+
+            let r = store
+                .run(&key, "fund", &subnet, &chain, async { Ok(1 as ChainEpoch) })
+                .await;
+            assert_eq!(r.unwrap(), 1);
+        }
+
+        // Transactions signed outside of a submission aren't recorded.
+        record_tx(dropped).unwrap();
+    }
+}
@@ -10,18 +10,25 @@ use serde::de::DeserializeOwned;
 use serde::Deserialize;
 use serde_json::json;
 use serde_json::Value;
+use std::collections::HashSet;
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::net::TcpStream;
-use tokio::spawn;
 use tokio_tungstenite::tungstenite::client::IntoClientRequest;
 use tokio_tungstenite::tungstenite::Message;
 use tokio_tungstenite::MaybeTlsStream;
 use tokio_tungstenite::{connect_async, WebSocketStream};
+use tokio_util::sync::CancellationToken;
 use url::Url;
 
+use crate::supervisor::TaskSupervisor;
+
+mod singleflight;
 #[cfg(test)]
 mod tests;
 
+pub use singleflight::SingleFlight;
+
 const DEFAULT_JSON_RPC_VERSION: &str = "2.0";
 const DEFAULT_JSON_RPC_ID: u8 = 1;
 /// Request timeout of the RPC client. It should be enough to accommodate
@@ -50,6 +57,10 @@ pub struct JsonRpcClientImpl {
     http_client: Client,
     url: Url,
     bearer_token: Option<String>,
+    supervisor: TaskSupervisor,
+    /// Methods whose identical requests in flight are coalesced into one.
+    deduplicated: HashSet<String>,
+    in_flight: Arc<SingleFlight<String, Value>>,
 }
 
 impl JsonRpcClientImpl {
@@ -59,15 +70,82 @@ impl JsonRpcClientImpl {
             http_client: Client::default(),
             url,
             bearer_token: bearer_token.map(String::from),
+            supervisor: TaskSupervisor::new(),
+            deduplicated: HashSet::new(),
+            in_flight: Arc::new(SingleFlight::new()),
         }
     }
+
+    /// Coalesce identical concurrent requests of the given methods into a single call.
+    ///
+    /// Only meant for methods returning immutable data, e.g. a block at a given height, as the
+    /// callers waiting for a request in flight get its result even if they asked after it was
+    /// sent.
+    pub fn with_deduplicated_methods<S: Into<String>>(
+        mut self,
+        methods: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.deduplicated
+            .extend(methods.into_iter().map(Into::into));
+        self
+    }
+
+    /// Run the subscription streams under the given supervisor, so they are closed on shutdown.
+    pub fn with_supervisor(mut self, supervisor: TaskSupervisor) -> Self {
+        self.supervisor = supervisor;
+        self
+    }
 }
 
 #[async_trait]
 impl JsonRpcClient for JsonRpcClientImpl {
     async fn request<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T> {
         let request_body = build_jsonrpc_request(method, params)?;
-        let mut builder = self.http_client.post(self.url.as_str()).json(&request_body);
+
+        let value = if self.deduplicated.contains(method) {
+            let key = request_body.to_string();
+            self.in_flight.run(key, self.send(&request_body)).await?
+        } else {
+            self.send(&request_body).await?
+        };
+
+        serde_json::from_value(value)
+            .map_err(|e| anyhow!("cannot parse json rpc result of {method} due to {e}"))
+    }
+
+    async fn subscribe(&self, method: &str) -> Result<Receiver<Value>> {
+        let mut request = self.url.as_str().into_client_request()?;
+
+        // Add the authorization bearer token if present
+        if self.bearer_token.is_some() {
+            let token_string = format!("Bearer {}", self.bearer_token.as_ref().unwrap());
+            let header_value = HeaderValue::from_str(token_string.as_str())?;
+            request.headers_mut().insert("Authorization", header_value);
+        }
+
+        let (mut ws_stream, _) = connect_async(request).await?;
+        let request_body = build_jsonrpc_request(method, NO_PARAMS)?;
+        ws_stream
+            .send(Message::text(request_body.to_string()))
+            .await?;
+
+        let (send_chan, recv_chan) = async_channel::unbounded::<Value>();
+        let shutdown = self.supervisor.shutdown_signal();
+        if !self.supervisor.spawn(
+            "jsonrpc subscription",
+            handle_stream(ws_stream, send_chan, shutdown),
+        ) {
+            return Err(anyhow!("cannot subscribe while shutting down"));
+        }
+
+        Ok(recv_chan)
+    }
+}
+
+impl JsonRpcClientImpl {
+    /// Send the request over HTTP/HTTPS and return the result.
+    async fn send(&self, request_body: &Value) -> Result<Value> {
+        let mut builder = self.http_client.post(self.url.as_str()).json(request_body);
         builder = builder.timeout(DEFAULT_REQ_TIMEOUT);
 
         // Add the authorization bearer token if present
@@ -80,8 +158,8 @@ impl JsonRpcClient for JsonRpcClientImpl {
         let response_body = response.text().await?;
         tracing::debug!("received raw response body: {:?}", response_body);
 
-        let value =
-            serde_json::from_str::<JsonRpcResponse<T>>(response_body.as_ref()).map_err(|e| {
+        let value = serde_json::from_str::<JsonRpcResponse<Value>>(response_body.as_ref())
+            .map_err(|e| {
                 tracing::error!("cannot parse json rpc client response: {:?}", response_body);
                 anyhow!(
                     "cannot parse json rpc response: {:} due to {:}",
@@ -96,28 +174,6 @@ impl JsonRpcClient for JsonRpcClientImpl {
 
         Result::from(value)
     }
-
-    async fn subscribe(&self, method: &str) -> Result<Receiver<Value>> {
-        let mut request = self.url.as_str().into_client_request()?;
-
-        // Add the authorization bearer token if present
-        if self.bearer_token.is_some() {
-            let token_string = format!("Bearer {}", self.bearer_token.as_ref().unwrap());
-            let header_value = HeaderValue::from_str(token_string.as_str())?;
-            request.headers_mut().insert("Authorization", header_value);
-        }
-
-        let (mut ws_stream, _) = connect_async(request).await?;
-        let request_body = build_jsonrpc_request(method, NO_PARAMS)?;
-        ws_stream
-            .send(Message::text(request_body.to_string()))
-            .await?;
-
-        let (send_chan, recv_chan) = async_channel::unbounded::<Value>();
-        spawn(handle_stream(ws_stream, send_chan));
-
-        Ok(recv_chan)
-    }
 }
 
 /// JsonRpcResponse wraps the json rpc response.
@@ -149,13 +205,22 @@ impl<T: DeserializeOwned> From<JsonRpcResponse<T>> for Result<T> {
 }
 
 // Processes a websocket stream by reading messages from the stream `ws_stream` and sending
-// them to an output channel `chan`.
+// them to an output channel `chan`, until the stream is closed or `shutdown` is signalled.
 async fn handle_stream(
     mut ws_stream: WebSocketStream<MaybeTlsStream<TcpStream>>,
     chan: Sender<Value>,
+    shutdown: CancellationToken,
 ) {
     loop {
-        match ws_stream.next().await {
+        let next = tokio::select! {
+            _ = shutdown.cancelled() => {
+                tracing::trace!("Closing websocket stream on shutdown.");
+                let _ = ws_stream.close(None).await;
+                break;
+            }
+            next = ws_stream.next() => next,
+        };
+        match next {
             None => {
                 tracing::trace!("No message in websocket stream. The stream was closed.");
                 break;
@@ -0,0 +1,195 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Coalescing of identical requests in flight.
+//!
+//! When several components ask for the same immutable data at the same time, e.g. the block
+//! hash at a height or the top-down messages of an epoch, only the first caller makes the
+//! request; the others wait for it and get a copy of its result. Nothing is cached: once the
+//! request is done, the next caller makes a new one.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::Mutex;
+
+use anyhow::anyhow;
+use tokio::sync::broadcast;
+
+/// The outcome shared with the waiting callers; errors are shared as their message.
+type Shared<V> = Result<V, String>;
+
+/// Runs at most one request per key at a time.
+pub struct SingleFlight<K, V> {
+    calls: Mutex<HashMap<K, broadcast::Sender<Shared<V>>>>,
+}
+
+impl<K, V> Default for SingleFlight<K, V> {
+    fn default() -> Self {
+        Self {
+            calls: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<K, V> SingleFlight<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run `request`, unless one with the same key is in flight, in which case wait for its
+    /// result instead.
+    ///
+    /// If the caller making the request is cancelled, one of the waiting callers takes over.
+    pub async fn run<F>(&self, key: K, request: F) -> anyhow::Result<V>
+    where
+        F: Future<Output = anyhow::Result<V>>,
+    {
+        loop {
+            let mut rx = {
+                let mut calls = self.calls.lock().expect("singleflight poisoned");
+                match calls.get(&key) {
+                    Some(tx) => tx.subscribe(),
+                    None => {
+                        let (tx, _) = broadcast::channel(1);
+                        calls.insert(key.clone(), tx);
+                        break;
+                    }
+                }
+            };
+            match rx.recv().await {
+                Ok(res) => return res.map_err(|e| anyhow!(e)),
+                // The caller making the request was cancelled, try again.
+                Err(_) => continue,
+            }
+        }
+
+        let mut call = Call {
+            calls: &self.calls,
+            key: Some(key),
+        };
+        let res = request.await;
+        call.complete(match &res {
+            Ok(v) => Ok(v.clone()),
+            Err(e) => Err(format!("{e:#}")),
+        });
+        res
+    }
+
+    /// Number of requests in flight.
+    pub fn in_flight(&self) -> usize {
+        self.calls.lock().expect("singleflight poisoned").len()
+    }
+}
+
+/// The request made on behalf of all the callers with the same key. Dropping it without
+/// completing it wakes up the waiting callers, so one of them can try again.
+struct Call<'a, K: Hash + Eq, V> {
+    calls: &'a Mutex<HashMap<K, broadcast::Sender<Shared<V>>>>,
+    key: Option<K>,
+}
+
+impl<K: Hash + Eq, V> Call<'_, K, V> {
+    fn take(&mut self) -> Option<broadcast::Sender<Shared<V>>> {
+        let key = self.key.take()?;
+        self.calls
+            .lock()
+            .expect("singleflight poisoned")
+            .remove(&key)
+    }
+
+    fn complete(&mut self, res: Shared<V>) {
+        if let Some(tx) = self.take() {
+            // Nobody might be waiting.
+            let _ = tx.send(res);
+        }
+    }
+}
+
+impl<K: Hash + Eq, V> Drop for Call<'_, K, V> {
+    fn drop(&mut self) {
+        self.take();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use anyhow::anyhow;
+    use futures_util::future::join_all;
+
+    use super::SingleFlight;
+
+    #[tokio::test]
+    async fn identical_requests_are_coalesced() {
+        let flight = Arc::new(SingleFlight::<u64, u64>::new());
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let request = |key: u64| {
+            let flight = flight.clone();
+            let calls = calls.clone();
+            async move {
+                flight
+                    .run(key, async {
+                        calls.fetch_add(1, Ordering::SeqCst);
+                        tokio::time::sleep(Duration::from_millis(50)).await;
+                        Ok(key * 10)
+                    })
+                    .await
+            }
+        };
+
+        let results = join_all((0..10).map(|i| request(i % 2))).await;
+        for (i, res) in results.into_iter().enumerate() {
+            assert_eq!(res.unwrap(), (i as u64 % 2) * 10);
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(flight.in_flight(), 0);
+
+        // Nothing is cached once the request is done.
+        request(0).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn errors_are_shared() {
+        let flight = SingleFlight::<&str, ()>::new();
+        let failing = || {
+            flight.run("key", async {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                Err(anyhow!("height does not exist"))
+            })
+        };
+        let (a, b) = tokio::join!(failing(), failing());
+        assert_eq!(a.unwrap_err().to_string(), "height does not exist");
+        assert_eq!(b.unwrap_err().to_string(), "height does not exist");
+    }
+
+    #[tokio::test]
+    async fn waiters_take_over_a_cancelled_request() {
+        let flight = Arc::new(SingleFlight::<&str, u64>::new());
+
+        let leader = {
+            let flight = flight.clone();
+            tokio::spawn(async move { flight.run("key", std::future::pending()).await })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(flight.in_flight(), 1);
+
+        let waiter = {
+            let flight = flight.clone();
+            tokio::spawn(async move { flight.run("key", async { Ok(42) }).await })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        leader.abort();
+
+        assert_eq!(waiter.await.unwrap().unwrap(), 42);
+        assert_eq!(flight.in_flight(), 0);
+    }
+}
@@ -3,41 +3,125 @@
 //! Ipc agent sdk, contains the json rpc client to interact with the IPC agent rpc server.
 
 use crate::manager::{GetBlockHashResult, TopDownQueryPayload};
-use anyhow::anyhow;
+use address_book::{AddressBook, Entry};
+use anyhow::{anyhow, Context};
+use audit::{AuditLog, AuditQuery, AuditRecord, AuditedWallet, SigningAudit};
+use backup::Backup;
 use base64::Engine;
+use checkpoint::{verify_checkpoint_quorum, CheckpointSchedule, CheckpointVerification};
+use cid::Cid;
 use config::Config;
+use deploy::{DeployedContract, PredictedSubnet};
+use ethers::signers::{LocalWallet, Signer};
+use evm_keystore::EvmKeyStoreBackend;
+use fees::{CrossMsgCost, CrossMsgStep, StepCost};
+use futures_util::{Stream, StreamExt};
+use fvm::{FvmMessage, FvmReceipt};
 use fvm_shared::{
-    address::Address, clock::ChainEpoch, crypto::signature::SignatureType, econ::TokenAmount,
+    address::Address,
+    clock::ChainEpoch,
+    crypto::signature::{Signature, SignatureType},
+    econ::TokenAmount,
 };
-use ipc_api::checkpoint::{BottomUpCheckpointBundle, QuorumReachedEvent};
+use health::{EndpointHealth, HealthMonitor, ProbeOptions};
+use hooks::{Hooks, Operation, OperationDescriptor, OperationHook, OperationOutput};
+use idempotency::{IdempotencyStore, Submission};
+use ipc_api::checkpoint::{BottomUpCheckpoint, BottomUpCheckpointBundle, QuorumReachedEvent};
 use ipc_api::evm::payload_to_evm_address;
 use ipc_api::staking::{StakingChangeRequest, ValidatorInfo};
-use ipc_api::subnet::{PermissionMode, SupplySource};
+use ipc_api::subnet::{PermissionMode, SubnetParam, SupplyKind, SupplySource};
 use ipc_api::{
-    cross::IpcEnvelope,
+    address::IPCAddress,
+    cross::{IpcEnvelope, TopDownReceipt},
+    evidence::DoubleSignEvidence,
+    model::{MlAlgorithm, ModelHash, ModelTransferStatus},
+    receipt::TxReceipt,
     subnet::{ConsensusType, ConstructParams},
     subnet_id::SubnetID,
+    token::Denomination,
 };
 use ipc_wallet::{
-    EthKeyAddress, EvmKeyStore, KeyStore, KeyStoreConfig, PersistentKeyStore, Wallet,
+    EthKeyAddress, EvmKeyStore, KeyStore, KeyStoreConfig, PersistentKeyStore, RemoteKeyStore,
+    Wallet,
 };
+use jsonrpc::SingleFlight;
+use lifecycle::{SubnetLease, SubnetLifecycle, SubnetRemoved};
+use lotus::client::{DefaultLotusJsonRPCClient, LotusJsonRPCClient};
 use lotus::message::wallet::WalletKeyType;
-use manager::{EthSubnetManager, SubnetGenesisInfo, SubnetInfo, SubnetManager};
+use lotus::LotusClient;
+use manager::{
+    ChainParams, EthSubnetManager, ParamChangeProposal, PostboxMsg, SubnetGenesisInfo, SubnetInfo,
+    SubnetManager,
+};
+use multisig::{AdminOperation, Multisig, Proposal};
+use num_traits::ToPrimitive;
+use offline::{
+    BroadcastReceipt, FvmUnsignedMessage, OfflineOp, SignedTx, TxSignature, UnsignedMessage,
+    UnsignedTx,
+};
+use policy::SpendingPolicy;
+use range::RangeQueryOptions;
+use response_cache::ResponseCache;
+use roles::{KeyRole, KeyRoles};
+use routing::Hop;
 use serde::{Deserialize, Serialize};
+use state_diff::{StateActor, StateDiff};
 use std::{
     borrow::Borrow,
-    collections::HashMap,
+    collections::{BTreeMap, BTreeSet, HashMap},
     path::{Path, PathBuf},
     str::FromStr,
-    sync::{Arc, RwLock},
+    sync::{Arc, Mutex, RwLock},
+    time::Duration,
 };
+use supervisor::TaskSupervisor;
+use supply::SupplyReport;
+use tokio::sync::Semaphore;
 use zeroize::Zeroize;
 
+pub mod address_book;
+pub mod audit;
+pub mod backup;
+pub mod call;
 pub mod checkpoint;
 pub mod config;
+pub mod deploy;
+pub mod doctor;
+pub mod evm_keystore;
+pub mod fees;
+pub mod fvm;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod health;
+pub mod hooks;
+pub mod idempotency;
 pub mod jsonrpc;
+pub mod lifecycle;
 pub mod lotus;
 pub mod manager;
+pub mod multisig;
+pub mod offline;
+pub mod policy;
+pub mod propagator;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod range;
+pub mod redact;
+pub mod response_cache;
+#[cfg(feature = "rest")]
+pub mod rest;
+pub mod roles;
+pub mod routing;
+pub mod stake_watcher;
+pub mod state_diff;
+pub mod stats;
+pub mod status;
+pub mod store;
+pub mod supervisor;
+pub mod supply;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod watchtower;
 
 const DEFAULT_REPO_PATH: &str = ".ipc";
 const DEFAULT_CONFIG_NAME: &str = "config.toml";
@@ -46,6 +130,10 @@ const DEFAULT_CONFIG_NAME: &str = "config.toml";
 pub struct Connection {
     subnet: config::Subnet,
     manager: Box<dyn SubnetManager + 'static>,
+    /// Held by the requests of the manager while in flight, if the subnet limits them.
+    request_permits: Option<Arc<Semaphore>>,
+    /// Counts the connection as in flight against the subnet until dropped.
+    lease: SubnetLease,
 }
 
 impl Connection {
@@ -58,39 +146,180 @@ impl Connection {
     pub fn manager(&self) -> &dyn SubnetManager {
         self.manager.borrow()
     }
+
+    /// The number of requests which can be sent before reaching the `max_concurrent_requests`
+    /// of the subnet, shared by the connections of the provider; `None` if unlimited.
+    pub fn available_requests(&self) -> Option<usize> {
+        self.request_permits
+            .as_ref()
+            .map(|permits| permits.available_permits())
+    }
+
+    /// The lease of the subnet held by the connection.
+    pub fn lease(&self) -> &SubnetLease {
+        &self.lease
+    }
 }
 
 #[derive(Clone)]
 pub struct IpcProvider {
     sender: Option<Address>,
-    config: Arc<Config>,
+    /// Swapped on reload, so that all the clones of the provider see the new config.
+    config: Arc<RwLock<Arc<Config>>>,
     fvm_wallet: Option<Arc<RwLock<Wallet>>>,
-    evm_keystore: Option<Arc<RwLock<PersistentKeyStore<EthKeyAddress>>>>,
+    evm_keystore: Option<Arc<RwLock<EvmKeyStoreBackend>>>,
+    /// The idempotency keys, kept in the durable store once it's opened.
+    idempotency: Arc<Mutex<Option<Arc<IdempotencyStore>>>>,
+    address_book: Option<Arc<AddressBook>>,
+    audit_log: Option<Arc<AuditLog>>,
+    key_roles: Option<Arc<KeyRoles>>,
+    hooks: Hooks,
+    in_flight: Arc<InFlightQueries>,
+    /// The response caches of the subnets, opened on first use; `None` if disabled.
+    response_caches: Arc<Mutex<HashMap<SubnetID, Option<Arc<ResponseCache>>>>>,
+    /// The permits shared by the connections to each subnet which limits its requests in flight.
+    request_permits: Arc<Mutex<HashMap<SubnetID, Arc<Semaphore>>>>,
+    /// The health of the endpoints of the subnets, which connections are routed by.
+    health: Arc<HealthMonitor>,
+    /// The repo the durable store is opened in, if any.
+    repo_path: Option<String>,
+    /// The durable store, opened on first use.
+    store: Arc<Mutex<Option<Arc<dyn ProviderStore>>>>,
+    /// The operations in flight against each subnet, drained when it's removed on reload.
+    lifecycle: Arc<SubnetLifecycle>,
+    /// The profile of the config the provider was created with, if any.
+    profile: Option<String>,
+}
+
+/// What a config reload changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReloadReport {
+    pub added: Vec<SubnetID>,
+    pub removed: Vec<SubnetID>,
+    /// Subnets whose config changed; their next connections use the new one.
+    pub changed: Vec<SubnetID>,
+    /// Operations against removed subnets still in flight at the end of the grace period.
+    pub cancelled: usize,
+}
+
+/// Queries of immutable chain data, shared by the concurrent callers asking for the same one.
+#[derive(Default)]
+struct InFlightQueries {
+    block_hashes: SingleFlight<(SubnetID, ChainEpoch), GetBlockHashResult>,
+    top_down_msgs: SingleFlight<(SubnetID, ChainEpoch), TopDownQueryPayload<Vec<IpcEnvelope>>>,
 }
 
 impl IpcProvider {
     fn new(
         config: Arc<Config>,
         fvm_wallet: Arc<RwLock<Wallet>>,
-        evm_keystore: Arc<RwLock<PersistentKeyStore<EthKeyAddress>>>,
+        evm_keystore: Arc<RwLock<EvmKeyStoreBackend>>,
+        address_book: Arc<AddressBook>,
+        audit_log: Arc<AuditLog>,
     ) -> Self {
         Self {
             sender: None,
-            config,
+            config: Arc::new(RwLock::new(config)),
             fvm_wallet: Some(fvm_wallet),
             evm_keystore: Some(evm_keystore),
+            idempotency: Default::default(),
+            address_book: Some(address_book),
+            audit_log: Some(audit_log),
+            key_roles: None,
+            hooks: Hooks::default(),
+            in_flight: Default::default(),
+            response_caches: Default::default(),
+            request_permits: Default::default(),
+            health: Default::default(),
+            repo_path: None,
+            store: Default::default(),
+            lifecycle: Default::default(),
+            profile: None,
         }
     }
 
     /// Initializes an `IpcProvider` from the config specified in the
     /// argument's config path.
     pub fn new_from_config(config_path: String) -> anyhow::Result<Self> {
-        let config = Arc::new(Config::from_file(config_path)?);
+        Self::new_from_config_with_profile(config_path, None)
+    }
+
+    /// Initializes an `IpcProvider` from the `profile` of the config in the config path, or
+    /// from the top of the config if `None`.
+    pub fn new_from_config_with_profile(
+        config_path: String,
+        profile: Option<&str>,
+    ) -> anyhow::Result<Self> {
+        let config = Config::from_file(config_path)?;
+        match profile {
+            Some(name) => Self::new_with_profile(&config, name),
+            None => Self::new_with_config(config),
+        }
+    }
+
+    /// Write an encrypted backup of the config, the keystores and the address book of the
+    /// provider to `path`, to restore them on another machine with [IpcProvider::restore].
+    /// Only the owner can read the file, like the keystores.
+    pub fn backup(&self, path: impl AsRef<Path>, passphrase: &str) -> anyhow::Result<()> {
+        use std::io::Write;
+
+        let data = Backup::collect(&self.config())?.seal(passphrase)?;
+        let write = || -> std::io::Result<()> {
+            let mut file = std::fs::File::create(path.as_ref())?;
+            #[cfg(unix)]
+            ipc_wallet::set_user_perm(&file)?;
+            file.write_all(&data)
+        };
+        write().with_context(|| format!("cannot write backup to {}", path.as_ref().display()))
+    }
+
+    /// Restore the keystores and the address book of the backup at `path` to the keystore
+    /// directory of its config, and create a provider with them. The config is not written
+    /// anywhere; get it with [IpcProvider::config] to save it.
+    pub fn restore(path: impl AsRef<Path>, passphrase: &str) -> anyhow::Result<Self> {
+        let data = std::fs::read(path.as_ref())
+            .with_context(|| format!("cannot read backup from {}", path.as_ref().display()))?;
+        let backup = Backup::open(&data, passphrase)?;
+        backup.restore_files()?;
+        Self::new_with_config(backup.config()?)
+    }
+
+    /// An `IpcProvider` on the profile `name` of the config of this one, with the keystores of
+    /// the profile. It shares nothing with this provider: hooks, the sender and stores set on
+    /// it have to be set again.
+    pub fn with_profile(&self, name: &str) -> anyhow::Result<Self> {
+        Self::new_with_profile(&self.config(), name)
+    }
+
+    fn new_with_profile(config: &Config, name: &str) -> anyhow::Result<Self> {
+        let mut provider = Self::new_with_config(config.profile(name)?)?;
+        provider.profile = Some(name.to_string());
+        tracing::info!(profile = name, "using config profile");
+        Ok(provider)
+    }
+
+    fn new_with_config(config: Config) -> anyhow::Result<Self> {
+        let config = Arc::new(config);
         let fvm_wallet = Arc::new(RwLock::new(Wallet::new(new_fvm_wallet_from_config(
             config.clone(),
         )?)));
         let evm_keystore = Arc::new(RwLock::new(new_evm_keystore_from_config(config.clone())?));
-        Ok(Self::new(config, fvm_wallet, evm_keystore))
+        let address_book = Arc::new(new_address_book_from_config(config.clone())?);
+        let audit_log = Arc::new(new_audit_log_from_config(config.clone())?);
+        let policy = new_spending_policy_from_config(config.clone())?;
+        let key_roles = Arc::new(new_key_roles_from_config(config.clone())?);
+        let requires_roles = !config.operation_roles.is_empty();
+        let repo_path = config.keystore_path.clone();
+        let mut provider = Self::new(config, fvm_wallet, evm_keystore, address_book, audit_log);
+        if let Some(policy) = policy {
+            provider.register_hook(Arc::new(policy));
+        }
+        if requires_roles {
+            provider.register_hook(key_roles.clone());
+        }
+        provider.key_roles = Some(key_roles);
+        provider.repo_path = repo_path;
+        Ok(provider)
     }
 
     /// Initializes a new `IpcProvider` configured to interact with
@@ -107,14 +336,35 @@ impl IpcProvider {
             let fvm_wallet = Arc::new(RwLock::new(Wallet::new(new_fvm_keystore_from_path(
                 &repo_path,
             )?)));
-            let evm_keystore = Arc::new(RwLock::new(new_evm_keystore_from_path(&repo_path)?));
-            Ok(Self::new(config, fvm_wallet, evm_keystore))
+            let evm_keystore = Arc::new(RwLock::new(EvmKeyStoreBackend::File(
+                new_evm_keystore_from_path(&repo_path)?,
+            )));
+            let address_book = Arc::new(new_address_book_from_path(&repo_path)?);
+            let audit_log = Arc::new(new_audit_log_from_path(&repo_path));
+            let key_roles = Arc::new(new_key_roles_from_path(&repo_path, Default::default())?);
+            let mut provider = Self::new(config, fvm_wallet, evm_keystore, address_book, audit_log);
+            provider.key_roles = Some(key_roles);
+            provider.repo_path = Some(repo_path);
+            Ok(provider)
         } else {
             Ok(Self {
                 sender: None,
-                config,
+                config: Arc::new(RwLock::new(config)),
                 fvm_wallet: None,
                 evm_keystore: None,
+                idempotency: Default::default(),
+                address_book: None,
+                audit_log: None,
+                key_roles: None,
+                hooks: Hooks::default(),
+                in_flight: Default::default(),
+                response_caches: Default::default(),
+                request_permits: Default::default(),
+                health: Default::default(),
+                repo_path: None,
+                store: Default::default(),
+                lifecycle: Default::default(),
+                profile: None,
             })
         }
     }
@@ -126,23 +376,37 @@ impl IpcProvider {
 
     /// Get the connection instance for the subnet.
     pub fn connection(&self, subnet: &SubnetID) -> Option<Connection> {
-        let subnets = &self.config.subnets;
+        let config = self.config();
 
-        match subnets.get(subnet) {
+        match config.subnets.get(subnet) {
             Some(subnet) => match &subnet.config {
                 config::subnet::SubnetConfig::Fevm(_) => {
+                    let lease = self.lifecycle.lease(&subnet.id).ok()?;
                     let wallet = self.evm_keystore.clone();
-                    let manager =
-                        match EthSubnetManager::from_subnet_with_wallet_store(subnet, wallet) {
-                            Ok(w) => Some(w),
-                            Err(e) => {
-                                tracing::warn!("error initializing evm manager: {e}");
-                                return None;
-                            }
-                        };
+                    let request_permits = self.request_permits(subnet);
+                    let manager = match self.health.select(subnet).and_then(|(url, client)| {
+                        EthSubnetManager::from_endpoint(
+                            subnet,
+                            url,
+                            client,
+                            wallet,
+                            request_permits.clone(),
+                        )
+                    }) {
+                        Ok(w) => Some(match &self.audit_log {
+                            Some(log) => w.with_audit_log(log.clone(), subnet.id.clone()),
+                            None => w,
+                        }),
+                        Err(e) => {
+                            tracing::warn!("error initializing evm manager: {e}");
+                            return None;
+                        }
+                    };
                     Some(Connection {
                         manager: Box::new(manager.unwrap()),
                         subnet: subnet.clone(),
+                        request_permits,
+                        lease,
                     })
                 }
             },
@@ -150,12 +414,82 @@ impl IpcProvider {
         }
     }
 
+    /// The permits limiting the requests in flight to a subnet, if its config sets a limit.
+    ///
+    /// Connections are opened per call, so they share the permits of the provider.
+    fn request_permits(&self, subnet: &config::Subnet) -> Option<Arc<Semaphore>> {
+        let limit = subnet.max_concurrent_requests()?;
+        let mut permits = self.request_permits.lock().unwrap();
+        Some(
+            permits
+                .entry(subnet.id.clone())
+                .or_insert_with(|| Arc::new(Semaphore::new(limit)))
+                .clone(),
+        )
+    }
+
+    /// The health of the endpoints of the subnets, as of their last probe.
+    ///
+    /// Endpoints are listed once they are probed or connected to; they are only probed in the
+    /// background after [Self::spawn_health_probes].
+    pub fn endpoint_health(&self) -> Vec<EndpointHealth> {
+        self.health.health()
+    }
+
+    /// Probe the endpoints of every subnet in the config once, and return their health.
+    pub async fn probe_endpoints(&self) -> Vec<EndpointHealth> {
+        let config = self.config();
+        self.health.probe(config.subnets.values()).await;
+        self.health.health()
+    }
+
+    /// Probe the endpoints of every subnet in the config in the background until `supervisor`
+    /// shuts down, keeping connections to them open and routing new connections away from the
+    /// ones that are degraded. Returns whether the probes were started.
+    pub fn spawn_health_probes(&self, supervisor: &TaskSupervisor) -> bool {
+        let health = self.health.clone();
+        let subnets = self.config().subnets.values().cloned().collect();
+        let shutdown = supervisor.shutdown_signal();
+        supervisor.spawn("endpoint-health", async move {
+            health.run(subnets, shutdown).await
+        })
+    }
+
+    /// Set how the endpoints are probed, forgetting their health so far.
+    pub fn with_probe_options(&mut self, options: ProbeOptions) {
+        self.health = Arc::new(HealthMonitor::new(options));
+    }
+
+    /// The response cache of a subnet, if enabled in its config.
+    ///
+    /// A cache which can't be opened is disabled, as it only saves requests.
+    fn response_cache(&self, subnet: &SubnetID) -> Option<Arc<ResponseCache>> {
+        let mut caches = self.response_caches.lock().unwrap();
+        caches
+            .entry(subnet.clone())
+            .or_insert_with(|| {
+                let config = self.config();
+                let subnet_config = config.subnets.get(subnet)?;
+                match new_response_cache_from_config(&config, subnet_config) {
+                    Ok(cache) => cache.map(Arc::new),
+                    Err(e) => {
+                        tracing::warn!("response cache of {subnet} disabled: {e:#}");
+                        None
+                    }
+                }
+            })
+            .clone()
+    }
+
     /// Get the connection of a subnet, or return an error.
     fn get_connection(&self, subnet: &SubnetID) -> anyhow::Result<Connection> {
+        if self.lifecycle.is_removed(subnet) {
+            return Err(SubnetRemoved(subnet.clone()).into());
+        }
         match self.connection(subnet) {
             None => Err(anyhow!(
                 "subnet not found: {subnet}; known subnets: {:?}",
-                self.config
+                self.config()
                     .subnets
                     .keys()
                     .map(|id| id.to_string())
@@ -174,7 +508,7 @@ impl IpcProvider {
     ///
     /// This method should be used when we want the wallet retrieval to throw an error
     /// if it is not configured (i.e. when the provider needs to sign transactions).
-    pub fn evm_wallet(&self) -> anyhow::Result<Arc<RwLock<PersistentKeyStore<EthKeyAddress>>>> {
+    pub fn evm_wallet(&self) -> anyhow::Result<Arc<RwLock<EvmKeyStoreBackend>>> {
         if let Some(wallet) = &self.evm_keystore {
             Ok(wallet.clone())
         } else {
@@ -192,6 +526,195 @@ impl IpcProvider {
         }
     }
 
+    /// Returns the store of the operations submitted under idempotency keys, kept in the
+    /// durable [store](Self::store) of the provider, and throws an error if it can't be opened.
+    ///
+    /// Wrap an operation with [IdempotencyStore::run] to make sure it's submitted only once,
+    /// with the connection to the subnet its transactions are sent to:
+    /// ```ignore
+    /// let store = provider.idempotency_store()?;
+    /// let parent = provider.connection(&subnet.parent().unwrap()).unwrap();
+    /// let fund = provider.fund(subnet.clone(), None, None, None, amount);
+    /// let receipt = store.run(key, "fund", &subnet, parent.manager(), fund).await?;
+    /// ```
+    pub fn idempotency_store(&self) -> anyhow::Result<Arc<IdempotencyStore>> {
+        let mut idempotency = self.idempotency.lock().unwrap();
+        if let Some(store) = idempotency.as_ref() {
+            return Ok(store.clone());
+        }
+        let opened = Arc::new(IdempotencyStore::new(self.store()?));
+        *idempotency = Some(opened.clone());
+        Ok(opened)
+    }
+
+    /// Use `store` to persist the operations submitted under idempotency keys.
+    pub fn with_idempotency_store(&mut self, store: Arc<IdempotencyStore>) {
+        self.idempotency = Arc::new(Mutex::new(Some(store)));
+    }
+
+    /// Looks up the operation submitted under an idempotency key.
+    pub fn submission(&self, key: &str) -> anyhow::Result<Option<Submission>> {
+        self.idempotency_store()?.get(key)
+    }
+
+    /// Lists the operations submitted under idempotency keys.
+    pub fn list_submissions(&self) -> anyhow::Result<Vec<Submission>> {
+        self.idempotency_store()?.list()
+    }
+
+    /// Forgets the operation submitted under an idempotency key, so that it can be retried.
+    pub fn forget_submission(&self, key: &str) -> anyhow::Result<Option<Submission>> {
+        self.idempotency_store()?.remove(key)
+    }
+
+    /// Returns the address book persisted in the keystore directory, and throws an error if no
+    /// keystore is configured.
+    pub fn address_book(&self) -> anyhow::Result<Arc<AddressBook>> {
+        if let Some(book) = &self.address_book {
+            Ok(book.clone())
+        } else {
+            Err(anyhow!("No address book found in provider"))
+        }
+    }
+
+    /// Use `book` to look up aliases.
+    pub fn with_address_book(&mut self, book: Arc<AddressBook>) {
+        self.address_book = Some(book);
+    }
+
+    /// Parses an alias from the address book, or a Filecoin or `0x` address, into an address.
+    pub fn resolve_address(&self, s: &str) -> anyhow::Result<Address> {
+        match &self.address_book {
+            Some(book) => book.resolve_address(s),
+            None => address_book::parse_address(s),
+        }
+    }
+
+    /// Parses an alias from the address book, or a subnet ID, into a subnet ID.
+    pub fn resolve_subnet(&self, s: &str) -> anyhow::Result<SubnetID> {
+        match &self.address_book {
+            Some(book) => book.resolve_subnet(s),
+            None => Ok(SubnetID::from_str(s)?),
+        }
+    }
+
+    /// Names an address in the address book, so that `alias` can be used in its place.
+    pub fn set_address_alias(&self, alias: &str, address: &str) -> anyhow::Result<Entry> {
+        self.address_book()?.set_address(alias, address)
+    }
+
+    /// Names a subnet in the address book, so that `alias` can be used in its place.
+    pub fn set_subnet_alias(&self, alias: &str, subnet: &SubnetID) -> anyhow::Result<Entry> {
+        self.address_book()?.set_subnet(alias, subnet)
+    }
+
+    /// Lists the aliases of the address book.
+    pub fn list_aliases(&self) -> anyhow::Result<Vec<Entry>> {
+        Ok(self.address_book()?.list())
+    }
+
+    /// Removes an alias from the address book.
+    pub fn remove_alias(&self, alias: &str) -> anyhow::Result<Option<Entry>> {
+        self.address_book()?.remove(alias)
+    }
+
+    /// Returns the roles of the keys of the keystore, and throws an error if no keystore is
+    /// configured.
+    pub fn key_roles(&self) -> anyhow::Result<Arc<KeyRoles>> {
+        self.key_roles
+            .clone()
+            .ok_or_else(|| anyhow!("No key roles found in provider"))
+    }
+
+    /// Gives `role` to the key of `addr`, on top of the roles it has.
+    pub fn assign_key_role(&self, addr: &Address, role: KeyRole) -> anyhow::Result<()> {
+        self.key_roles()?.assign(addr, role)
+    }
+
+    /// Takes `role` from the key of `addr`; false if it didn't have it.
+    pub fn unassign_key_role(&self, addr: &Address, role: KeyRole) -> anyhow::Result<bool> {
+        self.key_roles()?.unassign(addr, role)
+    }
+
+    /// Lists the roles of the keys, by address.
+    pub fn list_key_roles(&self) -> anyhow::Result<BTreeMap<String, BTreeSet<KeyRole>>> {
+        Ok(self.key_roles()?.list())
+    }
+
+    /// The key to send the operation named `operation` with, if the config restricts it to
+    /// the keys of a role; see [KeyRoles::sender_for]. The operations are named like
+    /// [Operation::name], or [roles::SUBMIT_CHECKPOINT] for checkpoint submissions.
+    pub fn sender_for(&self, operation: &str) -> anyhow::Result<Option<Address>> {
+        self.key_roles()?.sender_for(operation)
+    }
+
+    /// Returns the audit log of the signatures made with the keys of the keystores, and throws
+    /// an error if no keystore is configured.
+    pub fn audit_log(&self) -> anyhow::Result<Arc<AuditLog>> {
+        if let Some(log) = &self.audit_log {
+            Ok(log.clone())
+        } else {
+            Err(anyhow!("No audit log found in provider"))
+        }
+    }
+
+    /// Use `log` to record the signatures made with the keys.
+    pub fn with_audit_log(&mut self, log: Arc<AuditLog>) {
+        self.audit_log = Some(log);
+    }
+
+    /// Lists the signatures of the audit log matching `query`, oldest first.
+    pub fn query_audit_log(&self, query: &AuditQuery) -> anyhow::Result<Vec<AuditRecord>> {
+        self.audit_log()?.query(query)
+    }
+
+    /// Returns the durable store of the provider, opened in the keystore directory on first
+    /// use, and throws an error if no keystore is configured or the store can't be opened.
+    pub fn store(&self) -> anyhow::Result<Arc<dyn ProviderStore>> {
+        let mut store = self.store.lock().unwrap();
+        if let Some(store) = store.as_ref() {
+            return Ok(store.clone());
+        }
+        let Some(repo_path) = &self.repo_path else {
+            return Err(anyhow!("No store found in provider"));
+        };
+        let opened = new_provider_store_from_path(repo_path)?;
+        *store = Some(opened.clone());
+        Ok(opened)
+    }
+
+    /// Use `store` for the durable state of the provider.
+    pub fn with_store(&mut self, store: Arc<dyn ProviderStore>) {
+        self.store = Arc::new(Mutex::new(Some(store)));
+    }
+
+    /// Run `hook` around every operation the provider submits, after the hooks registered
+    /// before it.
+    pub fn register_hook(&mut self, hook: Arc<dyn OperationHook>) {
+        self.hooks.register(hook);
+    }
+
+    /// Submit `operation`, sent to `network` by `from`, between the registered hooks.
+    async fn submit<T, F>(
+        &self,
+        network: &SubnetID,
+        from: Address,
+        operation: Operation,
+        submit: F,
+    ) -> anyhow::Result<T>
+    where
+        F: std::future::Future<Output = anyhow::Result<T>>,
+        for<'a> OperationOutput: From<&'a T>,
+    {
+        let op = OperationDescriptor {
+            network: network.clone(),
+            from,
+            operation,
+        };
+        let lease = self.lifecycle.lease(network)?;
+        lease.run(self.hooks.run(op, submit)).await
+    }
+
     fn check_sender(
         &mut self,
         subnet: &config::Subnet,
@@ -228,7 +751,93 @@ impl IpcProvider {
 
     /// Lists available subnet connections
     pub fn list_connections(&self) -> HashMap<SubnetID, config::Subnet> {
-        self.config.subnets.clone()
+        self.config().subnets.clone()
+    }
+
+    /// The config of the provider, as of its last reload.
+    pub fn config(&self) -> Arc<Config> {
+        self.config.read().unwrap().clone()
+    }
+
+    /// Replace the subnets of the config with those of `config`, for the provider and its
+    /// clones.
+    ///
+    /// Removed subnets can't be connected to anymore, failing with [SubnetRemoved]. The
+    /// operations in flight against them are given `grace` to finish, after which those sent
+    /// through the provider are cancelled with [SubnetRemoved] too; their transactions may
+    /// still be included. Reads in flight are left to finish. The keystores, the spending
+    /// policy and the endpoints probed in the background are not reloaded.
+    pub async fn reload_config(&self, config: Config, grace: Duration) -> ReloadReport {
+        let old = self.config();
+        let mut report = ReloadReport::default();
+        for (id, subnet) in &config.subnets {
+            match old.subnets.get(id) {
+                None => report.added.push(id.clone()),
+                Some(old) if old != subnet => report.changed.push(id.clone()),
+                Some(_) => {}
+            }
+            self.lifecycle.restore(id);
+        }
+        report.removed = old
+            .subnets
+            .keys()
+            .filter(|id| !config.subnets.contains_key(id))
+            .cloned()
+            .collect();
+
+        // Stop leasing the removed subnets before they disappear from the config, so that new
+        // operations fail with `SubnetRemoved` rather than an unknown subnet.
+        let removals = report
+            .removed
+            .iter()
+            .map(|id| self.lifecycle.remove(id))
+            .collect::<Vec<_>>();
+        *self.config.write().unwrap() = Arc::new(config);
+
+        for id in report.removed.iter().chain(&report.changed) {
+            self.response_caches.lock().unwrap().remove(id);
+            self.request_permits.lock().unwrap().remove(id);
+        }
+        tracing::info!(
+            added = report.added.len(),
+            removed = report.removed.len(),
+            changed = report.changed.len(),
+            "config reloaded"
+        );
+
+        report.cancelled =
+            futures_util::future::join_all(removals.into_iter().map(|r| r.drain(grace)))
+                .await
+                .into_iter()
+                .sum();
+        if report.cancelled > 0 {
+            tracing::warn!(
+                cancelled = report.cancelled,
+                "operations against removed subnets cancelled"
+            );
+        }
+        report
+    }
+
+    /// Read the config at `path` again, and reload it like [Self::reload_config]. A provider
+    /// created with a profile reloads the same profile.
+    pub async fn reload_config_from_file(
+        &self,
+        path: impl AsRef<Path>,
+        grace: Duration,
+    ) -> anyhow::Result<ReloadReport> {
+        let mut config = Config::from_file_async(path).await?;
+        if let Some(name) = &self.profile {
+            config = config.profile(name)?;
+        }
+        Ok(self.reload_config(config, grace).await)
+    }
+
+    /// Shut the provider down as if all its subnets were removed from the config: the
+    /// operations in flight are given `grace` to finish, and later ones fail with
+    /// [SubnetRemoved].
+    pub async fn shutdown(&self, grace: Duration) -> ReloadReport {
+        self.reload_config(Config::new(), grace).await
     }
 }
 
@@ -242,6 +851,7 @@ impl IpcProvider {
     // use a SubnetOpts struct to provide the creation arguments and
     // remove this allow
     #[allow(clippy::too_many_arguments)]
+    #[tracing::instrument(skip_all, fields(subnet_id = %parent, method = "create_subnet"))]
     pub async fn create_subnet(
         &mut self,
         from: Option<Address>,
@@ -253,11 +863,26 @@ impl IpcProvider {
         min_cross_msg_fee: TokenAmount,
         permission_mode: PermissionMode,
         supply_source: SupplySource,
+        genesis_balances: BTreeMap<Address, TokenAmount>,
     ) -> anyhow::Result<Address> {
         let conn = self.get_connection(&parent)?;
 
         let subnet_config = conn.subnet();
+        ensure_single_key_admin(subnet_config)?;
         let sender = self.check_sender(subnet_config, from)?;
+        check_genesis_balances(&supply_source, &genesis_balances)?;
+        // Each account pre-funds its own balance, so it has to sign.
+        if !genesis_balances.is_empty() {
+            let keystore = self.evm_wallet()?;
+            for account in genesis_balances.keys() {
+                let addr = payload_to_evm_address(account.payload())?;
+                if keystore.read().unwrap().get(&addr.into())?.is_none() {
+                    return Err(anyhow!(
+                        "the genesis balance of {account} can't be pre-funded: its key is not in the keystore"
+                    ));
+                }
+            }
+        }
 
         let constructor_params = ConstructParams {
             parent,
@@ -270,19 +895,56 @@ impl IpcProvider {
             min_cross_msg_fee,
             permission_mode,
             supply_source,
+            genesis_balances: genesis_balances.clone(),
         };
 
-        conn.manager()
-            .create_subnet(sender, constructor_params)
-            .await
+        let network = constructor_params.parent.clone();
+        let address = self
+            .submit(
+                &network,
+                sender,
+                Operation::CreateSubnet(constructor_params.clone()),
+                conn.manager().create_subnet(sender, constructor_params),
+            )
+            .await?;
+
+        let subnet = SubnetID::new_from_parent(&network, address);
+        for (account, amount) in genesis_balances {
+            self.pre_fund(subnet.clone(), Some(account), amount)
+                .await
+                .with_context(|| {
+                    format!("subnet {subnet} was created, but the genesis balance of {account} was not pre-funded")
+                })?;
+        }
+        Ok(address)
+    }
+
+    /// The subnet the next subnet actor created in `parent` by `creator` would be the actor of,
+    /// so it can be configured ahead of [IpcProvider::create_subnet]. `creator` is the registry
+    /// of the parent if `None`, and `nonce` its current one, in which case the prediction only
+    /// holds until another subnet is created in `parent`.
+    pub async fn predict_subnet_address(
+        &self,
+        parent: &SubnetID,
+        creator: Option<Address>,
+        nonce: Option<u64>,
+    ) -> anyhow::Result<PredictedSubnet> {
+        let conn = self.get_connection(parent)?;
+        let creator = creator.unwrap_or_else(|| conn.subnet().registry_addr());
+        let nonce = match nonce {
+            Some(nonce) => nonce,
+            None => conn.manager().account_nonce(&creator).await?,
+        };
+        deploy::predict_subnet_address(parent, &creator, nonce)
     }
 
+    #[tracing::instrument(skip_all, fields(subnet_id = %subnet, method = "join_subnet"))]
     pub async fn join_subnet(
         &mut self,
         subnet: SubnetID,
         from: Option<Address>,
         collateral: TokenAmount,
-    ) -> anyhow::Result<ChainEpoch> {
+    ) -> anyhow::Result<TxReceipt> {
         let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
         let conn = self.get_connection(&parent)?;
 
@@ -300,9 +962,18 @@ impl IpcProvider {
         let hex_public_key = hex::encode(public_key);
         log::info!("joining subnet with public key: {hex_public_key:?}");
 
-        conn.manager()
-            .join_subnet(subnet, sender, collateral, public_key.into())
-            .await
+        let op = Operation::JoinSubnet {
+            subnet: subnet.clone(),
+            collateral: collateral.clone(),
+        };
+        self.submit(
+            &parent,
+            sender,
+            op,
+            conn.manager()
+                .join_subnet(subnet, sender, collateral, public_key.into()),
+        )
+        .await
     }
 
     pub async fn pre_fund(
@@ -310,13 +981,23 @@ impl IpcProvider {
         subnet: SubnetID,
         from: Option<Address>,
         balance: TokenAmount,
-    ) -> anyhow::Result<()> {
+    ) -> anyhow::Result<TxReceipt> {
         let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
         let conn = self.get_connection(&parent)?;
         let subnet_config = conn.subnet();
         let sender = self.check_sender(subnet_config, from)?;
 
-        conn.manager().pre_fund(subnet, sender, balance).await
+        let op = Operation::PreFund {
+            subnet: subnet.clone(),
+            amount: balance.clone(),
+        };
+        self.submit(
+            &parent,
+            sender,
+            op,
+            conn.manager().pre_fund(subnet, sender, balance),
+        )
+        .await
     }
 
     pub async fn pre_release(
@@ -324,86 +1005,242 @@ impl IpcProvider {
         subnet: SubnetID,
         from: Option<Address>,
         amount: TokenAmount,
-    ) -> anyhow::Result<()> {
+    ) -> anyhow::Result<TxReceipt> {
         let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
         let conn = self.get_connection(&parent)?;
 
         let subnet_config = conn.subnet();
         let sender = self.check_sender(subnet_config, from)?;
 
-        conn.manager().pre_release(subnet, sender, amount).await
+        let op = Operation::PreRelease {
+            subnet: subnet.clone(),
+            amount: amount.clone(),
+        };
+        self.submit(
+            &parent,
+            sender,
+            op,
+            conn.manager().pre_release(subnet, sender, amount),
+        )
+        .await
     }
 
+    #[tracing::instrument(skip_all, fields(subnet_id = %subnet, method = "stake"))]
     pub async fn stake(
         &mut self,
         subnet: SubnetID,
         from: Option<Address>,
         collateral: TokenAmount,
-    ) -> anyhow::Result<()> {
+    ) -> anyhow::Result<TxReceipt> {
         let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
         let conn = self.get_connection(&parent)?;
 
         let subnet_config = conn.subnet();
         let sender = self.check_sender(subnet_config, from)?;
 
-        conn.manager().stake(subnet, sender, collateral).await
+        let op = Operation::Stake {
+            subnet: subnet.clone(),
+            collateral: collateral.clone(),
+        };
+        self.submit(
+            &parent,
+            sender,
+            op,
+            conn.manager().stake(subnet, sender, collateral),
+        )
+        .await
     }
 
+    #[tracing::instrument(skip_all, fields(subnet_id = %subnet, method = "unstake"))]
     pub async fn unstake(
         &mut self,
         subnet: SubnetID,
         from: Option<Address>,
         collateral: TokenAmount,
-    ) -> anyhow::Result<()> {
+    ) -> anyhow::Result<TxReceipt> {
         let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
         let conn = self.get_connection(&parent)?;
 
         let subnet_config = conn.subnet();
         let sender = self.check_sender(subnet_config, from)?;
 
-        conn.manager().unstake(subnet, sender, collateral).await
+        let op = Operation::Unstake {
+            subnet: subnet.clone(),
+            collateral: collateral.clone(),
+        };
+        self.submit(
+            &parent,
+            sender,
+            op,
+            conn.manager().unstake(subnet, sender, collateral),
+        )
+        .await
     }
 
+    #[tracing::instrument(skip_all, fields(subnet_id = %subnet, method = "leave_subnet"))]
     pub async fn leave_subnet(
         &mut self,
         subnet: SubnetID,
         from: Option<Address>,
-    ) -> anyhow::Result<()> {
+    ) -> anyhow::Result<TxReceipt> {
         let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
         let conn = self.get_connection(&parent)?;
 
         let subnet_config = conn.subnet();
         let sender = self.check_sender(subnet_config, from)?;
 
-        conn.manager().leave_subnet(subnet, sender).await
+        let op = Operation::LeaveSubnet {
+            subnet: subnet.clone(),
+        };
+        self.submit(
+            &parent,
+            sender,
+            op,
+            conn.manager().leave_subnet(subnet, sender),
+        )
+        .await
     }
 
     pub async fn claim_collateral(
         &mut self,
         subnet: SubnetID,
         from: Option<Address>,
-    ) -> anyhow::Result<()> {
+    ) -> anyhow::Result<TxReceipt> {
         let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
         let conn = self.get_connection(&parent)?;
 
         let subnet_config = conn.subnet();
         let sender = self.check_sender(subnet_config, from)?;
 
-        conn.manager().claim_collateral(subnet, sender).await
+        let op = Operation::ClaimCollateral {
+            subnet: subnet.clone(),
+        };
+        self.submit(
+            &parent,
+            sender,
+            op,
+            conn.manager().claim_collateral(subnet, sender),
+        )
+        .await
     }
 
+    #[tracing::instrument(skip_all, fields(subnet_id = %subnet, method = "kill_subnet"))]
     pub async fn kill_subnet(
         &mut self,
         subnet: SubnetID,
         from: Option<Address>,
-    ) -> anyhow::Result<()> {
+    ) -> anyhow::Result<TxReceipt> {
+        let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
+        let conn = self.get_connection(&parent)?;
+
+        let subnet_config = conn.subnet();
+        ensure_single_key_admin(subnet_config)?;
+        let sender = self.check_sender(subnet_config, from)?;
+
+        let op = Operation::KillSubnet {
+            subnet: subnet.clone(),
+        };
+        self.submit(
+            &parent,
+            sender,
+            op,
+            conn.manager().kill_subnet(subnet, sender),
+        )
+        .await
+    }
+
+    /// Report a validator who signed conflicting votes in a subnet to the subnet actor on its parent.
+    pub async fn report_double_sign(
+        &mut self,
+        subnet: SubnetID,
+        from: Option<Address>,
+        evidence: DoubleSignEvidence,
+    ) -> anyhow::Result<TxReceipt> {
+        let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
+        let conn = self.get_connection(&parent)?;
+
+        let subnet_config = conn.subnet();
+        let sender = self.check_sender(subnet_config, from)?;
+
+        let op = Operation::ReportDoubleSign {
+            subnet: subnet.clone(),
+            validator: evidence.validator,
+        };
+        self.submit(
+            &parent,
+            sender,
+            op,
+            conn.manager().report_double_sign(subnet, sender, evidence),
+        )
+        .await
+    }
+
+    /// Propose to change a parameter of a subnet to its active validators, voting for it
+    /// right away. Returns the ID of the proposal, for the other validators to [Self::vote] on.
+    pub async fn propose_change(
+        &mut self,
+        subnet: SubnetID,
+        from: Option<Address>,
+        param: SubnetParam,
+        value: u64,
+    ) -> anyhow::Result<(u64, TxReceipt)> {
+        let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
+        let conn = self.get_connection(&parent)?;
+
+        let subnet_config = conn.subnet();
+        let sender = self.check_sender(subnet_config, from)?;
+
+        let op = Operation::ProposeParamChange {
+            subnet: subnet.clone(),
+            param,
+            value,
+        };
+        self.submit(
+            &parent,
+            sender,
+            op,
+            conn.manager()
+                .propose_param_change(subnet, sender, param, value),
+        )
+        .await
+    }
+
+    /// Vote for a parameter change proposal of a subnet as one of its active validators. The
+    /// change is applied once the votes reach the majority percentage of the subnet.
+    pub async fn vote(
+        &mut self,
+        subnet: SubnetID,
+        from: Option<Address>,
+        id: u64,
+    ) -> anyhow::Result<TxReceipt> {
         let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
         let conn = self.get_connection(&parent)?;
 
         let subnet_config = conn.subnet();
         let sender = self.check_sender(subnet_config, from)?;
 
-        conn.manager().kill_subnet(subnet, sender).await
+        let op = Operation::VoteParamChange {
+            subnet: subnet.clone(),
+            id,
+        };
+        self.submit(
+            &parent,
+            sender,
+            op,
+            conn.manager().vote_param_change(subnet, sender, id),
+        )
+        .await
+    }
+
+    /// List the parameter change proposals of a subnet, open and closed.
+    pub async fn list_proposals(
+        &self,
+        subnet: &SubnetID,
+    ) -> anyhow::Result<Vec<ParamChangeProposal>> {
+        let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
+        let conn = self.get_connection(&parent)?;
+
+        conn.manager().list_param_change_proposals(subnet).await
     }
 
     pub async fn list_child_subnets(
@@ -425,6 +1262,7 @@ impl IpcProvider {
 
     /// Funds an account in a child subnet, if `to` is `None`, the self account
     /// is funded.
+    #[tracing::instrument(skip_all, fields(subnet_id = %subnet, method = "fund"))]
     pub async fn fund(
         &mut self,
         subnet: SubnetID,
@@ -432,7 +1270,7 @@ impl IpcProvider {
         from: Option<Address>,
         to: Option<Address>,
         amount: TokenAmount,
-    ) -> anyhow::Result<ChainEpoch> {
+    ) -> anyhow::Result<TxReceipt> {
         let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
         let conn = self.get_connection(&parent)?;
 
@@ -444,30 +1282,52 @@ impl IpcProvider {
             Some(addr) => addr,
         };
 
-        conn.manager()
-            .fund(subnet, gateway_addr, sender, to.unwrap_or(sender), amount)
-            .await
+        let to = to.unwrap_or(sender);
+        let op = Operation::Fund {
+            subnet: subnet.clone(),
+            to,
+            amount: amount.clone(),
+        };
+        self.submit(
+            &parent,
+            sender,
+            op,
+            conn.manager()
+                .fund(subnet, gateway_addr, sender, to, amount),
+        )
+        .await
     }
 
     /// Funds an account in a child subnet with erc20 token, provided that the supply source kind is
     /// `ERC20`. If `from` is None, it will use the default address config in `ipc.toml`.
     /// If `to` is `None`, the `from` account will be funded.
+    #[tracing::instrument(skip_all, fields(subnet_id = %subnet, method = "fund_with_token"))]
     pub async fn fund_with_token(
         &mut self,
         subnet: SubnetID,
         from: Option<Address>,
         to: Option<Address>,
         amount: TokenAmount,
-    ) -> anyhow::Result<ChainEpoch> {
+    ) -> anyhow::Result<TxReceipt> {
         let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
         let conn = self.get_connection(&parent)?;
 
         let subnet_config = conn.subnet();
         let sender = self.check_sender(subnet_config, from)?;
 
-        conn.manager()
-            .fund_with_token(subnet, sender, to.unwrap_or(sender), amount)
-            .await
+        let to = to.unwrap_or(sender);
+        let op = Operation::FundWithToken {
+            subnet: subnet.clone(),
+            to,
+            amount: amount.clone(),
+        };
+        self.submit(
+            &parent,
+            sender,
+            op,
+            conn.manager().fund_with_token(subnet, sender, to, amount),
+        )
+        .await
     }
 
     /// Approve an erc20 token for transfer by the gateway. Can be used in preparation for fund_with_token.
@@ -478,7 +1338,7 @@ impl IpcProvider {
         subnet: SubnetID,
         from: Option<Address>,
         amount: TokenAmount,
-    ) -> anyhow::Result<ChainEpoch> {
+    ) -> anyhow::Result<TxReceipt> {
         let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
         let conn = match self.connection(&parent) {
             None => return Err(anyhow!("target parent subnet not found")),
@@ -488,11 +1348,22 @@ impl IpcProvider {
         let subnet_config = conn.subnet();
         let sender = self.check_sender(subnet_config, from)?;
 
-        conn.manager().approve_token(subnet, sender, amount).await
+        let op = Operation::ApproveToken {
+            subnet: subnet.clone(),
+            amount: amount.clone(),
+        };
+        self.submit(
+            &parent,
+            sender,
+            op,
+            conn.manager().approve_token(subnet, sender, amount),
+        )
+        .await
     }
 
     /// Release to an account in a child subnet, if `to` is `None`, the self account
     /// is funded.
+    #[tracing::instrument(skip_all, fields(subnet_id = %subnet, method = "release"))]
     pub async fn release(
         &mut self,
         subnet: SubnetID,
@@ -500,7 +1371,7 @@ impl IpcProvider {
         from: Option<Address>,
         to: Option<Address>,
         amount: TokenAmount,
-    ) -> anyhow::Result<ChainEpoch> {
+    ) -> anyhow::Result<TxReceipt> {
         let conn = match self.connection(&subnet) {
             None => return Err(anyhow!("target subnet not found: {subnet}")),
             Some(conn) => conn,
@@ -514,9 +1385,19 @@ impl IpcProvider {
             Some(addr) => addr,
         };
 
-        conn.manager()
-            .release(gateway_addr, sender, to.unwrap_or(sender), amount)
-            .await
+        let to = to.unwrap_or(sender);
+        let op = Operation::Release {
+            subnet: subnet.clone(),
+            to,
+            amount: amount.clone(),
+        };
+        self.submit(
+            &subnet,
+            sender,
+            op,
+            conn.manager().release(gateway_addr, sender, to, amount),
+        )
+        .await
     }
 
     /// Propagate a cross-net message forward. For `postbox_msg_key`, we are using bytes because different
@@ -528,18 +1409,27 @@ impl IpcProvider {
         _gateway_addr: Address,
         _from: Address,
         _postbox_msg_key: Vec<u8>,
-    ) -> anyhow::Result<()> {
+    ) -> anyhow::Result<TxReceipt> {
         todo!()
     }
 
+    /// The cross-net messages waiting in the postbox of the gateway of a subnet, which need a
+    /// [Self::propagate] to move on to their destination.
+    pub async fn list_postbox(&self, subnet: &SubnetID) -> anyhow::Result<Vec<PostboxMsg>> {
+        let conn = self.get_connection(subnet)?;
+
+        conn.manager().list_postbox().await
+    }
+
     /// Send value between two addresses in a subnet
+    #[tracing::instrument(skip_all, fields(subnet_id = %subnet, method = "send_value"))]
     pub async fn send_value(
         &mut self,
         subnet: &SubnetID,
         from: Option<Address>,
         to: Address,
         amount: TokenAmount,
-    ) -> anyhow::Result<()> {
+    ) -> anyhow::Result<TxReceipt> {
         let conn = self.get_connection(subnet)?;
 
         let subnet_config = conn.subnet();
@@ -558,39 +1448,613 @@ impl IpcProvider {
         //     }
         // };
 
-        conn.manager().send_value(sender, to, amount).await
+        let op = Operation::SendValue {
+            to,
+            amount: amount.clone(),
+        };
+        self.submit(
+            subnet,
+            sender,
+            op,
+            conn.manager().send_value(sender, to, amount),
+        )
+        .await
     }
 
-    /// Get the balance of an address
-    pub async fn wallet_balance(
+    /// Ask the faucet of a devnet subnet to send test funds to `to`. Each address can only be
+    /// dripped once per cooldown of the faucet, which is set in the genesis of the subnet.
+    #[tracing::instrument(skip_all, fields(subnet_id = %subnet, method = "faucet_drip"))]
+    pub async fn faucet_drip(
+        &mut self,
+        subnet: &SubnetID,
+        from: Option<Address>,
+        to: Address,
+    ) -> anyhow::Result<TxReceipt> {
+        let conn = self.get_connection(subnet)?;
+
+        let subnet_config = conn.subnet();
+        let sender = self.check_sender(subnet_config, from)?;
+
+        let op = Operation::FaucetDrip { to };
+        self.submit(subnet, sender, op, conn.manager().faucet_drip(sender, to))
+            .await
+    }
+
+    /// The balance of `owner` in the ERC-20 token contract at `token` of `subnet`, in the base
+    /// units of the token.
+    pub async fn erc20_balance(
         &self,
         subnet: &SubnetID,
-        address: &Address,
+        token: Address,
+        owner: Address,
     ) -> anyhow::Result<TokenAmount> {
         let conn = self.get_connection(subnet)?;
-
-        conn.manager().wallet_balance(address).await
+        conn.manager().erc20_balance(token, owner).await
     }
 
-    pub async fn chain_head(&self, subnet: &SubnetID) -> anyhow::Result<ChainEpoch> {
+    /// Transfer `amount` base units of the ERC-20 token contract at `token` of `subnet` to `to`.
+    /// If `from` is `None`, it will use the default address config in `ipc.toml`.
+    #[tracing::instrument(skip_all, fields(subnet_id = %subnet, method = "erc20_transfer"))]
+    pub async fn erc20_transfer(
+        &mut self,
+        subnet: &SubnetID,
+        from: Option<Address>,
+        token: Address,
+        to: Address,
+        amount: TokenAmount,
+    ) -> anyhow::Result<TxReceipt> {
         let conn = self.get_connection(subnet)?;
 
-        conn.manager().chain_head_height().await
-    }
+        let subnet_config = conn.subnet();
+        let sender = self.check_sender(subnet_config, from)?;
 
-    /// Obtain the genesis epoch of the input subnet.
-    pub async fn genesis_epoch(&self, subnet: &SubnetID) -> anyhow::Result<ChainEpoch> {
-        let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
-        let conn = self.get_connection(&parent)?;
-        conn.manager().genesis_epoch(subnet).await
+        let op = Operation::Erc20Transfer {
+            token,
+            to,
+            amount: amount.clone(),
+        };
+        self.submit(
+            subnet,
+            sender,
+            op,
+            conn.manager().erc20_transfer(sender, token, to, amount),
+        )
+        .await
     }
 
-    /// Get the validator information.
-    pub async fn get_validator_info(
+    /// The data returned by calling the contract at `to` in `subnet` with `calldata`, without
+    /// sending a transaction. The calldata can be built with [call::encode_call].
+    pub async fn call_contract(
         &self,
         subnet: &SubnetID,
-        validator: &Address,
-    ) -> anyhow::Result<ValidatorInfo> {
+        to: Address,
+        calldata: Vec<u8>,
+    ) -> anyhow::Result<Vec<u8>> {
+        let conn = self.get_connection(subnet)?;
+        conn.manager().call_contract(to, calldata).await
+    }
+
+    /// Send a transaction calling the contract at `to` in `subnet` with `calldata`, paying it
+    /// `value`. If `from` is `None`, it will use the default address config in `ipc.toml`.
+    #[tracing::instrument(skip_all, fields(subnet_id = %subnet, method = "send_contract_tx"))]
+    pub async fn send_contract_tx(
+        &mut self,
+        subnet: &SubnetID,
+        from: Option<Address>,
+        to: Address,
+        calldata: Vec<u8>,
+        value: TokenAmount,
+    ) -> anyhow::Result<TxReceipt> {
+        let conn = self.get_connection(subnet)?;
+
+        let subnet_config = conn.subnet();
+        let sender = self.check_sender(subnet_config, from)?;
+
+        let op = Operation::ContractTx {
+            to,
+            value: value.clone(),
+        };
+        self.submit(
+            subnet,
+            sender,
+            op,
+            conn.manager().send_contract_tx(sender, to, calldata, value),
+        )
+        .await
+    }
+
+    /// Push a message to an actor of the FVM `subnet`, signed with the FVM wallet of the
+    /// provider, and wait for it to be executed.
+    #[tracing::instrument(skip_all, fields(subnet_id = %subnet, method = "push_message"))]
+    pub async fn push_message(
+        &mut self,
+        subnet: &SubnetID,
+        msg: FvmMessage,
+    ) -> anyhow::Result<FvmReceipt> {
+        let client = self.lotus_client(subnet)?;
+
+        let op = Operation::PushMessage {
+            to: msg.to,
+            method: msg.method,
+            value: msg.value.clone(),
+        };
+        self.submit(subnet, msg.from, op, async move {
+            let (from, to) = (msg.from, msg.to);
+            let cid = client.mpool_push(msg.into()).await?;
+            tracing::info!(
+                "pushed message {cid} from {from} to {to}, waiting for it to be executed"
+            );
+            wait_for_message(&client, cid, to).await
+        })
+        .await
+    }
+
+    /// Build the transaction of `op` in `subnet`, with its nonce, gas and fees filled in, to be
+    /// signed offline with [IpcProvider::sign_offline] and broadcast later with
+    /// [IpcProvider::broadcast_signed]. The nonce is taken, so no other transaction should be
+    /// sent by the same address in between.
+    pub async fn build_unsigned(
+        &mut self,
+        subnet: &SubnetID,
+        op: OfflineOp,
+    ) -> anyhow::Result<UnsignedTx> {
+        match op {
+            OfflineOp::Evm {
+                from,
+                to,
+                calldata,
+                value,
+            } => {
+                let conn = self.get_connection(subnet)?;
+
+                let subnet_config = conn.subnet();
+                let from = self.check_sender(subnet_config, from)?;
+                let tx = conn
+                    .manager()
+                    .build_unsigned_tx(from, to, calldata, value)
+                    .await?;
+                Ok(UnsignedTx {
+                    subnet: subnet.clone(),
+                    from,
+                    message: UnsignedMessage::Evm(tx),
+                })
+            }
+            OfflineOp::Fvm(msg) => {
+                let client = self.unsigned_lotus_client(subnet)?;
+                let from = msg.from;
+                let message = client.mpool_prepare(msg.into()).await?;
+                Ok(UnsignedTx {
+                    subnet: subnet.clone(),
+                    from,
+                    message: UnsignedMessage::Fvm(FvmUnsignedMessage::from_message(&message)),
+                })
+            }
+        }
+    }
+
+    /// Sign a transaction built by [IpcProvider::build_unsigned] with the key of its sender,
+    /// without connecting to its subnet, so that it can be done on a machine without network
+    /// access.
+    ///
+    /// The transaction goes through the pre-submit hooks first, like the spending policy and
+    /// the roles of the keys, which guard the keys of this provider.
+    pub async fn sign_offline(&self, tx: &UnsignedTx) -> anyhow::Result<SignedTx> {
+        let op = OperationDescriptor {
+            network: tx.subnet.clone(),
+            from: tx.from,
+            operation: tx.operation()?,
+        };
+        self.hooks.check(&op).await?;
+
+        let audit = self
+            .audit_log
+            .clone()
+            .map(|log| SigningAudit::new(log, tx.subnet.clone()));
+
+        let signature = match &tx.message {
+            UnsignedMessage::Evm(evm_tx) => {
+                let addr = payload_to_evm_address(tx.from.payload())?;
+                if evm_tx.from() != Some(&addr) {
+                    return Err(anyhow!("the transaction is not sent by {}", tx.from));
+                }
+                // Without a chain ID the signature could be replayed on any chain.
+                if evm_tx.chain_id().is_none() {
+                    return Err(anyhow!("the transaction has no chain id"));
+                }
+                let key_info = self
+                    .evm_wallet()?
+                    .read()
+                    .unwrap()
+                    .get(&addr.into())?
+                    .ok_or_else(|| {
+                        anyhow!("address {addr:} does not have private key in key store")
+                    })?;
+                // Bound to the chain of the subnet, not the one in the file, so that a
+                // transaction edited to target another chain is refused.
+                let wallet = LocalWallet::from_bytes(key_info.private_key())?
+                    .with_chain_id(tx.subnet.chain_id());
+                let signature = AuditedWallet::new(wallet, audit)
+                    .sign_transaction(evm_tx)
+                    .await?;
+                TxSignature::Evm(signature)
+            }
+            UnsignedMessage::Fvm(msg) => {
+                let message = msg.to_message(tx.from);
+                let signature =
+                    lotus::client::sign_message(&self.fvm_wallet()?, audit.as_ref(), &message)?;
+                TxSignature::from_fvm(signature)
+            }
+        };
+
+        Ok(SignedTx {
+            tx: tx.clone(),
+            signature,
+        })
+    }
+
+    /// Broadcast a transaction signed by [IpcProvider::sign_offline] to its subnet, and wait
+    /// for it to be executed.
+    #[tracing::instrument(skip_all, fields(subnet_id = %signed.tx.subnet, method = "broadcast_signed"))]
+    pub async fn broadcast_signed(&self, signed: &SignedTx) -> anyhow::Result<BroadcastReceipt> {
+        let SignedTx { tx, signature } = signed;
+        let op = OperationDescriptor {
+            network: tx.subnet.clone(),
+            from: tx.from,
+            operation: tx.operation()?,
+        };
+        let lease = self.lifecycle.lease(&tx.subnet)?;
+
+        // The pre-submit hooks ran when the transaction was signed, where the key is; anyone
+        // holding the signed transaction can broadcast it anyway.
+        match (&tx.message, signature) {
+            (UnsignedMessage::Evm(evm_tx), TxSignature::Evm(signature)) => {
+                let conn = self.get_connection(&tx.subnet)?;
+                let raw = evm_tx.rlp_signed(signature).to_vec();
+                let broadcast = async move {
+                    let receipt = conn.manager().broadcast_signed_tx(raw).await?;
+                    Ok(BroadcastReceipt::Evm(receipt))
+                };
+                lease.run(self.hooks.run_checked(op, broadcast)).await
+            }
+            (UnsignedMessage::Fvm(msg), TxSignature::Fvm { sig_type, bytes }) => {
+                let client = self.unsigned_lotus_client(&tx.subnet)?;
+                let message = msg.to_message(tx.from);
+                let signature = Signature {
+                    sig_type: *sig_type,
+                    bytes: bytes.clone(),
+                };
+                let broadcast = async move {
+                    let cid = client.mpool_push_signed(&message, signature).await?;
+                    tracing::info!(
+                        "pushed message {cid} signed offline, waiting for it to be executed"
+                    );
+                    let receipt = wait_for_message(&client, cid, message.to).await?;
+                    Ok(BroadcastReceipt::Fvm(receipt))
+                };
+                lease.run(self.hooks.run_checked(op, broadcast)).await
+            }
+            _ => Err(anyhow!(
+                "the signature doesn't match the type of the transaction"
+            )),
+        }
+    }
+
+    /// Apply a message to an actor of the FVM `subnet` on top of `tipset`, the chain head if
+    /// empty, without sending it, and return what the actor returned.
+    pub async fn state_call(
+        &self,
+        subnet: &SubnetID,
+        msg: FvmMessage,
+        tipset: Vec<Cid>,
+    ) -> anyhow::Result<FvmReceipt> {
+        let client = self.lotus_client(subnet)?;
+        let r = client.state_call(msg.into(), tipset).await?;
+        let receipt = r
+            .receipt
+            .ok_or_else(|| anyhow!("state call failed: {}", r.error))?;
+        FvmReceipt::from_receipt(&receipt)
+    }
+
+    /// Deploy a contract in `subnet`, created by its `bytecode` with the ABI encoded
+    /// `constructor_args`. With a `salt`, it is created with `CREATE2` through the deterministic
+    /// deployment proxy, at the address given by [IpcProvider::contract_address] in every
+    /// subnet; otherwise at an address depending on the nonce of the sender.
+    /// If `from` is `None`, it will use the default address config in `ipc.toml`.
+    #[tracing::instrument(skip_all, fields(subnet_id = %subnet, method = "deploy_contract"))]
+    pub async fn deploy_contract(
+        &mut self,
+        subnet: &SubnetID,
+        from: Option<Address>,
+        bytecode: &[u8],
+        constructor_args: &[u8],
+        salt: Option<[u8; 32]>,
+    ) -> anyhow::Result<DeployedContract> {
+        let conn = self.get_connection(subnet)?;
+
+        let subnet_config = conn.subnet();
+        let sender = self.check_sender(subnet_config, from)?;
+
+        let init_code = deploy::init_code(bytecode, constructor_args);
+        let op = Operation::DeployContract { salt };
+        self.submit(
+            subnet,
+            sender,
+            op,
+            conn.manager().deploy_contract(sender, init_code, salt),
+        )
+        .await
+    }
+
+    /// The address a contract created by `bytecode` with `constructor_args` is deployed at by
+    /// [IpcProvider::deploy_contract] with `salt`.
+    pub fn contract_address(
+        salt: [u8; 32],
+        bytecode: &[u8],
+        constructor_args: &[u8],
+    ) -> ethers::types::Address {
+        deploy::create2_address(salt, &deploy::init_code(bytecode, constructor_args))
+    }
+
+    /// The owner of the token `token_id` of the ERC-721 contract at `token` of `subnet`.
+    pub async fn erc721_owner_of(
+        &self,
+        subnet: &SubnetID,
+        token: Address,
+        token_id: ethers::types::U256,
+    ) -> anyhow::Result<Address> {
+        let conn = self.get_connection(subnet)?;
+        conn.manager().erc721_owner_of(token, token_id).await
+    }
+
+    /// Send a model to another subnet through the `ModelExchange` contracts deployed at
+    /// `exchange` in `subnet` and at `to_exchange` in `to_subnet`.
+    ///
+    /// Returns the hash identifying the model, which can be used to track the transfer
+    /// with [IpcProvider::model_transfer_status].
+    #[allow(clippy::too_many_arguments)]
+    #[tracing::instrument(skip_all, fields(subnet_id = %subnet, method = "export_model"))]
+    pub async fn export_model(
+        &mut self,
+        subnet: &SubnetID,
+        from: Option<Address>,
+        exchange: Address,
+        to_subnet: &SubnetID,
+        to_exchange: Address,
+        model: Vec<u8>,
+        value: TokenAmount,
+    ) -> anyhow::Result<ModelHash> {
+        let route = routing::route(subnet, to_subnet)
+            .ok_or_else(|| anyhow!("no route from {subnet} to {to_subnet}"))?;
+        tracing::debug!(hops = route.hops().len(), "exporting model to {to_subnet}");
+
+        let conn = self.get_connection(subnet)?;
+
+        let subnet_config = conn.subnet();
+        let sender = self.check_sender(subnet_config, from)?;
+        let to = IPCAddress::new(to_subnet, &to_exchange)?;
+
+        let op = Operation::ExportModel {
+            to_subnet: to_subnet.clone(),
+            value: value.clone(),
+        };
+        self.submit(
+            subnet,
+            sender,
+            op,
+            conn.manager()
+                .export_model(sender, exchange, to, model, value),
+        )
+        .await
+    }
+
+    /// Check whether a model sent with [IpcProvider::export_model] has arrived at the
+    /// `ModelExchange` contract deployed at `exchange` in `subnet`.
+    #[tracing::instrument(skip_all, fields(subnet_id = %subnet, method = "model_transfer_status"))]
+    pub async fn model_transfer_status(
+        &self,
+        subnet: &SubnetID,
+        exchange: Address,
+        model_hash: ModelHash,
+    ) -> anyhow::Result<ModelTransferStatus> {
+        let conn = self.get_connection(subnet)?;
+
+        let imported = conn.manager().has_model(exchange, model_hash).await?;
+
+        Ok(if imported {
+            ModelTransferStatus::Imported
+        } else {
+            ModelTransferStatus::Pending
+        })
+    }
+
+    /// Train a model with the machine learning actor of `subnet`. The model is returned
+    /// rather than stored, so it can be used with [IpcProvider::ml_predict] or exported.
+    #[tracing::instrument(skip_all, fields(subnet_id = %subnet, method = "ml_train"))]
+    pub async fn ml_train(
+        &mut self,
+        subnet: &SubnetID,
+        from: Option<Address>,
+        algorithm: MlAlgorithm,
+        input_matrix: Vec<Vec<i64>>,
+        labels: Vec<i64>,
+    ) -> anyhow::Result<Vec<u8>> {
+        let conn = self.get_connection(subnet)?;
+
+        let subnet_config = conn.subnet();
+        let sender = self.check_sender(subnet_config, from)?;
+
+        conn.manager()
+            .ml_train(sender, algorithm, input_matrix, labels)
+            .await
+    }
+
+    /// Predict with a model returned by [IpcProvider::ml_train], using the machine learning
+    /// actor of `subnet`.
+    #[tracing::instrument(skip_all, fields(subnet_id = %subnet, method = "ml_predict"))]
+    pub async fn ml_predict(
+        &mut self,
+        subnet: &SubnetID,
+        from: Option<Address>,
+        algorithm: MlAlgorithm,
+        input_matrix: Vec<Vec<i64>>,
+        model: Vec<u8>,
+    ) -> anyhow::Result<Vec<i64>> {
+        let conn = self.get_connection(subnet)?;
+
+        let subnet_config = conn.subnet();
+        let sender = self.check_sender(subnet_config, from)?;
+
+        conn.manager()
+            .ml_predict(sender, algorithm, input_matrix, model)
+            .await
+    }
+
+    /// Get the balance of an address
+    pub async fn wallet_balance(
+        &self,
+        subnet: &SubnetID,
+        address: &Address,
+    ) -> anyhow::Result<TokenAmount> {
+        let conn = self.get_connection(subnet)?;
+
+        conn.manager().wallet_balance(address).await
+    }
+
+    /// Compare the state of an IPC actor deployed in `subnet` between `epoch_a` and `epoch_b`.
+    /// The subnet actor of a child is deployed in its parent.
+    pub async fn state_diff(
+        &self,
+        subnet: &SubnetID,
+        actor: StateActor,
+        epoch_a: ChainEpoch,
+        epoch_b: ChainEpoch,
+    ) -> anyhow::Result<StateDiff> {
+        if let StateActor::SubnetActor(child) = &actor {
+            if child.parent().as_ref() != Some(subnet) {
+                return Err(anyhow!(
+                    "the subnet actor of {child} is not deployed in {subnet}"
+                ));
+            }
+        }
+        let conn = self.get_connection(subnet)?;
+
+        let a = conn.manager().actor_state(&actor, epoch_a).await?;
+        let b = conn.manager().actor_state(&actor, epoch_b).await?;
+        Ok(StateDiff::new(actor, &a, &b))
+    }
+
+    /// Account for the supply of `subnet` between its parent and itself: the collateral and
+    /// supply the gateway of the parent holds for it, the supply the child reports, and the
+    /// value of the messages in flight both ways, flagging what breaks conservation.
+    ///
+    /// The child has to run Fendermint to report its circulating supply.
+    pub async fn supply_report(&self, subnet: &SubnetID) -> anyhow::Result<SupplyReport> {
+        let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
+        let parent_conn = self.get_connection(&parent)?;
+        let child_conn = self.get_connection(subnet)?;
+        let gateway_addr = parent_conn.subnet().gateway_addr();
+
+        let info = parent_conn
+            .manager()
+            .list_child_subnets(gateway_addr)
+            .await?
+            .remove(subnet)
+            .ok_or_else(|| anyhow!("subnet {subnet} is not registered in {parent}"))?;
+
+        let gateway_balance = parent_conn.manager().wallet_balance(&gateway_addr).await?;
+        let supply_source = parent_conn
+            .manager()
+            .get_subnet_supply_source(subnet)
+            .await?;
+        let gateway_token_balance = if supply_source.kind == SupplyKind::ERC20 as u8 {
+            let token = ipc_api::ethers_address_to_fil_address(&supply_source.token_address)?;
+            Some(
+                parent_conn
+                    .manager()
+                    .erc20_balance(token, gateway_addr)
+                    .await?,
+            )
+        } else {
+            None
+        };
+
+        let child_circ_supply = child_conn.manager().circ_supply().await?;
+
+        // Top-down messages committed in the parent after the finality the child executed.
+        let finality = child_conn.manager().latest_parent_finality().await?;
+        let parent_head = parent_conn.manager().chain_head_height().await?;
+        let mut pending_top_down = TokenAmount::default();
+        let mut msgs = std::pin::pin!(self.stream_top_down_msgs(
+            subnet,
+            finality + 1,
+            parent_head,
+            RangeQueryOptions::default(),
+        ));
+        while let Some(res) = msgs.next().await {
+            let (_, payload) = res?;
+            for msg in payload.value {
+                pending_top_down += msg.value;
+            }
+        }
+
+        // Bottom-up messages batched in the child for checkpoints the parent didn't commit.
+        let last = parent_conn
+            .manager()
+            .last_bottom_up_checkpoint_height(subnet)
+            .await?;
+        let period = parent_conn.manager().checkpoint_period(subnet).await?;
+        if period <= 0 {
+            return Err(anyhow!("invalid checkpoint period {period} for {subnet}"));
+        }
+        let child_head = child_conn.manager().chain_head_height().await?;
+        let mut pending_bottom_up = TokenAmount::default();
+        let mut height = last + period;
+        while height <= child_head + period {
+            for msg in child_conn.manager().bottom_up_msg_batch(height).await? {
+                pending_bottom_up += msg.value;
+            }
+            height += period;
+        }
+
+        Ok(SupplyReport::new(
+            subnet.clone(),
+            info.stake,
+            info.circ_supply,
+            child_circ_supply,
+            pending_top_down,
+            pending_bottom_up,
+            gateway_balance,
+            gateway_token_balance,
+        ))
+    }
+
+    pub async fn chain_head(&self, subnet: &SubnetID) -> anyhow::Result<ChainEpoch> {
+        let conn = self.get_connection(subnet)?;
+
+        conn.manager().chain_head_height().await
+    }
+
+    /// Obtain the version of the application running the subnet, to gate the features which
+    /// depend on its upgrades.
+    pub async fn app_version(&self, subnet: &SubnetID) -> anyhow::Result<u64> {
+        let conn = self.get_connection(subnet)?;
+
+        conn.manager().app_version().await
+    }
+
+    /// Obtain the genesis epoch of the input subnet.
+    pub async fn genesis_epoch(&self, subnet: &SubnetID) -> anyhow::Result<ChainEpoch> {
+        let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
+        let conn = self.get_connection(&parent)?;
+        conn.manager().genesis_epoch(subnet).await
+    }
+
+    /// Get the validator information.
+    pub async fn get_validator_info(
+        &self,
+        subnet: &SubnetID,
+        validator: &Address,
+    ) -> anyhow::Result<ValidatorInfo> {
         let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
         let conn = self.get_connection(&parent)?;
 
@@ -605,8 +2069,39 @@ impl IpcProvider {
     ) -> anyhow::Result<TopDownQueryPayload<Vec<StakingChangeRequest>>> {
         let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
         let conn = self.get_connection(&parent)?;
+        let fetch = conn.manager().get_validator_changeset(subnet, epoch);
 
-        conn.manager().get_validator_changeset(subnet, epoch).await
+        match self.response_cache(&parent) {
+            Some(cache) => {
+                cache
+                    .get_or_fetch("get_validator_changeset", subnet, epoch, fetch, || {
+                        conn.manager().chain_head_height()
+                    })
+                    .await
+            }
+            None => fetch.await,
+        }
+    }
+
+    /// The token balances in `subnet` are held in: the ERC20 token supplying it, or supplying
+    /// the closest of its ancestors that is not natively supplied, and FIL otherwise.
+    ///
+    /// Ancestors whose parent is not configured are taken to be natively supplied.
+    pub async fn supply_source_denomination(
+        &self,
+        subnet: &SubnetID,
+    ) -> anyhow::Result<Denomination> {
+        let mut subnet = subnet.clone();
+        while let Some(parent) = subnet.parent() {
+            let Some(conn) = self.connection(&parent) else {
+                break;
+            };
+            if let Some(token) = conn.manager().supply_source_token(&subnet).await? {
+                return Ok(token);
+            }
+            subnet = parent;
+        }
+        Ok(Denomination::fil())
     }
 
     /// Get genesis info for a child subnet. This can be used to deterministically
@@ -617,6 +2112,7 @@ impl IpcProvider {
         conn.manager().get_genesis_info(subnet).await
     }
 
+    #[tracing::instrument(skip_all, fields(subnet_id = %subnet, height = epoch, method = "get_top_down_msgs"))]
     pub async fn get_top_down_msgs(
         &self,
         subnet: &SubnetID,
@@ -625,7 +2121,38 @@ impl IpcProvider {
         let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
         let conn = self.get_connection(&parent)?;
 
-        conn.manager().get_top_down_msgs(subnet, epoch).await
+        let fetch = self.in_flight.top_down_msgs.run(
+            (subnet.clone(), epoch),
+            conn.manager().get_top_down_msgs(subnet, epoch),
+        );
+
+        match self.response_cache(&parent) {
+            Some(cache) => {
+                cache
+                    .get_or_fetch("get_top_down_msgs", subnet, epoch, fetch, || {
+                        conn.manager().chain_head_height()
+                    })
+                    .await
+            }
+            None => fetch.await,
+        }
+    }
+
+    /// Stream the top down messages of every epoch between `from` and `to`, both included,
+    /// without holding the whole range in memory.
+    pub fn stream_top_down_msgs<'a>(
+        &'a self,
+        subnet: &SubnetID,
+        from: ChainEpoch,
+        to: ChainEpoch,
+        options: RangeQueryOptions,
+    ) -> impl Stream<Item = anyhow::Result<(ChainEpoch, TopDownQueryPayload<Vec<IpcEnvelope>>)>> + 'a
+    {
+        let subnet = subnet.clone();
+        range::stream_epochs(from, to, options, move |epoch| {
+            let subnet = subnet.clone();
+            async move { self.get_top_down_msgs(&subnet, epoch).await }
+        })
     }
 
     pub async fn get_block_hash(
@@ -635,7 +2162,21 @@ impl IpcProvider {
     ) -> anyhow::Result<GetBlockHashResult> {
         let conn = self.get_connection(subnet)?;
 
-        conn.manager().get_block_hash(height).await
+        let fetch = self.in_flight.block_hashes.run(
+            (subnet.clone(), height),
+            conn.manager().get_block_hash(height),
+        );
+
+        match self.response_cache(subnet) {
+            Some(cache) => {
+                cache
+                    .get_or_fetch("get_block_hash", &height, height, fetch, || {
+                        conn.manager().chain_head_height()
+                    })
+                    .await
+            }
+            None => fetch.await,
+        }
     }
 
     pub async fn get_chain_id(&self, subnet: &SubnetID) -> anyhow::Result<String> {
@@ -644,6 +2185,65 @@ impl IpcProvider {
         conn.manager().get_chain_id().await
     }
 
+    /// The chain ID, block time, gas price floor and base fee of a subnet, to time and price
+    /// the transactions sent to it.
+    pub async fn chain_params(&self, subnet: &SubnetID) -> anyhow::Result<ChainParams> {
+        let conn = self.get_connection(subnet)?;
+
+        conn.manager().chain_params().await
+    }
+
+    /// Estimate what sending `msg` from `from_subnet` to `to_subnet` costs over all the hops
+    /// of its route: the `fund` or `release` of the sender, and the `propagate` needed in every
+    /// subnet in between, priced at the current gas price of each.
+    pub async fn estimate_cross_msg_cost(
+        &self,
+        from_subnet: &SubnetID,
+        to_subnet: &SubnetID,
+        msg: &IpcEnvelope,
+    ) -> anyhow::Result<CrossMsgCost> {
+        let route = routing::route(from_subnet, to_subnet)
+            .ok_or_else(|| anyhow!("no route from {from_subnet} to {to_subnet}"))?;
+
+        let mut steps = Vec::new();
+        for (i, hop) in route.hops().into_iter().enumerate() {
+            let step = CrossMsgStep::of_hop(i, &hop);
+            let gas_price = self
+                .chain_params(&hop.from)
+                .await
+                .with_context(|| format!("failed to get the gas price of {}", hop.from))?
+                .gas_price_floor;
+            steps.push(StepCost::new(
+                hop,
+                step,
+                step.gas(msg.message.len()),
+                gas_price,
+            ));
+        }
+
+        Ok(CrossMsgCost::new(steps, msg.value.clone()))
+    }
+
+    /// The hops of a cross-net message from `from` to `to`, with the gateway of the configured
+    /// subnet the message waits in at each, to follow its progress.
+    pub fn route(&self, from: &SubnetID, to: &SubnetID) -> anyhow::Result<Vec<(Hop, Address)>> {
+        let route =
+            routing::route(from, to).ok_or_else(|| anyhow!("no route from {from} to {to}"))?;
+        routing::hop_gateways(&self.config(), &route)
+    }
+
+    /// The result of executing the top-down message with the given nonce in the child subnet
+    /// it was sent to, or `None` if the child hasn't executed it yet.
+    pub async fn top_down_receipt(
+        &self,
+        subnet: &SubnetID,
+        nonce: u64,
+    ) -> anyhow::Result<Option<TopDownReceipt>> {
+        let conn = self.get_connection(subnet)?;
+
+        conn.manager().top_down_receipt(nonce).await
+    }
+
     pub async fn get_commit_sha(&self, subnet: &SubnetID) -> anyhow::Result<[u8; 32]> {
         let conn = self.get_connection(subnet)?;
 
@@ -656,6 +2256,7 @@ impl IpcProvider {
         conn.manager().chain_head_height().await
     }
 
+    #[tracing::instrument(skip_all, fields(subnet_id = %subnet, height = height, method = "get_bottom_up_bundle"))]
     pub async fn get_bottom_up_bundle(
         &self,
         subnet: &SubnetID,
@@ -669,6 +2270,28 @@ impl IpcProvider {
         conn.manager().checkpoint_bundle_at(height).await
     }
 
+    /// Stream the bottom-up checkpoint bundles cut between `from` and `to`, both included,
+    /// without holding the whole range in memory.
+    pub fn stream_bottom_up_bundles<'a>(
+        &'a self,
+        subnet: &SubnetID,
+        from: ChainEpoch,
+        to: ChainEpoch,
+        options: RangeQueryOptions,
+    ) -> impl Stream<Item = anyhow::Result<(ChainEpoch, BottomUpCheckpointBundle)>> + 'a {
+        let subnet = subnet.clone();
+        range::stream_epochs(from, to, options, move |epoch| {
+            let subnet = subnet.clone();
+            async move { self.get_bottom_up_bundle(&subnet, epoch).await }
+        })
+        .filter_map(|res| async move {
+            match res {
+                Ok((epoch, bundle)) => bundle.map(|bundle| Ok((epoch, bundle))),
+                Err(e) => Some(Err(e)),
+            }
+        })
+    }
+
     pub async fn last_bottom_up_checkpoint_height(
         &self,
         subnet: &SubnetID,
@@ -681,6 +2304,75 @@ impl IpcProvider {
             .await
     }
 
+    /// The bottom-up checkpoint period of `subnet`, the last checkpoint committed in its parent,
+    /// its chain head and when its next checkpoint is due, at its recent block time.
+    pub async fn checkpoint_schedule(
+        &self,
+        subnet: &SubnetID,
+    ) -> anyhow::Result<CheckpointSchedule> {
+        let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
+        let parent_conn = self.get_connection(&parent)?;
+        let child_conn = self.get_connection(subnet)?;
+
+        let (period, last_committed, child_head, block_time) = tokio::try_join!(
+            parent_conn.manager().checkpoint_period(subnet),
+            parent_conn
+                .manager()
+                .last_bottom_up_checkpoint_height(subnet),
+            child_conn.manager().current_epoch(),
+            child_conn.manager().block_time(),
+        )?;
+
+        Ok(CheckpointSchedule::new(
+            subnet.clone(),
+            period,
+            last_committed,
+            child_head,
+            block_time,
+        ))
+    }
+
+    /// Verify that `signatures`, by the validators paired with them, of a bottom-up checkpoint
+    /// of `subnet` reach the quorum of the membership of the subnet at the height of the
+    /// checkpoint, so that it can be trusted without trusting the relayer that submitted it.
+    ///
+    /// The membership is read from the subnet at that height, and the majority percentage from
+    /// its parent.
+    pub async fn verify_checkpoint(
+        &self,
+        subnet: &SubnetID,
+        checkpoint: BottomUpCheckpoint,
+        signatures: Vec<(Address, Vec<u8>)>,
+    ) -> anyhow::Result<CheckpointVerification> {
+        if &checkpoint.subnet_id != subnet {
+            return Err(anyhow!(
+                "checkpoint is of {}, not {subnet}",
+                checkpoint.subnet_id
+            ));
+        }
+        let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
+        let parent_conn = self.get_connection(&parent)?;
+        let child_conn = self.get_connection(subnet)?;
+
+        let ((configuration_number, validators), genesis) = tokio::try_join!(
+            child_conn.manager().membership_at(checkpoint.block_height),
+            parent_conn.manager().get_genesis_info(subnet),
+        )?;
+
+        let (signatories, signatures) = signatures.into_iter().unzip();
+        let bundle = BottomUpCheckpointBundle {
+            checkpoint,
+            signatures,
+            signatories,
+        };
+        verify_checkpoint_quorum(
+            &bundle,
+            configuration_number,
+            &validators,
+            genesis.majority_percentage,
+        )
+    }
+
     pub async fn quorum_reached_events(
         &self,
         subnet: &SubnetID,
@@ -704,9 +2396,17 @@ impl IpcProvider {
         let subnet_config = conn.subnet();
         let sender = self.check_sender(subnet_config, from)?;
 
-        conn.manager()
-            .add_bootstrap(subnet, &sender, endpoint)
-            .await
+        let op = Operation::AddBootstrap {
+            subnet: subnet.clone(),
+            endpoint: endpoint.clone(),
+        };
+        self.submit(
+            &parent,
+            sender,
+            op,
+            conn.manager().add_bootstrap(subnet, &sender, endpoint),
+        )
+        .await
     }
 
     /// Lists the bootstrap nodes of a subnet
@@ -731,15 +2431,154 @@ impl IpcProvider {
         validators: &[Address],
         public_keys: &[Vec<u8>],
         federated_power: &[u128],
-    ) -> anyhow::Result<ChainEpoch> {
+    ) -> anyhow::Result<TxReceipt> {
         let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
         let conn = self.get_connection(&parent)?;
-        conn.manager()
-            .set_federated_power(from, subnet, validators, public_keys, federated_power)
-            .await
+        ensure_single_key_admin(conn.subnet())?;
+        let op = Operation::SetFederatedPower {
+            subnet: subnet.clone(),
+            validators: validators.to_vec(),
+        };
+        self.submit(
+            &parent,
+            *from,
+            op,
+            conn.manager().set_federated_power(
+                from,
+                subnet,
+                validators,
+                public_keys,
+                federated_power,
+            ),
+        )
+        .await
+    }
+
+    /// Propose an administrative operation to the multisig administering the network it's
+    /// executed in, approving it on behalf of the sender.
+    #[tracing::instrument(skip_all, fields(method = "propose_admin_operation"))]
+    pub async fn propose_admin_operation(
+        &mut self,
+        from: Option<Address>,
+        op: AdminOperation,
+    ) -> anyhow::Result<Proposal> {
+        let (multisig, conn) = self.admin_multisig(&op.network()?)?;
+        let sender = self.check_sender(conn.subnet(), from)?;
+        let call = conn.manager().admin_call(&op).await?;
+        self.submit(
+            &conn.subnet().id,
+            sender,
+            Operation::ProposeAdminOperation(op),
+            multisig.propose(&sender, call),
+        )
+        .await
+    }
+
+    /// Approve the pending proposal of an administrative operation on behalf of the sender,
+    /// executing it if the approvals reach the threshold of the multisig.
+    #[tracing::instrument(skip_all, fields(method = "approve_admin_operation"))]
+    pub async fn approve_admin_operation(
+        &mut self,
+        from: Option<Address>,
+        op: AdminOperation,
+    ) -> anyhow::Result<Proposal> {
+        let (multisig, conn) = self.admin_multisig(&op.network()?)?;
+        let sender = self.check_sender(conn.subnet(), from)?;
+        let call = conn.manager().admin_call(&op).await?;
+        self.submit(
+            &conn.subnet().id,
+            sender,
+            Operation::ApproveAdminOperation(op),
+            multisig.approve(&sender, call),
+        )
+        .await
+    }
+
+    /// Lists the proposals pending approval in the multisig administering `subnet`.
+    pub async fn pending_admin_proposals(
+        &self,
+        subnet: &SubnetID,
+    ) -> anyhow::Result<Vec<Proposal>> {
+        let (multisig, _) = self.admin_multisig(subnet)?;
+        multisig.pending().await
+    }
+
+    /// A client of the Lotus API of the nodes of `subnet`, signing with the FVM wallet.
+    fn lotus_client(&self, subnet: &SubnetID) -> anyhow::Result<DefaultLotusJsonRPCClient> {
+        let conn = self.get_connection(subnet)?;
+        let client =
+            LotusJsonRPCClient::from_subnet_with_wallet_store(conn.subnet(), self.fvm_wallet()?)
+                .with_gas_overrides(conn.subnet().gas_overrides()?);
+        Ok(match &self.audit_log {
+            Some(log) => client.with_audit_log(log.clone()),
+            None => client,
+        })
+    }
+
+    /// A client of the Lotus API of the nodes of `subnet`, for messages signed elsewhere.
+    fn unsigned_lotus_client(
+        &self,
+        subnet: &SubnetID,
+    ) -> anyhow::Result<DefaultLotusJsonRPCClient> {
+        let conn = self.get_connection(subnet)?;
+        Ok(LotusJsonRPCClient::from_subnet(conn.subnet())
+            .with_gas_overrides(conn.subnet().gas_overrides()?))
+    }
+
+    /// Get the multisig administering `subnet`, along with the connection to it.
+    fn admin_multisig(&self, subnet: &SubnetID) -> anyhow::Result<(Multisig, Connection)> {
+        let conn = self.get_connection(subnet)?;
+        let config = conn
+            .subnet()
+            .admin()
+            .ok_or_else(|| anyhow!("no multisig admin configured for subnet: {subnet}"))?;
+        let multisig = Multisig::new(
+            conn.subnet(),
+            config,
+            self.evm_keystore.clone(),
+            self.fvm_wallet.clone(),
+            self.audit_log.clone(),
+        )?;
+        Ok((multisig, conn))
+    }
+}
+
+/// Administrative operations of subnets administered by a multisig have to be approved by its
+/// signers rather than sent by a single key.
+fn ensure_single_key_admin(subnet: &config::Subnet) -> anyhow::Result<()> {
+    match subnet.admin() {
+        None => Ok(()),
+        Some(admin) => Err(anyhow!(
+            "subnet {} is administered by the {} multisig {}: propose the operation to it instead",
+            subnet.id,
+            admin.kind,
+            admin.address
+        )),
     }
 }
 
+/// The genesis balances of a subnet are pre-funded in the native token, in amounts the subnet
+/// actor accepts.
+fn check_genesis_balances(
+    supply_source: &SupplySource,
+    balances: &BTreeMap<Address, TokenAmount>,
+) -> anyhow::Result<()> {
+    if balances.is_empty() {
+        return Ok(());
+    }
+    if supply_source.kind != SupplyKind::Native {
+        return Err(anyhow!(
+            "genesis balances can only be pre-funded in subnets supplied with the native token"
+        ));
+    }
+    for (account, amount) in balances {
+        if !amount.is_positive() || amount.atto().to_u128().is_none() {
+            return Err(anyhow!("invalid genesis balance of {account}: {amount}"));
+        }
+    }
+    Ok(())
+}
+
 /// Lotus JSON keytype format
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
@@ -836,12 +2675,17 @@ fn new_fvm_wallet_from_config(config: Arc<Config>) -> anyhow::Result<KeyStore> {
     }
 }
 
-pub fn new_evm_keystore_from_config(
-    config: Arc<Config>,
-) -> anyhow::Result<PersistentKeyStore<EthKeyAddress>> {
+/// The EVM keystore of the config: the remote one if it declares one, the file in the
+/// keystore directory otherwise.
+pub fn new_evm_keystore_from_config(config: Arc<Config>) -> anyhow::Result<EvmKeyStoreBackend> {
+    if let Some(remote) = &config.remote_keystore {
+        let keystore = RemoteKeyStore::new(remote.keystore_config()?)
+            .map_err(|e| anyhow!("Failed to connect to remote evm keystore: {}", e))?;
+        return Ok(EvmKeyStoreBackend::Remote(keystore));
+    }
     let repo_str = &config.keystore_path;
     if let Some(repo_str) = repo_str {
-        new_evm_keystore_from_path(repo_str)
+        new_evm_keystore_from_path(repo_str).map(EvmKeyStoreBackend::File)
     } else {
         Err(anyhow!("No keystore repo found in config"))
     }
@@ -855,6 +2699,138 @@ pub fn new_evm_keystore_from_path(
     PersistentKeyStore::new(repo).map_err(|e| anyhow!("Failed to create evm keystore: {}", e))
 }
 
+pub fn new_provider_store_from_config(
+    config: Arc<Config>,
+) -> anyhow::Result<Arc<dyn ProviderStore>> {
+    let repo_str = &config.keystore_path;
+    if let Some(repo_str) = repo_str {
+        new_provider_store_from_path(repo_str)
+    } else {
+        Err(anyhow!("No keystore repo found in config"))
+    }
+}
+
+/// Open the durable store of the provider in the repo, with the default implementation.
+pub fn new_provider_store_from_path(repo_str: &str) -> anyhow::Result<Arc<dyn ProviderStore>> {
+    let path = expand_tilde(Path::new(&repo_str).join(store::DEFAULT_STORE_DIR));
+    #[cfg(feature = "rocksdb")]
+    {
+        Ok(Arc::new(store::RocksStore::open(path)?))
+    }
+    #[cfg(not(feature = "rocksdb"))]
+    {
+        Err(anyhow!(
+            "cannot open the store at {}: the provider was built without the `rocksdb` feature",
+            path.display()
+        ))
+    }
+}
+
+/// Open the response cache of `subnet`, if enabled in its config.
+pub fn new_response_cache_from_config(
+    config: &Config,
+    subnet: &config::Subnet,
+) -> anyhow::Result<Option<ResponseCache>> {
+    let Some(cache) = subnet.response_cache() else {
+        return Ok(None);
+    };
+    let path = match (&cache.path, &config.keystore_path) {
+        (Some(path), _) => path.clone(),
+        (None, Some(repo_str)) => Path::new(repo_str)
+            .join(response_cache::DEFAULT_CACHE_DIR)
+            .join(response_cache::default_file_name(&subnet.id)),
+        (None, None) => {
+            return Err(anyhow!(
+                "No path for the response cache of {} nor keystore repo found in config",
+                subnet.id
+            ))
+        }
+    };
+    ResponseCache::open(expand_tilde(path), cache.finality).map(Some)
+}
+
+pub fn new_address_book_from_config(config: Arc<Config>) -> anyhow::Result<AddressBook> {
+    let repo_str = &config.keystore_path;
+    if let Some(repo_str) = repo_str {
+        new_address_book_from_path(repo_str)
+    } else {
+        Err(anyhow!("No keystore repo found in config"))
+    }
+}
+
+pub fn new_address_book_from_path(repo_str: &str) -> anyhow::Result<AddressBook> {
+    let path = Path::new(&repo_str).join(address_book::DEFAULT_ADDRESS_BOOK_NAME);
+    AddressBook::new(expand_tilde(path))
+}
+
+/// Wait for the message `cid` to `to` to be executed, and return its receipt if it succeeded.
+async fn wait_for_message(
+    client: &DefaultLotusJsonRPCClient,
+    cid: Cid,
+    to: Address,
+) -> anyhow::Result<FvmReceipt> {
+    let r = client.state_wait_msg(cid).await?;
+    if r.receipt.exit_code() != 0 {
+        return Err(anyhow!(
+            "message {cid} to {to} failed with exit code {}",
+            r.receipt.exit_code()
+        ));
+    }
+    let mut receipt = FvmReceipt::from_receipt(&r.receipt)?;
+    receipt.cid = Some(cid);
+    receipt.height = Some(r.height);
+    Ok(receipt)
+}
+
+pub fn new_key_roles_from_config(config: Arc<Config>) -> anyhow::Result<KeyRoles> {
+    let repo_str = &config.keystore_path;
+    if let Some(repo_str) = repo_str {
+        new_key_roles_from_path(repo_str, config.operation_roles.clone())
+    } else {
+        Err(anyhow!("No keystore repo found in config"))
+    }
+}
+
+/// Open the roles of the keys of the keystore in the repo, requiring the roles of `required`
+/// for the operations.
+pub fn new_key_roles_from_path(
+    repo_str: &str,
+    required: BTreeMap<String, KeyRole>,
+) -> anyhow::Result<KeyRoles> {
+    let path = Path::new(&repo_str).join(roles::DEFAULT_KEY_ROLES_NAME);
+    KeyRoles::new(expand_tilde(path), required)
+}
+
+pub fn new_audit_log_from_config(config: Arc<Config>) -> anyhow::Result<AuditLog> {
+    let repo_str = &config.keystore_path;
+    if let Some(repo_str) = repo_str {
+        Ok(new_audit_log_from_path(repo_str))
+    } else {
+        Err(anyhow!("No keystore repo found in config"))
+    }
+}
+
+pub fn new_audit_log_from_path(repo_str: &str) -> AuditLog {
+    let path = Path::new(&repo_str).join(audit::DEFAULT_AUDIT_LOG_NAME);
+    AuditLog::new(expand_tilde(path))
+}
+
+/// The spending policy of the config, if it declares one, checked by the providers created
+/// with [IpcProvider::new_from_config].
+pub fn new_spending_policy_from_config(
+    config: Arc<Config>,
+) -> anyhow::Result<Option<SpendingPolicy>> {
+    let Some(policy) = &config.policy else {
+        return Ok(None);
+    };
+    let repo_str = config
+        .keystore_path
+        .as_ref()
+        .ok_or_else(|| anyhow!("No keystore repo found in config"))?;
+    let path = Path::new(repo_str).join(policy::DEFAULT_SPENDING_NAME);
+    SpendingPolicy::new(policy, expand_tilde(path)).map(Some)
+}
+
 pub fn new_fvm_keystore_from_path(repo_str: &str) -> anyhow::Result<KeyStore> {
     let repo = Path::new(&repo_str);
     let repo = expand_tilde(repo);
@@ -896,3 +2872,39 @@ pub fn expand_tilde<P: AsRef<Path>>(path: P) -> PathBuf {
         })
         .unwrap_or(p)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use fvm_shared::address::Address;
+    use fvm_shared::econ::TokenAmount;
+    use ipc_api::subnet::{SupplyKind, SupplySource};
+
+    use super::check_genesis_balances;
+
+    #[test]
+    fn test_check_genesis_balances() {
+        let native = SupplySource {
+            kind: SupplyKind::Native,
+            token_address: None,
+        };
+        let erc20 = SupplySource {
+            kind: SupplyKind::ERC20,
+            token_address: Some(Address::new_id(200)),
+        };
+        let balances = |amount: TokenAmount| BTreeMap::from([(Address::new_id(100), amount)]);
+
+        assert!(check_genesis_balances(&native, &balances(TokenAmount::from_whole(1))).is_ok());
+        assert!(check_genesis_balances(&erc20, &BTreeMap::new()).is_ok());
+
+        // The subnet actor only takes pre-funding in the native token.
+        assert!(check_genesis_balances(&erc20, &balances(TokenAmount::from_whole(1))).is_err());
+        assert!(check_genesis_balances(&native, &balances(TokenAmount::from_atto(0))).is_err());
+        assert!(check_genesis_balances(&native, &balances(TokenAmount::from_atto(-1))).is_err());
+        assert!(
+            check_genesis_balances(&native, &balances(TokenAmount::from_atto(u128::MAX) * 2))
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,255 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! The lifecycle of the subnets of the provider, across config reloads.
+//!
+//! Every connection to a subnet holds a [SubnetLease] while it's used, so that removing the
+//! subnet from the config can tell what is still in flight against it. Removed subnets stop
+//! being leased at once, the leases held are given a grace period to be dropped, and the
+//! operations still running after it are cancelled. Callers see a [SubnetRemoved] error either
+//! way, which they can tell apart with `error.downcast_ref::<SubnetRemoved>()`.
+
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use ipc_api::subnet_id::SubnetID;
+use tokio::sync::watch;
+use tokio_util::sync::CancellationToken;
+
+/// An operation against a subnet removed from the provider, before it started or while in
+/// flight.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("subnet {0} was removed from the provider")]
+pub struct SubnetRemoved(pub SubnetID);
+
+/// The leases of a subnet.
+struct Slot {
+    in_flight: watch::Sender<usize>,
+    cancel: CancellationToken,
+}
+
+impl Slot {
+    fn new() -> Self {
+        Self {
+            in_flight: watch::channel(0).0,
+            cancel: CancellationToken::new(),
+        }
+    }
+}
+
+/// Counts as an operation in flight against a subnet until dropped.
+pub struct SubnetLease {
+    subnet: SubnetID,
+    slot: Arc<Slot>,
+}
+
+impl SubnetLease {
+    pub fn subnet(&self) -> &SubnetID {
+        &self.subnet
+    }
+
+    /// Whether the subnet was removed, and the operations against it are to be abandoned.
+    pub fn is_cancelled(&self) -> bool {
+        self.slot.cancel.is_cancelled()
+    }
+
+    /// Run `f`, unless the subnet is removed and the grace period elapses first, in which case
+    /// `f` is dropped where it stands. A transaction already sent by `f` may still be included.
+    pub async fn run<T, F>(&self, f: F) -> anyhow::Result<T>
+    where
+        F: Future<Output = anyhow::Result<T>>,
+    {
+        tokio::select! {
+            biased;
+            _ = self.slot.cancel.cancelled() => Err(SubnetRemoved(self.subnet.clone()).into()),
+            res = f => res,
+        }
+    }
+}
+
+impl Drop for SubnetLease {
+    fn drop(&mut self) {
+        self.slot.in_flight.send_modify(|n| *n -= 1);
+    }
+}
+
+#[derive(Default)]
+struct State {
+    slots: HashMap<SubnetID, Arc<Slot>>,
+    removed: HashSet<SubnetID>,
+}
+
+/// The leases of the subnets of the provider, shared by its clones.
+#[derive(Default)]
+pub struct SubnetLifecycle {
+    state: Mutex<State>,
+}
+
+impl SubnetLifecycle {
+    /// Lease `subnet` for an operation, unless it was removed.
+    pub fn lease(&self, subnet: &SubnetID) -> Result<SubnetLease, SubnetRemoved> {
+        let mut state = self.state.lock().unwrap();
+        if state.removed.contains(subnet) {
+            return Err(SubnetRemoved(subnet.clone()));
+        }
+        let slot = state
+            .slots
+            .entry(subnet.clone())
+            .or_insert_with(|| Arc::new(Slot::new()))
+            .clone();
+        slot.in_flight.send_modify(|n| *n += 1);
+        Ok(SubnetLease {
+            subnet: subnet.clone(),
+            slot,
+        })
+    }
+
+    pub fn is_removed(&self, subnet: &SubnetID) -> bool {
+        self.state.lock().unwrap().removed.contains(subnet)
+    }
+
+    /// The number of leases of `subnet` held.
+    pub fn in_flight(&self, subnet: &SubnetID) -> usize {
+        let state = self.state.lock().unwrap();
+        state
+            .slots
+            .get(subnet)
+            .map(|slot| *slot.in_flight.borrow())
+            .unwrap_or_default()
+    }
+
+    /// Lease `subnet` again, after it was added back to the config.
+    pub fn restore(&self, subnet: &SubnetID) {
+        self.state.lock().unwrap().removed.remove(subnet);
+    }
+
+    /// Stop leasing `subnet`; the leases held are drained with [Removal::drain].
+    pub fn remove(&self, subnet: &SubnetID) -> Removal {
+        let mut state = self.state.lock().unwrap();
+        state.removed.insert(subnet.clone());
+        Removal {
+            slot: state.slots.remove(subnet),
+        }
+    }
+}
+
+/// The leases of a removed subnet still held.
+pub struct Removal {
+    slot: Option<Arc<Slot>>,
+}
+
+impl Removal {
+    /// Wait up to `grace` for the leases to be dropped, then cancel the operations of those still
+    /// held. Returns the number of operations cancelled.
+    pub async fn drain(self, grace: Duration) -> usize {
+        let Some(slot) = self.slot else {
+            return 0;
+        };
+        let mut in_flight = slot.in_flight.subscribe();
+        if tokio::time::timeout(grace, in_flight.wait_for(|n| *n == 0))
+            .await
+            .is_ok()
+        {
+            return 0;
+        }
+        let cancelled = *slot.in_flight.borrow();
+        slot.cancel.cancel();
+        cancelled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use std::time::Duration;
+
+    use ipc_api::subnet_id::SubnetID;
+
+    use super::{SubnetLifecycle, SubnetRemoved};
+    use crate::config::Config;
+    use crate::IpcProvider;
+
+    fn subnet() -> SubnetID {
+        SubnetID::from_str("/r123/f01").unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_removal_drains_leases() {
+        let lifecycle = SubnetLifecycle::default();
+        let lease = lifecycle.lease(&subnet()).unwrap();
+        assert_eq!(lifecycle.in_flight(&subnet()), 1);
+
+        let removal = lifecycle.remove(&subnet());
+        assert_eq!(
+            lifecycle.lease(&subnet()).err(),
+            Some(SubnetRemoved(subnet()))
+        );
+
+        // The lease is dropped within the grace period, so nothing is cancelled.
+        let drain = tokio::spawn(removal.drain(Duration::from_secs(10)));
+        drop(lease);
+        assert_eq!(drain.await.unwrap(), 0);
+
+        lifecycle.restore(&subnet());
+        assert!(lifecycle.lease(&subnet()).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_removal_cancels_leases() {
+        let lifecycle = SubnetLifecycle::default();
+        let lease = lifecycle.lease(&subnet()).unwrap();
+
+        let op = lease.run(std::future::pending::<anyhow::Result<()>>());
+        let removal = lifecycle.remove(&subnet());
+        let (res, cancelled) = tokio::join!(op, removal.drain(Duration::from_millis(10)));
+
+        assert_eq!(cancelled, 1);
+        assert!(lease.is_cancelled());
+        assert_eq!(
+            res.unwrap_err().downcast_ref::<SubnetRemoved>(),
+            Some(&SubnetRemoved(subnet()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reload_removes_subnets() {
+        let config = || {
+            Config::from_toml_str(
+                r#"
+                [[subnets]]
+                id = "/r123/f01"
+
+                [subnets.config]
+                network_type = "fevm"
+                provider_http = "http://127.0.0.1:3030/rpc/v1"
+                gateway_addr = "0x6be1ccf648c74800380d0520d797a170c808b624"
+                registry_addr = "0x6be1ccf648c74800380d0520d797a170c808b624"
+                "#,
+            )
+            .unwrap()
+        };
+        let provider =
+            IpcProvider::new_with_subnet(None, config().subnets[&subnet()].clone()).unwrap();
+        let clone = provider.clone();
+
+        let report = provider
+            .reload_config(Config::new(), Duration::from_secs(1))
+            .await;
+        assert_eq!(report.removed, vec![subnet()]);
+        assert_eq!(report.cancelled, 0);
+
+        // Clones see the removal too.
+        let err = clone.chain_head(&subnet()).await.unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<SubnetRemoved>(),
+            Some(&SubnetRemoved(subnet()))
+        );
+
+        let report = provider
+            .reload_config(config(), Duration::from_secs(1))
+            .await;
+        assert_eq!(report.added, vec![subnet()]);
+        assert!(clone.connection(&subnet()).is_some());
+    }
+}
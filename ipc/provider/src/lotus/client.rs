@@ -5,7 +5,7 @@ use std::fmt::Debug;
 use std::str::FromStr;
 use std::sync::{Arc, RwLock};
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
 use base64::Engine;
 use cid::multihash::MultihashDigest;
@@ -16,18 +16,22 @@ use fvm_shared::bigint::BigInt;
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::crypto::signature::Signature;
 use fvm_shared::econ::TokenAmount;
+use fvm_shared::message::Message;
 use ipc_api::subnet_id::SubnetID;
 use ipc_wallet::Wallet;
 use num_traits::cast::ToPrimitive;
 use serde::de::DeserializeOwned;
 use serde_json::json;
 
+use crate::audit::{AuditLog, SigningAudit, SigningOperation};
+use crate::config::subnet::GasOverrides;
 use crate::jsonrpc::{JsonRpcClient, JsonRpcClientImpl, NO_PARAMS};
 use crate::lotus::message::chain::{ChainHeadResponse, GetTipSetByHeightResponse};
 use crate::lotus::message::mpool::{
     EstimateGasResponse, MpoolPushMessage, MpoolPushMessageResponse, MpoolPushMessageResponseInner,
 };
-use crate::lotus::message::state::{ReadStateResponse, StateWaitMsgResponse};
+use crate::lotus::message::msig::MsigPendingTransaction;
+use crate::lotus::message::state::{ReadStateResponse, StateCallResponse, StateWaitMsgResponse};
 use crate::lotus::message::wallet::{WalletKeyType, WalletListResponse};
 use crate::lotus::message::CIDMap;
 use crate::lotus::{LotusClient, NetworkVersion};
@@ -40,6 +44,7 @@ mod methods {
     pub const MPOOL_PUSH: &str = "Filecoin.MpoolPush";
     pub const MPOOL_GET_NONCE: &str = "Filecoin.MpoolGetNonce";
     pub const STATE_WAIT_MSG: &str = "Filecoin.StateWaitMsg";
+    pub const STATE_CALL: &str = "Filecoin.StateCall";
     pub const STATE_NETWORK_NAME: &str = "Filecoin.StateNetworkName";
     pub const STATE_NETWORK_VERSION: &str = "Filecoin.StateNetworkVersion";
     pub const STATE_ACTOR_CODE_CIDS: &str = "Filecoin.StateActorCodeCIDs";
@@ -48,9 +53,18 @@ mod methods {
     pub const WALLET_BALANCE: &str = "Filecoin.WalletBalance";
     pub const WALLET_DEFAULT_ADDRESS: &str = "Filecoin.WalletDefaultAddress";
     pub const STATE_READ_STATE: &str = "Filecoin.StateReadState";
+    pub const MSIG_GET_PENDING: &str = "Filecoin.MsigGetPending";
     pub const CHAIN_HEAD: &str = "Filecoin.ChainHead";
     pub const GET_TIPSET_BY_HEIGHT: &str = "Filecoin.ChainGetTipSetByHeight";
     pub const ESTIMATE_MESSAGE_GAS: &str = "Filecoin.GasEstimateMessageGas";
+
+    /// Methods returning data which doesn't change, whose identical concurrent requests can
+    /// share a single call.
+    pub const DEDUPLICATED: &[&str] = &[
+        GET_TIPSET_BY_HEIGHT,
+        STATE_NETWORK_NAME,
+        STATE_ACTOR_CODE_CIDS,
+    ];
 }
 
 /// The default state wait confidence value
@@ -87,6 +101,10 @@ pub struct LotusJsonRPCClient<T: JsonRpcClient> {
     client: T,
     subnet: SubnetID,
     wallet_store: Option<Arc<RwLock<Wallet>>>,
+    /// Records the signatures of the wallet, if set.
+    audit: Option<SigningAudit>,
+    /// The gas fees of the messages set in the config rather than estimated.
+    gas_overrides: GasOverrides,
 }
 
 impl<T: JsonRpcClient> LotusJsonRPCClient<T> {
@@ -95,6 +113,8 @@ impl<T: JsonRpcClient> LotusJsonRPCClient<T> {
             client,
             subnet,
             wallet_store: None,
+            audit: None,
+            gas_overrides: GasOverrides::default(),
         }
     }
 
@@ -107,6 +127,30 @@ impl<T: JsonRpcClient> LotusJsonRPCClient<T> {
             client,
             subnet,
             wallet_store: Some(wallet_store),
+            audit: None,
+            gas_overrides: GasOverrides::default(),
+        }
+    }
+
+    /// Record every signature of the wallet in the audit log.
+    pub fn with_audit_log(mut self, log: Arc<AuditLog>) -> Self {
+        self.audit = Some(SigningAudit::new(log, self.subnet.clone()));
+        self
+    }
+
+    /// Use the fees of `overrides` instead of estimating them.
+    pub fn with_gas_overrides(mut self, overrides: GasOverrides) -> Self {
+        self.gas_overrides = overrides;
+        self
+    }
+
+    /// Replace the gas fees of `msg` with the ones overridden.
+    fn apply_gas_overrides(&self, msg: &mut MpoolPushMessage) {
+        if let Some(fee_cap) = &self.gas_overrides.fee_cap {
+            msg.gas_fee_cap = Some(fee_cap.clone());
+        }
+        if let Some(premium) = &self.gas_overrides.premium {
+            msg.gas_premium = Some(premium.clone());
         }
     }
 }
@@ -115,8 +159,10 @@ impl<T: JsonRpcClient> LotusJsonRPCClient<T> {
 impl<T: JsonRpcClient + Send + Sync> LotusClient for LotusJsonRPCClient<T> {
     async fn mpool_push_message(
         &self,
-        msg: MpoolPushMessage,
+        mut msg: MpoolPushMessage,
     ) -> Result<MpoolPushMessageResponseInner> {
+        self.apply_gas_overrides(&mut msg);
+
         let nonce = msg
             .nonce
             .map(|n| serde_json::Value::Number(n.into()))
@@ -162,7 +208,7 @@ impl<T: JsonRpcClient + Send + Sync> LotusClient for LotusJsonRPCClient<T> {
         Ok(r.message)
     }
 
-    async fn mpool_push(&self, mut msg: MpoolPushMessage) -> Result<Cid> {
+    async fn mpool_prepare(&self, mut msg: MpoolPushMessage) -> Result<Message> {
         if msg.nonce.is_none() {
             let nonce = self.mpool_nonce(&msg.from).await?;
             tracing::info!(
@@ -178,10 +224,19 @@ impl<T: JsonRpcClient + Send + Sync> LotusClient for LotusJsonRPCClient<T> {
         }
 
         self.estimate_message_gas(&mut msg).await?;
+        self.apply_gas_overrides(&mut msg);
         tracing::debug!("estimated gas for message: {msg:?}");
 
-        let signature = self.sign_mpool_message(&msg)?;
+        to_message(&msg)
+    }
 
+    async fn mpool_push(&self, msg: MpoolPushMessage) -> Result<Cid> {
+        let message = self.mpool_prepare(msg).await?;
+        let signature = self.sign_mpool_message(&message)?;
+        self.mpool_push_signed(&message, signature).await
+    }
+
+    async fn mpool_push_signed(&self, msg: &Message, signature: Signature) -> Result<Cid> {
         let params = create_signed_message_params(msg, signature);
         tracing::debug!(
             "message to push to mpool: {params:?} in subnet: {:?}",
@@ -258,6 +313,38 @@ impl<T: JsonRpcClient + Send + Sync> LotusClient for LotusJsonRPCClient<T> {
         Ok(cids)
     }
 
+    async fn state_call(
+        &self,
+        msg: MpoolPushMessage,
+        tipset: Vec<Cid>,
+    ) -> Result<StateCallResponse> {
+        // refer to: https://lotus.filecoin.io/reference/lotus/state/#statecall
+        let params = json!([
+            {
+                "Version": msg.version.unwrap_or(0),
+                "To": msg.to.to_string(),
+                "From": msg.from.to_string(),
+                "Value": msg.value.atto().to_string(),
+                "Method": msg.method,
+                "Params": base64::engine::general_purpose::STANDARD.encode(&msg.params),
+                "Nonce": msg.nonce.unwrap_or(0),
+
+                // Lotus uses the block gas limit when it's zero.
+                "GasLimit": 0,
+                "GasFeeCap": "0",
+                "GasPremium": "0",
+            },
+            tipset.into_iter().map(CIDMap::from).collect::<Vec<_>>()
+        ]);
+
+        let r = self
+            .client
+            .request::<StateCallResponse>(methods::STATE_CALL, params)
+            .await?;
+        tracing::debug!("received state_call response: {r:?}");
+        Ok(r)
+    }
+
     async fn wallet_default(&self) -> Result<Address> {
         // refer to: https://lotus.filecoin.io/reference/lotus/wallet/#walletdefaultaddress
         let r = self
@@ -303,6 +390,19 @@ impl<T: JsonRpcClient + Send + Sync> LotusClient for LotusJsonRPCClient<T> {
         Ok(TokenAmount::from_atto(v))
     }
 
+    async fn msig_get_pending(&self, msig: &Address) -> Result<Vec<MsigPendingTransaction>> {
+        // refer to: https://lotus.filecoin.io/reference/lotus/msig/#msiggetpending
+        let r = self
+            .client
+            .request::<Option<Vec<MsigPendingTransaction>>>(
+                methods::MSIG_GET_PENDING,
+                json!([msig.to_string(), null]),
+            )
+            .await?;
+        tracing::debug!("received msig_get_pending response: {r:?}");
+        Ok(r.unwrap_or_default())
+    }
+
     async fn read_state<State: DeserializeOwned + Debug>(
         &self,
         address: Address,
@@ -351,47 +451,12 @@ impl<T: JsonRpcClient + Send + Sync> LotusClient for LotusJsonRPCClient<T> {
 }
 
 impl<T: JsonRpcClient + Send + Sync> LotusJsonRPCClient<T> {
-    fn sign_mpool_message(&self, msg: &MpoolPushMessage) -> anyhow::Result<Signature> {
-        if self.wallet_store.is_none() {
-            return Err(anyhow!("key store not set, function not supported"));
-        }
-
-        let message = fvm_shared::message::Message {
-            version: msg
-                .version
-                .ok_or_else(|| anyhow!("version should not be empty"))? as u64,
-            from: msg.from,
-            to: msg.to,
-            sequence: msg
-                .nonce
-                .ok_or_else(|| anyhow!("nonce should not be empty"))?,
-            value: msg.value.clone(),
-            method_num: msg.method,
-            params: RawBytes::from(msg.params.clone()),
-            gas_limit: msg
-                .gas_limit
-                .as_ref()
-                .ok_or_else(|| anyhow!("gas_limit should not be empty"))?
-                .atto()
-                .to_u64()
-                .unwrap(),
-            gas_fee_cap: msg
-                .gas_fee_cap
-                .as_ref()
-                .ok_or_else(|| anyhow!("gas_fee_cap should not be empty"))?
-                .clone(),
-            gas_premium: msg
-                .gas_premium
-                .as_ref()
-                .ok_or_else(|| anyhow!("gas_premium should not be empty"))?
-                .clone(),
-        };
-
-        let hash = cid::multihash::Code::Blake2b256.digest(&to_vec(&message)?);
-        let msg_cid = Cid::new_v1(fvm_ipld_encoding::DAG_CBOR, hash).to_bytes();
-
-        let mut wallet_store = self.wallet_store.as_ref().unwrap().write().unwrap();
-        Ok(wallet_store.sign(&msg.from, &msg_cid)?)
+    fn sign_mpool_message(&self, msg: &Message) -> anyhow::Result<Signature> {
+        let wallet_store = self
+            .wallet_store
+            .as_ref()
+            .ok_or_else(|| anyhow!("key store not set, function not supported"))?;
+        sign_message(wallet_store, self.audit.as_ref(), msg)
     }
 
     async fn estimate_message_gas(&self, msg: &mut MpoolPushMessage) -> anyhow::Result<()> {
@@ -449,7 +514,8 @@ impl LotusJsonRPCClient<JsonRpcClientImpl> {
     pub fn from_subnet(subnet: &crate::config::Subnet) -> Self {
         let url = subnet.rpc_http().clone();
         let auth_token = subnet.auth_token();
-        let jsonrpc_client = JsonRpcClientImpl::new(url, auth_token.as_deref());
+        let jsonrpc_client = JsonRpcClientImpl::new(url, auth_token.as_deref())
+            .with_deduplicated_methods(methods::DEDUPLICATED.iter().copied());
         LotusJsonRPCClient::new(jsonrpc_client, subnet.id.clone())
     }
 
@@ -459,51 +525,97 @@ impl LotusJsonRPCClient<JsonRpcClientImpl> {
     ) -> Self {
         let url = subnet.rpc_http().clone();
         let auth_token = subnet.auth_token();
-        let jsonrpc_client = JsonRpcClientImpl::new(url, auth_token.as_deref());
+        let jsonrpc_client = JsonRpcClientImpl::new(url, auth_token.as_deref())
+            .with_deduplicated_methods(methods::DEDUPLICATED.iter().copied());
         LotusJsonRPCClient::new_with_wallet_store(jsonrpc_client, subnet.id.clone(), wallet_store)
     }
 }
 
-fn create_signed_message_params(msg: MpoolPushMessage, signature: Signature) -> serde_json::Value {
-    let nonce = msg
-        .nonce
-        .map(|n| serde_json::Value::Number(n.into()))
-        .unwrap_or(serde_json::Value::Null);
-
-    let to_value_str = |t: Option<TokenAmount>| {
-        t.map(|n| serde_json::Value::String(n.atto().to_u64().unwrap().to_string()))
-            .unwrap_or(serde_json::Value::Null)
-    };
-    let to_value = |t: Option<TokenAmount>| {
-        t.map(|n| serde_json::Value::Number(n.atto().to_u64().unwrap().into()))
-            .unwrap_or(serde_json::Value::Null)
-    };
-
-    let gas_limit = to_value(msg.gas_limit);
-    let gas_premium = to_value_str(msg.gas_premium);
-    let gas_fee_cap = to_value_str(msg.gas_fee_cap);
+/// The CID of a message, which is what its sender signs.
+pub fn message_cid(msg: &Message) -> Result<Cid> {
+    let hash = cid::multihash::Code::Blake2b256.digest(&to_vec(msg)?);
+    Ok(Cid::new_v1(fvm_ipld_encoding::DAG_CBOR, hash))
+}
+
+/// Sign `msg` with the key of its sender in `wallet_store`, recording the signature in `audit`
+/// if set.
+pub(crate) fn sign_message(
+    wallet_store: &RwLock<Wallet>,
+    audit: Option<&SigningAudit>,
+    msg: &Message,
+) -> Result<Signature> {
+    let msg_cid = message_cid(msg)?;
+
+    let mut wallet_store = wallet_store.write().unwrap();
+    let signature = wallet_store.sign(&msg.from, &msg_cid.to_bytes())?;
+    if let Some(audit) = audit {
+        audit
+            .record(
+                &msg.from,
+                SigningOperation::FilecoinMessage,
+                msg_cid.to_string(),
+                Some(msg.to.to_string()),
+            )
+            .context("refusing to sign without recording it in the audit log")?;
+    }
+    Ok(signature)
+}
+
+/// The message to sign, once its nonce and gas are filled in.
+fn to_message(msg: &MpoolPushMessage) -> Result<Message> {
+    Ok(Message {
+        version: msg
+            .version
+            .ok_or_else(|| anyhow!("version should not be empty"))? as u64,
+        from: msg.from,
+        to: msg.to,
+        sequence: msg
+            .nonce
+            .ok_or_else(|| anyhow!("nonce should not be empty"))?,
+        value: msg.value.clone(),
+        method_num: msg.method,
+        params: RawBytes::from(msg.params.clone()),
+        gas_limit: msg
+            .gas_limit
+            .as_ref()
+            .ok_or_else(|| anyhow!("gas_limit should not be empty"))?
+            .atto()
+            .to_u64()
+            .unwrap(),
+        gas_fee_cap: msg
+            .gas_fee_cap
+            .as_ref()
+            .ok_or_else(|| anyhow!("gas_fee_cap should not be empty"))?
+            .clone(),
+        gas_premium: msg
+            .gas_premium
+            .as_ref()
+            .ok_or_else(|| anyhow!("gas_premium should not be empty"))?
+            .clone(),
+    })
+}
 
+fn create_signed_message_params(msg: &Message, signature: Signature) -> serde_json::Value {
     let Signature { sig_type, bytes } = signature;
     let sig_encoded = base64::engine::general_purpose::STANDARD.encode(bytes);
 
-    let params_encoded = base64::engine::general_purpose::STANDARD.encode(msg.params);
+    let params_encoded = base64::engine::general_purpose::STANDARD.encode(msg.params.bytes());
     // refer to: https://lotus.filecoin.io/reference/lotus/mpool/#mpoolpush
     json!([
         {
             "Message": {
-                "Version": msg.version.unwrap_or(0),
+                "Version": msg.version,
                 "To": msg.to.to_string(),
                 "From": msg.from.to_string(),
                 "Value": msg.value.atto().to_string(),
-                "Method": msg.method,
+                "Method": msg.method_num,
                 "Params": params_encoded,
 
-                // THESE ALL WILL AUTO POPULATE if null
-                "Nonce": nonce,
-                "GasLimit": gas_limit,
-                "GasFeeCap": gas_fee_cap,
-                "GasPremium": gas_premium,
-                "CID": CIDMap::from(msg.cid),
+                "Nonce": msg.sequence,
+                "GasLimit": msg.gas_limit,
+                "GasFeeCap": msg.gas_fee_cap.atto().to_string(),
+                "GasPremium": msg.gas_premium.atto().to_string(),
+                "CID": CIDMap::from(None::<Cid>),
             },
             "Signature": {
                 "Type": sig_type as u8,
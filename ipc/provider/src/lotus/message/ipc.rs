@@ -7,15 +7,11 @@ use fvm_shared::econ::TokenAmount;
 use fvm_shared::MethodNum;
 use ipc_api::address::IPCAddress;
 use ipc_api::subnet_id::SubnetID;
+use ipc_api::HumanReadable;
 use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
 
-use crate::lotus::message::deserialize::{
-    deserialize_ipc_address_from_map, deserialize_subnet_id_from_map,
-    deserialize_token_amount_from_str,
-};
-use crate::lotus::message::serialize::{
-    serialize_subnet_id_to_str, serialize_token_amount_to_atto,
-};
+use crate::lotus::message::deserialize::deserialize_ipc_address_from_map;
 use crate::lotus::message::CIDMap;
 
 #[derive(Deserialize, Debug)]
@@ -58,25 +54,17 @@ pub struct Voting {
 }
 
 /// SubnetInfo is an auxiliary struct that collects relevant information about the state of a subnet
-///
-/// Note that the serialization and deserialization casing are different. Reason because for deserialization,
-/// it is from the fvm actor, which is `PascalCase`. When serialize, we are using rust's default casing
-#[derive(Debug, Serialize, Deserialize)]
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SubnetInfo {
     /// Id of the subnet.
-    #[serde(rename(deserialize = "ID"))]
-    #[serde(deserialize_with = "deserialize_subnet_id_from_map")]
-    #[serde(serialize_with = "serialize_subnet_id_to_str")]
+    #[serde_as(as = "HumanReadable")]
     pub id: SubnetID,
     /// Collateral staked in the subnet.
-    #[serde(rename(deserialize = "Stake"))]
-    #[serde(deserialize_with = "deserialize_token_amount_from_str")]
-    #[serde(serialize_with = "serialize_token_amount_to_atto")]
+    #[serde_as(as = "HumanReadable")]
     pub stake: TokenAmount,
     /// Circulating supply available in the subnet.
-    #[serde(rename(deserialize = "CircSupply"))]
-    #[serde(deserialize_with = "deserialize_token_amount_from_str")]
-    #[serde(serialize_with = "serialize_token_amount_to_atto")]
+    #[serde_as(as = "HumanReadable")]
     pub circ_supply: TokenAmount,
     pub genesis_epoch: ChainEpoch,
 }
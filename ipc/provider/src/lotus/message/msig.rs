@@ -0,0 +1,78 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+use fvm_ipld_encoding::{strict_bytes, RawBytes};
+use fvm_shared::address::Address;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::MethodNum;
+use serde::Deserialize;
+use serde_tuple::{Deserialize_tuple, Serialize_tuple};
+
+use crate::lotus::message::deserialize::{
+    deserialize_address_from_str, deserialize_token_amount_from_str,
+};
+
+/// A transaction proposed to a multisig actor that hasn't been approved by enough signers yet.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct MsigPendingTransaction {
+    #[serde(rename = "ID")]
+    pub id: i64,
+    #[serde(deserialize_with = "deserialize_address_from_str")]
+    pub to: Address,
+    #[serde(deserialize_with = "deserialize_token_amount_from_str")]
+    pub value: TokenAmount,
+    pub method: MethodNum,
+    /// Base64 encoded params of the proposed message.
+    pub params: Option<String>,
+    /// The addresses of the signers that approved the transaction.
+    pub approved: Vec<String>,
+}
+
+/// The part of the state of a multisig actor needed to follow its proposals.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct MsigState {
+    pub signers: Vec<String>,
+    pub num_approvals_threshold: u64,
+}
+
+/// The method of a multisig actor proposing a transaction.
+pub const MSIG_PROPOSE_METHOD: MethodNum = 2;
+/// The method of a multisig actor approving a pending transaction.
+pub const MSIG_APPROVE_METHOD: MethodNum = 3;
+
+/// The params of [MSIG_PROPOSE_METHOD].
+#[derive(Serialize_tuple, Debug, Clone)]
+pub struct MsigProposeParams {
+    pub to: Address,
+    pub value: TokenAmount,
+    pub method: MethodNum,
+    pub params: RawBytes,
+}
+
+/// The params of [MSIG_APPROVE_METHOD]. The proposal hash is only checked by the actor if not empty.
+#[derive(Serialize_tuple, Debug, Clone)]
+pub struct MsigTxnIdParams {
+    pub id: i64,
+    #[serde(with = "strict_bytes")]
+    pub proposal_hash: Vec<u8>,
+}
+
+/// The return of [MSIG_PROPOSE_METHOD].
+#[derive(Deserialize_tuple, Debug, Clone, Default)]
+pub struct MsigProposeReturn {
+    pub txn_id: i64,
+    /// Whether the transaction was executed right away, because the threshold is one.
+    pub applied: bool,
+    pub code: u32,
+    pub ret: RawBytes,
+}
+
+/// The return of [MSIG_APPROVE_METHOD].
+#[derive(Deserialize_tuple, Debug, Clone, Default)]
+pub struct MsigApproveReturn {
+    /// Whether the approval reached the threshold and the transaction was executed.
+    pub applied: bool,
+    pub code: u32,
+    pub ret: RawBytes,
+}
@@ -17,3 +17,16 @@ where
 {
     s.serialize_str(&amount.atto().to_string())
 }
+
+pub fn serialize_some_token_amount_to_atto<S>(
+    amount: &Option<TokenAmount>,
+    s: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match amount {
+        Some(amount) => serialize_token_amount_to_atto(amount, s),
+        None => s.serialize_none(),
+    }
+}
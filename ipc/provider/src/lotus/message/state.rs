@@ -32,18 +32,46 @@ pub struct ReadStateResponse<State> {
     pub state: State,
 }
 
+/// The result of applying a message on top of a tipset without including it in the chain,
+/// see https://lotus.filecoin.io/reference/lotus/state/#statecall
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct StateCallResponse {
+    #[serde(rename = "MsgRct")]
+    pub receipt: Option<Receipt>,
+    /// Why the message could not be applied, empty if it was.
+    #[serde(default)]
+    pub error: String,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct Receipt {
-    #[allow(dead_code)]
     exit_code: u32,
     #[serde(rename = "Return")]
     pub result: Option<String>,
-    #[allow(dead_code)]
     gas_used: u64,
 }
 
 impl Receipt {
+    pub fn exit_code(&self) -> u32 {
+        self.exit_code
+    }
+
+    pub fn gas_used(&self) -> u64 {
+        self.gas_used
+    }
+
+    /// The raw bytes returned by the actor, empty if it returned nothing.
+    pub fn return_data(&self) -> anyhow::Result<Vec<u8>> {
+        match &self.result {
+            None => Ok(vec![]),
+            Some(r) => base64::engine::general_purpose::STANDARD
+                .decode(r)
+                .map_err(|e| anyhow!("cannot decode return string: {e}")),
+        }
+    }
+
     pub fn parse_result_into<T: Default + DeserializeOwned>(self) -> anyhow::Result<T> {
         if self.result.is_none() {
             return Ok(Default::default());
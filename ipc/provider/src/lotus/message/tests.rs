@@ -134,3 +134,87 @@ fn test_subnet_info_to_str() {
     let w = serde_json::to_string(&s);
     assert!(w.is_ok());
 }
+
+#[test]
+fn test_subnet_info_roundtrip() {
+    let s = SubnetInfo {
+        id: SubnetID::from_str("/r123/f0100").unwrap(),
+        stake: TokenAmount::from_whole(10),
+        circ_supply: TokenAmount::from_atto(5),
+        genesis_epoch: 100,
+    };
+
+    let json = serde_json::to_string(&s).unwrap();
+    assert_eq!(
+        json,
+        r#"{"id":"/r123/f0100","stake":"10000000000000000000","circ_supply":"5","genesis_epoch":100}"#
+    );
+    assert_eq!(serde_json::from_str::<SubnetInfo>(&json).unwrap(), s);
+
+    let bz = fvm_ipld_encoding::to_vec(&s).unwrap();
+    assert_eq!(fvm_ipld_encoding::from_slice::<SubnetInfo>(&bz).unwrap(), s);
+}
+
+#[test]
+fn test_subnet_genesis_info_roundtrip() {
+    use crate::manager::SubnetGenesisInfo;
+    use ipc_api::subnet::{PermissionMode, SupplySource};
+    use ipc_api::validator::Validator;
+
+    let info = SubnetGenesisInfo {
+        bottom_up_checkpoint_period: 10,
+        majority_percentage: 66,
+        active_validators_limit: 100,
+        min_collateral: TokenAmount::from_whole(1),
+        genesis_epoch: 20,
+        validators: vec![Validator {
+            addr: Address::new_id(101),
+            metadata: vec![4; 65],
+            weight: TokenAmount::from_whole(2),
+        }],
+        genesis_balances: [(Address::new_id(102), TokenAmount::from_whole(3))].into(),
+        permission_mode: PermissionMode::Collateral,
+        supply_source: SupplySource {
+            kind: ipc_api::subnet::SupplyKind::Native,
+            token_address: None,
+        },
+    };
+
+    let json = serde_json::to_string(&info).unwrap();
+    assert_eq!(
+        serde_json::from_str::<SubnetGenesisInfo>(&json).unwrap(),
+        info
+    );
+
+    let bz = fvm_ipld_encoding::to_vec(&info).unwrap();
+    assert_eq!(
+        fvm_ipld_encoding::from_slice::<SubnetGenesisInfo>(&bz).unwrap(),
+        info
+    );
+}
+
+#[test]
+fn test_state_call_response() {
+    use crate::lotus::message::state::StateCallResponse;
+
+    let raw_str = r#"
+    {
+        "MsgCid": {"/": "bafy2bzacea3wsdh6y3a36tb3skempjoxqpuyompjbmfeyf34fi3uy6uue42v4"},
+        "MsgRct": {"ExitCode": 0, "Return": "QgAB", "GasUsed": 1000, "EventsRoot": null},
+        "GasCost": null,
+        "ExecutionTrace": null,
+        "Error": "",
+        "Duration": 1000
+    }"#;
+
+    let r: StateCallResponse = serde_json::from_str(raw_str).unwrap();
+    let receipt = r.receipt.unwrap();
+    assert_eq!(receipt.exit_code(), 0);
+    assert_eq!(receipt.gas_used(), 1000);
+    assert_eq!(receipt.return_data().unwrap(), vec![0x42, 0x00, 0x01]);
+
+    let r: StateCallResponse =
+        serde_json::from_str(r#"{"MsgRct": null, "Error": "actor not found"}"#).unwrap();
+    assert!(r.receipt.is_none());
+    assert_eq!(r.error, "actor not found");
+}
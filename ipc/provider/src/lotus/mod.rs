@@ -9,13 +9,16 @@ use async_trait::async_trait;
 use cid::Cid;
 use fvm_shared::address::Address;
 use fvm_shared::clock::ChainEpoch;
+use fvm_shared::crypto::signature::Signature;
 use fvm_shared::econ::TokenAmount;
+use fvm_shared::message::Message;
 use serde::de::DeserializeOwned;
 
 use crate::lotus::message::chain::GetTipSetByHeightResponse;
 use message::chain::ChainHeadResponse;
 use message::mpool::{MpoolPushMessage, MpoolPushMessageResponseInner};
-use message::state::{ReadStateResponse, StateWaitMsgResponse};
+use message::msig::MsigPendingTransaction;
+use message::state::{ReadStateResponse, StateCallResponse, StateWaitMsgResponse};
 use message::wallet::{WalletKeyType, WalletListResponse};
 
 pub mod client;
@@ -39,6 +42,13 @@ pub trait LotusClient {
     /// See: https://lotus.filecoin.io/reference/lotus/mpool/#mpoolpush
     async fn mpool_push(&self, mut msg: MpoolPushMessage) -> Result<Cid>;
 
+    /// Fill in the nonce and the gas of the message without signing it, so that it can be
+    /// signed elsewhere and pushed with [LotusClient::mpool_push_signed].
+    async fn mpool_prepare(&self, mut msg: MpoolPushMessage) -> Result<Message>;
+
+    /// Push a message signed elsewhere to memory pool, see: https://lotus.filecoin.io/reference/lotus/mpool/#mpoolpush
+    async fn mpool_push_signed(&self, msg: &Message, signature: Signature) -> Result<Cid>;
+
     /// Wait for the message cid of a particular nonce, see: https://lotus.filecoin.io/reference/lotus/state/#statewaitmsg
     async fn state_wait_msg(&self, cid: Cid) -> Result<StateWaitMsgResponse>;
 
@@ -54,6 +64,14 @@ pub trait LotusClient {
         network_version: NetworkVersion,
     ) -> Result<HashMap<String, Cid>>;
 
+    /// Apply the message on top of the tipset, the chain head if empty, without including it in
+    /// the chain, see https://lotus.filecoin.io/reference/lotus/state/#statecall
+    async fn state_call(
+        &self,
+        msg: MpoolPushMessage,
+        tipset: Vec<Cid>,
+    ) -> Result<StateCallResponse>;
+
     /// Get the default wallet of the node, see: https://lotus.filecoin.io/reference/lotus/wallet/#walletdefaultaddress
     async fn wallet_default(&self) -> Result<Address>;
 
@@ -73,6 +91,9 @@ pub trait LotusClient {
         tipset: Cid,
     ) -> Result<ReadStateResponse<State>>;
 
+    /// Get the transactions pending approval in a multisig actor, see: https://lotus.filecoin.io/reference/lotus/msig/#msiggetpending
+    async fn msig_get_pending(&self, msig: &Address) -> Result<Vec<MsigPendingTransaction>>;
+
     /// Returns the current head of the chain.
     /// See: https://lotus.filecoin.io/reference/lotus/chain/#chainhead
     async fn chain_head(&self) -> Result<ChainHeadResponse>;
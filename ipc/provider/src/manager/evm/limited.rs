@@ -0,0 +1,44 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! HTTP transport with a limit on the requests in flight.
+
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use ethers::providers::{Http, HttpClientError, JsonRpcClient};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tokio::sync::Semaphore;
+
+/// Sends the JSON-RPC requests over HTTP, holding a permit of the semaphore, if any, while each
+/// request is in flight. Clones share the permits.
+#[derive(Debug, Clone)]
+pub struct LimitedHttp {
+    inner: Http,
+    permits: Option<Arc<Semaphore>>,
+}
+
+impl LimitedHttp {
+    pub fn new(inner: Http, permits: Option<Arc<Semaphore>>) -> Self {
+        Self { inner, permits }
+    }
+}
+
+#[async_trait]
+impl JsonRpcClient for LimitedHttp {
+    type Error = HttpClientError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let _permit = match &self.permits {
+            // The semaphore is never closed.
+            Some(permits) => Some(permits.acquire().await.expect("semaphore closed")),
+            None => None,
+        };
+        self.inner.request(method, params).await
+    }
+}
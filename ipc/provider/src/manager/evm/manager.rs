@@ -14,46 +14,62 @@ use ipc_actors_abis::{
     subnet_actor_reward_facet,
 };
 use ipc_api::evm::{fil_to_eth_amount, payload_to_evm_address, subnet_id_to_evm_addresses};
-use ipc_api::validator::from_contract_validators;
+use ipc_api::validator::{from_contract_validators, Validator};
 use reqwest::header::HeaderValue;
 use reqwest::Client;
 use std::net::{IpAddr, SocketAddr};
+use url::Url;
 
-use ipc_api::subnet::{PermissionMode, SupplyKind, SupplySource};
+use ipc_api::subnet::{PermissionMode, SubnetParam, SupplyKind, SupplySource};
 use ipc_api::{eth_to_fil_amount, ethers_address_to_fil_address};
 
-use crate::config::subnet::SubnetConfig;
+use crate::audit::{AuditLog, AuditedWallet, SigningAudit};
+use crate::config::subnet::{GasOverrides, SubnetConfig};
 use crate::config::Subnet;
+use crate::deploy::{self, DeployedContract};
+use crate::evm_keystore::EvmKeyStoreBackend;
 use crate::lotus::message::ipc::SubnetInfo;
+use crate::manager::evm::LimitedHttp;
 use crate::manager::subnet::{
-    BottomUpCheckpointRelayer, GetBlockHashResult, SubnetGenesisInfo, TopDownFinalityQuery,
-    TopDownQueryPayload,
+    BottomUpCheckpointRelayer, ChainParams, GetBlockHashResult, ParamChangeProposal, PostboxMsg,
+    SubnetGenesisInfo, SubnetLifecycleInfo, TopDownFinalityQuery, TopDownQueryPayload,
+    TransactionQuery, TxStatus,
 };
 use crate::manager::{EthManager, SubnetManager};
+use crate::multisig::{AdminCall, AdminOperation};
+use crate::state_diff::{ActorState, StateActor};
 use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
 use ethers::abi::Tokenizable;
 use ethers::contract::abigen;
-use ethers::prelude::k256::ecdsa::SigningKey;
 use ethers::prelude::{Signer, SignerMiddleware};
 use ethers::providers::{Authorization, Http, Middleware, Provider};
-use ethers::signers::{LocalWallet, Wallet};
-use ethers::types::{BlockId, Eip1559TransactionRequest, ValueOrArray, I256, U256};
+use ethers::signers::LocalWallet;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{BlockId, Eip1559TransactionRequest, ValueOrArray, H256, I256, U256};
 
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::{address::Address, econ::TokenAmount};
+use ipc_api::address::IPCAddress;
 use ipc_api::checkpoint::{
     BottomUpCheckpoint, BottomUpCheckpointBundle, QuorumReachedEvent, Signature,
 };
-use ipc_api::cross::IpcEnvelope;
-use ipc_api::staking::{StakingChangeRequest, ValidatorInfo, ValidatorStakingInfo};
+use ipc_api::cross::{IpcEnvelope, TopDownReceipt};
+use ipc_api::evidence::DoubleSignEvidence;
+use ipc_api::model::{model_hash, MlAlgorithm, ModelHash, MACHINELEARNING_ACTOR_ID};
+use ipc_api::receipt::TxReceipt;
+use ipc_api::staking::{
+    ConfigurationNumber, StakingChangeRequest, ValidatorInfo, ValidatorStakingInfo,
+};
 use ipc_api::subnet::ConstructParams;
 use ipc_api::subnet_id::SubnetID;
-use ipc_wallet::{EthKeyAddress, EvmKeyStore, PersistentKeyStore};
+use ipc_api::token::Denomination;
+use ipc_wallet::EvmKeyStore;
 use num_traits::ToPrimitive;
 use std::result;
+use tokio::sync::Semaphore;
 
-pub type DefaultSignerMiddleware = SignerMiddleware<Provider<Http>, Wallet<SigningKey>>;
+pub type DefaultSignerMiddleware = SignerMiddleware<Provider<LimitedHttp>, AuditedWallet>;
 
 /// Default polling time used by the Ethers provider to check for pending
 /// transactions and events. Default is 7, and for our child subnets we
@@ -71,9 +87,42 @@ const TRANSACTION_RECEIPT_RETRIES: usize = 200;
 /// The majority vote percentage for checkpoint submission when creating a subnet.
 const SUBNET_MAJORITY_PERCENTAGE: u8 = 67;
 
+/// Number of recent blocks the block time is averaged over.
+const BLOCK_TIME_SAMPLE: u64 = 20;
+
+/// ID of the faucet actor deployed by Fendermint, reachable by EVM tooling at its masked ID
+/// address.
+const FAUCET_ACTOR_ID: u64 = 51;
+
 pub struct EthSubnetManager {
-    keystore: Option<Arc<RwLock<PersistentKeyStore<EthKeyAddress>>>>,
+    keystore: Option<Arc<RwLock<EvmKeyStoreBackend>>>,
     ipc_contract_info: IPCContractInfo,
+    /// Records the signatures of the keys, if set.
+    audit: Option<SigningAudit>,
+    /// The fees of the transactions set in the config rather than estimated.
+    fee_overrides: FeeOverrides,
+}
+
+/// The EIP-1559 fees per unit of gas overriding the estimated ones, if set.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeeOverrides {
+    pub tip: Option<U256>,
+    pub fee_cap: Option<U256>,
+}
+
+impl TryFrom<&GasOverrides> for FeeOverrides {
+    type Error = anyhow::Error;
+
+    fn try_from(overrides: &GasOverrides) -> Result<Self> {
+        Ok(Self {
+            tip: overrides.tip.as_ref().map(fil_to_eth_amount).transpose()?,
+            fee_cap: overrides
+                .fee_cap
+                .as_ref()
+                .map(fil_to_eth_amount)
+                .transpose()?,
+        })
+    }
 }
 
 /// Keep track of the on chain information for the subnet manager
@@ -81,7 +130,7 @@ struct IPCContractInfo {
     gateway_addr: ethers::types::Address,
     registry_addr: ethers::types::Address,
     chain_id: u64,
-    provider: Provider<Http>,
+    provider: Provider<LimitedHttp>,
 }
 
 //TODO receive clarity on this implementation
@@ -89,11 +138,54 @@ abigen!(
     IERC20,
     r#"[
         function approve(address spender, uint256 amount) external returns (bool)
+        function transfer(address to, uint256 amount) external returns (bool)
+        function balanceOf(address owner) external view returns (uint256)
+        function symbol() external view returns (string)
+        function decimals() external view returns (uint8)
         event Transfer(address indexed from, address indexed to, uint256 value)
         event Approval(address indexed owner, address indexed spender, uint256 value)
     ]"#,
 );
 
+abigen!(
+    IERC721,
+    r#"[
+        function ownerOf(uint256 tokenId) external view returns (address)
+    ]"#,
+);
+
+abigen!(
+    IModelExchange,
+    r#"[
+        struct SubnetID { uint64 root; address[] route; }
+        struct FvmAddress { uint8 addrType; bytes payload; }
+        struct IPCAddress { SubnetID subnetId; FvmAddress rawAddress; }
+        function exportModel(IPCAddress to, bytes32 modelHash, bytes model) external payable
+        function hasModel(bytes32 modelHash) external view returns (bool)
+    ]"#,
+);
+
+// The ABI the Fendermint ETH API exposes for the machine learning actor.
+abigen!(
+    IMachineLearning,
+    r#"[
+        function trainLinearRegression(int64[][] input, int64[] labels) external view returns (bytes)
+        function trainLogisticRegression(int64[][] input, int64[] labels) external view returns (bytes)
+        function trainKNNRegression(int64[][] input, int64[] labels) external view returns (bytes)
+        function predictLinearRegression(int64[][] input, bytes model) external view returns (int64[])
+        function predictLogisticRegression(int64[][] input, bytes model) external view returns (int64[])
+        function predictKNNRegression(int64[][] input, bytes model) external view returns (int64[])
+    ]"#,
+);
+
+// The ABI of the faucet actor, which handles the calldata of EVM transactions itself.
+abigen!(
+    IFaucet,
+    r#"[
+        function drip(address to) external
+    ]"#,
+);
+
 #[async_trait]
 impl TopDownFinalityQuery for EthSubnetManager {
     async fn genesis_epoch(&self, subnet_id: &SubnetID) -> Result<ChainEpoch> {
@@ -248,35 +340,9 @@ impl TopDownFinalityQuery for EthSubnetManager {
 #[async_trait]
 impl SubnetManager for EthSubnetManager {
     async fn create_subnet(&self, from: Address, params: ConstructParams) -> Result<Address> {
-        self.ensure_same_gateway(&params.ipc_gateway_addr)?;
-
-        let min_validator_stake = params
-            .min_validator_stake
-            .atto()
-            .to_u128()
-            .ok_or_else(|| anyhow!("invalid min validator stake"))?;
-
         tracing::debug!("calling create subnet for EVM manager");
 
-        let route = subnet_id_to_evm_addresses(&params.parent)?;
-        tracing::debug!("root SubnetID as Ethereum type: {route:?}");
-
-        let params = register_subnet_facet::ConstructorParams {
-            parent_id: register_subnet_facet::SubnetID {
-                root: params.parent.root_id(),
-                route,
-            },
-            ipc_gateway_addr: self.ipc_contract_info.gateway_addr,
-            consensus: params.consensus as u64 as u8,
-            min_activation_collateral: ethers::types::U256::from(min_validator_stake),
-            min_validators: params.min_validators,
-            bottom_up_check_period: params.bottomup_check_period as u64,
-            majority_percentage: SUBNET_MAJORITY_PERCENTAGE,
-            active_validators_limit: params.active_validators_limit,
-            power_scale: 3,
-            permission_mode: params.permission_mode as u8,
-            supply_source: register_subnet_facet::SupplySource::try_from(params.supply_source)?,
-        };
+        let params = self.subnet_constructor_params(params)?;
 
         tracing::info!("creating subnet on evm with params: {params:?}");
 
@@ -287,8 +353,12 @@ impl SubnetManager for EthSubnetManager {
             signer.clone(),
         );
 
-        let call = call_with_premium_estimation(signer, registry_contract.new_subnet_actor(params))
-            .await?;
+        let call = call_with_premium_estimation(
+            signer,
+            &self.fee_overrides,
+            registry_contract.new_subnet_actor(params),
+        )
+        .await?;
         // TODO: Edit call to get estimate premium
         let pending_tx = call.send().await?;
         // We need the retry to parse the deployment event. At the time of this writing, it's a bug
@@ -328,7 +398,7 @@ impl SubnetManager for EthSubnetManager {
         from: Address,
         collateral: TokenAmount,
         pub_key: Vec<u8>,
-    ) -> Result<ChainEpoch> {
+    ) -> Result<TxReceipt> {
         let collateral = collateral
             .atto()
             .to_u128()
@@ -345,17 +415,23 @@ impl SubnetManager for EthSubnetManager {
 
         let mut txn = contract.join(ethers::types::Bytes::from(pub_key));
         txn.tx.set_value(collateral);
-        let txn = call_with_premium_estimation(signer, txn).await?;
+        let txn = call_with_premium_estimation(signer.clone(), &self.fee_overrides, txn).await?;
+        ensure_sufficient_funds(&signer, &txn, &parent_of(&subnet)?).await?;
 
         // Use the pending state to get the nonce because there could have been a pre-fund. Best would be to use this for everything.
         let txn = txn.block(BlockId::Number(ethers::types::BlockNumber::Pending));
 
         let pending_tx = txn.send().await?;
         let receipt = pending_tx.retries(TRANSACTION_RECEIPT_RETRIES).await?;
-        block_number_from_receipt(receipt)
+        tx_receipt(receipt)
     }
 
-    async fn pre_fund(&self, subnet: SubnetID, from: Address, balance: TokenAmount) -> Result<()> {
+    async fn pre_fund(
+        &self,
+        subnet: SubnetID,
+        from: Address,
+        balance: TokenAmount,
+    ) -> Result<TxReceipt> {
         let balance = balance
             .atto()
             .to_u128()
@@ -370,10 +446,14 @@ impl SubnetManager for EthSubnetManager {
 
         let mut txn = contract.pre_fund();
         txn.tx.set_value(balance);
-        let txn = call_with_premium_estimation(signer, txn).await?;
+        let txn = call_with_premium_estimation(signer, &self.fee_overrides, txn).await?;
 
-        txn.send().await?;
-        Ok(())
+        let receipt = txn
+            .send()
+            .await?
+            .retries(TRANSACTION_RECEIPT_RETRIES)
+            .await?;
+        tx_receipt(receipt)
     }
 
     async fn pre_release(
@@ -381,7 +461,7 @@ impl SubnetManager for EthSubnetManager {
         subnet: SubnetID,
         from: Address,
         amount: TokenAmount,
-    ) -> Result<()> {
+    ) -> Result<TxReceipt> {
         let address = contract_address_from_subnet(&subnet)?;
         tracing::info!("pre-release funds from {subnet:} at contract: {address:}");
 
@@ -394,16 +474,26 @@ impl SubnetManager for EthSubnetManager {
         let contract =
             subnet_actor_manager_facet::SubnetActorManagerFacet::new(address, signer.clone());
 
-        call_with_premium_estimation(signer, contract.pre_release(amount.into()))
-            .await?
-            .send()
-            .await?
-            .await?;
+        let receipt = call_with_premium_estimation(
+            signer,
+            &self.fee_overrides,
+            contract.pre_release(amount.into()),
+        )
+        .await?
+        .send()
+        .await?
+        .retries(TRANSACTION_RECEIPT_RETRIES)
+        .await?;
 
-        Ok(())
+        tx_receipt(receipt)
     }
 
-    async fn stake(&self, subnet: SubnetID, from: Address, collateral: TokenAmount) -> Result<()> {
+    async fn stake(
+        &self,
+        subnet: SubnetID,
+        from: Address,
+        collateral: TokenAmount,
+    ) -> Result<TxReceipt> {
         let collateral = collateral
             .atto()
             .to_u128()
@@ -420,11 +510,15 @@ impl SubnetManager for EthSubnetManager {
 
         let mut txn = contract.stake();
         txn.tx.set_value(collateral);
-        let txn = call_with_premium_estimation(signer, txn).await?;
+        let txn = call_with_premium_estimation(signer, &self.fee_overrides, txn).await?;
 
-        txn.send().await?.await?;
+        let receipt = txn
+            .send()
+            .await?
+            .retries(TRANSACTION_RECEIPT_RETRIES)
+            .await?;
 
-        Ok(())
+        tx_receipt(receipt)
     }
 
     async fn unstake(
@@ -432,7 +526,7 @@ impl SubnetManager for EthSubnetManager {
         subnet: SubnetID,
         from: Address,
         collateral: TokenAmount,
-    ) -> Result<()> {
+    ) -> Result<TxReceipt> {
         let collateral = collateral
             .atto()
             .to_u128()
@@ -447,13 +541,22 @@ impl SubnetManager for EthSubnetManager {
         let contract =
             subnet_actor_manager_facet::SubnetActorManagerFacet::new(address, signer.clone());
 
-        let txn = call_with_premium_estimation(signer, contract.unstake(collateral.into())).await?;
-        txn.send().await?.await?;
+        let txn = call_with_premium_estimation(
+            signer,
+            &self.fee_overrides,
+            contract.unstake(collateral.into()),
+        )
+        .await?;
+        let receipt = txn
+            .send()
+            .await?
+            .retries(TRANSACTION_RECEIPT_RETRIES)
+            .await?;
 
-        Ok(())
+        tx_receipt(receipt)
     }
 
-    async fn leave_subnet(&self, subnet: SubnetID, from: Address) -> Result<()> {
+    async fn leave_subnet(&self, subnet: SubnetID, from: Address) -> Result<TxReceipt> {
         let address = contract_address_from_subnet(&subnet)?;
         tracing::info!("leaving evm subnet: {subnet:} at contract: {address:}");
 
@@ -461,16 +564,17 @@ impl SubnetManager for EthSubnetManager {
         let contract =
             subnet_actor_manager_facet::SubnetActorManagerFacet::new(address, signer.clone());
 
-        call_with_premium_estimation(signer, contract.leave())
+        let receipt = call_with_premium_estimation(signer, &self.fee_overrides, contract.leave())
             .await?
             .send()
             .await?
+            .retries(TRANSACTION_RECEIPT_RETRIES)
             .await?;
 
-        Ok(())
+        tx_receipt(receipt)
     }
 
-    async fn kill_subnet(&self, subnet: SubnetID, from: Address) -> Result<()> {
+    async fn kill_subnet(&self, subnet: SubnetID, from: Address) -> Result<TxReceipt> {
         let address = contract_address_from_subnet(&subnet)?;
         tracing::info!("kill evm subnet: {subnet:} at contract: {address:}");
 
@@ -478,13 +582,150 @@ impl SubnetManager for EthSubnetManager {
         let contract =
             subnet_actor_manager_facet::SubnetActorManagerFacet::new(address, signer.clone());
 
-        call_with_premium_estimation(signer, contract.kill())
+        let receipt = call_with_premium_estimation(signer, &self.fee_overrides, contract.kill())
             .await?
             .send()
             .await?
+            .retries(TRANSACTION_RECEIPT_RETRIES)
             .await?;
 
-        Ok(())
+        tx_receipt(receipt)
+    }
+
+    async fn report_double_sign(
+        &self,
+        subnet: SubnetID,
+        from: Address,
+        evidence: DoubleSignEvidence,
+    ) -> Result<TxReceipt> {
+        let address = contract_address_from_subnet(&subnet)?;
+        tracing::info!("reporting {evidence} to evm subnet: {subnet:} at contract: {address:}");
+
+        let signer = Arc::new(self.get_signer(&from)?);
+        let contract =
+            subnet_actor_manager_facet::SubnetActorManagerFacet::new(address, signer.clone());
+
+        let evidence = subnet_actor_manager_facet::DoubleSignEvidence {
+            validator: evidence.validator,
+            vote_a: evidence.vote_a.into(),
+            signature_a: evidence.signature_a.into(),
+            vote_b: evidence.vote_b.into(),
+            signature_b: evidence.signature_b.into(),
+        };
+
+        let receipt = call_with_premium_estimation(
+            signer,
+            &self.fee_overrides,
+            contract.report_double_sign(evidence),
+        )
+        .await?
+        .send()
+        .await?
+        .retries(TRANSACTION_RECEIPT_RETRIES)
+        .await?;
+
+        tx_receipt(receipt)
+    }
+
+    async fn propose_param_change(
+        &self,
+        subnet: SubnetID,
+        from: Address,
+        param: SubnetParam,
+        value: u64,
+    ) -> Result<(u64, TxReceipt)> {
+        let address = contract_address_from_subnet(&subnet)?;
+        tracing::info!(
+            "proposing {param} = {value} to evm subnet: {subnet:} at contract: {address:}"
+        );
+
+        let signer = Arc::new(self.get_signer(&from)?);
+        let contract =
+            subnet_actor_manager_facet::SubnetActorManagerFacet::new(address, signer.clone());
+
+        let receipt = call_with_premium_estimation(
+            signer,
+            &self.fee_overrides,
+            contract.propose_param_change(param as u8, U256::from(value)),
+        )
+        .await?
+        .send()
+        .await?
+        .retries(TRANSACTION_RECEIPT_RETRIES)
+        .await?;
+
+        let id = receipt
+            .iter()
+            .flat_map(|r| r.logs.iter())
+            .find_map(|log| {
+                ethers_contract::parse_log::<subnet_actor_manager_facet::ParamChangeProposedFilter>(
+                    log.clone(),
+                )
+                .ok()
+            })
+            .map(|proposed| proposed.id.as_u64())
+            .ok_or_else(|| anyhow!("no ParamChangeProposed event in the receipt"))?;
+
+        Ok((id, tx_receipt(receipt)?))
+    }
+
+    async fn vote_param_change(
+        &self,
+        subnet: SubnetID,
+        from: Address,
+        id: u64,
+    ) -> Result<TxReceipt> {
+        let address = contract_address_from_subnet(&subnet)?;
+        tracing::info!("voting for proposal {id} of evm subnet: {subnet:} at contract: {address:}");
+
+        let signer = Arc::new(self.get_signer(&from)?);
+        let contract =
+            subnet_actor_manager_facet::SubnetActorManagerFacet::new(address, signer.clone());
+
+        let receipt = call_with_premium_estimation(
+            signer,
+            &self.fee_overrides,
+            contract.vote_param_change(U256::from(id)),
+        )
+        .await?
+        .send()
+        .await?
+        .retries(TRANSACTION_RECEIPT_RETRIES)
+        .await?;
+
+        tx_receipt(receipt)
+    }
+
+    async fn list_param_change_proposals(
+        &self,
+        subnet: &SubnetID,
+    ) -> Result<Vec<ParamChangeProposal>> {
+        let address = contract_address_from_subnet(subnet)?;
+        let contract = subnet_actor_getter_facet::SubnetActorGetterFacet::new(
+            address,
+            Arc::new(self.ipc_contract_info.provider.clone()),
+        );
+
+        contract
+            .param_change_proposals()
+            .call()
+            .await?
+            .into_iter()
+            .enumerate()
+            .map(|(id, p)| {
+                Ok(ParamChangeProposal {
+                    id: id as u64,
+                    param: SubnetParam::from_repr(p.param)
+                        .ok_or_else(|| anyhow!("unknown subnet parameter: {}", p.param))?,
+                    value: p.value.as_u64(),
+                    proposer: p.proposer,
+                    deadline: p.deadline.as_u64() as ChainEpoch,
+                    votes: u128::try_from(p.votes)
+                        .map_err(|_| anyhow!("proposal votes overflow: {}", p.votes))?,
+                    executed: p.executed,
+                })
+            })
+            .collect()
     }
 
     async fn list_child_subnets(
@@ -511,7 +752,7 @@ impl SubnetManager for EthSubnetManager {
         Ok(s)
     }
 
-    async fn claim_collateral(&self, subnet: SubnetID, from: Address) -> Result<()> {
+    async fn claim_collateral(&self, subnet: SubnetID, from: Address) -> Result<TxReceipt> {
         let address = contract_address_from_subnet(&subnet)?;
         tracing::info!("claim collateral evm subnet: {subnet:} at contract: {address:}");
 
@@ -519,13 +760,14 @@ impl SubnetManager for EthSubnetManager {
         let contract =
             subnet_actor_reward_facet::SubnetActorRewardFacet::new(address, signer.clone());
 
-        call_with_premium_estimation(signer, contract.claim())
+        let receipt = call_with_premium_estimation(signer, &self.fee_overrides, contract.claim())
             .await?
             .send()
             .await?
+            .retries(TRANSACTION_RECEIPT_RETRIES)
             .await?;
 
-        Ok(())
+        tx_receipt(receipt)
     }
 
     async fn fund(
@@ -535,7 +777,7 @@ impl SubnetManager for EthSubnetManager {
         from: Address,
         to: Address,
         amount: TokenAmount,
-    ) -> Result<ChainEpoch> {
+    ) -> Result<TxReceipt> {
         self.ensure_same_gateway(&gateway_addr)?;
 
         let value = amount
@@ -559,11 +801,12 @@ impl SubnetManager for EthSubnetManager {
             gateway_manager_facet::FvmAddress::try_from(to)?,
         );
         txn.tx.set_value(value);
-        let txn = call_with_premium_estimation(signer, txn).await?;
+        let txn = call_with_premium_estimation(signer.clone(), &self.fee_overrides, txn).await?;
+        ensure_sufficient_funds(&signer, &txn, &parent_of(&subnet)?).await?;
 
         let pending_tx = txn.send().await?;
         let receipt = pending_tx.retries(TRANSACTION_RECEIPT_RETRIES).await?;
-        block_number_from_receipt(receipt)
+        tx_receipt(receipt)
     }
 
     /// Approves the `from` address to use up to `amount` tokens from `token_address`.
@@ -572,7 +815,7 @@ impl SubnetManager for EthSubnetManager {
         subnet: SubnetID,
         from: Address,
         amount: TokenAmount,
-    ) -> Result<ChainEpoch> {
+    ) -> Result<TxReceipt> {
         log::debug!("approve token, subnet: {subnet}, amount: {amount}, from: {from}");
 
         let value = fil_amount_to_eth_amount(&amount)?;
@@ -587,11 +830,11 @@ impl SubnetManager for EthSubnetManager {
         let token_contract = IERC20::new(subnet_supply_source.token_address, signer.clone());
 
         let txn = token_contract.approve(self.ipc_contract_info.gateway_addr, value);
-        let txn = call_with_premium_estimation(signer, txn).await?;
+        let txn = call_with_premium_estimation(signer, &self.fee_overrides, txn).await?;
 
         let pending_tx = txn.send().await?;
         let receipt = pending_tx.retries(TRANSACTION_RECEIPT_RETRIES).await?;
-        block_number_from_receipt(receipt)
+        tx_receipt(receipt)
     }
 
     async fn fund_with_token(
@@ -600,7 +843,7 @@ impl SubnetManager for EthSubnetManager {
         from: Address,
         to: Address,
         amount: TokenAmount,
-    ) -> Result<ChainEpoch> {
+    ) -> Result<TxReceipt> {
         tracing::debug!(
             "fund with token, subnet: {subnet}, amount: {amount}, from: {from}, to: {to}"
         );
@@ -619,11 +862,11 @@ impl SubnetManager for EthSubnetManager {
             gateway_manager_facet::FvmAddress::try_from(to)?,
             value,
         );
-        let txn = call_with_premium_estimation(signer, txn).await?;
+        let txn = call_with_premium_estimation(signer, &self.fee_overrides, txn).await?;
 
         let pending_tx = txn.send().await?;
         let receipt = pending_tx.retries(TRANSACTION_RECEIPT_RETRIES).await?;
-        block_number_from_receipt(receipt)
+        tx_receipt(receipt)
     }
 
     async fn release(
@@ -632,7 +875,7 @@ impl SubnetManager for EthSubnetManager {
         from: Address,
         to: Address,
         amount: TokenAmount,
-    ) -> Result<ChainEpoch> {
+    ) -> Result<TxReceipt> {
         self.ensure_same_gateway(&gateway_addr)?;
 
         let value = amount
@@ -649,11 +892,11 @@ impl SubnetManager for EthSubnetManager {
         );
         let mut txn = gateway_contract.release(gateway_manager_facet::FvmAddress::try_from(to)?);
         txn.tx.set_value(value);
-        let txn = call_with_premium_estimation(signer, txn).await?;
+        let txn = call_with_premium_estimation(signer, &self.fee_overrides, txn).await?;
 
         let pending_tx = txn.send().await?;
         let receipt = pending_tx.retries(TRANSACTION_RECEIPT_RETRIES).await?;
-        block_number_from_receipt(receipt)
+        tx_receipt(receipt)
     }
 
     /// Propagate the postbox message key. The key should be `bytes32`.
@@ -663,7 +906,7 @@ impl SubnetManager for EthSubnetManager {
         gateway_addr: Address,
         from: Address,
         postbox_msg_key: Vec<u8>,
-    ) -> Result<()> {
+    ) -> Result<TxReceipt> {
         if postbox_msg_key.len() != 32 {
             return Err(anyhow!(
                 "invalid message cid length, expect 32 but found {}",
@@ -684,18 +927,40 @@ impl SubnetManager for EthSubnetManager {
         let mut key = [0u8; 32];
         key.copy_from_slice(&postbox_msg_key);
 
-        call_with_premium_estimation(signer, gateway_contract.propagate(key))
-            .await?
-            .send()
-            .await?;
+        let receipt = call_with_premium_estimation(
+            signer,
+            &self.fee_overrides,
+            gateway_contract.propagate(key),
+        )
+        .await?
+        .send()
+        .await?
+        .retries(TRANSACTION_RECEIPT_RETRIES)
+        .await?;
 
-        Ok(())
+        tx_receipt(receipt)
+    }
+
+    async fn list_postbox(&self) -> Result<Vec<PostboxMsg>> {
+        let gateway_contract = gateway_getter_facet::GatewayGetterFacet::new(
+            self.ipc_contract_info.gateway_addr,
+            Arc::new(self.ipc_contract_info.provider.clone()),
+        );
+
+        let (keys, msgs) = gateway_contract.list_postbox().call().await?;
+
+        postbox_msgs(keys, msgs)
     }
 
     /// Send value between two addresses in a subnet
-    async fn send_value(&self, from: Address, to: Address, amount: TokenAmount) -> Result<()> {
+    async fn send_value(
+        &self,
+        from: Address,
+        to: Address,
+        amount: TokenAmount,
+    ) -> Result<TxReceipt> {
         let signer = Arc::new(self.get_signer(&from)?);
-        let (fee, fee_cap) = premium_estimation(signer.clone()).await?;
+        let (fee, fee_cap) = premium_estimation(signer.clone(), &self.fee_overrides).await?;
         let tx = Eip1559TransactionRequest::new()
             .to(payload_to_evm_address(to.payload())?)
             .value(fil_to_eth_amount(&amount)?)
@@ -708,8 +973,296 @@ impl SubnetManager for EthSubnetManager {
             "sending FIL from {from:} to {to:} in tx {:?}",
             tx_pending.tx_hash()
         );
-        tx_pending.await?;
-        Ok(())
+        let receipt = tx_pending.retries(TRANSACTION_RECEIPT_RETRIES).await?;
+        tx_receipt(receipt)
+    }
+
+    async fn faucet_drip(&self, from: Address, to: Address) -> Result<TxReceipt> {
+        let signer = Arc::new(self.get_signer(&from)?);
+        let address = ipc_types::EthAddress::from_id(FAUCET_ACTOR_ID);
+        let contract = IFaucet::new(ethers::types::Address::from(address.0), signer.clone());
+
+        let txn = contract.drip(payload_to_evm_address(to.payload())?);
+        let txn = call_with_premium_estimation(signer, &self.fee_overrides, txn).await?;
+
+        let pending_tx = txn.send().await?;
+        tracing::info!(
+            "asking the faucet to drip to {to:} in tx {:?}",
+            pending_tx.tx_hash()
+        );
+        let receipt = pending_tx.retries(TRANSACTION_RECEIPT_RETRIES).await?;
+        tx_receipt(receipt)
+    }
+
+    async fn erc20_balance(&self, token: Address, owner: Address) -> Result<TokenAmount> {
+        let contract = IERC20::new(
+            payload_to_evm_address(token.payload())?,
+            Arc::new(self.ipc_contract_info.provider.clone()),
+        );
+        let balance = contract
+            .balance_of(payload_to_evm_address(owner.payload())?)
+            .call()
+            .await?;
+        eth_to_fil_amount(&balance)
+    }
+
+    async fn erc20_transfer(
+        &self,
+        from: Address,
+        token: Address,
+        to: Address,
+        amount: TokenAmount,
+    ) -> Result<TxReceipt> {
+        tracing::debug!("erc20 transfer, token: {token}, amount: {amount}, from: {from}, to: {to}");
+
+        let signer = Arc::new(self.get_signer(&from)?);
+        let contract = IERC20::new(payload_to_evm_address(token.payload())?, signer.clone());
+
+        let txn = contract.transfer(
+            payload_to_evm_address(to.payload())?,
+            fil_amount_to_eth_amount(&amount)?,
+        );
+        let txn = call_with_premium_estimation(signer, &self.fee_overrides, txn).await?;
+
+        let pending_tx = txn.send().await?;
+        let receipt = pending_tx.retries(TRANSACTION_RECEIPT_RETRIES).await?;
+        tx_receipt(receipt)
+    }
+
+    async fn call_contract(&self, to: Address, calldata: Vec<u8>) -> Result<Vec<u8>> {
+        let tx = Eip1559TransactionRequest::new()
+            .to(payload_to_evm_address(to.payload())?)
+            .data(calldata);
+        let data = self
+            .ipc_contract_info
+            .provider
+            .call(&tx.into(), None)
+            .await?;
+        Ok(data.to_vec())
+    }
+
+    async fn send_contract_tx(
+        &self,
+        from: Address,
+        to: Address,
+        calldata: Vec<u8>,
+        value: TokenAmount,
+    ) -> Result<TxReceipt> {
+        let signer = Arc::new(self.get_signer(&from)?);
+        let (fee, fee_cap) = premium_estimation(signer.clone(), &self.fee_overrides).await?;
+        let tx = Eip1559TransactionRequest::new()
+            .to(payload_to_evm_address(to.payload())?)
+            .data(calldata)
+            .value(fil_to_eth_amount(&value)?)
+            .max_priority_fee_per_gas(fee)
+            .max_fee_per_gas(fee_cap);
+
+        let tx_pending = signer.send_transaction(tx, None).await?;
+
+        tracing::info!(
+            "calling contract {to:} from {from:} in tx {:?}",
+            tx_pending.tx_hash()
+        );
+        let receipt = tx_pending.retries(TRANSACTION_RECEIPT_RETRIES).await?;
+        tx_receipt(receipt)
+    }
+
+    async fn build_unsigned_tx(
+        &self,
+        from: Address,
+        to: Address,
+        calldata: Vec<u8>,
+        value: TokenAmount,
+    ) -> Result<TypedTransaction> {
+        let provider = self.provider();
+        let (fee, fee_cap) = premium_estimation(provider.clone(), &self.fee_overrides).await?;
+        let mut tx: TypedTransaction = Eip1559TransactionRequest::new()
+            .from(payload_to_evm_address(from.payload())?)
+            .to(payload_to_evm_address(to.payload())?)
+            .data(calldata)
+            .value(fil_to_eth_amount(&value)?)
+            .max_priority_fee_per_gas(fee)
+            .max_fee_per_gas(fee_cap)
+            .chain_id(self.ipc_contract_info.chain_id)
+            .into();
+        // Fills in the nonce of the sender and the gas limit.
+        provider.fill_transaction(&mut tx, None).await?;
+        Ok(tx)
+    }
+
+    async fn broadcast_signed_tx(&self, raw: Vec<u8>) -> Result<TxReceipt> {
+        let provider = self.provider();
+        let tx_pending = provider.send_raw_transaction(raw.into()).await?;
+
+        tracing::info!(
+            "broadcasting transaction signed offline in tx {:?}",
+            tx_pending.tx_hash()
+        );
+        let receipt = tx_pending.retries(TRANSACTION_RECEIPT_RETRIES).await?;
+        tx_receipt(receipt)
+    }
+
+    async fn deploy_contract(
+        &self,
+        from: Address,
+        init_code: Vec<u8>,
+        salt: Option<[u8; 32]>,
+    ) -> Result<DeployedContract> {
+        let signer = Arc::new(self.get_signer(&from)?);
+        let (fee, fee_cap) = premium_estimation(signer.clone(), &self.fee_overrides).await?;
+
+        let (tx, address) = match salt {
+            Some(salt) => {
+                let factory = deploy::create2_factory();
+                if signer.get_code(factory, None).await?.is_empty() {
+                    return Err(anyhow!(
+                        "the deterministic deployment proxy is not deployed at {factory:?}"
+                    ));
+                }
+                let address = deploy::create2_address(salt, &init_code);
+                if !signer.get_code(address, None).await?.is_empty() {
+                    return Err(anyhow!("a contract is already deployed at {address:?}"));
+                }
+                let data = [salt.as_slice(), &init_code].concat();
+                let tx = Eip1559TransactionRequest::new().to(factory).data(data);
+                (tx, Some(address))
+            }
+            None => (Eip1559TransactionRequest::new().data(init_code), None),
+        };
+        let tx = tx.max_priority_fee_per_gas(fee).max_fee_per_gas(fee_cap);
+
+        let tx_pending = signer.send_transaction(tx, None).await?;
+        tracing::info!(
+            "deploying contract from {from:} in tx {:?}",
+            tx_pending.tx_hash()
+        );
+
+        let receipt = tx_pending
+            .retries(TRANSACTION_RECEIPT_RETRIES)
+            .await?
+            .ok_or_else(|| {
+                anyhow!("txn sent to network, but receipt cannot be obtained, please check scanner")
+            })?;
+        let address = address
+            .or(receipt.contract_address)
+            .ok_or_else(|| anyhow!("no contract address in the receipt of the deployment"))?;
+
+        let receipt = TxReceipt::try_from(receipt)?;
+        if !receipt.success {
+            return Err(anyhow!("deployment reverted in tx {:?}", receipt.tx_hash));
+        }
+
+        Ok(DeployedContract { address, receipt })
+    }
+
+    async fn erc721_owner_of(&self, token: Address, token_id: U256) -> Result<Address> {
+        let contract = IERC721::new(
+            payload_to_evm_address(token.payload())?,
+            Arc::new(self.ipc_contract_info.provider.clone()),
+        );
+        let owner = contract.owner_of(token_id).call().await?;
+        ethers_address_to_fil_address(&owner)
+    }
+
+    async fn export_model(
+        &self,
+        from: Address,
+        exchange: Address,
+        to: IPCAddress,
+        model: Vec<u8>,
+        value: TokenAmount,
+    ) -> Result<ModelHash> {
+        let model_hash = model_hash(&model);
+
+        tracing::info!(
+            "exporting model {} through exchange {exchange:} to {to:?}",
+            hex::encode(model_hash)
+        );
+
+        // Reuse the conversions generated for the gateway, which has the same structs.
+        let to = gateway_messenger_facet::Ipcaddress::try_from(to)?;
+        let to = imodel_exchange::Ipcaddress {
+            subnet_id: imodel_exchange::SubnetID {
+                root: to.subnet_id.root,
+                route: to.subnet_id.route,
+            },
+            raw_address: imodel_exchange::FvmAddress {
+                addr_type: to.raw_address.addr_type,
+                payload: to.raw_address.payload,
+            },
+        };
+
+        let signer = Arc::new(self.get_signer(&from)?);
+        let contract =
+            IModelExchange::new(payload_to_evm_address(exchange.payload())?, signer.clone());
+
+        let mut txn = contract.export_model(to, model_hash, model.into());
+        txn.tx.set_value(fil_to_eth_amount(&value)?);
+        let txn = call_with_premium_estimation(signer, &self.fee_overrides, txn).await?;
+
+        let pending_tx = txn.send().await?;
+        pending_tx.retries(TRANSACTION_RECEIPT_RETRIES).await?;
+
+        Ok(model_hash)
+    }
+
+    async fn has_model(&self, exchange: Address, model_hash: ModelHash) -> Result<bool> {
+        let contract = IModelExchange::new(
+            payload_to_evm_address(exchange.payload())?,
+            Arc::new(self.ipc_contract_info.provider.clone()),
+        );
+        Ok(contract.has_model(model_hash).call().await?)
+    }
+
+    async fn ml_train(
+        &self,
+        from: Address,
+        algorithm: MlAlgorithm,
+        input_matrix: Vec<Vec<i64>>,
+        labels: Vec<i64>,
+    ) -> Result<Vec<u8>> {
+        let contract = self.machine_learning();
+
+        let call = match algorithm {
+            MlAlgorithm::LinearRegression => contract.train_linear_regression(input_matrix, labels),
+            MlAlgorithm::LogisticRegression => {
+                contract.train_logistic_regression(input_matrix, labels)
+            }
+            MlAlgorithm::KnnRegression => contract.train_knn_regression(input_matrix, labels),
+        };
+
+        let model = call
+            .from(payload_to_evm_address(from.payload())?)
+            .call()
+            .await?;
+
+        Ok(model.to_vec())
+    }
+
+    async fn ml_predict(
+        &self,
+        from: Address,
+        algorithm: MlAlgorithm,
+        input_matrix: Vec<Vec<i64>>,
+        model: Vec<u8>,
+    ) -> Result<Vec<i64>> {
+        let contract = self.machine_learning();
+        let model = ethers::types::Bytes::from(model);
+
+        let call = match algorithm {
+            MlAlgorithm::LinearRegression => {
+                contract.predict_linear_regression(input_matrix, model)
+            }
+            MlAlgorithm::LogisticRegression => {
+                contract.predict_logistic_regression(input_matrix, model)
+            }
+            MlAlgorithm::KnnRegression => contract.predict_knn_regression(input_matrix, model),
+        };
+
+        Ok(call
+            .from(payload_to_evm_address(from.payload())?)
+            .call()
+            .await?)
     }
 
     async fn wallet_balance(&self, address: &Address) -> Result<TokenAmount> {
@@ -719,16 +1272,303 @@ impl SubnetManager for EthSubnetManager {
             .clone()
             .get_balance(payload_to_evm_address(address.payload())?, None)
             .await?;
-        Ok(TokenAmount::from_atto(balance.as_u128()))
+        Ok(TokenAmount::from_atto(balance.as_u128()))
+    }
+
+    async fn get_chain_id(&self) -> Result<String> {
+        Ok(self
+            .ipc_contract_info
+            .provider
+            .get_chainid()
+            .await?
+            .to_string())
+    }
+
+    async fn chain_params(&self) -> Result<ChainParams> {
+        let provider = &self.ipc_contract_info.provider;
+        let chain_id = provider.get_chainid().await?.to_string();
+
+        let latest = provider
+            .get_block(ethers::types::BlockNumber::Latest)
+            .await?
+            .ok_or_else(|| anyhow!("latest block not found"))?;
+        let head = latest
+            .number
+            .ok_or_else(|| anyhow!("latest block has no number"))?
+            .as_u64();
+
+        let block_time = self
+            .recent_block_time(head, latest.timestamp.as_u64())
+            .await?;
+
+        // The base fees of the history include the one of the next block.
+        let fee_history = provider
+            .fee_history(1u64, ethers::types::BlockNumber::Latest, &[])
+            .await?;
+        let next_base_fee = fee_history
+            .base_fee_per_gas
+            .last()
+            .copied()
+            .or(latest.base_fee_per_gas)
+            .unwrap_or_default();
+
+        Ok(ChainParams {
+            chain_id,
+            chain_head: head as ChainEpoch,
+            block_time,
+            gas_price_floor: eth_to_fil_amount(&next_base_fee)?,
+            base_fee: latest
+                .base_fee_per_gas
+                .map(|fee| eth_to_fil_amount(&fee))
+                .transpose()?,
+        })
+    }
+
+    async fn top_down_receipt(&self, nonce: u64) -> Result<Option<TopDownReceipt>> {
+        // Fendermint indexes the receipts of the top-down messages it executes; other nodes
+        // don't know about the method.
+        let receipt = self
+            .ipc_contract_info
+            .provider
+            .request("ipc_getTopDownReceipt", [ethers::types::U64::from(nonce)])
+            .await
+            .context("failed to query topdown receipt, is the subnet running fendermint?")?;
+        Ok(receipt)
+    }
+
+    async fn circ_supply(&self) -> Result<TokenAmount> {
+        // Only Fendermint tracks the circulating supply of a subnet.
+        let supply: U256 = self
+            .ipc_contract_info
+            .provider
+            .request("ipc_getCircSupply", ())
+            .await
+            .context("failed to query circulating supply, is the subnet running fendermint?")?;
+        eth_to_fil_amount(&supply)
+    }
+
+    async fn app_version(&self) -> Result<u64> {
+        // The app version is maintained by the upgrade scheduler of Fendermint.
+        let version: ethers::types::U64 = self
+            .ipc_contract_info
+            .provider
+            .request("ipc_getAppVersion", ())
+            .await
+            .context("failed to query app version, is the subnet running fendermint?")?;
+        Ok(version.as_u64())
+    }
+
+    async fn contract_code_size(&self, address: &Address) -> Result<usize> {
+        let code = self
+            .ipc_contract_info
+            .provider
+            .get_code(payload_to_evm_address(address.payload())?, None)
+            .await?;
+        Ok(code.len())
     }
 
-    async fn get_chain_id(&self) -> Result<String> {
-        Ok(self
+    async fn account_nonce(&self, address: &Address) -> Result<u64> {
+        let nonce = self
             .ipc_contract_info
             .provider
-            .get_chainid()
-            .await?
-            .to_string())
+            .get_transaction_count(
+                payload_to_evm_address(address.payload())?,
+                Some(BlockId::Number(ethers::types::BlockNumber::Pending)),
+            )
+            .await?;
+        Ok(nonce.as_u64())
+    }
+
+    async fn actor_state(&self, actor: &StateActor, epoch: ChainEpoch) -> Result<ActorState> {
+        let block = BlockId::Number(ethers::types::BlockNumber::Number((epoch as u64).into()));
+        let provider = Arc::new(self.ipc_contract_info.provider.clone());
+        let gateway = gateway_getter_facet::GatewayGetterFacet::new(
+            self.ipc_contract_info.gateway_addr,
+            provider.clone(),
+        );
+        let mut fields = BTreeMap::new();
+
+        let address = match actor {
+            StateActor::Gateway => {
+                let finality = gateway
+                    .get_latest_parent_finality()
+                    .block(block)
+                    .call()
+                    .await?;
+                let state = [
+                    (
+                        "bottom_up_nonce",
+                        gateway
+                            .bottom_up_nonce()
+                            .block(block)
+                            .call()
+                            .await?
+                            .to_string(),
+                    ),
+                    (
+                        "applied_top_down_nonce",
+                        gateway
+                            .applied_top_down_nonce()
+                            .block(block)
+                            .call()
+                            .await?
+                            .to_string(),
+                    ),
+                    (
+                        "total_subnets",
+                        gateway
+                            .total_subnets()
+                            .block(block)
+                            .call()
+                            .await?
+                            .to_string(),
+                    ),
+                    (
+                        "current_configuration_number",
+                        gateway
+                            .get_current_configuration_number()
+                            .block(block)
+                            .call()
+                            .await?
+                            .to_string(),
+                    ),
+                    (
+                        "last_configuration_number",
+                        gateway
+                            .get_last_configuration_number()
+                            .block(block)
+                            .call()
+                            .await?
+                            .to_string(),
+                    ),
+                    ("parent_finality.height", finality.height.to_string()),
+                    (
+                        "parent_finality.block_hash",
+                        hex::encode(finality.block_hash),
+                    ),
+                ];
+                fields.extend(state.map(|(k, v)| (k.to_string(), v)));
+                self.ipc_contract_info.gateway_addr
+            }
+            StateActor::SubnetActor(subnet) => {
+                let address = contract_address_from_subnet(subnet)?;
+                let contract = subnet_actor_getter_facet::SubnetActorGetterFacet::new(
+                    address,
+                    provider.clone(),
+                );
+                let (next_configuration_number, start_configuration_number) = contract
+                    .get_configuration_numbers()
+                    .block(block)
+                    .call()
+                    .await?;
+                let (exists, child) = gateway
+                    .get_subnet(gateway_getter_facet::SubnetID::try_from(subnet)?)
+                    .block(block)
+                    .call()
+                    .await?;
+
+                let mut state = vec![
+                    (
+                        "bootstrapped",
+                        contract
+                            .bootstrapped()
+                            .block(block)
+                            .call()
+                            .await?
+                            .to_string(),
+                    ),
+                    (
+                        "killed",
+                        contract.killed().block(block).call().await?.to_string(),
+                    ),
+                    (
+                        "last_bottom_up_checkpoint_height",
+                        contract
+                            .last_bottom_up_checkpoint_height()
+                            .block(block)
+                            .call()
+                            .await?
+                            .to_string(),
+                    ),
+                    (
+                        "next_configuration_number",
+                        next_configuration_number.to_string(),
+                    ),
+                    (
+                        "start_configuration_number",
+                        start_configuration_number.to_string(),
+                    ),
+                    (
+                        "total_confirmed_collateral",
+                        contract
+                            .get_total_confirmed_collateral()
+                            .block(block)
+                            .call()
+                            .await?
+                            .to_string(),
+                    ),
+                    (
+                        "total_collateral",
+                        contract
+                            .get_total_collateral()
+                            .block(block)
+                            .call()
+                            .await?
+                            .to_string(),
+                    ),
+                    (
+                        "active_validators",
+                        contract
+                            .get_active_validators_number()
+                            .block(block)
+                            .call()
+                            .await?
+                            .to_string(),
+                    ),
+                    (
+                        "total_validators",
+                        contract
+                            .get_total_validators_number()
+                            .block(block)
+                            .call()
+                            .await?
+                            .to_string(),
+                    ),
+                    ("gateway.registered", exists.to_string()),
+                ];
+                // What the gateway tracks of the child, once it's registered.
+                if exists {
+                    state.extend([
+                        ("gateway.stake", child.stake.to_string()),
+                        ("gateway.circ_supply", child.circ_supply.to_string()),
+                        ("gateway.top_down_nonce", child.top_down_nonce.to_string()),
+                        (
+                            "gateway.applied_bottom_up_nonce",
+                            child.applied_bottom_up_nonce.to_string(),
+                        ),
+                    ]);
+                }
+                fields.extend(state.into_iter().map(|(k, v)| (k.to_string(), v)));
+                address
+            }
+            StateActor::MachineLearning => self.machine_learning().address(),
+        };
+
+        let balance = provider.get_balance(address, Some(block)).await?;
+        let nonce = provider.get_transaction_count(address, Some(block)).await?;
+        let code = provider.get_code(address, Some(block)).await?;
+        fields.insert("balance".to_string(), balance.to_string());
+        fields.insert("nonce".to_string(), nonce.to_string());
+        fields.insert(
+            "code_hash".to_string(),
+            hex::encode(ethers::utils::keccak256(&code)),
+        );
+
+        Ok(ActorState {
+            address,
+            epoch,
+            fields,
+        })
     }
 
     async fn get_commit_sha(&self) -> Result<[u8; 32]> {
@@ -766,6 +1606,21 @@ impl SubnetManager for EthSubnetManager {
         Ok(contract.supply_source().call().await?)
     }
 
+    async fn supply_source_token(&self, subnet: &SubnetID) -> Result<Option<Denomination>> {
+        let supply_source = self.get_subnet_supply_source(subnet).await?;
+        if supply_source.kind != SupplyKind::ERC20 as u8 {
+            return Ok(None);
+        }
+
+        let token = IERC20::new(supply_source.token_address, self.provider());
+        let symbol = token.symbol().call().await?;
+        let decimals = token.decimals().call().await?;
+        Ok(Some(Denomination {
+            symbol,
+            decimals: decimals as u32,
+        }))
+    }
+
     async fn get_genesis_info(&self, subnet: &SubnetID) -> Result<SubnetGenesisInfo> {
         let address = contract_address_from_subnet(subnet)?;
         let contract = subnet_actor_getter_facet::SubnetActorGetterFacet::new(
@@ -799,6 +1654,29 @@ impl SubnetManager for EthSubnetManager {
         })
     }
 
+    async fn get_lifecycle_info(&self, subnet: &SubnetID) -> Result<SubnetLifecycleInfo> {
+        let address = contract_address_from_subnet(subnet)?;
+        let contract = subnet_actor_getter_facet::SubnetActorGetterFacet::new(
+            address,
+            Arc::new(self.ipc_contract_info.provider.clone()),
+        );
+
+        Ok(SubnetLifecycleInfo {
+            bootstrapped: contract.bootstrapped().call().await?,
+            killed: contract.killed().call().await?,
+            min_validators: contract.min_validators().call().await?,
+            active_validators: contract.get_active_validators_number().call().await?,
+            total_validators: contract.get_total_validators_number().call().await?,
+            last_bottom_up_checkpoint_height: contract
+                .last_bottom_up_checkpoint_height()
+                .call()
+                .await?
+                .as_u64() as ChainEpoch,
+            bottom_up_checkpoint_period: contract.bottom_up_check_period().call().await?.as_u64()
+                as ChainEpoch,
+        })
+    }
+
     async fn add_bootstrap(
         &self,
         subnet: &SubnetID,
@@ -815,11 +1693,15 @@ impl SubnetManager for EthSubnetManager {
         let contract =
             subnet_actor_manager_facet::SubnetActorManagerFacet::new(address, signer.clone());
 
-        call_with_premium_estimation(signer, contract.add_bootstrap_node(endpoint))
-            .await?
-            .send()
-            .await?
-            .await?;
+        call_with_premium_estimation(
+            signer,
+            &self.fee_overrides,
+            contract.add_bootstrap_node(endpoint),
+        )
+        .await?
+        .send()
+        .await?
+        .await?;
 
         Ok(())
     }
@@ -863,7 +1745,7 @@ impl SubnetManager for EthSubnetManager {
         validators: &[Address],
         public_keys: &[Vec<u8>],
         federated_power: &[u128],
-    ) -> Result<ChainEpoch> {
+    ) -> Result<TxReceipt> {
         let address = contract_address_from_subnet(subnet)?;
         tracing::info!("interacting with evm subnet contract: {address:}");
 
@@ -871,31 +1753,70 @@ impl SubnetManager for EthSubnetManager {
         let contract =
             subnet_actor_manager_facet::SubnetActorManagerFacet::new(address, signer.clone());
 
-        let addresses: Vec<ethers::core::types::Address> = validators
-            .iter()
-            .map(|validator_address| payload_to_evm_address(validator_address.payload()).unwrap())
-            .collect();
-        tracing::debug!("converted addresses: {:?}", addresses);
-
-        let pubkeys: Vec<ethers::core::types::Bytes> = public_keys
-            .iter()
-            .map(|key| ethers::core::types::Bytes::from(key.clone()))
-            .collect();
-        tracing::debug!("converted pubkeys: {:?}", pubkeys);
-
-        let power_u256: Vec<ethers::core::types::U256> = federated_power
-            .iter()
-            .map(|power| ethers::core::types::U256::from(*power))
-            .collect();
-        tracing::debug!("converted power: {:?}", power_u256);
+        let (addresses, pubkeys, power_u256) =
+            federated_power_params(validators, public_keys, federated_power)?;
 
         tracing::debug!("from address: {:?}", from);
 
         let call = contract.set_federated_power(addresses, pubkeys, power_u256);
-        let txn = call_with_premium_estimation(signer, call).await?;
+        let txn = call_with_premium_estimation(signer, &self.fee_overrides, call).await?;
         let pending_tx = txn.send().await?;
         let receipt = pending_tx.retries(TRANSACTION_RECEIPT_RETRIES).await?;
-        block_number_from_receipt(receipt)
+        tx_receipt(receipt)
+    }
+
+    async fn admin_call(&self, op: &AdminOperation) -> Result<AdminCall> {
+        let provider = Arc::new(self.ipc_contract_info.provider.clone());
+
+        let (to, calldata) = match op {
+            AdminOperation::CreateSubnet(params) => {
+                self.ensure_same_gateway(&params.ipc_gateway_addr)?;
+                if !params.genesis_balances.is_empty() {
+                    return Err(anyhow!(
+                        "genesis balances are pre-funded by their accounts, not by a multisig"
+                    ));
+                }
+                let params = self.subnet_constructor_params(params.clone())?;
+                let contract = register_subnet_facet::RegisterSubnetFacet::new(
+                    self.ipc_contract_info.registry_addr,
+                    provider,
+                );
+                (
+                    self.ipc_contract_info.registry_addr,
+                    contract.new_subnet_actor(params).calldata(),
+                )
+            }
+            AdminOperation::KillSubnet(subnet) => {
+                let address = contract_address_from_subnet(subnet)?;
+                let contract =
+                    subnet_actor_manager_facet::SubnetActorManagerFacet::new(address, provider);
+                (address, contract.kill().calldata())
+            }
+            AdminOperation::SetFederatedPower {
+                subnet,
+                validators,
+                public_keys,
+                federated_power,
+            } => {
+                let address = contract_address_from_subnet(subnet)?;
+                let contract =
+                    subnet_actor_manager_facet::SubnetActorManagerFacet::new(address, provider);
+                let (addresses, pubkeys, power) =
+                    federated_power_params(validators, public_keys, federated_power)?;
+                (
+                    address,
+                    contract
+                        .set_federated_power(addresses, pubkeys, power)
+                        .calldata(),
+                )
+            }
+        };
+
+        Ok(AdminCall {
+            to,
+            value: U256::zero(),
+            calldata: calldata.ok_or_else(|| anyhow!("cannot encode {}", op.name()))?,
+        })
     }
 }
 
@@ -1005,8 +1926,8 @@ impl EthSubnetManager {
         gateway_addr: ethers::types::Address,
         registry_addr: ethers::types::Address,
         chain_id: u64,
-        provider: Provider<Http>,
-        keystore: Option<Arc<RwLock<PersistentKeyStore<EthKeyAddress>>>>,
+        provider: Provider<LimitedHttp>,
+        keystore: Option<Arc<RwLock<EvmKeyStoreBackend>>>,
     ) -> Self {
         Self {
             keystore,
@@ -1016,9 +1937,23 @@ impl EthSubnetManager {
                 chain_id,
                 provider,
             },
+            audit: None,
+            fee_overrides: FeeOverrides::default(),
         }
     }
 
+    /// Record every signature made for `subnet` in the audit log.
+    pub fn with_audit_log(mut self, log: Arc<AuditLog>, subnet: SubnetID) -> Self {
+        self.audit = Some(SigningAudit::new(log, subnet));
+        self
+    }
+
+    /// Use the fees of `overrides` instead of estimating them.
+    pub fn with_fee_overrides(mut self, overrides: FeeOverrides) -> Self {
+        self.fee_overrides = overrides;
+        self
+    }
+
     pub fn ensure_same_gateway(&self, gateway: &Address) -> Result<()> {
         let evm_gateway_addr = payload_to_evm_address(gateway.payload())?;
         if evm_gateway_addr != self.ipc_contract_info.gateway_addr {
@@ -1028,7 +1963,7 @@ impl EthSubnetManager {
         }
     }
 
-    pub fn keystore(&self) -> Result<Arc<RwLock<PersistentKeyStore<EthKeyAddress>>>> {
+    pub fn keystore(&self) -> Result<Arc<RwLock<EvmKeyStoreBackend>>> {
         self.keystore
             .clone()
             .ok_or(anyhow!("no evm keystore available"))
@@ -1037,7 +1972,7 @@ impl EthSubnetManager {
     /// Get the ethers singer instance.
     /// We use filecoin addresses throughout our whole code-base
     /// and translate them to evm addresses when relevant.
-    fn get_signer(&self, addr: &Address) -> Result<DefaultSignerMiddleware> {
+    pub(crate) fn get_signer(&self, addr: &Address) -> Result<DefaultSignerMiddleware> {
         // convert to its underlying eth address
         let addr = payload_to_evm_address(addr.payload())?;
         let keystore = self.keystore()?;
@@ -1050,39 +1985,109 @@ impl EthSubnetManager {
 
         Ok(SignerMiddleware::new(
             self.ipc_contract_info.provider.clone(),
-            wallet,
+            AuditedWallet::new(wallet, self.audit.clone()),
         ))
     }
 
-    pub fn from_subnet_with_wallet_store(
-        subnet: &Subnet,
-        keystore: Option<Arc<RwLock<PersistentKeyStore<EthKeyAddress>>>>,
-    ) -> Result<Self> {
-        let url = subnet.rpc_http().clone();
-        let auth_token = subnet.auth_token();
+    /// The provider of the network the manager connects to, for read-only contract calls.
+    pub(crate) fn provider(&self) -> Arc<Provider<LimitedHttp>> {
+        Arc::new(self.ipc_contract_info.provider.clone())
+    }
 
-        let SubnetConfig::Fevm(config) = &subnet.config;
+    /// Convert the parameters of a new subnet to the ones of the registry contract.
+    fn subnet_constructor_params(
+        &self,
+        params: ConstructParams,
+    ) -> Result<register_subnet_facet::ConstructorParams> {
+        let min_validator_stake = params
+            .min_validator_stake
+            .atto()
+            .to_u128()
+            .ok_or_else(|| anyhow!("invalid min validator stake"))?;
 
-        let mut client = Client::builder();
+        let route = subnet_id_to_evm_addresses(&params.parent)?;
+        tracing::debug!("root SubnetID as Ethereum type: {route:?}");
 
-        if let Some(auth_token) = auth_token {
-            let auth = Authorization::Bearer(auth_token);
-            let mut auth_value = HeaderValue::from_str(&auth.to_string())?;
-            auth_value.set_sensitive(true);
+        Ok(register_subnet_facet::ConstructorParams {
+            parent_id: register_subnet_facet::SubnetID {
+                root: params.parent.root_id(),
+                route,
+            },
+            ipc_gateway_addr: self.ipc_contract_info.gateway_addr,
+            consensus: params.consensus as u64 as u8,
+            min_activation_collateral: ethers::types::U256::from(min_validator_stake),
+            min_validators: params.min_validators,
+            bottom_up_check_period: params.bottomup_check_period as u64,
+            majority_percentage: SUBNET_MAJORITY_PERCENTAGE,
+            active_validators_limit: params.active_validators_limit,
+            power_scale: 3,
+            permission_mode: params.permission_mode as u8,
+            supply_source: register_subnet_facet::SupplySource::try_from(params.supply_source)?,
+        })
+    }
 
-            let mut headers = reqwest::header::HeaderMap::new();
-            headers.insert(reqwest::header::AUTHORIZATION, auth_value);
+    /// The machine learning actor of the subnet, called at its masked ID address.
+    fn machine_learning(&self) -> IMachineLearning<Provider<LimitedHttp>> {
+        let address = ipc_types::EthAddress::from_id(MACHINELEARNING_ACTOR_ID);
+        IMachineLearning::new(
+            ethers::types::Address::from(address.0),
+            Arc::new(self.ipc_contract_info.provider.clone()),
+        )
+    }
 
-            client = client.default_headers(headers);
+    /// The average time between the last [BLOCK_TIME_SAMPLE] blocks up to `head`, produced at
+    /// `timestamp`.
+    async fn recent_block_time(&self, head: u64, timestamp: u64) -> Result<Option<Duration>> {
+        let sample_height = head.saturating_sub(BLOCK_TIME_SAMPLE);
+        if sample_height == head {
+            return Ok(None);
         }
+        let sample = self
+            .ipc_contract_info
+            .provider
+            .get_block(sample_height)
+            .await?
+            .ok_or_else(|| anyhow!("block {sample_height} not found"))?;
+        Ok(estimate_block_time(
+            (sample_height, sample.timestamp.as_u64()),
+            (head, timestamp),
+        ))
+    }
 
-        if let Some(timeout) = subnet.rpc_timeout() {
-            client = client.timeout(timeout);
-        }
+    pub fn from_subnet_with_wallet_store(
+        subnet: &Subnet,
+        keystore: Option<Arc<RwLock<EvmKeyStoreBackend>>>,
+    ) -> Result<Self> {
+        let permits = subnet
+            .max_concurrent_requests()
+            .map(|n| Arc::new(Semaphore::new(n)));
+        Self::from_subnet_with_request_permits(subnet, keystore, permits)
+    }
+
+    /// Like [Self::from_subnet_with_wallet_store], but holding one of the `permits` while each
+    /// request is in flight, so managers can share a limit on the requests to the subnet.
+    pub fn from_subnet_with_request_permits(
+        subnet: &Subnet,
+        keystore: Option<Arc<RwLock<EvmKeyStoreBackend>>>,
+        permits: Option<Arc<Semaphore>>,
+    ) -> Result<Self> {
+        let client = http_client(subnet)?;
+        Self::from_endpoint(subnet, subnet.rpc_http().clone(), client, keystore, permits)
+    }
 
-        let client = client.build()?;
+    /// Like [Self::from_subnet_with_request_permits], but sending the requests to `url`, one of
+    /// the endpoints of the subnet, with an existing `client`, so its open connections are
+    /// reused.
+    pub fn from_endpoint(
+        subnet: &Subnet,
+        url: Url,
+        client: Client,
+        keystore: Option<Arc<RwLock<EvmKeyStoreBackend>>>,
+        permits: Option<Arc<Semaphore>>,
+    ) -> Result<Self> {
+        let SubnetConfig::Fevm(config) = &subnet.config;
 
-        let provider = Http::new_with_client(url, client);
+        let provider = LimitedHttp::new(Http::new_with_client(url, client), permits);
 
         let mut provider = Provider::new(provider);
         // set polling interval for provider to fit fast child subnets block times.
@@ -1091,6 +2096,7 @@ impl EthSubnetManager {
         provider.set_interval(ETH_PROVIDER_POLLING_TIME);
         let gateway_address = payload_to_evm_address(config.gateway_addr.payload())?;
         let registry_address = payload_to_evm_address(config.registry_addr.payload())?;
+        let fee_overrides = FeeOverrides::try_from(&subnet.gas_overrides()?)?;
 
         Ok(Self::new(
             gateway_address,
@@ -1098,7 +2104,22 @@ impl EthSubnetManager {
             subnet.id.chain_id(),
             provider,
             keystore,
-        ))
+        )
+        .with_fee_overrides(fee_overrides))
+    }
+}
+
+#[async_trait]
+impl TransactionQuery for EthSubnetManager {
+    async fn tx_status(&self, tx_hash: H256) -> Result<TxStatus> {
+        let provider = self.provider();
+        if let Some(receipt) = provider.get_transaction_receipt(tx_hash).await? {
+            return Ok(TxStatus::Included(TxReceipt::try_from(receipt)?));
+        }
+        Ok(match provider.get_transaction(tx_hash).await? {
+            Some(_) => TxStatus::Pending,
+            None => TxStatus::Unknown,
+        })
     }
 }
 
@@ -1112,6 +2133,7 @@ impl BottomUpCheckpointRelayer for EthSubnetManager {
         signatories: Vec<Address>,
     ) -> anyhow::Result<ChainEpoch> {
         let address = contract_address_from_subnet(&checkpoint.subnet_id)?;
+        let parent = parent_of(&checkpoint.subnet_id)?;
         tracing::debug!(
             "submit bottom up checkpoint: {checkpoint:?} in evm subnet contract: {address:}"
         );
@@ -1134,7 +2156,8 @@ impl BottomUpCheckpointRelayer for EthSubnetManager {
             signer.clone(),
         );
         let call = contract.submit_checkpoint(checkpoint, signatories, signatures);
-        let call = call_with_premium_estimation(signer, call).await?;
+        let call = call_with_premium_estimation(signer.clone(), &self.fee_overrides, call).await?;
+        ensure_sufficient_funds(&signer, &call, &parent).await?;
 
         let pending_tx = call.send().await?;
         let receipt = pending_tx.retries(TRANSACTION_RECEIPT_RETRIES).await?;
@@ -1199,6 +2222,53 @@ impl BottomUpCheckpointRelayer for EthSubnetManager {
         }))
     }
 
+    async fn bottom_up_msg_batch(&self, height: ChainEpoch) -> Result<Vec<IpcEnvelope>> {
+        let contract = gateway_getter_facet::GatewayGetterFacet::new(
+            self.ipc_contract_info.gateway_addr,
+            Arc::new(self.ipc_contract_info.provider.clone()),
+        );
+
+        let batch = contract
+            .bottom_up_msg_batch(U256::from(height))
+            .call()
+            .await?;
+        batch
+            .msgs
+            .into_iter()
+            .map(IpcEnvelope::try_from)
+            .collect::<Result<Vec<_>>>()
+    }
+
+    async fn membership_at(
+        &self,
+        height: ChainEpoch,
+    ) -> Result<(ConfigurationNumber, Vec<Validator>)> {
+        let block = BlockId::Number(ethers::types::BlockNumber::Number((height as u64).into()));
+        let contract = gateway_getter_facet::GatewayGetterFacet::new(
+            self.ipc_contract_info.gateway_addr,
+            Arc::new(self.ipc_contract_info.provider.clone()),
+        );
+
+        let membership = contract
+            .get_current_membership()
+            .block(block)
+            .call()
+            .await
+            .with_context(|| format!("cannot get the membership at height {height}"))?;
+        let validators = membership
+            .validators
+            .into_iter()
+            .map(|validator| {
+                Ok(Validator {
+                    addr: ethers_address_to_fil_address(&validator.addr)?,
+                    weight: eth_to_fil_amount(&validator.weight)?,
+                    metadata: validator.metadata.to_vec(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok((membership.configuration_number, validators))
+    }
+
     async fn quorum_reached_events(&self, height: ChainEpoch) -> Result<Vec<QuorumReachedEvent>> {
         let contract = checkpointing_facet::CheckpointingFacet::new(
             self.ipc_contract_info.gateway_addr,
@@ -1232,20 +2302,168 @@ impl BottomUpCheckpointRelayer for EthSubnetManager {
             .as_u64();
         Ok(epoch as ChainEpoch)
     }
+
+    async fn block_time(&self) -> Result<Option<Duration>> {
+        let latest = self
+            .ipc_contract_info
+            .provider
+            .get_block(ethers::types::BlockNumber::Latest)
+            .await?
+            .ok_or_else(|| anyhow!("latest block not found"))?;
+        let head = latest
+            .number
+            .ok_or_else(|| anyhow!("latest block has no number"))?
+            .as_u64();
+        self.recent_block_time(head, latest.timestamp.as_u64())
+            .await
+    }
 }
 
 /// Receives an input `FunctionCall` and returns a new instance
 /// after estimating an optimal `gas_premium` for the transaction
 pub(crate) async fn call_with_premium_estimation<B, D, M>(
     signer: Arc<DefaultSignerMiddleware>,
+    overrides: &FeeOverrides,
     call: ethers_contract::FunctionCall<B, D, M>,
 ) -> Result<ethers_contract::FunctionCall<B, D, M>>
 where
     B: std::borrow::Borrow<D>,
     M: ethers::abi::Detokenize,
 {
-    let (max_priority_fee_per_gas, _) = premium_estimation(signer).await?;
-    Ok(call.gas_price(max_priority_fee_per_gas))
+    let (max_priority_fee_per_gas, max_fee_per_gas) = premium_estimation(signer, overrides).await?;
+    let mut call = call.gas_price(max_priority_fee_per_gas);
+    // The gas price sets both fees, so a fee floor has to be restored on its own.
+    if overrides.fee_cap.is_some() {
+        if let TypedTransaction::Eip1559(tx) = &mut call.tx {
+            tx.max_fee_per_gas = Some(max_fee_per_gas);
+        }
+    }
+    Ok(call)
+}
+
+/// Checks that the sender of `call` can pay for its value and the gas it is estimated to use,
+/// so that it fails before broadcasting with the amount missing, instead of on-chain.
+///
+/// `network` is the subnet the transaction is sent to, and is only used in the error.
+pub(crate) async fn ensure_sufficient_funds<B, D, M>(
+    signer: &Arc<DefaultSignerMiddleware>,
+    call: &ethers_contract::FunctionCall<B, D, M>,
+    network: &SubnetID,
+) -> Result<()>
+where
+    B: std::borrow::Borrow<D>,
+    D: Middleware + 'static,
+    M: ethers::abi::Detokenize,
+{
+    let sender = signer.address();
+    let balance = signer.get_balance(sender, None).await?;
+    let value = call.tx.value().cloned().unwrap_or_default();
+
+    // The gas can't be estimated if the value alone can't be paid for.
+    if let Some(missing) = funds_shortfall(balance, value, U256::zero()) {
+        return Err(insufficient_funds(
+            sender, network, balance, value, None, missing,
+        ));
+    }
+
+    let gas = match call.tx.gas() {
+        Some(gas) => *gas,
+        None => call.estimate_gas().await?,
+    };
+    let gas_price = match call.tx.gas_price() {
+        Some(price) => price,
+        None => signer.get_gas_price().await?,
+    };
+    let fee = gas.saturating_mul(gas_price);
+
+    if let Some(missing) = funds_shortfall(balance, value, fee) {
+        return Err(insufficient_funds(
+            sender,
+            network,
+            balance,
+            value,
+            Some(fee),
+            missing,
+        ));
+    }
+    Ok(())
+}
+
+/// The amount `balance` is short of to pay for `value` and `fee`, if any.
+fn funds_shortfall(balance: U256, value: U256, fee: U256) -> Option<U256> {
+    let required = value.saturating_add(fee);
+    (balance < required).then(|| required - balance)
+}
+
+fn insufficient_funds(
+    sender: ethers::types::Address,
+    network: &SubnetID,
+    balance: U256,
+    value: U256,
+    fee: Option<U256>,
+    missing: U256,
+) -> anyhow::Error {
+    let fmt = |amount: U256| {
+        eth_to_fil_amount(&amount)
+            .map(|a| a.to_string())
+            .unwrap_or_else(|_| format!("{amount} atto"))
+    };
+    let fee = match fee {
+        Some(fee) => format!("an estimated {} in gas", fmt(fee)),
+        None => "gas".to_string(),
+    };
+    anyhow!(
+        "insufficient funds: need {} more FIL/ETH on subnet {network} ({sender:?} has {}, the transaction needs {} in value plus {fee})",
+        fmt(missing),
+        fmt(balance),
+        fmt(value),
+    )
+}
+
+/// Convert the arguments of `setFederatedPower` to their contract types.
+fn federated_power_params(
+    validators: &[Address],
+    public_keys: &[Vec<u8>],
+    federated_power: &[u128],
+) -> Result<(
+    Vec<ethers::types::Address>,
+    Vec<ethers::types::Bytes>,
+    Vec<U256>,
+)> {
+    let addresses = validators
+        .iter()
+        .map(|validator_address| payload_to_evm_address(validator_address.payload()))
+        .collect::<Result<Vec<_>>>()?;
+    tracing::debug!("converted addresses: {:?}", addresses);
+
+    let pubkeys = public_keys
+        .iter()
+        .map(|key| ethers::types::Bytes::from(key.clone()))
+        .collect::<Vec<_>>();
+    tracing::debug!("converted pubkeys: {:?}", pubkeys);
+
+    let power = federated_power
+        .iter()
+        .map(|power| U256::from(*power))
+        .collect::<Vec<_>>();
+    tracing::debug!("converted power: {:?}", power);
+
+    Ok((addresses, pubkeys, power))
+}
+
+/// The average time between the blocks at two heights, given as `(height, timestamp)`, with
+/// timestamps in seconds; `None` if they don't tell.
+fn estimate_block_time(from: (u64, u64), to: (u64, u64)) -> Option<Duration> {
+    let blocks = to.0.checked_sub(from.0).filter(|n| *n > 0)?;
+    let secs = to.1.checked_sub(from.1).filter(|s| *s > 0)?;
+    Some(Duration::from_millis(secs * 1000 / blocks))
+}
+
+/// The subnet in which the transactions targeting the contracts of `subnet` are executed.
+fn parent_of(subnet: &SubnetID) -> Result<SubnetID> {
+    subnet
+        .parent()
+        .ok_or_else(|| anyhow!("subnet {subnet} has no parent"))
 }
 
 /// Returns an estimation of an optimal `gas_premium` and `gas_fee_cap`
@@ -1253,9 +2471,20 @@ where
 /// past blocks
 /// This is adaptation of ethers' `eip1559_default_estimator`:
 /// https://github.com/gakonst/ethers-rs/blob/5dcd3b7e754174448f9a8cbfc0523896609629f9/ethers-core/src/utils/mod.rs#L476
-async fn premium_estimation(
-    signer: Arc<DefaultSignerMiddleware>,
-) -> Result<(ethers::types::U256, ethers::types::U256)> {
+///
+/// The fees set in `overrides` are used as they are, and not estimated.
+async fn premium_estimation<M>(
+    signer: Arc<M>,
+    overrides: &FeeOverrides,
+) -> Result<(ethers::types::U256, ethers::types::U256)>
+where
+    M: Middleware,
+    M::Error: 'static,
+{
+    if let (Some(tip), Some(fee_cap)) = (overrides.tip, overrides.fee_cap) {
+        return Ok((tip.min(fee_cap), fee_cap));
+    }
+
     let base_fee_per_gas = signer
         .get_block(ethers::types::BlockNumber::Latest)
         .await?
@@ -1263,23 +2492,45 @@ async fn premium_estimation(
         .base_fee_per_gas
         .ok_or_else(|| anyhow!("EIP-1559 not activated"))?;
 
-    let fee_history = signer
-        .fee_history(
-            ethers::utils::EIP1559_FEE_ESTIMATION_PAST_BLOCKS,
-            ethers::types::BlockNumber::Latest,
-            &[ethers::utils::EIP1559_FEE_ESTIMATION_REWARD_PERCENTILE],
-        )
-        .await?;
-
-    let max_priority_fee_per_gas = estimate_priority_fee(fee_history.reward); //overestimate?
-    let potential_max_fee = base_fee_surged(base_fee_per_gas);
-    let max_fee_per_gas = if max_priority_fee_per_gas > potential_max_fee {
-        max_priority_fee_per_gas + potential_max_fee
-    } else {
-        potential_max_fee
+    let estimated_tip = match overrides.tip {
+        Some(tip) => tip,
+        None => {
+            let fee_history = signer
+                .fee_history(
+                    ethers::utils::EIP1559_FEE_ESTIMATION_PAST_BLOCKS,
+                    ethers::types::BlockNumber::Latest,
+                    &[ethers::utils::EIP1559_FEE_ESTIMATION_REWARD_PERCENTILE],
+                )
+                .await?;
+            estimate_priority_fee(fee_history.reward) //overestimate?
+        }
     };
 
-    Ok((max_priority_fee_per_gas, max_fee_per_gas))
+    Ok(eip1559_fees(base_fee_per_gas, estimated_tip, overrides))
+}
+
+/// The `(max_priority_fee_per_gas, max_fee_per_gas)` of a transaction given the base fee and
+/// the estimated priority fee, unless `overrides` sets them. The priority fee never exceeds the
+/// max fee, which the node would reject.
+fn eip1559_fees(
+    base_fee_per_gas: U256,
+    estimated_tip: U256,
+    overrides: &FeeOverrides,
+) -> (U256, U256) {
+    let max_priority_fee_per_gas = overrides.tip.unwrap_or(estimated_tip);
+    let max_fee_per_gas = overrides.fee_cap.unwrap_or_else(|| {
+        let potential_max_fee = base_fee_surged(base_fee_per_gas);
+        if max_priority_fee_per_gas > potential_max_fee {
+            max_priority_fee_per_gas + potential_max_fee
+        } else {
+            potential_max_fee
+        }
+    });
+
+    (
+        max_priority_fee_per_gas.min(max_fee_per_gas),
+        max_fee_per_gas,
+    )
 }
 
 /// Implementation borrowed from
@@ -1353,6 +2604,15 @@ fn estimate_priority_fee(rewards: Vec<Vec<U256>>) -> U256 {
 }
 
 /// Get the block number from the transaction receipt
+fn tx_receipt(receipt: Option<ethers::types::TransactionReceipt>) -> Result<TxReceipt> {
+    match receipt {
+        Some(r) => TxReceipt::try_from(r),
+        None => Err(anyhow!(
+            "txn sent to network, but receipt cannot be obtained, please check scanner"
+        )),
+    }
+}
+
 fn block_number_from_receipt(
     receipt: Option<ethers::types::TransactionReceipt>,
 ) -> Result<ChainEpoch> {
@@ -1431,6 +2691,28 @@ pub(crate) fn fil_amount_to_eth_amount(amount: &TokenAmount) -> Result<ethers::t
     Ok(v)
 }
 
+/// The HTTP client for the endpoints of a subnet, with its auth token and timeout.
+pub fn http_client(subnet: &Subnet) -> Result<Client> {
+    let mut client = Client::builder();
+
+    if let Some(auth_token) = subnet.auth_token() {
+        let auth = Authorization::Bearer(auth_token);
+        let mut auth_value = HeaderValue::from_str(&auth.to_string())?;
+        auth_value.set_sensitive(true);
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(reqwest::header::AUTHORIZATION, auth_value);
+
+        client = client.default_headers(headers);
+    }
+
+    if let Some(timeout) = subnet.rpc_timeout() {
+        client = client.timeout(timeout);
+    }
+
+    Ok(client.build()?)
+}
+
 /// Convert the ipc SubnetID type to an evm address. It extracts the last address from the Subnet id
 /// children and turns it into evm address.
 pub(crate) fn contract_address_from_subnet(subnet: &SubnetID) -> Result<ethers::types::Address> {
@@ -1455,12 +2737,43 @@ impl TryFrom<gateway_getter_facet::Subnet> for SubnetInfo {
     }
 }
 
+/// Pair up the keys and the messages of the postbox, as returned by the gateway getter.
+fn postbox_msgs(
+    keys: Vec<[u8; 32]>,
+    msgs: Vec<gateway_getter_facet::IpcEnvelope>,
+) -> Result<Vec<PostboxMsg>> {
+    if keys.len() != msgs.len() {
+        return Err(anyhow!(
+            "postbox returned {} keys for {} messages",
+            keys.len(),
+            msgs.len()
+        ));
+    }
+    keys.into_iter()
+        .zip(msgs)
+        .map(|(key, msg)| {
+            Ok(PostboxMsg {
+                key: key.to_vec(),
+                msg: IpcEnvelope::try_from(msg)?,
+            })
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::manager::evm::manager::contract_address_from_subnet;
+    use crate::manager::evm::manager::{
+        contract_address_from_subnet, eip1559_fees, estimate_block_time, funds_shortfall,
+        postbox_msgs, FeeOverrides,
+    };
+    use ethers::types::U256;
     use fvm_shared::address::Address;
+    use fvm_shared::econ::TokenAmount;
+    use ipc_actors_abis::gateway_getter_facet;
+    use ipc_api::cross::IpcEnvelope;
     use ipc_api::subnet_id::SubnetID;
     use std::str::FromStr;
+    use std::time::Duration;
 
     #[test]
     fn test_agent_subnet_to_evm_address() {
@@ -1473,4 +2786,88 @@ mod tests {
             "0x2e714a3c385ea88a09998ed74db265dae9853667"
         );
     }
+
+    #[test]
+    fn test_funds_shortfall() {
+        let balance = U256::from(100);
+        assert_eq!(
+            funds_shortfall(balance, U256::from(60), U256::from(40)),
+            None
+        );
+        assert_eq!(
+            funds_shortfall(balance, U256::from(60), U256::from(50)),
+            Some(U256::from(10))
+        );
+        assert_eq!(
+            funds_shortfall(balance, U256::MAX, U256::from(1)),
+            Some(U256::MAX - balance)
+        );
+    }
+
+    #[test]
+    fn test_postbox_msgs() {
+        // Only delegated addresses can be represented in the contracts.
+        let eth_addr = |n: u8| Address::new_delegated(10, &[n; 20]).unwrap();
+        let subnet = SubnetID::new(123, vec![eth_addr(1)]);
+        let msg = IpcEnvelope::new_release_msg(
+            &subnet,
+            &eth_addr(2),
+            &eth_addr(3),
+            TokenAmount::from_whole(1),
+        )
+        .unwrap();
+        let evm_msg = gateway_getter_facet::IpcEnvelope::try_from(msg.clone()).unwrap();
+
+        let msgs = postbox_msgs(vec![[1; 32]], vec![evm_msg.clone()]).unwrap();
+        assert_eq!(msgs.len(), 1);
+        assert_eq!(msgs[0].key, vec![1; 32]);
+        assert_eq!(msgs[0].msg, msg);
+
+        // A message the gateway could not have stored is an error rather than skipped.
+        let mut bad_msg = evm_msg.clone();
+        bad_msg.kind = u8::MAX;
+        assert!(postbox_msgs(vec![[1; 32], [2; 32]], vec![evm_msg.clone(), bad_msg]).is_err());
+
+        assert!(postbox_msgs(vec![[1; 32], [2; 32]], vec![evm_msg]).is_err());
+    }
+
+    #[test]
+    fn test_estimate_block_time() {
+        assert_eq!(
+            estimate_block_time((100, 1_000), (120, 1_030)),
+            Some(Duration::from_millis(1500))
+        );
+        assert_eq!(estimate_block_time((120, 1_030), (120, 1_030)), None);
+        // Blocks produced within the same second.
+        assert_eq!(estimate_block_time((100, 1_000), (101, 1_000)), None);
+    }
+
+    #[test]
+    fn test_eip1559_fees() {
+        let base_fee = U256::from(100);
+        let tip = U256::from(10);
+        let overrides = |tip: Option<u64>, fee_cap: Option<u64>| FeeOverrides {
+            tip: tip.map(U256::from),
+            fee_cap: fee_cap.map(U256::from),
+        };
+
+        // The base fee is doubled at low fees.
+        assert_eq!(
+            eip1559_fees(base_fee, tip, &FeeOverrides::default()),
+            (tip, U256::from(200))
+        );
+        assert_eq!(
+            eip1559_fees(base_fee, tip, &overrides(Some(50), None)),
+            (U256::from(50), U256::from(200))
+        );
+        assert_eq!(
+            eip1559_fees(base_fee, tip, &overrides(None, Some(1000))),
+            (tip, U256::from(1000))
+        );
+        // The tip can't exceed the fee cap.
+        assert_eq!(
+            eip1559_fees(base_fee, tip, &overrides(Some(50), Some(20))),
+            (U256::from(20), U256::from(20))
+        );
+    }
 }
@@ -1,6 +1,7 @@
 // Copyright 2022-2024 Protocol Labs
 // SPDX-License-Identifier: MIT
 
+mod limited;
 mod manager;
 
 use async_trait::async_trait;
@@ -8,7 +9,8 @@ use fvm_shared::clock::ChainEpoch;
 use ipc_api::subnet_id::SubnetID;
 
 use super::subnet::SubnetManager;
-pub use manager::EthSubnetManager;
+pub use limited::LimitedHttp;
+pub use manager::{http_client, EthSubnetManager};
 
 use ipc_actors_abis::subnet_actor_checkpointing_facet;
 
@@ -3,8 +3,9 @@
 pub use crate::lotus::message::ipc::SubnetInfo;
 pub use evm::{EthManager, EthSubnetManager};
 pub use subnet::{
-    BottomUpCheckpointRelayer, GetBlockHashResult, SubnetGenesisInfo, SubnetManager,
-    TopDownFinalityQuery, TopDownQueryPayload,
+    BottomUpCheckpointRelayer, ChainParams, GetBlockHashResult, ParamChangeProposal, PostboxMsg,
+    SubnetGenesisInfo, SubnetLifecycleInfo, SubnetManager, TopDownFinalityQuery,
+    TopDownQueryPayload, TransactionQuery, TxStatus,
 };
 
 pub mod evm;
@@ -2,25 +2,45 @@
 // SPDX-License-Identifier: MIT
 
 use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
 
 use anyhow::Result;
 use async_trait::async_trait;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::H256;
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::{address::Address, econ::TokenAmount};
+use ipc_api::address::IPCAddress;
 use ipc_api::checkpoint::{
     BottomUpCheckpoint, BottomUpCheckpointBundle, QuorumReachedEvent, Signature,
 };
-use ipc_api::cross::IpcEnvelope;
-use ipc_api::staking::{StakingChangeRequest, ValidatorInfo};
-use ipc_api::subnet::{ConstructParams, PermissionMode, SupplySource};
+use ipc_api::cross::{IpcEnvelope, TopDownReceipt};
+use ipc_api::evidence::DoubleSignEvidence;
+use ipc_api::model::{MlAlgorithm, ModelHash};
+use ipc_api::receipt::TxReceipt;
+use ipc_api::staking::{ConfigurationNumber, StakingChangeRequest, ValidatorInfo};
+use ipc_api::subnet::{ConstructParams, PermissionMode, SubnetParam, SupplySource};
 use ipc_api::subnet_id::SubnetID;
+use ipc_api::token::Denomination;
 use ipc_api::validator::Validator;
+use ipc_api::HumanReadable;
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DurationMilliSeconds};
 
+use crate::deploy::DeployedContract;
 use crate::lotus::message::ipc::SubnetInfo;
+use crate::lotus::message::serialize::{
+    serialize_some_token_amount_to_atto, serialize_token_amount_to_atto,
+};
+use crate::multisig::{AdminCall, AdminOperation};
+use crate::state_diff::{ActorState, StateActor};
 
 /// Trait to interact with a subnet and handle its lifecycle.
 #[async_trait]
-pub trait SubnetManager: Send + Sync + TopDownFinalityQuery + BottomUpCheckpointRelayer {
+pub trait SubnetManager:
+    Send + Sync + TopDownFinalityQuery + BottomUpCheckpointRelayer + TransactionQuery
+{
     /// Deploys a new subnet actor on the `parent` subnet and with the
     /// configuration passed in `ConstructParams`.
     /// The result of the function is the ID address for the subnet actor from which the final
@@ -37,30 +57,80 @@ pub trait SubnetManager: Send + Sync + TopDownFinalityQuery + BottomUpCheckpoint
         from: Address,
         collateral: TokenAmount,
         metadata: Vec<u8>,
-    ) -> Result<ChainEpoch>;
+    ) -> Result<TxReceipt>;
 
     /// Adds some initial balance to an address before a child subnet bootstraps to make
     /// it available in the subnet at genesis.
-    async fn pre_fund(&self, subnet: SubnetID, from: Address, balance: TokenAmount) -> Result<()>;
+    async fn pre_fund(
+        &self,
+        subnet: SubnetID,
+        from: Address,
+        balance: TokenAmount,
+    ) -> Result<TxReceipt>;
 
     /// Releases initial funds from an address for a subnet that has not yet been bootstrapped
-    async fn pre_release(&self, subnet: SubnetID, from: Address, amount: TokenAmount)
-        -> Result<()>;
+    async fn pre_release(
+        &self,
+        subnet: SubnetID,
+        from: Address,
+        amount: TokenAmount,
+    ) -> Result<TxReceipt>;
 
     /// Allows validators that have already joined the subnet to stake more collateral
     /// and increase their power in the subnet.
-    async fn stake(&self, subnet: SubnetID, from: Address, collateral: TokenAmount) -> Result<()>;
+    async fn stake(
+        &self,
+        subnet: SubnetID,
+        from: Address,
+        collateral: TokenAmount,
+    ) -> Result<TxReceipt>;
 
     /// Allows validators that have already joined the subnet to unstake collateral
     /// and reduce their power in the subnet.
-    async fn unstake(&self, subnet: SubnetID, from: Address, collateral: TokenAmount)
-        -> Result<()>;
+    async fn unstake(
+        &self,
+        subnet: SubnetID,
+        from: Address,
+        collateral: TokenAmount,
+    ) -> Result<TxReceipt>;
 
     /// Sends a request to leave a subnet from a wallet address.
-    async fn leave_subnet(&self, subnet: SubnetID, from: Address) -> Result<()>;
+    async fn leave_subnet(&self, subnet: SubnetID, from: Address) -> Result<TxReceipt>;
 
     /// Sends a signal to kill a subnet
-    async fn kill_subnet(&self, subnet: SubnetID, from: Address) -> Result<()>;
+    async fn kill_subnet(&self, subnet: SubnetID, from: Address) -> Result<TxReceipt>;
+
+    /// Reports a validator who signed conflicting votes in the subnet to its subnet actor.
+    async fn report_double_sign(
+        &self,
+        subnet: SubnetID,
+        from: Address,
+        evidence: DoubleSignEvidence,
+    ) -> Result<TxReceipt>;
+
+    /// Proposes to change a parameter of a subnet, as one of its active validators. Returns the
+    /// ID of the proposal, which the proposer has already voted for.
+    async fn propose_param_change(
+        &self,
+        subnet: SubnetID,
+        from: Address,
+        param: SubnetParam,
+        value: u64,
+    ) -> Result<(u64, TxReceipt)>;
+
+    /// Votes for a parameter change proposal of a subnet, as one of its active validators.
+    async fn vote_param_change(
+        &self,
+        subnet: SubnetID,
+        from: Address,
+        id: u64,
+    ) -> Result<TxReceipt>;
+
+    /// Lists the parameter change proposals of a subnet, by ID.
+    async fn list_param_change_proposals(
+        &self,
+        subnet: &SubnetID,
+    ) -> Result<Vec<ParamChangeProposal>>;
 
     /// Lists all the registered children in a gateway.
     async fn list_child_subnets(
@@ -70,7 +140,7 @@ pub trait SubnetManager: Send + Sync + TopDownFinalityQuery + BottomUpCheckpoint
 
     /// Claims any collateral that may be available to claim by validators that
     /// have left the subnet.
-    async fn claim_collateral(&self, subnet: SubnetID, from: Address) -> Result<()>;
+    async fn claim_collateral(&self, subnet: SubnetID, from: Address) -> Result<TxReceipt>;
 
     /// Fund injects new funds from an account of the parent chain to a subnet.
     /// Returns the epoch that the fund is executed in the parent.
@@ -81,7 +151,7 @@ pub trait SubnetManager: Send + Sync + TopDownFinalityQuery + BottomUpCheckpoint
         from: Address,
         to: Address,
         amount: TokenAmount,
-    ) -> Result<ChainEpoch>;
+    ) -> Result<TxReceipt>;
 
     /// Sends funds to a specified subnet receiver using ERC20 tokens.
     /// This function locks the amount of ERC20 tokens into custody and then mints the supply in the specified subnet.
@@ -100,7 +170,7 @@ pub trait SubnetManager: Send + Sync + TopDownFinalityQuery + BottomUpCheckpoint
         from: Address,
         to: Address,
         amount: TokenAmount,
-    ) -> Result<ChainEpoch>;
+    ) -> Result<TxReceipt>;
 
     /// Grants an allowance to the `from` address to withdraw up to `amount` of tokens from the contract at `token_address`.
     /// This function sets up an approval, allowing the `from` address to later transfer or utilize the tokens from the specified ERC20 token contract.
@@ -125,7 +195,7 @@ pub trait SubnetManager: Send + Sync + TopDownFinalityQuery + BottomUpCheckpoint
         subnet: SubnetID,
         from: Address,
         amount: TokenAmount,
-    ) -> Result<ChainEpoch>;
+    ) -> Result<TxReceipt>;
 
     /// Release creates a new check message to release funds in parent chain
     /// Returns the epoch that the released is executed in the child.
@@ -135,7 +205,7 @@ pub trait SubnetManager: Send + Sync + TopDownFinalityQuery + BottomUpCheckpoint
         from: Address,
         to: Address,
         amount: TokenAmount,
-    ) -> Result<ChainEpoch>;
+    ) -> Result<TxReceipt>;
 
     /// Propagate a cross-net message forward. For `postbox_msg_key`, we are using bytes because different
     /// runtime have different representations. For FVM, it should be `CID` as bytes. For EVM, it is
@@ -146,10 +216,112 @@ pub trait SubnetManager: Send + Sync + TopDownFinalityQuery + BottomUpCheckpoint
         gateway_addr: Address,
         from: Address,
         postbox_msg_key: Vec<u8>,
-    ) -> Result<()>;
+    ) -> Result<TxReceipt>;
+
+    /// The messages waiting in the postbox of the gateway to be propagated further.
+    async fn list_postbox(&self) -> Result<Vec<PostboxMsg>>;
 
     /// Send value between two addresses in a subnet
-    async fn send_value(&self, from: Address, to: Address, amount: TokenAmount) -> Result<()>;
+    async fn send_value(
+        &self,
+        from: Address,
+        to: Address,
+        amount: TokenAmount,
+    ) -> Result<TxReceipt>;
+
+    /// Ask the faucet actor of the subnet to send test funds to an address. The faucet refuses
+    /// addresses it sent funds to within its cooldown.
+    async fn faucet_drip(&self, from: Address, to: Address) -> Result<TxReceipt>;
+
+    /// The balance of `owner` in the ERC-20 token contract at `token`, in the base units of
+    /// the token, whatever its decimals.
+    async fn erc20_balance(&self, token: Address, owner: Address) -> Result<TokenAmount>;
+
+    /// Transfer `amount` base units of the ERC-20 token contract at `token` from `from` to `to`.
+    async fn erc20_transfer(
+        &self,
+        from: Address,
+        token: Address,
+        to: Address,
+        amount: TokenAmount,
+    ) -> Result<TxReceipt>;
+
+    /// The data returned by calling the contract at `to` with `calldata`, without sending a
+    /// transaction.
+    async fn call_contract(&self, to: Address, calldata: Vec<u8>) -> Result<Vec<u8>>;
+
+    /// Send a transaction calling the contract at `to` with `calldata` and `value`.
+    async fn send_contract_tx(
+        &self,
+        from: Address,
+        to: Address,
+        calldata: Vec<u8>,
+        value: TokenAmount,
+    ) -> Result<TxReceipt>;
+
+    /// Build the transaction sending `calldata` and `value` from `from` to `to`, with its nonce,
+    /// gas and fees filled in, without signing it, so that it can be signed offline.
+    async fn build_unsigned_tx(
+        &self,
+        from: Address,
+        to: Address,
+        calldata: Vec<u8>,
+        value: TokenAmount,
+    ) -> Result<TypedTransaction>;
+
+    /// Broadcast a transaction signed offline, RLP encoded with its signature, and wait for it
+    /// to be executed.
+    async fn broadcast_signed_tx(&self, raw: Vec<u8>) -> Result<TxReceipt>;
+
+    /// Deploy a contract by running `init_code`, with `CREATE2` through the deterministic
+    /// deployment proxy if there is a `salt`, or with a plain transaction otherwise.
+    async fn deploy_contract(
+        &self,
+        from: Address,
+        init_code: Vec<u8>,
+        salt: Option<[u8; 32]>,
+    ) -> Result<DeployedContract>;
+
+    /// The owner of the token `token_id` of the ERC-721 contract at `token`.
+    async fn erc721_owner_of(
+        &self,
+        token: Address,
+        token_id: ethers::types::U256,
+    ) -> Result<Address>;
+
+    /// Send a model through the `ModelExchange` contract deployed at `exchange` to another
+    /// instance of the contract at `to`, returning the hash identifying the model.
+    async fn export_model(
+        &self,
+        from: Address,
+        exchange: Address,
+        to: IPCAddress,
+        model: Vec<u8>,
+        value: TokenAmount,
+    ) -> Result<ModelHash>;
+
+    /// Check whether the `ModelExchange` contract at `exchange` has imported a model.
+    async fn has_model(&self, exchange: Address, model_hash: ModelHash) -> Result<bool>;
+
+    /// Train a model with the machine learning actor of the subnet, without storing it,
+    /// and return the serialized model.
+    async fn ml_train(
+        &self,
+        from: Address,
+        algorithm: MlAlgorithm,
+        input_matrix: Vec<Vec<i64>>,
+        labels: Vec<i64>,
+    ) -> Result<Vec<u8>>;
+
+    /// Predict the labels of the rows of the input matrix with a model returned by
+    /// [SubnetManager::ml_train].
+    async fn ml_predict(
+        &self,
+        from: Address,
+        algorithm: MlAlgorithm,
+        input_matrix: Vec<Vec<i64>>,
+        model: Vec<u8>,
+    ) -> Result<Vec<i64>>;
 
     /// Get the balance of an address
     async fn wallet_balance(&self, address: &Address) -> Result<TokenAmount>;
@@ -159,18 +331,49 @@ pub trait SubnetManager: Send + Sync + TopDownFinalityQuery + BottomUpCheckpoint
     /// networks is a `U256` that wouldn't fit in an integer type.
     async fn get_chain_id(&self) -> Result<String>;
 
+    /// Get the parameters of the network which submissions are timed and priced by.
+    async fn chain_params(&self) -> Result<ChainParams>;
+
+    /// Get the result of executing the top-down message with the given nonce in this subnet,
+    /// if it has been executed.
+    async fn top_down_receipt(&self, nonce: u64) -> Result<Option<TopDownReceipt>>;
+
     /// Get commit sha for deployed contracts
     async fn get_commit_sha(&self) -> Result<[u8; 32]>;
 
+    /// Get the circulating supply of the subnet: its genesis supply, plus what was funded from
+    /// the parent, minus what was released to it.
+    async fn circ_supply(&self) -> Result<TokenAmount>;
+
+    /// Get the version of the application the subnet runs, which its upgrades bump.
+    async fn app_version(&self) -> Result<u64>;
+
+    /// Get the size of the bytecode deployed at an address, zero if there is no contract.
+    async fn contract_code_size(&self, address: &Address) -> Result<usize>;
+
+    /// Get the nonce of an account, counting its pending transactions; for a contract, the
+    /// number of contracts it created, plus one.
+    async fn account_nonce(&self, address: &Address) -> Result<u64>;
+
+    /// Read the state of one of the IPC actors of the subnet at `epoch`.
+    async fn actor_state(&self, actor: &StateActor, epoch: ChainEpoch) -> Result<ActorState>;
+
     /// Gets the subnet supply source
     async fn get_subnet_supply_source(
         &self,
         subnet: &SubnetID,
     ) -> Result<ipc_actors_abis::subnet_actor_getter_facet::SupplySource>;
 
+    /// Gets the symbol and decimals of the ERC20 token supplying the subnet, or `None` if the
+    /// subnet is supplied with the native token of its parent.
+    async fn supply_source_token(&self, subnet: &SubnetID) -> Result<Option<Denomination>>;
+
     /// Gets the genesis information required to bootstrap a child subnet
     async fn get_genesis_info(&self, subnet: &SubnetID) -> Result<SubnetGenesisInfo>;
 
+    /// Gets the lifecycle information of a subnet recorded in its subnet actor.
+    async fn get_lifecycle_info(&self, subnet: &SubnetID) -> Result<SubnetLifecycleInfo>;
+
     /// Advertises the endpoint of a bootstrap node for the subnet.
     async fn add_bootstrap(
         &self,
@@ -196,31 +399,154 @@ pub trait SubnetManager: Send + Sync + TopDownFinalityQuery + BottomUpCheckpoint
         validators: &[Address],
         public_keys: &[Vec<u8>],
         federated_power: &[u128],
-    ) -> Result<ChainEpoch>;
+    ) -> Result<TxReceipt>;
+
+    /// Encode an administrative operation as the call that performs it, so that it can be
+    /// proposed to a multisig instead of being sent by a single key.
+    async fn admin_call(&self, op: &AdminOperation) -> Result<AdminCall>;
 }
 
-#[derive(Debug)]
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SubnetGenesisInfo {
     pub bottom_up_checkpoint_period: u64,
     pub majority_percentage: u8,
     pub active_validators_limit: u16,
+    #[serde_as(as = "HumanReadable")]
     pub min_collateral: TokenAmount,
     pub genesis_epoch: ChainEpoch,
     pub validators: Vec<Validator>,
+    /// A list of pairs rather than a map, as DAG-CBOR only has string keys.
+    #[serde_as(as = "Vec<(HumanReadable, HumanReadable)>")]
     pub genesis_balances: BTreeMap<Address, TokenAmount>,
     pub permission_mode: PermissionMode,
     pub supply_source: SupplySource,
 }
 
+/// The parameters of a network which transactions are timed and priced by.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChainParams {
+    /// The chain ID, as a string like [SubnetManager::get_chain_id].
+    pub chain_id: String,
+    /// The chain head the parameters were gathered at.
+    pub chain_head: ChainEpoch,
+    /// The average time between the recent blocks, if there are enough of them.
+    #[serde_as(as = "Option<DurationMilliSeconds<u64>>")]
+    #[serde(rename = "block_time_ms")]
+    pub block_time: Option<Duration>,
+    /// The lowest gas price transactions can pay to be included in the next block.
+    #[serde(serialize_with = "serialize_token_amount_to_atto")]
+    pub gas_price_floor: TokenAmount,
+    /// The base fee of the chain head, if the network has one.
+    #[serde(serialize_with = "serialize_some_token_amount_to_atto")]
+    pub base_fee: Option<TokenAmount>,
+}
+
+impl Display for ChainParams {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "chain id: {}", self.chain_id)?;
+        writeln!(f, "chain head: {}", self.chain_head)?;
+        match self.block_time {
+            Some(t) => writeln!(f, "block time: {t:?}")?,
+            None => writeln!(f, "block time: unknown")?,
+        }
+        writeln!(
+            f,
+            "gas price floor: {} attoFIL",
+            self.gas_price_floor.atto()
+        )?;
+        match &self.base_fee {
+            Some(fee) => write!(f, "base fee: {} attoFIL", fee.atto()),
+            None => write!(f, "base fee: none"),
+        }
+    }
+}
+
+/// A cross-net message waiting in the postbox of a gateway, to be propagated with
+/// [SubnetManager::propagate].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PostboxMsg {
+    /// The key of the message in the postbox, to propagate it with.
+    #[serde(serialize_with = "serialize_postbox_key")]
+    pub key: Vec<u8>,
+    #[serde(flatten)]
+    pub msg: IpcEnvelope,
+}
+
+fn serialize_postbox_key<S: serde::Serializer>(key: &[u8], s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(&format!("0x{}", hex::encode(key)))
+}
+
+impl Display for PostboxMsg {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "key: 0x{}, kind: {}, from: {}, to: {}, value: {} FIL, nonce: {}",
+            hex::encode(&self.key),
+            self.msg.kind,
+            self.msg.from,
+            self.msg.to,
+            self.msg.value,
+            self.msg.nonce
+        )
+    }
+}
+
+/// A proposal to change a parameter of a subnet, voted on by its active validators.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ParamChangeProposal {
+    pub id: u64,
+    pub param: SubnetParam,
+    pub value: u64,
+    pub proposer: ethers::types::Address,
+    /// The last epoch at which the proposal accepts votes.
+    pub deadline: ChainEpoch,
+    /// The power of the validators who voted for the proposal, at the time of their vote.
+    pub votes: u128,
+    /// Whether the votes reached the majority and the change was applied.
+    pub executed: bool,
+}
+
+impl Display for ParamChangeProposal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "id: {}, {} = {}, proposer: {:?}, deadline: {}, votes: {}, executed: {}",
+            self.id,
+            self.param,
+            self.value,
+            self.proposer,
+            self.deadline,
+            self.votes,
+            self.executed
+        )
+    }
+}
+
+/// What the subnet actor records about where a subnet is in its lifecycle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubnetLifecycleInfo {
+    pub bootstrapped: bool,
+    pub killed: bool,
+    /// Validators needed for the subnet to bootstrap.
+    pub min_validators: u64,
+    pub active_validators: u16,
+    /// Active and waiting validators.
+    pub total_validators: u16,
+    pub last_bottom_up_checkpoint_height: ChainEpoch,
+    pub bottom_up_checkpoint_period: ChainEpoch,
+}
+
 /// The generic payload that returns the block hash of the data returning block with the actual
 /// data payload.
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TopDownQueryPayload<T> {
     pub value: T,
     pub block_hash: Vec<u8>,
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
 pub struct GetBlockHashResult {
     pub parent_block_hash: Vec<u8>,
     pub block_hash: Vec<u8>,
@@ -274,8 +600,38 @@ pub trait BottomUpCheckpointRelayer: Send + Sync {
         &self,
         height: ChainEpoch,
     ) -> Result<Option<BottomUpCheckpointBundle>>;
+    /// The bottom-up messages batched by the gateway for the checkpoint at `height`, whether
+    /// the checkpoint is cut yet or not.
+    async fn bottom_up_msg_batch(&self, height: ChainEpoch) -> Result<Vec<IpcEnvelope>>;
+    /// The validators of the current subnet and the configuration number of their membership
+    /// at `height`, i.e. those signing the checkpoint cut at that height.
+    async fn membership_at(
+        &self,
+        height: ChainEpoch,
+    ) -> Result<(ConfigurationNumber, Vec<Validator>)>;
     /// Queries the signature quorum reached events at target height.
     async fn quorum_reached_events(&self, height: ChainEpoch) -> Result<Vec<QuorumReachedEvent>>;
     /// Get the current epoch in the current subnet
     async fn current_epoch(&self) -> Result<ChainEpoch>;
+    /// The average time between the recent blocks of the current subnet, if there are enough
+    /// of them.
+    async fn block_time(&self) -> Result<Option<Duration>>;
+}
+
+/// What the subnet knows about a transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TxStatus {
+    /// Not known to the node: it was never broadcast, or dropped from the mempool.
+    Unknown,
+    /// In the mempool, waiting to be included in a block.
+    Pending,
+    /// Included in a block, whether it succeeded or not.
+    Included(TxReceipt),
+}
+
+/// Looks up the transactions sent to a subnet, e.g. to find out what happened to the ones sent
+/// by a process which crashed before it got their receipt.
+#[async_trait]
+pub trait TransactionQuery: Send + Sync {
+    async fn tx_status(&self, tx_hash: H256) -> Result<TxStatus>;
 }
@@ -0,0 +1,455 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Administration of subnets by a multisig.
+//!
+//! When the admin of a subnet is a multisig, its administrative operations can't be sent by a
+//! single key: a signer proposes the call performing the operation to the multisig, and the
+//! other signers approve it until the threshold is reached and the call is executed.
+//!
+//! Approvers restate the operation they approve, instead of referring to the proposal by its
+//! identifier, so that nobody signs off a call they haven't checked themselves.
+
+use std::collections::BTreeSet;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+
+use anyhow::{anyhow, Context, Result};
+use ethers::contract::abigen;
+use ethers::providers::Middleware;
+use ethers::types::{Bytes, H256, U256};
+use fvm_ipld_encoding::{BytesSer, RawBytes};
+use fvm_shared::address::Address;
+use fvm_shared::econ::TokenAmount;
+use ipc_api::ethers_address_to_fil_address;
+use ipc_api::subnet::ConstructParams;
+use ipc_api::subnet_id::SubnetID;
+use ipc_wallet::Wallet;
+use serde::Serialize;
+
+use crate::audit::AuditLog;
+use crate::config::subnet::{MultisigConfig, MultisigKind};
+use crate::config::Subnet;
+use crate::evm_keystore::EvmKeyStoreBackend;
+use crate::jsonrpc::JsonRpcClientImpl;
+use crate::lotus::client::LotusJsonRPCClient;
+use crate::lotus::message::mpool::MpoolPushMessage;
+use crate::lotus::message::msig::{
+    MsigApproveReturn, MsigPendingTransaction, MsigProposeParams, MsigProposeReturn, MsigState,
+    MsigTxnIdParams, MSIG_APPROVE_METHOD, MSIG_PROPOSE_METHOD,
+};
+use crate::lotus::LotusClient;
+use crate::manager::EthSubnetManager;
+
+/// The method of the EVM actor invoking a contract, used to call contracts from a Filecoin msig.
+const EVM_INVOKE_CONTRACT_METHOD: u64 = 3844450837;
+/// `Enum.Operation.Call` of the Safe contracts, as opposed to a delegate call.
+const SAFE_OPERATION_CALL: u8 = 0;
+
+abigen!(
+    ISafe,
+    r#"[
+        function nonce() external view returns (uint256)
+        function getThreshold() external view returns (uint256)
+        function approvedHashes(address owner, bytes32 hash) external view returns (uint256)
+        function getTransactionHash(address to, uint256 value, bytes data, uint8 operation, uint256 safeTxGas, uint256 baseGas, uint256 gasPrice, address gasToken, address refundReceiver, uint256 _nonce) external view returns (bytes32)
+        function getOwners() external view returns (address[])
+        function approveHash(bytes32 hashToApprove) external
+        function execTransaction(address to, uint256 value, bytes data, uint8 operation, uint256 safeTxGas, uint256 baseGas, uint256 gasPrice, address gasToken, address refundReceiver, bytes signatures) external payable returns (bool)
+        event ApproveHash(bytes32 indexed approvedHash, address indexed owner)
+        event ExecutionSuccess(bytes32 txHash, uint256 payment)
+        event ExecutionFailure(bytes32 txHash, uint256 payment)
+    ]"#,
+);
+
+/// An administrative operation of a subnet, which the admin multisig has to approve.
+#[derive(Debug, Clone)]
+pub enum AdminOperation {
+    CreateSubnet(ConstructParams),
+    KillSubnet(SubnetID),
+    SetFederatedPower {
+        subnet: SubnetID,
+        validators: Vec<Address>,
+        public_keys: Vec<Vec<u8>>,
+        federated_power: Vec<u128>,
+    },
+}
+
+impl AdminOperation {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AdminOperation::CreateSubnet(_) => "create_subnet",
+            AdminOperation::KillSubnet(_) => "kill_subnet",
+            AdminOperation::SetFederatedPower { .. } => "set_federated_power",
+        }
+    }
+
+    /// The network in which the operation is executed.
+    pub fn network(&self) -> Result<SubnetID> {
+        match self {
+            AdminOperation::CreateSubnet(params) => Ok(params.parent.clone()),
+            AdminOperation::KillSubnet(subnet)
+            | AdminOperation::SetFederatedPower { subnet, .. } => subnet
+                .parent()
+                .ok_or_else(|| anyhow!("no parent found for {subnet}")),
+        }
+    }
+}
+
+/// The contract call performing an [AdminOperation].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdminCall {
+    pub to: ethers::types::Address,
+    pub value: U256,
+    pub calldata: Bytes,
+}
+
+/// A call proposed to a multisig, and the signers that approved it so far.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Proposal {
+    /// The Safe transaction hash, or the ID of the transaction in the msig actor.
+    pub id: String,
+    /// The contract called; unknown for Safe proposals only seen through their approvals.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub calldata: Option<String>,
+    pub approvals: Vec<String>,
+    pub threshold: u64,
+    /// Whether the approvals reached the threshold and the call was executed.
+    pub executed: bool,
+}
+
+/// A multisig administering subnets, with the keys of the local wallets to sign with.
+pub(crate) enum Multisig {
+    Safe {
+        address: ethers::types::Address,
+        manager: EthSubnetManager,
+    },
+    Msig {
+        address: Address,
+        client: LotusJsonRPCClient<JsonRpcClientImpl>,
+    },
+}
+
+impl Multisig {
+    pub fn new(
+        subnet: &Subnet,
+        config: &MultisigConfig,
+        evm_keystore: Option<Arc<RwLock<EvmKeyStoreBackend>>>,
+        fvm_wallet: Option<Arc<RwLock<Wallet>>>,
+        audit_log: Option<Arc<AuditLog>>,
+    ) -> Result<Self> {
+        match config.kind {
+            MultisigKind::Safe => Ok(Multisig::Safe {
+                address: ethers::types::Address::from_str(&config.address)
+                    .context("invalid Safe address")?,
+                manager: {
+                    let manager =
+                        EthSubnetManager::from_subnet_with_wallet_store(subnet, evm_keystore)?;
+                    match audit_log {
+                        Some(log) => manager.with_audit_log(log, subnet.id.clone()),
+                        None => manager,
+                    }
+                },
+            }),
+            MultisigKind::Msig => {
+                let wallet =
+                    fvm_wallet.ok_or_else(|| anyhow!("No fvm wallet found in provider"))?;
+                let auth_token = subnet.auth_token();
+                let client =
+                    JsonRpcClientImpl::new(subnet.rpc_http().clone(), auth_token.as_deref());
+                let client =
+                    LotusJsonRPCClient::new_with_wallet_store(client, subnet.id.clone(), wallet)
+                        .with_gas_overrides(subnet.gas_overrides()?);
+                Ok(Multisig::Msig {
+                    address: Address::from_str(&config.address).context("invalid msig address")?,
+                    client: match audit_log {
+                        Some(log) => client.with_audit_log(log),
+                        None => client,
+                    },
+                })
+            }
+        }
+    }
+
+    /// Propose a call to the multisig, approving it on behalf of `from`.
+    pub async fn propose(&self, from: &Address, call: AdminCall) -> Result<Proposal> {
+        match self {
+            Multisig::Safe { address, manager } => {
+                let safe = ISafe::new(*address, Arc::new(manager.get_signer(from)?));
+                let nonce = safe.nonce().call().await?;
+                safe_approve(&safe, call, nonce).await
+            }
+            Multisig::Msig { address, client } => {
+                let params = MsigProposeParams {
+                    to: ethers_address_to_fil_address(&call.to)?,
+                    value: TokenAmount::from_atto(call.value.as_u128()),
+                    method: EVM_INVOKE_CONTRACT_METHOD,
+                    params: RawBytes::serialize(BytesSer(&call.calldata))?,
+                };
+                let ret: MsigProposeReturn =
+                    msig_send(client, address, from, MSIG_PROPOSE_METHOD, &params).await?;
+                let mut proposal = msig_proposal(client, address, ret.txn_id, &call).await?;
+                proposal.approvals = vec![from.to_string()];
+                proposal.executed = ret.applied;
+                Ok(proposal)
+            }
+        }
+    }
+
+    /// Approve a pending proposal of `call` on behalf of `from`, executing it if the threshold
+    /// is reached.
+    pub async fn approve(&self, from: &Address, call: AdminCall) -> Result<Proposal> {
+        match self {
+            Multisig::Safe { address, manager } => {
+                let safe = ISafe::new(*address, Arc::new(manager.get_signer(from)?));
+                // Safe transactions are executed in the order of their nonces, so the
+                // proposal to approve must be the one of the current nonce.
+                let nonce = safe.nonce().call().await?;
+                safe_approve(&safe, call, nonce).await
+            }
+            Multisig::Msig { address, client } => {
+                let to = ethers_address_to_fil_address(&call.to)?;
+                let params = RawBytes::serialize(BytesSer(&call.calldata))?;
+                let txn = client
+                    .msig_get_pending(address)
+                    .await?
+                    .into_iter()
+                    .find(|txn| {
+                        txn.to == to
+                            && txn.method == EVM_INVOKE_CONTRACT_METHOD
+                            && msig_params(txn).as_ref() == Some(&params)
+                    })
+                    .ok_or_else(|| anyhow!("no pending proposal of this call in msig {address}"))?;
+
+                let mut proposal = msig_proposal(client, address, txn.id, &call).await?;
+                let params = MsigTxnIdParams {
+                    id: txn.id,
+                    proposal_hash: vec![],
+                };
+                let ret: MsigApproveReturn =
+                    msig_send(client, address, from, MSIG_APPROVE_METHOD, &params).await?;
+                proposal.approvals.push(from.to_string());
+                proposal.executed = ret.applied;
+                Ok(proposal)
+            }
+        }
+    }
+
+    /// The proposals waiting for approvals.
+    pub async fn pending(&self) -> Result<Vec<Proposal>> {
+        match self {
+            Multisig::Safe { address, manager } => {
+                let safe = ISafe::new(*address, manager.provider());
+                let threshold = safe.get_threshold().call().await?.as_u64();
+
+                let executed = safe
+                    .execution_success_filter()
+                    .from_block(0)
+                    .query()
+                    .await?
+                    .into_iter()
+                    .map(|e| e.tx_hash)
+                    .chain(
+                        safe.execution_failure_filter()
+                            .from_block(0)
+                            .query()
+                            .await?
+                            .into_iter()
+                            .map(|e| e.tx_hash),
+                    )
+                    .collect::<BTreeSet<_>>();
+
+                let mut proposals: Vec<Proposal> = vec![];
+                for approval in safe.approve_hash_filter().from_block(0).query().await? {
+                    if executed.contains(&approval.approved_hash) {
+                        continue;
+                    }
+                    let id = format!("{:?}", H256::from(approval.approved_hash));
+                    let owner = format!("{:?}", approval.owner);
+                    match proposals.iter_mut().find(|p| p.id == id) {
+                        Some(p) => p.approvals.push(owner),
+                        None => proposals.push(Proposal {
+                            id,
+                            to: None,
+                            calldata: None,
+                            approvals: vec![owner],
+                            threshold,
+                            executed: false,
+                        }),
+                    }
+                }
+                Ok(proposals)
+            }
+            Multisig::Msig { address, client } => {
+                let threshold = msig_threshold(client, address).await?;
+                Ok(client
+                    .msig_get_pending(address)
+                    .await?
+                    .into_iter()
+                    .map(|txn| Proposal {
+                        id: txn.id.to_string(),
+                        to: Some(txn.to.to_string()),
+                        calldata: msig_params(&txn)
+                            .and_then(|p| p.deserialize::<fvm_ipld_encoding::BytesDe>().ok())
+                            .map(|b| format!("0x{}", hex::encode(b.0))),
+                        approvals: txn.approved,
+                        threshold,
+                        executed: false,
+                    })
+                    .collect())
+            }
+        }
+    }
+}
+
+/// Approve the Safe transaction of `call` at `nonce` with the signer of `safe`, then execute it
+/// if enough owners approved it.
+async fn safe_approve<M: Middleware + 'static>(
+    safe: &ISafe<M>,
+    call: AdminCall,
+    nonce: U256,
+) -> Result<Proposal> {
+    let zero = ethers::types::Address::zero();
+    let hash = safe
+        .get_transaction_hash(
+            call.to,
+            call.value,
+            call.calldata.clone(),
+            SAFE_OPERATION_CALL,
+            U256::zero(),
+            U256::zero(),
+            U256::zero(),
+            zero,
+            zero,
+            nonce,
+        )
+        .call()
+        .await?;
+
+    tracing::info!("approving Safe transaction {}", H256::from(hash));
+    safe.approve_hash(hash)
+        .send()
+        .await?
+        .await?
+        .ok_or_else(|| anyhow!("approval of Safe transaction sent, but receipt not found"))?;
+
+    let threshold = safe.get_threshold().call().await?;
+    let mut approvals = vec![];
+    for owner in safe.get_owners().call().await? {
+        if !safe.approved_hashes(owner, hash).call().await?.is_zero() {
+            approvals.push(owner);
+        }
+    }
+
+    let executed = U256::from(approvals.len()) >= threshold;
+    if executed {
+        // Signatures of pre-approved hashes, sorted by owner as the Safe requires: the owner
+        // padded to 32 bytes, an empty `s` and a `v` of 1.
+        approvals.sort();
+        let mut signatures = vec![];
+        for owner in &approvals {
+            signatures.extend_from_slice(H256::from(*owner).as_bytes());
+            signatures.extend_from_slice(&[0u8; 32]);
+            signatures.push(1);
+        }
+
+        tracing::info!("executing Safe transaction {}", H256::from(hash));
+        safe.exec_transaction(
+            call.to,
+            call.value,
+            call.calldata.clone(),
+            SAFE_OPERATION_CALL,
+            U256::zero(),
+            U256::zero(),
+            U256::zero(),
+            zero,
+            zero,
+            Bytes::from(signatures),
+        )
+        .send()
+        .await?
+        .await?
+        .ok_or_else(|| anyhow!("Safe transaction executed, but receipt not found"))?;
+    }
+
+    Ok(Proposal {
+        id: format!("{:?}", H256::from(hash)),
+        to: Some(format!("{:?}", call.to)),
+        calldata: Some(call.calldata.to_string()),
+        approvals: approvals.iter().map(|a| format!("{a:?}")).collect(),
+        threshold: threshold.as_u64(),
+        executed,
+    })
+}
+
+/// Send a message to the msig actor from `from`, and return the decoded result once executed.
+async fn msig_send<P: serde::Serialize, R: Default + serde::de::DeserializeOwned>(
+    client: &LotusJsonRPCClient<JsonRpcClientImpl>,
+    msig: &Address,
+    from: &Address,
+    method: u64,
+    params: &P,
+) -> Result<R> {
+    let params = fvm_ipld_encoding::to_vec(params)?;
+    let cid = client
+        .mpool_push(MpoolPushMessage::new(*msig, *from, method, params))
+        .await?;
+    tracing::info!("sent message {cid} to msig {msig}, waiting for it to be executed");
+
+    let r = client.state_wait_msg(cid).await?;
+    if r.receipt.exit_code() != 0 {
+        return Err(anyhow!(
+            "message {cid} to msig {msig} failed with exit code {}",
+            r.receipt.exit_code()
+        ));
+    }
+    r.receipt.parse_result_into()
+}
+
+async fn msig_threshold(
+    client: &LotusJsonRPCClient<JsonRpcClientImpl>,
+    msig: &Address,
+) -> Result<u64> {
+    let head = client.chain_head().await?;
+    let tipset = head
+        .cids
+        .first()
+        .ok_or_else(|| anyhow!("chain head has no blocks"))?;
+    let state = client
+        .read_state::<MsigState>(*msig, cid::Cid::try_from(tipset)?)
+        .await?;
+    Ok(state.state.num_approvals_threshold)
+}
+
+async fn msig_proposal(
+    client: &LotusJsonRPCClient<JsonRpcClientImpl>,
+    msig: &Address,
+    id: i64,
+    call: &AdminCall,
+) -> Result<Proposal> {
+    let approvals = client
+        .msig_get_pending(msig)
+        .await?
+        .into_iter()
+        .find(|txn| txn.id == id)
+        .map(|txn| txn.approved)
+        .unwrap_or_default();
+
+    Ok(Proposal {
+        id: id.to_string(),
+        to: Some(format!("{:?}", call.to)),
+        calldata: Some(call.calldata.to_string()),
+        approvals,
+        threshold: msig_threshold(client, msig).await?,
+        executed: false,
+    })
+}
+
+/// The params of the message proposed in a msig transaction.
+fn msig_params(txn: &MsigPendingTransaction) -> Option<RawBytes> {
+    let params = txn.params.as_ref()?;
+    base64::Engine::decode(&base64::engine::general_purpose::STANDARD, params)
+        .ok()
+        .map(RawBytes::new)
+}
@@ -0,0 +1,305 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Transactions built online, signed offline and broadcast later.
+//!
+//! The keys of treasuries and validators are best kept on machines without network access.
+//! [crate::IpcProvider::build_unsigned] fills in the nonce, gas and fees of a transaction with
+//! a connected provider and returns an [UnsignedTx], which is written to a file and carried to
+//! the air-gapped machine. There [crate::IpcProvider::sign_offline] signs it with the keystore
+//! of a provider which needs no connection, and the [SignedTx] is carried back to be broadcast
+//! with [crate::IpcProvider::broadcast_signed].
+//!
+//! The files are JSON, so that what is about to be signed can be inspected.
+
+use std::path::Path;
+
+use anyhow::{anyhow, Context};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::NameOrAddress;
+use fvm_ipld_encoding::RawBytes;
+use fvm_shared::address::Address;
+use fvm_shared::crypto::signature::{Signature, SignatureType};
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::message::Message;
+use fvm_shared::MethodNum;
+use ipc_api::receipt::TxReceipt;
+use ipc_api::subnet_id::SubnetID;
+use ipc_api::HumanReadable;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+
+use crate::fvm::{FvmMessage, FvmReceipt};
+use crate::hooks::Operation;
+
+/// What a transaction built for offline signing does.
+#[derive(Debug, Clone)]
+pub enum OfflineOp {
+    /// A transaction to an address of an EVM subnet, calling a contract with `calldata` or
+    /// sending `value` to an account if there is none. If `from` is `None`, the default
+    /// address is used.
+    Evm {
+        from: Option<Address>,
+        to: Address,
+        calldata: Vec<u8>,
+        value: TokenAmount,
+    },
+    /// A message to an actor of an FVM subnet.
+    Fvm(FvmMessage),
+}
+
+/// A transaction with everything but its signature.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnsignedTx {
+    /// The subnet the transaction is broadcast to.
+    #[serde_as(as = "HumanReadable")]
+    pub subnet: SubnetID,
+    /// The address signing the transaction.
+    #[serde_as(as = "HumanReadable")]
+    pub from: Address,
+    pub message: UnsignedMessage,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnsignedMessage {
+    Evm(TypedTransaction),
+    Fvm(FvmUnsignedMessage),
+}
+
+/// A transaction and its signature, ready to be broadcast.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignedTx {
+    pub tx: UnsignedTx,
+    pub signature: TxSignature,
+}
+
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TxSignature {
+    Evm(ethers::types::Signature),
+    Fvm {
+        sig_type: SignatureType,
+        #[serde_as(as = "HumanReadable")]
+        bytes: Vec<u8>,
+    },
+}
+
+/// The receipt of a transaction signed offline.
+#[derive(Debug, Clone)]
+pub enum BroadcastReceipt {
+    Evm(TxReceipt),
+    Fvm(FvmReceipt),
+}
+
+/// A message to an FVM actor with its nonce and gas, in a form fit to be read before signing.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FvmUnsignedMessage {
+    pub version: u64,
+    #[serde_as(as = "HumanReadable")]
+    pub to: Address,
+    pub sequence: u64,
+    #[serde_as(as = "HumanReadable")]
+    pub value: TokenAmount,
+    pub method_num: MethodNum,
+    /// The CBOR encoded parameters of the method.
+    #[serde_as(as = "HumanReadable")]
+    pub params: Vec<u8>,
+    pub gas_limit: u64,
+    #[serde_as(as = "HumanReadable")]
+    pub gas_fee_cap: TokenAmount,
+    #[serde_as(as = "HumanReadable")]
+    pub gas_premium: TokenAmount,
+}
+
+impl FvmUnsignedMessage {
+    pub fn from_message(msg: &Message) -> Self {
+        Self {
+            version: msg.version,
+            to: msg.to,
+            sequence: msg.sequence,
+            value: msg.value.clone(),
+            method_num: msg.method_num,
+            params: msg.params.bytes().to_vec(),
+            gas_limit: msg.gas_limit,
+            gas_fee_cap: msg.gas_fee_cap.clone(),
+            gas_premium: msg.gas_premium.clone(),
+        }
+    }
+
+    /// The message sent by `from`.
+    pub fn to_message(&self, from: Address) -> Message {
+        Message {
+            version: self.version,
+            from,
+            to: self.to,
+            sequence: self.sequence,
+            value: self.value.clone(),
+            method_num: self.method_num,
+            params: RawBytes::new(self.params.clone()),
+            gas_limit: self.gas_limit,
+            gas_fee_cap: self.gas_fee_cap.clone(),
+            gas_premium: self.gas_premium.clone(),
+        }
+    }
+}
+
+impl TxSignature {
+    pub fn from_fvm(signature: Signature) -> Self {
+        TxSignature::Fvm {
+            sig_type: signature.sig_type,
+            bytes: signature.bytes,
+        }
+    }
+}
+
+impl UnsignedTx {
+    /// The operation the hooks see for the transaction, when it's signed and when it's broadcast.
+    pub fn operation(&self) -> anyhow::Result<Operation> {
+        match &self.message {
+            UnsignedMessage::Evm(evm_tx) => {
+                let to = match evm_tx.to() {
+                    Some(NameOrAddress::Address(to)) => ipc_api::ethers_address_to_fil_address(to)?,
+                    _ => return Err(anyhow!("the transaction has no recipient")),
+                };
+                let value =
+                    ipc_api::eth_to_fil_amount(evm_tx.value().unwrap_or(&Default::default()))?;
+                Ok(match evm_tx.data() {
+                    Some(data) if !data.is_empty() => Operation::ContractTx { to, value },
+                    _ => Operation::SendValue { to, amount: value },
+                })
+            }
+            UnsignedMessage::Fvm(msg) => Ok(Operation::PushMessage {
+                to: msg.to,
+                method: msg.method_num,
+                value: msg.value.clone(),
+            }),
+        }
+    }
+
+    pub fn write_to_file(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        write_json(self, path.as_ref())
+    }
+
+    pub fn read_from_file(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        read_json(path.as_ref())
+    }
+}
+
+impl SignedTx {
+    pub fn write_to_file(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        write_json(self, path.as_ref())
+    }
+
+    pub fn read_from_file(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        read_json(path.as_ref())
+    }
+}
+
+fn write_json<T: Serialize>(value: &T, path: &Path) -> anyhow::Result<()> {
+    let content = serde_json::to_string_pretty(value)?;
+    std::fs::write(path, content).with_context(|| format!("cannot write {}", path.display()))
+}
+
+fn read_json<T: DeserializeOwned>(path: &Path) -> anyhow::Result<T> {
+    let content =
+        std::fs::read_to_string(path).with_context(|| format!("cannot read {}", path.display()))?;
+    serde_json::from_str(&content).with_context(|| format!("cannot parse {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::transaction::eip2718::TypedTransaction;
+    use ethers::types::Eip1559TransactionRequest;
+    use fvm_ipld_encoding::RawBytes;
+    use fvm_shared::address::Address;
+    use fvm_shared::crypto::signature::Signature;
+    use fvm_shared::econ::TokenAmount;
+    use fvm_shared::message::Message;
+    use ipc_api::subnet_id::SubnetID;
+
+    use super::{FvmUnsignedMessage, SignedTx, TxSignature, UnsignedMessage, UnsignedTx};
+    use crate::hooks::Operation;
+
+    #[test]
+    fn offline_txs_roundtrip_through_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let subnet = SubnetID::new(123, vec![Address::new_id(1001)]);
+
+        let evm: TypedTransaction = Eip1559TransactionRequest::new()
+            .to(ethers::types::Address::repeat_byte(1))
+            .value(10u64)
+            .nonce(3u64)
+            .gas(21_000u64)
+            .chain_id(123u64)
+            .into();
+        let unsigned = UnsignedTx {
+            subnet: subnet.clone(),
+            from: Address::new_id(100),
+            message: UnsignedMessage::Evm(evm),
+        };
+        let path = dir.path().join("unsigned.json");
+        unsigned.write_to_file(&path).unwrap();
+        assert_eq!(UnsignedTx::read_from_file(&path).unwrap(), unsigned);
+
+        let msg = Message {
+            version: 0,
+            from: Address::new_id(100),
+            to: Address::new_id(101),
+            sequence: 7,
+            value: TokenAmount::from_whole(1),
+            method_num: 2,
+            params: RawBytes::new(vec![1, 2, 3]),
+            gas_limit: 1000,
+            gas_fee_cap: TokenAmount::from_atto(100),
+            gas_premium: TokenAmount::from_atto(10),
+        };
+        let fvm = FvmUnsignedMessage::from_message(&msg);
+        assert_eq!(fvm.to_message(msg.from), msg);
+
+        let signed = SignedTx {
+            tx: UnsignedTx {
+                subnet,
+                from: msg.from,
+                message: UnsignedMessage::Fvm(fvm),
+            },
+            signature: TxSignature::from_fvm(Signature::new_secp256k1(vec![4; 65])),
+        };
+        let path = dir.path().join("signed.json");
+        signed.write_to_file(&path).unwrap();
+        assert_eq!(SignedTx::read_from_file(&path).unwrap(), signed);
+    }
+
+    #[test]
+    fn offline_txs_are_described_to_the_hooks() {
+        let subnet = SubnetID::new(123, vec![Address::new_id(1001)]);
+        let evm: TypedTransaction = Eip1559TransactionRequest::new()
+            .to(ethers::types::Address::repeat_byte(1))
+            .value(10u64)
+            .chain_id(123u64)
+            .into();
+        let tx = UnsignedTx {
+            subnet,
+            from: Address::new_id(100),
+            message: UnsignedMessage::Evm(evm.clone()),
+        };
+        assert!(matches!(
+            tx.operation().unwrap(),
+            Operation::SendValue { amount, .. } if amount == TokenAmount::from_atto(10)
+        ));
+
+        let mut with_data = evm;
+        with_data.set_data(vec![1, 2, 3].into());
+        let tx = UnsignedTx {
+            message: UnsignedMessage::Evm(with_data),
+            ..tx
+        };
+        assert!(matches!(
+            tx.operation().unwrap(),
+            Operation::ContractTx { value, .. } if value == TokenAmount::from_atto(10)
+        ));
+    }
+}
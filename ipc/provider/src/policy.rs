@@ -0,0 +1,320 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! A spending policy guarding the keys of the keystore.
+//!
+//! The policy is a hook checking every operation before it's signed: the value it sends, the
+//! budget of the day of the key sending it, and the subnet the value goes to. Operations
+//! breaking it fail with a [PolicyViolation], which callers can tell apart from other errors
+//! with `error.downcast_ref::<PolicyViolation>()`.
+//!
+//! The spending of the day is persisted, so that it's shared by the processes using the same
+//! keystore, and counts the operations as soon as the policy allows them, whether or not they
+//! make it on-chain. Every check re-reads it while holding a lock on a file next to it, so
+//! processes sending at the same time can't both spend the same budget.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs::OpenOptions;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Context;
+use async_trait::async_trait;
+use fs2::FileExt;
+use fvm_shared::address::Address;
+use fvm_shared::bigint::BigInt;
+use fvm_shared::econ::TokenAmount;
+use ipc_api::subnet_id::SubnetID;
+use ipc_api::token::TokenAmountExt;
+use num_traits::Zero;
+use serde::{Deserialize, Serialize};
+
+use crate::config::policy::PolicyConfig;
+use crate::hooks::{OperationDescriptor, OperationHook};
+
+/// The name of the file the spending of the day is persisted to, in the keystore directory.
+pub const DEFAULT_SPENDING_NAME: &str = "spending.json";
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// An operation refused by the spending policy.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PolicyViolation {
+    #[error("{operation} of {value} FIL is above the limit of {max} FIL per transaction")]
+    ValueAboveLimit {
+        operation: &'static str,
+        value: TokenAmount,
+        max: TokenAmount,
+    },
+    #[error("{operation} of {value} FIL would exceed the daily budget of {budget} FIL of {key}, which already sent {spent} FIL today")]
+    DailyBudgetExceeded {
+        operation: &'static str,
+        key: Address,
+        value: TokenAmount,
+        spent: TokenAmount,
+        budget: TokenAmount,
+    },
+    #[error("{operation} to subnet {destination} is not allowed by the spending policy")]
+    DestinationNotAllowed {
+        operation: &'static str,
+        destination: SubnetID,
+    },
+}
+
+/// What a key sent on a day.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct DailySpending {
+    /// Days since the unix epoch.
+    day: u64,
+    /// In atto.
+    spent: String,
+}
+
+/// Checks the operations against the limits of the config.
+#[derive(Debug)]
+pub struct SpendingPolicy {
+    max_value_per_tx: Option<TokenAmount>,
+    daily_budget: Option<TokenAmount>,
+    allowed_destinations: Option<HashSet<SubnetID>>,
+    /// Where the spending of the day is persisted.
+    path: PathBuf,
+}
+
+impl SpendingPolicy {
+    /// Parse the limits of `config`, persisting the spending of the day at `path`.
+    pub fn new(config: &PolicyConfig, path: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        let amount = |s: &Option<String>| {
+            s.as_deref()
+                .map(TokenAmount::from_denominated_str)
+                .transpose()
+        };
+        let allowed_destinations = config
+            .allowed_destinations
+            .as_ref()
+            .map(|subnets| {
+                subnets
+                    .iter()
+                    .map(|s| SubnetID::from_str(s).with_context(|| format!("invalid subnet {s}")))
+                    .collect::<anyhow::Result<HashSet<_>>>()
+            })
+            .transpose()?;
+
+        let policy = Self {
+            max_value_per_tx: amount(&config.max_value_per_tx)
+                .context("invalid max_value_per_tx")?,
+            daily_budget: amount(&config.daily_budget).context("invalid daily_budget")?,
+            allowed_destinations,
+            path: path.into(),
+        };
+        // Fail early on a corrupted spending file.
+        policy.load()?;
+
+        Ok(policy)
+    }
+
+    /// Check `op` against the limits, counting its value in the spending of the day of its
+    /// sender if it's allowed.
+    pub fn check(&self, op: &OperationDescriptor) -> anyhow::Result<()> {
+        let operation = op.operation.name();
+        let value = op.operation.value();
+
+        if let Some(allowed) = &self.allowed_destinations {
+            let destination = op
+                .operation
+                .destination()
+                .unwrap_or_else(|| op.network.clone());
+            if !allowed.contains(&destination) {
+                return Err(PolicyViolation::DestinationNotAllowed {
+                    operation,
+                    destination,
+                }
+                .into());
+            }
+        }
+
+        if let Some(max) = &self.max_value_per_tx {
+            if value > *max {
+                return Err(PolicyViolation::ValueAboveLimit {
+                    operation,
+                    value,
+                    max: max.clone(),
+                }
+                .into());
+            }
+        }
+
+        let Some(budget) = &self.daily_budget else {
+            return Ok(());
+        };
+        if value.is_zero() {
+            return Ok(());
+        }
+
+        let today = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() / SECONDS_PER_DAY;
+        let key = op.from.to_string();
+
+        let _lock = self.lock()?;
+        let mut spending = self.load()?;
+        let spent = match spending.get(&key) {
+            Some(s) if s.day == today => TokenAmount::from_atto(BigInt::from_str(&s.spent)?),
+            _ => TokenAmount::zero(),
+        };
+        if &spent + &value > *budget {
+            return Err(PolicyViolation::DailyBudgetExceeded {
+                operation,
+                key: op.from,
+                value,
+                spent,
+                budget: budget.clone(),
+            }
+            .into());
+        }
+
+        spending.insert(
+            key,
+            DailySpending {
+                day: today,
+                spent: (spent + value).atto().to_string(),
+            },
+        );
+        self.persist(&spending)
+    }
+
+    /// Take an exclusive lock on the file next to the spending, waiting for other processes
+    /// to release it; the lock is released when the returned file is dropped.
+    fn lock(&self) -> anyhow::Result<std::fs::File> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let path = self.path.with_extension("json.lock");
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .with_context(|| format!("cannot open {}", path.display()))?;
+        file.lock_exclusive()
+            .with_context(|| format!("cannot lock {}", path.display()))?;
+        Ok(file)
+    }
+
+    fn load(&self) -> anyhow::Result<BTreeMap<String, DailySpending>> {
+        if !self.path.exists() {
+            return Ok(BTreeMap::new());
+        }
+        let content = std::fs::read_to_string(&self.path)
+            .with_context(|| format!("cannot read spending from {}", self.path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("cannot parse spending in {}", self.path.display()))
+    }
+
+    /// Write the spending to a temporary file first, so a crash can't leave it truncated.
+    fn persist(&self, spending: &BTreeMap<String, DailySpending>) -> anyhow::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let content = serde_json::to_string_pretty(spending)?;
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, content)?;
+        std::fs::rename(&tmp, &self.path)
+            .with_context(|| format!("cannot persist spending to {}", self.path.display()))
+    }
+}
+
+#[async_trait]
+impl OperationHook for SpendingPolicy {
+    async fn pre_submit(&self, op: &OperationDescriptor) -> anyhow::Result<()> {
+        self.check(op)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use fvm_shared::address::Address;
+    use fvm_shared::econ::TokenAmount;
+    use ipc_api::subnet_id::SubnetID;
+
+    use super::{PolicyViolation, SpendingPolicy};
+    use crate::config::policy::PolicyConfig;
+    use crate::hooks::{Operation, OperationDescriptor};
+
+    const ROOT: &str = "/r123";
+    const CHILD: &str = "/r123/f0100";
+
+    fn fund(from: u64, amount: u64, subnet: &str) -> OperationDescriptor {
+        OperationDescriptor {
+            network: SubnetID::from_str(ROOT).unwrap(),
+            from: Address::new_id(from),
+            operation: Operation::Fund {
+                subnet: SubnetID::from_str(subnet).unwrap(),
+                to: Address::new_id(from),
+                amount: TokenAmount::from_whole(amount),
+            },
+        }
+    }
+
+    fn violation(result: anyhow::Result<()>) -> PolicyViolation {
+        result
+            .unwrap_err()
+            .downcast_ref::<PolicyViolation>()
+            .cloned()
+            .unwrap()
+    }
+
+    #[test]
+    fn operations_are_checked_against_the_limits() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("spending.json");
+        let config = PolicyConfig {
+            max_value_per_tx: Some("10".to_string()),
+            daily_budget: Some("15 FIL".to_string()),
+            allowed_destinations: Some(vec![CHILD.to_string()]),
+        };
+        let policy = SpendingPolicy::new(&config, &path).unwrap();
+
+        assert!(matches!(
+            violation(policy.check(&fund(100, 11, CHILD))),
+            PolicyViolation::ValueAboveLimit { .. }
+        ));
+        assert!(matches!(
+            violation(policy.check(&fund(100, 1, "/r123/f0101"))),
+            PolicyViolation::DestinationNotAllowed { .. }
+        ));
+
+        policy.check(&fund(100, 10, CHILD)).unwrap();
+        assert!(matches!(
+            violation(policy.check(&fund(100, 10, CHILD))),
+            PolicyViolation::DailyBudgetExceeded { .. }
+        ));
+        // The budget is per key.
+        policy.check(&fund(101, 10, CHILD)).unwrap();
+
+        // The spending survives a restart.
+        let policy = SpendingPolicy::new(&config, &path).unwrap();
+        policy.check(&fund(100, 5, CHILD)).unwrap();
+        assert!(policy.check(&fund(100, 1, CHILD)).is_err());
+    }
+
+    #[test]
+    fn spending_is_shared_by_processes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("spending.json");
+        let config = PolicyConfig {
+            daily_budget: Some("15".to_string()),
+            ..Default::default()
+        };
+        // As if opened by two processes at the same time.
+        let first = SpendingPolicy::new(&config, &path).unwrap();
+        let second = SpendingPolicy::new(&config, &path).unwrap();
+
+        first.check(&fund(100, 10, CHILD)).unwrap();
+        assert!(matches!(
+            violation(second.check(&fund(100, 10, CHILD))),
+            PolicyViolation::DailyBudgetExceeded { .. }
+        ));
+        second.check(&fund(100, 5, CHILD)).unwrap();
+        assert!(first.check(&fund(100, 1, CHILD)).is_err());
+    }
+}
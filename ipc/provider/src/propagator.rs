@@ -0,0 +1,365 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Automatic propagation of the messages waiting in the postbox of a gateway.
+//!
+//! A cross-net message going through more than one level of the hierarchy stops in the
+//! postbox of every subnet on its way, until someone calls `propagate` for it. The
+//! [PostboxPropagator] runs next to the bottom-up relayer and does it for the messages which
+//! have waited long enough, to allowed destinations only, and within a budget of fees.
+//!
+//! The postbox doesn't record when a message arrived, so its age is counted from when the
+//! propagator first saw it.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use fvm_shared::address::Address;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
+use ipc_api::receipt::TxReceipt;
+use ipc_api::subnet_id::SubnetID;
+use tokio_util::sync::CancellationToken;
+
+use crate::config::Subnet;
+use crate::manager::{BottomUpCheckpointRelayer, EthSubnetManager, PostboxMsg, SubnetManager};
+use crate::stats;
+
+/// Number of epochs a message waits in the postbox before it's propagated, unless configured
+/// otherwise; it leaves a chance to the senders to propagate their own messages.
+pub const DEFAULT_PROPAGATION_MIN_AGE: ChainEpoch = 10;
+
+/// What the propagator needs from the subnet whose postbox it empties.
+#[async_trait]
+pub trait PostboxHandler: Send + Sync {
+    async fn current_epoch(&self) -> Result<ChainEpoch>;
+
+    async fn list_postbox(&self) -> Result<Vec<PostboxMsg>>;
+
+    async fn propagate(
+        &self,
+        subnet: SubnetID,
+        gateway_addr: Address,
+        from: Address,
+        postbox_msg_key: Vec<u8>,
+    ) -> Result<TxReceipt>;
+
+    /// The price of a unit of gas, to account for the fees of the propagations.
+    async fn gas_price(&self) -> Result<TokenAmount>;
+}
+
+#[async_trait]
+impl PostboxHandler for EthSubnetManager {
+    async fn current_epoch(&self) -> Result<ChainEpoch> {
+        BottomUpCheckpointRelayer::current_epoch(self).await
+    }
+
+    async fn list_postbox(&self) -> Result<Vec<PostboxMsg>> {
+        SubnetManager::list_postbox(self).await
+    }
+
+    async fn propagate(
+        &self,
+        subnet: SubnetID,
+        gateway_addr: Address,
+        from: Address,
+        postbox_msg_key: Vec<u8>,
+    ) -> Result<TxReceipt> {
+        SubnetManager::propagate(self, subnet, gateway_addr, from, postbox_msg_key).await
+    }
+
+    async fn gas_price(&self) -> Result<TokenAmount> {
+        Ok(self.chain_params().await?.gas_price_floor)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PropagationConfig {
+    /// Number of epochs a message waits in the postbox before it's propagated.
+    pub min_age: ChainEpoch,
+    /// The total fees the propagator can spend; it stops propagating once they're reached.
+    pub fee_budget: TokenAmount,
+    /// The subnets messages are propagated to; all of them if unset.
+    pub allowed_destinations: Option<HashSet<SubnetID>>,
+}
+
+impl PropagationConfig {
+    fn is_allowed(&self, destination: &SubnetID) -> bool {
+        self.allowed_destinations
+            .as_ref()
+            .map_or(true, |allowed| allowed.contains(destination))
+    }
+}
+
+/// Propagates the messages waiting in the postbox of a subnet.
+pub struct PostboxPropagator<T> {
+    subnet: Subnet,
+    handler: T,
+    config: PropagationConfig,
+    /// The epoch at which each message in the postbox was first seen.
+    first_seen: HashMap<Vec<u8>, ChainEpoch>,
+    /// The fees spent so far, at the base fee when each propagation was sent.
+    spent: TokenAmount,
+    shutdown: CancellationToken,
+}
+
+impl<T: PostboxHandler> PostboxPropagator<T> {
+    pub fn new(subnet: Subnet, handler: T, config: PropagationConfig) -> Self {
+        Self {
+            subnet,
+            handler,
+            config,
+            first_seen: HashMap::new(),
+            spent: TokenAmount::default(),
+            shutdown: CancellationToken::new(),
+        }
+    }
+
+    /// Stop propagating when the signal is cancelled, e.g. by [crate::supervisor::TaskSupervisor].
+    pub fn with_shutdown_signal(mut self, shutdown: CancellationToken) -> Self {
+        self.shutdown = shutdown;
+        self
+    }
+
+    /// The fees spent so far.
+    pub fn spent(&self) -> &TokenAmount {
+        &self.spent
+    }
+
+    fn budget_exhausted(&self) -> bool {
+        self.spent >= self.config.fee_budget
+    }
+
+    pub async fn run(mut self, from: Address, interval: Duration) {
+        tracing::info!("launching {self} for {from}");
+
+        loop {
+            if let Err(e) = self.propagate_due(from).await {
+                tracing::error!("cannot propagate the postbox of {}: {e:#}", self.subnet.id);
+            }
+            if self.budget_exhausted() {
+                tracing::warn!(
+                    "{self} spent its fee budget of {} FIL, stopping",
+                    self.config.fee_budget
+                );
+                break;
+            }
+            tokio::select! {
+                _ = self.shutdown.cancelled() => break,
+                _ = tokio::time::sleep(interval) => {}
+            }
+        }
+
+        tracing::info!("stopped {self} for {from}");
+    }
+
+    /// Go through the postbox once, propagating the messages which are due, and return their
+    /// keys.
+    pub async fn propagate_due(&mut self, from: Address) -> Result<Vec<Vec<u8>>> {
+        let (epoch, msgs) =
+            tokio::try_join!(self.handler.current_epoch(), self.handler.list_postbox())?;
+        stats::RELAYER_POSTBOX_SIZE.set(msgs.len() as i64);
+
+        // Forget the messages which were propagated by someone else.
+        self.first_seen
+            .retain(|key, _| msgs.iter().any(|msg| &msg.key == key));
+
+        let mut propagated = Vec::new();
+        for msg in msgs {
+            let seen = *self.first_seen.entry(msg.key.clone()).or_insert(epoch);
+            if epoch - seen < self.config.min_age {
+                continue;
+            }
+
+            let destination = msg.msg.to.subnet()?;
+            if !self.config.is_allowed(&destination) {
+                tracing::debug!("not propagating {msg}: destination {destination} not allowed");
+                continue;
+            }
+
+            if self.budget_exhausted() {
+                break;
+            }
+
+            let gas_price = self.handler.gas_price().await?;
+            match self
+                .handler
+                .propagate(
+                    self.subnet.id.clone(),
+                    self.subnet.gateway_addr(),
+                    from,
+                    msg.key.clone(),
+                )
+                .await
+            {
+                Ok(receipt) => {
+                    let gas_used = receipt.gas_used.unwrap_or_default();
+                    self.spent += TokenAmount::from_atto(gas_price.atto() * gas_used);
+                    self.first_seen.remove(&msg.key);
+                    stats::RELAYER_PROPAGATED_MSGS.inc();
+                    tracing::info!("propagated {msg} in {:?}", receipt.tx_hash);
+                    propagated.push(msg.key);
+                }
+                Err(e) => {
+                    stats::RELAYER_PROPAGATION_FAILURE.inc();
+                    tracing::error!("cannot propagate {msg}: {e:#}");
+                }
+            }
+        }
+
+        Ok(propagated)
+    }
+}
+
+impl<T> Display for PostboxPropagator<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "postbox propagator, subnet: {}", self.subnet.id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::str::FromStr;
+    use std::sync::Mutex;
+
+    use anyhow::Result;
+    use async_trait::async_trait;
+    use ethers::types::H256;
+    use fvm_shared::address::Address;
+    use fvm_shared::clock::ChainEpoch;
+    use fvm_shared::econ::TokenAmount;
+    use ipc_api::address::IPCAddress;
+    use ipc_api::cross::{IpcEnvelope, IpcMsgKind};
+    use ipc_api::receipt::TxReceipt;
+    use ipc_api::subnet_id::SubnetID;
+    use url::Url;
+
+    use super::{PostboxHandler, PostboxPropagator, PropagationConfig};
+    use crate::config::subnet::{EVMSubnet, SubnetConfig};
+    use crate::config::Subnet;
+    use crate::manager::PostboxMsg;
+
+    /// A postbox whose messages are removed when propagated, costing 100 gas at 1 atto each.
+    #[derive(Default)]
+    struct MockPostbox {
+        epoch: Mutex<ChainEpoch>,
+        msgs: Mutex<Vec<PostboxMsg>>,
+    }
+
+    #[async_trait]
+    impl PostboxHandler for MockPostbox {
+        async fn current_epoch(&self) -> Result<ChainEpoch> {
+            Ok(*self.epoch.lock().unwrap())
+        }
+
+        async fn list_postbox(&self) -> Result<Vec<PostboxMsg>> {
+            Ok(self.msgs.lock().unwrap().clone())
+        }
+
+        async fn propagate(
+            &self,
+            _subnet: SubnetID,
+            _gateway_addr: Address,
+            _from: Address,
+            postbox_msg_key: Vec<u8>,
+        ) -> Result<TxReceipt> {
+            self.msgs
+                .lock()
+                .unwrap()
+                .retain(|msg| msg.key != postbox_msg_key);
+            Ok(TxReceipt {
+                tx_hash: H256::zero(),
+                epoch: *self.epoch.lock().unwrap(),
+                gas_used: Some(100),
+                success: true,
+                events: vec![],
+            })
+        }
+
+        async fn gas_price(&self) -> Result<TokenAmount> {
+            Ok(TokenAmount::from_atto(1))
+        }
+    }
+
+    fn postbox_msg(key: u8, to: &str) -> PostboxMsg {
+        let from = SubnetID::from_str("/r314159/f0100").unwrap();
+        let to = SubnetID::from_str(to).unwrap();
+        PostboxMsg {
+            key: vec![key; 32],
+            msg: IpcEnvelope {
+                kind: IpcMsgKind::Transfer,
+                to: IPCAddress::new(&to, &Address::new_id(1)).unwrap(),
+                value: TokenAmount::from_whole(1),
+                from: IPCAddress::new(&from, &Address::new_id(2)).unwrap(),
+                message: vec![],
+                nonce: key as u64,
+            },
+        }
+    }
+
+    fn subnet() -> Subnet {
+        Subnet {
+            id: SubnetID::from_str("/r314159").unwrap(),
+            config: SubnetConfig::Fevm(EVMSubnet {
+                provider_http: Url::parse("http://127.0.0.1:8545").unwrap(),
+                provider_timeout: None,
+                auth_token: None,
+                registry_addr: Address::new_id(10),
+                gateway_addr: Address::new_id(11),
+                admin: None,
+                response_cache: None,
+                max_concurrent_requests: None,
+                fallback_provider_http: vec![],
+                gas: None,
+            }),
+        }
+    }
+
+    #[tokio::test]
+    async fn propagates_old_allowed_messages_within_budget() {
+        let handler = MockPostbox::default();
+        *handler.msgs.lock().unwrap() = vec![
+            postbox_msg(1, "/r314159/f0200"),
+            postbox_msg(2, "/r314159/f0300"),
+            postbox_msg(3, "/r314159/f0200"),
+            postbox_msg(4, "/r314159/f0200"),
+        ];
+        *handler.epoch.lock().unwrap() = 10;
+
+        let config = PropagationConfig {
+            min_age: 2,
+            fee_budget: TokenAmount::from_atto(150),
+            allowed_destinations: Some(HashSet::from([
+                SubnetID::from_str("/r314159/f0200").unwrap()
+            ])),
+        };
+        let mut propagator = PostboxPropagator::new(subnet(), handler, config);
+        let from = Address::new_id(3);
+
+        // Too young.
+        assert!(propagator.propagate_due(from).await.unwrap().is_empty());
+        *propagator.handler.epoch.lock().unwrap() = 11;
+        assert!(propagator.propagate_due(from).await.unwrap().is_empty());
+
+        // The second propagation reaches the budget, and the message to a subnet which
+        // isn't allowed stays in the postbox.
+        *propagator.handler.epoch.lock().unwrap() = 12;
+        let propagated = propagator.propagate_due(from).await.unwrap();
+        assert_eq!(propagated, vec![vec![1; 32], vec![3; 32]]);
+        assert_eq!(propagator.spent(), &TokenAmount::from_atto(200));
+
+        *propagator.handler.epoch.lock().unwrap() = 20;
+        assert!(propagator.propagate_due(from).await.unwrap().is_empty());
+        let left = propagator.handler.msgs.lock().unwrap().clone();
+        assert_eq!(
+            left,
+            vec![
+                postbox_msg(2, "/r314159/f0300"),
+                postbox_msg(4, "/r314159/f0200")
+            ]
+        );
+    }
+}
@@ -0,0 +1,351 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Python bindings for the [IpcProvider], enabled with the `python` feature.
+//!
+//! The module exposes wallet management, the subnet operations and the machine learning
+//! helpers, so scripts can use the provider without shelling out to `ipc-cli`:
+//!
+//! ```python
+//! from ipc_provider import IpcProvider
+//!
+//! provider = IpcProvider("~/.ipc/config.toml")
+//! model = provider.ml_train("/r314159/t410f...", "linear_regression", [[100], [200]], [200, 400])
+//! print(provider.ml_predict("/r314159/t410f...", "linear_regression", [[300]], model))
+//! ```
+//!
+//! Amounts are strings parsed exactly like in the CLI, e.g. `"1.5"` or `"1000 attoFIL"`, in
+//! whole FIL without a unit, and balances are returned in atto.
+//! Transaction receipts are returned as dictionaries with the fields of [TxReceipt].
+
+use std::str::FromStr;
+
+use fvm_shared::{address::Address, econ::TokenAmount};
+use ipc_api::{model::MlAlgorithm, receipt::TxReceipt, subnet_id::SubnetID, token::TokenAmountExt};
+use ipc_wallet::EvmKeyStore;
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+use crate::address_book::parse_address;
+use crate::{default_config_path, expand_tilde, IpcProvider};
+
+/// The Python facade of the provider, blocking on a dedicated runtime for each call.
+#[pyclass(name = "IpcProvider")]
+pub struct PyIpcProvider {
+    provider: IpcProvider,
+    runtime: tokio::runtime::Runtime,
+}
+
+#[pymethods]
+impl PyIpcProvider {
+    /// Create a provider from the config file, or the default `~/.ipc/config.toml`.
+    #[new]
+    #[pyo3(signature = (config_path = None))]
+    fn new(config_path: Option<String>) -> PyResult<Self> {
+        let config_path = config_path.unwrap_or_else(default_config_path);
+        let config_path = expand_tilde(config_path).to_string_lossy().to_string();
+        let provider = IpcProvider::new_from_config(config_path).map_err(to_py_err)?;
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()?;
+        Ok(Self { provider, runtime })
+    }
+
+    /// Set the default account used to send transactions.
+    fn set_sender(&mut self, address: &str) -> PyResult<()> {
+        self.provider
+            .with_sender(parse_address(address).map_err(to_py_err)?);
+        Ok(())
+    }
+
+    /// Create a new key in the EVM wallet and return its address.
+    fn wallet_new(&self) -> PyResult<String> {
+        let addr = self.provider.new_evm_key().map_err(to_py_err)?;
+        Ok(addr.to_string())
+    }
+
+    /// Import a hex encoded private key into the EVM wallet and return its address.
+    fn wallet_import(&self, private_key: &str) -> PyResult<String> {
+        let addr = self
+            .provider
+            .import_evm_key_from_privkey(private_key)
+            .map_err(to_py_err)?;
+        Ok(addr.to_string())
+    }
+
+    /// List the addresses of the EVM wallet.
+    fn wallet_list(&self) -> PyResult<Vec<String>> {
+        let wallet = self.provider.evm_wallet().map_err(to_py_err)?;
+        let addrs = wallet.read().unwrap().list().map_err(to_py_err)?;
+        Ok(addrs.iter().map(|a| a.to_string()).collect())
+    }
+
+    /// Get the balance of an address in a subnet, in atto.
+    fn wallet_balance(&self, py: Python, subnet: &str, address: &str) -> PyResult<String> {
+        let subnet = parse_subnet(subnet)?;
+        let address = parse_address(address).map_err(to_py_err)?;
+        let balance = py
+            .allow_threads(|| {
+                self.runtime
+                    .block_on(self.provider.wallet_balance(&subnet, &address))
+            })
+            .map_err(to_py_err)?;
+        Ok(balance.atto().to_string())
+    }
+
+    /// List the child subnets registered in the gateway of a subnet.
+    fn list_subnets(&self, py: Python, subnet: &str) -> PyResult<Vec<String>> {
+        let subnet = parse_subnet(subnet)?;
+        let subnets = py
+            .allow_threads(|| {
+                self.runtime
+                    .block_on(self.provider.list_child_subnets(None, &subnet))
+            })
+            .map_err(to_py_err)?;
+        Ok(subnets.keys().map(|id| id.to_string()).collect())
+    }
+
+    /// Join a subnet as a validator, staking `collateral`.
+    #[pyo3(signature = (subnet, collateral, sender = None))]
+    fn join_subnet(
+        &mut self,
+        py: Python,
+        subnet: &str,
+        collateral: &str,
+        sender: Option<&str>,
+    ) -> PyResult<PyObject> {
+        let (subnet, sender) = (parse_subnet(subnet)?, parse_sender(sender)?);
+        let collateral = parse_amount(collateral)?;
+        let receipt = py.allow_threads(|| {
+            self.runtime
+                .block_on(self.provider.join_subnet(subnet, sender, collateral))
+        });
+        receipt_to_py(py, receipt)
+    }
+
+    /// Add `collateral` to the stake in a subnet.
+    #[pyo3(signature = (subnet, collateral, sender = None))]
+    fn stake(
+        &mut self,
+        py: Python,
+        subnet: &str,
+        collateral: &str,
+        sender: Option<&str>,
+    ) -> PyResult<PyObject> {
+        let (subnet, sender) = (parse_subnet(subnet)?, parse_sender(sender)?);
+        let collateral = parse_amount(collateral)?;
+        let receipt = py.allow_threads(|| {
+            self.runtime
+                .block_on(self.provider.stake(subnet, sender, collateral))
+        });
+        receipt_to_py(py, receipt)
+    }
+
+    /// Remove `collateral` from the stake in a subnet.
+    #[pyo3(signature = (subnet, collateral, sender = None))]
+    fn unstake(
+        &mut self,
+        py: Python,
+        subnet: &str,
+        collateral: &str,
+        sender: Option<&str>,
+    ) -> PyResult<PyObject> {
+        let (subnet, sender) = (parse_subnet(subnet)?, parse_sender(sender)?);
+        let collateral = parse_amount(collateral)?;
+        let receipt = py.allow_threads(|| {
+            self.runtime
+                .block_on(self.provider.unstake(subnet, sender, collateral))
+        });
+        receipt_to_py(py, receipt)
+    }
+
+    /// Leave a subnet as a validator.
+    #[pyo3(signature = (subnet, sender = None))]
+    fn leave_subnet(
+        &mut self,
+        py: Python,
+        subnet: &str,
+        sender: Option<&str>,
+    ) -> PyResult<PyObject> {
+        let (subnet, sender) = (parse_subnet(subnet)?, parse_sender(sender)?);
+        let receipt = py.allow_threads(|| {
+            self.runtime
+                .block_on(self.provider.leave_subnet(subnet, sender))
+        });
+        receipt_to_py(py, receipt)
+    }
+
+    /// Send `amount` from the parent to an address in a subnet.
+    #[pyo3(signature = (subnet, amount, sender = None, to = None))]
+    fn fund(
+        &mut self,
+        py: Python,
+        subnet: &str,
+        amount: &str,
+        sender: Option<&str>,
+        to: Option<&str>,
+    ) -> PyResult<PyObject> {
+        let (subnet, sender, to) = (
+            parse_subnet(subnet)?,
+            parse_sender(sender)?,
+            parse_sender(to)?,
+        );
+        let amount = parse_amount(amount)?;
+        let receipt = py.allow_threads(|| {
+            self.runtime
+                .block_on(self.provider.fund(subnet, None, sender, to, amount))
+        });
+        receipt_to_py(py, receipt)
+    }
+
+    /// Release `amount` from a subnet to an address in its parent.
+    #[pyo3(signature = (subnet, amount, sender = None, to = None))]
+    fn release(
+        &mut self,
+        py: Python,
+        subnet: &str,
+        amount: &str,
+        sender: Option<&str>,
+        to: Option<&str>,
+    ) -> PyResult<PyObject> {
+        let (subnet, sender, to) = (
+            parse_subnet(subnet)?,
+            parse_sender(sender)?,
+            parse_sender(to)?,
+        );
+        let amount = parse_amount(amount)?;
+        let receipt = py.allow_threads(|| {
+            self.runtime
+                .block_on(self.provider.release(subnet, None, sender, to, amount))
+        });
+        receipt_to_py(py, receipt)
+    }
+
+    /// Train a model with the machine learning actor of a subnet and return it as bytes.
+    ///
+    /// The input matrix and labels are fixed point values, multiplied by 100.
+    #[pyo3(signature = (subnet, algorithm, input_matrix, labels, sender = None))]
+    fn ml_train<'py>(
+        &mut self,
+        py: Python<'py>,
+        subnet: &str,
+        algorithm: &str,
+        input_matrix: Vec<Vec<i64>>,
+        labels: Vec<i64>,
+        sender: Option<&str>,
+    ) -> PyResult<&'py PyBytes> {
+        let (subnet, sender) = (parse_subnet(subnet)?, parse_sender(sender)?);
+        let algorithm = parse_algorithm(algorithm)?;
+        let model = py
+            .allow_threads(|| {
+                self.runtime.block_on(self.provider.ml_train(
+                    &subnet,
+                    sender,
+                    algorithm,
+                    input_matrix,
+                    labels,
+                ))
+            })
+            .map_err(to_py_err)?;
+        Ok(PyBytes::new(py, &model))
+    }
+
+    /// Predict with a model returned by `ml_train`, one fixed point value per row.
+    #[pyo3(signature = (subnet, algorithm, input_matrix, model, sender = None))]
+    fn ml_predict(
+        &mut self,
+        py: Python,
+        subnet: &str,
+        algorithm: &str,
+        input_matrix: Vec<Vec<i64>>,
+        model: &[u8],
+        sender: Option<&str>,
+    ) -> PyResult<Vec<i64>> {
+        let (subnet, sender) = (parse_subnet(subnet)?, parse_sender(sender)?);
+        let algorithm = parse_algorithm(algorithm)?;
+        let model = model.to_vec();
+        py.allow_threads(|| {
+            self.runtime.block_on(self.provider.ml_predict(
+                &subnet,
+                sender,
+                algorithm,
+                input_matrix,
+                model,
+            ))
+        })
+        .map_err(to_py_err)
+    }
+}
+
+/// The `ipc_provider` Python module.
+#[pymodule]
+fn ipc_provider(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_class::<PyIpcProvider>()?;
+    Ok(())
+}
+
+fn to_py_err(e: impl std::fmt::Display) -> PyErr {
+    PyRuntimeError::new_err(e.to_string())
+}
+
+/// Convert a receipt into a Python dictionary, going through its JSON representation.
+fn receipt_to_py(py: Python, receipt: anyhow::Result<TxReceipt>) -> PyResult<PyObject> {
+    let json = serde_json::to_string(&receipt.map_err(to_py_err)?).map_err(to_py_err)?;
+    let json_module = py.import("json")?;
+    Ok(json_module.call_method1("loads", (json,))?.into())
+}
+
+fn parse_subnet(subnet: &str) -> PyResult<SubnetID> {
+    SubnetID::from_str(subnet).map_err(to_py_err)
+}
+
+fn parse_sender(sender: Option<&str>) -> PyResult<Option<Address>> {
+    sender.map(parse_address).transpose().map_err(to_py_err)
+}
+
+fn parse_algorithm(algorithm: &str) -> PyResult<MlAlgorithm> {
+    MlAlgorithm::from_str(algorithm).map_err(to_py_err)
+}
+
+fn parse_amount(amount: &str) -> PyResult<TokenAmount> {
+    TokenAmount::from_denominated_str(amount).map_err(to_py_err)
+}
+
+#[cfg(test)]
+mod tests {
+    use fvm_shared::address::Protocol;
+    use fvm_shared::econ::TokenAmount;
+    use ipc_api::model::MlAlgorithm;
+
+    use super::{parse_algorithm, parse_amount, parse_sender, parse_subnet};
+
+    #[test]
+    fn arguments_are_parsed_like_in_the_cli() {
+        assert_eq!(
+            parse_amount("1.5").unwrap(),
+            TokenAmount::from_nano(1_500_000_000u64)
+        );
+        assert_eq!(
+            parse_amount("1000 attoFIL").unwrap(),
+            TokenAmount::from_atto(1000)
+        );
+        // Amounts are exact, so what doesn't fit in atto is refused rather than rounded.
+        assert!(parse_amount("0.1234567890123456789").is_err());
+        assert!(parse_amount("1e18").is_err());
+
+        assert_eq!(parse_sender(None).unwrap(), None);
+        let sender = parse_sender(Some("0x6be1ccf648c74800380d0520d797a170c808b624")).unwrap();
+        assert_eq!(sender.map(|a| a.protocol()), Some(Protocol::Delegated));
+        // Aliases of the address book are only resolved by the CLI.
+        assert!(parse_sender(Some("relayer")).is_err());
+
+        assert_eq!(parse_subnet("/r123").unwrap().root_id(), 123);
+        assert!(parse_subnet("r123").is_err());
+
+        assert_eq!(
+            parse_algorithm("linear_regression").unwrap(),
+            MlAlgorithm::LinearRegression
+        );
+        assert!(parse_algorithm("LinearRegression").is_err());
+    }
+}
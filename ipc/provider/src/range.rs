@@ -0,0 +1,165 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Queries over ranges of epochs, streamed instead of collected.
+//!
+//! The range is split in chunks of consecutive epochs; each chunk is fetched one epoch after
+//! the other, a bounded number of chunks at a time, and the results are yielded in the order of
+//! the epochs as soon as their chunk is done. At most `chunk_size * concurrency` results are held
+//! in memory, however large the range.
+
+use std::future::Future;
+
+use futures_util::stream::{self, Stream, StreamExt};
+use fvm_shared::clock::ChainEpoch;
+
+/// Epochs fetched one after the other by each task, unless configured otherwise.
+pub const DEFAULT_CHUNK_SIZE: usize = 50;
+/// Chunks fetched at the same time, unless configured otherwise.
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+/// How to fetch a range of epochs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RangeQueryOptions {
+    /// Number of consecutive epochs fetched by each task.
+    pub chunk_size: usize,
+    /// Number of tasks running at the same time.
+    pub concurrency: usize,
+}
+
+impl Default for RangeQueryOptions {
+    fn default() -> Self {
+        Self {
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            concurrency: DEFAULT_CONCURRENCY,
+        }
+    }
+}
+
+/// Stream the results of `fetch` for every epoch between `from` and `to`, both included.
+///
+/// A chunk stops at the first error, which is yielded in place of its epoch; callers usually
+/// stop there too.
+pub fn stream_epochs<'a, T, F, Fut>(
+    from: ChainEpoch,
+    to: ChainEpoch,
+    options: RangeQueryOptions,
+    fetch: F,
+) -> impl Stream<Item = anyhow::Result<(ChainEpoch, T)>> + 'a
+where
+    T: 'a,
+    F: Fn(ChainEpoch) -> Fut + Clone + 'a,
+    Fut: Future<Output = anyhow::Result<T>> + 'a,
+{
+    let chunk_size = options.chunk_size.max(1) as ChainEpoch;
+    let chunks = (from..=to).step_by(chunk_size as usize);
+
+    stream::iter(chunks)
+        .map(move |start| {
+            let fetch = fetch.clone();
+            let end = to.min(start.saturating_add(chunk_size - 1));
+            async move {
+                let mut results = Vec::with_capacity((end - start + 1) as usize);
+                for epoch in start..=end {
+                    let res = fetch(epoch).await;
+                    let failed = res.is_err();
+                    results.push(res.map(|value| (epoch, value)));
+                    if failed {
+                        break;
+                    }
+                }
+                stream::iter(results)
+            }
+        })
+        .buffered(options.concurrency.max(1))
+        .flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use anyhow::anyhow;
+    use futures_util::StreamExt;
+    use fvm_shared::clock::ChainEpoch;
+
+    use super::{stream_epochs, RangeQueryOptions};
+
+    #[tokio::test]
+    async fn epochs_are_yielded_in_order_with_bounded_concurrency() {
+        let running = Arc::new(AtomicUsize::new(0));
+        let max_running = Arc::new(AtomicUsize::new(0));
+
+        let options = RangeQueryOptions {
+            chunk_size: 3,
+            concurrency: 2,
+        };
+        let fetch = {
+            let running = running.clone();
+            let max_running = max_running.clone();
+            move |epoch: ChainEpoch| {
+                let running = running.clone();
+                let max_running = max_running.clone();
+                async move {
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    max_running.fetch_max(now, Ordering::SeqCst);
+                    // Later epochs finish first, the order must hold anyway.
+                    tokio::time::sleep(Duration::from_millis(20 - epoch as u64 % 3 * 5)).await;
+                    running.fetch_sub(1, Ordering::SeqCst);
+                    Ok(epoch * 2)
+                }
+            }
+        };
+
+        let results = stream_epochs(1, 10, options, fetch)
+            .map(|res| res.unwrap())
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(results, (1..=10).map(|e| (e, e * 2)).collect::<Vec<_>>());
+        assert!(max_running.load(Ordering::SeqCst) <= 2);
+    }
+
+    #[tokio::test]
+    async fn chunks_stop_at_the_first_error() {
+        let options = RangeQueryOptions {
+            chunk_size: 5,
+            concurrency: 1,
+        };
+        let fetch = |epoch: ChainEpoch| async move {
+            if epoch == 3 {
+                Err(anyhow!("cannot fetch epoch {epoch}"))
+            } else {
+                Ok(())
+            }
+        };
+
+        let results = stream_epochs(1, 7, options, fetch)
+            .collect::<Vec<_>>()
+            .await;
+        let epochs = results
+            .iter()
+            .map(|res| res.as_ref().map(|(e, _)| *e).map_err(|e| e.to_string()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            epochs,
+            vec![
+                Ok(1),
+                Ok(2),
+                Err("cannot fetch epoch 3".to_string()),
+                Ok(6),
+                Ok(7)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn empty_range() {
+        let results = stream_epochs(5, 4, RangeQueryOptions::default(), |_| async { Ok(()) })
+            .collect::<Vec<_>>()
+            .await;
+        assert!(results.is_empty());
+    }
+}
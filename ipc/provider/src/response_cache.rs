@@ -0,0 +1,316 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! On-disk cache of the RPC responses about the finalized history of a subnet.
+//!
+//! Block hashes, cross messages and the other data of a block don't change once the block is
+//! final, yet a relayer or a node restarted after a while fetches them again for every epoch it
+//! replays. The [ResponseCache] persists the responses about final blocks, keyed by the method,
+//! its parameters and the height they are about, so the replay only hits the network for the
+//! blocks it didn't see before.
+//!
+//! Responses are appended to a file as JSON lines, and loaded in memory when the cache is
+//! opened. A line cut short by a crash is skipped.
+
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::future::Future;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Mutex;
+
+use anyhow::Context;
+use fvm_shared::clock::ChainEpoch;
+use ipc_api::subnet_id::SubnetID;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// The directory the caches are persisted to, in the keystore directory, unless configured
+/// otherwise.
+pub const DEFAULT_CACHE_DIR: &str = "rpc-cache";
+
+/// The name of the file of the cache of `subnet` in the default directory.
+pub fn default_file_name(subnet: &SubnetID) -> String {
+    let name = subnet.to_string();
+    format!("{}.jsonl", name.trim_start_matches('/').replace('/', "_"))
+}
+
+/// A line of the cache file.
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    key: String,
+    value: Value,
+}
+
+/// Persists the responses about the blocks which are final.
+pub struct ResponseCache {
+    path: PathBuf,
+    /// Number of blocks behind the chain head after which blocks are final.
+    finality: ChainEpoch,
+    /// Highest final height seen so far.
+    finalized: AtomicI64,
+    entries: Mutex<Entries>,
+}
+
+struct Entries {
+    values: HashMap<String, Value>,
+    /// Opened on the first insert.
+    file: Option<File>,
+}
+
+impl ResponseCache {
+    /// Open the cache persisted at `path`, which is created on the first insert.
+    pub fn open(path: impl Into<PathBuf>, finality: ChainEpoch) -> anyhow::Result<Self> {
+        let path = path.into();
+        let mut values = HashMap::new();
+
+        if path.exists() {
+            let file = File::open(&path)
+                .with_context(|| format!("cannot open response cache {}", path.display()))?;
+            for line in BufReader::new(file).lines() {
+                let line =
+                    line.with_context(|| format!("cannot read response cache {}", path.display()))?;
+                match serde_json::from_str::<Entry>(&line) {
+                    Ok(entry) => {
+                        values.insert(entry.key, entry.value);
+                    }
+                    Err(e) => {
+                        tracing::warn!("skipping corrupt entry in {}: {e}", path.display());
+                    }
+                }
+            }
+        }
+
+        tracing::debug!(
+            "opened response cache {} with {} entries",
+            path.display(),
+            values.len()
+        );
+
+        Ok(Self {
+            path,
+            finality,
+            finalized: AtomicI64::new(ChainEpoch::MIN),
+            entries: Mutex::new(Entries { values, file: None }),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Number of cached responses.
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Record the current height of the chain, which tells which blocks are final.
+    pub fn observe_head(&self, head: ChainEpoch) {
+        self.finalized
+            .fetch_max(head.saturating_sub(self.finality), Ordering::Relaxed);
+    }
+
+    /// Whether the block at `height` is known to be final.
+    pub fn is_final(&self, height: ChainEpoch) -> bool {
+        height <= self.finalized.load(Ordering::Relaxed)
+    }
+
+    /// Look up the response of `method` called with `params` about the block at `height`.
+    pub fn get<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: &impl Serialize,
+        height: ChainEpoch,
+    ) -> anyhow::Result<Option<T>> {
+        let key = cache_key(method, params, height)?;
+        let entries = self.entries.lock().unwrap();
+        match entries.values.get(&key) {
+            Some(value) => Ok(Some(serde_json::from_value(value.clone()).with_context(
+                || format!("cannot parse the response cached under {key}"),
+            )?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Persist the response of `method` called with `params` about the block at `height`,
+    /// unless the block isn't known to be final. Returns whether it was inserted.
+    pub fn insert(
+        &self,
+        method: &str,
+        params: &impl Serialize,
+        height: ChainEpoch,
+        value: &impl Serialize,
+    ) -> anyhow::Result<bool> {
+        if !self.is_final(height) {
+            return Ok(false);
+        }
+
+        let entry = Entry {
+            key: cache_key(method, params, height)?,
+            value: serde_json::to_value(value)?,
+        };
+        let mut line = serde_json::to_string(&entry)?;
+        line.push('\n');
+
+        let mut entries = self.entries.lock().unwrap();
+        if entries.values.contains_key(&entry.key) {
+            return Ok(false);
+        }
+        if entries.file.is_none() {
+            if let Some(dir) = self.path.parent() {
+                std::fs::create_dir_all(dir)
+                    .with_context(|| format!("cannot create directory {}", dir.display()))?;
+            }
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)
+                .with_context(|| format!("cannot open response cache {}", self.path.display()))?;
+            entries.file = Some(file);
+        }
+        entries
+            .file
+            .as_mut()
+            .expect("file opened")
+            .write_all(line.as_bytes())
+            .with_context(|| format!("cannot write response cache {}", self.path.display()))?;
+        entries.values.insert(entry.key, entry.value);
+
+        Ok(true)
+    }
+
+    /// Return the cached response, or fetch it and cache it if the block is final.
+    ///
+    /// The chain head is only asked for when the block isn't known to be final yet.
+    pub async fn get_or_fetch<T, F, H, HF>(
+        &self,
+        method: &str,
+        params: &impl Serialize,
+        height: ChainEpoch,
+        fetch: F,
+        head: H,
+    ) -> anyhow::Result<T>
+    where
+        T: Serialize + DeserializeOwned,
+        F: Future<Output = anyhow::Result<T>>,
+        H: FnOnce() -> HF,
+        HF: Future<Output = anyhow::Result<ChainEpoch>>,
+    {
+        match self.get(method, params, height) {
+            Ok(Some(value)) => return Ok(value),
+            Ok(None) => {}
+            Err(e) => tracing::warn!("ignoring cached response: {e:#}"),
+        }
+
+        let value = fetch.await?;
+
+        if !self.is_final(height) {
+            match head().await {
+                Ok(head) => self.observe_head(head),
+                Err(e) => {
+                    tracing::warn!("cannot get the chain head to check finality: {e:#}");
+                    return Ok(value);
+                }
+            }
+        }
+        // The response is good either way, the cache only saves fetching it again.
+        if let Err(e) = self.insert(method, params, height, &value) {
+            tracing::warn!("cannot cache the response of {method}: {e:#}");
+        }
+
+        Ok(value)
+    }
+}
+
+/// The key of a response: the method, its parameters and the height, which is usually among
+/// the parameters too, but is spelled out so entries can be told apart by height.
+fn cache_key(method: &str, params: &impl Serialize, height: ChainEpoch) -> anyhow::Result<String> {
+    let params = serde_json::to_string(params).context("cannot serialize the parameters")?;
+    Ok(format!("{method}:{params}@{height}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use anyhow::anyhow;
+    use fvm_shared::clock::ChainEpoch;
+
+    use super::ResponseCache;
+
+    /// Get a made up block hash through the cache, counting the fetches; the head is at 100.
+    async fn fetch(cache: &ResponseCache, fetches: &AtomicUsize, height: ChainEpoch) -> Vec<u8> {
+        cache
+            .get_or_fetch(
+                "get_block_hash",
+                &"/r314159",
+                height,
+                async {
+                    fetches.fetch_add(1, Ordering::SeqCst);
+                    Ok(vec![height as u8; 4])
+                },
+                || async { Ok(100) },
+            )
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn final_responses_are_persisted() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache").join("r314159.jsonl");
+        let fetches = AtomicUsize::new(0);
+
+        {
+            let cache = ResponseCache::open(&path, 10).unwrap();
+            assert_eq!(fetch(&cache, &fetches, 50).await, vec![50; 4]);
+            assert_eq!(fetch(&cache, &fetches, 50).await, vec![50; 4]);
+            // Not final yet, so not cached.
+            assert_eq!(fetch(&cache, &fetches, 95).await, vec![95; 4]);
+            assert_eq!(fetch(&cache, &fetches, 95).await, vec![95; 4]);
+            assert_eq!(fetches.load(Ordering::SeqCst), 3);
+            assert_eq!(cache.len(), 1);
+        }
+
+        // A crash cut the last line short.
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        file.write_all(b"{\"key\":\"get_block").unwrap();
+
+        let cache = ResponseCache::open(&path, 10).unwrap();
+        assert_eq!(cache.len(), 1);
+        assert_eq!(fetch(&cache, &fetches, 50).await, vec![50; 4]);
+        assert_eq!(fetches.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn errors_are_not_cached() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ResponseCache::open(dir.path().join("cache.jsonl"), 0).unwrap();
+        cache.observe_head(10);
+
+        let res = cache
+            .get_or_fetch::<u64, _, _, _>(
+                "get_block_hash",
+                &(),
+                5,
+                async { Err(anyhow!("height does not exist")) },
+                || async { Ok(10) },
+            )
+            .await;
+        assert!(res.is_err());
+        assert!(cache.is_empty());
+        assert!(cache
+            .insert("get_block_hash", &(), 11, &1u64)
+            .map(|inserted| !inserted)
+            .unwrap());
+    }
+}
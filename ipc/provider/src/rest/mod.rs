@@ -0,0 +1,315 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Read-only HTTP gateway over the [IpcProvider], enabled with the `rest` feature, for
+//! integrations that used the queries of the deprecated daemon.
+//!
+//! The endpoints are described by the OpenAPI spec served at `/openapi.json`. Subnet IDs
+//! contain slashes, so they have to be URL encoded in paths, e.g. `/subnets/%2Fr314159%2Ft410f.../validators`.
+
+use std::net::SocketAddr;
+use std::str::FromStr;
+
+use axum::extract::{Path, Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use fvm_shared::address::Address;
+use fvm_shared::clock::ChainEpoch;
+use ipc_api::subnet_id::SubnetID;
+use serde::{Deserialize, Serialize};
+
+use crate::{address_book, IpcProvider};
+
+const OPENAPI_SPEC: &str = include_str!("openapi.json");
+
+/// The default and maximum number of checkpoints returned by one request.
+const DEFAULT_CHECKPOINTS_LIMIT: usize = 10;
+const MAX_CHECKPOINTS_LIMIT: usize = 100;
+
+/// Serve the gateway on `addr` until the server fails.
+pub async fn serve(provider: IpcProvider, addr: SocketAddr) -> anyhow::Result<()> {
+    tracing::info!("serving the ipc provider REST gateway on {addr}");
+    axum::Server::try_bind(&addr)?
+        .serve(router(provider).into_make_service())
+        .await?;
+    Ok(())
+}
+
+/// The routes of the gateway, to be served or nested in another router.
+pub fn router(provider: IpcProvider) -> Router {
+    Router::new()
+        .route("/openapi.json", get(openapi))
+        .route("/subnets", get(list_subnets))
+        .route("/subnets/:id/validators", get(list_validators))
+        .route("/subnets/:id/checkpoints", get(list_checkpoints))
+        .route("/balances/:addr", get(balance))
+        .with_state(provider)
+}
+
+/// An error turned into a JSON response with an `error` message.
+#[derive(Debug)]
+pub struct ApiError(StatusCode, String);
+
+impl ApiError {
+    fn bad_request(msg: impl ToString) -> Self {
+        Self(StatusCode::BAD_REQUEST, msg.to_string())
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(e: anyhow::Error) -> Self {
+        Self(StatusCode::INTERNAL_SERVER_ERROR, format!("{e:#}"))
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(serde_json::json!({ "error": self.1 }))).into_response()
+    }
+}
+
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SubnetView {
+    pub id: String,
+    pub parent: String,
+    /// Collateral staked in the subnet, in atto.
+    pub stake: String,
+    /// Circulating supply of the subnet, in atto.
+    pub circ_supply: String,
+    pub genesis_epoch: ChainEpoch,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ValidatorView {
+    pub address: String,
+    /// Collateral confirmed in the subnet, in atto.
+    pub confirmed_collateral: String,
+    /// Collateral including the changes not yet confirmed, in atto.
+    pub total_collateral: String,
+    pub metadata: String,
+    pub is_active: bool,
+    pub is_waiting: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CheckpointView {
+    pub block_height: ChainEpoch,
+    pub block_hash: String,
+    pub next_configuration_number: u64,
+    /// Number of cross messages carried by the checkpoint.
+    pub msgs: usize,
+    pub signatories: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CheckpointsView {
+    /// The height of the last checkpoint committed in the parent.
+    pub last_committed_height: ChainEpoch,
+    pub checkpoint_period: u64,
+    pub checkpoints: Vec<CheckpointView>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BalanceView {
+    pub address: String,
+    pub subnet: String,
+    /// The balance, in atto.
+    pub balance: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SubnetsQuery {
+    /// The parent to list the children of; all the configured subnets if missing.
+    parent: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ValidatorsQuery {
+    /// Comma separated addresses to report on top of the genesis validators.
+    address: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CheckpointsQuery {
+    /// The height to start from; the last committed checkpoint if missing.
+    from: Option<ChainEpoch>,
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BalanceQuery {
+    subnet: String,
+}
+
+async fn openapi() -> impl IntoResponse {
+    ([(header::CONTENT_TYPE, "application/json")], OPENAPI_SPEC)
+}
+
+async fn list_subnets(
+    State(provider): State<IpcProvider>,
+    Query(query): Query<SubnetsQuery>,
+) -> ApiResult<Vec<SubnetView>> {
+    let parents = match query.parent {
+        Some(parent) => vec![parse_subnet(&parent)?],
+        None => provider.list_connections().into_keys().collect(),
+    };
+
+    let mut subnets = Vec::new();
+    for parent in parents {
+        let children = provider.list_child_subnets(None, &parent).await?;
+        subnets.extend(children.into_values().map(|s| SubnetView {
+            id: s.id.to_string(),
+            parent: parent.to_string(),
+            stake: s.stake.atto().to_string(),
+            circ_supply: s.circ_supply.atto().to_string(),
+            genesis_epoch: s.genesis_epoch,
+        }));
+    }
+    subnets.sort_by(|a, b| a.id.cmp(&b.id));
+
+    Ok(Json(subnets))
+}
+
+/// The genesis validators of the subnet, and any other addresses asked for, with their
+/// current collateral and status as recorded in the parent.
+async fn list_validators(
+    State(provider): State<IpcProvider>,
+    Path(id): Path<String>,
+    Query(query): Query<ValidatorsQuery>,
+) -> ApiResult<Vec<ValidatorView>> {
+    let subnet = parse_subnet(&id)?;
+
+    let mut addresses = provider
+        .get_genesis_info(&subnet)
+        .await?
+        .validators
+        .into_iter()
+        .map(|v| v.addr)
+        .collect::<Vec<_>>();
+
+    for addr in query.address.iter().flat_map(|a| a.split(',')) {
+        let addr = parse_address(addr.trim())?;
+        if !addresses.contains(&addr) {
+            addresses.push(addr);
+        }
+    }
+
+    let mut validators = Vec::with_capacity(addresses.len());
+    for addr in addresses {
+        let info = provider.get_validator_info(&subnet, &addr).await?;
+        validators.push(ValidatorView {
+            address: addr.to_string(),
+            confirmed_collateral: info.staking.confirmed_collateral().atto().to_string(),
+            total_collateral: info.staking.total_collateral().atto().to_string(),
+            metadata: hex::encode(info.staking.metadata()),
+            is_active: info.is_active,
+            is_waiting: info.is_waiting,
+        });
+    }
+
+    Ok(Json(validators))
+}
+
+/// The bottom-up checkpoints cut in the subnet, starting from the last one committed in the
+/// parent, up to the first one that hasn't been cut yet.
+async fn list_checkpoints(
+    State(provider): State<IpcProvider>,
+    Path(id): Path<String>,
+    Query(query): Query<CheckpointsQuery>,
+) -> ApiResult<CheckpointsView> {
+    let subnet = parse_subnet(&id)?;
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_CHECKPOINTS_LIMIT)
+        .min(MAX_CHECKPOINTS_LIMIT);
+
+    let last_committed_height = provider.last_bottom_up_checkpoint_height(&subnet).await?;
+    let checkpoint_period = provider
+        .get_genesis_info(&subnet)
+        .await?
+        .bottom_up_checkpoint_period;
+
+    if checkpoint_period == 0 {
+        return Err(anyhow::anyhow!("subnet has no checkpoint period").into());
+    }
+
+    let mut height = query.from.unwrap_or(last_committed_height).max(0);
+    let mut checkpoints = Vec::new();
+    while checkpoints.len() < limit {
+        let Some(bundle) = provider.get_bottom_up_bundle(&subnet, height).await? else {
+            break;
+        };
+        checkpoints.push(CheckpointView {
+            block_height: bundle.checkpoint.block_height,
+            block_hash: hex::encode(&bundle.checkpoint.block_hash),
+            next_configuration_number: bundle.checkpoint.next_configuration_number,
+            msgs: bundle.checkpoint.msgs.len(),
+            signatories: bundle.signatories.iter().map(|a| a.to_string()).collect(),
+        });
+        height += checkpoint_period as ChainEpoch;
+    }
+
+    Ok(Json(CheckpointsView {
+        last_committed_height,
+        checkpoint_period,
+        checkpoints,
+    }))
+}
+
+async fn balance(
+    State(provider): State<IpcProvider>,
+    Path(addr): Path<String>,
+    Query(query): Query<BalanceQuery>,
+) -> ApiResult<BalanceView> {
+    let address = parse_address(&addr)?;
+    let subnet = parse_subnet(&query.subnet)?;
+    let balance = provider.wallet_balance(&subnet, &address).await?;
+
+    Ok(Json(BalanceView {
+        address: address.to_string(),
+        subnet: subnet.to_string(),
+        balance: balance.atto().to_string(),
+    }))
+}
+
+fn parse_subnet(s: &str) -> Result<SubnetID, ApiError> {
+    SubnetID::from_str(s).map_err(|e| ApiError::bad_request(format!("invalid subnet {s}: {e}")))
+}
+
+fn parse_address(s: &str) -> Result<Address, ApiError> {
+    address_book::parse_address(s).map_err(|e| ApiError::bad_request(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+
+    use super::{parse_address, parse_subnet, OPENAPI_SPEC};
+
+    #[test]
+    fn openapi_spec_is_valid_json() {
+        let spec: serde_json::Value = serde_json::from_str(OPENAPI_SPEC).unwrap();
+        let paths = spec["paths"].as_object().unwrap();
+        for path in [
+            "/subnets",
+            "/subnets/{id}/validators",
+            "/subnets/{id}/checkpoints",
+            "/balances/{addr}",
+        ] {
+            assert!(paths.contains_key(path), "missing {path}");
+        }
+    }
+
+    #[test]
+    fn invalid_arguments_are_bad_requests() {
+        assert_eq!(parse_subnet("nope").unwrap_err().0, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            parse_address("nope").unwrap_err().0,
+            StatusCode::BAD_REQUEST
+        );
+        assert!(parse_address("0x2e714a3c385ea88a09998ed74db265dae9853667").is_ok());
+    }
+}
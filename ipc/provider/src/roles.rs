@@ -0,0 +1,237 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Roles of the keys of the keystore, and the operations only the keys of a role can send.
+//!
+//! Operators label their keys as validator, relayer, treasury or test keys, and declare in the
+//! config the role of the keys sending each type of operation, e.g. that checkpoints are only
+//! ever submitted by the relayer key. [KeyRoles] then picks the senders of these operations,
+//! and refuses them from keys without the role.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::{anyhow, Context};
+use async_trait::async_trait;
+use fvm_shared::address::Address;
+use serde::{Deserialize, Serialize};
+
+use crate::address_book::parse_address;
+use crate::hooks::{OperationDescriptor, OperationHook};
+
+/// The name of the file the roles are persisted to, in the keystore directory.
+pub const DEFAULT_KEY_ROLES_NAME: &str = "key_roles.json";
+
+/// The type of operation of the checkpoints submitted by relayers, which are not sent as
+/// [crate::hooks::Operation]s.
+pub const SUBMIT_CHECKPOINT: &str = "submit_checkpoint";
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+    strum::Display,
+    strum::EnumString,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum KeyRole {
+    Validator,
+    Relayer,
+    Treasury,
+    Test,
+}
+
+/// The roles of the keys, persisted to a JSON file, and the roles the operations require.
+#[derive(Debug)]
+pub struct KeyRoles {
+    path: PathBuf,
+    /// The role of the keys allowed to send each type of operation, by the name of the operation.
+    required: BTreeMap<String, KeyRole>,
+    /// The roles of each key, by address.
+    roles: Mutex<BTreeMap<String, BTreeSet<KeyRole>>>,
+}
+
+impl KeyRoles {
+    /// Open the roles persisted at `path`, which is created on the first role, requiring the
+    /// roles of `required` for the operations.
+    pub fn new(
+        path: impl Into<PathBuf>,
+        required: BTreeMap<String, KeyRole>,
+    ) -> anyhow::Result<Self> {
+        let path = path.into();
+        let roles = if path.exists() {
+            let content = std::fs::read_to_string(&path)
+                .with_context(|| format!("cannot read key roles from {}", path.display()))?;
+            serde_json::from_str(&content)
+                .with_context(|| format!("cannot parse key roles in {}", path.display()))?
+        } else {
+            BTreeMap::new()
+        };
+
+        Ok(Self {
+            path,
+            required,
+            roles: Mutex::new(roles),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The roles of every key with any, by address.
+    pub fn list(&self) -> BTreeMap<String, BTreeSet<KeyRole>> {
+        self.roles.lock().unwrap().clone()
+    }
+
+    /// The roles of `addr`.
+    pub fn roles(&self, addr: &Address) -> BTreeSet<KeyRole> {
+        self.roles
+            .lock()
+            .unwrap()
+            .get(&addr.to_string())
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// The keys with `role`.
+    pub fn keys_with(&self, role: KeyRole) -> anyhow::Result<Vec<Address>> {
+        self.roles
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, roles)| roles.contains(&role))
+            .map(|(addr, _)| parse_address(addr))
+            .collect()
+    }
+
+    /// Give `role` to `addr`, on top of the roles it has.
+    pub fn assign(&self, addr: &Address, role: KeyRole) -> anyhow::Result<()> {
+        let mut roles = self.roles.lock().unwrap();
+        if roles.entry(addr.to_string()).or_default().insert(role) {
+            self.persist(&roles)?;
+        }
+        Ok(())
+    }
+
+    /// Take `role` from `addr`; false if it didn't have it.
+    pub fn unassign(&self, addr: &Address, role: KeyRole) -> anyhow::Result<bool> {
+        let mut roles = self.roles.lock().unwrap();
+        let key = addr.to_string();
+        let Some(key_roles) = roles.get_mut(&key) else {
+            return Ok(false);
+        };
+        if !key_roles.remove(&role) {
+            return Ok(false);
+        }
+        if key_roles.is_empty() {
+            roles.remove(&key);
+        }
+        self.persist(&roles)?;
+        Ok(true)
+    }
+
+    /// The role of the keys allowed to send `operation`, if the config restricts it.
+    pub fn required_role(&self, operation: &str) -> Option<KeyRole> {
+        self.required.get(operation).copied()
+    }
+
+    /// The key to send `operation` with, if the config restricts it to a role: the only key
+    /// with the role. It's an error if no key or more than one has it.
+    pub fn sender_for(&self, operation: &str) -> anyhow::Result<Option<Address>> {
+        let Some(role) = self.required_role(operation) else {
+            return Ok(None);
+        };
+        match self.keys_with(role)?.as_slice() {
+            [] => Err(anyhow!(
+                "{operation} is sent by {role} keys, but there are none"
+            )),
+            [addr] => Ok(Some(*addr)),
+            addrs => Err(anyhow!(
+                "{operation} is sent by {role} keys, pick one of {}",
+                addrs
+                    .iter()
+                    .map(|a| a.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        }
+    }
+
+    /// Check that `from` has the role required to send `operation`, if any.
+    pub fn check(&self, operation: &str, from: &Address) -> anyhow::Result<()> {
+        match self.required_role(operation) {
+            Some(role) if !self.roles(from).contains(&role) => Err(anyhow!(
+                "{operation} can only be sent by {role} keys, and {from} is not one"
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Write the roles to a temporary file first, so a crash can't leave it truncated.
+    fn persist(&self, roles: &BTreeMap<String, BTreeSet<KeyRole>>) -> anyhow::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let content = serde_json::to_string_pretty(roles)?;
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, content)?;
+        std::fs::rename(&tmp, &self.path)
+            .with_context(|| format!("cannot persist key roles to {}", self.path.display()))
+    }
+}
+
+#[async_trait]
+impl OperationHook for KeyRoles {
+    async fn pre_submit(&self, op: &OperationDescriptor) -> anyhow::Result<()> {
+        self.check(op.operation.name(), &op.from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use fvm_shared::address::Address;
+
+    use super::{KeyRole, KeyRoles, SUBMIT_CHECKPOINT};
+
+    #[test]
+    fn operations_are_sent_by_keys_with_their_role() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("key_roles.json");
+        let required = BTreeMap::from([(SUBMIT_CHECKPOINT.to_string(), KeyRole::Relayer)]);
+        let relayer = Address::new_id(100);
+        let treasury = Address::new_id(101);
+
+        let roles = KeyRoles::new(&path, required.clone()).unwrap();
+        assert!(roles.sender_for(SUBMIT_CHECKPOINT).is_err());
+        roles.assign(&relayer, KeyRole::Relayer).unwrap();
+        roles.assign(&treasury, KeyRole::Treasury).unwrap();
+
+        // Reopen as if the process restarted.
+        let roles = KeyRoles::new(&path, required).unwrap();
+        assert_eq!(roles.sender_for(SUBMIT_CHECKPOINT).unwrap(), Some(relayer));
+        assert!(roles.check(SUBMIT_CHECKPOINT, &relayer).is_ok());
+        assert!(roles.check(SUBMIT_CHECKPOINT, &treasury).is_err());
+
+        // Operations without a role can be sent by any key.
+        assert_eq!(roles.sender_for("send_value").unwrap(), None);
+        assert!(roles.check("send_value", &treasury).is_ok());
+
+        // Several keys with the role are ambiguous.
+        roles.assign(&treasury, KeyRole::Relayer).unwrap();
+        assert!(roles.sender_for(SUBMIT_CHECKPOINT).is_err());
+
+        assert!(roles.unassign(&treasury, KeyRole::Relayer).unwrap());
+        assert!(!roles.unassign(&treasury, KeyRole::Relayer).unwrap());
+        assert_eq!(roles.keys_with(KeyRole::Treasury).unwrap(), vec![treasury]);
+    }
+}
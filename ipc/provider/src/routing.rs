@@ -0,0 +1,228 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Routing of cross-net messages through the hierarchy of subnets.
+//!
+//! A message between two subnets travels up from its source to their closest common ancestor,
+//! leaving each subnet in a bottom-up checkpoint, then down to its destination, entering each
+//! subnet as a top-down message. At every hop the message waits in the gateway of the subnet it
+//! is leaving.
+
+use std::fmt::{Display, Formatter};
+
+use anyhow::anyhow;
+use fvm_shared::address::Address;
+use ipc_api::subnet_id::SubnetID;
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::lotus::message::serialize::serialize_subnet_id_to_str;
+
+/// The direction a message moves in the hierarchy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, strum::Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum Direction {
+    /// From a child to its parent, in a bottom-up checkpoint.
+    Up,
+    /// From a parent to its child, as a top-down message.
+    Down,
+}
+
+/// A move of a message between a subnet and its parent or one of its children.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Hop {
+    #[serde(serialize_with = "serialize_subnet_id_to_str")]
+    pub from: SubnetID,
+    #[serde(serialize_with = "serialize_subnet_id_to_str")]
+    pub to: SubnetID,
+    pub direction: Direction,
+}
+
+impl Display for Hop {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} -> {} ({})", self.from, self.to, self.direction)
+    }
+}
+
+/// The path of a message between two subnets of the same root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Route {
+    /// The closest subnet which both ends descend from, or are.
+    pub ancestor: SubnetID,
+    /// The subnets from the source up to the ancestor, including both.
+    pub up: Vec<SubnetID>,
+    /// The subnets from the ancestor down to the destination, excluding the ancestor.
+    pub down: Vec<SubnetID>,
+}
+
+impl Route {
+    /// The subnets the message goes through, from the source to the destination.
+    pub fn subnets(&self) -> impl Iterator<Item = &SubnetID> {
+        self.up.iter().chain(self.down.iter())
+    }
+
+    /// The hops of the message, from the source to the destination; empty if they are the same.
+    pub fn hops(&self) -> Vec<Hop> {
+        let ups = self.up.windows(2).map(|w| Hop {
+            from: w[0].clone(),
+            to: w[1].clone(),
+            direction: Direction::Up,
+        });
+        let downs = std::iter::once(&self.ancestor)
+            .chain(self.down.iter())
+            .collect::<Vec<_>>();
+        let downs = downs.windows(2).map(|w| Hop {
+            from: w[0].clone(),
+            to: w[1].clone(),
+            direction: Direction::Down,
+        });
+        ups.chain(downs).collect()
+    }
+}
+
+/// The closest subnet which both `a` and `b` descend from or are, unless they have different roots.
+pub fn common_ancestor(a: &SubnetID, b: &SubnetID) -> Option<SubnetID> {
+    a.common_parent(b).map(|(_, ancestor)| ancestor)
+}
+
+/// The path of a message from `from` to `to`, unless they have different roots.
+pub fn route(from: &SubnetID, to: &SubnetID) -> Option<Route> {
+    let ancestor = common_ancestor(from, to)?;
+    let depth = ancestor.children_as_ref().len();
+
+    let up = (depth..=from.children_as_ref().len())
+        .rev()
+        .map(|len| prefix(from, len))
+        .collect();
+    let down = (depth + 1..=to.children_as_ref().len())
+        .map(|len| prefix(to, len))
+        .collect();
+
+    Some(Route { ancestor, up, down })
+}
+
+/// The ancestor of `subnet` with the first `len` children of its path.
+fn prefix(subnet: &SubnetID, len: usize) -> SubnetID {
+    SubnetID::new(subnet.root_id(), subnet.children_as_ref()[..len].to_vec())
+}
+
+/// The gateways holding the message at each hop of a route, which is the gateway of the
+/// subnet the hop leaves, as configured.
+pub fn hop_gateways(config: &Config, route: &Route) -> anyhow::Result<Vec<(Hop, Address)>> {
+    route
+        .hops()
+        .into_iter()
+        .map(|hop| {
+            let subnet = config
+                .subnets
+                .get(&hop.from)
+                .ok_or_else(|| anyhow!("subnet {} of hop {hop} is not configured", hop.from))?;
+            let gateway = subnet.gateway_addr();
+            Ok((hop, gateway))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use fvm_shared::address::Address;
+    use ipc_api::subnet_id::SubnetID;
+    use url::Url;
+
+    use super::{common_ancestor, hop_gateways, route, Direction};
+    use crate::config::subnet::{EVMSubnet, SubnetConfig};
+    use crate::config::{Config, Subnet};
+
+    fn id(s: &str) -> SubnetID {
+        SubnetID::from_str(s).unwrap()
+    }
+
+    fn ids(ss: &[&str]) -> Vec<SubnetID> {
+        ss.iter().map(|s| id(s)).collect()
+    }
+
+    #[test]
+    fn test_common_ancestor() {
+        assert_eq!(
+            common_ancestor(&id("/r123/f01/f02"), &id("/r123/f01/f03")),
+            Some(id("/r123/f01"))
+        );
+        assert_eq!(
+            common_ancestor(&id("/r123/f01/f02"), &id("/r123/f01")),
+            Some(id("/r123/f01"))
+        );
+        assert_eq!(
+            common_ancestor(&id("/r123/f01"), &id("/r123/f02")),
+            Some(id("/r123"))
+        );
+        assert_eq!(common_ancestor(&id("/r123/f01"), &id("/r124/f01")), None);
+    }
+
+    #[test]
+    fn test_route() {
+        let r = route(&id("/r123/f01/f02"), &id("/r123/f01/f03/f04")).unwrap();
+        assert_eq!(r.ancestor, id("/r123/f01"));
+        assert_eq!(r.up, ids(&["/r123/f01/f02", "/r123/f01"]));
+        assert_eq!(r.down, ids(&["/r123/f01/f03", "/r123/f01/f03/f04"]));
+
+        let hops = r.hops();
+        assert_eq!(hops.len(), 3);
+        assert_eq!(hops[0].direction, Direction::Up);
+        assert_eq!(hops[1].from, id("/r123/f01"));
+        assert_eq!(hops[1].to, id("/r123/f01/f03"));
+        assert_eq!(hops[2].direction, Direction::Down);
+
+        // Straight down from the root.
+        let r = route(&id("/r123"), &id("/r123/f01/f02")).unwrap();
+        assert_eq!(r.up, ids(&["/r123"]));
+        assert_eq!(
+            r.subnets().cloned().collect::<Vec<_>>(),
+            ids(&["/r123", "/r123/f01", "/r123/f01/f02"])
+        );
+
+        // Straight up to the root.
+        let r = route(&id("/r123/f01/f02"), &id("/r123")).unwrap();
+        assert!(r.down.is_empty());
+        assert!(r.hops().iter().all(|h| h.direction == Direction::Up));
+
+        assert!(route(&id("/r123/f01"), &id("/r123/f01"))
+            .unwrap()
+            .hops()
+            .is_empty());
+        assert!(route(&id("/r123/f01"), &id("/r124/f01")).is_none());
+    }
+
+    #[test]
+    fn test_hop_gateways() {
+        let mut config = Config::new();
+        for (subnet, gateway) in [("/r123", 1), ("/r123/f01", 2)] {
+            config.add_subnet(Subnet {
+                id: id(subnet),
+                config: SubnetConfig::Fevm(EVMSubnet {
+                    provider_http: Url::parse("http://127.0.0.1:8545").unwrap(),
+                    provider_timeout: None,
+                    auth_token: None,
+                    registry_addr: Address::new_id(0),
+                    gateway_addr: Address::new_id(gateway),
+                    admin: None,
+                    response_cache: None,
+                    max_concurrent_requests: None,
+                    fallback_provider_http: vec![],
+                    gas: None,
+                }),
+            });
+        }
+
+        let r = route(&id("/r123/f01"), &id("/r123/f02")).unwrap();
+        let gateways = hop_gateways(&config, &r).unwrap();
+        assert_eq!(
+            gateways.iter().map(|(_, g)| *g).collect::<Vec<_>>(),
+            vec![Address::new_id(2), Address::new_id(1)]
+        );
+
+        let r = route(&id("/r123/f01"), &id("/r123/f01/f02/f03")).unwrap();
+        assert!(hop_gateways(&config, &r).is_err());
+    }
+}
@@ -0,0 +1,271 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! A watcher of the collateral of a validator, topping it up from a reserve account when it
+//! falls below a threshold.
+//!
+//! The collateral is the one including the changes not yet confirmed in the subnet, so that a
+//! pending unstake counts against the validator, and a pending top-up isn't sent twice. A
+//! validator which left the subnet joins it again with the missing collateral.
+
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+use anyhow::anyhow;
+use fvm_shared::address::Address;
+use fvm_shared::econ::TokenAmount;
+use ipc_api::subnet_id::SubnetID;
+use num_traits::Zero;
+use tokio_util::sync::CancellationToken;
+
+use crate::IpcProvider;
+
+/// Where the collateral topping up the validator comes from.
+#[derive(Debug, Clone)]
+pub struct TopUpConfig {
+    /// The account funding the top-ups, which can be the validator itself. The validator pays
+    /// for the gas of staking either way.
+    pub reserve: Address,
+    /// The largest top-up sent at once; a validator missing more is only reported.
+    pub max_amount: TokenAmount,
+}
+
+#[derive(Debug, Clone)]
+pub struct StakeWatcherConfig {
+    pub subnet: SubnetID,
+    pub validator: Address,
+    /// The collateral under which the validator is reported, and topped up.
+    pub threshold: TokenAmount,
+    /// Only report the validator if unset.
+    pub top_up: Option<TopUpConfig>,
+}
+
+/// What the watcher found, or did, at a check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StakeEvent {
+    Healthy {
+        collateral: TokenAmount,
+    },
+    BelowThreshold {
+        collateral: TokenAmount,
+        threshold: TokenAmount,
+    },
+    ToppedUp {
+        amount: TokenAmount,
+        /// The validator had left the subnet, and joined it again.
+        rejoined: bool,
+    },
+    TopUpRefused {
+        amount: TokenAmount,
+        max_amount: TokenAmount,
+    },
+    TopUpFailed {
+        amount: TokenAmount,
+        error: String,
+    },
+}
+
+impl StakeEvent {
+    /// Whether the event needs the attention of an operator.
+    pub fn is_alert(&self) -> bool {
+        !matches!(
+            self,
+            StakeEvent::Healthy { .. } | StakeEvent::ToppedUp { .. }
+        )
+    }
+}
+
+impl Display for StakeEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StakeEvent::Healthy { collateral } => write!(f, "collateral at {collateral} FIL"),
+            StakeEvent::BelowThreshold {
+                collateral,
+                threshold,
+            } => write!(
+                f,
+                "collateral at {collateral} FIL, below the threshold of {threshold} FIL"
+            ),
+            StakeEvent::ToppedUp { amount, rejoined } if *rejoined => {
+                write!(f, "joined the subnet again with {amount} FIL")
+            }
+            StakeEvent::ToppedUp { amount, .. } => write!(f, "topped up with {amount} FIL"),
+            StakeEvent::TopUpRefused { amount, max_amount } => write!(
+                f,
+                "missing {amount} FIL, more than the top-up limit of {max_amount} FIL"
+            ),
+            StakeEvent::TopUpFailed { amount, error } => {
+                write!(f, "cannot top up with {amount} FIL: {error}")
+            }
+        }
+    }
+}
+
+/// The collateral missing to reach the threshold, if any.
+fn missing_collateral(collateral: &TokenAmount, threshold: &TokenAmount) -> Option<TokenAmount> {
+    (collateral < threshold).then(|| threshold - collateral)
+}
+
+/// Watches the collateral of a validator in the parent of its subnet.
+pub struct StakeWatcher {
+    provider: IpcProvider,
+    config: StakeWatcherConfig,
+    shutdown: CancellationToken,
+}
+
+impl StakeWatcher {
+    pub fn new(provider: IpcProvider, config: StakeWatcherConfig) -> Self {
+        Self {
+            provider,
+            config,
+            shutdown: CancellationToken::new(),
+        }
+    }
+
+    /// Stop watching when the signal is cancelled, after the check in progress, if any.
+    pub fn with_shutdown_signal(mut self, shutdown: CancellationToken) -> Self {
+        self.shutdown = shutdown;
+        self
+    }
+
+    /// Check the collateral of the validator once, topping it up if needed.
+    pub async fn check(&mut self) -> anyhow::Result<Vec<StakeEvent>> {
+        let StakeWatcherConfig {
+            subnet,
+            validator,
+            threshold,
+            top_up,
+        } = self.config.clone();
+
+        let info = self
+            .provider
+            .get_validator_info(&subnet, &validator)
+            .await?;
+        let collateral = info.staking.total_collateral().clone();
+
+        let Some(missing) = missing_collateral(&collateral, &threshold) else {
+            return Ok(vec![StakeEvent::Healthy { collateral }]);
+        };
+        let mut events = vec![StakeEvent::BelowThreshold {
+            collateral: collateral.clone(),
+            threshold,
+        }];
+
+        let Some(top_up) = top_up else {
+            return Ok(events);
+        };
+        if missing > top_up.max_amount {
+            events.push(StakeEvent::TopUpRefused {
+                amount: missing,
+                max_amount: top_up.max_amount,
+            });
+            return Ok(events);
+        }
+
+        let rejoined = collateral.is_zero();
+        match self.top_up(&top_up, missing.clone(), rejoined).await {
+            Ok(()) => events.push(StakeEvent::ToppedUp {
+                amount: missing,
+                rejoined,
+            }),
+            Err(e) => events.push(StakeEvent::TopUpFailed {
+                amount: missing,
+                error: format!("{e:#}"),
+            }),
+        }
+        Ok(events)
+    }
+
+    /// Move `amount` from the reserve to the validator in the parent, and stake it.
+    async fn top_up(
+        &mut self,
+        top_up: &TopUpConfig,
+        amount: TokenAmount,
+        rejoin: bool,
+    ) -> anyhow::Result<()> {
+        let subnet = self.config.subnet.clone();
+        let validator = self.config.validator;
+        let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
+
+        if top_up.reserve != validator {
+            self.provider
+                .send_value(&parent, Some(top_up.reserve), validator, amount.clone())
+                .await?;
+        }
+        if rejoin {
+            self.provider
+                .join_subnet(subnet, Some(validator), amount)
+                .await?;
+        } else {
+            self.provider.stake(subnet, Some(validator), amount).await?;
+        }
+        Ok(())
+    }
+
+    /// Check the validator every `interval`, handing the events to `on_event`.
+    pub async fn run(mut self, interval: Duration, on_event: impl Fn(&StakeEvent) + Send) {
+        tracing::info!(
+            "watching the collateral of {} in {}",
+            self.config.validator,
+            self.config.subnet
+        );
+
+        loop {
+            match self.check().await {
+                Ok(events) => {
+                    for event in &events {
+                        if event.is_alert() {
+                            tracing::warn!("validator {}: {event}", self.config.validator);
+                        } else {
+                            tracing::info!("validator {}: {event}", self.config.validator);
+                        }
+                        on_event(event);
+                    }
+                }
+                Err(e) => tracing::error!(
+                    "cannot check the collateral of {}: {e:#}",
+                    self.config.validator
+                ),
+            }
+            tokio::select! {
+                _ = self.shutdown.cancelled() => break,
+                _ = tokio::time::sleep(interval) => {}
+            }
+        }
+
+        tracing::info!("stopped watching {}", self.config.validator);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fvm_shared::econ::TokenAmount;
+
+    use super::{missing_collateral, StakeEvent};
+
+    #[test]
+    fn collateral_below_the_threshold_is_missing() {
+        let threshold = TokenAmount::from_whole(10);
+        assert_eq!(
+            missing_collateral(&TokenAmount::from_whole(4), &threshold),
+            Some(TokenAmount::from_whole(6))
+        );
+        assert_eq!(
+            missing_collateral(&TokenAmount::from_whole(10), &threshold),
+            None
+        );
+
+        let healthy = StakeEvent::Healthy {
+            collateral: threshold.clone(),
+        };
+        let refused = StakeEvent::TopUpRefused {
+            amount: TokenAmount::from_whole(6),
+            max_amount: TokenAmount::from_whole(5),
+        };
+        assert!(!healthy.is_alert());
+        assert!(refused.is_alert());
+        assert_eq!(
+            refused.to_string(),
+            "missing 6 FIL, more than the top-up limit of 5 FIL"
+        );
+    }
+}
@@ -0,0 +1,170 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Comparison of the state of the IPC actors of a subnet between two epochs.
+//!
+//! The state of an actor is read through its getters at each epoch, along with its balance,
+//! nonce and code, and flattened into named fields, so that checkpoint or nonce mismatches
+//! can be traced to the epochs where the fields changed.
+
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+use fvm_shared::clock::ChainEpoch;
+use ipc_api::subnet_id::SubnetID;
+use serde::Serialize;
+
+use crate::lotus::message::serialize::serialize_subnet_id_to_str;
+
+/// The actors whose state can be compared, in the subnet they are deployed in.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StateActor {
+    Gateway,
+    /// The subnet actor of a child subnet, along with what the gateway tracks about the child.
+    SubnetActor(#[serde(serialize_with = "serialize_subnet_id_to_str")] SubnetID),
+    MachineLearning,
+}
+
+impl Display for StateActor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StateActor::Gateway => write!(f, "gateway"),
+            StateActor::SubnetActor(subnet) => write!(f, "subnet actor of {subnet}"),
+            StateActor::MachineLearning => write!(f, "machine learning actor"),
+        }
+    }
+}
+
+/// The state of an actor at an epoch, as named fields.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ActorState {
+    pub address: ethers::types::Address,
+    pub epoch: ChainEpoch,
+    pub fields: BTreeMap<String, String>,
+}
+
+/// A field whose value differs between two epochs; missing at one of them if `None`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+/// The fields of an actor which changed between two epochs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StateDiff {
+    pub actor: StateActor,
+    pub address: ethers::types::Address,
+    pub epoch_a: ChainEpoch,
+    pub epoch_b: ChainEpoch,
+    pub changes: Vec<FieldChange>,
+}
+
+impl StateDiff {
+    pub fn new(actor: StateActor, a: &ActorState, b: &ActorState) -> Self {
+        Self {
+            actor,
+            address: b.address,
+            epoch_a: a.epoch,
+            epoch_b: b.epoch,
+            changes: diff_fields(&a.fields, &b.fields),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl Display for StateDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} at {:?} from epoch {} to {}:",
+            self.actor, self.address, self.epoch_a, self.epoch_b
+        )?;
+        if self.changes.is_empty() {
+            return write!(f, " no changes");
+        }
+        for c in &self.changes {
+            write!(
+                f,
+                "\n  {}: {} -> {}",
+                c.field,
+                c.before.as_deref().unwrap_or("<none>"),
+                c.after.as_deref().unwrap_or("<none>")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The fields which differ between `a` and `b`, in the order of their names.
+pub fn diff_fields(a: &BTreeMap<String, String>, b: &BTreeMap<String, String>) -> Vec<FieldChange> {
+    let mut fields = a.keys().chain(b.keys()).collect::<Vec<_>>();
+    fields.sort();
+    fields.dedup();
+
+    fields
+        .into_iter()
+        .filter_map(|field| {
+            let (before, after) = (a.get(field), b.get(field));
+            (before != after).then(|| FieldChange {
+                field: field.clone(),
+                before: before.cloned(),
+                after: after.cloned(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{diff_fields, ActorState, StateActor, StateDiff};
+
+    fn fields(kvs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        kvs.iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_diff_fields() {
+        let a = fields(&[("nonce", "1"), ("balance", "10"), ("removed", "x")]);
+        let b = fields(&[("nonce", "2"), ("balance", "10"), ("added", "y")]);
+
+        let changes = diff_fields(&a, &b);
+        assert_eq!(
+            changes
+                .iter()
+                .map(|c| (c.field.as_str(), c.before.as_deref(), c.after.as_deref()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("added", None, Some("y")),
+                ("nonce", Some("1"), Some("2")),
+                ("removed", Some("x"), None),
+            ]
+        );
+
+        assert!(diff_fields(&a, &a).is_empty());
+    }
+
+    #[test]
+    fn test_state_diff() {
+        let state = |epoch, nonce| ActorState {
+            address: Default::default(),
+            epoch,
+            fields: fields(&[("bottom_up_nonce", nonce)]),
+        };
+
+        let diff = StateDiff::new(StateActor::Gateway, &state(10, "3"), &state(20, "4"));
+        assert_eq!((diff.epoch_a, diff.epoch_b), (10, 20));
+        assert_eq!(diff.changes.len(), 1);
+        assert!(diff.to_string().contains("bottom_up_nonce: 3 -> 4"));
+
+        assert!(StateDiff::new(StateActor::Gateway, &state(10, "3"), &state(20, "3")).is_empty());
+    }
+}
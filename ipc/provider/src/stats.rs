@@ -0,0 +1,89 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Prometheus metrics of the daemons run by the provider.
+//!
+//! The metrics are global; register them with a [Registry] to export them.
+use lazy_static::lazy_static;
+use prometheus::{IntCounter, IntGauge, Registry};
+
+macro_rules! metrics {
+    ($($name:ident : $type:ty = $make:expr);* $(;)?) => {
+        $(
+          lazy_static! {
+            pub static ref $name: $type = $make.unwrap();
+          }
+        )*
+
+        pub fn register_metrics(registry: &Registry) -> anyhow::Result<()> {
+          $(registry.register(Box::new($name.clone()))?;)*
+          Ok(())
+        }
+    };
+}
+
+metrics! {
+    RELAYER_FINALIZED_HEIGHT: IntGauge = IntGauge::new(
+        "relayer_finalized_height",
+        "Highest final height of the child subnet"
+    );
+
+    RELAYER_POLLED_HEIGHT: IntGauge = IntGauge::new(
+        "relayer_polled_height",
+        "Highest height of the child subnet scanned for checkpoints"
+    );
+
+    RELAYER_SUBMITTED_HEIGHT: IntGauge = IntGauge::new(
+        "relayer_submitted_height",
+        "Height of the last checkpoint submitted to the parent subnet"
+    );
+
+    RELAYER_LAG: IntGauge = IntGauge::new(
+        "relayer_lag",
+        "Number of final child heights not covered by a checkpoint committed in the parent"
+    );
+
+    RELAYER_NEXT_CHECKPOINT_HEIGHT: IntGauge = IntGauge::new(
+        "relayer_next_checkpoint_height",
+        "Height of the next checkpoint expected in the child subnet"
+    );
+
+    RELAYER_NEXT_CHECKPOINT_DUE: IntGauge = IntGauge::new(
+        "relayer_next_checkpoint_due_seconds",
+        "Estimated seconds until the next checkpoint is final in the child subnet"
+    );
+
+    RELAYER_ASSEMBLY_QUEUE: IntGauge = IntGauge::new(
+        "relayer_assembly_queue",
+        "Number of checkpoints waiting for their signature bundle to be fetched"
+    );
+
+    RELAYER_SUBMISSION_QUEUE: IntGauge = IntGauge::new(
+        "relayer_submission_queue",
+        "Number of checkpoint bundles waiting to be submitted"
+    );
+
+    RELAYER_BACKPRESSURE: IntCounter = IntCounter::new(
+        "relayer_backpressure",
+        "Number of times a stage of the relayer waited for the next one to catch up"
+    );
+
+    RELAYER_SUBMISSION_FAILURE: IntCounter = IntCounter::new(
+        "relayer_submission_failure",
+        "Number of failed checkpoint submissions"
+    );
+
+    RELAYER_POSTBOX_SIZE: IntGauge = IntGauge::new(
+        "relayer_postbox_size",
+        "Number of messages waiting in the postbox of the gateway"
+    );
+
+    RELAYER_PROPAGATED_MSGS: IntCounter = IntCounter::new(
+        "relayer_propagated_msgs",
+        "Number of postbox messages propagated by the relayer"
+    );
+
+    RELAYER_PROPAGATION_FAILURE: IntCounter = IntCounter::new(
+        "relayer_propagation_failure",
+        "Number of failed propagations of postbox messages"
+    );
+}
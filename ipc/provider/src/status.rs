@@ -0,0 +1,271 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! The lifecycle of a subnet, derived from what its parent records about it, so that tooling
+//! can wait for a subnet to be ready before acting on it.
+
+use std::fmt::{Display, Formatter};
+
+use anyhow::anyhow;
+use fvm_shared::clock::ChainEpoch;
+use ipc_api::subnet_id::SubnetID;
+use serde::Serialize;
+
+use crate::doctor::MAX_CHECKPOINT_LAG_PERIODS;
+use crate::manager::SubnetLifecycleInfo;
+use crate::IpcProvider;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SubnetState {
+    /// Registered in the parent, without any validator yet.
+    Created,
+    /// Validators are joining, or the subnet bootstrapped and its chain is starting.
+    Bootstrapping,
+    /// Bootstrapped, with enough validators and checkpoints keeping up.
+    Active,
+    /// Bootstrapped, but short of validators, unreachable or with checkpoints lagging behind.
+    Unhealthy,
+    /// Killed in the parent.
+    Killed,
+}
+
+impl Display for SubnetState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            SubnetState::Created => "created",
+            SubnetState::Bootstrapping => "bootstrapping",
+            SubnetState::Active => "active",
+            SubnetState::Unhealthy => "unhealthy",
+            SubnetState::Killed => "killed",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// The state of a subnet, with the data it was derived from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SubnetStatus {
+    pub subnet: String,
+    pub state: SubnetState,
+    /// Why the subnet is in this state.
+    pub reason: String,
+    pub min_validators: u64,
+    pub active_validators: u16,
+    pub total_validators: u16,
+    pub genesis_available: bool,
+    pub last_bottom_up_checkpoint_height: ChainEpoch,
+    pub bottom_up_checkpoint_period: ChainEpoch,
+    /// The head of the subnet, if it's in the config and reachable.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chain_head: Option<ChainEpoch>,
+}
+
+impl Display for SubnetStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "subnet {}: {} ({})",
+            self.subnet, self.state, self.reason
+        )?;
+        writeln!(
+            f,
+            "validators: {} active, {} in total, {} needed",
+            self.active_validators, self.total_validators, self.min_validators
+        )?;
+        write!(
+            f,
+            "last checkpoint: {} (period {})",
+            self.last_bottom_up_checkpoint_height, self.bottom_up_checkpoint_period
+        )?;
+        if let Some(head) = self.chain_head {
+            write!(f, "\nchain head: {head}")?;
+        }
+        Ok(())
+    }
+}
+
+/// What could be learnt about the chain of the subnet itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ChainHead {
+    /// The subnet is not in the config, so its chain can't be queried.
+    NotConfigured,
+    Unreachable(String),
+    At(ChainEpoch),
+}
+
+/// Derive the state of a subnet from the data of its parent and the head of its chain.
+fn derive_state(
+    info: &SubnetLifecycleInfo,
+    genesis_available: bool,
+    head: &ChainHead,
+) -> (SubnetState, String) {
+    if info.killed {
+        return (SubnetState::Killed, "killed in the parent".to_string());
+    }
+    if !info.bootstrapped {
+        return if info.total_validators == 0 {
+            (SubnetState::Created, "no validator joined yet".to_string())
+        } else {
+            (
+                SubnetState::Bootstrapping,
+                format!(
+                    "{} of the {} validators needed joined",
+                    info.total_validators, info.min_validators
+                ),
+            )
+        };
+    }
+    if !genesis_available {
+        return (
+            SubnetState::Bootstrapping,
+            "the genesis is not available from the parent yet".to_string(),
+        );
+    }
+    if (info.active_validators as u64) < info.min_validators {
+        return (
+            SubnetState::Unhealthy,
+            format!(
+                "{} active validators, below the minimum of {}",
+                info.active_validators, info.min_validators
+            ),
+        );
+    }
+
+    let last = info.last_bottom_up_checkpoint_height;
+    match head {
+        ChainHead::NotConfigured => (
+            SubnetState::Active,
+            "bootstrapped; checkpoints not checked, the subnet is not in the config".to_string(),
+        ),
+        // A subnet which never checkpointed is most likely still starting its nodes.
+        ChainHead::Unreachable(e) if last == 0 => (
+            SubnetState::Bootstrapping,
+            format!("bootstrapped, but its chain is not reachable yet: {e}"),
+        ),
+        ChainHead::Unreachable(e) => (
+            SubnetState::Unhealthy,
+            format!("its chain is not reachable: {e}"),
+        ),
+        ChainHead::At(head)
+            if head - last > MAX_CHECKPOINT_LAG_PERIODS * info.bottom_up_checkpoint_period =>
+        {
+            (
+                SubnetState::Unhealthy,
+                format!("last checkpoint at {last}, {} epochs behind", head - last),
+            )
+        }
+        ChainHead::At(_) => (SubnetState::Active, format!("last checkpoint at {last}")),
+    }
+}
+
+impl IpcProvider {
+    /// Get where `subnet` is in its lifecycle, from the data of its parent and, if the subnet is
+    /// in the config, the head of its chain.
+    pub async fn subnet_status(&self, subnet: &SubnetID) -> anyhow::Result<SubnetStatus> {
+        let parent = subnet.parent().ok_or_else(|| anyhow!("no parent found"))?;
+        let conn = self.get_connection(&parent)?;
+        let info = conn.manager().get_lifecycle_info(subnet).await?;
+
+        let live = info.bootstrapped && !info.killed;
+        let genesis_available = live && conn.manager().get_genesis_info(subnet).await.is_ok();
+        let head = if !live || self.connection(subnet).is_none() {
+            ChainHead::NotConfigured
+        } else {
+            match self.chain_head(subnet).await {
+                Ok(head) => ChainHead::At(head),
+                Err(e) => ChainHead::Unreachable(format!("{e:#}")),
+            }
+        };
+
+        let (state, reason) = derive_state(&info, genesis_available, &head);
+        Ok(SubnetStatus {
+            subnet: subnet.to_string(),
+            state,
+            reason,
+            min_validators: info.min_validators,
+            active_validators: info.active_validators,
+            total_validators: info.total_validators,
+            genesis_available,
+            last_bottom_up_checkpoint_height: info.last_bottom_up_checkpoint_height,
+            bottom_up_checkpoint_period: info.bottom_up_checkpoint_period,
+            chain_head: match head {
+                ChainHead::At(head) => Some(head),
+                _ => None,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{derive_state, ChainHead, SubnetState};
+    use crate::manager::SubnetLifecycleInfo;
+
+    fn info() -> SubnetLifecycleInfo {
+        SubnetLifecycleInfo {
+            bootstrapped: false,
+            killed: false,
+            min_validators: 3,
+            active_validators: 0,
+            total_validators: 0,
+            last_bottom_up_checkpoint_height: 0,
+            bottom_up_checkpoint_period: 10,
+        }
+    }
+
+    #[test]
+    fn states_follow_the_lifecycle() {
+        let state = |info: &SubnetLifecycleInfo, genesis, head: ChainHead| {
+            derive_state(info, genesis, &head).0
+        };
+
+        let mut info = info();
+        assert_eq!(
+            state(&info, false, ChainHead::NotConfigured),
+            SubnetState::Created
+        );
+
+        info.total_validators = 2;
+        assert_eq!(
+            state(&info, false, ChainHead::NotConfigured),
+            SubnetState::Bootstrapping
+        );
+
+        info.bootstrapped = true;
+        info.total_validators = 3;
+        info.active_validators = 3;
+        assert_eq!(
+            state(&info, false, ChainHead::NotConfigured),
+            SubnetState::Bootstrapping
+        );
+        assert_eq!(
+            state(&info, true, ChainHead::Unreachable("refused".into())),
+            SubnetState::Bootstrapping
+        );
+        assert_eq!(state(&info, true, ChainHead::At(15)), SubnetState::Active);
+        assert_eq!(
+            state(&info, true, ChainHead::NotConfigured),
+            SubnetState::Active
+        );
+
+        // Checkpoints lagging more than two periods behind.
+        info.last_bottom_up_checkpoint_height = 10;
+        assert_eq!(
+            state(&info, true, ChainHead::At(31)),
+            SubnetState::Unhealthy
+        );
+        assert_eq!(
+            state(&info, true, ChainHead::Unreachable("refused".into())),
+            SubnetState::Unhealthy
+        );
+
+        info.active_validators = 2;
+        assert_eq!(
+            state(&info, true, ChainHead::At(20)),
+            SubnetState::Unhealthy
+        );
+
+        info.killed = true;
+        assert_eq!(state(&info, true, ChainHead::At(20)), SubnetState::Killed);
+    }
+}
@@ -0,0 +1,235 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Durable state of the provider, in a directory of the repo.
+//!
+//! The operations submitted under idempotency keys, with the transactions they sent, outlive
+//! the process which submitted them, and need more than a JSON file rewritten on every change.
+//! A [ProviderStore] keeps them as key-value pairs in separate [Namespace]s of an embedded
+//! database; the default one is RocksDB, with the `rocksdb` feature.
+//!
+//! The layout of the store is versioned: opening a store written by an older version migrates
+//! it, and opening one written by a newer version fails rather than corrupting it.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+use anyhow::{anyhow, Context};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+#[cfg(feature = "rocksdb")]
+mod rocks;
+
+#[cfg(feature = "rocksdb")]
+pub use rocks::RocksStore;
+
+/// The directory the store is persisted to, in the keystore directory.
+pub const DEFAULT_STORE_DIR: &str = "store";
+
+/// The version of the layout of the store written by this version of the provider.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The key of the schema version in [Namespace::Meta].
+const SCHEMA_VERSION_KEY: &[u8] = b"schema_version";
+
+/// The separate key spaces of the store.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, strum::Display, strum::EnumIter, strum::AsRefStr,
+)]
+#[strum(serialize_all = "snake_case")]
+pub enum Namespace {
+    /// Bookkeeping of the store itself, like its schema version.
+    Meta,
+    /// Operations submitted under an idempotency key.
+    Idempotency,
+}
+
+/// A durable key-value store, split in namespaces.
+///
+/// Keys within a namespace are ordered bytewise, so keys sharing a prefix, e.g. a subnet ID,
+/// can be scanned together.
+pub trait ProviderStore: Send + Sync {
+    fn get(&self, ns: Namespace, key: &[u8]) -> anyhow::Result<Option<Vec<u8>>>;
+
+    fn put(&self, ns: Namespace, key: &[u8], value: &[u8]) -> anyhow::Result<()>;
+
+    fn delete(&self, ns: Namespace, key: &[u8]) -> anyhow::Result<()>;
+
+    /// The entries whose key starts with `prefix`, in the order of their keys.
+    fn scan_prefix(&self, ns: Namespace, prefix: &[u8]) -> anyhow::Result<Vec<(Vec<u8>, Vec<u8>)>>;
+}
+
+impl dyn ProviderStore {
+    /// Get a value stored with [Self::put_json].
+    pub fn get_json<T: DeserializeOwned>(
+        &self,
+        ns: Namespace,
+        key: &[u8],
+    ) -> anyhow::Result<Option<T>> {
+        match self.get(ns, key)? {
+            Some(value) => Ok(Some(serde_json::from_slice(&value).with_context(|| {
+                format!("cannot parse {ns} entry {}", String::from_utf8_lossy(key))
+            })?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Store a value encoded as JSON.
+    pub fn put_json<T: Serialize>(
+        &self,
+        ns: Namespace,
+        key: &[u8],
+        value: &T,
+    ) -> anyhow::Result<()> {
+        self.put(ns, key, &serde_json::to_vec(value)?)
+    }
+
+    /// The version of the layout the store was written with; `None` if it's empty.
+    pub fn schema_version(&self) -> anyhow::Result<Option<u32>> {
+        self.get_json(Namespace::Meta, SCHEMA_VERSION_KEY)
+    }
+}
+
+/// Bring the layout of a newly opened store to [SCHEMA_VERSION].
+pub(crate) fn init_schema(store: &dyn ProviderStore) -> anyhow::Result<()> {
+    match store.schema_version()? {
+        None => {}
+        Some(SCHEMA_VERSION) => return Ok(()),
+        Some(version) if version > SCHEMA_VERSION => {
+            return Err(anyhow!(
+                "the store has schema version {version} from a newer version of the provider, \
+                 which supports up to {SCHEMA_VERSION}"
+            ))
+        }
+        // There is only one version so far; migrations from older ones go here.
+        Some(version) => return Err(anyhow!("unknown store schema version {version}")),
+    }
+    store.put_json(Namespace::Meta, SCHEMA_VERSION_KEY, &SCHEMA_VERSION)
+}
+
+/// A store kept in memory, for tests and providers without a repo.
+#[derive(Default)]
+pub struct MemoryStore {
+    entries: Mutex<HashMap<Namespace, BTreeMap<Vec<u8>, Vec<u8>>>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> anyhow::Result<Self> {
+        let store = Self::default();
+        init_schema(&store)?;
+        Ok(store)
+    }
+}
+
+impl ProviderStore for MemoryStore {
+    fn get(&self, ns: Namespace, key: &[u8]) -> anyhow::Result<Option<Vec<u8>>> {
+        let entries = self.entries.lock().unwrap();
+        Ok(entries.get(&ns).and_then(|ns| ns.get(key)).cloned())
+    }
+
+    fn put(&self, ns: Namespace, key: &[u8], value: &[u8]) -> anyhow::Result<()> {
+        let mut entries = self.entries.lock().unwrap();
+        entries
+            .entry(ns)
+            .or_default()
+            .insert(key.to_vec(), value.to_vec());
+        Ok(())
+    }
+
+    fn delete(&self, ns: Namespace, key: &[u8]) -> anyhow::Result<()> {
+        let mut entries = self.entries.lock().unwrap();
+        if let Some(ns) = entries.get_mut(&ns) {
+            ns.remove(key);
+        }
+        Ok(())
+    }
+
+    fn scan_prefix(&self, ns: Namespace, prefix: &[u8]) -> anyhow::Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        let entries = self.entries.lock().unwrap();
+        let Some(ns) = entries.get(&ns) else {
+            return Ok(Vec::new());
+        };
+        Ok(ns
+            .range(prefix.to_vec()..)
+            .take_while(|(k, _)| k.starts_with(prefix))
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{init_schema, MemoryStore, Namespace, ProviderStore, SCHEMA_VERSION};
+
+    /// Exercise a store the way every implementation should behave.
+    pub(crate) fn check_store(store: &dyn ProviderStore) {
+        assert_eq!(store.schema_version().unwrap(), Some(SCHEMA_VERSION));
+
+        store
+            .put(Namespace::Idempotency, b"/r314159/0xa", b"1")
+            .unwrap();
+        store
+            .put(Namespace::Idempotency, b"/r314159/0xb", b"2")
+            .unwrap();
+        store
+            .put(Namespace::Idempotency, b"/r314160/0xa", b"3")
+            .unwrap();
+        // Namespaces don't see each other's keys.
+        store.put(Namespace::Meta, b"/r314159/0xc", b"4").unwrap();
+
+        assert_eq!(
+            store.get(Namespace::Idempotency, b"/r314159/0xa").unwrap(),
+            Some(b"1".to_vec())
+        );
+        assert_eq!(store.get(Namespace::Meta, b"/r314159/0xa").unwrap(), None);
+
+        let keys = |prefix: &[u8]| {
+            store
+                .scan_prefix(Namespace::Idempotency, prefix)
+                .unwrap()
+                .into_iter()
+                .map(|(k, _)| String::from_utf8(k).unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(keys(b"/r314159/"), vec!["/r314159/0xa", "/r314159/0xb"]);
+        assert_eq!(
+            keys(b""),
+            vec!["/r314159/0xa", "/r314159/0xb", "/r314160/0xa"]
+        );
+
+        store
+            .delete(Namespace::Idempotency, b"/r314159/0xa")
+            .unwrap();
+        assert_eq!(keys(b"/r314159/"), vec!["/r314159/0xb"]);
+
+        store
+            .put_json(Namespace::Idempotency, b"key", &vec![1u64, 2])
+            .unwrap();
+        assert_eq!(
+            store
+                .get_json::<Vec<u64>>(Namespace::Idempotency, b"key")
+                .unwrap(),
+            Some(vec![1, 2])
+        );
+    }
+
+    #[test]
+    fn memory_store() {
+        let store = MemoryStore::new().unwrap();
+        check_store(&store);
+    }
+
+    #[test]
+    fn newer_schemas_are_refused() {
+        let store = MemoryStore::new().unwrap();
+        let store: &dyn ProviderStore = &store;
+        // Opening again is a no-op.
+        init_schema(store).unwrap();
+
+        store
+            .put_json(Namespace::Meta, b"schema_version", &(SCHEMA_VERSION + 1))
+            .unwrap();
+        let err = init_schema(store).unwrap_err();
+        assert!(err.to_string().contains("newer version"), "{err}");
+    }
+}
@@ -0,0 +1,101 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! The RocksDB implementation of the [ProviderStore], with a column family per namespace.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context};
+use rocksdb::{ColumnFamily, Direction, IteratorMode, Options, DB};
+use strum::IntoEnumIterator;
+
+use super::{init_schema, Namespace, ProviderStore};
+
+/// A [ProviderStore] persisted in a RocksDB database.
+///
+/// RocksDB locks its directory, so only one process can open the store at a time.
+pub struct RocksStore {
+    path: PathBuf,
+    db: DB,
+}
+
+impl RocksStore {
+    /// Open the store at `path`, creating it if it doesn't exist, and bring its layout up to
+    /// date.
+    pub fn open(path: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        let path = path.into();
+        let mut options = Options::default();
+        options.create_if_missing(true);
+        options.create_missing_column_families(true);
+
+        let db = DB::open_cf(&options, &path, Namespace::iter().map(|ns| ns.as_ref()))
+            .with_context(|| format!("cannot open the provider store {}", path.display()))?;
+
+        let store = Self { path, db };
+        init_schema(&store)?;
+        Ok(store)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn cf(&self, ns: Namespace) -> anyhow::Result<&ColumnFamily> {
+        self.db
+            .cf_handle(ns.as_ref())
+            .ok_or_else(|| anyhow!("column family {ns} missing from {}", self.path.display()))
+    }
+}
+
+impl ProviderStore for RocksStore {
+    fn get(&self, ns: Namespace, key: &[u8]) -> anyhow::Result<Option<Vec<u8>>> {
+        Ok(self.db.get_cf(self.cf(ns)?, key)?)
+    }
+
+    fn put(&self, ns: Namespace, key: &[u8], value: &[u8]) -> anyhow::Result<()> {
+        Ok(self.db.put_cf(self.cf(ns)?, key, value)?)
+    }
+
+    fn delete(&self, ns: Namespace, key: &[u8]) -> anyhow::Result<()> {
+        Ok(self.db.delete_cf(self.cf(ns)?, key)?)
+    }
+
+    fn scan_prefix(&self, ns: Namespace, prefix: &[u8]) -> anyhow::Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        let mut entries = Vec::new();
+        let iter = self
+            .db
+            .iterator_cf(self.cf(ns)?, IteratorMode::From(prefix, Direction::Forward));
+        for entry in iter {
+            let (k, v) = entry?;
+            if !k.starts_with(prefix) {
+                break;
+            }
+            entries.push((k.into_vec(), v.into_vec()));
+        }
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RocksStore;
+    use crate::store::tests::check_store;
+    use crate::store::{Namespace, ProviderStore};
+
+    #[test]
+    fn rocks_store_persists() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store");
+        {
+            let store = RocksStore::open(&path).unwrap();
+            check_store(&store);
+        }
+
+        let store = RocksStore::open(&path).unwrap();
+        assert_eq!(
+            store.get(Namespace::Idempotency, b"/r314159/0xb").unwrap(),
+            Some(b"2".to_vec())
+        );
+        // Locked while open.
+        assert!(RocksStore::open(&path).is_err());
+    }
+}
@@ -0,0 +1,211 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Supervision of background tasks.
+//!
+//! Work which outlives the call that started it, like broadcasting signatures or submitting
+//! checkpoints, is spawned through a [TaskSupervisor] instead of being detached with
+//! `tokio::spawn`. On shutdown the supervisor signals the tasks to stop retrying, waits for the
+//! ones in flight to finish, and aborts whatever is still running after a grace period, so that
+//! nothing is silently dropped halfway and nothing keeps retrying in the background.
+
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures_util::FutureExt;
+use tokio::task::{JoinError, JoinSet};
+use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
+
+/// What happened to the tasks which were running when the supervisor was shut down.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ShutdownReport {
+    /// Tasks which finished within the grace period.
+    pub completed: usize,
+    /// Tasks which panicked.
+    pub failed: usize,
+    /// Tasks which were still running at the end of the grace period.
+    pub aborted: usize,
+}
+
+/// Keeps track of background tasks and shuts them down together.
+///
+/// Clones share the same set of tasks.
+#[derive(Clone, Default)]
+pub struct TaskSupervisor {
+    tasks: Arc<Mutex<JoinSet<()>>>,
+    shutdown: CancellationToken,
+}
+
+impl TaskSupervisor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run a task in the background, unless the supervisor is shutting down.
+    ///
+    /// Returns whether the task was started. Errors are up to the task to handle; the name is
+    /// only used to tell tasks apart in the logs.
+    pub fn spawn<F>(&self, name: &'static str, task: F) -> bool
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        if self.is_shutting_down() {
+            tracing::warn!(task = name, "not starting task while shutting down");
+            return false;
+        }
+
+        let mut tasks = self.tasks.lock().expect("task supervisor poisoned");
+        Self::reap(&mut tasks);
+        tasks.spawn(task.instrument(tracing::debug_span!("task", name)));
+        true
+    }
+
+    /// A signal which is set when the supervisor starts shutting down; tasks which retry or
+    /// loop should stop at the next opportunity when it is cancelled.
+    pub fn shutdown_signal(&self) -> CancellationToken {
+        self.shutdown.clone()
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutdown.is_cancelled()
+    }
+
+    /// Number of tasks which haven't finished yet.
+    pub fn running(&self) -> usize {
+        let mut tasks = self.tasks.lock().expect("task supervisor poisoned");
+        Self::reap(&mut tasks);
+        tasks.len()
+    }
+
+    /// Signal the tasks to stop, wait up to `grace` for them to finish, then abort the rest.
+    pub async fn shutdown(&self, grace: Duration) -> ShutdownReport {
+        self.shutdown.cancel();
+
+        // No more tasks can be added once the signal is set, so the set can be taken out.
+        let mut tasks = std::mem::take(&mut *self.tasks.lock().expect("task supervisor poisoned"));
+        let mut report = ShutdownReport::default();
+
+        if !tasks.is_empty() {
+            tracing::info!(
+                tasks = tasks.len(),
+                "waiting for background tasks to finish"
+            );
+        }
+
+        let drained = tokio::time::timeout(grace, async {
+            while let Some(res) = tasks.join_next().await {
+                Self::count(&mut report, res);
+            }
+        })
+        .await;
+
+        if drained.is_err() {
+            report.aborted = tasks.len();
+            tracing::warn!(
+                tasks = report.aborted,
+                "aborting background tasks still running after {grace:?}"
+            );
+            tasks.shutdown().await;
+        }
+
+        report
+    }
+
+    /// Drop the results of the finished tasks, so the set doesn't grow forever.
+    fn reap(tasks: &mut JoinSet<()>) {
+        while let Some(Some(res)) = tasks.join_next().now_or_never() {
+            Self::count(&mut ShutdownReport::default(), res);
+        }
+    }
+
+    fn count(report: &mut ShutdownReport, res: Result<(), JoinError>) {
+        match res {
+            Ok(()) => report.completed += 1,
+            Err(e) if e.is_panic() => {
+                tracing::error!(error = e.to_string(), "background task panicked");
+                report.failed += 1;
+            }
+            // Only aborted by the shutdown, which counts them separately.
+            Err(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use super::{ShutdownReport, TaskSupervisor};
+
+    #[tokio::test]
+    async fn shutdown_waits_for_tasks_in_flight() {
+        let supervisor = TaskSupervisor::new();
+        let done = Arc::new(AtomicUsize::new(0));
+
+        for _ in 0..3 {
+            let done = done.clone();
+            assert!(supervisor.spawn("test", async move {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                done.fetch_add(1, Ordering::SeqCst);
+            }));
+        }
+        assert_eq!(supervisor.running(), 3);
+
+        let report = supervisor.shutdown(Duration::from_secs(5)).await;
+        assert_eq!(
+            report,
+            ShutdownReport {
+                completed: 3,
+                failed: 0,
+                aborted: 0
+            }
+        );
+        assert_eq!(done.load(Ordering::SeqCst), 3);
+
+        // Nothing starts after the shutdown.
+        assert!(!supervisor.spawn("late", async {}));
+        assert_eq!(supervisor.running(), 0);
+    }
+
+    #[tokio::test]
+    async fn shutdown_stops_retries_and_aborts_stragglers() {
+        let supervisor = TaskSupervisor::new();
+        let attempts = Arc::new(AtomicUsize::new(0));
+
+        // A retry loop which gives up when signalled.
+        let signal = supervisor.shutdown_signal();
+        let counter = attempts.clone();
+        supervisor.spawn("retry", async move {
+            loop {
+                counter.fetch_add(1, Ordering::SeqCst);
+                tokio::select! {
+                    _ = signal.cancelled() => break,
+                    _ = tokio::time::sleep(Duration::from_millis(10)) => {}
+                }
+            }
+        });
+
+        // A task which ignores the signal.
+        supervisor.spawn("stuck", std::future::pending());
+        supervisor.spawn("panic", async { panic!("boom") });
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let report = supervisor.shutdown(Duration::from_millis(100)).await;
+        assert_eq!(
+            report,
+            ShutdownReport {
+                completed: 1,
+                failed: 1,
+                aborted: 1
+            }
+        );
+
+        let after = attempts.load(Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(attempts.load(Ordering::SeqCst), after, "zombie retries");
+    }
+}
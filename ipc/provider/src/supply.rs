@@ -0,0 +1,211 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Accounting of the supply of a subnet between its parent and itself.
+//!
+//! The gateway of the parent keeps the collateral of the validators and the supply backing the
+//! child, which is the genesis supply, plus what was funded, minus the releases committed in
+//! checkpoints. The child mints the funds once it executes them and burns the releases when
+//! they're sent, so the supply it reports only matches the parent once the messages in flight
+//! both ways are taken into account:
+//!
+//! `child supply = parent supply - pending top-down funds - pending bottom-up releases`
+//!
+//! The figures are read from both subnets one after the other rather than at the same height,
+//! so a violation caused by a message moving between the reads disappears on the next report.
+
+use std::fmt::{Display, Formatter};
+
+use fvm_shared::econ::TokenAmount;
+use ipc_api::subnet_id::SubnetID;
+use num_traits::Zero;
+use serde::Serialize;
+
+use crate::lotus::message::serialize::{
+    serialize_some_token_amount_to_atto, serialize_subnet_id_to_str, serialize_token_amount_to_atto,
+};
+
+/// What the parent and the child of a subnet hold of its supply.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SupplyReport {
+    #[serde(serialize_with = "serialize_subnet_id_to_str")]
+    pub subnet: SubnetID,
+    /// The collateral of the validators, locked in the gateway of the parent.
+    #[serde(serialize_with = "serialize_token_amount_to_atto")]
+    pub locked_collateral: TokenAmount,
+    /// The supply the gateway of the parent holds for the child.
+    #[serde(serialize_with = "serialize_token_amount_to_atto")]
+    pub parent_circ_supply: TokenAmount,
+    /// The circulating supply reported by the child.
+    #[serde(serialize_with = "serialize_token_amount_to_atto")]
+    pub child_circ_supply: TokenAmount,
+    /// The value of the top-down messages not executed by the child yet.
+    #[serde(serialize_with = "serialize_token_amount_to_atto")]
+    pub pending_top_down: TokenAmount,
+    /// The value of the bottom-up messages not committed in the parent yet.
+    #[serde(serialize_with = "serialize_token_amount_to_atto")]
+    pub pending_bottom_up: TokenAmount,
+    /// The native balance of the gateway of the parent, shared by all its children.
+    #[serde(serialize_with = "serialize_token_amount_to_atto")]
+    pub gateway_balance: TokenAmount,
+    /// The balance of the gateway of the parent in the ERC-20 token backing the supply of the
+    /// child, if it's not the native currency.
+    #[serde(serialize_with = "serialize_some_token_amount_to_atto")]
+    pub gateway_token_balance: Option<TokenAmount>,
+    /// The accounting rules the figures break.
+    pub violations: Vec<String>,
+}
+
+impl SupplyReport {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        subnet: SubnetID,
+        locked_collateral: TokenAmount,
+        parent_circ_supply: TokenAmount,
+        child_circ_supply: TokenAmount,
+        pending_top_down: TokenAmount,
+        pending_bottom_up: TokenAmount,
+        gateway_balance: TokenAmount,
+        gateway_token_balance: Option<TokenAmount>,
+    ) -> Self {
+        let mut report = Self {
+            subnet,
+            locked_collateral,
+            parent_circ_supply,
+            child_circ_supply,
+            pending_top_down,
+            pending_bottom_up,
+            gateway_balance,
+            gateway_token_balance,
+            violations: vec![],
+        };
+        report.violations = report.check();
+        report
+    }
+
+    /// The supply the child should report given the parent and the messages in flight.
+    pub fn expected_child_circ_supply(&self) -> TokenAmount {
+        &self.parent_circ_supply - &self.pending_top_down - &self.pending_bottom_up
+    }
+
+    pub fn is_solvent(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    fn check(&self) -> Vec<String> {
+        let mut violations = vec![];
+
+        let expected = self.expected_child_circ_supply();
+        if expected < TokenAmount::zero() {
+            violations.push(format!(
+                "the messages in flight ({} FIL down, {} FIL up) exceed the supply held by the parent ({} FIL)",
+                self.pending_top_down, self.pending_bottom_up, self.parent_circ_supply
+            ));
+        } else if self.child_circ_supply != expected {
+            violations.push(format!(
+                "the child reports a supply of {} FIL, but the parent and the messages in flight account for {} FIL",
+                self.child_circ_supply, expected
+            ));
+        }
+
+        // The native balance of the gateway backs the collateral, and the supply unless an
+        // ERC-20 token does.
+        let native_backing = match &self.gateway_token_balance {
+            None => &self.locked_collateral + &self.parent_circ_supply,
+            Some(_) => self.locked_collateral.clone(),
+        };
+        if self.gateway_balance < native_backing {
+            violations.push(format!(
+                "the gateway of the parent holds {} FIL, less than the {} FIL it backs for this subnet alone",
+                self.gateway_balance, native_backing
+            ));
+        }
+        if let Some(token_balance) = &self.gateway_token_balance {
+            if token_balance < &self.parent_circ_supply {
+                violations.push(format!(
+                    "the gateway of the parent holds {} of the supply token, less than the supply of {}",
+                    token_balance.atto(),
+                    self.parent_circ_supply.atto()
+                ));
+            }
+        }
+
+        violations
+    }
+}
+
+impl Display for SupplyReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "subnet: {}", self.subnet)?;
+        writeln!(f, "locked collateral: {} FIL", self.locked_collateral)?;
+        writeln!(
+            f,
+            "supply held by the parent: {} FIL",
+            self.parent_circ_supply
+        )?;
+        writeln!(f, "supply of the child: {} FIL", self.child_circ_supply)?;
+        writeln!(f, "pending top-down funds: {} FIL", self.pending_top_down)?;
+        writeln!(
+            f,
+            "pending bottom-up releases: {} FIL",
+            self.pending_bottom_up
+        )?;
+        write!(f, "gateway balance: {} FIL", self.gateway_balance)?;
+        if let Some(balance) = &self.gateway_token_balance {
+            write!(f, "\ngateway token balance: {}", balance.atto())?;
+        }
+        if self.violations.is_empty() {
+            write!(f, "\nno violations")
+        } else {
+            for v in &self.violations {
+                write!(f, "\nviolation: {v}")?;
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use fvm_shared::econ::TokenAmount;
+    use ipc_api::subnet_id::SubnetID;
+
+    use super::SupplyReport;
+
+    fn report(child: u64, gateway: u64, token: Option<u64>) -> SupplyReport {
+        SupplyReport::new(
+            SubnetID::from_str("/r123/f01").unwrap(),
+            TokenAmount::from_whole(10),
+            TokenAmount::from_whole(100),
+            TokenAmount::from_whole(child),
+            TokenAmount::from_whole(5),
+            TokenAmount::from_whole(3),
+            TokenAmount::from_whole(gateway),
+            token.map(TokenAmount::from_whole),
+        )
+    }
+
+    #[test]
+    fn test_balanced_supply() {
+        let r = report(92, 110, None);
+        assert_eq!(r.expected_child_circ_supply(), TokenAmount::from_whole(92));
+        assert!(r.is_solvent());
+
+        // The supply is backed by the token, only the collateral by the native balance.
+        assert!(report(92, 10, Some(100)).is_solvent());
+    }
+
+    #[test]
+    fn test_violations() {
+        // The child minted more than the parent holds.
+        let r = report(95, 110, None);
+        assert_eq!(r.violations.len(), 1);
+        assert!(r.violations[0].contains("reports a supply of 95"));
+
+        // The gateway can't back the collateral and the supply.
+        assert_eq!(report(92, 109, None).violations.len(), 1);
+        assert_eq!(report(92, 10, Some(99)).violations.len(), 1);
+        assert_eq!(report(92, 9, Some(100)).violations.len(), 1);
+    }
+}
@@ -0,0 +1,260 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! A devnet for the integration tests of applications built on the provider, started with a
+//! single function call.
+//!
+//! The devnet is the single node Fendermint testnode of `infra/fendermint`, orchestrated through
+//! its `cargo make` tasks, which need Docker and `cargo-make`. Once the chain produces blocks, a
+//! set of keys derived from a seed is funded from the validator, so that tests get the same
+//! funded addresses at every run, and an `IpcProvider` holding these keys is wired to the node.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Context};
+use base64::Engine;
+use fvm_shared::address::Address;
+use fvm_shared::econ::TokenAmount;
+use ipc_api::ethers_address_to_fil_address;
+use ipc_api::subnet_id::SubnetID;
+use url::Url;
+
+use crate::config::subnet::{EVMSubnet, SubnetConfig};
+use crate::config::Subnet;
+use crate::IpcProvider;
+
+/// The ID of the root network of the testnode.
+const DEVNET_ROOT_ID: u64 = 0;
+/// The masked ID addresses of the gateway and the registry deployed in the testnode genesis.
+const DEVNET_GATEWAY_ADDR: &str = "0xff00000000000000000000000000000000000040";
+const DEVNET_REGISTRY_ADDR: &str = "0xff00000000000000000000000000000000000041";
+/// Where the `cargo make` tasks put the testnode data, under the home directory.
+const DEVNET_BASE_DIR: &str = ".ipc/r0/ipc-node";
+const VALIDATOR_KEY_PATH: &str = "ipc-node/keys/validator_key.sk";
+/// How often the chain is polled while waiting for it.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone)]
+pub struct DevnetConfig {
+    /// The `infra/fendermint` directory holding the `Makefile.toml` of the testnode.
+    pub infra_dir: PathBuf,
+    /// The number of funded keys handed to the tests.
+    pub accounts: usize,
+    /// The balance of each funded key.
+    pub balance: TokenAmount,
+    /// The seed the funded keys are derived from.
+    pub seed: u64,
+    /// The host port of the Ethereum API of the node.
+    pub eth_api_port: u16,
+    /// How long to wait for the chain to produce blocks.
+    pub startup_timeout: Duration,
+    /// Leave the node running when the devnet is dropped, to look into a failed test.
+    pub keep_running: bool,
+}
+
+impl Default for DevnetConfig {
+    fn default() -> Self {
+        Self {
+            infra_dir: PathBuf::from(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/../../infra/fendermint"
+            )),
+            accounts: 4,
+            balance: TokenAmount::from_whole(100),
+            seed: 0,
+            eth_api_port: 8545,
+            startup_timeout: Duration::from_secs(300),
+            keep_running: false,
+        }
+    }
+}
+
+/// A running devnet, stopped when dropped.
+pub struct Devnet {
+    config: DevnetConfig,
+    subnet: SubnetID,
+    provider: IpcProvider,
+    accounts: Vec<Address>,
+}
+
+impl Devnet {
+    /// Start a devnet with the default config.
+    pub async fn start() -> anyhow::Result<Self> {
+        Self::start_with(DevnetConfig::default()).await
+    }
+
+    /// Start a devnet, wait for its chain to produce blocks, and fund the keys of the tests.
+    pub async fn start_with(config: DevnetConfig) -> anyhow::Result<Self> {
+        // The validator funds the keys, keeping as much again to pay for the tests' own gas.
+        let funds =
+            TokenAmount::from_atto(config.balance.atto() * (2 * config.accounts as u64 + 1));
+        run_task(&config, "testnode", &[("BALANCE", whole_fil(&funds))]).await?;
+
+        let base_dir = dirs::home_dir()
+            .ok_or_else(|| anyhow!("no home directory"))?
+            .join(DEVNET_BASE_DIR);
+        let subnet = SubnetID::new_root(DEVNET_ROOT_ID);
+        let provider = IpcProvider::new_with_subnet(
+            Some(base_dir.join("keystore").to_string_lossy().to_string()),
+            devnet_subnet(&subnet, config.eth_api_port)?,
+        )?;
+
+        // Built before waiting, so that the node is stopped if the devnet fails to start.
+        let mut devnet = Self {
+            config: config.clone(),
+            subnet,
+            provider,
+            accounts: vec![],
+        };
+        devnet.wait_for_blocks().await?;
+
+        let validator = import_key(&devnet.provider, &validator_key(&base_dir)?)?;
+        for i in 0..config.accounts {
+            let account = import_key(&devnet.provider, &derive_key(config.seed, i))?;
+            let subnet = devnet.subnet.clone();
+            devnet
+                .provider
+                .send_value(&subnet, Some(validator), account, config.balance.clone())
+                .await
+                .with_context(|| format!("failed to fund devnet account {account}"))?;
+            devnet.accounts.push(account);
+        }
+        tracing::info!(
+            "devnet ready with {} funded accounts",
+            devnet.accounts.len()
+        );
+
+        Ok(devnet)
+    }
+
+    /// The subnet of the devnet.
+    pub fn subnet(&self) -> &SubnetID {
+        &self.subnet
+    }
+
+    /// A provider wired to the devnet, holding the keys of the funded accounts.
+    pub fn provider(&self) -> IpcProvider {
+        self.provider.clone()
+    }
+
+    /// The funded accounts, in the order of their derivation from the seed.
+    pub fn accounts(&self) -> &[Address] {
+        &self.accounts
+    }
+
+    /// The Ethereum API of the node.
+    pub fn eth_api_url(&self) -> String {
+        format!("http://localhost:{}", self.config.eth_api_port)
+    }
+
+    async fn wait_for_blocks(&mut self) -> anyhow::Result<()> {
+        let started = Instant::now();
+        loop {
+            match self.provider.chain_head(&self.subnet).await {
+                Ok(head) if head > 0 => return Ok(()),
+                Ok(_) => {}
+                Err(e) => tracing::debug!("devnet not reachable yet: {e:#}"),
+            }
+            if started.elapsed() > self.config.startup_timeout {
+                return Err(anyhow!(
+                    "devnet produced no block within {:?}",
+                    self.config.startup_timeout
+                ));
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+}
+
+impl Drop for Devnet {
+    fn drop(&mut self) {
+        if self.config.keep_running {
+            return;
+        }
+        if let Err(e) = task_command(&self.config, "testnode-down", &[]).status() {
+            tracing::error!("failed to stop the devnet: {e}");
+        }
+    }
+}
+
+fn task_command(config: &DevnetConfig, task: &str, env: &[(&str, String)]) -> Command {
+    let mut cmd = Command::new("cargo");
+    cmd.args(["make", "--makefile", "Makefile.toml", task])
+        .current_dir(&config.infra_dir)
+        .env("ETHAPI_HOST_PORT", config.eth_api_port.to_string());
+    for (k, v) in env {
+        cmd.env(k, v);
+    }
+    cmd
+}
+
+async fn run_task(config: &DevnetConfig, task: &str, env: &[(&str, String)]) -> anyhow::Result<()> {
+    let mut cmd = task_command(config, task, env);
+    let status = tokio::task::spawn_blocking(move || cmd.status())
+        .await?
+        .with_context(|| format!("failed to run `cargo make {task}`"))?;
+    if !status.success() {
+        return Err(anyhow!("`cargo make {task}` failed with {status}"));
+    }
+    Ok(())
+}
+
+fn devnet_subnet(subnet: &SubnetID, eth_api_port: u16) -> anyhow::Result<Subnet> {
+    Ok(Subnet {
+        id: subnet.clone(),
+        config: SubnetConfig::Fevm(EVMSubnet {
+            provider_http: Url::parse(&format!("http://localhost:{eth_api_port}"))?,
+            provider_timeout: None,
+            auth_token: None,
+            registry_addr: ethers_address_to_fil_address(&DEVNET_REGISTRY_ADDR.parse()?)?,
+            gateway_addr: ethers_address_to_fil_address(&DEVNET_GATEWAY_ADDR.parse()?)?,
+            admin: None,
+            response_cache: None,
+            max_concurrent_requests: None,
+            fallback_provider_http: vec![],
+            gas: None,
+        }),
+    })
+}
+
+/// The secret key of the `i`-th account derived from `seed`.
+fn derive_key(seed: u64, i: usize) -> Vec<u8> {
+    ethers::utils::keccak256(format!("ipc-devnet/{seed}/{i}")).to_vec()
+}
+
+/// The secret key of the validator of the testnode, which holds the funds of the genesis.
+fn validator_key(base_dir: &Path) -> anyhow::Result<Vec<u8>> {
+    let path = base_dir.join(VALIDATOR_KEY_PATH);
+    let encoded = std::fs::read_to_string(&path)
+        .with_context(|| format!("failed to read validator key at {}", path.display()))?;
+    Ok(base64::engine::general_purpose::STANDARD.decode(encoded.trim())?)
+}
+
+fn import_key(provider: &IpcProvider, secret_key: &[u8]) -> anyhow::Result<Address> {
+    let key = provider.import_evm_key_from_privkey(&hex::encode(secret_key))?;
+    Ok(Address::try_from(key)?)
+}
+
+// `cargo make` takes the balance of the genesis accounts in whole FIL
+fn whole_fil(amount: &TokenAmount) -> String {
+    let whole = amount.atto() / TokenAmount::from_whole(1).atto();
+    (whole + 1u8).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use fvm_shared::econ::TokenAmount;
+
+    use super::{derive_key, whole_fil};
+
+    #[test]
+    fn accounts_are_derived_from_the_seed() {
+        assert_eq!(derive_key(0, 1), derive_key(0, 1));
+        assert_ne!(derive_key(0, 1), derive_key(0, 2));
+        assert_ne!(derive_key(0, 1), derive_key(1, 1));
+        assert!(libsecp256k1::SecretKey::parse_slice(&derive_key(0, 1)).is_ok());
+
+        assert_eq!(whole_fil(&TokenAmount::from_whole(900)), "901");
+    }
+}
@@ -0,0 +1,615 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! A watchtower checking the health, checkpoint lag and solvency of subnets continuously, and
+//! pushing alerts to sinks like webhooks, Slack or PagerDuty.
+//!
+//! An alert is sent once a check failed `alert_after` times in a row, and resolved once the
+//! check passes again, rather than at every failed check. A check which can't be run, because
+//! a subnet is unreachable for instance, fails with a warning.
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use fvm_shared::clock::ChainEpoch;
+use ipc_api::subnet_id::SubnetID;
+use serde::Serialize;
+use serde_json::json;
+use tokio_util::sync::CancellationToken;
+use url::Url;
+
+use crate::config::watchtower::{AlertSinkConfig, WatchedSubnetConfig, WatchtowerConfig};
+use crate::doctor::MAX_CHECKPOINT_LAG_PERIODS;
+use crate::status::{SubnetState, SubnetStatus};
+use crate::IpcProvider;
+
+/// How often the subnets are checked if the config doesn't say.
+pub const DEFAULT_WATCHTOWER_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The endpoint of the PagerDuty Events API v2.
+pub const PAGERDUTY_EVENTS_URL: &str = "https://events.pagerduty.com/v2/enqueue";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    /// The subnet is killed, unreachable, or short of validators.
+    Health,
+    /// The last bottom-up checkpoint lags too far behind the head of the subnet.
+    CheckpointLag,
+    /// The supply of the subnet breaks the accounting rules of [crate::supply::SupplyReport].
+    Solvency,
+}
+
+impl Display for AlertKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            AlertKind::Health => "health",
+            AlertKind::CheckpointLag => "checkpoint_lag",
+            AlertKind::Solvency => "solvency",
+        };
+        write!(f, "{s}")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Warning,
+    Critical,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Critical => write!(f, "critical"),
+        }
+    }
+}
+
+/// A failed check of a subnet, or its resolution.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Alert {
+    pub subnet: String,
+    pub kind: AlertKind,
+    pub severity: Severity,
+    pub message: String,
+    /// The check passes again.
+    pub resolved: bool,
+}
+
+impl Alert {
+    fn new(subnet: &SubnetID, kind: AlertKind, severity: Severity, message: String) -> Self {
+        Self {
+            subnet: subnet.to_string(),
+            kind,
+            severity,
+            message,
+            resolved: false,
+        }
+    }
+
+    /// Identifies the alerts of the same check of the same subnet, to resolve them.
+    pub fn dedup_key(&self) -> String {
+        format!("ipc-watchtower/{}/{}", self.subnet, self.kind)
+    }
+}
+
+impl Display for Alert {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.resolved {
+            write!(
+                f,
+                "[resolved] {} of {}: {}",
+                self.kind, self.subnet, self.message
+            )
+        } else {
+            write!(
+                f,
+                "[{}] {} of {}: {}",
+                self.severity, self.kind, self.subnet, self.message
+            )
+        }
+    }
+}
+
+/// Where alerts are pushed.
+#[async_trait]
+pub trait AlertSink: Send + Sync {
+    fn name(&self) -> &str;
+
+    async fn send(&self, alert: &Alert) -> anyhow::Result<()>;
+}
+
+async fn post_json(
+    client: &reqwest::Client,
+    url: &Url,
+    body: &serde_json::Value,
+) -> anyhow::Result<()> {
+    client
+        .post(url.clone())
+        .json(body)
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+/// Posts every alert as JSON.
+pub struct WebhookSink {
+    client: reqwest::Client,
+    url: Url,
+}
+
+impl WebhookSink {
+    pub fn new(url: Url) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url,
+        }
+    }
+}
+
+#[async_trait]
+impl AlertSink for WebhookSink {
+    fn name(&self) -> &str {
+        "webhook"
+    }
+
+    async fn send(&self, alert: &Alert) -> anyhow::Result<()> {
+        post_json(&self.client, &self.url, &serde_json::to_value(alert)?).await
+    }
+}
+
+/// Posts every alert as a message to a Slack incoming webhook.
+pub struct SlackSink {
+    client: reqwest::Client,
+    webhook_url: Url,
+}
+
+impl SlackSink {
+    pub fn new(webhook_url: Url) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            webhook_url,
+        }
+    }
+}
+
+#[async_trait]
+impl AlertSink for SlackSink {
+    fn name(&self) -> &str {
+        "slack"
+    }
+
+    async fn send(&self, alert: &Alert) -> anyhow::Result<()> {
+        let body = json!({ "text": alert.to_string() });
+        post_json(&self.client, &self.webhook_url, &body).await
+    }
+}
+
+/// Triggers an incident per failed check with the PagerDuty Events API v2, and resolves it
+/// when the check passes again.
+pub struct PagerDutySink {
+    client: reqwest::Client,
+    routing_key: String,
+    url: Url,
+}
+
+impl PagerDutySink {
+    pub fn new(routing_key: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            routing_key,
+            url: Url::parse(PAGERDUTY_EVENTS_URL).expect("valid PagerDuty URL"),
+        }
+    }
+
+    /// Send the events to a PagerDuty compatible endpoint.
+    pub fn with_url(mut self, url: Url) -> Self {
+        self.url = url;
+        self
+    }
+
+    fn event(&self, alert: &Alert) -> serde_json::Value {
+        if alert.resolved {
+            return json!({
+                "routing_key": self.routing_key,
+                "event_action": "resolve",
+                "dedup_key": alert.dedup_key(),
+            });
+        }
+        json!({
+            "routing_key": self.routing_key,
+            "event_action": "trigger",
+            "dedup_key": alert.dedup_key(),
+            "payload": {
+                "summary": format!("{} of {}: {}", alert.kind, alert.subnet, alert.message),
+                "source": alert.subnet,
+                "severity": alert.severity.to_string(),
+                "component": alert.kind.to_string(),
+            },
+        })
+    }
+}
+
+#[async_trait]
+impl AlertSink for PagerDutySink {
+    fn name(&self) -> &str {
+        "pagerduty"
+    }
+
+    async fn send(&self, alert: &Alert) -> anyhow::Result<()> {
+        post_json(&self.client, &self.url, &self.event(alert)).await
+    }
+}
+
+/// The sink declared in the config.
+pub fn sink_from_config(config: &AlertSinkConfig) -> Box<dyn AlertSink> {
+    match config {
+        AlertSinkConfig::Webhook { url } => Box::new(WebhookSink::new(url.clone())),
+        AlertSinkConfig::Slack { webhook_url } => Box::new(SlackSink::new(webhook_url.clone())),
+        AlertSinkConfig::Pagerduty { routing_key, url } => {
+            let sink = PagerDutySink::new(routing_key.clone());
+            Box::new(match url {
+                Some(url) => sink.with_url(url.clone()),
+                None => sink,
+            })
+        }
+    }
+}
+
+/// A subnet to check, with its thresholds.
+#[derive(Debug, Clone)]
+pub struct WatchedSubnet {
+    pub id: SubnetID,
+    /// Two checkpoint periods if unset.
+    pub max_checkpoint_lag: Option<ChainEpoch>,
+    /// The minimum of the subnet actor if unset.
+    pub min_active_validators: Option<u16>,
+    pub check_solvency: bool,
+    /// The consecutive failed checks before an alert is sent.
+    pub alert_after: u32,
+}
+
+impl WatchedSubnet {
+    pub fn new(id: SubnetID) -> Self {
+        Self {
+            id,
+            max_checkpoint_lag: None,
+            min_active_validators: None,
+            check_solvency: true,
+            alert_after: 1,
+        }
+    }
+}
+
+impl TryFrom<&WatchedSubnetConfig> for WatchedSubnet {
+    type Error = anyhow::Error;
+
+    fn try_from(config: &WatchedSubnetConfig) -> Result<Self, Self::Error> {
+        let id = SubnetID::from_str(&config.id)
+            .with_context(|| format!("invalid watched subnet {}", config.id))?;
+        Ok(Self {
+            id,
+            max_checkpoint_lag: config.max_checkpoint_lag,
+            min_active_validators: config.min_active_validators,
+            check_solvency: config.check_solvency,
+            alert_after: config.alert_after.unwrap_or(1).max(1),
+        })
+    }
+}
+
+/// Whether the subnet is killed, unreachable or short of validators.
+fn health_failure(
+    status: &SubnetStatus,
+    min_active_validators: Option<u16>,
+) -> Option<(Severity, String)> {
+    let unhealthy = match status.state {
+        SubnetState::Killed => true,
+        // Lagging checkpoints alone make a reachable subnet with enough validators unhealthy;
+        // they are reported by their own check, with their own threshold.
+        SubnetState::Unhealthy => {
+            status.chain_head.is_none() || (status.active_validators as u64) < status.min_validators
+        }
+        _ => false,
+    };
+    if unhealthy {
+        return Some((Severity::Critical, status.reason.clone()));
+    }
+    match min_active_validators {
+        Some(min) if status.active_validators < min => Some((
+            Severity::Warning,
+            format!(
+                "{} active validators, below the threshold of {min}",
+                status.active_validators
+            ),
+        )),
+        _ => None,
+    }
+}
+
+/// Whether the last checkpoint lags more than `max_lag` epochs behind the head of the subnet,
+/// or two checkpoint periods if unset.
+fn checkpoint_lag_failure(status: &SubnetStatus, max_lag: Option<ChainEpoch>) -> Option<String> {
+    let head = status.chain_head?;
+    let max_lag =
+        max_lag.unwrap_or(MAX_CHECKPOINT_LAG_PERIODS * status.bottom_up_checkpoint_period);
+    let lag = head - status.last_bottom_up_checkpoint_height;
+    (lag > max_lag).then(|| {
+        format!(
+            "last checkpoint at {}, {lag} epochs behind the head at {head}, above the threshold of {max_lag}",
+            status.last_bottom_up_checkpoint_height
+        )
+    })
+}
+
+/// The failures of a check of a subnet, and the alert sent for them, if any.
+#[derive(Debug, Default)]
+struct AlertState {
+    failures: u32,
+    fired: Option<Alert>,
+}
+
+impl AlertState {
+    /// The alert to send after the check failed with `failure`, or passed if `None`.
+    fn update(&mut self, failure: Option<Alert>, alert_after: u32) -> Option<Alert> {
+        match failure {
+            Some(alert) => {
+                self.failures += 1;
+                if self.fired.is_some() || self.failures < alert_after {
+                    return None;
+                }
+                self.fired = Some(alert.clone());
+                Some(alert)
+            }
+            None => {
+                self.failures = 0;
+                self.fired.take().map(|mut alert| {
+                    alert.resolved = true;
+                    alert.message = format!("passing again after: {}", alert.message);
+                    alert
+                })
+            }
+        }
+    }
+}
+
+/// Checks subnets continuously, pushing alerts to its sinks.
+pub struct Watchtower {
+    provider: IpcProvider,
+    subnets: Vec<WatchedSubnet>,
+    sinks: Vec<Box<dyn AlertSink>>,
+    states: HashMap<(SubnetID, AlertKind), AlertState>,
+    shutdown: CancellationToken,
+}
+
+impl Watchtower {
+    pub fn new(provider: IpcProvider, subnets: Vec<WatchedSubnet>) -> Self {
+        Self {
+            provider,
+            subnets,
+            sinks: vec![],
+            states: HashMap::new(),
+            shutdown: CancellationToken::new(),
+        }
+    }
+
+    /// The watchtower of the config, with its subnets and sinks.
+    pub fn from_config(provider: IpcProvider, config: &WatchtowerConfig) -> anyhow::Result<Self> {
+        let subnets = config
+            .subnets
+            .iter()
+            .map(WatchedSubnet::try_from)
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let mut watchtower = Self::new(provider, subnets);
+        for sink in &config.sinks {
+            watchtower = watchtower.with_sink(sink_from_config(sink));
+        }
+        Ok(watchtower)
+    }
+
+    pub fn with_sink(mut self, sink: Box<dyn AlertSink>) -> Self {
+        self.sinks.push(sink);
+        self
+    }
+
+    /// Stop watching when the signal is cancelled, after the checks in progress, if any.
+    pub fn with_shutdown_signal(mut self, shutdown: CancellationToken) -> Self {
+        self.shutdown = shutdown;
+        self
+    }
+
+    /// Run the checks of a subnet, with the failure of each, if any.
+    async fn check_subnet(&self, subnet: &WatchedSubnet) -> Vec<(AlertKind, Option<Alert>)> {
+        let id = &subnet.id;
+        let mut results = vec![];
+
+        match self.provider.subnet_status(id).await {
+            Ok(status) => {
+                let health = health_failure(&status, subnet.min_active_validators)
+                    .map(|(severity, msg)| Alert::new(id, AlertKind::Health, severity, msg));
+                let lag = checkpoint_lag_failure(&status, subnet.max_checkpoint_lag)
+                    .map(|msg| Alert::new(id, AlertKind::CheckpointLag, Severity::Warning, msg));
+                results.push((AlertKind::Health, health));
+                results.push((AlertKind::CheckpointLag, lag));
+            }
+            Err(e) => results.push((
+                AlertKind::Health,
+                Some(Alert::new(
+                    id,
+                    AlertKind::Health,
+                    Severity::Warning,
+                    format!("cannot get the status: {e:#}"),
+                )),
+            )),
+        }
+
+        if subnet.check_solvency {
+            let failure = match self.provider.supply_report(id).await {
+                Ok(report) if report.is_solvent() => None,
+                Ok(report) => Some(Alert::new(
+                    id,
+                    AlertKind::Solvency,
+                    Severity::Critical,
+                    report.violations.join("; "),
+                )),
+                Err(e) => Some(Alert::new(
+                    id,
+                    AlertKind::Solvency,
+                    Severity::Warning,
+                    format!("cannot audit the supply: {e:#}"),
+                )),
+            };
+            results.push((AlertKind::Solvency, failure));
+        }
+
+        results
+    }
+
+    /// Check every subnet once, pushing the new and resolved alerts to the sinks, and returning
+    /// them.
+    pub async fn check(&mut self) -> Vec<Alert> {
+        let mut alerts = vec![];
+        for subnet in &self.subnets {
+            for (kind, failure) in self.check_subnet(subnet).await {
+                let state = self.states.entry((subnet.id.clone(), kind)).or_default();
+                if let Some(alert) = state.update(failure, subnet.alert_after) {
+                    alerts.push(alert);
+                }
+            }
+        }
+
+        for alert in &alerts {
+            if alert.resolved {
+                tracing::info!("{alert}");
+            } else {
+                tracing::warn!("{alert}");
+            }
+            for sink in &self.sinks {
+                if let Err(e) = sink.send(alert).await {
+                    tracing::error!("cannot push alert to {}: {e:#}", sink.name());
+                }
+            }
+        }
+        alerts
+    }
+
+    /// Check the subnets every `interval`.
+    pub async fn run(mut self, interval: Duration) {
+        tracing::info!(
+            "watching {} subnets with {} alert sinks",
+            self.subnets.len(),
+            self.sinks.len()
+        );
+
+        loop {
+            self.check().await;
+            tokio::select! {
+                _ = self.shutdown.cancelled() => break,
+                _ = tokio::time::sleep(interval) => {}
+            }
+        }
+
+        tracing::info!("stopped watching subnets");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use ipc_api::subnet_id::SubnetID;
+
+    use super::{
+        checkpoint_lag_failure, health_failure, Alert, AlertKind, AlertState, PagerDutySink,
+        Severity,
+    };
+    use crate::status::{SubnetState, SubnetStatus};
+
+    fn status(state: SubnetState, active: u16, head: Option<i64>) -> SubnetStatus {
+        SubnetStatus {
+            subnet: "/r123/f01".to_string(),
+            state,
+            reason: "reason".to_string(),
+            min_validators: 3,
+            active_validators: active,
+            total_validators: active,
+            genesis_available: true,
+            last_bottom_up_checkpoint_height: 10,
+            bottom_up_checkpoint_period: 10,
+            chain_head: head,
+        }
+    }
+
+    fn alert() -> Alert {
+        Alert::new(
+            &SubnetID::from_str("/r123/f01").unwrap(),
+            AlertKind::Solvency,
+            Severity::Critical,
+            "violation".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_checks() {
+        assert!(health_failure(&status(SubnetState::Active, 3, Some(20)), None).is_none());
+        assert!(health_failure(&status(SubnetState::Killed, 3, None), None).is_some());
+        assert!(health_failure(&status(SubnetState::Unhealthy, 2, Some(20)), None).is_some());
+        assert!(health_failure(&status(SubnetState::Unhealthy, 3, None), None).is_some());
+        // Unhealthy because of the checkpoint lag only.
+        assert!(health_failure(&status(SubnetState::Unhealthy, 3, Some(40)), None).is_none());
+        assert_eq!(
+            health_failure(&status(SubnetState::Active, 3, Some(20)), Some(4))
+                .unwrap()
+                .0,
+            Severity::Warning
+        );
+
+        // Two periods by default.
+        assert!(checkpoint_lag_failure(&status(SubnetState::Active, 3, Some(30)), None).is_none());
+        assert!(checkpoint_lag_failure(&status(SubnetState::Active, 3, Some(31)), None).is_some());
+        assert!(
+            checkpoint_lag_failure(&status(SubnetState::Active, 3, Some(31)), Some(50)).is_none()
+        );
+        assert!(checkpoint_lag_failure(&status(SubnetState::Active, 3, None), None).is_none());
+    }
+
+    #[test]
+    fn test_alerts_fire_and_resolve_once() {
+        let mut state = AlertState::default();
+
+        assert_eq!(state.update(Some(alert()), 2), None);
+        assert_eq!(state.update(Some(alert()), 2), Some(alert()));
+        assert_eq!(state.update(Some(alert()), 2), None);
+
+        let resolved = state.update(None, 2).unwrap();
+        assert!(resolved.resolved);
+        assert_eq!(resolved.dedup_key(), alert().dedup_key());
+        assert_eq!(state.update(None, 2), None);
+
+        // A failure followed by a success doesn't count towards the next alert.
+        assert_eq!(state.update(Some(alert()), 2), None);
+        assert_eq!(state.update(None, 2), None);
+        assert_eq!(state.update(Some(alert()), 2), None);
+    }
+
+    #[test]
+    fn test_pagerduty_events() {
+        let sink = PagerDutySink::new("key".to_string());
+
+        let trigger = sink.event(&alert());
+        assert_eq!(trigger["event_action"], "trigger");
+        assert_eq!(trigger["payload"]["severity"], "critical");
+        assert_eq!(trigger["dedup_key"], "ipc-watchtower//r123/f01/solvency");
+
+        let mut resolved = alert();
+        resolved.resolved = true;
+        let resolve = sink.event(&resolved);
+        assert_eq!(resolve["event_action"], "resolve");
+        assert_eq!(resolve["dedup_key"], trigger["dedup_key"]);
+    }
+}
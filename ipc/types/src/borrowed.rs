@@ -0,0 +1,236 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+//! Reading state without copying it.
+//!
+//! Loading a [THamt] and iterating it decodes every node into an intermediate IPLD tree and then
+//! into owned values, and keeps the loaded nodes cached until the map is dropped, so walking a
+//! large map holds all of it in memory, several times over. The methods here decode each block
+//! straight into values borrowing from its bytes, and visit the nodes of a map one at a time,
+//! so only the blocks on the path from the root to the current node are held in memory.
+use std::fmt;
+use std::marker::PhantomData;
+
+use anyhow::{anyhow, Context, Result};
+use cid::Cid;
+use fvm_ipld_blockstore::Blockstore;
+use serde::de::{self, Deserialize, Deserializer, IgnoredAny, SeqAccess, Visitor};
+
+use super::{CodeType, TCid, THamt, TLink};
+
+/// Types which have a counterpart borrowing from the bytes they are decoded from, e.g. `&str`
+/// instead of `String` and `&[u8]` instead of `Vec<u8>`, with the same encoding.
+pub trait BorrowDecode {
+    type Ref<'a>: Deserialize<'a>;
+}
+
+impl<T, C: CodeType> TCid<TLink<T>, C>
+where
+    T: BorrowDecode,
+{
+    /// Decode the linked value borrowing from its block and pass it to `f`.
+    ///
+    /// Fails if the block is not in the store, like [TCid::load].
+    pub fn read_borrowed<S, R, F>(&self, store: &S, f: F) -> Result<R>
+    where
+        S: Blockstore,
+        F: for<'a> FnOnce(T::Ref<'a>) -> Result<R>,
+    {
+        let block = get_block(store, &self.cid)?;
+        let value = fvm_ipld_encoding::from_slice(&block)
+            .with_context(|| format!("error decoding {}", self.cid))?;
+        f(value)
+    }
+}
+
+impl<K, V, const W: u32> TCid<THamt<K, V, W>>
+where
+    V: BorrowDecode,
+{
+    /// Visit the raw key and the borrowed value of every entry of the map, without loading the
+    /// whole map in memory. Stops at the first error returned by `f`.
+    ///
+    /// Entries are visited in the order of the hashes of their keys, like `Hamt::for_each`.
+    pub fn for_each_borrowed<S, F>(&self, store: &S, mut f: F) -> Result<()>
+    where
+        S: Blockstore,
+        F: for<'a> FnMut(&'a [u8], V::Ref<'a>) -> Result<()>,
+    {
+        visit_node::<S, V, F>(store, &self.cid, &mut f)
+    }
+}
+
+fn get_block<S: Blockstore>(store: &S, cid: &Cid) -> Result<Vec<u8>> {
+    store
+        .get(cid)?
+        .ok_or_else(|| anyhow!("block {cid} not found in the store"))
+}
+
+/// Visit the entries of a HAMT node, then those of its children, depth first.
+fn visit_node<S, V, F>(store: &S, cid: &Cid, f: &mut F) -> Result<()>
+where
+    S: Blockstore,
+    V: BorrowDecode,
+    F: for<'a> FnMut(&'a [u8], V::Ref<'a>) -> Result<()>,
+{
+    let block = get_block(store, cid)?;
+    // A node is a tuple of the bitfield of its occupied slots, which we don't need to visit all
+    // of them, and the pointers in those slots.
+    let (_, pointers): (IgnoredAny, Vec<Pointer<'_, V::Ref<'_>>>) =
+        fvm_ipld_encoding::from_slice(&block)
+            .with_context(|| format!("error decoding HAMT node {cid}"))?;
+
+    for pointer in pointers {
+        match pointer {
+            Pointer::Values(values) => {
+                for (key, value) in values {
+                    f(key, value)?;
+                }
+            }
+            Pointer::Link(child) => visit_node::<S, V, F>(store, &child, f)?,
+        }
+    }
+    Ok(())
+}
+
+/// A slot of a HAMT node: either a bucket of entries, encoded as a list of key-value tuples, or
+/// a link to a child node.
+enum Pointer<'a, V> {
+    Values(Vec<(&'a [u8], V)>),
+    Link(Cid),
+}
+
+impl<'a, V: Deserialize<'a>> Deserialize<'a> for Pointer<'a, V> {
+    fn deserialize<D: Deserializer<'a>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(PointerVisitor(PhantomData))
+    }
+}
+
+struct PointerVisitor<'a, V>(PhantomData<(&'a (), V)>);
+
+impl<'a, V: Deserialize<'a>> Visitor<'a> for PointerVisitor<'a, V> {
+    type Value = Pointer<'a, V>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a bucket of HAMT entries or a link to a HAMT node")
+    }
+
+    fn visit_seq<A: SeqAccess<'a>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut values = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+        while let Some(entry) = seq.next_element()? {
+            values.push(entry);
+        }
+        Ok(Pointer::Values(values))
+    }
+
+    /// CIDs are passed to the visitor as a newtype wrapping their bytes.
+    fn visit_newtype_struct<D: Deserializer<'a>>(
+        self,
+        deserializer: D,
+    ) -> Result<Self::Value, D::Error> {
+        deserializer
+            .deserialize_bytes(CidVisitor)
+            .map(Pointer::Link)
+    }
+}
+
+struct CidVisitor;
+
+impl<'de> Visitor<'de> for CidVisitor {
+    type Value = Cid;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("the bytes of a CID")
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        // Strip the multibase prefix of the binary CIDs in DAG-CBOR, if it's still there.
+        let v = v.strip_prefix(&[0]).unwrap_or(v);
+        Cid::try_from(v).map_err(E::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fvm_ipld_blockstore::MemoryBlockstore;
+    use fvm_ipld_encoding::tuple::*;
+    use fvm_ipld_hamt::BytesKey;
+    use serde::Deserialize;
+
+    use crate::{BorrowDecode, TCid, THamt, TLink};
+
+    #[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug, PartialEq)]
+    struct Validator {
+        power: u64,
+        name: String,
+    }
+
+    /// Decodes from the same bytes as [Validator], without copying the name.
+    #[derive(Deserialize)]
+    struct ValidatorRef<'a>(u64, &'a str);
+
+    impl BorrowDecode for Validator {
+        type Ref<'a> = ValidatorRef<'a>;
+    }
+
+    fn validator(i: u64) -> Validator {
+        Validator {
+            power: i,
+            name: format!("validator-{i}"),
+        }
+    }
+
+    #[test]
+    fn borrowed_hamt_entries_match_owned_ones() {
+        let store = MemoryBlockstore::new();
+        let mut map: TCid<THamt<String, Validator>> = TCid::new_hamt(&store).unwrap();
+
+        // Enough entries to split the root into child nodes.
+        map.update(&store, |map| {
+            for i in 0..500 {
+                map.set(BytesKey::from(format!("key-{i}").as_str()), validator(i))?;
+            }
+            Ok(())
+        })
+        .unwrap();
+
+        let mut owned = Vec::new();
+        map.load(&store)
+            .unwrap()
+            .for_each(|k, v| {
+                owned.push((k.0.clone(), v.clone()));
+                Ok(())
+            })
+            .unwrap();
+
+        let mut borrowed = Vec::new();
+        map.for_each_borrowed(&store, |k: &[u8], ValidatorRef(power, name)| {
+            borrowed.push((
+                k.to_vec(),
+                Validator {
+                    power,
+                    name: name.to_string(),
+                },
+            ));
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(borrowed.len(), 500);
+        assert_eq!(borrowed, owned);
+    }
+
+    #[test]
+    fn borrowed_link_content() {
+        let store = MemoryBlockstore::new();
+        let link: TCid<TLink<Validator>> = TCid::new_link(&store, &validator(7)).unwrap();
+
+        let name_len = link
+            .read_borrowed(&store, |ValidatorRef(_, name)| Ok(name.len()))
+            .unwrap();
+        assert_eq!(name_len, "validator-7".len());
+
+        assert!(TCid::<TLink<Validator>>::default()
+            .read_borrowed(&store, |_| Ok(()))
+            .is_err());
+    }
+}
@@ -8,19 +8,23 @@ pub use self::actor_error::*;
 
 pub mod actor_error;
 mod amt;
+mod borrowed;
 mod ethaddr;
 mod hamt;
 mod link;
+mod matrix;
 mod taddress;
 mod uints;
 
 pub use amt::TAmt;
+pub use borrowed::BorrowDecode;
 pub use ethaddr::*;
 use fvm_ipld_amt::Amt;
 use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_hamt::{BytesKey, Error as HamtError, Hamt};
 pub use hamt::THamt;
 pub use link::TLink;
+pub use matrix::{InputMatrix, SparseMatrix, TrainingInput};
 use serde::{de::DeserializeOwned, Serialize};
 pub use taddress::*;
 
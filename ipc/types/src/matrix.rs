@@ -0,0 +1,276 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+//! Input matrices of the machine learning actor, shared by the actor and its clients so that
+//! they agree on the encoding.
+
+use fvm_ipld_encoding::tuple::{Deserialize_tuple, Serialize_tuple};
+use serde::{Deserialize, Serialize};
+
+/// Compressed sparse row (CSR) encoding of a matrix, which only carries the non-zero values.
+///
+/// The non-zero values of row `i` are `values[row_offsets[i]..row_offsets[i + 1]]`, in the
+/// columns given by the same range of `col_indices`, in increasing order.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct SparseMatrix {
+    pub rows: u64,
+    pub cols: u64,
+    pub row_offsets: Vec<u64>,
+    pub col_indices: Vec<u64>,
+    pub values: Vec<i64>,
+}
+
+impl SparseMatrix {
+    /// Encode a dense matrix, dropping the zeros.
+    pub fn from_dense(input_matrix: &[Vec<i64>]) -> Result<Self, String> {
+        let cols = num_cols(input_matrix)?;
+        let mut m = Self {
+            rows: input_matrix.len() as u64,
+            cols: cols as u64,
+            row_offsets: vec![0],
+            ..Default::default()
+        };
+        for row in input_matrix {
+            for (c, v) in row.iter().enumerate().filter(|(_, v)| **v != 0) {
+                m.col_indices.push(c as u64);
+                m.values.push(*v);
+            }
+            m.row_offsets.push(m.values.len() as u64);
+        }
+        Ok(m)
+    }
+
+    /// Check that the offsets and column indices are consistent with the dimensions.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.row_offsets.len() as u64 != self.rows + 1 {
+            return Err(format!(
+                "there are {} row offsets for {} rows",
+                self.row_offsets.len(),
+                self.rows
+            ));
+        }
+        if self.col_indices.len() != self.values.len() {
+            return Err(format!(
+                "there are {} column indices for {} values",
+                self.col_indices.len(),
+                self.values.len()
+            ));
+        }
+        if self.row_offsets.first() != Some(&0)
+            || self.row_offsets.last() != Some(&(self.values.len() as u64))
+        {
+            return Err(
+                "the row offsets have to start at 0 and end at the number of values".into(),
+            );
+        }
+        // checked before slicing, so all offsets are within the bounds of the values
+        if let Some(i) = self.row_offsets.windows(2).position(|w| w[0] > w[1]) {
+            return Err(format!("the row offsets decrease at row {i}"));
+        }
+        for (i, w) in self.row_offsets.windows(2).enumerate() {
+            let row = &self.col_indices[w[0] as usize..w[1] as usize];
+            if row.windows(2).any(|c| c[0] >= c[1]) {
+                return Err(format!("the column indices of row {i} are not increasing"));
+            }
+            if row.last().is_some_and(|c| *c >= self.cols) {
+                return Err(format!("row {i} has a column index out of bounds"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Decode into a dense matrix, filling in the zeros.
+    pub fn to_dense(&self) -> Result<Vec<Vec<i64>>, String> {
+        self.validate()?;
+        Ok(self
+            .row_offsets
+            .windows(2)
+            .map(|w| {
+                let mut row = vec![0; self.cols as usize];
+                let (start, end) = (w[0] as usize, w[1] as usize);
+                for (c, v) in self.col_indices[start..end]
+                    .iter()
+                    .zip(&self.values[start..end])
+                {
+                    row[*c as usize] = *v;
+                }
+                row
+            })
+            .collect())
+    }
+
+    /// Ratio of non-zero values; the sparse encoding is smaller roughly below one half.
+    pub fn density(&self) -> f64 {
+        let size = self.rows * self.cols;
+        if size == 0 {
+            0.0
+        } else {
+            self.values.len() as f64 / size as f64
+        }
+    }
+}
+
+/// Input matrix of the machine learning methods, either with every value or only the non-zero
+/// ones. The dense form is encoded the same way as a plain `Vec<Vec<i64>>`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum InputMatrix {
+    Dense(Vec<Vec<i64>>),
+    Sparse(SparseMatrix),
+}
+
+impl InputMatrix {
+    pub fn num_rows(&self) -> usize {
+        match self {
+            Self::Dense(m) => m.len(),
+            Self::Sparse(m) => m.rows as usize,
+        }
+    }
+
+    /// Convert to the dense form expected by the syscalls.
+    pub fn into_dense(self) -> Result<Vec<Vec<i64>>, String> {
+        match self {
+            Self::Dense(m) => Ok(m),
+            Self::Sparse(m) => m.to_dense(),
+        }
+    }
+}
+
+impl Default for InputMatrix {
+    fn default() -> Self {
+        Self::Dense(Vec::new())
+    }
+}
+
+impl From<Vec<Vec<i64>>> for InputMatrix {
+    fn from(value: Vec<Vec<i64>>) -> Self {
+        Self::Dense(value)
+    }
+}
+
+impl From<SparseMatrix> for InputMatrix {
+    fn from(value: SparseMatrix) -> Self {
+        Self::Sparse(value)
+    }
+}
+
+/// Input of the machine learning methods which train a model: either the matrix itself, or the
+/// id of a dataset stored in the machine learning actor, so that large datasets don't have to be
+/// uploaded again for every training run. The matrix is encoded the same way as an [InputMatrix].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TrainingInput {
+    Matrix(InputMatrix),
+    Dataset(u64),
+}
+
+impl TrainingInput {
+    /// The matrix sent with the message, if it doesn't refer to a stored dataset.
+    pub fn matrix(&self) -> Option<&InputMatrix> {
+        match self {
+            Self::Matrix(m) => Some(m),
+            Self::Dataset(_) => None,
+        }
+    }
+}
+
+impl Default for TrainingInput {
+    fn default() -> Self {
+        Self::Matrix(InputMatrix::default())
+    }
+}
+
+impl From<InputMatrix> for TrainingInput {
+    fn from(value: InputMatrix) -> Self {
+        Self::Matrix(value)
+    }
+}
+
+impl From<Vec<Vec<i64>>> for TrainingInput {
+    fn from(value: Vec<Vec<i64>>) -> Self {
+        Self::Matrix(value.into())
+    }
+}
+
+impl From<SparseMatrix> for TrainingInput {
+    fn from(value: SparseMatrix) -> Self {
+        Self::Matrix(value.into())
+    }
+}
+
+fn num_cols(input_matrix: &[Vec<i64>]) -> Result<usize, String> {
+    let cols = match input_matrix.first() {
+        Some(row) if !row.is_empty() => row.len(),
+        _ => return Err("the input matrix is empty".to_owned()),
+    };
+    if input_matrix.iter().any(|row| row.len() != cols) {
+        return Err("the rows of the input matrix have different lengths".to_owned());
+    }
+    Ok(cols)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InputMatrix, SparseMatrix, TrainingInput};
+
+    #[test]
+    fn sparse_matrix_roundtrip() {
+        let dense = vec![vec![0, 150, 0], vec![0, 0, 0], vec![-20, 0, 7]];
+        let sparse = SparseMatrix::from_dense(&dense).unwrap();
+        assert_eq!(sparse.row_offsets, vec![0, 1, 1, 3]);
+        assert_eq!(sparse.col_indices, vec![1, 0, 2]);
+        assert_eq!(sparse.values, vec![150, -20, 7]);
+        assert_eq!(sparse.to_dense().unwrap(), dense);
+
+        let mut invalid = sparse.clone();
+        invalid.col_indices[2] = 3;
+        assert!(invalid.to_dense().is_err());
+        let mut invalid = sparse;
+        invalid.row_offsets[1] = 2;
+        invalid.row_offsets[2] = 1;
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn input_matrix_encoding() {
+        let dense = vec![vec![1, 0], vec![0, 2]];
+
+        // the dense form is compatible with callers sending a plain matrix
+        let bytes = fvm_ipld_encoding::to_vec(&InputMatrix::from(dense.clone())).unwrap();
+        assert_eq!(bytes, fvm_ipld_encoding::to_vec(&dense).unwrap());
+        let decoded: InputMatrix = fvm_ipld_encoding::from_slice(&bytes).unwrap();
+        assert_eq!(decoded, InputMatrix::Dense(dense.clone()));
+
+        let sparse = InputMatrix::from(SparseMatrix::from_dense(&dense).unwrap());
+        let bytes = fvm_ipld_encoding::to_vec(&sparse).unwrap();
+        let decoded: InputMatrix = fvm_ipld_encoding::from_slice(&bytes).unwrap();
+        assert_eq!(decoded, sparse);
+        assert_eq!(decoded.num_rows(), 2);
+        assert_eq!(decoded.into_dense().unwrap(), dense);
+    }
+
+    #[test]
+    fn training_input_encoding() {
+        let dense = vec![vec![1, 0], vec![0, 2]];
+
+        // a matrix is encoded as before, so existing callers keep working
+        let bytes = fvm_ipld_encoding::to_vec(&TrainingInput::from(dense.clone())).unwrap();
+        assert_eq!(bytes, fvm_ipld_encoding::to_vec(&dense).unwrap());
+        let decoded: TrainingInput = fvm_ipld_encoding::from_slice(&bytes).unwrap();
+        assert_eq!(decoded.matrix(), Some(&InputMatrix::Dense(dense)));
+
+        let bytes = fvm_ipld_encoding::to_vec(&TrainingInput::Dataset(7)).unwrap();
+        assert_eq!(bytes, fvm_ipld_encoding::to_vec(&7u64).unwrap());
+        let decoded: TrainingInput = fvm_ipld_encoding::from_slice(&bytes).unwrap();
+        assert_eq!(decoded, TrainingInput::Dataset(7));
+        assert_eq!(decoded.matrix(), None);
+    }
+
+    #[test]
+    fn sparse_matrix_density() {
+        let dense = vec![vec![0, 5, 0, 0], vec![0, 0, 0, 0], vec![-1, 0, 0, 2]];
+        let sparse = SparseMatrix::from_dense(&dense).unwrap();
+        assert_eq!(sparse.density(), 0.25);
+
+        assert!(SparseMatrix::from_dense(&[vec![1, 2], vec![3]]).is_err());
+    }
+}
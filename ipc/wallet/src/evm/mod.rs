@@ -5,6 +5,8 @@
 
 mod memory;
 mod persistent;
+#[cfg(feature = "with-remote")]
+mod remote;
 
 use anyhow::Result;
 use std::fmt::{Display, Formatter};
@@ -15,6 +17,8 @@ use zeroize::Zeroize;
 use std::str::FromStr;
 
 pub use crate::evm::persistent::{PersistentKeyInfo, PersistentKeyStore};
+#[cfg(feature = "with-remote")]
+pub use crate::evm::remote::{RemoteKeyStore, RemoteKeyStoreConfig};
 
 pub const DEFAULT_KEYSTORE_NAME: &str = "evm_keystore.json";
 
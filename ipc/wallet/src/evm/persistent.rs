@@ -40,6 +40,18 @@ impl PersistentKeyInfo {
         }
     }
 
+    /// The key info of `addr`, as persisted.
+    pub fn from_key<T: ToString>(addr: &T, info: &KeyInfo) -> Self {
+        Self {
+            address: addr.to_string(),
+            private_key: hex::encode(&info.private_key),
+        }
+    }
+
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
     pub fn private_key(&self) -> &str {
         &self.private_key
     }
@@ -122,34 +134,8 @@ impl<T: Clone + Eq + Hash + TryFrom<KeyInfo> + Default + ToString> PersistentKey
                 )
             })?;
 
-        let mut key_infos = HashMap::new();
-        for info in persisted_key_info.iter() {
-            let key_info = KeyInfo {
-                private_key: hex::decode(&info.private_key)?,
-            };
-            let mut addr = T::default();
-            // only infer the address if this is not the default key
-            if info.address != addr.to_string() {
-                addr = T::try_from(key_info.clone())
-                    .map_err(|_| anyhow!("cannot convert private key to address"))?;
-            }
-
-            key_infos.insert(addr, key_info);
-        }
-
-        // check if there is default in the keystore
-        let default = match key_infos.get(&T::default()) {
-            Some(i) => Some(
-                T::try_from(i.clone()).map_err(|_| anyhow!("couldn't get info for default key"))?,
-            ),
-            None => None,
-        };
-
         Ok(Self {
-            memory: MemoryKeyStore {
-                data: key_infos,
-                default,
-            },
+            memory: from_persisted(&persisted_key_info)?,
             file_path: path,
         })
     }
@@ -173,14 +159,7 @@ impl<T: Clone + Eq + Hash + TryFrom<KeyInfo> + Default + ToString> PersistentKey
             .memory
             .data
             .iter()
-            .map(|(key, val)| {
-                let private_key = hex::encode(&val.private_key);
-                let address = key.to_string();
-                PersistentKeyInfo {
-                    address,
-                    private_key,
-                }
-            })
+            .map(|(key, val)| PersistentKeyInfo::from_key(key, val))
             .collect::<Vec<_>>();
 
         serde_json::to_writer_pretty(writer, &to_persist)
@@ -190,6 +169,39 @@ impl<T: Clone + Eq + Hash + TryFrom<KeyInfo> + Default + ToString> PersistentKey
     }
 }
 
+/// The keys of the persisted key infos, and the default one if they have it.
+pub(crate) fn from_persisted<T: Clone + Eq + Hash + TryFrom<KeyInfo> + Default + ToString>(
+    persisted_key_info: &[PersistentKeyInfo],
+) -> Result<MemoryKeyStore<T>> {
+    let mut key_infos = HashMap::new();
+    for info in persisted_key_info.iter() {
+        let key_info = KeyInfo {
+            private_key: hex::decode(&info.private_key)?,
+        };
+        let mut addr = T::default();
+        // only infer the address if this is not the default key
+        if info.address != addr.to_string() {
+            addr = T::try_from(key_info.clone())
+                .map_err(|_| anyhow!("cannot convert private key to address"))?;
+        }
+
+        key_infos.insert(addr, key_info);
+    }
+
+    // check if there is default in the keystore
+    let default = match key_infos.get(&T::default()) {
+        Some(i) => {
+            Some(T::try_from(i.clone()).map_err(|_| anyhow!("couldn't get info for default key"))?)
+        }
+        None => None,
+    };
+
+    Ok(MemoryKeyStore {
+        data: key_infos,
+        default,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use crate::evm::KeyInfo;
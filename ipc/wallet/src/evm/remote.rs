@@ -0,0 +1,256 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+
+//! Remote key store, backed by a secrets service shared by several hosts.
+//!
+//! The service keeps the keys in the same form as the persistent key store file, and serves
+//! them over HTTPS:
+//! * `GET <url>/keys` returns all the keys, as a JSON list of `{"address", "private_key"}`;
+//! * `PUT <url>/keys/<address>` stores the key in the body, in the same form;
+//! * `DELETE <url>/keys/<address>` removes the key.
+//!
+//! Like in the file, the default key is stored under the address of the default key.
+
+use crate::evm::memory::MemoryKeyStore;
+use crate::evm::persistent::{from_persisted, PersistentKeyInfo};
+use crate::evm::{KeyInfo, KeyStore};
+use anyhow::{anyhow, Result};
+use reqwest::blocking::Client;
+use reqwest::tls::{Certificate, Version};
+use reqwest::{Method, Url};
+use std::hash::Hash;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tokio::runtime::{Handle, RuntimeFlavor};
+
+/// Where the remote key store is, and how to reach it.
+#[derive(Clone)]
+pub struct RemoteKeyStoreConfig {
+    /// Base URL of the service; only `https` is accepted.
+    pub url: Url,
+    /// Bearer token sent with every request, if any.
+    pub token: Option<String>,
+    /// PEM certificate of the only CA trusted to certify the service, instead of the roots of
+    /// the system.
+    pub ca_cert: Option<Vec<u8>>,
+    /// How long the keys fetched from the service are used before they are fetched again.
+    pub cache_ttl: Duration,
+    /// Timeout of every request.
+    pub timeout: Duration,
+}
+
+/// The keys fetched from the service.
+struct Cache<T> {
+    memory: MemoryKeyStore<T>,
+    fetched_at: Option<Instant>,
+}
+
+pub struct RemoteKeyStore<T> {
+    config: RemoteKeyStoreConfig,
+    /// Shared by all the requests, so the connections and TLS sessions are reused.
+    client: Client,
+    cache: Mutex<Cache<T>>,
+}
+
+impl<T: Clone + Eq + Hash + TryFrom<KeyInfo> + Default + ToString + Send> KeyStore
+    for RemoteKeyStore<T>
+{
+    type Key = T;
+
+    fn get(&self, addr: &Self::Key) -> Result<Option<KeyInfo>> {
+        self.refresh_if_stale()?;
+        if let Some(info) = self.lock().memory.get(addr)? {
+            return Ok(Some(info));
+        }
+        // The key may have been added by another host since the keys were fetched.
+        self.refresh()?;
+        self.lock().memory.get(addr)
+    }
+
+    fn list(&self) -> Result<Vec<Self::Key>> {
+        self.refresh_if_stale()?;
+        self.lock().memory.list()
+    }
+
+    fn put(&mut self, info: KeyInfo) -> Result<Self::Key> {
+        let addr = Self::Key::try_from(info.clone())
+            .map_err(|_| anyhow!("cannot convert private key to public key"))?;
+        self.store(&addr, &info)?;
+        self.lock().memory.put(info)
+    }
+
+    fn remove(&mut self, addr: &Self::Key) -> Result<()> {
+        let is_default = self.get_default()?.as_ref() == Some(addr);
+        if is_default {
+            self.request(Method::DELETE, &Self::Key::default(), None)?;
+        }
+        self.request(Method::DELETE, addr, None)?;
+        self.lock().memory.remove(addr)
+    }
+
+    fn set_default(&mut self, addr: &Self::Key) -> Result<()> {
+        let info = self
+            .get(addr)?
+            .ok_or_else(|| anyhow!("can't set default key: not found in keystore"))?;
+        self.store(&Self::Key::default(), &info)?;
+        self.lock().memory.set_default(addr)
+    }
+
+    fn get_default(&mut self) -> Result<Option<Self::Key>> {
+        self.refresh_if_stale()?;
+        self.lock().memory.get_default()
+    }
+}
+
+impl<T: Clone + Eq + Hash + TryFrom<KeyInfo> + Default + ToString + Send> RemoteKeyStore<T> {
+    /// Connect to the service and fetch its keys.
+    pub fn new(config: RemoteKeyStoreConfig) -> Result<Self> {
+        if config.url.scheme() != "https" {
+            return Err(anyhow!(
+                "the remote key store must be reached over https, not {}",
+                config.url.scheme()
+            ));
+        }
+        let client = client(&config)?;
+        let ks = Self {
+            config,
+            client,
+            cache: Mutex::new(Cache {
+                memory: MemoryKeyStore::default(),
+                fetched_at: None,
+            }),
+        };
+        ks.refresh()?;
+        Ok(ks)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Cache<T>> {
+        self.cache.lock().expect("remote key store cache poisoned")
+    }
+
+    fn refresh_if_stale(&self) -> Result<()> {
+        let stale = self
+            .lock()
+            .fetched_at
+            .map_or(true, |at| at.elapsed() >= self.config.cache_ttl);
+        if stale {
+            self.refresh()?;
+        }
+        Ok(())
+    }
+
+    /// Replace the cached keys with those of the service.
+    fn refresh(&self) -> Result<()> {
+        let body = self.send(Method::GET, self.url("keys")?, None)?;
+        let persisted: Vec<PersistentKeyInfo> = serde_json::from_str(&body)
+            .map_err(|e| anyhow!("failed to deserialize keys of remote key store: {e}"))?;
+        *self.lock() = Cache {
+            memory: from_persisted(&persisted)?,
+            fetched_at: Some(Instant::now()),
+        };
+        Ok(())
+    }
+
+    fn store(&self, addr: &T, info: &KeyInfo) -> Result<()> {
+        let body = serde_json::to_string(&PersistentKeyInfo::from_key(addr, info))?;
+        self.request(Method::PUT, addr, Some(body))?;
+        Ok(())
+    }
+
+    fn request(&self, method: Method, addr: &T, body: Option<String>) -> Result<String> {
+        let addr = addr.to_string();
+        let url = self.url(&format!("keys/{addr}"))?;
+        self.send(method, url, body)
+    }
+
+    fn url(&self, path: &str) -> Result<Url> {
+        let base = self.config.url.as_str().trim_end_matches('/');
+        Ok(Url::parse(&format!("{base}/{path}"))?)
+    }
+
+    fn send(&self, method: Method, url: Url, body: Option<String>) -> Result<String> {
+        run_blocking(|| {
+            let mut request = self.client.request(method.clone(), url.clone());
+            if let Some(token) = &self.config.token {
+                request = request.bearer_auth(token);
+            }
+            if let Some(body) = body {
+                request = request
+                    .header(reqwest::header::CONTENT_TYPE, "application/json")
+                    .body(body);
+            }
+            let response = request.send()?;
+            let status = response.status();
+            let text = response.text()?;
+            if !status.is_success() {
+                return Err(anyhow!(
+                    "remote key store failed {method} {url} with {status}: {text}"
+                ));
+            }
+            Ok(text)
+        })
+    }
+}
+
+/// A client which only speaks TLS 1.2 or later, and only trusts the configured CA if any.
+fn client(config: &RemoteKeyStoreConfig) -> Result<Client> {
+    let mut builder = Client::builder()
+        .https_only(true)
+        .min_tls_version(Version::TLS_1_2)
+        .timeout(config.timeout);
+    if let Some(pem) = &config.ca_cert {
+        builder = builder
+            .tls_built_in_root_certs(false)
+            .add_root_certificate(Certificate::from_pem(pem)?);
+    }
+    run_blocking(|| Ok(builder.build()?))
+}
+
+/// Run `f`, which blocks on the client, in a way the async runtime the callers of the key
+/// store usually run in allows: the blocking client panics when used from a runtime thread.
+fn run_blocking<R: Send>(f: impl FnOnce() -> R + Send) -> R {
+    match Handle::try_current().map(|handle| handle.runtime_flavor()) {
+        Ok(RuntimeFlavor::MultiThread) => tokio::task::block_in_place(f),
+        // The current thread runtime can't give up its only thread.
+        Ok(_) => std::thread::scope(|s| {
+            s.spawn(f)
+                .join()
+                .unwrap_or_else(|e| std::panic::resume_unwind(e))
+        }),
+        Err(_) => f(),
+    }
+}
+
+#[cfg(all(test, feature = "with-ethers"))]
+mod tests {
+    use std::time::Duration;
+
+    use super::{client, RemoteKeyStore, RemoteKeyStoreConfig};
+    use crate::EthKeyAddress;
+
+    fn config(url: &str) -> RemoteKeyStoreConfig {
+        RemoteKeyStoreConfig {
+            url: url.parse().unwrap(),
+            token: None,
+            ca_cert: None,
+            cache_ttl: Duration::from_secs(60),
+            timeout: Duration::from_secs(5),
+        }
+    }
+
+    #[test]
+    fn test_plain_http_rejected() {
+        let config = config("http://127.0.0.1:8200/ipc");
+        assert!(RemoteKeyStore::<EthKeyAddress>::new(config).is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_client_in_multi_thread_runtime() {
+        client(&config("https://127.0.0.1:8200/ipc")).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_client_in_current_thread_runtime() {
+        client(&config("https://127.0.0.1:8200/ipc")).unwrap();
+    }
+}
@@ -493,6 +493,28 @@ impl EncryptedKeyStore {
     }
 }
 
+/// Encrypt `msg` with a key derived from `passphrase` like the encrypted keystore does: the
+/// salt of the key is prepended to the ciphertext, so only the passphrase is needed to decrypt it.
+pub fn encrypt_with_passphrase(passphrase: &str, msg: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let (salt, encryption_key) = EncryptedKeyStore::derive_key(passphrase, None)?;
+    let mut data = salt.to_vec();
+    data.extend(EncryptedKeyStore::encrypt(&encryption_key, msg)?);
+    Ok(data)
+}
+
+/// Decrypt data encrypted with [encrypt_with_passphrase].
+pub fn decrypt_with_passphrase(passphrase: &str, data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    if data.len() < RECOMMENDED_SALT_LEN + NONCE_SIZE {
+        anyhow::bail!("the encrypted data is too short");
+    }
+    let (salt, data) = data.split_at(RECOMMENDED_SALT_LEN);
+    let mut prev_salt = [0; RECOMMENDED_SALT_LEN];
+    prev_salt.copy_from_slice(salt);
+    let (_, encryption_key) = EncryptedKeyStore::derive_key(passphrase, Some(prev_salt))?;
+    EncryptedKeyStore::decrypt(&encryption_key, data)
+        .map_err(|_| anyhow::anyhow!("cannot decrypt the data, is the passphrase right?"))
+}
+
 fn map_err_to_anyhow<T: Display>(e: T) -> anyhow::Error {
     anyhow::Error::msg(e.to_string())
 }
@@ -547,6 +569,14 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_decrypt_with_passphrase() -> Result<()> {
+        let data = encrypt_with_passphrase(PASSPHRASE, b"foo is coming")?;
+        ensure!(decrypt_with_passphrase(PASSPHRASE, &data)? == b"foo is coming");
+        ensure!(decrypt_with_passphrase("barbazfoo", &data).is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_read_old_encrypted_keystore() -> Result<()> {
         let dir: PathBuf = "tests/keystore_encrypted_old".into();
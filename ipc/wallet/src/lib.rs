@@ -15,6 +15,8 @@ pub use crate::evm::{
     KeyInfo as EvmKeyInfo, KeyStore as EvmKeyStore, PersistentKeyInfo, PersistentKeyStore,
     DEFAULT_KEYSTORE_NAME,
 };
+#[cfg(feature = "with-remote")]
+pub use crate::evm::{RemoteKeyStore, RemoteKeyStoreConfig};
 pub use crate::fvm::*;
 
 /// WalletType determines the kind of keys and wallets
@@ -0,0 +1,150 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! A `wasm32-unknown-unknown` build of the read path of the provider, so web dashboards can
+//! query subnet and checkpoint state directly from the browser, without a backend.
+//!
+//! Only read-only queries and signing with keys held in memory are supported; sending
+//! transactions is left to the page, e.g. with `eth_sendRawTransaction` on a transaction
+//! signed by a [MemoryWallet].
+//!
+//! ```js
+//! import init, { IpcClient } from "ipc-wasm";
+//!
+//! await init();
+//! const client = new IpcClient("http://localhost:8545", "0x77aa40b105843728088c0132e43fc44348881da8");
+//! console.log(await client.listSubnets());
+//! ```
+
+pub mod query;
+#[cfg(target_arch = "wasm32")]
+pub mod transport;
+pub mod wallet;
+
+pub use query::{CheckpointView, IpcReader, SubnetView};
+pub use wallet::MemoryWallet;
+
+use wasm_bindgen::JsError;
+
+fn to_js_err(e: impl std::fmt::Display) -> JsError {
+    JsError::new(&e.to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+mod client {
+    use std::future::Future;
+    use std::str::FromStr;
+
+    use ethers::types::Address;
+    use ipc_api::subnet_id::SubnetID;
+    use js_sys::Promise;
+    use serde::Serialize;
+    use wasm_bindgen::prelude::*;
+    use wasm_bindgen_futures::future_to_promise;
+
+    use crate::query::IpcReader;
+    use crate::to_js_err;
+    use crate::transport::FetchTransport;
+
+    /// Read-only client of the IPC contracts of a subnet, talking to its Ethereum JSON-RPC API.
+    ///
+    /// Every query returns a promise of a plain JS value.
+    #[wasm_bindgen]
+    pub struct IpcClient {
+        reader: IpcReader<FetchTransport>,
+    }
+
+    #[wasm_bindgen]
+    impl IpcClient {
+        /// Create a client for the subnet served at `rpc_url`, with its gateway at `gateway`.
+        #[wasm_bindgen(constructor)]
+        pub fn new(rpc_url: &str, gateway: &str) -> Result<IpcClient, JsError> {
+            let gateway = Address::from_str(gateway).map_err(to_js_err)?;
+            Ok(Self {
+                reader: IpcReader::new(FetchTransport::new(rpc_url), gateway),
+            })
+        }
+
+        #[wasm_bindgen(js_name = chainId)]
+        pub fn chain_id(&self) -> Promise {
+            let reader = self.reader.clone();
+            to_promise(async move { reader.chain_id().await })
+        }
+
+        #[wasm_bindgen(js_name = chainHead)]
+        pub fn chain_head(&self) -> Promise {
+            let reader = self.reader.clone();
+            to_promise(async move { reader.chain_head().await })
+        }
+
+        /// The balance of an address, in atto, as a decimal string.
+        pub fn balance(&self, address: String) -> Promise {
+            let reader = self.reader.clone();
+            to_promise(async move {
+                let balance = reader.balance(Address::from_str(&address)?).await?;
+                Ok(balance.to_string())
+            })
+        }
+
+        #[wasm_bindgen(js_name = listSubnets)]
+        pub fn list_subnets(&self) -> Promise {
+            let reader = self.reader.clone();
+            to_promise(async move { reader.list_subnets().await })
+        }
+
+        #[wasm_bindgen(js_name = genesisEpoch)]
+        pub fn genesis_epoch(&self, subnet: String) -> Promise {
+            let reader = self.reader.clone();
+            to_promise(async move { reader.genesis_epoch(&SubnetID::from_str(&subnet)?).await })
+        }
+
+        #[wasm_bindgen(js_name = latestParentFinality)]
+        pub fn latest_parent_finality(&self) -> Promise {
+            let reader = self.reader.clone();
+            to_promise(async move { reader.latest_parent_finality().await })
+        }
+
+        /// The height of the last bottom-up checkpoint of a child subnet, queried in its parent.
+        #[wasm_bindgen(js_name = lastBottomUpCheckpointHeight)]
+        pub fn last_bottom_up_checkpoint_height(&self, subnet: String) -> Promise {
+            let reader = self.reader.clone();
+            to_promise(async move {
+                let subnet = SubnetID::from_str(&subnet)?;
+                reader.last_bottom_up_checkpoint_height(&subnet).await
+            })
+        }
+
+        #[wasm_bindgen(js_name = checkpointPeriod)]
+        pub fn checkpoint_period(&self, subnet: String) -> Promise {
+            let reader = self.reader.clone();
+            to_promise(async move {
+                reader
+                    .checkpoint_period(&SubnetID::from_str(&subnet)?)
+                    .await
+            })
+        }
+
+        /// The bottom-up checkpoint cut at `height` in this subnet, or `null`.
+        #[wasm_bindgen(js_name = checkpointAt)]
+        pub fn checkpoint_at(&self, height: f64) -> Promise {
+            let reader = self.reader.clone();
+            to_promise(async move { reader.checkpoint_at(height as i64).await })
+        }
+    }
+
+    /// Run a query to completion and resolve it as a plain JS object, through its JSON
+    /// representation, since the returned futures have to own everything they use.
+    fn to_promise<F, T>(query: F) -> Promise
+    where
+        F: Future<Output = anyhow::Result<T>> + 'static,
+        T: Serialize,
+    {
+        future_to_promise(async move {
+            let value = query.await.map_err(to_js_err)?;
+            let json = serde_json::to_string(&value).map_err(to_js_err)?;
+            js_sys::JSON::parse(&json)
+        })
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub use client::IpcClient;
@@ -0,0 +1,230 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! Read-only queries of subnet and checkpoint state, mirroring the getters of the provider.
+
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use ethers::providers::{JsonRpcClient, Middleware, Provider};
+use ethers::types::{Address, U256};
+use fvm_shared::clock::ChainEpoch;
+use ipc_actors_abis::{gateway_getter_facet, subnet_actor_getter_facet};
+use ipc_api::checkpoint::BottomUpCheckpoint;
+use ipc_api::eth_to_fil_amount;
+use ipc_api::evm::payload_to_evm_address;
+use ipc_api::subnet_id::SubnetID;
+use serde::Serialize;
+
+/// A child subnet registered in the gateway.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SubnetView {
+    pub id: String,
+    /// Collateral staked in the subnet, in atto.
+    pub stake: String,
+    /// Circulating supply of the subnet, in atto.
+    pub circ_supply: String,
+    pub genesis_epoch: ChainEpoch,
+}
+
+impl TryFrom<gateway_getter_facet::Subnet> for SubnetView {
+    type Error = anyhow::Error;
+
+    fn try_from(value: gateway_getter_facet::Subnet) -> Result<Self, Self::Error> {
+        Ok(SubnetView {
+            id: SubnetID::try_from(value.id)?.to_string(),
+            stake: eth_to_fil_amount(&value.stake)?.atto().to_string(),
+            circ_supply: eth_to_fil_amount(&value.circ_supply)?.atto().to_string(),
+            genesis_epoch: value.genesis_epoch.as_u64() as ChainEpoch,
+        })
+    }
+}
+
+/// A bottom-up checkpoint with the validators that signed it so far.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CheckpointView {
+    pub subnet_id: String,
+    pub block_height: ChainEpoch,
+    /// Hex encoded hash of the checkpointed block.
+    pub block_hash: String,
+    pub next_configuration_number: u64,
+    /// Number of cross messages carried by the checkpoint.
+    pub msgs: usize,
+    pub signatories: Vec<String>,
+}
+
+impl CheckpointView {
+    fn new(checkpoint: BottomUpCheckpoint, signatories: Vec<Address>) -> Self {
+        CheckpointView {
+            subnet_id: checkpoint.subnet_id.to_string(),
+            block_height: checkpoint.block_height,
+            block_hash: hex::encode(checkpoint.block_hash),
+            next_configuration_number: checkpoint.next_configuration_number,
+            msgs: checkpoint.msgs.len(),
+            signatories: signatories.iter().map(|a| format!("{a:?}")).collect(),
+        }
+    }
+}
+
+/// Queries the IPC contracts of a single subnet through any JSON-RPC transport.
+#[derive(Debug)]
+pub struct IpcReader<C> {
+    provider: Arc<Provider<C>>,
+    gateway: Address,
+}
+
+impl<C> Clone for IpcReader<C> {
+    fn clone(&self) -> Self {
+        Self {
+            provider: self.provider.clone(),
+            gateway: self.gateway,
+        }
+    }
+}
+
+impl<C: JsonRpcClient + 'static> IpcReader<C> {
+    pub fn new(client: C, gateway: Address) -> Self {
+        Self {
+            provider: Arc::new(Provider::new(client)),
+            gateway,
+        }
+    }
+
+    fn gateway(&self) -> gateway_getter_facet::GatewayGetterFacet<Provider<C>> {
+        gateway_getter_facet::GatewayGetterFacet::new(self.gateway, self.provider.clone())
+    }
+
+    fn subnet_actor(
+        &self,
+        subnet: &SubnetID,
+    ) -> anyhow::Result<subnet_actor_getter_facet::SubnetActorGetterFacet<Provider<C>>> {
+        let address = subnet_actor_address(subnet)?;
+        Ok(subnet_actor_getter_facet::SubnetActorGetterFacet::new(
+            address,
+            self.provider.clone(),
+        ))
+    }
+
+    pub async fn chain_id(&self) -> anyhow::Result<u64> {
+        Ok(self.provider.get_chainid().await?.as_u64())
+    }
+
+    pub async fn chain_head(&self) -> anyhow::Result<ChainEpoch> {
+        Ok(self.provider.get_block_number().await?.as_u64() as ChainEpoch)
+    }
+
+    /// The balance of an address, in atto.
+    pub async fn balance(&self, address: Address) -> anyhow::Result<U256> {
+        Ok(self.provider.get_balance(address, None).await?)
+    }
+
+    pub async fn list_subnets(&self) -> anyhow::Result<Vec<SubnetView>> {
+        self.gateway()
+            .list_subnets()
+            .call()
+            .await?
+            .into_iter()
+            .map(SubnetView::try_from)
+            .collect()
+    }
+
+    pub async fn genesis_epoch(&self, subnet: &SubnetID) -> anyhow::Result<ChainEpoch> {
+        let evm_subnet_id = gateway_getter_facet::SubnetID::try_from(subnet)?;
+        let (exists, subnet_info) = self.gateway().get_subnet(evm_subnet_id).call().await?;
+        if !exists {
+            return Err(anyhow!("subnet: {} does not exists", subnet));
+        }
+        Ok(subnet_info.genesis_epoch.as_u64() as ChainEpoch)
+    }
+
+    pub async fn latest_parent_finality(&self) -> anyhow::Result<ChainEpoch> {
+        let finality = self.gateway().get_latest_parent_finality().call().await?;
+        Ok(finality.height.as_u64() as ChainEpoch)
+    }
+
+    pub async fn last_bottom_up_checkpoint_height(
+        &self,
+        subnet: &SubnetID,
+    ) -> anyhow::Result<ChainEpoch> {
+        let epoch = self
+            .subnet_actor(subnet)?
+            .last_bottom_up_checkpoint_height()
+            .call()
+            .await?;
+        Ok(epoch.as_u64() as ChainEpoch)
+    }
+
+    pub async fn checkpoint_period(&self, subnet: &SubnetID) -> anyhow::Result<ChainEpoch> {
+        let period = self
+            .subnet_actor(subnet)?
+            .bottom_up_check_period()
+            .call()
+            .await?;
+        Ok(period.as_u64() as ChainEpoch)
+    }
+
+    /// The bottom-up checkpoint cut at `height` in this subnet, if there is one.
+    pub async fn checkpoint_at(
+        &self,
+        height: ChainEpoch,
+    ) -> anyhow::Result<Option<CheckpointView>> {
+        let (checkpoint, _, signatories, _) = self
+            .gateway()
+            .get_checkpoint_signature_bundle(U256::from(height))
+            .call()
+            .await?;
+
+        if checkpoint.block_height.as_u64() == 0 {
+            return Ok(None);
+        }
+
+        let checkpoint = BottomUpCheckpoint::try_from(checkpoint)?;
+        Ok(Some(CheckpointView::new(checkpoint, signatories)))
+    }
+}
+
+/// The address of the subnet actor contract of a subnet in its parent.
+pub fn subnet_actor_address(subnet: &SubnetID) -> anyhow::Result<Address> {
+    let children = subnet.children();
+    let ipc_addr = children
+        .last()
+        .ok_or_else(|| anyhow!("{subnet:} has no child"))?;
+
+    payload_to_evm_address(ipc_addr.payload())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use ethers::types::U256;
+    use fvm_shared::address::Address;
+    use ipc_actors_abis::gateway_getter_facet;
+    use ipc_api::subnet_id::SubnetID;
+
+    use super::{subnet_actor_address, SubnetView};
+
+    #[test]
+    fn subnet_view_from_gateway() {
+        let addr = Address::from_str("f410ffzyuupbyl2uiucmzr3lu3mtf3luyknthaz4xsrq").unwrap();
+        let id = SubnetID::new(314159, vec![addr]);
+
+        let subnet = gateway_getter_facet::Subnet {
+            id: gateway_getter_facet::SubnetID::try_from(&id).unwrap(),
+            stake: U256::from(1_000_000_000_000_000_000u64),
+            genesis_epoch: U256::from(10),
+            circ_supply: U256::from(5),
+            ..Default::default()
+        };
+
+        let view = SubnetView::try_from(subnet).unwrap();
+        assert_eq!(view.id, id.to_string());
+        assert_eq!(view.stake, "1000000000000000000");
+        assert_eq!(view.circ_supply, "5");
+        assert_eq!(view.genesis_epoch, 10);
+
+        assert_eq!(
+            format!("{:?}", subnet_actor_address(&id).unwrap()),
+            "0x2e714a3c385ea88a09998ed74db265dae9853667"
+        );
+    }
+}
@@ -0,0 +1,135 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! JSON-RPC transport over the `fetch` API of the browser.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use async_trait::async_trait;
+use ethers::providers::{JsonRpcClient, JsonRpcError, ProviderError, RpcError};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+
+#[derive(Debug, thiserror::Error)]
+pub enum FetchError {
+    #[error("fetch failed: {0}")]
+    Fetch(String),
+    #[error(transparent)]
+    Serde(#[from] serde_json::Error),
+    #[error(transparent)]
+    JsonRpc(#[from] JsonRpcError),
+}
+
+impl From<JsValue> for FetchError {
+    fn from(value: JsValue) -> Self {
+        FetchError::Fetch(format!("{value:?}"))
+    }
+}
+
+impl RpcError for FetchError {
+    fn as_error_response(&self) -> Option<&JsonRpcError> {
+        match self {
+            FetchError::JsonRpc(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    fn as_serde_error(&self) -> Option<&serde_json::Error> {
+        match self {
+            FetchError::Serde(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<FetchError> for ProviderError {
+    fn from(value: FetchError) -> Self {
+        ProviderError::JsonRpcClientError(Box::new(value))
+    }
+}
+
+#[derive(Serialize)]
+struct Request<'a, T> {
+    jsonrpc: &'static str,
+    id: u64,
+    method: &'a str,
+    params: T,
+}
+
+#[derive(Deserialize)]
+struct Response {
+    result: Option<Box<RawValue>>,
+    error: Option<JsonRpcError>,
+}
+
+/// Sends JSON-RPC requests with `window.fetch`, as sockets and native HTTP clients are
+/// not available in the browser.
+#[derive(Debug)]
+pub struct FetchTransport {
+    url: String,
+    next_id: AtomicU64,
+}
+
+impl FetchTransport {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    async fn post(&self, body: String) -> Result<String, FetchError> {
+        let mut init = web_sys::RequestInit::new();
+        init.method("POST");
+        init.body(Some(&JsValue::from_str(&body)));
+
+        let request = web_sys::Request::new_with_str_and_init(&self.url, &init)?;
+        request.headers().set("Content-Type", "application/json")?;
+
+        let window = web_sys::window()
+            .ok_or_else(|| FetchError::Fetch("no window available".to_string()))?;
+        let response: web_sys::Response = JsFuture::from(window.fetch_with_request(&request))
+            .await?
+            .dyn_into()?;
+
+        if !response.ok() {
+            return Err(FetchError::Fetch(format!(
+                "unexpected status: {}",
+                response.status()
+            )));
+        }
+
+        JsFuture::from(response.text()?)
+            .await?
+            .as_string()
+            .ok_or_else(|| FetchError::Fetch("response body is not text".to_string()))
+    }
+}
+
+#[async_trait(?Send)]
+impl JsonRpcClient for FetchTransport {
+    type Error = FetchError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: std::fmt::Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let request = Request {
+            jsonrpc: "2.0",
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            method,
+            params,
+        };
+        let body = self.post(serde_json::to_string(&request)?).await?;
+        let response: Response = serde_json::from_str(&body)?;
+
+        match (response.error, response.result) {
+            (Some(e), _) => Err(e.into()),
+            (None, Some(result)) => Ok(serde_json::from_str(result.get())?),
+            (None, None) => Ok(serde_json::from_str("null")?),
+        }
+    }
+}
@@ -0,0 +1,120 @@
+// Copyright 2022-2024 Protocol Labs
+// SPDX-License-Identifier: MIT
+//! EVM signing with keys held in memory, since there is no keystore on disk in the browser.
+
+use anyhow::Context;
+use ethers::core::rand::thread_rng;
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Bytes, Signature};
+use ethers::utils::hash_message;
+use wasm_bindgen::prelude::*;
+
+use crate::to_js_err;
+
+/// An EVM key that only lives as long as the page that created it.
+#[wasm_bindgen]
+pub struct MemoryWallet {
+    wallet: LocalWallet,
+}
+
+impl MemoryWallet {
+    /// Load a hex encoded secp256k1 private key, with or without the `0x` prefix.
+    pub fn from_private_key(private_key: &str) -> anyhow::Result<Self> {
+        let private_key = private_key.strip_prefix("0x").unwrap_or(private_key);
+        let wallet = private_key
+            .parse::<LocalWallet>()
+            .context("invalid private key")?;
+        Ok(Self { wallet })
+    }
+
+    /// Generate a new random key.
+    pub fn generate() -> Self {
+        Self {
+            wallet: LocalWallet::new(&mut thread_rng()),
+        }
+    }
+
+    /// Sign a message with the EIP-191 prefix, like `personal_sign`.
+    pub fn sign_message_bytes(&self, message: &[u8]) -> anyhow::Result<Signature> {
+        Ok(self.wallet.sign_hash(hash_message(message))?)
+    }
+
+    /// Sign a transaction given in its JSON form and return the RLP encoded signed transaction,
+    /// ready to be sent with `eth_sendRawTransaction`.
+    pub fn sign_transaction_json(&self, tx: &str) -> anyhow::Result<Bytes> {
+        let mut tx: TypedTransaction = serde_json::from_str(tx).context("invalid transaction")?;
+        if tx.chain_id().is_none() {
+            tx.set_chain_id(self.wallet.chain_id());
+        }
+        let signature = self.wallet.sign_transaction_sync(&tx)?;
+        Ok(tx.rlp_signed(&signature))
+    }
+}
+
+#[wasm_bindgen]
+impl MemoryWallet {
+    #[wasm_bindgen(constructor)]
+    pub fn new(private_key: &str) -> Result<MemoryWallet, JsError> {
+        Self::from_private_key(private_key).map_err(to_js_err)
+    }
+
+    pub fn random() -> MemoryWallet {
+        Self::generate()
+    }
+
+    /// The `0x` prefixed address of the key.
+    pub fn address(&self) -> String {
+        format!("{:?}", self.wallet.address())
+    }
+
+    #[wasm_bindgen(js_name = signMessage)]
+    pub fn sign_message(&self, message: &[u8]) -> Result<String, JsError> {
+        let signature = self.sign_message_bytes(message).map_err(to_js_err)?;
+        Ok(format!("0x{signature}"))
+    }
+
+    #[wasm_bindgen(js_name = signTransaction)]
+    pub fn sign_transaction(&self, tx: &str) -> Result<String, JsError> {
+        let signed = self.sign_transaction_json(tx).map_err(to_js_err)?;
+        Ok(signed.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::transaction::eip2718::TypedTransaction;
+    use ethers::utils::{hash_message, rlp};
+
+    use super::MemoryWallet;
+
+    // The first default anvil/hardhat account.
+    const PRIVATE_KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+    const ADDRESS: &str = "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266";
+
+    #[test]
+    fn sign_message_recovers_address() {
+        let wallet = MemoryWallet::from_private_key(PRIVATE_KEY).unwrap();
+        assert_eq!(wallet.address(), ADDRESS);
+
+        let signature = wallet.sign_message_bytes(b"hello ipc").unwrap();
+        let signer = signature.recover(hash_message(b"hello ipc")).unwrap();
+        assert_eq!(format!("{signer:?}"), ADDRESS);
+    }
+
+    #[test]
+    fn sign_transaction_recovers_address() {
+        let wallet = MemoryWallet::from_private_key(PRIVATE_KEY).unwrap();
+        let tx = r#"{"type":"0x02","to":"0x70997970c51812dc3a010c7d01b50e0d17dc79c8","value":"0x1","nonce":"0x0","gas":"0x5208","maxFeePerGas":"0x1","maxPriorityFeePerGas":"0x1","chainId":"0x1"}"#;
+
+        let signed = wallet.sign_transaction_json(tx).unwrap();
+        let (tx, signature) = TypedTransaction::decode_signed(&rlp::Rlp::new(&signed)).unwrap();
+        let signer = signature.recover(tx.sighash()).unwrap();
+        assert_eq!(format!("{signer:?}"), ADDRESS);
+    }
+
+    #[test]
+    fn rejects_invalid_keys() {
+        assert!(MemoryWallet::from_private_key("0x1234").is_err());
+    }
+}